@@ -0,0 +1,41 @@
+//! Generates the `CompactTxStreamer` server over the `zcash_client_backend` message types.
+
+const COMPACT_FORMATS: &[&str] = &[
+    "ChainMetadata",
+    "CompactBlock",
+    "CompactTx",
+    "CompactSaplingSpend",
+    "CompactSaplingOutput",
+    "CompactOrchardAction",
+];
+const SERVICE: &[(&str, &str)] = &[
+    ("BlockID", "BlockId"),
+    ("BlockRange", "BlockRange"),
+    ("ChainSpec", "ChainSpec"),
+    ("Empty", "Empty"),
+    ("RawTransaction", "RawTransaction"),
+    ("TreeState", "TreeState"),
+];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto/service.proto");
+
+    let mut builder = tonic_prost_build::configure()
+        .build_client(false)
+        .build_server(true);
+    for name in COMPACT_FORMATS {
+        builder = builder.extern_path(
+            format!(".cash.z.wallet.sdk.rpc.{name}"),
+            format!("::zcash_client_backend::proto::compact_formats::{name}"),
+        );
+    }
+    for (proto_name, rust_name) in SERVICE {
+        builder = builder.extern_path(
+            format!(".cash.z.wallet.sdk.rpc.{proto_name}"),
+            format!("::zcash_client_backend::proto::service::{rust_name}"),
+        );
+    }
+    builder.compile_protos(&["proto/service.proto"], &["proto"])?;
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+//! In-memory lightwalletd `CompactTxStreamer` server for integration tests.
+//!
+//! Serves a synthetic chain of compact blocks and tree states over gRPC, so the `zair` pipeline
+//! (`config build` → `claim run` → `verify run`) can be exercised in CI without network access.
+
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures::Stream;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tonic::transport::Server;
+use tonic::transport::server::TcpIncoming;
+use tonic::{Request, Response, Status};
+use zcash_client_backend::proto::compact_formats::{
+    CompactBlock, CompactOrchardAction, CompactSaplingSpend, CompactTx,
+};
+use zcash_client_backend::proto::service::{
+    BlockId, BlockRange, ChainSpec, Empty, RawTransaction, TreeState,
+};
+
+use crate::proto::compact_tx_streamer_server::{CompactTxStreamer, CompactTxStreamerServer};
+
+#[allow(
+    missing_docs,
+    unused_qualifications,
+    clippy::all,
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::restriction,
+    reason = "Generated gRPC server code"
+)]
+mod proto {
+    tonic::include_proto!("cash.z.wallet.sdk.rpc");
+}
+
+/// Synthetic chain served by [`TestLightwalletd`].
+///
+/// Heights without a stored block are skipped by `GetBlockRange`, so fixtures only need to
+/// contain the blocks that matter to a test.
+#[derive(Debug, Clone, Default)]
+pub struct MockChain {
+    blocks: BTreeMap<u64, CompactBlock>,
+    tree_states: BTreeMap<u64, TreeState>,
+    mempool: Vec<Vec<u8>>,
+}
+
+impl MockChain {
+    /// Create an empty chain.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a block, replacing any block previously stored at the same height.
+    pub fn push_block(&mut self, block: CompactBlock) {
+        self.blocks.insert(block.height, block);
+    }
+
+    /// Insert a block that only reveals the given nullifiers.
+    ///
+    /// Orchard nullifiers must be canonical `pallas::Base` encodings to be picked up by scanners.
+    pub fn push_nullifier_block(
+        &mut self,
+        height: u64,
+        sapling_nullifiers: &[[u8; 32]],
+        orchard_nullifiers: &[[u8; 32]],
+    ) {
+        let tx = CompactTx {
+            spends: sapling_nullifiers
+                .iter()
+                .map(|nf| CompactSaplingSpend { nf: nf.to_vec() })
+                .collect(),
+            actions: orchard_nullifiers
+                .iter()
+                .map(|nf| CompactOrchardAction {
+                    nullifier: nf.to_vec(),
+                    ..CompactOrchardAction::default()
+                })
+                .collect(),
+            ..CompactTx::default()
+        };
+        self.push_block(CompactBlock {
+            height,
+            hash: block_hash(height).to_vec(),
+            prev_hash: block_hash(height.saturating_sub(1)).to_vec(),
+            vtx: vec![tx],
+            ..CompactBlock::default()
+        });
+    }
+
+    /// Insert the tree state returned for `GetTreeState` at `tree_state.height`.
+    pub fn push_tree_state(&mut self, tree_state: TreeState) {
+        self.tree_states.insert(tree_state.height, tree_state);
+    }
+
+    /// Add a serialized transaction to the mempool served by `GetMempoolStream`.
+    pub fn push_mempool_transaction(&mut self, data: Vec<u8>) {
+        self.mempool.push(data);
+    }
+
+    /// Highest stored block, if any.
+    #[must_use]
+    pub fn tip(&self) -> Option<&CompactBlock> {
+        self.blocks.values().next_back()
+    }
+
+    /// Tree state at `height`: the closest stored state at or below it, or empty trees.
+    fn tree_state_at(&self, height: u64) -> TreeState {
+        let mut state = self
+            .tree_states
+            .range(..=height)
+            .next_back()
+            .map_or_else(TreeState::default, |(_, state)| state.clone());
+        state.height = height;
+        state
+    }
+
+    /// Stored blocks in a `BlockRange` request, in the order lightwalletd streams them.
+    ///
+    /// Returns `None` if the range has no start or end.
+    fn blocks_in(&self, range: BlockRange) -> Option<Vec<CompactBlock>> {
+        let (start, end) = (range.start?, range.end?);
+
+        // lightwalletd streams descending ranges in reverse order.
+        let blocks = if start.height <= end.height {
+            self.blocks
+                .range(start.height..=end.height)
+                .map(|(_, block)| block.clone())
+                .collect()
+        } else {
+            self.blocks
+                .range(end.height..=start.height)
+                .rev()
+                .map(|(_, block)| block.clone())
+                .collect()
+        };
+        Some(blocks)
+    }
+}
+
+/// Block reduced to its nullifiers, as `GetBlockRangeNullifiers` serves it.
+fn nullifiers_only(mut block: CompactBlock) -> CompactBlock {
+    for tx in &mut block.vtx {
+        tx.outputs.clear();
+        for action in &mut tx.actions {
+            *action = CompactOrchardAction {
+                nullifier: std::mem::take(&mut action.nullifier),
+                ..CompactOrchardAction::default()
+            };
+        }
+    }
+    block
+}
+
+/// Deterministic synthetic block hash: the height in little-endian, zero padded.
+#[must_use]
+pub fn block_hash(height: u64) -> [u8; 32] {
+    let mut hash = [0_u8; 32];
+    for (dst, src) in hash.iter_mut().zip(height.to_le_bytes()) {
+        *dst = src;
+    }
+    hash
+}
+
+/// Error for a `BlockRange` request without a start or end.
+const MISSING_RANGE_BOUND: &str = "Block range requires start and end";
+
+struct Streamer {
+    chain: Arc<MockChain>,
+}
+
+type BlockStream = Pin<Box<dyn Stream<Item = Result<CompactBlock, Status>> + Send>>;
+type TransactionStream = Pin<Box<dyn Stream<Item = Result<RawTransaction, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl CompactTxStreamer for Streamer {
+    type GetBlockRangeStream = BlockStream;
+    type GetBlockRangeNullifiersStream = BlockStream;
+    type GetMempoolStreamStream = TransactionStream;
+
+    async fn get_latest_block(
+        &self,
+        _request: Request<ChainSpec>,
+    ) -> Result<Response<BlockId>, Status> {
+        let tip = self
+            .chain
+            .tip()
+            .ok_or_else(|| Status::not_found("Chain has no blocks"))?;
+        Ok(Response::new(BlockId {
+            height: tip.height,
+            hash: tip.hash.clone(),
+        }))
+    }
+
+    async fn get_block(&self, request: Request<BlockId>) -> Result<Response<CompactBlock>, Status> {
+        let height = request.into_inner().height;
+        self.chain
+            .blocks
+            .get(&height)
+            .cloned()
+            .map(Response::new)
+            .ok_or_else(|| Status::not_found(format!("No block at height {height}")))
+    }
+
+    async fn get_block_range(
+        &self,
+        request: Request<BlockRange>,
+    ) -> Result<Response<Self::GetBlockRangeStream>, Status> {
+        let blocks = self
+            .chain
+            .blocks_in(request.into_inner())
+            .ok_or_else(|| Status::invalid_argument(MISSING_RANGE_BOUND))?;
+        Ok(Response::new(Box::pin(futures::stream::iter(
+            blocks.into_iter().map(Ok),
+        ))))
+    }
+
+    async fn get_block_range_nullifiers(
+        &self,
+        request: Request<BlockRange>,
+    ) -> Result<Response<Self::GetBlockRangeNullifiersStream>, Status> {
+        let blocks = self
+            .chain
+            .blocks_in(request.into_inner())
+            .ok_or_else(|| Status::invalid_argument(MISSING_RANGE_BOUND))?;
+        Ok(Response::new(Box::pin(futures::stream::iter(
+            blocks.into_iter().map(|block| Ok(nullifiers_only(block))),
+        ))))
+    }
+
+    async fn get_tree_state(
+        &self,
+        request: Request<BlockId>,
+    ) -> Result<Response<TreeState>, Status> {
+        let height = request.into_inner().height;
+        Ok(Response::new(self.chain.tree_state_at(height)))
+    }
+
+    /// Sends the mempool and ends, as lightwalletd does when the next block arrives.
+    async fn get_mempool_stream(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::GetMempoolStreamStream>, Status> {
+        let transactions: Vec<_> = self
+            .chain
+            .mempool
+            .iter()
+            .map(|data| {
+                Ok(RawTransaction {
+                    data: data.clone(),
+                    height: 0,
+                })
+            })
+            .collect();
+        Ok(Response::new(Box::pin(futures::stream::iter(transactions))))
+    }
+}
+
+/// A running in-memory lightwalletd server.
+///
+/// The server listens on an ephemeral localhost port and shuts down when dropped.
+pub struct TestLightwalletd {
+    addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl TestLightwalletd {
+    /// Start serving `chain` on `127.0.0.1` with an OS-assigned port.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    /// Returns an error if the listener cannot be bound.
+    pub async fn spawn(chain: MockChain) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let addr = listener.local_addr()?;
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+
+        let service = CompactTxStreamerServer::new(Streamer {
+            chain: Arc::new(chain),
+        });
+        tokio::spawn(
+            Server::builder()
+                .add_service(service)
+                .serve_with_incoming_shutdown(TcpIncoming::from(listener), async {
+                    let _ = shutdown_rx.await;
+                }),
+        );
+
+        Ok(Self {
+            addr,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// Socket address the server listens on.
+    #[must_use]
+    pub const fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Plain-text gRPC URL accepted by `zair --lightwalletd`.
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for TestLightwalletd {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Scans the in-memory server with the real `zair-scan` client.
+
+use std::str::FromStr as _;
+
+use test_lightwalletd::{MockChain, TestLightwalletd};
+use tonic::transport::Uri;
+use zair_core::base::Nullifier;
+use zair_scan::Network;
+use zair_scan::light_walletd::{Backend, LightWalletd};
+use zair_scan::scanner::{BlockNullifiersVisitor, ChainNullifiersVisitor};
+use zcash_protocol::consensus::BlockHeight;
+
+async fn connect(server: &TestLightwalletd) -> LightWalletd {
+    let uri = Uri::from_str(&server.url()).expect("server URL is valid");
+    LightWalletd::connect(uri)
+        .await
+        .expect("should connect to test lightwalletd")
+}
+
+fn chain() -> MockChain {
+    let mut chain = MockChain::new();
+    chain.push_nullifier_block(100, &[[1_u8; 32], [2_u8; 32]], &[]);
+    chain.push_nullifier_block(105, &[], &[[7_u8; 32]]);
+    chain.push_nullifier_block(110, &[[3_u8; 32]], &[[8_u8; 32]]);
+    chain
+}
+
+#[tokio::test]
+async fn scan_nullifiers_over_range() {
+    let server = TestLightwalletd::spawn(chain())
+        .await
+        .expect("server should start");
+    let lightwalletd = connect(&server).await;
+
+    let mut visitor = ChainNullifiersVisitor::default();
+    lightwalletd
+        .scan_nullifiers(&mut visitor, &(100..=105))
+        .await
+        .expect("scan should succeed");
+    let (sapling, orchard) = visitor.sanitise_nullifiers();
+
+    assert_eq!(
+        sapling.to_vec(),
+        vec![Nullifier::from([1_u8; 32]), Nullifier::from([2_u8; 32])]
+    );
+    assert_eq!(orchard.to_vec(), vec![Nullifier::from([7_u8; 32])]);
+}
+
+#[tokio::test]
+async fn zaino_backend_scans_block_range_nullifiers() {
+    let server = TestLightwalletd::spawn(chain())
+        .await
+        .expect("server should start");
+    let uri = Uri::from_str(&server.url()).expect("server URL is valid");
+    let zaino = LightWalletd::connect_backend(uri, Backend::Zaino)
+        .await
+        .expect("should connect to test lightwalletd");
+    assert_eq!(zaino.backend(), Backend::Zaino);
+
+    let mut visitor = ChainNullifiersVisitor::default();
+    zaino
+        .scan_nullifiers(&mut visitor, &(100..=110))
+        .await
+        .expect("scan should succeed");
+    let (sapling, orchard) = visitor.sanitise_nullifiers();
+
+    assert_eq!(sapling.len(), 3);
+    assert_eq!(
+        orchard.to_vec(),
+        vec![Nullifier::from([7_u8; 32]), Nullifier::from([8_u8; 32])]
+    );
+}
+
+#[tokio::test]
+async fn block_nullifiers_keep_heights() {
+    let server = TestLightwalletd::spawn(chain())
+        .await
+        .expect("server should start");
+    let lightwalletd = connect(&server).await;
+
+    let mut visitor = BlockNullifiersVisitor::default();
+    lightwalletd
+        .scan_nullifiers(&mut visitor, &(0..=200))
+        .await
+        .expect("scan should succeed");
+    let blocks = visitor.into_blocks();
+
+    assert_eq!(
+        blocks.keys().copied().collect::<Vec<_>>(),
+        vec![100, 105, 110]
+    );
+    assert_eq!(
+        blocks.get(&110).map(|block| block.orchard.clone()),
+        Some(vec![Nullifier::from([8_u8; 32])])
+    );
+}
+
+#[tokio::test]
+async fn missing_tree_state_yields_empty_trees() {
+    let server = TestLightwalletd::spawn(chain())
+        .await
+        .expect("server should start");
+    let lightwalletd = connect(&server).await;
+
+    let anchors = lightwalletd
+        .commitment_tree_anchors(BlockHeight::from_u32(111))
+        .await
+        .expect("tree state should be served");
+
+    assert_eq!(
+        anchors.sapling,
+        sapling::CommitmentTree::empty().root().to_bytes()
+    );
+}
+
+#[tokio::test]
+async fn typed_anchors_match_tree_state_anchors() {
+    let server = TestLightwalletd::spawn(chain())
+        .await
+        .expect("server should start");
+    let lightwalletd = connect(&server).await;
+    let height = BlockHeight::from_u32(111);
+
+    let anchors = lightwalletd
+        .commitment_tree_anchors(height)
+        .await
+        .expect("tree state should be served");
+    let sapling = lightwalletd
+        .get_sapling_anchor(height)
+        .await
+        .expect("cached Sapling anchor");
+    let orchard = lightwalletd
+        .get_orchard_anchor(height)
+        .await
+        .expect("cached Orchard anchor");
+
+    assert_eq!(sapling, anchors.sapling);
+    assert_eq!(orchard, anchors.orchard);
+}
+
+#[tokio::test]
+async fn unparseable_mempool_transactions_are_skipped() {
+    let mut chain = chain();
+    chain.push_mempool_transaction(vec![0xff; 16]);
+    let server = TestLightwalletd::spawn(chain)
+        .await
+        .expect("server should start");
+    let lightwalletd = connect(&server).await;
+
+    let nullifiers = lightwalletd
+        .mempool_nullifiers(Network::TestNetwork)
+        .await
+        .expect("mempool should be read");
+    assert!(nullifiers.is_empty());
+}
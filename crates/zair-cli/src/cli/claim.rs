@@ -2,20 +2,36 @@
 
 use std::path::PathBuf;
 
-use zair_sdk::commands::{GapTreeMode, OrchardParamsMode};
+use zair_sdk::commands::{GapTreeMode, OrchardParamsMode, SnapshotFiles};
+use zair_sdk::common::PoolSelection;
+use zair_sdk::paths::{default_input_path, params_dir, snapshots_dir};
 
+#[cfg(feature = "prove")]
+use super::constants::ZAIR_NOTIFY_URL;
 use super::constants::{
-    DEFAULT_CLAIMS_FILE, DEFAULT_CONFIG_FILE, DEFAULT_GAP_TREE_MODE, DEFAULT_ORCHARD_PARAMS_FILE,
-    DEFAULT_ORCHARD_PARAMS_MODE, DEFAULT_PROOFS_FILE, DEFAULT_SAPLING_PK_FILE,
-    DEFAULT_SECRETS_FILE, DEFAULT_SUBMISSION_FILE, DEFAULT_UFVK_FILE, ZAIR_ACCOUNT_ID,
-    ZAIR_BIRTHDAY, ZAIR_CLAIMS_IN, ZAIR_CLAIMS_OUT, ZAIR_CONFIG_FILE, ZAIR_GAP_TREE_MODE,
-    ZAIR_GAP_TREE_ORCHARD_FILE, ZAIR_GAP_TREE_SAPLING_FILE, ZAIR_LIGHTWALLETD_URL,
-    ZAIR_MESSAGE_FILE, ZAIR_MESSAGES_FILE, ZAIR_ORCHARD_PARAMS_FILE, ZAIR_ORCHARD_PARAMS_MODE,
-    ZAIR_PROOFS_IN, ZAIR_PROOFS_OUT, ZAIR_SAPLING_PK_FILE, ZAIR_SECRETS_IN, ZAIR_SECRETS_OUT,
-    ZAIR_SEED_FILE, ZAIR_SNAPSHOT_ORCHARD_FILE, ZAIR_SNAPSHOT_SAPLING_FILE, ZAIR_SUBMISSION_OUT,
-    ZAIR_UFVK_FILE,
+    DEFAULT_ATTESTATION_FILE, DEFAULT_CLAIMED_VALUE_FILE, DEFAULT_CLAIMS_FILE, DEFAULT_CONFIG_FILE,
+    DEFAULT_DETACHED_SIGNATURES_FILE, DEFAULT_GAP_TREE_MODE, DEFAULT_IPFS_GATEWAY,
+    DEFAULT_MESSAGES_FILE, DEFAULT_ORCHARD_PARAMS_FILE, DEFAULT_ORCHARD_PARAMS_MODE,
+    DEFAULT_PACKAGE_FILE, DEFAULT_POOL, DEFAULT_PROOFS_FILE, DEFAULT_SAPLING_PK_FILE,
+    DEFAULT_SECRETS_FILE, DEFAULT_SIGNATURES_FILE, DEFAULT_SIGNING_REQUEST_FILE,
+    DEFAULT_SNAPSHOT_TRANSPARENT_FILE, DEFAULT_SUBMISSION_FILE, DEFAULT_TRANSPARENT_CLAIMS_FILE,
+    DEFAULT_UFVK_FILE, ZAIR_ACCOUNT_ID, ZAIR_ADDRESS_COUNT, ZAIR_BIRTHDAY, ZAIR_CHECK_MEMPOOL,
+    ZAIR_CHECK_NOTE_POSITIONS, ZAIR_CLAIMED_VALUE_OUT, ZAIR_CLAIMS_IN, ZAIR_CLAIMS_OUT,
+    ZAIR_CONFIG_FILE, ZAIR_DETACHED, ZAIR_DETACHED_SIGNATURES_OUT, ZAIR_EPOCH, ZAIR_EXPIRY_HEIGHT,
+    ZAIR_GAP_TREE_MODE, ZAIR_GAP_TREE_ORCHARD_FILE, ZAIR_GAP_TREE_SAPLING_FILE, ZAIR_IPFS_GATEWAY,
+    ZAIR_LIGHTWALLETD_URL, ZAIR_MERGE_CLAIMS, ZAIR_MERGE_PROOFS, ZAIR_MERGE_SECRETS,
+    ZAIR_MESSAGE_FILE, ZAIR_MESSAGES_FILE, ZAIR_MESSAGES_OUT, ZAIR_OFFLINE, ZAIR_OPERATOR_KEY_FILE,
+    ZAIR_ORCHARD_PARAMS_FILE, ZAIR_ORCHARD_PARAMS_MODE, ZAIR_PACKAGE_OUT, ZAIR_PAYLOAD_FILE,
+    ZAIR_POOL, ZAIR_PROOFS_IN, ZAIR_PROOFS_OUT, ZAIR_RECIPIENT, ZAIR_RECIPIENTS_FILE,
+    ZAIR_SAPLING_PK_FILE, ZAIR_SECRETS_IN, ZAIR_SECRETS_OUT, ZAIR_SEED_FILE, ZAIR_SIGNATURES_IN,
+    ZAIR_SIGNATURES_OUT, ZAIR_SIGNING_REQUEST_IN, ZAIR_SIGNING_REQUEST_OUT, ZAIR_SNAPSHOT_CID,
+    ZAIR_SNAPSHOT_MAGNETS, ZAIR_SNAPSHOT_ORCHARD_FILE, ZAIR_SNAPSHOT_SAPLING_FILE,
+    ZAIR_SNAPSHOT_TRANSPARENT_FILE, ZAIR_SNAPSHOT_URL, ZAIR_SUBMISSION_IN, ZAIR_SUBMISSION_OUT,
+    ZAIR_SUBMISSION_URL, ZAIR_TRANSPARENT_CLAIMS_OUT, ZAIR_UFVK_FILE, ZAIR_VERIFICATION_REPORT_IN,
 };
-use super::{parse_gap_tree_mode, parse_orchard_params_mode};
+#[cfg(feature = "serve")]
+use super::constants::{DEFAULT_SERVE_ADDR, ZAIR_SERVE_ADDR};
+use super::{parse_gap_tree_mode, parse_orchard_params_mode, parse_pool_selection};
 
 /// Arguments for the end-to-end claim pipeline.
 #[cfg(feature = "prove")]
@@ -29,27 +45,36 @@ pub struct ClaimRunArgs {
         default_value = DEFAULT_CONFIG_FILE
     )]
     pub config: PathBuf,
-    /// Path to file containing 64-byte seed as hex.
+    /// Path to file containing 64-byte seed as hex, or a unified spending key.
     #[arg(long, env = ZAIR_SEED_FILE, value_name = "SEED_FILE")]
     pub seed: PathBuf,
     /// Shared message payload file fallback used for claim signatures.
     #[arg(long = "message", env = ZAIR_MESSAGE_FILE, value_name = "MESSAGE_FILE")]
     pub message: Option<PathBuf>,
-    /// Per-claim message assignments JSON.
+    /// Per-claim message assignments JSON. Defaults to `claim-messages.json` in the working
+    /// directory if present and `--message` is not given.
     #[arg(long = "messages", env = ZAIR_MESSAGES_FILE, value_name = "MESSAGES_FILE")]
     pub messages: Option<PathBuf>,
+    /// Last block height at which the submission may be accepted. Defaults to the end of the
+    /// claim window in the configuration.
+    #[arg(long, env = ZAIR_EXPIRY_HEIGHT)]
+    pub expiry_height: Option<u64>,
     /// Sapling snapshot nullifiers file.
-    /// Defaults to `snapshot-sapling.bin` when Sapling is enabled in config.
+    /// Defaults to `snapshot-sapling.bin` in the working directory if present, else in the
+    /// standard snapshots directory, when Sapling is enabled in config.
     #[arg(long, env = ZAIR_SNAPSHOT_SAPLING_FILE)]
     pub snapshot_sapling: Option<PathBuf>,
     /// Orchard snapshot nullifiers file.
-    /// Defaults to `snapshot-orchard.bin` when Orchard is enabled in config.
+    /// Defaults to `snapshot-orchard.bin` in the working directory if present, else in the
+    /// standard snapshots directory, when Orchard is enabled in config.
     #[arg(long, env = ZAIR_SNAPSHOT_ORCHARD_FILE)]
     pub snapshot_orchard: Option<PathBuf>,
-    /// Sapling gap-tree file. Defaults to `gaptree-sapling.bin` when Sapling is enabled.
+    /// Sapling gap-tree file. Defaults to `gaptree-sapling.bin` in the working directory if
+    /// present, else in the standard gap-trees directory, when Sapling is enabled.
     #[arg(long, env = ZAIR_GAP_TREE_SAPLING_FILE)]
     pub gap_tree_sapling: Option<PathBuf>,
-    /// Orchard gap-tree file. Defaults to `gaptree-orchard.bin` when Orchard is enabled.
+    /// Orchard gap-tree file. Defaults to `gaptree-orchard.bin` in the working directory if
+    /// present, else in the standard gap-trees directory, when Orchard is enabled.
     #[arg(long, env = ZAIR_GAP_TREE_ORCHARD_FILE)]
     pub gap_tree_orchard: Option<PathBuf>,
     /// Gap-tree mode: `none` (require files), `rebuild` (recompute and persist), `sparse`
@@ -61,22 +86,16 @@ pub struct ClaimRunArgs {
         value_parser = parse_gap_tree_mode
     )]
     pub gap_tree_mode: GapTreeMode,
-    /// Path to Sapling proving key file.
-    #[arg(
-        long = "sapling-pk",
-        env = ZAIR_SAPLING_PK_FILE,
-        value_name = "SAPLING_PK_FILE",
-        default_value = DEFAULT_SAPLING_PK_FILE
-    )]
-    pub sapling_pk: PathBuf,
-    /// Path to the Orchard Halo2 params file.
-    #[arg(
-        long,
-        env = ZAIR_ORCHARD_PARAMS_FILE,
-        value_name = "ORCHARD_PARAMS_FILE",
-        default_value = DEFAULT_ORCHARD_PARAMS_FILE
-    )]
-    pub orchard_params: PathBuf,
+    /// Path to Sapling proving key file. Defaults to `setup-sapling-pk-<scheme>.params` for the
+    /// Sapling scheme in the configuration if present, else `setup-sapling-pk.params`, in the
+    /// working directory or the standard params directory.
+    #[arg(long = "sapling-pk", env = ZAIR_SAPLING_PK_FILE, value_name = "SAPLING_PK_FILE")]
+    pub sapling_pk: Option<PathBuf>,
+    /// Path to the Orchard Halo2 params file. Defaults to `setup-orchard-params-<scheme>.bin`
+    /// for the Orchard scheme in the configuration if present, else `setup-orchard-params.bin`,
+    /// in the working directory or the standard params directory.
+    #[arg(long, env = ZAIR_ORCHARD_PARAMS_FILE, value_name = "ORCHARD_PARAMS_FILE")]
+    pub orchard_params: Option<PathBuf>,
     /// Orchard params handling mode: `require` (fail if missing) or `auto` (generate and persist).
     #[arg(
         long,
@@ -114,6 +133,31 @@ pub struct ClaimRunArgs {
         default_value = DEFAULT_SUBMISSION_FILE
     )]
     pub submission_out: PathBuf,
+    /// Pools to claim: `sapling`, `orchard` or `both`. A single pool only needs that pool's
+    /// snapshot, gap-tree and setup files.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
+    /// POST a JSON event to this URL when the run completes or fails.
+    #[arg(long, env = ZAIR_NOTIFY_URL, value_name = "URL")]
+    pub notify_url: Option<String>,
+    #[command(flatten)]
+    pub proof_rng: ProofRngArgs,
+}
+
+/// Source of the proof randomness of `claim run` and `claim prove`.
+#[cfg(feature = "prove")]
+#[derive(Debug, clap::Args)]
+pub struct ProofRngArgs {
+    /// Testing only: derive all proof randomness from this seed for reproducible output.
+    /// Never use for real claims, the secrets become predictable.
+    #[cfg(feature = "deterministic-rng")]
+    #[arg(long, value_name = "SEED")]
+    pub deterministic_rng: Option<u64>,
 }
 
 /// Arguments for claim preparation.
@@ -131,17 +175,21 @@ pub struct ClaimPrepareArgs {
     #[arg(long, env = ZAIR_UFVK_FILE, default_value = DEFAULT_UFVK_FILE)]
     pub ufvk: PathBuf,
     /// Sapling snapshot nullifiers file.
-    /// Defaults to `snapshot-sapling.bin` when Sapling is enabled in config.
+    /// Defaults to `snapshot-sapling.bin` in the working directory if present, else in the
+    /// standard snapshots directory, when Sapling is enabled in config.
     #[arg(long, env = ZAIR_SNAPSHOT_SAPLING_FILE)]
     pub snapshot_sapling: Option<PathBuf>,
     /// Orchard snapshot nullifiers file.
-    /// Defaults to `snapshot-orchard.bin` when Orchard is enabled in config.
+    /// Defaults to `snapshot-orchard.bin` in the working directory if present, else in the
+    /// standard snapshots directory, when Orchard is enabled in config.
     #[arg(long, env = ZAIR_SNAPSHOT_ORCHARD_FILE)]
     pub snapshot_orchard: Option<PathBuf>,
-    /// Sapling gap-tree file. Defaults to `gaptree-sapling.bin` when Sapling is enabled.
+    /// Sapling gap-tree file. Defaults to `gaptree-sapling.bin` in the working directory if
+    /// present, else in the standard gap-trees directory, when Sapling is enabled.
     #[arg(long, env = ZAIR_GAP_TREE_SAPLING_FILE)]
     pub gap_tree_sapling: Option<PathBuf>,
-    /// Orchard gap-tree file. Defaults to `gaptree-orchard.bin` when Orchard is enabled.
+    /// Orchard gap-tree file. Defaults to `gaptree-orchard.bin` in the working directory if
+    /// present, else in the standard gap-trees directory, when Orchard is enabled.
     #[arg(long, env = ZAIR_GAP_TREE_ORCHARD_FILE)]
     pub gap_tree_orchard: Option<PathBuf>,
     /// Gap-tree mode: `none` (require files), `rebuild` (recompute and persist), `sparse`
@@ -153,15 +201,93 @@ pub struct ClaimPrepareArgs {
         value_parser = parse_gap_tree_mode
     )]
     pub gap_tree_mode: GapTreeMode,
+    /// Download the snapshot and gap-tree files from a `snapshot serve` host first. Files are
+    /// saved to the paths above (or the standard directories) and checked against the host's
+    /// manifest, which must list the same airdrop configuration.
+    #[arg(long, env = ZAIR_SNAPSHOT_URL, value_name = "URL")]
+    pub snapshot_url: Option<String>,
+    /// Download the snapshot and gap-tree files published with `snapshot publish --ipfs` under
+    /// this CID, through `--ipfs-gateway`. Checked like `--snapshot-url`.
+    #[arg(
+        long,
+        env = ZAIR_SNAPSHOT_CID,
+        value_name = "CID",
+        conflicts_with = "snapshot_url"
+    )]
+    pub snapshot_cid: Option<String>,
+    /// Download the snapshot and gap-tree files named by these magnet links, as logged by
+    /// `config build --torrent`, from their web seeds. Repeat for each file; a magnet link needs
+    /// an exact source (`xs`) for its `.torrent`.
+    #[arg(
+        long = "snapshot-magnet",
+        env = ZAIR_SNAPSHOT_MAGNETS,
+        value_name = "MAGNET",
+        value_delimiter = ' ',
+        conflicts_with_all = ["snapshot_url", "snapshot_cid"]
+    )]
+    pub snapshot_magnets: Vec<String>,
+    /// IPFS HTTP gateway used with `--snapshot-cid`.
+    #[arg(long, env = ZAIR_IPFS_GATEWAY, default_value = DEFAULT_IPFS_GATEWAY)]
+    pub ipfs_gateway: String,
     /// Scan start height for note discovery.
     #[arg(long, env = ZAIR_BIRTHDAY)]
-    pub birthday: u64,
+    #[cfg_attr(feature = "serve", arg(required_unless_present = "serve"))]
+    #[cfg_attr(not(feature = "serve"), arg(required = true))]
+    pub birthday: Option<u64>,
     /// Optional lightwalletd gRPC endpoint URL override.
     #[arg(long, env = ZAIR_LIGHTWALLETD_URL)]
     pub lightwalletd: Option<String>,
     /// Output file for prepared claims JSON.
     #[arg(long, env = ZAIR_CLAIMS_OUT, default_value = DEFAULT_CLAIMS_FILE)]
     pub claims_out: PathBuf,
+    /// Pools to prepare claims for: `sapling`, `orchard` or `both`. A single pool only needs
+    /// that pool's snapshot and gap-tree files; merge the claims files with `claim merge`.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
+    /// Epoch of a phased airdrop to claim; the base epoch by default. The snapshot and gap-tree
+    /// files must be those of the epoch.
+    #[arg(long, env = ZAIR_EPOCH, value_name = "EPOCH")]
+    pub epoch: Option<u32>,
+    /// Warn about claimed notes that a transaction in the lightwalletd mempool is spending.
+    #[arg(long, env = ZAIR_CHECK_MEMPOOL, default_value_t = false)]
+    pub check_mempool: bool,
+    /// Run as a local HTTP service instead: `POST /prepare` with a UFVK and birthday streams
+    /// progress and returns the prepared claims. Listens on `127.0.0.1:8547` unless an address is
+    /// given; `--ufvk`, `--birthday` and `--claims-out` are ignored.
+    #[cfg(feature = "serve")]
+    #[arg(
+        long,
+        env = ZAIR_SERVE_ADDR,
+        value_name = "ADDR",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_SERVE_ADDR
+    )]
+    pub serve: Option<std::net::SocketAddr>,
+}
+
+impl ClaimPrepareArgs {
+    /// Take the snapshot and gap-tree paths, as destinations for a download.
+    pub const fn take_snapshot_files(&mut self) -> SnapshotFiles {
+        SnapshotFiles {
+            sapling_snapshot: self.snapshot_sapling.take(),
+            orchard_snapshot: self.snapshot_orchard.take(),
+            sapling_gap_tree: self.gap_tree_sapling.take(),
+            orchard_gap_tree: self.gap_tree_orchard.take(),
+        }
+    }
+
+    /// Use the downloaded snapshot and gap-tree files.
+    pub fn set_snapshot_files(&mut self, files: SnapshotFiles) {
+        self.snapshot_sapling = files.sapling_snapshot;
+        self.snapshot_orchard = files.orchard_snapshot;
+        self.gap_tree_sapling = files.sapling_gap_tree;
+        self.gap_tree_orchard = files.orchard_gap_tree;
+    }
 }
 
 /// Arguments for claim proof generation.
@@ -179,7 +305,8 @@ pub struct ClaimProveArgs {
     /// Input file containing claim inputs.
     #[arg(long, env = ZAIR_CLAIMS_IN, default_value = DEFAULT_CLAIMS_FILE)]
     pub claims_in: PathBuf,
-    /// Path to file containing 64-byte seed as hex for deriving spending keys.
+    /// Path to file containing 64-byte seed as hex, or a unified spending key, for deriving
+    /// spending keys.
     #[arg(long, env = ZAIR_SEED_FILE, value_name = "SEED_FILE")]
     pub seed: PathBuf,
     /// Path to Sapling proving key file.
@@ -187,7 +314,7 @@ pub struct ClaimProveArgs {
         long = "sapling-pk",
         env = ZAIR_SAPLING_PK_FILE,
         value_name = "SAPLING_PK_FILE",
-        default_value = DEFAULT_SAPLING_PK_FILE
+        default_value_os_t = default_input_path(params_dir, DEFAULT_SAPLING_PK_FILE)
     )]
     pub sapling_pk: PathBuf,
     /// Path to the Orchard Halo2 params file.
@@ -195,7 +322,7 @@ pub struct ClaimProveArgs {
         long,
         env = ZAIR_ORCHARD_PARAMS_FILE,
         value_name = "ORCHARD_PARAMS_FILE",
-        default_value = DEFAULT_ORCHARD_PARAMS_FILE
+        default_value_os_t = default_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE)
     )]
     pub orchard_params: PathBuf,
     /// Orchard params handling mode: `require` (fail if missing) or `auto` (generate and persist).
@@ -219,6 +346,197 @@ pub struct ClaimProveArgs {
         default_value = DEFAULT_SECRETS_FILE
     )]
     pub secrets_out: PathBuf,
+    #[command(flatten)]
+    pub proof_rng: ProofRngArgs,
+    /// Before proving, recompute the Sapling note commitment tree anchor of every Sapling claim
+    /// from the lightwalletd tree state at the snapshot height.
+    #[arg(long, env = ZAIR_CHECK_NOTE_POSITIONS, default_value_t = false)]
+    pub check_note_positions: bool,
+    /// Optional lightwalletd gRPC endpoint URL override for `--check-note-positions`.
+    #[arg(long, env = ZAIR_LIGHTWALLETD_URL)]
+    pub lightwalletd: Option<String>,
+    /// Also write a signing request for `claim sign --offline` on another machine.
+    #[arg(long, env = ZAIR_SIGNING_REQUEST_OUT, value_name = "SIGNING_REQUEST_FILE")]
+    pub export_signing_request: Option<PathBuf>,
+    /// Pools to prove: `sapling`, `orchard` or `both`. Claims of other pools in the claims
+    /// file are skipped, and only the selected pools' setup files are loaded.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
+}
+
+/// Arguments for `zair:` claim URIs.
+#[derive(Debug, clap::Args)]
+pub struct ClaimQrArgs {
+    /// Signed submission file to encode.
+    #[arg(
+        long,
+        env = ZAIR_SUBMISSION_IN,
+        default_value = DEFAULT_SUBMISSION_FILE
+    )]
+    pub submission_in: PathBuf,
+    /// URL the submission is published at. The URI then references it by digest instead of
+    /// carrying it inline.
+    #[arg(long, env = ZAIR_SUBMISSION_URL, conflicts_with = "decode")]
+    pub url: Option<String>,
+    /// Decode this `zair:` URI into `--submission-out` instead of encoding a submission.
+    #[arg(long, value_name = "URI")]
+    pub decode: Option<String>,
+    /// Output file for the submission decoded with `--decode`.
+    #[arg(
+        long,
+        env = ZAIR_SUBMISSION_OUT,
+        default_value = DEFAULT_SUBMISSION_FILE
+    )]
+    pub submission_out: PathBuf,
+}
+
+/// Arguments for single-file claim packages.
+#[derive(Debug, clap::Args)]
+pub struct ClaimPackageArgs {
+    /// Airdrop configuration file.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Proofs file generated by `claim prove`.
+    #[arg(long, env = ZAIR_PROOFS_IN, default_value = DEFAULT_PROOFS_FILE)]
+    pub proofs_in: PathBuf,
+    /// Signed submission file generated by `claim sign`.
+    #[arg(
+        long,
+        env = ZAIR_SUBMISSION_IN,
+        default_value = DEFAULT_SUBMISSION_FILE
+    )]
+    pub submission_in: PathBuf,
+    /// Verification report from `verify run` to include.
+    #[arg(long, env = ZAIR_VERIFICATION_REPORT_IN)]
+    pub report_in: Option<PathBuf>,
+    /// Shared message payload file the submission was signed over.
+    #[arg(long = "message", env = ZAIR_MESSAGE_FILE, value_name = "MESSAGE_FILE")]
+    pub message: Option<PathBuf>,
+    /// Per-claim message assignments JSON the submission was signed over. The message files it
+    /// references are included.
+    #[arg(
+        long = "messages",
+        env = ZAIR_MESSAGES_FILE,
+        value_name = "MESSAGES_FILE",
+        conflicts_with = "message"
+    )]
+    pub messages: Option<PathBuf>,
+    /// Output file for the package.
+    #[arg(long, env = ZAIR_PACKAGE_OUT, default_value = DEFAULT_PACKAGE_FILE)]
+    pub package_out: PathBuf,
+}
+
+/// Arguments for holdings attestations.
+#[derive(Debug, clap::Args)]
+pub struct ClaimAttestArgs {
+    /// Prepared claims file generated by `claim prepare`, for the note values of secrets written
+    /// by earlier versions, which record no value blindings.
+    #[arg(long, env = ZAIR_CLAIMS_IN)]
+    pub claims_in: Option<PathBuf>,
+    /// Proofs file generated by `claim prove`.
+    #[arg(long, env = ZAIR_PROOFS_IN, default_value = DEFAULT_PROOFS_FILE)]
+    pub proofs_in: PathBuf,
+    /// Local-only secrets file generated by `claim prove`, for the commitment randomness.
+    #[arg(
+        long,
+        env = ZAIR_SECRETS_IN,
+        default_value = DEFAULT_SECRETS_FILE
+    )]
+    pub secrets_in: PathBuf,
+    /// Output file for the holdings attestation.
+    #[arg(
+        long,
+        env = ZAIR_ATTESTATION_OUT,
+        default_value = DEFAULT_ATTESTATION_FILE
+    )]
+    pub attestation_out: PathBuf,
+    /// Pools to attest.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
+}
+
+/// Arguments for claimed value proofs.
+#[derive(Debug, clap::Args)]
+pub struct ClaimTotalArgs {
+    /// Prepared claims file generated by `claim prepare`, for the note values of secrets written
+    /// by earlier versions, which record no value blindings.
+    #[arg(long, env = ZAIR_CLAIMS_IN)]
+    pub claims_in: Option<PathBuf>,
+    /// Proofs file generated by `claim prove`.
+    #[arg(long, env = ZAIR_PROOFS_IN, default_value = DEFAULT_PROOFS_FILE)]
+    pub proofs_in: PathBuf,
+    /// Local-only secrets file generated by `claim prove`, for the commitment randomness.
+    #[arg(
+        long,
+        env = ZAIR_SECRETS_IN,
+        default_value = DEFAULT_SECRETS_FILE
+    )]
+    pub secrets_in: PathBuf,
+    /// Output file for the claimed value proof.
+    #[arg(
+        long,
+        env = ZAIR_CLAIMED_VALUE_OUT,
+        default_value = DEFAULT_CLAIMED_VALUE_FILE
+    )]
+    pub total_out: PathBuf,
+    /// Pools whose claims are counted.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
+}
+
+/// Arguments for canonical claim message construction.
+#[derive(Debug, clap::Args)]
+pub struct ClaimMessageArgs {
+    /// Airdrop configuration file.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Proofs file generated by `claim prove`.
+    #[arg(long, env = ZAIR_PROOFS_IN, default_value = DEFAULT_PROOFS_FILE)]
+    pub proofs_in: PathBuf,
+    /// Target-chain address that receives the claimed amounts.
+    #[arg(long, env = ZAIR_RECIPIENT)]
+    pub recipient: Option<String>,
+    /// JSON object mapping hex claim IDs to their own target-chain addresses. Claims not listed
+    /// pay out to `--recipient`.
+    #[arg(long, env = ZAIR_RECIPIENTS_FILE, value_name = "RECIPIENTS_FILE")]
+    pub recipients: Option<PathBuf>,
+    /// Output per-claim message assignments JSON for `claim sign --messages`.
+    /// The message files are written next to it.
+    #[arg(long, env = ZAIR_MESSAGES_OUT, default_value = DEFAULT_MESSAGES_FILE)]
+    pub messages_out: PathBuf,
+    /// Pools to build messages for.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
 }
 
 /// Arguments for claim signing.
@@ -242,7 +560,8 @@ pub struct ClaimSignArgs {
         default_value = DEFAULT_SECRETS_FILE
     )]
     pub secrets_in: PathBuf,
-    /// Path to file containing 64-byte seed as hex for deriving spending keys.
+    /// Path to file containing 64-byte seed as hex, or a unified spending key, for deriving
+    /// spending keys.
     #[arg(long, env = ZAIR_SEED_FILE, value_name = "SEED_FILE")]
     pub seed: PathBuf,
     /// Shared message payload file fallback used for claim signatures.
@@ -251,6 +570,19 @@ pub struct ClaimSignArgs {
     /// Per-claim message assignments JSON.
     #[arg(long = "messages", env = ZAIR_MESSAGES_FILE, value_name = "MESSAGES_FILE")]
     pub messages: Option<PathBuf>,
+    /// JSON object mapping hex claim IDs to target-chain addresses. Each claim signs a canonical
+    /// claim message paying out to its address, written next to the submission.
+    #[arg(
+        long,
+        env = ZAIR_RECIPIENTS_FILE,
+        value_name = "RECIPIENTS_FILE",
+        conflicts_with_all = ["messages", "offline", "detached"]
+    )]
+    pub recipients: Option<PathBuf>,
+    /// Last block height at which the submission may be accepted. Defaults to the end of the
+    /// claim window in the configuration.
+    #[arg(long, env = ZAIR_EXPIRY_HEIGHT)]
+    pub expiry_height: Option<u64>,
     /// ZIP-32 account index used to derive spend-auth keys from the seed.
     #[arg(long, env = ZAIR_ACCOUNT_ID, default_value_t = 0)]
     pub account: u32,
@@ -261,6 +593,193 @@ pub struct ClaimSignArgs {
         default_value = DEFAULT_SUBMISSION_FILE
     )]
     pub submission_out: PathBuf,
+    /// Sign a signing request from `claim prove --export-signing-request` instead of the proofs
+    /// and secrets, and write only the signatures for `claim assemble`.
+    #[arg(long, env = ZAIR_OFFLINE, default_value_t = false)]
+    pub offline: bool,
+    /// Signing request file read with `--offline`.
+    #[arg(
+        long,
+        env = ZAIR_SIGNING_REQUEST_IN,
+        default_value = DEFAULT_SIGNING_REQUEST_FILE
+    )]
+    pub signing_request_in: PathBuf,
+    /// Output file for claim signatures with `--offline`.
+    #[arg(
+        long,
+        env = ZAIR_SIGNATURES_OUT,
+        default_value = DEFAULT_SIGNATURES_FILE
+    )]
+    pub signatures_out: PathBuf,
+    /// Sign the external payload given with `--payload` instead of a claim message, and write
+    /// only the detached signatures instead of a submission.
+    #[arg(
+        long,
+        env = ZAIR_DETACHED,
+        default_value_t = false,
+        conflicts_with = "offline",
+        requires = "payload"
+    )]
+    pub detached: bool,
+    /// External payload file signed with `--detached`.
+    #[arg(long, env = ZAIR_PAYLOAD_FILE, value_name = "PAYLOAD_FILE")]
+    pub payload: Option<PathBuf>,
+    /// Output file for detached signatures with `--detached`.
+    #[arg(
+        long,
+        env = ZAIR_DETACHED_SIGNATURES_OUT,
+        default_value = DEFAULT_DETACHED_SIGNATURES_FILE
+    )]
+    pub detached_signatures_out: PathBuf,
+    /// Pools to sign: `sapling`, `orchard` or `both`. Proofs of other pools in the proofs file
+    /// are left out of the submission. Ignored with `--offline`; use `claim prove --pool` to limit
+    /// the signing request instead.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
+}
+
+/// Arguments for merging per-pool claim stage files.
+#[derive(Debug, clap::Args)]
+pub struct ClaimMergeArgs {
+    /// Claims files from `claim prepare --pool` to merge. Repeat or comma-separate.
+    #[arg(
+        long = "claims",
+        env = ZAIR_MERGE_CLAIMS,
+        value_name = "CLAIMS_FILE",
+        value_delimiter = ','
+    )]
+    pub claims: Vec<PathBuf>,
+    /// Output file for the merged claims.
+    #[arg(long, env = ZAIR_CLAIMS_OUT, default_value = DEFAULT_CLAIMS_FILE)]
+    pub claims_out: PathBuf,
+    /// Proofs files from `claim prove --pool` to merge. Repeat or comma-separate.
+    #[arg(
+        long = "proofs",
+        env = ZAIR_MERGE_PROOFS,
+        value_name = "PROOFS_FILE",
+        value_delimiter = ',',
+        requires = "secrets"
+    )]
+    pub proofs: Vec<PathBuf>,
+    /// Output file for the merged proofs.
+    #[arg(long, env = ZAIR_PROOFS_OUT, default_value = DEFAULT_PROOFS_FILE)]
+    pub proofs_out: PathBuf,
+    /// Secrets files written with the `--proofs` files, in the same order.
+    #[arg(
+        long = "secrets",
+        env = ZAIR_MERGE_SECRETS,
+        value_name = "SECRETS_FILE",
+        value_delimiter = ',',
+        requires = "proofs"
+    )]
+    pub secrets: Vec<PathBuf>,
+    /// Output file for the merged secrets.
+    #[arg(
+        long,
+        env = ZAIR_SECRETS_OUT,
+        default_value = DEFAULT_SECRETS_FILE
+    )]
+    pub secrets_out: PathBuf,
+}
+
+/// Arguments for assembling a submission from offline signatures.
+#[derive(Debug, clap::Args)]
+pub struct ClaimAssembleArgs {
+    /// Proofs file generated by `claim prove`.
+    #[arg(long, env = ZAIR_PROOFS_IN, default_value = DEFAULT_PROOFS_FILE)]
+    pub proofs_in: PathBuf,
+    /// Signatures file generated by `claim sign --offline`.
+    #[arg(
+        long,
+        env = ZAIR_SIGNATURES_IN,
+        default_value = DEFAULT_SIGNATURES_FILE
+    )]
+    pub signatures_in: PathBuf,
+    /// Output file for signed submission bundle.
+    #[arg(
+        long,
+        env = ZAIR_SUBMISSION_OUT,
+        default_value = DEFAULT_SUBMISSION_FILE
+    )]
+    pub submission_out: PathBuf,
+}
+
+/// Arguments for claim countersigning.
+#[derive(Debug, clap::Args)]
+pub struct ClaimCountersignArgs {
+    /// Airdrop configuration file.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Signed submission file generated by `claim sign`.
+    #[arg(
+        long,
+        env = ZAIR_SUBMISSION_IN,
+        default_value = DEFAULT_SUBMISSION_FILE
+    )]
+    pub submission_in: PathBuf,
+    /// Path to file containing the 32-byte Ed25519 operator secret key as hex.
+    #[arg(long, env = ZAIR_OPERATOR_KEY_FILE, value_name = "OPERATOR_KEY_FILE")]
+    pub operator_key: PathBuf,
+    /// Output file for the countersigned submission. Defaults to overwriting the input.
+    #[arg(long, env = ZAIR_SUBMISSION_OUT)]
+    pub submission_out: Option<PathBuf>,
+}
+
+/// Arguments for transparent claim signing.
+#[derive(Debug, clap::Args)]
+pub struct ClaimSignTransparentArgs {
+    /// Airdrop configuration file with a transparent snapshot from `config add-transparent`.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Transparent UTXO set written by `config add-transparent`.
+    #[arg(
+        long,
+        env = ZAIR_SNAPSHOT_TRANSPARENT_FILE,
+        default_value_os_t = default_input_path(snapshots_dir, DEFAULT_SNAPSHOT_TRANSPARENT_FILE)
+    )]
+    pub snapshot_transparent: PathBuf,
+    /// Path to file containing 64-byte seed as hex, or a unified spending key, for deriving
+    /// transparent address keys.
+    #[arg(long, env = ZAIR_SEED_FILE, value_name = "SEED_FILE")]
+    pub seed: PathBuf,
+    /// BIP 44 account index used to derive transparent address keys from the seed.
+    #[arg(long, env = ZAIR_ACCOUNT_ID, default_value_t = 0)]
+    pub account: u32,
+    /// Number of external addresses of the account to search for snapshot outputs.
+    #[arg(long, env = ZAIR_ADDRESS_COUNT, default_value_t = 20)]
+    pub address_count: u32,
+    /// Claim message file signed by every address.
+    #[arg(long = "message", env = ZAIR_MESSAGE_FILE, value_name = "MESSAGE_FILE")]
+    pub message: PathBuf,
+    /// Epoch of a phased airdrop to claim; the base epoch by default.
+    #[arg(long, env = ZAIR_EPOCH, value_name = "EPOCH")]
+    pub epoch: Option<u32>,
+    /// Last block height at which the claims may be accepted. Defaults to the end of the claim
+    /// window in the configuration.
+    #[arg(long, env = ZAIR_EXPIRY_HEIGHT)]
+    pub expiry_height: Option<u64>,
+    /// Output file for the signed transparent claims.
+    #[arg(
+        long,
+        env = ZAIR_TRANSPARENT_CLAIMS_OUT,
+        default_value = DEFAULT_TRANSPARENT_CLAIMS_FILE
+    )]
+    pub transparent_claims_out: PathBuf,
 }
 
 /// Claim command group.
@@ -291,10 +810,21 @@ pub enum ClaimCommands {
         #[command(flatten)]
         args: ClaimProveArgs,
     },
-    /// Sign claim proofs into a submission package.
+    /// Build the canonical claim message of every proof for `claim sign --messages`.
+    #[command(group(
+        clap::ArgGroup::new("recipient_input")
+            .args(["recipient", "recipients"])
+            .required(true)
+            .multiple(true)
+    ))]
+    Message {
+        #[command(flatten)]
+        args: ClaimMessageArgs,
+    },
+    /// Sign claim proofs into a submission package, or a signing request with `--offline`.
     #[command(group(
         clap::ArgGroup::new("message_input")
-            .args(["message", "messages"])
+            .args(["message", "messages", "payload", "recipients"])
             .required(true)
             .multiple(true)
     ))]
@@ -302,4 +832,53 @@ pub enum ClaimCommands {
         #[command(flatten)]
         args: ClaimSignArgs,
     },
+    /// Merge the claims, or the proofs and secrets, of separate `--pool` runs into one file each.
+    #[command(group(
+        clap::ArgGroup::new("merge_input")
+            .args(["claims", "proofs"])
+            .required(true)
+            .multiple(true)
+    ))]
+    Merge {
+        #[command(flatten)]
+        args: ClaimMergeArgs,
+    },
+    /// Merge claim proofs with signatures from `claim sign --offline` into a submission package.
+    Assemble {
+        #[command(flatten)]
+        args: ClaimAssembleArgs,
+    },
+    /// Add an operator countersignature to every claim of a signed submission.
+    Countersign {
+        #[command(flatten)]
+        args: ClaimCountersignArgs,
+    },
+    /// Print a signed submission as a `zair:` URI for a QR code, or decode one with `--decode`.
+    Qr {
+        #[command(flatten)]
+        args: ClaimQrArgs,
+    },
+    /// Bundle the configuration, proofs, signed submission, messages and verification report into
+    /// a single checksummed `.zairpkg` archive.
+    Package {
+        #[command(flatten)]
+        args: ClaimPackageArgs,
+    },
+    /// Attest the unspent value held at the snapshot with the claim proofs and their value
+    /// commitment openings, without signing a claim.
+    Attest {
+        #[command(flatten)]
+        args: ClaimAttestArgs,
+    },
+    /// Prove the total value of the proven claims to an auditor, opening the sum of each pool's
+    /// native value commitments instead of every claim.
+    Total {
+        #[command(flatten)]
+        args: ClaimTotalArgs,
+    },
+    /// Sign the snapshot balance of every transparent address of the account with its key.
+    SignTransparent {
+        #[command(flatten)]
+        args: ClaimSignTransparentArgs,
+    },
 }
@@ -2,20 +2,34 @@
 
 use std::path::PathBuf;
 
+use zair_core::base::TargetId;
 use zair_core::schema::config::ValueCommitmentScheme;
-use zair_sdk::common::PoolSelection;
+use zair_sdk::common::{Backend, PoolSelection};
+use zair_sdk::paths::{default_output_path, gap_trees_dir, snapshots_dir};
 
+#[cfg(feature = "sqlite")]
+use super::constants::ZAIR_NULLIFIER_DB;
 use super::constants::{
-    DEFAULT_CONFIG_FILE, DEFAULT_GAP_TREE_ORCHARD_FILE, DEFAULT_GAP_TREE_SAPLING_FILE,
-    DEFAULT_POOL, DEFAULT_SCHEME, DEFAULT_SNAPSHOT_ORCHARD_FILE, DEFAULT_SNAPSHOT_SAPLING_FILE,
-    DEFAULT_TARGET_ORCHARD, DEFAULT_TARGET_SAPLING, ZAIR_CONFIG_OUT, ZAIR_GAP_TREE_OUT_ORCHARD,
-    ZAIR_GAP_TREE_OUT_SAPLING, ZAIR_NO_GAP_TREE, ZAIR_POOL, ZAIR_SCHEME_ORCHARD,
-    ZAIR_SCHEME_SAPLING, ZAIR_SNAPSHOT_OUT_ORCHARD, ZAIR_SNAPSHOT_OUT_SAPLING, ZAIR_TARGET_ORCHARD,
-    ZAIR_TARGET_SAPLING,
+    DEFAULT_BACKEND, DEFAULT_CONFIG_FILE, DEFAULT_CONFIG_SIGNATURE_FILE,
+    DEFAULT_GAP_TREE_ORCHARD_FILE, DEFAULT_GAP_TREE_SAPLING_FILE, DEFAULT_POOL, DEFAULT_SCHEME,
+    DEFAULT_SNAPSHOT_ORCHARD_FILE, DEFAULT_SNAPSHOT_SAPLING_FILE,
+    DEFAULT_SNAPSHOT_TRANSPARENT_FILE, DEFAULT_TARGET_ORCHARD, DEFAULT_TARGET_SAPLING,
+    DEFAULT_TARGET_TRANSPARENT, ZAIR_ARTIFACT_PARAMS, ZAIR_ARTIFACTS_MANIFEST_FILE,
+    ZAIR_ARTIFACTS_OUT, ZAIR_BACKEND, ZAIR_BIND_ORCHARD_PARAMS, ZAIR_BIND_SAPLING_VK,
+    ZAIR_CHAIN_ID, ZAIR_CLAIM_EXPIRY_HEIGHT, ZAIR_CLAIM_START_HEIGHT, ZAIR_CONFIG_FILE,
+    ZAIR_CONFIG_OUT, ZAIR_CONFIG_SIGNATURE_IN, ZAIR_CONFIG_SIGNATURE_OUT, ZAIR_COUNTERSIGNERS,
+    ZAIR_EPOCH, ZAIR_EPOCH_CONFIG_FILE, ZAIR_GAP_TREE_OUT_ORCHARD, ZAIR_GAP_TREE_OUT_SAPLING,
+    ZAIR_INDEX_HEIGHTS, ZAIR_LIGHTWALLETD_URL, ZAIR_MAX_CLAIMS_PER_SUBMISSION, ZAIR_MIN_NOTE_VALUE,
+    ZAIR_NO_DISK_CHECK, ZAIR_NO_GAP_TREE, ZAIR_NOTIFY_URL, ZAIR_NULLIFIER_FILTER,
+    ZAIR_ORGANIZER_KEY, ZAIR_ORGANIZER_KEY_FILE, ZAIR_POOL, ZAIR_SCHEME_ORCHARD,
+    ZAIR_SCHEME_SAPLING, ZAIR_SNAPSHOT_OUT_ORCHARD, ZAIR_SNAPSHOT_OUT_SAPLING,
+    ZAIR_SNAPSHOT_OUT_TRANSPARENT, ZAIR_TARGET_ORCHARD, ZAIR_TARGET_SAPLING,
+    ZAIR_TARGET_TRANSPARENT, ZAIR_TORRENT, ZAIR_TRANSPARENT_ADDRESSES_FILE, ZAIR_WEB_SEEDS,
 };
 use super::{
-    BuildConfigArgs, parse_orchard_target_id, parse_pool_selection, parse_sapling_target_id,
-    parse_value_commitment_scheme,
+    BuildConfigArgs, parse_backend, parse_countersigner, parse_orchard_target_id,
+    parse_organizer_key, parse_pool_selection, parse_sapling_target_id,
+    parse_transparent_target_id, parse_value_commitment_scheme,
 };
 
 /// Arguments for `config build`.
@@ -39,7 +53,7 @@ pub struct ConfigBuildArgs {
         default_value = DEFAULT_TARGET_SAPLING,
         value_parser = parse_sapling_target_id
     )]
-    pub target_sapling: String,
+    pub target_sapling: TargetId,
     /// Sapling value commitment scheme.
     #[arg(
         long,
@@ -55,7 +69,7 @@ pub struct ConfigBuildArgs {
         default_value = DEFAULT_TARGET_ORCHARD,
         value_parser = parse_orchard_target_id
     )]
-    pub target_orchard: String,
+    pub target_orchard: TargetId,
     /// Orchard value commitment scheme.
     #[arg(
         long,
@@ -71,33 +85,249 @@ pub struct ConfigBuildArgs {
     #[arg(
         long,
         env = ZAIR_SNAPSHOT_OUT_SAPLING,
-        default_value = DEFAULT_SNAPSHOT_SAPLING_FILE
+        default_value_os_t = default_output_path(snapshots_dir, DEFAULT_SNAPSHOT_SAPLING_FILE)
     )]
     pub snapshot_out_sapling: PathBuf,
     /// Orchard snapshot nullifiers output file.
     #[arg(
         long,
         env = ZAIR_SNAPSHOT_OUT_ORCHARD,
-        default_value = DEFAULT_SNAPSHOT_ORCHARD_FILE
+        default_value_os_t = default_output_path(snapshots_dir, DEFAULT_SNAPSHOT_ORCHARD_FILE)
     )]
     pub snapshot_out_orchard: PathBuf,
     /// Sapling gap-tree output file.
     #[arg(
         long,
         env = ZAIR_GAP_TREE_OUT_SAPLING,
-        default_value = DEFAULT_GAP_TREE_SAPLING_FILE
+        default_value_os_t = default_output_path(gap_trees_dir, DEFAULT_GAP_TREE_SAPLING_FILE)
     )]
     pub gap_tree_out_sapling: PathBuf,
     /// Orchard gap-tree output file.
     #[arg(
         long,
         env = ZAIR_GAP_TREE_OUT_ORCHARD,
-        default_value = DEFAULT_GAP_TREE_ORCHARD_FILE
+        default_value_os_t = default_output_path(gap_trees_dir, DEFAULT_GAP_TREE_ORCHARD_FILE)
     )]
     pub gap_tree_out_orchard: PathBuf,
     /// Skip writing gap-tree artifacts.
     #[arg(long, env = ZAIR_NO_GAP_TREE, default_value_t = false)]
     pub no_gap_tree: bool,
+    /// Also write a `.idx` sidecar per snapshot recording the scanned range and the height at
+    /// which each nullifier was revealed.
+    #[arg(long, env = ZAIR_INDEX_HEIGHTS, default_value_t = false)]
+    pub index_heights: bool,
+    /// Also write a `.bloom` nullifier filter per snapshot, used by `claim prepare` to skip
+    /// snapshot searches.
+    #[arg(long, env = ZAIR_NULLIFIER_FILTER, default_value_t = false)]
+    pub nullifier_filter: bool,
+    /// Skip the check that the snapshot and gap-tree files fit on disk before the scan.
+    #[arg(long, env = ZAIR_NO_DISK_CHECK, default_value_t = false)]
+    pub no_disk_check: bool,
+    /// Read the snapshot nullifiers from this SQLite database (`pool`, `nullifier`, `height`
+    /// columns) instead of scanning lightwalletd. Note commitment roots still come from
+    /// lightwalletd.
+    #[cfg(feature = "sqlite")]
+    #[arg(
+        long,
+        env = ZAIR_NULLIFIER_DB,
+        value_name = "SQLITE_FILE",
+        conflicts_with = "index_heights"
+    )]
+    pub nullifier_db: Option<PathBuf>,
+    /// Sapling verifying key to bind into the configuration; `verify` rejects any other key.
+    #[arg(long, env = ZAIR_BIND_SAPLING_VK, value_name = "SAPLING_VK_FILE")]
+    pub bind_sapling_vk: Option<PathBuf>,
+    /// Orchard Halo2 params to bind into the configuration; `verify` rejects any other params.
+    #[arg(long, env = ZAIR_BIND_ORCHARD_PARAMS, value_name = "ORCHARD_PARAMS_FILE")]
+    pub bind_orchard_params: Option<PathBuf>,
+    /// Smallest note value in zatoshis that is claimed; `claim prepare` reports smaller notes as
+    /// excluded.
+    #[arg(long, env = ZAIR_MIN_NOTE_VALUE, value_name = "ZATOSHIS")]
+    pub min_note_value: Option<u64>,
+    /// Most claims, over both pools, that one submission may carry; `claim prepare` and `verify`
+    /// reject more.
+    #[arg(long, env = ZAIR_MAX_CLAIMS_PER_SUBMISSION, value_name = "CLAIMS")]
+    pub max_claims_per_submission: Option<u32>,
+    /// First block height of the claim window; `verify` rejects submissions at an earlier
+    /// `--current-height`.
+    #[arg(long, env = ZAIR_CLAIM_START_HEIGHT)]
+    pub claim_start_height: Option<u64>,
+    /// Last block height of the claim window; `claim sign` uses it as the default submission
+    /// expiry and `verify signature` rejects submissions that expire later.
+    #[arg(long, env = ZAIR_CLAIM_EXPIRY_HEIGHT)]
+    pub claim_expiry_height: Option<u64>,
+    /// Hex Ed25519 public key of an operator that must countersign every claim; repeat or
+    /// comma-separate for several operators.
+    #[arg(
+        long = "countersigner",
+        env = ZAIR_COUNTERSIGNERS,
+        value_name = "PUBLIC_KEY",
+        value_delimiter = ',',
+        value_parser = parse_countersigner
+    )]
+    pub countersigners: Vec<[u8; 32]>,
+    /// Chain ID of the chain the claims are submitted to, bound into every claim signature so
+    /// signatures for a testnet rehearsal are never valid on mainnet.
+    #[arg(long, env = ZAIR_CHAIN_ID)]
+    pub chain_id: Option<String>,
+    /// Also write a `.torrent` next to every snapshot and gap-tree file and log its magnet link.
+    #[arg(long, env = ZAIR_TORRENT, default_value_t = false)]
+    pub torrent: bool,
+    /// HTTP URL serving the files, added to the torrents as a web seed; a URL ending in `/` is a
+    /// directory holding the files and their `.torrent`s. Repeat or comma-separate for several.
+    #[arg(
+        long = "web-seed",
+        env = ZAIR_WEB_SEEDS,
+        value_name = "URL",
+        value_delimiter = ',',
+        requires = "torrent"
+    )]
+    pub web_seeds: Vec<String>,
+    /// Also write a manifest of the configuration, snapshot, gap-tree and setup files with their
+    /// BLAKE3 hashes to this file, usually `artifacts.json`, for `zair fetch`.
+    #[arg(long, env = ZAIR_ARTIFACTS_OUT, value_name = "FILE")]
+    pub artifacts_out: Option<PathBuf>,
+    /// Setup parameter file to list in the artifact manifest, such as the Sapling proving key.
+    /// Files bound with `--bind-sapling-vk` and `--bind-orchard-params` are listed as well.
+    /// Repeat or comma-separate for several.
+    #[arg(
+        long = "artifact-params",
+        env = ZAIR_ARTIFACT_PARAMS,
+        value_name = "FILE",
+        value_delimiter = ',',
+        requires = "artifacts_out"
+    )]
+    pub artifact_params: Vec<PathBuf>,
+    /// POST a JSON event to this URL when the build completes or fails.
+    #[arg(long, env = ZAIR_NOTIFY_URL, value_name = "URL")]
+    pub notify_url: Option<String>,
+}
+
+/// Arguments for `config add-epoch`.
+#[derive(Debug, clap::Args)]
+pub struct ConfigAddEpochArgs {
+    /// Configuration of the phased airdrop; its top-level snapshot is the base epoch.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Number of the new epoch, above 0.
+    #[arg(long, env = ZAIR_EPOCH, value_name = "EPOCH")]
+    pub epoch: u32,
+    /// Configuration built by `config build` at the snapshot height of the new epoch.
+    #[arg(long, env = ZAIR_EPOCH_CONFIG_FILE, value_name = "FILE")]
+    pub epoch_config: PathBuf,
+    /// Output file for the combined configuration; `--config` is updated in place by default.
+    #[arg(long, env = ZAIR_CONFIG_OUT)]
+    pub config_out: Option<PathBuf>,
+}
+
+/// Arguments for `config add-transparent`.
+#[derive(Debug, clap::Args)]
+pub struct ConfigAddTransparentArgs {
+    /// Airdrop configuration built by `config build`; its snapshot height is the height of the
+    /// transparent snapshot.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// File listing one eligible P2PKH address per line; empty lines and `#` comments are
+    /// skipped.
+    #[arg(long, env = ZAIR_TRANSPARENT_ADDRESSES_FILE, value_name = "ADDRESSES_FILE")]
+    pub addresses: PathBuf,
+    /// Optional lightwalletd gRPC endpoint URL override.
+    #[arg(long, env = ZAIR_LIGHTWALLETD_URL)]
+    pub lightwalletd: Option<String>,
+    /// Indexer serving `--lightwalletd` (lightwalletd or zaino).
+    #[arg(
+        long,
+        env = ZAIR_BACKEND,
+        default_value = DEFAULT_BACKEND,
+        value_parser = parse_backend
+    )]
+    pub backend: Backend,
+    /// Transparent target id bound into every transparent claim signature. Must be <= 32 bytes.
+    #[arg(
+        long,
+        env = ZAIR_TARGET_TRANSPARENT,
+        default_value = DEFAULT_TARGET_TRANSPARENT,
+        value_parser = parse_transparent_target_id
+    )]
+    pub target_transparent: TargetId,
+    /// Transparent UTXO set output file.
+    #[arg(
+        long,
+        env = ZAIR_SNAPSHOT_OUT_TRANSPARENT,
+        default_value_os_t = default_output_path(snapshots_dir, DEFAULT_SNAPSHOT_TRANSPARENT_FILE)
+    )]
+    pub snapshot_out_transparent: PathBuf,
+    /// Output file for the configuration; `--config` is updated in place by default.
+    #[arg(long, env = ZAIR_CONFIG_OUT)]
+    pub config_out: Option<PathBuf>,
+}
+
+/// Arguments for `config audit`.
+#[derive(Debug, clap::Args)]
+pub struct ConfigAuditArgs {
+    /// Airdrop configuration file to audit.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+}
+
+/// Arguments for `config sign`.
+#[derive(Debug, clap::Args)]
+pub struct ConfigSignArgs {
+    /// Final airdrop configuration to sign.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Artifact manifest written by `config build --artifacts-out`, signed with the
+    /// configuration.
+    #[arg(long, env = ZAIR_ARTIFACTS_MANIFEST_FILE, value_name = "FILE")]
+    pub artifacts: Option<PathBuf>,
+    /// File holding the hex-encoded 32-byte Ed25519 secret key of the organizer.
+    #[arg(long, env = ZAIR_ORGANIZER_KEY_FILE, value_name = "FILE")]
+    pub organizer_key: PathBuf,
+    /// Output file for the signature.
+    #[arg(long, env = ZAIR_CONFIG_SIGNATURE_OUT, default_value = DEFAULT_CONFIG_SIGNATURE_FILE)]
+    pub signature_out: PathBuf,
+}
+
+/// Arguments for `config verify-signature`.
+#[derive(Debug, clap::Args)]
+pub struct ConfigVerifySignatureArgs {
+    /// Airdrop configuration to verify.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Artifact manifest to verify with the configuration.
+    #[arg(long, env = ZAIR_ARTIFACTS_MANIFEST_FILE, value_name = "FILE")]
+    pub artifacts: Option<PathBuf>,
+    /// Signature written by `config sign`.
+    #[arg(long, env = ZAIR_CONFIG_SIGNATURE_IN, default_value = DEFAULT_CONFIG_SIGNATURE_FILE)]
+    pub signature_in: PathBuf,
+    /// Hex Ed25519 public key of the organizer, obtained from a trusted channel.
+    #[arg(long, env = ZAIR_ORGANIZER_KEY, value_parser = parse_organizer_key)]
+    pub organizer_key: [u8; 32],
 }
 
 /// Config command group.
@@ -108,4 +338,31 @@ pub enum ConfigCommands {
         #[command(flatten)]
         args: ConfigBuildArgs,
     },
+    /// Check the target IDs against the domains of the Zcash protocol. Fails on a collision.
+    Audit {
+        #[command(flatten)]
+        args: ConfigAuditArgs,
+    },
+    /// Add the snapshot of another `config build` run as a later epoch of a phased airdrop.
+    AddEpoch {
+        #[command(flatten)]
+        args: ConfigAddEpochArgs,
+    },
+    /// Collect the outputs of a list of transparent addresses at the snapshot height and bind
+    /// them into the configuration, so those addresses can sign transparent claims.
+    AddTransparent {
+        #[command(flatten)]
+        args: ConfigAddTransparentArgs,
+    },
+    /// Sign the final configuration and its artifact manifest with the organizer key.
+    Sign {
+        #[command(flatten)]
+        args: ConfigSignArgs,
+    },
+    /// Verify the organizer signature of a configuration before trusting its roots and target
+    /// IDs.
+    VerifySignature {
+        #[command(flatten)]
+        args: ConfigVerifySignatureArgs,
+    },
 }
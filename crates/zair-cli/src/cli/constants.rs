@@ -10,14 +10,23 @@ pub const ZAIR_SEED_FILE: &str = "ZAIR_SEED_FILE";
 pub const ZAIR_MESSAGE_FILE: &str = "ZAIR_MESSAGE_FILE";
 pub const ZAIR_MESSAGES_FILE: &str = "ZAIR_MESSAGES_FILE";
 pub const ZAIR_ACCOUNT_ID: &str = "ZAIR_ACCOUNT_ID";
+pub const ZAIR_ADDRESS_INDEX: &str = "ZAIR_ADDRESS_INDEX";
 pub const ZAIR_NETWORK: &str = "ZAIR_NETWORK";
+pub const ZAIR_NUPARAMS: &str = "ZAIR_NUPARAMS";
 pub const ZAIR_LIGHTWALLETD_URL: &str = "ZAIR_LIGHTWALLETD_URL";
+pub const ZAIR_BACKEND: &str = "ZAIR_BACKEND";
 pub const ZAIR_BIRTHDAY: &str = "ZAIR_BIRTHDAY";
 pub const ZAIR_SNAPSHOT_HEIGHT: &str = "ZAIR_SNAPSHOT_HEIGHT";
+pub const ZAIR_EPOCH: &str = "ZAIR_EPOCH";
+pub const ZAIR_NOTIFY_URL: &str = "ZAIR_NOTIFY_URL";
+pub const ZAIR_NON_INTERACTIVE: &str = "ZAIR_NON_INTERACTIVE";
+pub const ZAIR_LOG_SENSITIVE: &str = "ZAIR_LOG_SENSITIVE";
+pub const ZAIR_AUDIT_TRAIL: &str = "ZAIR_AUDIT_TRAIL";
 
 // Snapshot files
 pub const ZAIR_SNAPSHOT_SAPLING_FILE: &str = "ZAIR_SNAPSHOT_SAPLING_FILE";
 pub const ZAIR_SNAPSHOT_ORCHARD_FILE: &str = "ZAIR_SNAPSHOT_ORCHARD_FILE";
+pub const ZAIR_SNAPSHOT_TRANSPARENT_FILE: &str = "ZAIR_SNAPSHOT_TRANSPARENT_FILE";
 
 // Gap-tree
 pub const ZAIR_GAP_TREE_SAPLING_FILE: &str = "ZAIR_GAP_TREE_SAPLING_FILE";
@@ -35,13 +44,24 @@ pub const ZAIR_SETUP_SCHEME: &str = "ZAIR_SETUP_SCHEME";
 pub const ZAIR_SETUP_PK_OUT: &str = "ZAIR_SETUP_PK_OUT";
 pub const ZAIR_SETUP_VK_OUT: &str = "ZAIR_SETUP_VK_OUT";
 pub const ZAIR_SETUP_ORCHARD_PARAMS_OUT: &str = "ZAIR_SETUP_ORCHARD_PARAMS_OUT";
+pub const ZAIR_SETUP_BIND_CONFIG: &str = "ZAIR_SETUP_BIND_CONFIG";
+pub const ZAIR_SETUP_FETCH_URL: &str = "ZAIR_SETUP_FETCH_URL";
+pub const ZAIR_SETUP_FETCH_SHA256: &str = "ZAIR_SETUP_FETCH_SHA256";
+pub const ZAIR_SETUP_FETCH_DIR: &str = "ZAIR_SETUP_FETCH_DIR";
 
 // Key
 pub const ZAIR_SEED_OUT: &str = "ZAIR_SEED_OUT";
 pub const ZAIR_MNEMONIC_FILE: &str = "ZAIR_MNEMONIC_FILE";
 pub const ZAIR_MNEMONIC_STDIN: &str = "ZAIR_MNEMONIC_STDIN";
+pub const ZAIR_MNEMONIC_LANGUAGE: &str = "ZAIR_MNEMONIC_LANGUAGE";
 pub const ZAIR_NO_PASSPHRASE: &str = "ZAIR_NO_PASSPHRASE";
+pub const ZAIR_PASSPHRASE_FILE: &str = "ZAIR_PASSPHRASE_FILE";
+pub const ZAIR_PASSPHRASE_ENV: &str = "ZAIR_PASSPHRASE_ENV";
 pub const ZAIR_UFVK_OUT: &str = "ZAIR_UFVK_OUT";
+pub const ZAIR_PROVING_KEY_OUT: &str = "ZAIR_PROVING_KEY_OUT";
+pub const ZAIR_WALLET_EXPORT: &str = "ZAIR_WALLET_EXPORT";
+pub const ZAIR_WALLET_EXPORT_FORMAT: &str = "ZAIR_WALLET_EXPORT_FORMAT";
+pub const ZAIR_WALLET_ACCOUNT_NAME: &str = "ZAIR_WALLET_ACCOUNT_NAME";
 
 // Config
 pub const ZAIR_POOL: &str = "ZAIR_POOL";
@@ -55,6 +75,75 @@ pub const ZAIR_SNAPSHOT_OUT_ORCHARD: &str = "ZAIR_SNAPSHOT_OUT_ORCHARD";
 pub const ZAIR_GAP_TREE_OUT_SAPLING: &str = "ZAIR_GAP_TREE_OUT_SAPLING";
 pub const ZAIR_GAP_TREE_OUT_ORCHARD: &str = "ZAIR_GAP_TREE_OUT_ORCHARD";
 pub const ZAIR_NO_GAP_TREE: &str = "ZAIR_NO_GAP_TREE";
+pub const ZAIR_INDEX_HEIGHTS: &str = "ZAIR_INDEX_HEIGHTS";
+pub const ZAIR_NULLIFIER_FILTER: &str = "ZAIR_NULLIFIER_FILTER";
+pub const ZAIR_NO_DISK_CHECK: &str = "ZAIR_NO_DISK_CHECK";
+pub const ZAIR_NULLIFIER_DB: &str = "ZAIR_NULLIFIER_DB";
+pub const ZAIR_BIND_SAPLING_VK: &str = "ZAIR_BIND_SAPLING_VK";
+pub const ZAIR_BIND_ORCHARD_PARAMS: &str = "ZAIR_BIND_ORCHARD_PARAMS";
+pub const ZAIR_MIN_NOTE_VALUE: &str = "ZAIR_MIN_NOTE_VALUE";
+pub const ZAIR_MAX_CLAIMS_PER_SUBMISSION: &str = "ZAIR_MAX_CLAIMS_PER_SUBMISSION";
+pub const ZAIR_CLAIM_START_HEIGHT: &str = "ZAIR_CLAIM_START_HEIGHT";
+pub const ZAIR_CLAIM_EXPIRY_HEIGHT: &str = "ZAIR_CLAIM_EXPIRY_HEIGHT";
+pub const ZAIR_COUNTERSIGNERS: &str = "ZAIR_COUNTERSIGNERS";
+pub const ZAIR_CHAIN_ID: &str = "ZAIR_CHAIN_ID";
+pub const ZAIR_TORRENT: &str = "ZAIR_TORRENT";
+pub const ZAIR_WEB_SEEDS: &str = "ZAIR_WEB_SEEDS";
+pub const ZAIR_ARTIFACTS_OUT: &str = "ZAIR_ARTIFACTS_OUT";
+pub const ZAIR_ARTIFACT_PARAMS: &str = "ZAIR_ARTIFACT_PARAMS";
+pub const ZAIR_EPOCH_CONFIG_FILE: &str = "ZAIR_EPOCH_CONFIG_FILE";
+pub const ZAIR_TRANSPARENT_ADDRESSES_FILE: &str = "ZAIR_TRANSPARENT_ADDRESSES_FILE";
+pub const ZAIR_TARGET_TRANSPARENT: &str = "ZAIR_TARGET_TRANSPARENT";
+pub const ZAIR_SNAPSHOT_OUT_TRANSPARENT: &str = "ZAIR_SNAPSHOT_OUT_TRANSPARENT";
+pub const ZAIR_ARTIFACTS_MANIFEST_FILE: &str = "ZAIR_ARTIFACTS_MANIFEST_FILE";
+pub const ZAIR_ORGANIZER_KEY_FILE: &str = "ZAIR_ORGANIZER_KEY_FILE";
+pub const ZAIR_ORGANIZER_KEY: &str = "ZAIR_ORGANIZER_KEY";
+pub const ZAIR_CONFIG_SIGNATURE_OUT: &str = "ZAIR_CONFIG_SIGNATURE_OUT";
+pub const ZAIR_CONFIG_SIGNATURE_IN: &str = "ZAIR_CONFIG_SIGNATURE_IN";
+
+// Snapshot
+pub const ZAIR_AGAINST_LIGHTWALLETD_URL: &str = "ZAIR_AGAINST_LIGHTWALLETD_URL";
+pub const ZAIR_AGAINST_SNAPSHOT_SAPLING: &str = "ZAIR_AGAINST_SNAPSHOT_SAPLING";
+pub const ZAIR_AGAINST_SNAPSHOT_ORCHARD: &str = "ZAIR_AGAINST_SNAPSHOT_ORCHARD";
+pub const ZAIR_RECONCILE_REPORT_OUT: &str = "ZAIR_RECONCILE_REPORT_OUT";
+pub const ZAIR_EXPORT_FORMAT: &str = "ZAIR_EXPORT_FORMAT";
+pub const ZAIR_EXPORT_OUT: &str = "ZAIR_EXPORT_OUT";
+pub const ZAIR_ORIGIN_HEIGHTS: &str = "ZAIR_ORIGIN_HEIGHTS";
+#[cfg(feature = "serve")]
+pub const ZAIR_SNAPSHOT_SERVE_ADDR: &str = "ZAIR_SNAPSHOT_SERVE_ADDR";
+pub const ZAIR_IPFS_API_URL: &str = "ZAIR_IPFS_API_URL";
+
+// Bench
+pub const ZAIR_BENCH_SIZES: &str = "ZAIR_BENCH_SIZES";
+
+// Fetch
+pub const ZAIR_ARTIFACTS_MANIFEST_URL: &str = "ZAIR_ARTIFACTS_MANIFEST_URL";
+pub const ZAIR_FETCH_DIR: &str = "ZAIR_FETCH_DIR";
+
+// Doctor
+pub const ZAIR_DOCTOR_DATA_DIR: &str = "ZAIR_DOCTOR_DATA_DIR";
+
+// Self-update
+pub const ZAIR_RELEASE_MANIFEST_URL: &str = "ZAIR_RELEASE_MANIFEST_URL";
+pub const ZAIR_RELEASE_KEY: &str = "ZAIR_RELEASE_KEY";
+/// Hex Ed25519 release key embedded by release builds from `ZAIR_BUILD_RELEASE_KEY`.
+pub const EMBEDDED_RELEASE_KEY: Option<&str> = option_env!("ZAIR_BUILD_RELEASE_KEY");
+pub const ZAIR_UPDATE_CHECK: &str = "ZAIR_UPDATE_CHECK";
+
+// Watch
+pub const ZAIR_WATCH_FROM_HEIGHT: &str = "ZAIR_WATCH_FROM_HEIGHT";
+pub const ZAIR_WATCH_POLL_INTERVAL: &str = "ZAIR_WATCH_POLL_INTERVAL";
+pub const ZAIR_WATCH_WEBHOOK_URL: &str = "ZAIR_WATCH_WEBHOOK_URL";
+pub const ZAIR_WATCH_ONCE: &str = "ZAIR_WATCH_ONCE";
+
+// Prove
+pub const ZAIR_PROVE_QUEUE_DIR: &str = "ZAIR_PROVE_QUEUE_DIR";
+pub const ZAIR_PROVING_KEY_FILE: &str = "ZAIR_PROVING_KEY_FILE";
+pub const ZAIR_PROVE_JOB: &str = "ZAIR_PROVE_JOB";
+#[cfg(feature = "prove")]
+pub const ZAIR_PROVE_POLL_INTERVAL: &str = "ZAIR_PROVE_POLL_INTERVAL";
+#[cfg(feature = "prove")]
+pub const ZAIR_PROVE_ONCE: &str = "ZAIR_PROVE_ONCE";
 
 // Claim
 pub const ZAIR_CLAIMS_OUT: &str = "ZAIR_CLAIMS_OUT";
@@ -66,6 +155,68 @@ pub const ZAIR_SECRETS_IN: &str = "ZAIR_SECRETS_IN";
 pub const ZAIR_SUBMISSION_OUT: &str = "ZAIR_SUBMISSION_OUT";
 pub const ZAIR_SUBMISSION_IN: &str = "ZAIR_SUBMISSION_IN";
 pub const ZAIR_UFVK_FILE: &str = "ZAIR_UFVK_FILE";
+pub const ZAIR_CHECK_NOTE_POSITIONS: &str = "ZAIR_CHECK_NOTE_POSITIONS";
+pub const ZAIR_CHECK_MEMPOOL: &str = "ZAIR_CHECK_MEMPOOL";
+pub const ZAIR_EXPIRY_HEIGHT: &str = "ZAIR_EXPIRY_HEIGHT";
+pub const ZAIR_CURRENT_HEIGHT: &str = "ZAIR_CURRENT_HEIGHT";
+pub const ZAIR_HEIGHT_FROM: &str = "ZAIR_HEIGHT_FROM";
+pub const ZAIR_HEIGHT_URL: &str = "ZAIR_HEIGHT_URL";
+pub const ZAIR_OPERATOR_KEY_FILE: &str = "ZAIR_OPERATOR_KEY_FILE";
+pub const ZAIR_SIGNING_REQUEST_OUT: &str = "ZAIR_SIGNING_REQUEST_OUT";
+pub const ZAIR_SIGNING_REQUEST_IN: &str = "ZAIR_SIGNING_REQUEST_IN";
+pub const ZAIR_SIGNATURES_OUT: &str = "ZAIR_SIGNATURES_OUT";
+pub const ZAIR_SIGNATURES_IN: &str = "ZAIR_SIGNATURES_IN";
+pub const ZAIR_OFFLINE: &str = "ZAIR_OFFLINE";
+pub const ZAIR_DETACHED: &str = "ZAIR_DETACHED";
+pub const ZAIR_PAYLOAD_FILE: &str = "ZAIR_PAYLOAD_FILE";
+pub const ZAIR_DETACHED_SIGNATURES_OUT: &str = "ZAIR_DETACHED_SIGNATURES_OUT";
+pub const ZAIR_DETACHED_SIGNATURES_IN: &str = "ZAIR_DETACHED_SIGNATURES_IN";
+pub const ZAIR_ADDRESS_COUNT: &str = "ZAIR_ADDRESS_COUNT";
+pub const ZAIR_TRANSPARENT_CLAIMS_OUT: &str = "ZAIR_TRANSPARENT_CLAIMS_OUT";
+pub const ZAIR_TRANSPARENT_CLAIMS_IN: &str = "ZAIR_TRANSPARENT_CLAIMS_IN";
+pub const ZAIR_AUDIT_TRAIL_IN: &str = "ZAIR_AUDIT_TRAIL_IN";
+pub const ZAIR_RECIPIENT: &str = "ZAIR_RECIPIENT";
+pub const ZAIR_RECIPIENTS_FILE: &str = "ZAIR_RECIPIENTS_FILE";
+pub const ZAIR_MESSAGES_OUT: &str = "ZAIR_MESSAGES_OUT";
+pub const ZAIR_ATTESTATION_OUT: &str = "ZAIR_ATTESTATION_OUT";
+pub const ZAIR_ATTESTATION_IN: &str = "ZAIR_ATTESTATION_IN";
+pub const ZAIR_CLAIMED_VALUE_OUT: &str = "ZAIR_CLAIMED_VALUE_OUT";
+pub const ZAIR_CLAIMED_VALUE_IN: &str = "ZAIR_CLAIMED_VALUE_IN";
+pub const ZAIR_SUBMISSION_URL: &str = "ZAIR_SUBMISSION_URL";
+pub const ZAIR_PACKAGE_OUT: &str = "ZAIR_PACKAGE_OUT";
+pub const ZAIR_PACKAGE_IN: &str = "ZAIR_PACKAGE_IN";
+pub const ZAIR_PACKAGE_EXTRACT_DIR: &str = "ZAIR_PACKAGE_EXTRACT_DIR";
+pub const ZAIR_VERIFICATION_REPORT_IN: &str = "ZAIR_VERIFICATION_REPORT_IN";
+pub const ZAIR_SNAPSHOT_URL: &str = "ZAIR_SNAPSHOT_URL";
+pub const ZAIR_SNAPSHOT_CID: &str = "ZAIR_SNAPSHOT_CID";
+pub const ZAIR_SNAPSHOT_MAGNETS: &str = "ZAIR_SNAPSHOT_MAGNETS";
+pub const ZAIR_IPFS_GATEWAY: &str = "ZAIR_IPFS_GATEWAY";
+pub const ZAIR_MERGE_CLAIMS: &str = "ZAIR_MERGE_CLAIMS";
+pub const ZAIR_MERGE_PROOFS: &str = "ZAIR_MERGE_PROOFS";
+pub const ZAIR_MERGE_SECRETS: &str = "ZAIR_MERGE_SECRETS";
+#[cfg(feature = "serve")]
+pub const ZAIR_SERVE_ADDR: &str = "ZAIR_SERVE_ADDR";
+
+// Verify
+pub const ZAIR_SUBMISSIONS_DIR: &str = "ZAIR_SUBMISSIONS_DIR";
+pub const ZAIR_DEDUPE_REPORT_OUT: &str = "ZAIR_DEDUPE_REPORT_OUT";
+pub const ZAIR_TOTALS_DIR: &str = "ZAIR_TOTALS_DIR";
+pub const ZAIR_STATS_OUT: &str = "ZAIR_STATS_OUT";
+pub const ZAIR_VERIFICATION_REPORT_OUT: &str = "ZAIR_VERIFICATION_REPORT_OUT";
+#[cfg(feature = "serve")]
+pub const ZAIR_VERIFY_SERVE_ADDR: &str = "ZAIR_VERIFY_SERVE_ADDR";
+#[cfg(feature = "serve")]
+pub const ZAIR_API_KEYS_FILE: &str = "ZAIR_API_KEYS_FILE";
+#[cfg(feature = "serve")]
+pub const ZAIR_RATE_LIMIT: &str = "ZAIR_RATE_LIMIT";
+#[cfg(feature = "serve")]
+pub const ZAIR_AUDIT_LOG: &str = "ZAIR_AUDIT_LOG";
+#[cfg(feature = "registry")]
+pub const ZAIR_REGISTRY_FILE: &str = "ZAIR_REGISTRY_FILE";
+
+// Metrics
+#[cfg(feature = "metrics")]
+pub const ZAIR_METRICS_ADDR: &str = "ZAIR_METRICS_ADDR";
 
 // -------------------------
 // Default values
@@ -73,25 +224,58 @@ pub const ZAIR_UFVK_FILE: &str = "ZAIR_UFVK_FILE";
 
 // File paths
 pub const DEFAULT_CONFIG_FILE: &str = "config.json";
+pub const DEFAULT_CONFIG_SIGNATURE_FILE: &str = "config.sig.json";
 pub const DEFAULT_CLAIMS_FILE: &str = "claim-prepared.json";
 pub const DEFAULT_PROOFS_FILE: &str = "claim-proofs.json";
 pub const DEFAULT_SECRETS_FILE: &str = "claim-proofs-secrets.json";
 pub const DEFAULT_SUBMISSION_FILE: &str = "claim-submission.json";
+pub const DEFAULT_VERIFICATION_REPORT_FILE: &str = "verification-report.json";
+pub const DEFAULT_SIGNING_REQUEST_FILE: &str = "claim-signing-request.json";
+pub const DEFAULT_SIGNATURES_FILE: &str = "claim-signatures.json";
+pub const DEFAULT_DETACHED_SIGNATURES_FILE: &str = "claim-detached-signatures.json";
+pub const DEFAULT_TRANSPARENT_CLAIMS_FILE: &str = "claim-transparent.json";
+pub const DEFAULT_AUDIT_TRAIL_FILE: &str = "audit.log";
+pub const DEFAULT_MESSAGES_FILE: &str = "claim-messages.json";
+pub const DEFAULT_ATTESTATION_FILE: &str = "holdings-attestation.json";
+pub const DEFAULT_CLAIMED_VALUE_FILE: &str = "claimed-value.json";
+pub const DEFAULT_PACKAGE_FILE: &str = "claim.zairpkg";
+pub const DEFAULT_PACKAGE_EXTRACT_DIR: &str = "claim-package";
 pub const DEFAULT_SAPLING_PK_FILE: &str = "setup-sapling-pk.params";
 pub const DEFAULT_SAPLING_VK_FILE: &str = "setup-sapling-vk.params";
 pub const DEFAULT_ORCHARD_PARAMS_FILE: &str = "setup-orchard-params.bin";
 pub const DEFAULT_SNAPSHOT_SAPLING_FILE: &str = "snapshot-sapling.bin";
 pub const DEFAULT_SNAPSHOT_ORCHARD_FILE: &str = "snapshot-orchard.bin";
+pub const DEFAULT_SNAPSHOT_TRANSPARENT_FILE: &str = "snapshot-transparent.json";
 pub const DEFAULT_GAP_TREE_SAPLING_FILE: &str = "gaptree-sapling.bin";
 pub const DEFAULT_GAP_TREE_ORCHARD_FILE: &str = "gaptree-orchard.bin";
+pub const DEFAULT_RECONCILE_REPORT_FILE: &str = "snapshot-reconcile.json";
+pub const DEFAULT_EXPORT_FILE_STEM: &str = "snapshot-nullifiers";
 pub const DEFAULT_UFVK_FILE: &str = "ufvk.txt";
 pub const DEFAULT_SEED_FILE: &str = "seed.txt";
+pub const DEFAULT_PROVING_KEY_FILE: &str = "proving-key.json";
 
 // Parsed values
 pub const DEFAULT_NETWORK: &str = "mainnet";
+pub const DEFAULT_BACKEND: &str = "lightwalletd";
 pub const DEFAULT_SCHEME: &str = "native";
 pub const DEFAULT_GAP_TREE_MODE: &str = "none";
 pub const DEFAULT_ORCHARD_PARAMS_MODE: &str = "auto";
+#[cfg(feature = "serve")]
+pub const DEFAULT_SERVE_ADDR: &str = "127.0.0.1:8547";
+#[cfg(feature = "serve")]
+pub const DEFAULT_VERIFY_SERVE_ADDR: &str = "127.0.0.1:8548";
+#[cfg(feature = "serve")]
+pub const DEFAULT_RATE_LIMIT: &str = "60";
+#[cfg(feature = "serve")]
+pub const DEFAULT_SNAPSHOT_SERVE_ADDR: &str = "127.0.0.1:8549";
+pub const DEFAULT_IPFS_API_URL: &str = "http://127.0.0.1:5001";
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io";
 pub const DEFAULT_POOL: &str = "both";
+pub const DEFAULT_EXPORT_FORMAT: &str = "csv";
+pub const DEFAULT_BENCH_SIZES: &str = "10000,100000";
+pub const DEFAULT_WATCH_POLL_INTERVAL: &str = "60";
+#[cfg(feature = "prove")]
+pub const DEFAULT_PROVE_POLL_INTERVAL: &str = "5";
 pub const DEFAULT_TARGET_SAPLING: &str = "ZAIRTEST";
 pub const DEFAULT_TARGET_ORCHARD: &str = "ZAIRTEST:O";
+pub const DEFAULT_TARGET_TRANSPARENT: &str = "ZAIRTEST:T";
@@ -0,0 +1,38 @@
+//! Doctor command.
+
+use std::path::PathBuf;
+
+use super::constants::{
+    DEFAULT_CONFIG_FILE, ZAIR_CONFIG_FILE, ZAIR_DOCTOR_DATA_DIR, ZAIR_LIGHTWALLETD_URL,
+    ZAIR_ORCHARD_PARAMS_FILE, ZAIR_SAPLING_PK_FILE, ZAIR_SAPLING_VK_FILE,
+};
+
+/// Arguments for checking the environment before a long-running command.
+#[derive(Debug, clap::Args)]
+pub struct DoctorArgs {
+    /// Airdrop configuration file to check, for the network, pools and snapshot height.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Optional lightwalletd gRPC endpoint URL override.
+    #[arg(long, env = ZAIR_LIGHTWALLETD_URL)]
+    pub lightwalletd: Option<String>,
+    /// Sapling proving key file. Defaults to the one `claim run` would use.
+    #[arg(long = "sapling-pk", env = ZAIR_SAPLING_PK_FILE, value_name = "SAPLING_PK_FILE")]
+    pub sapling_pk: Option<PathBuf>,
+    /// Sapling verifying key file. Defaults to `setup-sapling-vk-<scheme>.params` or
+    /// `setup-sapling-vk.params`, in the working directory or the standard params directory.
+    #[arg(long = "sapling-vk", env = ZAIR_SAPLING_VK_FILE, value_name = "SAPLING_VK_FILE")]
+    pub sapling_vk: Option<PathBuf>,
+    /// Orchard Halo2 params file. Defaults to the one `claim run` would use.
+    #[arg(long, env = ZAIR_ORCHARD_PARAMS_FILE, value_name = "ORCHARD_PARAMS_FILE")]
+    pub orchard_params: Option<PathBuf>,
+    /// Directory whose file system must hold the snapshots and gap trees. Defaults to the
+    /// standard snapshots directory.
+    #[arg(long, env = ZAIR_DOCTOR_DATA_DIR, value_name = "DIR")]
+    pub data_dir: Option<PathBuf>,
+}
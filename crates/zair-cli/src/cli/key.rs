@@ -1,16 +1,19 @@
-//! Key derivation subcommands.
+//! Key derivation and import subcommands.
 
 use std::path::PathBuf;
 
 use clap::ArgGroup;
-use zcash_protocol::consensus::Network;
+use zair_sdk::commands::{MnemonicLanguage, WalletExportFormat};
+use zair_sdk::common::Network;
 
 use super::constants::{
-    DEFAULT_NETWORK, DEFAULT_SEED_FILE, DEFAULT_UFVK_FILE, ZAIR_ACCOUNT_ID, ZAIR_MNEMONIC_FILE,
-    ZAIR_MNEMONIC_STDIN, ZAIR_NETWORK, ZAIR_NO_PASSPHRASE, ZAIR_SEED_FILE, ZAIR_SEED_OUT,
-    ZAIR_UFVK_OUT,
+    DEFAULT_NETWORK, DEFAULT_PROVING_KEY_FILE, DEFAULT_SEED_FILE, DEFAULT_UFVK_FILE,
+    ZAIR_ACCOUNT_ID, ZAIR_ADDRESS_INDEX, ZAIR_MNEMONIC_FILE, ZAIR_MNEMONIC_LANGUAGE,
+    ZAIR_MNEMONIC_STDIN, ZAIR_NETWORK, ZAIR_NO_PASSPHRASE, ZAIR_PASSPHRASE_ENV,
+    ZAIR_PASSPHRASE_FILE, ZAIR_PROVING_KEY_OUT, ZAIR_SEED_FILE, ZAIR_SEED_OUT, ZAIR_UFVK_OUT,
+    ZAIR_WALLET_ACCOUNT_NAME, ZAIR_WALLET_EXPORT, ZAIR_WALLET_EXPORT_FORMAT,
 };
-use super::parse_network;
+use super::{parse_mnemonic_language, parse_network, parse_wallet_export_format};
 
 /// Arguments for `zair key derive-seed`.
 #[derive(Debug, clap::Args)]
@@ -27,15 +30,32 @@ pub struct DeriveSeedArgs {
     #[arg(long, env = ZAIR_MNEMONIC_STDIN, default_value_t = false)]
     pub mnemonic_stdin: bool,
 
+    /// Wordlist of the mnemonic: english, chinese-simplified, chinese-traditional, czech, french,
+    /// italian, japanese, korean, portuguese or spanish. Detected from the words if omitted.
+    #[arg(long, env = ZAIR_MNEMONIC_LANGUAGE, value_parser = parse_mnemonic_language)]
+    pub mnemonic_language: Option<MnemonicLanguage>,
+
     /// Do not prompt for a BIP-39 passphrase (use empty passphrase).
     #[arg(long, env = ZAIR_NO_PASSPHRASE, default_value_t = false)]
     pub no_passphrase: bool,
+
+    /// Read the BIP-39 passphrase from a file instead of prompting for it.
+    #[arg(long, env = ZAIR_PASSPHRASE_FILE, conflicts_with = "no_passphrase")]
+    pub passphrase_file: Option<PathBuf>,
+
+    /// Read the BIP-39 passphrase from the named environment variable instead of prompting for it.
+    #[arg(
+        long,
+        env = ZAIR_PASSPHRASE_ENV,
+        conflicts_with_all = ["no_passphrase", "passphrase_file"]
+    )]
+    pub passphrase_env: Option<String>,
 }
 
 /// Arguments for `zair key derive-ufvk`.
 #[derive(Debug, clap::Args)]
 pub struct DeriveUfvkArgs {
-    /// Network to derive keys for (mainnet or testnet).
+    /// Network to derive keys for (mainnet, testnet or regtest).
     #[arg(
         long,
         env = ZAIR_NETWORK,
@@ -48,6 +68,11 @@ pub struct DeriveUfvkArgs {
     #[arg(long, env = ZAIR_ACCOUNT_ID, default_value_t = 0)]
     pub account: u32,
 
+    /// Diversifier index of a unified address to log with the derivation path, to compare with
+    /// an address of the wallet.
+    #[arg(long, env = ZAIR_ADDRESS_INDEX)]
+    pub address_index: Option<u32>,
+
     /// Read seed from a file (hex). Defaults to `seed.txt` if omitted.
     #[arg(long, env = ZAIR_SEED_FILE)]
     pub seed: Option<PathBuf>,
@@ -60,15 +85,119 @@ pub struct DeriveUfvkArgs {
     #[arg(long, env = ZAIR_MNEMONIC_STDIN, default_value_t = false)]
     pub mnemonic_stdin: bool,
 
+    /// Wordlist of the mnemonic: english, chinese-simplified, chinese-traditional, czech, french,
+    /// italian, japanese, korean, portuguese or spanish. Detected from the words if omitted.
+    #[arg(long, env = ZAIR_MNEMONIC_LANGUAGE, value_parser = parse_mnemonic_language)]
+    pub mnemonic_language: Option<MnemonicLanguage>,
+
     /// Do not prompt for a BIP-39 passphrase (use empty passphrase).
     #[arg(long, env = ZAIR_NO_PASSPHRASE, default_value_t = false)]
     pub no_passphrase: bool,
 
+    /// Read the BIP-39 passphrase from a file instead of prompting for it.
+    #[arg(long, env = ZAIR_PASSPHRASE_FILE, conflicts_with = "no_passphrase")]
+    pub passphrase_file: Option<PathBuf>,
+
+    /// Read the BIP-39 passphrase from the named environment variable instead of prompting for it.
+    #[arg(
+        long,
+        env = ZAIR_PASSPHRASE_ENV,
+        conflicts_with_all = ["no_passphrase", "passphrase_file"]
+    )]
+    pub passphrase_env: Option<String>,
+
     /// Output file for the derived UFVK.
     #[arg(long, env = ZAIR_UFVK_OUT, default_value = DEFAULT_UFVK_FILE)]
     pub output: PathBuf,
 }
 
+/// Arguments for `zair key export-proving-key`.
+#[derive(Debug, clap::Args)]
+pub struct ExportProvingKeyArgs {
+    /// Network to derive keys for (mainnet, testnet or regtest).
+    #[arg(
+        long,
+        env = ZAIR_NETWORK,
+        default_value = DEFAULT_NETWORK,
+        value_parser = parse_network
+    )]
+    pub network: Network,
+
+    /// ZIP-32 account index used for key derivation.
+    #[arg(long, env = ZAIR_ACCOUNT_ID, default_value_t = 0)]
+    pub account: u32,
+
+    /// Read seed from a file (hex). Defaults to `seed.txt` if omitted.
+    #[arg(long, env = ZAIR_SEED_FILE)]
+    pub seed: Option<PathBuf>,
+
+    /// Read mnemonic from a file (derives seed internally).
+    #[arg(long, env = ZAIR_MNEMONIC_FILE)]
+    pub mnemonic_file: Option<PathBuf>,
+
+    /// Read mnemonic from stdin (derives seed internally).
+    #[arg(long, env = ZAIR_MNEMONIC_STDIN, default_value_t = false)]
+    pub mnemonic_stdin: bool,
+
+    /// Wordlist of the mnemonic: english, chinese-simplified, chinese-traditional, czech, french,
+    /// italian, japanese, korean, portuguese or spanish. Detected from the words if omitted.
+    #[arg(long, env = ZAIR_MNEMONIC_LANGUAGE, value_parser = parse_mnemonic_language)]
+    pub mnemonic_language: Option<MnemonicLanguage>,
+
+    /// Do not prompt for a BIP-39 passphrase (use empty passphrase).
+    #[arg(long, env = ZAIR_NO_PASSPHRASE, default_value_t = false)]
+    pub no_passphrase: bool,
+
+    /// Read the BIP-39 passphrase from a file instead of prompting for it.
+    #[arg(long, env = ZAIR_PASSPHRASE_FILE, conflicts_with = "no_passphrase")]
+    pub passphrase_file: Option<PathBuf>,
+
+    /// Read the BIP-39 passphrase from the named environment variable instead of prompting for it.
+    #[arg(
+        long,
+        env = ZAIR_PASSPHRASE_ENV,
+        conflicts_with_all = ["no_passphrase", "passphrase_file"]
+    )]
+    pub passphrase_env: Option<String>,
+
+    /// Output file for the proving key.
+    #[arg(long, env = ZAIR_PROVING_KEY_OUT, default_value = DEFAULT_PROVING_KEY_FILE)]
+    pub output: PathBuf,
+}
+
+/// Arguments for `zair key import`.
+#[derive(Debug, clap::Args)]
+pub struct ImportArgs {
+    /// Wallet export file (Ywallet backup or Zashi export JSON).
+    #[arg(long, env = ZAIR_WALLET_EXPORT)]
+    pub from: PathBuf,
+
+    /// Export format: `ywallet` or `zashi`. Detected from the file if omitted.
+    #[arg(long, env = ZAIR_WALLET_EXPORT_FORMAT, value_parser = parse_wallet_export_format)]
+    pub format: Option<WalletExportFormat>,
+
+    /// Account to import from a Ywallet backup with several accounts.
+    #[arg(long, env = ZAIR_WALLET_ACCOUNT_NAME)]
+    pub account_name: Option<String>,
+
+    /// Network to derive keys for (mainnet, testnet or regtest).
+    #[arg(
+        long,
+        env = ZAIR_NETWORK,
+        default_value = DEFAULT_NETWORK,
+        value_parser = parse_network
+    )]
+    pub network: Network,
+
+    /// Output file for the seed (hex).
+    #[arg(long, env = ZAIR_SEED_OUT, default_value = DEFAULT_SEED_FILE)]
+    pub seed_output: PathBuf,
+
+    /// Output file for the UFVK.
+    #[arg(long, env = ZAIR_UFVK_OUT, default_value = DEFAULT_UFVK_FILE)]
+    pub ufvk_output: PathBuf,
+}
+
 /// Key command group.
 #[derive(Debug, clap::Subcommand)]
 pub enum KeyCommands {
@@ -93,4 +222,21 @@ pub enum KeyCommands {
         #[command(flatten)]
         args: DeriveUfvkArgs,
     },
+
+    /// Export a key that proves claims of an account but cannot sign them, for a proving farm.
+    #[command(group(
+        ArgGroup::new("key_input")
+            .args(["seed", "mnemonic_file", "mnemonic_stdin"])
+            .multiple(false)
+    ))]
+    ExportProvingKey {
+        #[command(flatten)]
+        args: ExportProvingKeyArgs,
+    },
+
+    /// Import the seed and UFVK from a Ywallet backup or Zashi export.
+    Import {
+        #[command(flatten)]
+        args: ImportArgs,
+    },
 }
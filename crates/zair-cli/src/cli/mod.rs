@@ -3,25 +3,57 @@
 mod claim;
 mod config;
 pub mod constants;
+mod doctor;
 mod key;
+mod prove;
 #[cfg(feature = "prove")]
 mod setup;
+mod snapshot;
+mod stats;
 mod verify;
+mod watch;
 
-use clap::Parser;
+use std::path::Path;
+
+use clap::{ArgMatches, Parser};
 use eyre::{Result, ensure, eyre};
+use zair_core::base::{Pool, TargetId};
 use zair_core::schema::config::ValueCommitmentScheme;
-use zair_sdk::commands::{GapTreeMode, OrchardParamsMode};
-use zair_sdk::common::{CommonConfig, PoolSelection};
-use zcash_protocol::consensus::Network;
-
-pub use self::claim::ClaimCommands;
-pub use self::config::ConfigCommands;
-use self::constants::{DEFAULT_NETWORK, ZAIR_LIGHTWALLETD_URL, ZAIR_NETWORK, ZAIR_SNAPSHOT_HEIGHT};
-pub use self::key::KeyCommands;
+use zair_sdk::commands::{
+    GapTreeMode, HeightChain, MnemonicLanguage, OrchardParamsMode, SnapshotExportFormat,
+    WalletExportFormat,
+};
+use zair_sdk::common::{
+    Backend, CommonConfig, CustomNetwork, Network, PoolSelection, parse_branch_id,
+};
+use zair_sdk::error::ZairResult;
+use zair_sdk::stdio::STDIO_PATH;
+
+#[cfg(feature = "prove")]
+pub use self::claim::ProofRngArgs;
+pub use self::claim::{ClaimCommands, ClaimMergeArgs, ClaimPrepareArgs, ClaimQrArgs};
+pub use self::config::{ConfigAuditArgs, ConfigBuildArgs, ConfigCommands};
+#[cfg(feature = "metrics")]
+use self::constants::ZAIR_METRICS_ADDR;
+use self::constants::{
+    DEFAULT_BACKEND, DEFAULT_BENCH_SIZES, DEFAULT_NETWORK, ZAIR_ARTIFACTS_MANIFEST_URL,
+    ZAIR_AUDIT_TRAIL, ZAIR_BACKEND, ZAIR_BENCH_SIZES, ZAIR_FETCH_DIR, ZAIR_LIGHTWALLETD_URL,
+    ZAIR_LOG_SENSITIVE, ZAIR_NETWORK, ZAIR_NON_INTERACTIVE, ZAIR_NUPARAMS, ZAIR_RELEASE_KEY,
+    ZAIR_RELEASE_MANIFEST_URL, ZAIR_SNAPSHOT_HEIGHT, ZAIR_UPDATE_CHECK,
+};
+pub use self::doctor::DoctorArgs;
+pub use self::key::{DeriveSeedArgs, DeriveUfvkArgs, ExportProvingKeyArgs, KeyCommands};
+#[cfg(feature = "prove")]
+pub use self::prove::ProveWorkerArgs;
+pub use self::prove::{ProveCollectArgs, ProveCommands, ProveSubmitArgs};
 #[cfg(feature = "prove")]
 pub use self::setup::SetupCommands;
-pub use self::verify::VerifyCommands;
+pub use self::snapshot::SnapshotCommands;
+pub use self::stats::StatsArgs;
+pub use self::verify::{
+    VerifyAttestationArgs, VerifyCommands, VerifyProofArgs, VerifyRunArgs, VerifyTotalArgs,
+};
+pub use self::watch::WatchArgs;
 
 /// Command-line interface definition.
 #[derive(Debug, Parser)]
@@ -31,6 +63,22 @@ pub struct Cli {
     /// CLI top-level command group.
     #[command(subcommand)]
     pub command: Commands,
+    /// Serve Prometheus metrics on this address (e.g. `127.0.0.1:9000`) while the command runs.
+    #[cfg(feature = "metrics")]
+    #[arg(long, global = true, env = ZAIR_METRICS_ADDR)]
+    pub metrics_addr: Option<std::net::SocketAddr>,
+    /// Never prompt: fail with an error naming the missing flag when a mnemonic or passphrase is
+    /// not given by file or stdin.
+    #[arg(long, global = true, env = ZAIR_NON_INTERACTIVE, default_value_t = false)]
+    pub non_interactive: bool,
+    /// Log nullifiers, keys, seeds and other 32-byte hex values in full instead of redacting
+    /// them. For debugging only.
+    #[arg(long, global = true, env = ZAIR_LOG_SENSITIVE, default_value_t = false)]
+    pub log_sensitive: bool,
+    /// Append the command line, exit status and SHA-256 of the files read and written to this
+    /// hash-chained audit trail.
+    #[arg(long, global = true, env = ZAIR_AUDIT_TRAIL, value_name = "AUDIT_TRAIL_FILE")]
+    pub audit_trail: Option<std::path::PathBuf>,
 }
 
 /// Top-level command groups.
@@ -55,6 +103,12 @@ pub enum Commands {
         #[command(subcommand)]
         command: ConfigCommands,
     },
+    /// Snapshot auditing utilities.
+    Snapshot {
+        /// Snapshot subcommands.
+        #[command(subcommand)]
+        command: SnapshotCommands,
+    },
     /// Claim pipeline commands.
     Claim {
         /// Claim subcommands.
@@ -67,12 +121,81 @@ pub enum Commands {
         #[command(subcommand)]
         command: VerifyCommands,
     },
+    /// Proving farm commands: submit claims to a proving queue or run a worker.
+    Prove {
+        /// Prove subcommands.
+        #[command(subcommand)]
+        command: ProveCommands,
+    },
+    /// Watch new blocks for spends of claimed notes. Exits with an error once a claimed note is
+    /// spent.
+    Watch {
+        /// Watch arguments.
+        #[command(flatten)]
+        args: WatchArgs,
+    },
+    /// Check the indexer, setup files, disk space, clock and configuration before a long run.
+    Doctor {
+        /// Doctor arguments.
+        #[command(flatten)]
+        args: DoctorArgs,
+    },
+    /// Summarize a directory of signed submissions: claims and proof pass rates per pool, and the
+    /// distribution of proven claimed values.
+    Stats {
+        /// Stats arguments.
+        #[command(flatten)]
+        args: StatsArgs,
+    },
+    /// Download and verify every file listed in a published `artifacts.json` manifest.
+    Fetch {
+        /// URL of the artifact manifest; files are fetched relative to it.
+        #[arg(long, env = ZAIR_ARTIFACTS_MANIFEST_URL, value_name = "URL")]
+        manifest: String,
+        /// Directory to store every file in. Defaults to the working directory for the
+        /// configuration and the standard directories for the other files.
+        #[arg(long, env = ZAIR_FETCH_DIR)]
+        out_dir: Option<std::path::PathBuf>,
+    },
+    /// Replace this binary with the latest signed release for this platform.
+    SelfUpdate {
+        /// URL of the release manifest; its signature is fetched from `<URL>.sig`.
+        #[arg(long, env = ZAIR_RELEASE_MANIFEST_URL, value_name = "URL")]
+        manifest: String,
+        /// Hex Ed25519 public key to verify the release manifest with instead of the key embedded
+        /// in this build. Only for builds without an embedded key or a self-hosted release
+        /// channel; a warning is logged whenever it is used.
+        #[arg(long, env = ZAIR_RELEASE_KEY, value_parser = parse_release_key)]
+        release_key: Option<[u8; 32]>,
+        /// Only report whether a newer release exists.
+        #[arg(long, env = ZAIR_UPDATE_CHECK, default_value_t = false)]
+        check: bool,
+    },
+    /// Print the standard data and cache directories used for default file locations.
+    Paths,
+    /// Print the version, git commit, lockfile hash and build flags of this binary.
+    Version {
+        /// Print the build metadata as JSON, as recorded in the `generated_by` field of artifacts.
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Time gap tree construction on this machine to estimate the runtime of a real snapshot.
+    Bench {
+        /// Snapshot sizes to time, in chain nullifiers per pool.
+        #[arg(
+            long,
+            env = ZAIR_BENCH_SIZES,
+            value_delimiter = ',',
+            default_value = DEFAULT_BENCH_SIZES
+        )]
+        sizes: Vec<usize>,
+    },
 }
 
 /// Common arguments for `config build`.
 #[derive(Debug, clap::Args)]
 pub struct BuildConfigArgs {
-    /// Network to use (mainnet or testnet).
+    /// Network to use (mainnet, testnet or regtest).
     #[arg(
         long,
         env = ZAIR_NETWORK,
@@ -80,21 +203,58 @@ pub struct BuildConfigArgs {
         value_parser = parse_network
     )]
     pub network: Network,
+    /// Custom network upgrade activations of a private devnet, as `<branch id>:<height>` like
+    /// zcashd's `-nuparams`. Requires `--network regtest`.
+    #[arg(
+        long,
+        env = ZAIR_NUPARAMS,
+        value_delimiter = ',',
+        value_parser = parse_nuparams
+    )]
+    pub nuparams: Vec<(u32, u32)>,
     /// Snapshot block height (inclusive).
     #[arg(long, env = ZAIR_SNAPSHOT_HEIGHT)]
     pub height: u64,
     /// Optional lightwalletd gRPC endpoint URL override.
     #[arg(long, env = ZAIR_LIGHTWALLETD_URL)]
     pub lightwalletd: Option<String>,
+    /// Indexer serving `--lightwalletd` (lightwalletd or zaino), for the requests where they
+    /// differ.
+    #[arg(
+        long,
+        env = ZAIR_BACKEND,
+        default_value = DEFAULT_BACKEND,
+        value_parser = parse_backend
+    )]
+    pub backend: Backend,
 }
 
-impl From<BuildConfigArgs> for CommonConfig {
-    fn from(args: BuildConfigArgs) -> Self {
-        Self {
-            network: args.network,
+impl BuildConfigArgs {
+    /// The selected network, with the custom activation heights of `--nuparams` if given.
+    pub fn network(&self) -> Result<Network> {
+        if self.nuparams.is_empty() {
+            return Ok(self.network);
+        }
+        ensure!(
+            self.network == Network::RegtestNetwork,
+            "--nuparams requires --network regtest"
+        );
+        Ok(Network::Custom(CustomNetwork::new(
+            self.nuparams.iter().copied(),
+        )?))
+    }
+}
+
+impl TryFrom<BuildConfigArgs> for CommonConfig {
+    type Error = eyre::Report;
+
+    fn try_from(args: BuildConfigArgs) -> Result<Self> {
+        Ok(Self {
+            network: args.network()?,
             snapshot_height: args.height,
             lightwalletd_url: args.lightwalletd,
-        }
+            backend: args.backend,
+        })
     }
 }
 
@@ -102,12 +262,43 @@ pub fn parse_network(s: &str) -> Result<Network> {
     match s {
         "mainnet" => Ok(Network::MainNetwork),
         "testnet" => Ok(Network::TestNetwork),
+        "regtest" => Ok(Network::RegtestNetwork),
+        other => Err(eyre!(
+            "Invalid network: {other}. Expected 'mainnet', 'testnet' or 'regtest'."
+        )),
+    }
+}
+
+pub fn parse_backend(s: &str) -> Result<Backend> {
+    match s {
+        "lightwalletd" => Ok(Backend::Lightwalletd),
+        "zaino" => Ok(Backend::Zaino),
         other => Err(eyre!(
-            "Invalid network: {other}. Expected 'mainnet' or 'testnet'."
+            "Invalid backend: {other}. Expected 'lightwalletd' or 'zaino'."
         )),
     }
 }
 
+pub fn parse_height_chain(s: &str) -> Result<HeightChain> {
+    match s {
+        "zcash" => Ok(HeightChain::Zcash),
+        "namada" => Ok(HeightChain::Namada),
+        other => Err(eyre!(
+            "Invalid height chain: {other}. Expected 'zcash' or 'namada'."
+        )),
+    }
+}
+
+pub fn parse_nuparams(s: &str) -> Result<(u32, u32)> {
+    let (branch_id, height) = s
+        .split_once(':')
+        .ok_or_else(|| eyre!("Invalid network upgrade: {s}. Expected '<branch id>:<height>'."))?;
+    let height = height
+        .parse()
+        .map_err(|e| eyre!("Invalid activation height in {s}: {e}"))?;
+    Ok((parse_branch_id(branch_id)?, height))
+}
+
 pub fn parse_pool_selection(s: &str) -> Result<PoolSelection> {
     match s {
         "sapling" => Ok(PoolSelection::Sapling),
@@ -119,22 +310,25 @@ pub fn parse_pool_selection(s: &str) -> Result<PoolSelection> {
     }
 }
 
-pub fn parse_sapling_target_id(s: &str) -> Result<String> {
-    ensure!(s.len() == 8, "Sapling target_id must be exactly 8 bytes");
-    Ok(s.to_string())
+pub fn parse_sapling_target_id(s: &str) -> Result<TargetId> {
+    Ok(TargetId::new(Pool::Sapling, s)?)
 }
 
-pub fn parse_orchard_target_id(s: &str) -> Result<String> {
-    ensure!(s.len() <= 32, "Orchard target_id must be at most 32 bytes");
-    Ok(s.to_string())
+pub fn parse_orchard_target_id(s: &str) -> Result<TargetId> {
+    Ok(TargetId::new(Pool::Orchard, s)?)
+}
+
+pub fn parse_transparent_target_id(s: &str) -> Result<TargetId> {
+    Ok(s.parse()?)
 }
 
 pub fn parse_value_commitment_scheme(s: &str) -> Result<ValueCommitmentScheme> {
     match s {
         "native" => Ok(ValueCommitmentScheme::Native),
         "sha256" => Ok(ValueCommitmentScheme::Sha256),
+        "poseidon" => Ok(ValueCommitmentScheme::Poseidon),
         other => Err(eyre!(
-            "Invalid value commitment scheme: {other}. Expected 'native' or 'sha256'."
+            "Invalid value commitment scheme: {other}. Expected 'native', 'sha256' or 'poseidon'."
         )),
     }
 }
@@ -160,6 +354,112 @@ pub fn parse_orchard_params_mode(s: &str) -> Result<OrchardParamsMode> {
     }
 }
 
+pub fn parse_snapshot_export_format(s: &str) -> Result<SnapshotExportFormat> {
+    match s {
+        "csv" => Ok(SnapshotExportFormat::Csv),
+        #[cfg(feature = "parquet")]
+        "parquet" => Ok(SnapshotExportFormat::Parquet),
+        #[cfg(not(feature = "parquet"))]
+        "parquet" => Err(eyre!("Parquet export requires the `parquet` feature.")),
+        other => Err(eyre!(
+            "Invalid snapshot export format: {other}. Expected 'csv' or 'parquet'."
+        )),
+    }
+}
+
+pub fn parse_wallet_export_format(s: &str) -> Result<WalletExportFormat> {
+    match s {
+        "ywallet" => Ok(WalletExportFormat::Ywallet),
+        "zashi" => Ok(WalletExportFormat::Zashi),
+        other => Err(eyre!(
+            "Invalid wallet export format: {other}. Expected 'ywallet' or 'zashi'."
+        )),
+    }
+}
+
+pub fn parse_mnemonic_language(s: &str) -> Result<MnemonicLanguage> {
+    match s {
+        "english" => Ok(MnemonicLanguage::English),
+        "chinese-simplified" => Ok(MnemonicLanguage::SimplifiedChinese),
+        "chinese-traditional" => Ok(MnemonicLanguage::TraditionalChinese),
+        "czech" => Ok(MnemonicLanguage::Czech),
+        "french" => Ok(MnemonicLanguage::French),
+        "italian" => Ok(MnemonicLanguage::Italian),
+        "japanese" => Ok(MnemonicLanguage::Japanese),
+        "korean" => Ok(MnemonicLanguage::Korean),
+        "portuguese" => Ok(MnemonicLanguage::Portuguese),
+        "spanish" => Ok(MnemonicLanguage::Spanish),
+        other => Err(eyre!(
+            "Invalid mnemonic language: {other}. Expected 'english', 'chinese-simplified', \
+             'chinese-traditional', 'czech', 'french', 'italian', 'japanese', 'korean', \
+             'portuguese' or 'spanish'."
+        )),
+    }
+}
+
+pub fn parse_countersigner(s: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(s.trim()).map_err(|e| eyre!("Invalid countersigner key: {e}"))?;
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| eyre!("Countersigner key must be 32 bytes, got {}", bytes.len()))
+}
+
+/// Check that the command in `matches` takes at most one `-` input and one `-` output.
+///
+/// `*_out`, `*_output` and `export_*` arguments are outputs, every other argument an input.
+///
+/// # Errors
+/// Returns a configuration error naming the arguments that share stdin or stdout.
+pub fn ensure_single_stdio(matches: &ArgMatches) -> ZairResult<()> {
+    let mut matches = matches;
+    while let Some((_, subcommand)) = matches.subcommand() {
+        matches = subcommand;
+    }
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for id in matches.ids() {
+        let name = id.as_str();
+        let Some(values) = matches.try_get_raw(name).ok().flatten() else {
+            continue;
+        };
+        let is_output = name
+            .split('_')
+            .any(|word| matches!(word, "out" | "output" | "export"));
+        for _ in values.filter(|value| *value == STDIO_PATH) {
+            if is_output {
+                outputs.push((name, Path::new(STDIO_PATH)));
+            } else {
+                inputs.push((name, Path::new(STDIO_PATH)));
+            }
+        }
+    }
+    zair_sdk::stdio::ensure_single_stdio(inputs, outputs)
+}
+
+pub fn parse_release_key(s: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(s.trim()).map_err(|e| eyre!("Invalid release key: {e}"))?;
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| eyre!("Release key must be 32 bytes, got {}", bytes.len()))
+}
+
+pub fn parse_organizer_key(s: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(s.trim()).map_err(|e| eyre!("Invalid organizer key: {e}"))?;
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| eyre!("Organizer key must be 32 bytes, got {}", bytes.len()))
+}
+
+pub fn parse_claim_id(s: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(s.trim()).map_err(|e| eyre!("Invalid claim ID: {e}"))?;
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| eyre!("Claim ID must be 32 bytes, got {}", bytes.len()))
+}
+
+#[cfg(feature = "prove")]
+pub fn parse_sha256_digest(s: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(s.trim()).map_err(|e| eyre!("Invalid SHA-256 digest: {e}"))?;
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| eyre!("SHA-256 digest must be 32 bytes, got {}", bytes.len()))
+}
+
 #[cfg(test)]
 mod tests {
     use clap::Parser as _;
@@ -172,9 +472,21 @@ mod tests {
         assert_eq!(network, Network::MainNetwork);
         let network = parse_network("testnet").expect("Failed to parse testnet");
         assert_eq!(network, Network::TestNetwork);
+        let network = parse_network("regtest").expect("Failed to parse regtest");
+        assert_eq!(network, Network::RegtestNetwork);
         assert!(parse_network("invalid_network").is_err());
     }
 
+    #[test]
+    fn nuparams_parse() {
+        assert_eq!(
+            parse_nuparams("c2d6d0b4:100").expect("Failed to parse nuparams"),
+            (0xc2d6_d0b4, 100)
+        );
+        assert!(parse_nuparams("c2d6d0b4").is_err());
+        assert!(parse_nuparams("nu5:100").is_err());
+    }
+
     #[test]
     fn pool_selection_parse() {
         assert!(matches!(
@@ -222,6 +534,59 @@ mod tests {
         assert!(parse_orchard_params_mode("invalid").is_err());
     }
 
+    #[test]
+    fn parse_paths_command() {
+        let cli = Cli::try_parse_from(["zair", "paths"]).expect("paths should parse");
+        assert!(matches!(cli.command, Commands::Paths));
+    }
+
+    #[test]
+    fn parse_version_command() {
+        let cli = Cli::try_parse_from(["zair", "version", "--json"]).expect("version should parse");
+        assert!(matches!(cli.command, Commands::Version { json: true }));
+    }
+
+    #[cfg(feature = "prove")]
+    #[test]
+    fn one_stdio_output_per_command() {
+        let parse = |args: &[&str]| {
+            <Cli as clap::CommandFactory>::command()
+                .try_get_matches_from(args)
+                .expect("command should parse")
+        };
+        let collect = [
+            "zair",
+            "prove",
+            "collect",
+            "--queue-dir",
+            "queue",
+            "--job",
+            "job",
+        ];
+
+        let one = parse(&[&collect[..], &["--proofs-out", "-"][..]].concat());
+        ensure_single_stdio(&one).expect("one - output");
+        let two = parse(
+            &[
+                &collect[..],
+                &["--proofs-out", "-", "--secrets-out", "-"][..],
+            ]
+            .concat(),
+        );
+        let err = ensure_single_stdio(&two).expect_err("two - outputs");
+        assert!(err.to_string().contains("proofs_out, secrets_out"));
+        let inputs = parse(&[&collect[..], &["--claims-in", "-", "--config", "-"][..]].concat());
+        assert!(ensure_single_stdio(&inputs).is_err());
+    }
+
+    #[test]
+    fn sha256_digest_parse() {
+        let digest = parse_sha256_digest(&"ab".repeat(32)).expect("digest should parse");
+        assert_eq!(digest, [0xab; 32]);
+        assert!(parse_sha256_digest("abcd").is_err());
+        assert!(parse_sha256_digest(&"zz".repeat(32)).is_err());
+    }
+
     #[cfg(feature = "prove")]
     #[test]
     fn parse_claim_run_command_requires_message_input() {
@@ -250,6 +615,45 @@ mod tests {
         assert!(cli.is_ok());
     }
 
+    #[test]
+    fn parse_prove_submit_requires_queue_dir() {
+        assert!(Cli::try_parse_from(["zair", "prove", "submit"]).is_err());
+        let cli = Cli::try_parse_from(["zair", "prove", "submit", "--queue-dir", "queue"])
+            .expect("prove submit should parse");
+        assert!(matches!(
+            cli.command,
+            Commands::Prove {
+                command: ProveCommands::Submit { .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_snapshot_reconcile_requires_single_secondary_source() {
+        let base = ["zair", "snapshot", "reconcile", "--height", "3000000"];
+        assert!(Cli::try_parse_from(base).is_err());
+
+        let cli = Cli::try_parse_from(
+            base.into_iter()
+                .chain(["--against-lightwalletd", "https://zec.rocks:443"]),
+        );
+        assert!(cli.is_ok());
+
+        let cli = Cli::try_parse_from(
+            base.into_iter()
+                .chain(["--against-snapshot-orchard", "snapshot-orchard.bin"]),
+        );
+        assert!(cli.is_ok());
+
+        let cli = Cli::try_parse_from(base.into_iter().chain([
+            "--against-lightwalletd",
+            "https://zec.rocks:443",
+            "--against-snapshot-sapling",
+            "snapshot-sapling.bin",
+        ]));
+        assert!(cli.is_err());
+    }
+
     #[test]
     fn parse_verify_run_command_requires_message_input() {
         let cli = Cli::try_parse_from(["zair", "verify", "run"]);
@@ -259,4 +663,43 @@ mod tests {
             Cli::try_parse_from(["zair", "verify", "run", "--messages", "claim-messages.json"]);
         assert!(cli.is_ok());
     }
+
+    #[test]
+    fn parse_verify_run_height_source() {
+        let cli = Cli::try_parse_from([
+            "zair",
+            "verify",
+            "run",
+            "--messages",
+            "claim-messages.json",
+            "--height-from",
+            "namada",
+            "--height-url",
+            "http://127.0.0.1:26657",
+        ])
+        .expect("height source should parse");
+        let Commands::Verify {
+            command: VerifyCommands::Run { args },
+        } = cli.command
+        else {
+            panic!("expected verify run");
+        };
+        assert_eq!(args.height_from, Some(HeightChain::Namada));
+
+        let cli = Cli::try_parse_from([
+            "zair",
+            "verify",
+            "run",
+            "--messages",
+            "claim-messages.json",
+            "--height-from",
+            "zcash",
+            "--current-height",
+            "100",
+        ]);
+        assert!(
+            cli.is_err(),
+            "a fixed height conflicts with a height source"
+        );
+    }
 }
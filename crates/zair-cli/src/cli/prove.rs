@@ -0,0 +1,159 @@
+//! Proving farm subcommands.
+
+use std::path::PathBuf;
+
+use zair_sdk::commands::OrchardParamsMode;
+use zair_sdk::paths::{default_input_path, params_dir};
+
+use super::constants::{
+    DEFAULT_CLAIMS_FILE, DEFAULT_CONFIG_FILE, DEFAULT_ORCHARD_PARAMS_FILE,
+    DEFAULT_ORCHARD_PARAMS_MODE, DEFAULT_PROOFS_FILE, DEFAULT_PROVING_KEY_FILE,
+    DEFAULT_SAPLING_VK_FILE, DEFAULT_SECRETS_FILE, ZAIR_CLAIMS_IN, ZAIR_CONFIG_FILE,
+    ZAIR_ORCHARD_PARAMS_FILE, ZAIR_ORCHARD_PARAMS_MODE, ZAIR_PROOFS_OUT, ZAIR_PROVE_JOB,
+    ZAIR_PROVE_QUEUE_DIR, ZAIR_PROVING_KEY_FILE, ZAIR_SAPLING_VK_FILE, ZAIR_SECRETS_OUT,
+};
+#[cfg(feature = "prove")]
+use super::constants::{
+    DEFAULT_PROVE_POLL_INTERVAL, DEFAULT_SAPLING_PK_FILE, ZAIR_PROVE_ONCE,
+    ZAIR_PROVE_POLL_INTERVAL, ZAIR_SAPLING_PK_FILE,
+};
+use super::parse_orchard_params_mode;
+
+/// Arguments for `zair prove submit`.
+#[derive(Debug, clap::Args)]
+pub struct ProveSubmitArgs {
+    /// Queue directory shared with the proving workers.
+    #[arg(long, env = ZAIR_PROVE_QUEUE_DIR)]
+    pub queue_dir: PathBuf,
+    /// Prepared claims file generated by `claim prepare`.
+    #[arg(long, env = ZAIR_CLAIMS_IN, default_value = DEFAULT_CLAIMS_FILE)]
+    pub claims_in: PathBuf,
+    /// Proving key of the account, written by `key export-proving-key`.
+    #[arg(long, env = ZAIR_PROVING_KEY_FILE, default_value = DEFAULT_PROVING_KEY_FILE)]
+    pub proving_key: PathBuf,
+}
+
+/// Arguments for `zair prove collect`.
+#[derive(Debug, clap::Args)]
+pub struct ProveCollectArgs {
+    /// Queue directory the job was submitted to.
+    #[arg(long, env = ZAIR_PROVE_QUEUE_DIR)]
+    pub queue_dir: PathBuf,
+    /// Job identifier printed by `prove submit`.
+    #[arg(long, env = ZAIR_PROVE_JOB)]
+    pub job: String,
+    /// Prepared claims file submitted with the job.
+    #[arg(long, env = ZAIR_CLAIMS_IN, default_value = DEFAULT_CLAIMS_FILE)]
+    pub claims_in: PathBuf,
+    /// Airdrop configuration file the proofs are verified against.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Path to the Sapling verifying key file.
+    #[arg(
+        long = "sapling-vk",
+        env = ZAIR_SAPLING_VK_FILE,
+        value_name = "SAPLING_VK_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_SAPLING_VK_FILE)
+    )]
+    pub sapling_vk: PathBuf,
+    /// Path to the Orchard Halo2 params file.
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_FILE,
+        value_name = "ORCHARD_PARAMS_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE)
+    )]
+    pub orchard_params: PathBuf,
+    /// Orchard params handling mode: `require` (fail if missing) or `auto` (generate and persist).
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_MODE,
+        default_value = DEFAULT_ORCHARD_PARAMS_MODE,
+        value_parser = parse_orchard_params_mode
+    )]
+    pub orchard_params_mode: OrchardParamsMode,
+    /// Output file for the claim proofs of the job.
+    #[arg(long, env = ZAIR_PROOFS_OUT, default_value = DEFAULT_PROOFS_FILE)]
+    pub proofs_out: PathBuf,
+    /// Output file for the claim secrets of the job.
+    #[arg(long, env = ZAIR_SECRETS_OUT, default_value = DEFAULT_SECRETS_FILE)]
+    pub secrets_out: PathBuf,
+}
+
+/// Arguments for `zair prove worker`.
+#[cfg(feature = "prove")]
+#[derive(Debug, clap::Args)]
+pub struct ProveWorkerArgs {
+    /// Queue directory to take jobs from.
+    #[arg(long, env = ZAIR_PROVE_QUEUE_DIR)]
+    pub queue_dir: PathBuf,
+    /// Airdrop configuration file the jobs are proven against.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Path to Sapling proving key file.
+    #[arg(
+        long = "sapling-pk",
+        env = ZAIR_SAPLING_PK_FILE,
+        value_name = "SAPLING_PK_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_SAPLING_PK_FILE)
+    )]
+    pub sapling_pk: PathBuf,
+    /// Path to the Orchard Halo2 params file.
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_FILE,
+        value_name = "ORCHARD_PARAMS_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE)
+    )]
+    pub orchard_params: PathBuf,
+    /// Orchard params handling mode: `require` (fail if missing) or `auto` (generate and persist).
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_MODE,
+        default_value = DEFAULT_ORCHARD_PARAMS_MODE,
+        value_parser = parse_orchard_params_mode
+    )]
+    pub orchard_params_mode: OrchardParamsMode,
+    /// Seconds between checks for new jobs.
+    #[arg(
+        long,
+        env = ZAIR_PROVE_POLL_INTERVAL,
+        value_name = "SECS",
+        default_value = DEFAULT_PROVE_POLL_INTERVAL
+    )]
+    pub poll_interval: u64,
+    /// Prove the pending jobs once and exit instead of waiting for new ones.
+    #[arg(long, env = ZAIR_PROVE_ONCE, default_value_t = false)]
+    pub once: bool,
+}
+
+/// Proving farm command group.
+#[derive(Debug, clap::Subcommand)]
+pub enum ProveCommands {
+    /// Submit prepared claims and a proving key to a proving queue. Prints the job identifier.
+    Submit {
+        #[command(flatten)]
+        args: ProveSubmitArgs,
+    },
+    /// Verify the proofs of a finished job and copy them with their secrets for `claim sign`.
+    Collect {
+        #[command(flatten)]
+        args: ProveCollectArgs,
+    },
+    /// Prove the jobs of a proving queue.
+    #[cfg(feature = "prove")]
+    Worker {
+        #[command(flatten)]
+        args: ProveWorkerArgs,
+    },
+}
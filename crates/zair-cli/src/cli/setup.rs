@@ -3,12 +3,14 @@
 use std::path::PathBuf;
 
 use zair_core::schema::config::ValueCommitmentScheme;
+use zair_sdk::paths::{default_output_path, params_dir};
 
 use super::constants::{
     DEFAULT_ORCHARD_PARAMS_FILE, DEFAULT_SAPLING_PK_FILE, DEFAULT_SAPLING_VK_FILE, DEFAULT_SCHEME,
+    ZAIR_SETUP_BIND_CONFIG, ZAIR_SETUP_FETCH_DIR, ZAIR_SETUP_FETCH_SHA256, ZAIR_SETUP_FETCH_URL,
     ZAIR_SETUP_ORCHARD_PARAMS_OUT, ZAIR_SETUP_PK_OUT, ZAIR_SETUP_SCHEME, ZAIR_SETUP_VK_OUT,
 };
-use super::parse_value_commitment_scheme;
+use super::{parse_sha256_digest, parse_value_commitment_scheme};
 
 /// Setup command group.
 #[derive(Debug, clap::Subcommand)]
@@ -25,12 +27,16 @@ pub enum SetupCommands {
         scheme: ValueCommitmentScheme,
 
         /// Output file for proving key.
-        #[arg(long, env = ZAIR_SETUP_PK_OUT, default_value = DEFAULT_SAPLING_PK_FILE)]
+        #[arg(long, env = ZAIR_SETUP_PK_OUT, default_value_os_t = default_output_path(params_dir, DEFAULT_SAPLING_PK_FILE))]
         pk_out: PathBuf,
 
         /// Output file for verifying key.
-        #[arg(long, env = ZAIR_SETUP_VK_OUT, default_value = DEFAULT_SAPLING_VK_FILE)]
+        #[arg(long, env = ZAIR_SETUP_VK_OUT, default_value_os_t = default_output_path(params_dir, DEFAULT_SAPLING_VK_FILE))]
         vk_out: PathBuf,
+
+        /// Existing airdrop configuration to bind the generated verifying key into.
+        #[arg(long, env = ZAIR_SETUP_BIND_CONFIG, value_name = "CONFIG_FILE")]
+        bind_config: Option<PathBuf>,
     },
     /// Generate Orchard Halo2 params for proving and verification.
     Orchard {
@@ -47,8 +53,41 @@ pub enum SetupCommands {
         #[arg(
             long,
             env = ZAIR_SETUP_ORCHARD_PARAMS_OUT,
-            default_value = DEFAULT_ORCHARD_PARAMS_FILE
+            default_value_os_t = default_output_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE)
         )]
         params_out: PathBuf,
+
+        /// Existing airdrop configuration to bind the generated params into.
+        #[arg(long, env = ZAIR_SETUP_BIND_CONFIG, value_name = "CONFIG_FILE")]
+        bind_config: Option<PathBuf>,
+    },
+    /// Download published setup parameters instead of generating them locally.
+    Fetch {
+        /// URL of the published parameter file.
+        #[arg(long, env = ZAIR_SETUP_FETCH_URL)]
+        url: String,
+
+        /// Expected SHA-256 digest of the file (hex).
+        #[arg(long, env = ZAIR_SETUP_FETCH_SHA256, value_parser = parse_sha256_digest)]
+        sha256: [u8; 32],
+
+        /// Directory to place the file in. Defaults to the platform data directory.
+        #[arg(long, env = ZAIR_SETUP_FETCH_DIR)]
+        out_dir: Option<PathBuf>,
+
+        /// File name to store the download as. Defaults to the last URL path segment.
+        #[arg(long)]
+        file_name: Option<String>,
+    },
+    /// Print the constraint count of every gadget of the Sapling claim circuit.
+    Report {
+        /// Sapling circuit scheme to report on.
+        #[arg(
+            long,
+            env = ZAIR_SETUP_SCHEME,
+            default_value = DEFAULT_SCHEME,
+            value_parser = parse_value_commitment_scheme
+        )]
+        scheme: ValueCommitmentScheme,
     },
 }
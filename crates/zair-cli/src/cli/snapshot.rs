@@ -0,0 +1,270 @@
+//! Snapshot subcommands.
+
+use std::path::PathBuf;
+
+use zair_sdk::commands::{SnapshotExportFormat, SnapshotSource};
+use zair_sdk::common::{Backend, CommonConfig, Network, PoolSelection};
+use zair_sdk::paths::{default_input_path, snapshots_dir};
+
+use super::constants::{
+    DEFAULT_BACKEND, DEFAULT_CONFIG_FILE, DEFAULT_EXPORT_FILE_STEM, DEFAULT_EXPORT_FORMAT,
+    DEFAULT_IPFS_API_URL, DEFAULT_NETWORK, DEFAULT_POOL, DEFAULT_RECONCILE_REPORT_FILE,
+    DEFAULT_SNAPSHOT_ORCHARD_FILE, DEFAULT_SNAPSHOT_SAPLING_FILE, ZAIR_AGAINST_LIGHTWALLETD_URL,
+    ZAIR_AGAINST_SNAPSHOT_ORCHARD, ZAIR_AGAINST_SNAPSHOT_SAPLING, ZAIR_BACKEND, ZAIR_CONFIG_FILE,
+    ZAIR_EXPORT_FORMAT, ZAIR_EXPORT_OUT, ZAIR_GAP_TREE_ORCHARD_FILE, ZAIR_GAP_TREE_SAPLING_FILE,
+    ZAIR_IPFS_API_URL, ZAIR_LIGHTWALLETD_URL, ZAIR_NETWORK, ZAIR_ORIGIN_HEIGHTS, ZAIR_POOL,
+    ZAIR_RECONCILE_REPORT_OUT, ZAIR_SNAPSHOT_HEIGHT, ZAIR_SNAPSHOT_ORCHARD_FILE,
+    ZAIR_SNAPSHOT_SAPLING_FILE,
+};
+#[cfg(feature = "serve")]
+use super::constants::{DEFAULT_SNAPSHOT_SERVE_ADDR, ZAIR_SNAPSHOT_SERVE_ADDR};
+use super::{
+    BuildConfigArgs, parse_backend, parse_network, parse_pool_selection,
+    parse_snapshot_export_format,
+};
+
+/// Arguments for `snapshot reconcile`.
+#[derive(Debug, clap::Args)]
+pub struct SnapshotReconcileArgs {
+    /// Snapshot network, height and primary lightwalletd source.
+    #[command(flatten)]
+    pub config: BuildConfigArgs,
+    /// Pool to reconcile.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
+    /// Secondary lightwalletd gRPC endpoint URL to reconcile against.
+    #[arg(
+        long,
+        env = ZAIR_AGAINST_LIGHTWALLETD_URL,
+        conflicts_with_all = ["against_snapshot_sapling", "against_snapshot_orchard"]
+    )]
+    pub against_lightwalletd: Option<String>,
+    /// Sapling snapshot nullifiers file to reconcile against.
+    #[arg(long, env = ZAIR_AGAINST_SNAPSHOT_SAPLING)]
+    pub against_snapshot_sapling: Option<PathBuf>,
+    /// Orchard snapshot nullifiers file to reconcile against.
+    #[arg(long, env = ZAIR_AGAINST_SNAPSHOT_ORCHARD)]
+    pub against_snapshot_orchard: Option<PathBuf>,
+    /// Reconciliation report output file.
+    #[arg(
+        long,
+        env = ZAIR_RECONCILE_REPORT_OUT,
+        default_value = DEFAULT_RECONCILE_REPORT_FILE
+    )]
+    pub report_out: PathBuf,
+}
+
+impl SnapshotReconcileArgs {
+    /// Secondary source selected by the `--against-*` arguments.
+    ///
+    /// Snapshot files that are not given fall back to the `config build` defaults.
+    pub fn secondary_source(&mut self) -> SnapshotSource {
+        if let Some(url) = self.against_lightwalletd.take() {
+            return SnapshotSource::Lightwalletd(url, Backend::default());
+        }
+        SnapshotSource::Files {
+            sapling: self.against_snapshot_sapling.take().unwrap_or_else(|| {
+                default_input_path(snapshots_dir, DEFAULT_SNAPSHOT_SAPLING_FILE)
+            }),
+            orchard: self.against_snapshot_orchard.take().unwrap_or_else(|| {
+                default_input_path(snapshots_dir, DEFAULT_SNAPSHOT_ORCHARD_FILE)
+            }),
+        }
+    }
+}
+
+/// Arguments for `snapshot export`.
+#[derive(Debug, clap::Args)]
+pub struct SnapshotExportArgs {
+    /// Output format: `csv` or `parquet`.
+    #[arg(
+        long,
+        env = ZAIR_EXPORT_FORMAT,
+        default_value = DEFAULT_EXPORT_FORMAT,
+        value_parser = parse_snapshot_export_format
+    )]
+    pub format: SnapshotExportFormat,
+    /// Pool to export.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
+    /// Sapling snapshot nullifiers file.
+    #[arg(
+        long,
+        env = ZAIR_SNAPSHOT_SAPLING_FILE,
+        default_value_os_t = default_input_path(snapshots_dir, DEFAULT_SNAPSHOT_SAPLING_FILE)
+    )]
+    pub snapshot_sapling: PathBuf,
+    /// Orchard snapshot nullifiers file.
+    #[arg(
+        long,
+        env = ZAIR_SNAPSHOT_ORCHARD_FILE,
+        default_value_os_t = default_input_path(snapshots_dir, DEFAULT_SNAPSHOT_ORCHARD_FILE)
+    )]
+    pub snapshot_orchard: PathBuf,
+    /// Recover the height each nullifier was revealed at by rescanning the snapshot range.
+    #[arg(long, env = ZAIR_ORIGIN_HEIGHTS, default_value_t = false, requires = "height")]
+    pub origin_heights: bool,
+    /// Network of the snapshot (mainnet, testnet or regtest), used with `--origin-heights`.
+    #[arg(
+        long,
+        env = ZAIR_NETWORK,
+        default_value = DEFAULT_NETWORK,
+        value_parser = parse_network
+    )]
+    pub network: Network,
+    /// Snapshot block height (inclusive), used with `--origin-heights`.
+    #[arg(long, env = ZAIR_SNAPSHOT_HEIGHT)]
+    pub height: Option<u64>,
+    /// Optional lightwalletd gRPC endpoint URL override, used with `--origin-heights`.
+    #[arg(long, env = ZAIR_LIGHTWALLETD_URL)]
+    pub lightwalletd: Option<String>,
+    /// Indexer serving `--lightwalletd` (lightwalletd or zaino), used with `--origin-heights`.
+    #[arg(
+        long,
+        env = ZAIR_BACKEND,
+        default_value = DEFAULT_BACKEND,
+        value_parser = parse_backend
+    )]
+    pub backend: Backend,
+    /// Output file. Defaults to `snapshot-nullifiers.csv` or `snapshot-nullifiers.parquet`.
+    #[arg(long, env = ZAIR_EXPORT_OUT)]
+    pub output: Option<PathBuf>,
+}
+
+impl SnapshotExportArgs {
+    /// Scan configuration for `--origin-heights`, if requested.
+    pub fn origin_heights(&mut self) -> Option<CommonConfig> {
+        let snapshot_height = self.height.filter(|_| self.origin_heights)?;
+        Some(CommonConfig {
+            network: self.network,
+            snapshot_height,
+            lightwalletd_url: self.lightwalletd.take(),
+            backend: self.backend,
+        })
+    }
+
+    /// Output file, defaulting to a name matching the format.
+    pub fn output(&mut self) -> PathBuf {
+        self.output.take().unwrap_or_else(|| {
+            let extension = match self.format {
+                SnapshotExportFormat::Csv => "csv",
+                #[cfg(feature = "parquet")]
+                SnapshotExportFormat::Parquet => "parquet",
+            };
+            PathBuf::from(format!("{DEFAULT_EXPORT_FILE_STEM}.{extension}"))
+        })
+    }
+}
+
+/// Arguments for `snapshot serve`.
+#[cfg(feature = "serve")]
+#[derive(Debug, clap::Args)]
+pub struct SnapshotServeArgs {
+    /// Address to listen on.
+    #[arg(long, env = ZAIR_SNAPSHOT_SERVE_ADDR, default_value = DEFAULT_SNAPSHOT_SERVE_ADDR)]
+    pub addr: std::net::SocketAddr,
+    /// Airdrop configuration file the served files belong to.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Sapling snapshot nullifiers file. Defaults to `snapshot-sapling.bin` in the working
+    /// directory if present, else in the standard snapshots directory.
+    #[arg(long, env = ZAIR_SNAPSHOT_SAPLING_FILE)]
+    pub snapshot_sapling: Option<PathBuf>,
+    /// Orchard snapshot nullifiers file. Defaults to `snapshot-orchard.bin` in the working
+    /// directory if present, else in the standard snapshots directory.
+    #[arg(long, env = ZAIR_SNAPSHOT_ORCHARD_FILE)]
+    pub snapshot_orchard: Option<PathBuf>,
+    /// Sapling gap-tree file, served when present. Defaults to `gaptree-sapling.bin` in the
+    /// working directory if present, else in the standard gap-trees directory.
+    #[arg(long, env = ZAIR_GAP_TREE_SAPLING_FILE)]
+    pub gap_tree_sapling: Option<PathBuf>,
+    /// Orchard gap-tree file, served when present. Defaults to `gaptree-orchard.bin` in the
+    /// working directory if present, else in the standard gap-trees directory.
+    #[arg(long, env = ZAIR_GAP_TREE_ORCHARD_FILE)]
+    pub gap_tree_orchard: Option<PathBuf>,
+}
+
+/// Arguments for `snapshot publish`.
+#[derive(Debug, clap::Args)]
+pub struct SnapshotPublishArgs {
+    /// Publish to IPFS through the node API at `--ipfs-api`.
+    #[arg(long, required = true)]
+    pub ipfs: bool,
+    /// HTTP API of the IPFS node that adds and pins the files.
+    #[arg(long, env = ZAIR_IPFS_API_URL, default_value = DEFAULT_IPFS_API_URL)]
+    pub ipfs_api: String,
+    /// Airdrop configuration file the published files belong to.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Sapling snapshot nullifiers file. Defaults to `snapshot-sapling.bin` in the working
+    /// directory if present, else in the standard snapshots directory.
+    #[arg(long, env = ZAIR_SNAPSHOT_SAPLING_FILE)]
+    pub snapshot_sapling: Option<PathBuf>,
+    /// Orchard snapshot nullifiers file. Defaults to `snapshot-orchard.bin` in the working
+    /// directory if present, else in the standard snapshots directory.
+    #[arg(long, env = ZAIR_SNAPSHOT_ORCHARD_FILE)]
+    pub snapshot_orchard: Option<PathBuf>,
+    /// Sapling gap-tree file, published when present. Defaults to `gaptree-sapling.bin` in the
+    /// working directory if present, else in the standard gap-trees directory.
+    #[arg(long, env = ZAIR_GAP_TREE_SAPLING_FILE)]
+    pub gap_tree_sapling: Option<PathBuf>,
+    /// Orchard gap-tree file, published when present. Defaults to `gaptree-orchard.bin` in the
+    /// working directory if present, else in the standard gap-trees directory.
+    #[arg(long, env = ZAIR_GAP_TREE_ORCHARD_FILE)]
+    pub gap_tree_orchard: Option<PathBuf>,
+}
+
+/// Snapshot command group.
+#[derive(Debug, clap::Subcommand)]
+pub enum SnapshotCommands {
+    /// Rebuild the snapshot nullifier set from two independent sources and report divergence.
+    #[command(group(
+        clap::ArgGroup::new("secondary_source")
+            .args(["against_lightwalletd", "against_snapshot_sapling", "against_snapshot_orchard"])
+            .required(true)
+            .multiple(true)
+    ))]
+    Reconcile {
+        #[command(flatten)]
+        args: SnapshotReconcileArgs,
+    },
+
+    /// Export snapshot nullifiers to CSV or Parquet for external analysis.
+    Export {
+        #[command(flatten)]
+        args: SnapshotExportArgs,
+    },
+
+    /// Publish snapshot and gap-tree files to IPFS and print the directory CID.
+    Publish {
+        #[command(flatten)]
+        args: SnapshotPublishArgs,
+    },
+
+    /// Serve snapshot and gap-tree files over HTTP, with a manifest and resumable downloads.
+    #[cfg(feature = "serve")]
+    Serve {
+        #[command(flatten)]
+        args: SnapshotServeArgs,
+    },
+}
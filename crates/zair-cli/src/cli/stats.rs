@@ -0,0 +1,60 @@
+//! Stats command.
+
+use std::path::PathBuf;
+
+use zair_sdk::commands::OrchardParamsMode;
+use zair_sdk::paths::{default_input_path, params_dir};
+
+use super::constants::{
+    DEFAULT_CONFIG_FILE, DEFAULT_ORCHARD_PARAMS_FILE, DEFAULT_ORCHARD_PARAMS_MODE,
+    DEFAULT_SAPLING_VK_FILE, ZAIR_CONFIG_FILE, ZAIR_ORCHARD_PARAMS_FILE, ZAIR_ORCHARD_PARAMS_MODE,
+    ZAIR_SAPLING_VK_FILE, ZAIR_STATS_OUT, ZAIR_SUBMISSIONS_DIR, ZAIR_TOTALS_DIR,
+};
+use super::parse_orchard_params_mode;
+
+/// Arguments for claim statistics over a directory of submissions.
+#[derive(Debug, clap::Args)]
+pub struct StatsArgs {
+    /// Directory of signed submission files; every `.json` file in it is scanned.
+    #[arg(long, env = ZAIR_SUBMISSIONS_DIR, value_name = "DIR")]
+    pub submissions_dir: PathBuf,
+    /// Directory of claimed value proofs from `claim total`, for the value distribution; every
+    /// `.json` file in it is checked against the submissions.
+    #[arg(long, env = ZAIR_TOTALS_DIR, value_name = "DIR")]
+    pub totals_dir: Option<PathBuf>,
+    /// Airdrop configuration file used to bind expected roots and scheme.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Path to the Sapling verifying key file.
+    #[arg(
+        long = "sapling-vk",
+        env = ZAIR_SAPLING_VK_FILE,
+        value_name = "SAPLING_VK_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_SAPLING_VK_FILE)
+    )]
+    pub sapling_vk: PathBuf,
+    /// Path to the Orchard Halo2 params file.
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_FILE,
+        value_name = "ORCHARD_PARAMS_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE)
+    )]
+    pub orchard_params: PathBuf,
+    /// Orchard params handling mode: `require` (fail if missing) or `auto` (generate and persist).
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_MODE,
+        default_value = DEFAULT_ORCHARD_PARAMS_MODE,
+        value_parser = parse_orchard_params_mode
+    )]
+    pub orchard_params_mode: OrchardParamsMode,
+    /// Optional JSON report of the statistics.
+    #[arg(long, env = ZAIR_STATS_OUT)]
+    pub stats_out: Option<PathBuf>,
+}
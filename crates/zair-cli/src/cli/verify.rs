@@ -2,15 +2,35 @@
 
 use std::path::PathBuf;
 
-use zair_sdk::commands::OrchardParamsMode;
+use zair_sdk::commands::{HeightChain, OrchardParamsMode};
+use zair_sdk::common::{Backend, PoolSelection};
+use zair_sdk::paths::{default_input_path, params_dir, snapshots_dir};
 
+#[cfg(feature = "registry")]
+use super::constants::ZAIR_REGISTRY_FILE;
 use super::constants::{
-    DEFAULT_CONFIG_FILE, DEFAULT_ORCHARD_PARAMS_FILE, DEFAULT_ORCHARD_PARAMS_MODE,
-    DEFAULT_PROOFS_FILE, DEFAULT_SAPLING_VK_FILE, DEFAULT_SUBMISSION_FILE, ZAIR_CONFIG_FILE,
+    DEFAULT_ATTESTATION_FILE, DEFAULT_AUDIT_TRAIL_FILE, DEFAULT_BACKEND,
+    DEFAULT_CLAIMED_VALUE_FILE, DEFAULT_CONFIG_FILE, DEFAULT_DETACHED_SIGNATURES_FILE,
+    DEFAULT_ORCHARD_PARAMS_FILE, DEFAULT_ORCHARD_PARAMS_MODE, DEFAULT_PACKAGE_EXTRACT_DIR,
+    DEFAULT_PACKAGE_FILE, DEFAULT_POOL, DEFAULT_PROOFS_FILE, DEFAULT_SAPLING_VK_FILE,
+    DEFAULT_SNAPSHOT_TRANSPARENT_FILE, DEFAULT_SUBMISSION_FILE, DEFAULT_TRANSPARENT_CLAIMS_FILE,
+    DEFAULT_VERIFICATION_REPORT_FILE, ZAIR_ATTESTATION_IN, ZAIR_AUDIT_TRAIL_IN, ZAIR_BACKEND,
+    ZAIR_CLAIMED_VALUE_IN, ZAIR_CONFIG_FILE, ZAIR_CURRENT_HEIGHT, ZAIR_DEDUPE_REPORT_OUT,
+    ZAIR_DETACHED, ZAIR_DETACHED_SIGNATURES_IN, ZAIR_HEIGHT_FROM, ZAIR_HEIGHT_URL,
     ZAIR_MESSAGE_FILE, ZAIR_MESSAGES_FILE, ZAIR_ORCHARD_PARAMS_FILE, ZAIR_ORCHARD_PARAMS_MODE,
-    ZAIR_PROOFS_IN, ZAIR_SAPLING_VK_FILE, ZAIR_SUBMISSION_IN,
+    ZAIR_PACKAGE_EXTRACT_DIR, ZAIR_PACKAGE_IN, ZAIR_PAYLOAD_FILE, ZAIR_POOL, ZAIR_PROOFS_IN,
+    ZAIR_SAPLING_VK_FILE, ZAIR_SNAPSHOT_TRANSPARENT_FILE, ZAIR_SUBMISSION_IN, ZAIR_SUBMISSIONS_DIR,
+    ZAIR_TRANSPARENT_CLAIMS_IN, ZAIR_VERIFICATION_REPORT_OUT,
+};
+#[cfg(feature = "serve")]
+use super::constants::{
+    DEFAULT_RATE_LIMIT, DEFAULT_VERIFY_SERVE_ADDR, ZAIR_API_KEYS_FILE, ZAIR_AUDIT_LOG,
+    ZAIR_RATE_LIMIT, ZAIR_VERIFY_SERVE_ADDR,
+};
+use super::{
+    parse_backend, parse_claim_id, parse_height_chain, parse_orchard_params_mode,
+    parse_pool_selection,
 };
-use super::parse_orchard_params_mode;
 
 /// Arguments for end-to-end verification.
 #[derive(Debug, clap::Args)]
@@ -28,7 +48,7 @@ pub struct VerifyRunArgs {
         long = "sapling-vk",
         env = ZAIR_SAPLING_VK_FILE,
         value_name = "SAPLING_VK_FILE",
-        default_value = DEFAULT_SAPLING_VK_FILE
+        default_value_os_t = default_input_path(params_dir, DEFAULT_SAPLING_VK_FILE)
     )]
     pub sapling_vk: PathBuf,
     /// Path to the Orchard Halo2 params file.
@@ -36,7 +56,7 @@ pub struct VerifyRunArgs {
         long,
         env = ZAIR_ORCHARD_PARAMS_FILE,
         value_name = "ORCHARD_PARAMS_FILE",
-        default_value = DEFAULT_ORCHARD_PARAMS_FILE
+        default_value_os_t = default_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE)
     )]
     pub orchard_params: PathBuf,
     /// Orchard params handling mode: `require` (fail if missing) or `auto` (generate and persist).
@@ -56,6 +76,42 @@ pub struct VerifyRunArgs {
     /// Per-claim message assignments JSON.
     #[arg(long = "messages", env = ZAIR_MESSAGES_FILE, value_name = "MESSAGES_FILE")]
     pub messages: Option<PathBuf>,
+    /// Current chain height; a submission that expired before it or that comes before the claim
+    /// window opens is rejected.
+    #[arg(long, env = ZAIR_CURRENT_HEIGHT)]
+    pub current_height: Option<u64>,
+    /// Read the current height from the tip of this chain instead: `zcash` or `namada`.
+    #[arg(
+        long,
+        env = ZAIR_HEIGHT_FROM,
+        value_parser = parse_height_chain,
+        conflicts_with = "current_height"
+    )]
+    pub height_from: Option<HeightChain>,
+    /// Endpoint of `--height-from`: a lightwalletd gRPC URL for Zcash, defaulting to the
+    /// configured network's, or the CometBFT RPC URL of a Namada node.
+    #[arg(long, env = ZAIR_HEIGHT_URL, value_name = "URL", requires = "height_from")]
+    pub height_url: Option<String>,
+    /// Indexer serving a Zcash `--height-url` (lightwalletd or zaino).
+    #[arg(
+        long,
+        env = ZAIR_BACKEND,
+        default_value = DEFAULT_BACKEND,
+        value_parser = parse_backend
+    )]
+    pub backend: Backend,
+    /// Output file for the verification report: per-claim verdicts, checked roots, setup
+    /// fingerprints and timestamps.
+    #[arg(
+        long,
+        env = ZAIR_VERIFICATION_REPORT_OUT,
+        default_value = DEFAULT_VERIFICATION_REPORT_FILE
+    )]
+    pub report_out: PathBuf,
+    /// SQLite claim registry that records the verdict of every claim in the submission.
+    #[cfg(feature = "registry")]
+    #[arg(long, env = ZAIR_REGISTRY_FILE, value_name = "REGISTRY_FILE")]
+    pub registry: Option<PathBuf>,
 }
 
 /// Arguments for proof verification.
@@ -74,7 +130,7 @@ pub struct VerifyProofArgs {
         long = "sapling-vk",
         env = ZAIR_SAPLING_VK_FILE,
         value_name = "SAPLING_VK_FILE",
-        default_value = DEFAULT_SAPLING_VK_FILE
+        default_value_os_t = default_input_path(params_dir, DEFAULT_SAPLING_VK_FILE)
     )]
     pub sapling_vk: PathBuf,
     /// Path to the Orchard Halo2 params file.
@@ -82,7 +138,7 @@ pub struct VerifyProofArgs {
         long,
         env = ZAIR_ORCHARD_PARAMS_FILE,
         value_name = "ORCHARD_PARAMS_FILE",
-        default_value = DEFAULT_ORCHARD_PARAMS_FILE
+        default_value_os_t = default_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE)
     )]
     pub orchard_params: PathBuf,
     /// Orchard params handling mode: `require` (fail if missing) or `auto` (generate and persist).
@@ -96,6 +152,20 @@ pub struct VerifyProofArgs {
     /// JSON file containing claim proofs.
     #[arg(long, env = ZAIR_PROOFS_IN, default_value = DEFAULT_PROOFS_FILE)]
     pub proofs_in: PathBuf,
+    /// Pools to verify: `sapling`, `orchard` or `both`. Proofs of other pools are skipped.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
+    /// Verify only the proof at this position in its pool, as numbered in the verification logs.
+    #[arg(long)]
+    pub index: Option<usize>,
+    /// Verify only the proof of this claim ID (hex).
+    #[arg(long, value_parser = parse_claim_id)]
+    pub claim_id: Option<[u8; 32]>,
 }
 
 /// Arguments for signature verification.
@@ -118,6 +188,242 @@ pub struct VerifySignatureArgs {
     /// Per-claim message assignments JSON.
     #[arg(long = "messages", env = ZAIR_MESSAGES_FILE, value_name = "MESSAGES_FILE")]
     pub messages: Option<PathBuf>,
+    /// Current chain height; a submission that expired before it is rejected.
+    #[arg(long, env = ZAIR_CURRENT_HEIGHT)]
+    pub current_height: Option<u64>,
+    /// Verify the detached signatures of `claim sign --detached` over `--payload` instead of a
+    /// submission.
+    #[arg(long, env = ZAIR_DETACHED, default_value_t = false, requires = "payload")]
+    pub detached: bool,
+    /// External payload file the detached signatures were made over.
+    #[arg(long, env = ZAIR_PAYLOAD_FILE, value_name = "PAYLOAD_FILE")]
+    pub payload: Option<PathBuf>,
+    /// Detached signatures file read with `--detached`.
+    #[arg(
+        long,
+        env = ZAIR_DETACHED_SIGNATURES_IN,
+        default_value = DEFAULT_DETACHED_SIGNATURES_FILE
+    )]
+    pub detached_signatures_in: PathBuf,
+    /// Pools to verify: `sapling`, `orchard` or `both`. Claims of other pools are skipped.
+    #[arg(
+        long,
+        env = ZAIR_POOL,
+        default_value = DEFAULT_POOL,
+        value_parser = parse_pool_selection
+    )]
+    pub pool: PoolSelection,
+}
+
+/// Arguments for claim package verification.
+#[derive(Debug, clap::Args)]
+pub struct VerifyPackageArgs {
+    /// Path to the Sapling verifying key file.
+    #[arg(
+        long = "sapling-vk",
+        env = ZAIR_SAPLING_VK_FILE,
+        value_name = "SAPLING_VK_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_SAPLING_VK_FILE)
+    )]
+    pub sapling_vk: PathBuf,
+    /// Path to the Orchard Halo2 params file.
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_FILE,
+        value_name = "ORCHARD_PARAMS_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE)
+    )]
+    pub orchard_params: PathBuf,
+    /// Orchard params handling mode: `require` (fail if missing) or `auto` (generate and persist).
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_MODE,
+        default_value = DEFAULT_ORCHARD_PARAMS_MODE,
+        value_parser = parse_orchard_params_mode
+    )]
+    pub orchard_params_mode: OrchardParamsMode,
+    /// Claim package generated by `claim package`.
+    #[arg(long, env = ZAIR_PACKAGE_IN, default_value = DEFAULT_PACKAGE_FILE)]
+    pub package_in: PathBuf,
+    /// Directory the package is unpacked into.
+    #[arg(
+        long,
+        env = ZAIR_PACKAGE_EXTRACT_DIR,
+        default_value = DEFAULT_PACKAGE_EXTRACT_DIR
+    )]
+    pub extract_dir: PathBuf,
+    /// Current chain height; a submission that expired before it is rejected.
+    #[arg(long, env = ZAIR_CURRENT_HEIGHT)]
+    pub current_height: Option<u64>,
+    /// Output file for the verification report of the packaged submission.
+    #[arg(
+        long,
+        env = ZAIR_VERIFICATION_REPORT_OUT,
+        default_value = DEFAULT_VERIFICATION_REPORT_FILE
+    )]
+    pub report_out: PathBuf,
+}
+
+/// Arguments for holdings attestation verification.
+#[derive(Debug, clap::Args)]
+pub struct VerifyAttestationArgs {
+    /// Airdrop configuration file used to bind expected roots and scheme.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Path to the Sapling verifying key file.
+    #[arg(
+        long = "sapling-vk",
+        env = ZAIR_SAPLING_VK_FILE,
+        value_name = "SAPLING_VK_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_SAPLING_VK_FILE)
+    )]
+    pub sapling_vk: PathBuf,
+    /// Path to the Orchard Halo2 params file.
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_FILE,
+        value_name = "ORCHARD_PARAMS_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE)
+    )]
+    pub orchard_params: PathBuf,
+    /// Orchard params handling mode: `require` (fail if missing) or `auto` (generate and persist).
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_MODE,
+        default_value = DEFAULT_ORCHARD_PARAMS_MODE,
+        value_parser = parse_orchard_params_mode
+    )]
+    pub orchard_params_mode: OrchardParamsMode,
+    /// Holdings attestation generated by `claim attest`.
+    #[arg(
+        long,
+        env = ZAIR_ATTESTATION_IN,
+        default_value = DEFAULT_ATTESTATION_FILE
+    )]
+    pub attestation_in: PathBuf,
+}
+
+/// Arguments for claimed value proof verification.
+#[derive(Debug, clap::Args)]
+pub struct VerifyTotalArgs {
+    /// Claimed value proof generated by `claim total`.
+    #[arg(
+        long,
+        env = ZAIR_CLAIMED_VALUE_IN,
+        default_value = DEFAULT_CLAIMED_VALUE_FILE
+    )]
+    pub total_in: PathBuf,
+    /// Signed submission file whose value commitments the proof opens.
+    #[arg(long, env = ZAIR_SUBMISSION_IN, default_value = DEFAULT_SUBMISSION_FILE)]
+    pub submission_in: PathBuf,
+}
+
+/// Arguments for transparent claim verification.
+#[derive(Debug, clap::Args)]
+pub struct VerifyTransparentArgs {
+    /// Airdrop configuration file with a transparent snapshot from `config add-transparent`.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Transparent UTXO set written by `config add-transparent`.
+    #[arg(
+        long,
+        env = ZAIR_SNAPSHOT_TRANSPARENT_FILE,
+        default_value_os_t = default_input_path(snapshots_dir, DEFAULT_SNAPSHOT_TRANSPARENT_FILE)
+    )]
+    pub snapshot_transparent: PathBuf,
+    /// Transparent claims generated by `claim sign-transparent`.
+    #[arg(
+        long,
+        env = ZAIR_TRANSPARENT_CLAIMS_IN,
+        default_value = DEFAULT_TRANSPARENT_CLAIMS_FILE
+    )]
+    pub transparent_claims_in: PathBuf,
+    /// Claim message file the addresses signed.
+    #[arg(long = "message", env = ZAIR_MESSAGE_FILE, value_name = "MESSAGE_FILE")]
+    pub message: PathBuf,
+    /// Current chain height; claims checked before the claim window opens, or after they
+    /// expired, are rejected.
+    #[arg(long, env = ZAIR_CURRENT_HEIGHT)]
+    pub current_height: Option<u64>,
+}
+
+/// Arguments for audit trail verification.
+#[derive(Debug, clap::Args)]
+pub struct VerifyAuditTrailArgs {
+    /// Audit trail written with `--audit-trail`.
+    #[arg(long, env = ZAIR_AUDIT_TRAIL_IN, default_value = DEFAULT_AUDIT_TRAIL_FILE)]
+    pub trail_in: PathBuf,
+}
+
+/// Arguments for duplicate-claim detection.
+#[derive(Debug, clap::Args)]
+pub struct VerifyDedupeArgs {
+    /// Directory of signed submission files; every `.json` file in it is scanned.
+    #[arg(long, env = ZAIR_SUBMISSIONS_DIR, value_name = "DIR")]
+    pub submissions_dir: PathBuf,
+    /// Optional report of the scanned claims and duplicates.
+    #[arg(long, env = ZAIR_DEDUPE_REPORT_OUT)]
+    pub report_out: Option<PathBuf>,
+}
+
+/// Arguments for the verification service.
+#[cfg(feature = "serve")]
+#[derive(Debug, clap::Args)]
+pub struct VerifyServeArgs {
+    /// Address to listen on.
+    #[arg(long, env = ZAIR_VERIFY_SERVE_ADDR, default_value = DEFAULT_VERIFY_SERVE_ADDR)]
+    pub addr: std::net::SocketAddr,
+    /// Airdrop configuration file used for proof/signature binding checks.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Path to the Sapling verifying key file.
+    #[arg(
+        long = "sapling-vk",
+        env = ZAIR_SAPLING_VK_FILE,
+        value_name = "SAPLING_VK_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_SAPLING_VK_FILE)
+    )]
+    pub sapling_vk: PathBuf,
+    /// Path to the Orchard Halo2 params file.
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_FILE,
+        value_name = "ORCHARD_PARAMS_FILE",
+        default_value_os_t = default_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE)
+    )]
+    pub orchard_params: PathBuf,
+    /// Orchard params handling mode: `require` (fail if missing) or `auto` (generate and persist).
+    #[arg(
+        long,
+        env = ZAIR_ORCHARD_PARAMS_MODE,
+        default_value = DEFAULT_ORCHARD_PARAMS_MODE,
+        value_parser = parse_orchard_params_mode
+    )]
+    pub orchard_params_mode: OrchardParamsMode,
+    /// Allowlist of API keys, one `<client name> <key>` per line.
+    #[arg(long, env = ZAIR_API_KEYS_FILE, value_name = "API_KEYS_FILE")]
+    pub api_keys: PathBuf,
+    /// Requests per minute allowed for each API key.
+    #[arg(long, env = ZAIR_RATE_LIMIT, default_value = DEFAULT_RATE_LIMIT)]
+    pub rate_limit: std::num::NonZeroU32,
+    /// Append a JSON line per request to this audit log.
+    #[arg(long, env = ZAIR_AUDIT_LOG, value_name = "AUDIT_LOG_FILE")]
+    pub audit_log: Option<PathBuf>,
 }
 
 /// Verify command group.
@@ -140,10 +446,10 @@ pub enum VerifyCommands {
         #[command(flatten)]
         args: VerifyProofArgs,
     },
-    /// Verify signatures in a signed claim submission.
+    /// Verify signatures in a signed claim submission, or detached signatures with `--detached`.
     #[command(group(
         clap::ArgGroup::new("message_input")
-            .args(["message", "messages"])
+            .args(["message", "messages", "payload"])
             .required(true)
             .multiple(true)
     ))]
@@ -151,4 +457,42 @@ pub enum VerifyCommands {
         #[command(flatten)]
         args: VerifySignatureArgs,
     },
+    /// Flag airdrop nullifiers claimed more than once across signed submissions.
+    Dedupe {
+        #[command(flatten)]
+        args: VerifyDedupeArgs,
+    },
+    /// Unpack a `.zairpkg` archive from `claim package`, check its checksums and verify the
+    /// packaged submission end-to-end.
+    Package {
+        #[command(flatten)]
+        args: VerifyPackageArgs,
+    },
+    /// Verify a holdings attestation from `claim attest` and print the attested value.
+    Attestation {
+        #[command(flatten)]
+        args: VerifyAttestationArgs,
+    },
+    /// Verify a claimed value proof from `claim total` against a submission and print the total.
+    Total {
+        #[command(flatten)]
+        args: VerifyTotalArgs,
+    },
+    /// Verify transparent claims from `claim sign-transparent` against the transparent snapshot.
+    Transparent {
+        #[command(flatten)]
+        args: VerifyTransparentArgs,
+    },
+    /// Check the hash chain of an audit trail written with `--audit-trail`.
+    AuditTrail {
+        #[command(flatten)]
+        args: VerifyAuditTrailArgs,
+    },
+    /// Serve `POST /verify` over HTTP for allowlisted API keys, with per-key rate limits and an
+    /// audit log.
+    #[cfg(feature = "serve")]
+    Serve {
+        #[command(flatten)]
+        args: VerifyServeArgs,
+    },
 }
@@ -0,0 +1,45 @@
+//! Watch command.
+
+use std::path::PathBuf;
+
+use super::constants::{
+    DEFAULT_CLAIMS_FILE, DEFAULT_CONFIG_FILE, DEFAULT_WATCH_POLL_INTERVAL, ZAIR_CLAIMS_IN,
+    ZAIR_CONFIG_FILE, ZAIR_LIGHTWALLETD_URL, ZAIR_WATCH_FROM_HEIGHT, ZAIR_WATCH_ONCE,
+    ZAIR_WATCH_POLL_INTERVAL, ZAIR_WATCH_WEBHOOK_URL,
+};
+
+/// Arguments for watching the chain for spends of claimed notes.
+#[derive(Debug, clap::Args)]
+pub struct WatchArgs {
+    /// Airdrop configuration file, for the network and snapshot height.
+    #[arg(
+        long,
+        env = ZAIR_CONFIG_FILE,
+        value_name = "CONFIG_FILE",
+        default_value = DEFAULT_CONFIG_FILE
+    )]
+    pub config: PathBuf,
+    /// Prepared claims file generated by `claim prepare`, for the nullifiers of the claimed notes.
+    #[arg(long, env = ZAIR_CLAIMS_IN, default_value = DEFAULT_CLAIMS_FILE)]
+    pub claims_in: PathBuf,
+    /// Optional lightwalletd gRPC endpoint URL override.
+    #[arg(long, env = ZAIR_LIGHTWALLETD_URL)]
+    pub lightwalletd: Option<String>,
+    /// First block to scan. Defaults to the block after the snapshot height.
+    #[arg(long, env = ZAIR_WATCH_FROM_HEIGHT)]
+    pub from_height: Option<u64>,
+    /// Seconds between checks for new blocks.
+    #[arg(
+        long,
+        env = ZAIR_WATCH_POLL_INTERVAL,
+        value_name = "SECS",
+        default_value = DEFAULT_WATCH_POLL_INTERVAL
+    )]
+    pub poll_interval: u64,
+    /// POST a JSON alert to this URL when a claimed note is spent.
+    #[arg(long, env = ZAIR_WATCH_WEBHOOK_URL, value_name = "URL")]
+    pub webhook_url: Option<String>,
+    /// Scan up to the current chain tip once and exit instead of waiting for new blocks.
+    #[arg(long, env = ZAIR_WATCH_ONCE, default_value_t = false)]
+    pub once: bool,
+}
@@ -1,15 +1,47 @@
 //! ZAIR CLI Application
 
 mod cli;
+mod redact;
 
-use clap::Parser as _;
+use std::io::Write as _;
+use std::path::PathBuf;
 #[cfg(feature = "prove")]
-use cli::SetupCommands;
-use cli::{ClaimCommands, Cli, Commands, ConfigCommands, KeyCommands, VerifyCommands};
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::{CommandFactory as _, FromArgMatches as _};
+use cli::{
+    ClaimCommands, ClaimMergeArgs, ClaimPrepareArgs, ClaimQrArgs, Cli, Commands, ConfigAuditArgs,
+    ConfigBuildArgs, ConfigCommands, DeriveSeedArgs, DeriveUfvkArgs, DoctorArgs,
+    ExportProvingKeyArgs, KeyCommands, ProveCollectArgs, ProveCommands, ProveSubmitArgs,
+    SnapshotCommands, StatsArgs, VerifyAttestationArgs, VerifyCommands, VerifyProofArgs,
+    VerifyRunArgs, VerifyTotalArgs, WatchArgs,
+};
+#[cfg(feature = "prove")]
+use cli::{ProofRngArgs, SetupCommands};
 use eyre::Context as _;
-use zair_sdk::commands::build_airdrop_configuration;
+use zair_sdk::audit::{append_audit_entry, verify_audit_trail};
+use zair_sdk::build_info::BuildInfo;
+#[cfg(feature = "deterministic-rng")]
+use zair_sdk::commands::DeterministicProofRng;
+use zair_sdk::commands::{
+    ArtifactsOutput, BenchmarkReport, CheckStatus, DoctorCheck, MnemonicSource, NullifierSource,
+    PassphraseSource, ProofFilter, ProofVerdict, SelfUpdateOutcome, SnapshotFiles, SnapshotSource,
+    SubmissionStats, build_airdrop_configuration, ensure_all_valid, fetch_artifacts,
+    fetch_chain_height, fetch_snapshot_files, fetch_snapshot_magnets, publish_snapshot_ipfs,
+    reconcile_snapshot_sources, run_benchmark, run_with_notification, self_update,
+};
+#[cfg(feature = "prove")]
+use zair_sdk::commands::{ConstraintReport, OsProofRng, ProofRngSource};
+#[cfg(feature = "object-store")]
+use zair_sdk::commands::{ObjectStoreUploads, SnapshotArtifact, download_object_store_files};
+use zair_sdk::common::resolve_lightwalletd_url;
+use zair_sdk::error::{ZairError, ZairResult};
+use zair_sdk::paths::StandardPaths;
+#[cfg(feature = "registry")]
+use zair_sdk::registry::{Verdict, record_submission_verdict};
 
-fn init_tracing() -> eyre::Result<()> {
+fn init_tracing(log_sensitive: bool) -> eyre::Result<()> {
     #[cfg(feature = "tokio-console")]
     {
         // tokio-console: layers the console subscriber with fmt
@@ -17,10 +49,13 @@ fn init_tracing() -> eyre::Result<()> {
         tracing_subscriber::registry()
             .with(console_subscriber::spawn())
             .with(
-                tracing_subscriber::fmt::layer().with_filter(
-                    tracing_subscriber::EnvFilter::try_from_default_env()
-                        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
-                ),
+                tracing_subscriber::fmt::layer()
+                    .fmt_fields(redact::fields(log_sensitive))
+                    .with_writer(std::io::stderr)
+                    .with_filter(
+                        tracing_subscriber::EnvFilter::try_from_default_env()
+                            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+                    ),
             )
             .try_init()
             .map_err(|e| eyre::eyre!("Failed to initialize tracing: {:?}", e))?;
@@ -29,12 +64,14 @@ fn init_tracing() -> eyre::Result<()> {
     #[cfg(not(feature = "tokio-console"))]
     {
         tracing_subscriber::fmt()
+            .fmt_fields(redact::fields(log_sensitive))
             .with_env_filter(
                 tracing_subscriber::EnvFilter::try_from_default_env()
                     .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
             )
             .with_timer(tracing_subscriber::fmt::time::uptime())
             .with_target(false)
+            .with_writer(std::io::stderr)
             .try_init()
             .map_err(|e| eyre::eyre!("Failed to initialize tracing: {:?}", e))?;
     }
@@ -42,6 +79,649 @@ fn init_tracing() -> eyre::Result<()> {
     Ok(())
 }
 
+fn print_paths() -> eyre::Result<()> {
+    let paths = StandardPaths::resolve()?;
+    let mut stdout = std::io::stdout().lock();
+    for (name, dir) in [
+        ("data", &paths.data),
+        ("cache", &paths.cache),
+        ("params", &paths.params),
+        ("snapshots", &paths.snapshots),
+        ("gaptrees", &paths.gap_trees),
+    ] {
+        writeln!(stdout, "{name:<10} {}", dir.display())?;
+    }
+    Ok(())
+}
+
+fn print_version(json: bool) -> eyre::Result<()> {
+    let info = BuildInfo::current();
+    let mut stdout = std::io::stdout().lock();
+    if json {
+        writeln!(stdout, "{}", serde_json::to_string_pretty(&info)?)?;
+        return Ok(());
+    }
+    let unknown = || "unknown".to_owned();
+    for (name, value) in [
+        ("version", info.version),
+        ("commit", info.git_commit.unwrap_or_else(unknown)),
+        ("lockfile", info.lockfile_sha256.unwrap_or_else(unknown)),
+        (
+            "profile",
+            format!("{} (opt-level {})", info.profile, info.opt_level),
+        ),
+        ("target", info.target),
+        ("rustc", info.rustc.unwrap_or_else(unknown)),
+        ("rustflags", info.rustflags.unwrap_or_default()),
+        ("features", info.features.join(",")),
+    ] {
+        writeln!(stdout, "{name:<10} {value}")?;
+    }
+    Ok(())
+}
+
+/// Key the release manifest of `self-update` must be signed with: the key embedded at build time,
+/// unless `--release-key` overrides it.
+fn resolve_release_key(override_key: Option<[u8; 32]>) -> eyre::Result<[u8; 32]> {
+    if let Some(key) = override_key {
+        tracing::warn!(
+            release_key_fingerprint = %release_key_fingerprint(&key),
+            "Verifying the release with the --release-key override instead of the embedded release key"
+        );
+        return Ok(key);
+    }
+    let embedded = cli::constants::EMBEDDED_RELEASE_KEY.ok_or_else(|| {
+        eyre::eyre!(
+            "This build has no embedded release key; pass --release-key to trust a key explicitly"
+        )
+    })?;
+    cli::parse_release_key(embedded).context("Invalid embedded release key")
+}
+
+/// First 8 hex digits of the `BLAKE2b` hash of a release key.
+///
+/// The full key is 64 hex digits, which the log redaction hides as a possible secret.
+fn release_key_fingerprint(key: &[u8; 32]) -> String {
+    let [a, b, c, d, ..] = zair_core::base::hash_bytes(key);
+    hex::encode([a, b, c, d])
+}
+
+fn print_self_update(outcome: &SelfUpdateOutcome) -> eyre::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    match outcome {
+        SelfUpdateOutcome::UpToDate { version } => writeln!(stdout, "up to date ({version})")?,
+        SelfUpdateOutcome::Available { version } => writeln!(stdout, "available {version}")?,
+        SelfUpdateOutcome::Updated { version } => writeln!(stdout, "updated to {version}")?,
+    }
+    Ok(())
+}
+
+fn print_benchmark(report: &BenchmarkReport) -> eyre::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    let machine = &report.machine;
+    writeln!(
+        stdout,
+        "machine    {} {}, {} threads",
+        machine.os, machine.arch, machine.threads
+    )?;
+    writeln!(
+        stdout,
+        "{:<8} {:>12} {:>12} {:>12}",
+        "pool", "nullifiers", "build", "witness"
+    )?;
+    for tree in &report.trees {
+        writeln!(
+            stdout,
+            "{:<8} {:>12} {:>12} {:>12}",
+            tree.pool.to_string(),
+            tree.nullifiers,
+            format!("{:.2?}", tree.build),
+            format!("{:.2?}", tree.witness)
+        )?;
+    }
+    Ok(())
+}
+
+fn print_submission_stats(stats: &SubmissionStats) -> eyre::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "submissions {}", stats.submission_count)?;
+    writeln!(
+        stdout,
+        "{:<8} {:>8} {:>8} {:>8} {:>8} {:>9}",
+        "pool", "claims", "unique", "valid", "invalid", "pass rate"
+    )?;
+    for pool in &stats.pools {
+        let verified = pool.valid_proofs.saturating_add(pool.invalid_proofs);
+        let pass_rate = pool
+            .valid_proofs
+            .saturating_mul(1000)
+            .checked_div(verified)
+            .map_or_else(
+                || "-".to_owned(),
+                |permille| format!("{}.{}%", permille / 10, permille % 10),
+            );
+        writeln!(
+            stdout,
+            "{:<8} {:>8} {:>8} {:>8} {:>8} {:>9}",
+            pool.pool.to_string(),
+            pool.claims,
+            pool.unique_claims,
+            pool.valid_proofs,
+            pool.invalid_proofs,
+            pass_rate
+        )?;
+    }
+    if let Some(values) = &stats.values {
+        writeln!(
+            stdout,
+            "value proofs {} ({} rejected), {} claims, {} zatoshis",
+            values.proofs, values.rejected_proofs, values.claims, values.total_value
+        )?;
+        writeln!(stdout, "{:<28} {:>8}", "value (zatoshis)", "proofs")?;
+        for bucket in &values.buckets {
+            let range = bucket.max_value.map_or_else(
+                || format!(">= {}", bucket.min_value),
+                |max| format!("{} - {max}", bucket.min_value),
+            );
+            writeln!(stdout, "{range:<28} {:>8}", bucket.count)?;
+        }
+    }
+    Ok(())
+}
+
+/// Summarize a directory of submissions and print the statistics.
+async fn stats(args: StatsArgs) -> ZairResult<()> {
+    let stats = zair_sdk::commands::submission_statistics(
+        args.submissions_dir,
+        args.totals_dir,
+        args.sapling_vk,
+        args.orchard_params,
+        args.orchard_params_mode,
+        args.config,
+        args.stats_out,
+    )
+    .await?;
+    print_submission_stats(&stats).map_err(ZairError::Config)
+}
+
+#[cfg(feature = "prove")]
+fn print_constraint_report(report: &ConstraintReport) -> eyre::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{:<32} {:>12}", "namespace", "constraints")?;
+    for count in &report.namespaces {
+        writeln!(stdout, "{:<32} {:>12}", count.namespace, count.constraints)?;
+    }
+    writeln!(stdout, "{:<32} {:>12}", "total", report.constraints)?;
+    writeln!(
+        stdout,
+        "{} public inputs, {} auxiliary variables",
+        report.inputs, report.aux
+    )?;
+    Ok(())
+}
+
+/// Verify the proofs selected by `verify proof` and print a verdict per proof.
+async fn verify_proof(args: VerifyProofArgs) -> ZairResult<()> {
+    let verdicts = zair_sdk::commands::verify_claim_proofs(
+        args.proofs_in,
+        args.sapling_vk,
+        args.orchard_params,
+        args.orchard_params_mode,
+        args.config,
+        args.pool,
+        ProofFilter {
+            index: args.index,
+            claim_id: args.claim_id,
+        },
+    )
+    .await?;
+    print_proof_verdicts(&verdicts).map_err(ZairError::Config)?;
+    ensure_all_valid(&verdicts).map_err(ZairError::Verification)
+}
+
+/// Watch for spends of claimed notes and fail once one is spent.
+async fn watch(args: WatchArgs) -> ZairResult<()> {
+    let alerts = zair_sdk::commands::watch_claimed_spends(
+        args.config,
+        args.claims_in,
+        args.lightwalletd,
+        args.from_height,
+        Duration::from_secs(args.poll_interval),
+        args.webhook_url,
+        args.once,
+    )
+    .await?;
+    if alerts.is_empty() {
+        return Ok(());
+    }
+    Err(ZairError::Verification(eyre::eyre!(
+        "{} claimed note(s) spent on chain",
+        alerts.len()
+    )))
+}
+
+/// Source of the claim randomness of `claim run` and `claim prove`: the seed of
+/// `--deterministic-rng` if given.
+#[cfg(feature = "deterministic-rng")]
+fn proof_rng_source(args: &ProofRngArgs) -> Arc<dyn ProofRngSource> {
+    match args.deterministic_rng {
+        Some(seed) => Arc::new(DeterministicProofRng::new(seed)),
+        None => Arc::new(OsProofRng),
+    }
+}
+
+/// Source of the claim randomness of `claim run` and `claim prove`: always the OS RNG in release
+/// builds.
+#[cfg(all(feature = "prove", not(feature = "deterministic-rng")))]
+fn proof_rng_source(_args: &ProofRngArgs) -> Arc<dyn ProofRngSource> {
+    Arc::new(OsProofRng)
+}
+
+/// Submit claims to a proving queue and print the job identifier.
+async fn prove_submit(args: ProveSubmitArgs) -> ZairResult<()> {
+    let job_id =
+        zair_sdk::commands::submit_proving_job(args.queue_dir, args.claims_in, args.proving_key)
+            .await?;
+    writeln!(std::io::stdout().lock(), "{job_id}").map_err(|e| ZairError::Config(e.into()))
+}
+
+/// Verify the proofs of a finished proving job, then copy them with their secrets.
+///
+/// The proofs are checked before they are written, so `claim sign` never sees a proof from the
+/// proving farm that does not verify.
+async fn prove_collect(args: ProveCollectArgs) -> ZairResult<()> {
+    let job_dir = zair_sdk::commands::finished_proving_job(args.queue_dir, args.job).await?;
+    let verdicts = zair_sdk::commands::collect_proving_job(
+        job_dir,
+        args.claims_in,
+        args.sapling_vk,
+        args.orchard_params,
+        args.orchard_params_mode,
+        args.config,
+        args.proofs_out,
+        args.secrets_out,
+    )
+    .await?;
+    print_proof_verdicts(&verdicts).map_err(ZairError::Config)
+}
+
+/// Run `doctor` and print its checklist, failing if any check failed.
+async fn doctor(args: DoctorArgs) -> ZairResult<()> {
+    let checks = zair_sdk::commands::run_doctor(
+        args.config,
+        args.lightwalletd,
+        args.sapling_pk,
+        args.sapling_vk,
+        args.orchard_params,
+        args.data_dir,
+    )
+    .await;
+    print_checks(&checks, "doctor")
+}
+
+/// Run `config audit` and print its checklist, failing if any check failed.
+async fn config_audit(args: ConfigAuditArgs) -> ZairResult<()> {
+    let checks = zair_sdk::commands::audit_airdrop_configuration(args.config).await;
+    print_checks(&checks, "audit")
+}
+
+/// Print a checklist, failing if any check failed.
+fn print_checks(checks: &[DoctorCheck], command: &str) -> ZairResult<()> {
+    let mut stdout = std::io::stdout().lock();
+    for check in checks {
+        writeln!(
+            stdout,
+            "[{}] {:<14} {}",
+            check.status, check.name, check.detail
+        )
+        .map_err(|e| ZairError::Config(e.into()))?;
+    }
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Fail)
+        .count();
+    if failed == 0 {
+        return Ok(());
+    }
+    Err(ZairError::Config(eyre::eyre!(
+        "{failed} {command} check(s) failed"
+    )))
+}
+
+/// Run `key derive-seed`, refusing to prompt for secrets with `--non-interactive`.
+async fn derive_seed(args: DeriveSeedArgs, non_interactive: bool) -> ZairResult<()> {
+    let mnemonic_source = if args.mnemonic_stdin {
+        MnemonicSource::Stdin
+    } else if let Some(path) = args.mnemonic_file {
+        MnemonicSource::File(path)
+    } else if non_interactive {
+        return Err(non_interactive_error(
+            "No mnemonic given: pass --mnemonic-file <FILE> (env ZAIR_MNEMONIC_FILE) or \
+             --mnemonic-stdin",
+        ));
+    } else {
+        MnemonicSource::Prompt
+    };
+    let passphrase_source = passphrase_source(
+        args.no_passphrase,
+        args.passphrase_file,
+        args.passphrase_env,
+        non_interactive,
+    )?;
+    zair_sdk::commands::key_derive_seed(
+        args.output,
+        mnemonic_source,
+        args.mnemonic_language,
+        passphrase_source,
+    )
+    .await
+}
+
+/// Run `key derive-ufvk`, refusing to prompt for secrets with `--non-interactive`.
+async fn derive_ufvk(args: DeriveUfvkArgs, non_interactive: bool) -> ZairResult<()> {
+    let (mnemonic_source, passphrase_source) = seed_sources(
+        args.mnemonic_stdin,
+        args.mnemonic_file,
+        args.no_passphrase,
+        args.passphrase_file,
+        args.passphrase_env,
+        non_interactive,
+    )?;
+    zair_sdk::commands::key_derive_ufvk(
+        args.network,
+        args.account,
+        args.address_index,
+        args.seed,
+        mnemonic_source,
+        args.mnemonic_language,
+        passphrase_source,
+        args.output,
+    )
+    .await
+}
+
+/// Run `key export-proving-key`, refusing to prompt for secrets with `--non-interactive`.
+async fn export_proving_key(args: ExportProvingKeyArgs, non_interactive: bool) -> ZairResult<()> {
+    let (mnemonic_source, passphrase_source) = seed_sources(
+        args.mnemonic_stdin,
+        args.mnemonic_file,
+        args.no_passphrase,
+        args.passphrase_file,
+        args.passphrase_env,
+        non_interactive,
+    )?;
+    zair_sdk::commands::key_export_proving_key(
+        args.network,
+        args.account,
+        args.seed,
+        mnemonic_source,
+        args.mnemonic_language,
+        passphrase_source,
+        args.output,
+    )
+    .await
+}
+
+/// Mnemonic and passphrase sources of a key command that reads a seed file unless a mnemonic is
+/// given.
+fn seed_sources(
+    mnemonic_stdin: bool,
+    mnemonic_file: Option<PathBuf>,
+    no_passphrase: bool,
+    passphrase_file: Option<PathBuf>,
+    passphrase_env: Option<String>,
+    non_interactive: bool,
+) -> ZairResult<(Option<MnemonicSource>, PassphraseSource)> {
+    let mnemonic_source = if mnemonic_stdin {
+        Some(MnemonicSource::Stdin)
+    } else {
+        mnemonic_file.map(MnemonicSource::File)
+    };
+    // The passphrase is only read with a mnemonic.
+    let passphrase_source = if mnemonic_source.is_some() {
+        passphrase_source(
+            no_passphrase,
+            passphrase_file,
+            passphrase_env,
+            non_interactive,
+        )?
+    } else {
+        PassphraseSource::Empty
+    };
+    Ok((mnemonic_source, passphrase_source))
+}
+
+/// Passphrase source of a key command, refusing to prompt with `--non-interactive`.
+fn passphrase_source(
+    no_passphrase: bool,
+    passphrase_file: Option<PathBuf>,
+    passphrase_env: Option<String>,
+    non_interactive: bool,
+) -> ZairResult<PassphraseSource> {
+    if no_passphrase {
+        return Ok(PassphraseSource::Empty);
+    }
+    match (passphrase_file, passphrase_env) {
+        (Some(path), _) => Ok(PassphraseSource::File(path)),
+        (None, Some(name)) => Ok(PassphraseSource::Env(name)),
+        (None, None) if non_interactive => Err(non_interactive_error(
+            "No passphrase given: pass --passphrase-file <FILE> (env ZAIR_PASSPHRASE_FILE), \
+             --passphrase-env <VAR> (env ZAIR_PASSPHRASE_ENV), or --no-passphrase for the empty \
+             passphrase",
+        )),
+        (None, None) => Ok(PassphraseSource::Prompt),
+    }
+}
+
+/// Error for a secret that `--non-interactive` would otherwise prompt for.
+fn non_interactive_error(missing: &str) -> ZairError {
+    ZairError::Config(eyre::eyre!(
+        "{missing} (--non-interactive disables prompts)"
+    ))
+}
+
+async fn claim_qr(args: ClaimQrArgs) -> ZairResult<()> {
+    if let Some(uri) = args.decode {
+        return zair_sdk::commands::decode_claim_uri(uri, args.submission_out).await;
+    }
+    let uri = zair_sdk::commands::encode_claim_uri(args.submission_in, args.url).await?;
+    writeln!(std::io::stdout().lock(), "{uri}").map_err(|e| ZairError::Config(e.into()))
+}
+
+async fn verify_attestation(args: VerifyAttestationArgs) -> ZairResult<()> {
+    let total_value = zair_sdk::commands::verify_holdings_attestation(
+        args.attestation_in,
+        args.sapling_vk,
+        args.orchard_params,
+        args.orchard_params_mode,
+        args.config,
+    )
+    .await?;
+    writeln!(
+        std::io::stdout().lock(),
+        "attested value: {total_value} zatoshis"
+    )
+    .map_err(|e| ZairError::Config(e.into()))
+}
+
+async fn verify_total(args: VerifyTotalArgs) -> ZairResult<()> {
+    let total_value =
+        zair_sdk::commands::verify_claimed_value(args.total_in, args.submission_in).await?;
+    writeln!(
+        std::io::stdout().lock(),
+        "claimed value: {total_value} zatoshis"
+    )
+    .map_err(|e| ZairError::Config(e.into()))
+}
+
+fn print_proof_verdicts(verdicts: &[ProofVerdict]) -> eyre::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    writeln!(
+        stdout,
+        "{:<8} {:>6} {:<64} {:<7} reason",
+        "pool", "index", "claim id", "verdict"
+    )?;
+    for verdict in verdicts {
+        writeln!(
+            stdout,
+            "{:<8} {:>6} {:<64} {:<7} {}",
+            verdict.pool.to_string(),
+            verdict.index,
+            hex::encode(verdict.claim_id),
+            if verdict.valid { "pass" } else { "fail" },
+            verdict.reason.as_deref().unwrap_or("")
+        )?;
+    }
+    Ok(())
+}
+
+/// Merge the claims files, then the proofs and secrets files, given to `claim merge`.
+async fn merge_claim_files(args: ClaimMergeArgs) -> ZairResult<()> {
+    if !args.claims.is_empty() {
+        zair_sdk::commands::merge_claim_inputs(args.claims, args.claims_out).await?;
+    }
+    if args.proofs.is_empty() {
+        return Ok(());
+    }
+    zair_sdk::commands::merge_claim_proofs(
+        args.proofs,
+        args.secrets,
+        args.proofs_out,
+        args.secrets_out,
+    )
+    .await
+}
+
+/// Run `config build`, staging `s3://` and `gs://` outputs locally and uploading them afterwards.
+async fn config_build(args: ConfigBuildArgs) -> ZairResult<()> {
+    #[cfg(feature = "object-store")]
+    let (args, uploads) = stage_object_store_outputs(args)?;
+    #[cfg(feature = "sqlite")]
+    let nullifier_source = args
+        .nullifier_db
+        .map_or(NullifierSource::Lightwalletd, NullifierSource::Sqlite);
+    #[cfg(not(feature = "sqlite"))]
+    let nullifier_source = NullifierSource::Lightwalletd;
+    build_airdrop_configuration(
+        args.config.try_into().map_err(ZairError::Config)?,
+        args.pool,
+        nullifier_source,
+        args.config_out,
+        args.snapshot_out_sapling,
+        args.snapshot_out_orchard,
+        args.gap_tree_out_sapling,
+        args.gap_tree_out_orchard,
+        args.no_gap_tree,
+        args.index_heights,
+        args.nullifier_filter,
+        args.no_disk_check,
+        args.target_sapling,
+        args.scheme_sapling,
+        args.target_orchard,
+        args.scheme_orchard,
+        args.bind_sapling_vk,
+        args.bind_orchard_params,
+        args.min_note_value,
+        args.max_claims_per_submission,
+        args.claim_start_height,
+        args.claim_expiry_height,
+        args.countersigners,
+        args.chain_id,
+        args.torrent.then_some(args.web_seeds),
+        args.artifacts_out.map(|manifest| ArtifactsOutput {
+            manifest,
+            params: args.artifact_params,
+        }),
+    )
+    .await?;
+
+    #[cfg(feature = "object-store")]
+    uploads.upload().await?;
+    Ok(())
+}
+
+#[cfg(feature = "object-store")]
+fn stage_object_store_outputs(
+    mut args: ConfigBuildArgs,
+) -> ZairResult<(ConfigBuildArgs, ObjectStoreUploads)> {
+    let mut uploads = ObjectStoreUploads::default();
+    args.snapshot_out_sapling =
+        uploads.stage(args.snapshot_out_sapling, SnapshotArtifact::SaplingSnapshot)?;
+    args.snapshot_out_orchard =
+        uploads.stage(args.snapshot_out_orchard, SnapshotArtifact::OrchardSnapshot)?;
+    args.gap_tree_out_sapling =
+        uploads.stage(args.gap_tree_out_sapling, SnapshotArtifact::SaplingGapTree)?;
+    args.gap_tree_out_orchard =
+        uploads.stage(args.gap_tree_out_orchard, SnapshotArtifact::OrchardGapTree)?;
+    Ok((args, uploads))
+}
+
+/// Download the snapshot files of `claim prepare --snapshot-url`, `--snapshot-cid` or
+/// `--snapshot-magnet` and point the arguments at them.
+async fn fetch_prepare_snapshot(args: &mut ClaimPrepareArgs) -> ZairResult<()> {
+    #[cfg(feature = "object-store")]
+    {
+        let files = download_object_store_files(args.take_snapshot_files()).await?;
+        args.set_snapshot_files(files);
+    }
+    let files = if args.snapshot_magnets.is_empty() {
+        let url = match (args.snapshot_url.take(), args.snapshot_cid.take()) {
+            (Some(url), _) => url,
+            (None, Some(cid)) => format!("{}/ipfs/{cid}", args.ipfs_gateway.trim_end_matches('/')),
+            (None, None) => return Ok(()),
+        };
+        fetch_snapshot_files(url, args.config.clone(), args.take_snapshot_files()).await?
+    } else {
+        let magnets = std::mem::take(&mut args.snapshot_magnets);
+        fetch_snapshot_magnets(magnets, args.take_snapshot_files()).await?
+    };
+    args.set_snapshot_files(files);
+    Ok(())
+}
+
+/// Run `verify run` and record its verdict in the claim registry, if one is given.
+#[cfg_attr(
+    not(feature = "registry"),
+    allow(
+        clippy::let_and_return,
+        reason = "The verdict is only recorded with the registry feature"
+    )
+)]
+async fn verify_run(args: VerifyRunArgs) -> ZairResult<()> {
+    #[cfg(feature = "registry")]
+    let (registry, submission_in) = (args.registry, args.submission_in.clone());
+    let current_height = match args.height_from {
+        Some(chain) => Some(
+            fetch_chain_height(chain, args.height_url, args.backend, args.config.clone()).await?,
+        ),
+        None => args.current_height,
+    };
+    let verified = zair_sdk::commands::verify_run(
+        args.sapling_vk,
+        args.orchard_params,
+        args.orchard_params_mode,
+        args.submission_in,
+        args.message,
+        args.messages,
+        args.config,
+        current_height,
+        Some(args.report_out),
+    )
+    .await;
+
+    #[cfg(feature = "registry")]
+    if let Some(registry) = registry {
+        let verdict = if verified.is_ok() {
+            Verdict::Accepted
+        } else {
+            Verdict::Rejected
+        };
+        let recorded = record_submission_verdict(&registry, &submission_in, verdict).await;
+        return verified.and(recorded);
+    }
+    verified
+}
+
 #[tokio::main(flavor = "multi_thread")]
 #[allow(
     clippy::too_many_lines,
@@ -56,9 +736,29 @@ async fn main() -> eyre::Result<()> {
     // Load .env file (fails silently if not found)
     let _ = dotenvy::dotenv();
 
-    init_tracing()?;
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
-    let cli = Cli::parse();
+    init_tracing(cli.log_sensitive)?;
+
+    if let Err(e) = cli::ensure_single_stdio(&matches) {
+        exit_with(&e);
+    }
+
+    #[cfg(feature = "metrics")]
+    if let Some(addr) = cli.metrics_addr {
+        // An unusable address is a configuration error, with its exit status.
+        if let Err(e) = metrics_exporter_prometheus::PrometheusBuilder::new()
+            .with_http_listener(addr)
+            .install()
+            .context("Failed to start Prometheus metrics endpoint")
+        {
+            exit_with(&ZairError::Config(e));
+        }
+        tracing::info!(%addr, "Serving Prometheus metrics");
+    }
+
+    tokio::spawn(exit_on_signal());
 
     let res = match cli.command {
         #[cfg(feature = "prove")]
@@ -67,26 +767,130 @@ async fn main() -> eyre::Result<()> {
                 scheme,
                 pk_out,
                 vk_out,
-            } => zair_sdk::commands::generate_claim_params(pk_out, vk_out, scheme).await,
-            SetupCommands::Orchard { scheme, params_out } => {
-                zair_sdk::commands::generate_orchard_params(params_out, scheme).await
+                bind_config,
+            } => {
+                zair_sdk::commands::generate_claim_params(pk_out, vk_out, scheme, bind_config).await
+            }
+            SetupCommands::Orchard {
+                scheme,
+                params_out,
+                bind_config,
+            } => zair_sdk::commands::generate_orchard_params(params_out, scheme, bind_config).await,
+            SetupCommands::Fetch {
+                url,
+                sha256,
+                out_dir,
+                file_name,
+            } => zair_sdk::commands::fetch_setup_file(url, sha256, out_dir, file_name).await,
+            SetupCommands::Report { scheme } => {
+                zair_sdk::commands::sapling_constraint_report(scheme)
+                    .and_then(|report| print_constraint_report(&report).map_err(ZairError::Config))
             }
         },
         Commands::Config { command } => match command {
-            ConfigCommands::Build { args } => {
-                build_airdrop_configuration(
-                    args.config.into(),
+            ConfigCommands::Build { mut args } => {
+                let notify_url = args.notify_url.take();
+                run_with_notification(notify_url, "config build", config_build(args)).await
+            }
+            ConfigCommands::Audit { args } => config_audit(args).await,
+            ConfigCommands::AddEpoch { args } => {
+                let config_out = args.config_out.unwrap_or_else(|| args.config.clone());
+                zair_sdk::commands::add_airdrop_epoch(
+                    args.config,
+                    args.epoch,
+                    args.epoch_config,
+                    config_out,
+                )
+                .await
+            }
+            ConfigCommands::AddTransparent { args } => {
+                let config_out = args.config_out.unwrap_or_else(|| args.config.clone());
+                zair_sdk::commands::add_transparent_snapshot(
+                    args.config,
+                    args.addresses,
+                    args.lightwalletd,
+                    args.backend,
+                    args.target_transparent,
+                    args.snapshot_out_transparent,
+                    config_out,
+                )
+                .await
+            }
+            ConfigCommands::Sign { args } => {
+                zair_sdk::commands::sign_airdrop_configuration(
+                    args.config,
+                    args.artifacts,
+                    args.organizer_key,
+                    args.signature_out,
+                )
+                .await
+            }
+            ConfigCommands::VerifySignature { args } => {
+                zair_sdk::commands::verify_airdrop_configuration_signature(
+                    args.config,
+                    args.artifacts,
+                    args.signature_in,
+                    args.organizer_key,
+                )
+                .await
+            }
+        },
+        Commands::Snapshot { command } => match command {
+            SnapshotCommands::Reconcile { mut args } => {
+                let secondary = args.secondary_source();
+                let network = args.config.network()?;
+                let primary = SnapshotSource::Lightwalletd(
+                    resolve_lightwalletd_url(network, args.config.lightwalletd.as_deref()),
+                    args.config.backend,
+                );
+                reconcile_snapshot_sources(
+                    network,
+                    args.config.height,
+                    args.pool,
+                    primary,
+                    secondary,
+                    args.report_out,
+                )
+                .await
+            }
+            SnapshotCommands::Export { mut args } => {
+                let origin_heights = args.origin_heights();
+                let output = args.output();
+                zair_sdk::commands::export_snapshot(
+                    args.format,
                     args.pool,
-                    args.config_out,
-                    args.snapshot_out_sapling,
-                    args.snapshot_out_orchard,
-                    args.gap_tree_out_sapling,
-                    args.gap_tree_out_orchard,
-                    args.no_gap_tree,
-                    args.target_sapling,
-                    args.scheme_sapling,
-                    args.target_orchard,
-                    args.scheme_orchard,
+                    args.snapshot_sapling,
+                    args.snapshot_orchard,
+                    origin_heights,
+                    output,
+                )
+                .await
+            }
+            SnapshotCommands::Publish { args } => publish_snapshot_ipfs(
+                args.ipfs_api,
+                args.config,
+                SnapshotFiles {
+                    sapling_snapshot: args.snapshot_sapling,
+                    orchard_snapshot: args.snapshot_orchard,
+                    sapling_gap_tree: args.gap_tree_sapling,
+                    orchard_gap_tree: args.gap_tree_orchard,
+                },
+            )
+            .await
+            .and_then(|cid| {
+                writeln!(std::io::stdout().lock(), "{cid}").map_err(|e| ZairError::Config(e.into()))
+            }),
+            #[cfg(feature = "serve")]
+            SnapshotCommands::Serve { args } => {
+                zair_sdk::commands::serve_snapshot_files(
+                    args.addr,
+                    args.config,
+                    SnapshotFiles {
+                        sapling_snapshot: args.snapshot_sapling,
+                        orchard_snapshot: args.snapshot_orchard,
+                        sapling_gap_tree: args.gap_tree_sapling,
+                        orchard_gap_tree: args.gap_tree_orchard,
+                    },
                 )
                 .await
             }
@@ -94,7 +898,9 @@ async fn main() -> eyre::Result<()> {
         Commands::Claim { command } => match command {
             #[cfg(feature = "prove")]
             ClaimCommands::Run { args } => {
-                zair_sdk::commands::claim_run(
+                let notify_url = args.notify_url;
+                let rng_source = proof_rng_source(&args.proof_rng);
+                let run = zair_sdk::commands::claim_run(
                     args.lightwalletd,
                     args.snapshot_sapling,
                     args.snapshot_orchard,
@@ -113,30 +919,68 @@ async fn main() -> eyre::Result<()> {
                     args.orchard_params_mode,
                     args.message,
                     args.messages,
+                    args.expiry_height,
                     args.config,
-                )
-                .await
+                    rng_source,
+                    args.pool,
+                );
+                run_with_notification(notify_url, "claim run", run).await
             }
-            ClaimCommands::Prepare { args } => {
-                let ufvk = tokio::fs::read_to_string(&args.ufvk)
+            #[cfg(feature = "serve")]
+            ClaimCommands::Prepare {
+                args:
+                    mut args @ ClaimPrepareArgs {
+                        serve: Some(addr), ..
+                    },
+            } => match fetch_prepare_snapshot(&mut args).await {
+                Ok(()) => {
+                    zair_sdk::commands::serve_claim_prepare(
+                        addr,
+                        args.lightwalletd,
+                        args.snapshot_sapling,
+                        args.snapshot_orchard,
+                        args.gap_tree_sapling,
+                        args.gap_tree_orchard,
+                        args.gap_tree_mode,
+                        args.config,
+                        args.pool,
+                    )
                     .await
-                    .with_context(|| format!("Failed to read UFVK file {}", args.ufvk.display()))?;
-                zair_sdk::commands::airdrop_claim(
-                    args.lightwalletd,
-                    args.snapshot_sapling,
-                    args.snapshot_orchard,
-                    args.gap_tree_sapling,
-                    args.gap_tree_orchard,
-                    args.gap_tree_mode,
-                    ufvk.trim().to_owned(),
-                    args.birthday,
-                    args.claims_out,
-                    args.config,
-                )
-                .await
-            }
+                }
+                Err(e) => Err(e),
+            },
+            ClaimCommands::Prepare { mut args } => match fetch_prepare_snapshot(&mut args).await {
+                Ok(()) => {
+                    let birthday = args
+                        .birthday
+                        .ok_or_else(|| eyre::eyre!("--birthday is required"))?;
+                    let ufvk = zair_sdk::stdio::read_to_string(&args.ufvk)
+                        .await
+                        .with_context(|| {
+                            format!("Failed to read UFVK file {}", args.ufvk.display())
+                        })?;
+                    zair_sdk::commands::airdrop_claim(
+                        args.lightwalletd,
+                        args.snapshot_sapling,
+                        args.snapshot_orchard,
+                        args.gap_tree_sapling,
+                        args.gap_tree_orchard,
+                        args.gap_tree_mode,
+                        ufvk.trim().to_owned(),
+                        birthday,
+                        args.claims_out,
+                        args.config,
+                        args.pool,
+                        args.epoch,
+                        args.check_mempool,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            },
             #[cfg(feature = "prove")]
             ClaimCommands::Prove { args } => {
+                let rng_source = proof_rng_source(&args.proof_rng);
                 zair_sdk::commands::generate_claim_proofs(
                     args.claims_in,
                     args.proofs_out,
@@ -147,6 +991,49 @@ async fn main() -> eyre::Result<()> {
                     args.orchard_params_mode,
                     args.secrets_out,
                     args.config,
+                    rng_source,
+                    args.check_note_positions,
+                    args.lightwalletd,
+                    args.export_signing_request,
+                    args.pool,
+                )
+                .await
+            }
+            ClaimCommands::Message { args } => {
+                zair_sdk::commands::build_claim_messages(
+                    args.proofs_in,
+                    args.config,
+                    args.recipient,
+                    args.recipients,
+                    args.messages_out,
+                    args.pool,
+                )
+                .await
+            }
+            ClaimCommands::Sign { args } if args.detached => {
+                zair_sdk::commands::sign_detached_payload(
+                    args.proofs_in,
+                    args.secrets_in,
+                    args.seed,
+                    args.account,
+                    args.config,
+                    args.payload
+                        .ok_or_else(|| eyre::eyre!("--detached requires --payload"))?,
+                    args.detached_signatures_out,
+                    args.pool,
+                )
+                .await
+            }
+            ClaimCommands::Sign { args } if args.offline => {
+                zair_sdk::commands::sign_claim_signing_request(
+                    args.signing_request_in,
+                    args.seed,
+                    args.account,
+                    args.config,
+                    args.message,
+                    args.messages,
+                    args.expiry_height,
+                    args.signatures_out,
                 )
                 .await
             }
@@ -159,84 +1046,288 @@ async fn main() -> eyre::Result<()> {
                     args.config,
                     args.message,
                     args.messages,
+                    args.recipients,
+                    args.expiry_height,
                     args.submission_out,
+                    args.pool,
+                )
+                .await
+            }
+            ClaimCommands::Merge { args } => merge_claim_files(args).await,
+            ClaimCommands::Assemble { args } => {
+                zair_sdk::commands::assemble_claim_submission(
+                    args.proofs_in,
+                    args.signatures_in,
+                    args.submission_out,
+                )
+                .await
+            }
+            ClaimCommands::Countersign { args } => {
+                let submission_out = args
+                    .submission_out
+                    .unwrap_or_else(|| args.submission_in.clone());
+                zair_sdk::commands::countersign_claim_submission(
+                    args.submission_in,
+                    args.operator_key,
+                    args.config,
+                    submission_out,
+                )
+                .await
+            }
+            ClaimCommands::Qr { args } => claim_qr(args).await,
+            ClaimCommands::Package { args } => zair_sdk::commands::package_claim(
+                args.config,
+                args.submission_in,
+                args.proofs_in,
+                args.report_in,
+                args.message,
+                args.messages,
+                args.package_out,
+            )
+            .await
+            .map(|_| ()),
+            ClaimCommands::Attest { args } => {
+                zair_sdk::commands::attest_holdings(
+                    args.claims_in,
+                    args.proofs_in,
+                    args.secrets_in,
+                    args.attestation_out,
+                    args.pool,
+                )
+                .await
+            }
+            ClaimCommands::Total { args } => {
+                zair_sdk::commands::prove_claimed_value(
+                    args.claims_in,
+                    args.proofs_in,
+                    args.secrets_in,
+                    args.total_out,
+                    args.pool,
+                )
+                .await
+            }
+            ClaimCommands::SignTransparent { args } => {
+                zair_sdk::commands::sign_transparent_claims(
+                    args.config,
+                    args.snapshot_transparent,
+                    args.seed,
+                    args.account,
+                    args.address_count,
+                    args.message,
+                    args.epoch,
+                    args.expiry_height,
+                    args.transparent_claims_out,
                 )
                 .await
             }
         },
         Commands::Verify { command } => match command {
-            VerifyCommands::Run { args } => {
-                zair_sdk::commands::verify_run(
-                    args.sapling_vk,
-                    args.orchard_params,
-                    args.orchard_params_mode,
+            VerifyCommands::Run { args } => verify_run(args).await,
+            VerifyCommands::Proof { args } => verify_proof(args).await,
+            VerifyCommands::Signature { args } if args.detached => {
+                zair_sdk::commands::verify_detached_signatures(
+                    args.detached_signatures_in,
+                    args.payload
+                        .ok_or_else(|| eyre::eyre!("--detached requires --payload"))?,
+                    args.config,
+                    args.pool,
+                )
+                .await
+            }
+            VerifyCommands::Signature { args } => {
+                zair_sdk::commands::verify_claim_submission_signature(
                     args.submission_in,
                     args.message,
                     args.messages,
                     args.config,
+                    args.current_height,
+                    args.pool,
                 )
                 .await
             }
-            VerifyCommands::Proof { args } => {
-                zair_sdk::commands::verify_claim_proofs(
-                    args.proofs_in,
+            #[cfg(feature = "serve")]
+            VerifyCommands::Serve { args } => {
+                zair_sdk::commands::serve_verification(
+                    args.addr,
                     args.sapling_vk,
                     args.orchard_params,
                     args.orchard_params_mode,
                     args.config,
+                    args.api_keys,
+                    args.rate_limit,
+                    args.audit_log,
                 )
                 .await
             }
-            VerifyCommands::Signature { args } => {
-                zair_sdk::commands::verify_claim_submission_signature(
-                    args.submission_in,
-                    args.message,
-                    args.messages,
+            VerifyCommands::Dedupe { args } => {
+                zair_sdk::commands::dedupe_claim_submissions(args.submissions_dir, args.report_out)
+                    .await
+            }
+            VerifyCommands::Package { args } => {
+                zair_sdk::commands::verify_claim_package(
+                    args.package_in,
+                    args.extract_dir,
+                    args.sapling_vk,
+                    args.orchard_params,
+                    args.orchard_params_mode,
+                    args.current_height,
+                    Some(args.report_out),
+                )
+                .await
+            }
+            VerifyCommands::Attestation { args } => verify_attestation(args).await,
+            VerifyCommands::Total { args } => verify_total(args).await,
+            VerifyCommands::AuditTrail { args } => {
+                verify_audit_trail(args.trail_in).await.map(|entries| {
+                    tracing::info!(entries = entries.len(), "Audit trail is intact");
+                })
+            }
+            VerifyCommands::Transparent { args } => {
+                zair_sdk::commands::verify_transparent_claims(
+                    args.transparent_claims_in,
+                    args.snapshot_transparent,
                     args.config,
+                    args.message,
+                    args.current_height,
                 )
                 .await
             }
         },
         Commands::Key { command } => match command {
-            KeyCommands::DeriveSeed { args } => {
-                let mnemonic_source = if args.mnemonic_stdin {
-                    zair_sdk::commands::MnemonicSource::Stdin
-                } else if let Some(path) = args.mnemonic_file {
-                    zair_sdk::commands::MnemonicSource::File(path)
-                } else {
-                    zair_sdk::commands::MnemonicSource::Prompt
-                };
-                zair_sdk::commands::key_derive_seed(
-                    args.output,
-                    mnemonic_source,
-                    args.no_passphrase,
-                )
-                .await
-            }
-            KeyCommands::DeriveUfvk { args } => {
-                let mnemonic_source = if args.mnemonic_stdin {
-                    Some(zair_sdk::commands::MnemonicSource::Stdin)
-                } else {
-                    args.mnemonic_file
-                        .map(zair_sdk::commands::MnemonicSource::File)
-                };
-                zair_sdk::commands::key_derive_ufvk(
+            KeyCommands::DeriveSeed { args } => derive_seed(args, cli.non_interactive).await,
+            KeyCommands::DeriveUfvk { args } => derive_ufvk(args, cli.non_interactive).await,
+            KeyCommands::ExportProvingKey { args } => {
+                export_proving_key(args, cli.non_interactive).await
+            }
+            KeyCommands::Import { args } => {
+                zair_sdk::commands::key_import(
                     args.network,
-                    args.account,
-                    args.seed,
-                    mnemonic_source,
-                    args.no_passphrase,
-                    args.output,
+                    args.from,
+                    args.format,
+                    args.account_name,
+                    args.seed_output,
+                    args.ufvk_output,
                 )
                 .await
             }
         },
+        Commands::Prove { command } => match command {
+            ProveCommands::Submit { args } => prove_submit(args).await,
+            ProveCommands::Collect { args } => prove_collect(args).await,
+            #[cfg(feature = "prove")]
+            ProveCommands::Worker { args } => {
+                zair_sdk::commands::run_proving_worker(
+                    args.queue_dir,
+                    args.config,
+                    args.sapling_pk,
+                    args.orchard_params,
+                    args.orchard_params_mode,
+                    Duration::from_secs(args.poll_interval),
+                    args.once,
+                )
+                .await
+            }
+        },
+        Commands::Watch { args } => watch(args).await,
+        Commands::Doctor { args } => doctor(args).await,
+        Commands::Stats { args } => stats(args).await,
+        Commands::Fetch { manifest, out_dir } => fetch_artifacts(manifest, out_dir).await,
+        Commands::SelfUpdate {
+            manifest,
+            release_key,
+            check,
+        } => match resolve_release_key(release_key) {
+            Ok(release_key) => self_update(
+                manifest,
+                release_key,
+                env!("CARGO_PKG_VERSION").to_owned(),
+                check,
+            )
+            .await
+            .and_then(|outcome| print_self_update(&outcome).map_err(ZairError::Config)),
+            Err(e) => Err(ZairError::Config(e)),
+        },
+        Commands::Paths => print_paths().map_err(ZairError::Config),
+        Commands::Version { json } => print_version(json).map_err(ZairError::Config),
+        Commands::Bench { sizes } => run_benchmark(sizes)
+            .await
+            .and_then(|report| print_benchmark(&report).map_err(ZairError::Config)),
+    };
+
+    let res = match cli.audit_trail {
+        Some(audit_trail) => {
+            let exit_code = res.as_ref().map_or_else(ZairError::exit_code, |()| 0);
+            let command = std::env::args_os()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            let audited = append_audit_entry(audit_trail, command, exit_code).await;
+            res.and(audited.map(|_| ()))
+        }
+        None => res,
     };
 
     if let Err(e) = res {
-        tracing::error!("Error: {:?}", e);
-        std::process::exit(1);
+        exit_with(&e);
     }
 
     Ok(())
 }
+
+/// Log `e` with its category and exit with the status of the category, removing the outputs
+/// that were not finished.
+fn exit_with(e: &ZairError) -> ! {
+    tracing::error!(code = e.code(), "Error: {:?}", e.report());
+    zair_sdk::paths::remove_partial_outputs();
+    std::process::exit(e.exit_code());
+}
+
+/// Wait for Ctrl-C or SIGTERM, then remove the half-written outputs and exit with `128 + signal`.
+///
+/// Finished outputs are always complete, since they are renamed into place only once written.
+/// Interrupted downloads keep their `.part` files and resume when the command is run again.
+async fn exit_on_signal() {
+    let (signal, code) = shutdown_signal().await;
+    let removed = zair_sdk::paths::remove_partial_outputs();
+    for file in &removed {
+        tracing::info!(file = %file.display(), "Removed half-written output");
+    }
+    if removed.is_empty() {
+        tracing::warn!(signal, "Interrupted");
+    } else {
+        tracing::warn!(
+            signal,
+            "Interrupted; no complete output was touched. Run the same command again to redo the \
+             unfinished outputs; interrupted downloads resume from their .part files"
+        );
+    }
+    std::process::exit(code);
+}
+
+/// Name and exit status of the first Ctrl-C or SIGTERM received.
+async fn shutdown_signal() -> (&'static str, i32) {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    {
+        let terminate = async {
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(mut terminate) => {
+                    terminate.recv().await;
+                }
+                Err(_) => std::future::pending::<()>().await,
+            }
+        };
+        tokio::select! {
+            () = ctrl_c => ("SIGINT", 130),
+            () = terminate => ("SIGTERM", 143),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        ctrl_c.await;
+        ("Ctrl-C", 130)
+    }
+}
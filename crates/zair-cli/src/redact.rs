@@ -0,0 +1,91 @@
+//! Redaction of sensitive hex in log output.
+//!
+//! Logs name claims by their nullifiers and can carry keys and seeds as hex. Unless
+//! `--log-sensitive` is given, every run of at least [`MIN_REDACTED_HEX_DIGITS`] hex digits in an
+//! event's message, its fields and the fields of its spans is replaced with [`REDACTED`]. Hashes
+//! of the same length, such as claim IDs and tree roots, are redacted as well.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use tracing::field::Field;
+use tracing_subscriber::field::MakeExt as _;
+use tracing_subscriber::fmt::FormatFields;
+use tracing_subscriber::fmt::format::{Writer, debug_fn};
+
+/// Shortest run of hex digits that is redacted: the encoding of a 32-byte value.
+pub const MIN_REDACTED_HEX_DIGITS: usize = 64;
+/// Replacement of a redacted hex run.
+pub const REDACTED: &str = "<redacted>";
+
+/// Replace every run of at least [`MIN_REDACTED_HEX_DIGITS`] hex digits in `text` with
+/// [`REDACTED`].
+#[must_use]
+pub fn redact_hex(text: &str) -> Cow<'_, str> {
+    let mut redacted = String::new();
+    let mut copied = 0;
+    let mut run_start = None;
+    for (index, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_ascii_hexdigit(), run_start) {
+            (true, None) => run_start = Some(index),
+            (false, Some(start)) => {
+                run_start = None;
+                if index.saturating_sub(start) >= MIN_REDACTED_HEX_DIGITS {
+                    redacted.push_str(text.get(copied..start).unwrap_or_default());
+                    redacted.push_str(REDACTED);
+                    copied = index;
+                }
+            }
+            _ => {}
+        }
+    }
+    if copied == 0 {
+        return Cow::Borrowed(text);
+    }
+    redacted.push_str(text.get(copied..).unwrap_or_default());
+    Cow::Owned(redacted)
+}
+
+/// Field formatter of the log output, redacting sensitive hex unless `log_sensitive` is set.
+#[must_use]
+pub fn fields(log_sensitive: bool) -> impl for<'writer> FormatFields<'writer> + 'static {
+    debug_fn(
+        move |writer: &mut Writer<'_>, field: &Field, value: &dyn fmt::Debug| {
+            let value = format!("{value:?}");
+            let value = if log_sensitive {
+                Cow::Borrowed(value.as_str())
+            } else {
+                redact_hex(&value)
+            };
+            if field.name() == "message" {
+                write!(writer, "{value}")
+            } else {
+                write!(writer, "{}={value}", field.name())
+            }
+        },
+    )
+    .delimited(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_hex_runs_are_redacted() {
+        let nullifier = "ab".repeat(32);
+        let seed = "0f".repeat(64);
+        assert_eq!(
+            redact_hex(&format!("nullifier {nullifier} at height 3663119")),
+            "nullifier <redacted> at height 3663119"
+        );
+        assert_eq!(
+            redact_hex(&format!("seed={seed}, short={}", "cd".repeat(31))),
+            format!("seed=<redacted>, short={}", "cd".repeat(31))
+        );
+        assert!(matches!(
+            redact_hex("nothing to hide"),
+            Cow::Borrowed("nothing to hide")
+        ));
+    }
+}
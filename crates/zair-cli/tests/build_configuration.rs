@@ -147,7 +147,8 @@ fn test_build_airdrop_configuration() {
             .sapling
             .as_ref()
             .expect("sapling should be present")
-            .target_id,
+            .target_id
+            .as_str(),
         "ZAIRTEST"
     );
     assert_eq!(
@@ -155,7 +156,8 @@ fn test_build_airdrop_configuration() {
             .orchard
             .as_ref()
             .expect("orchard should be present")
-            .target_id,
+            .target_id
+            .as_str(),
         "ZAIRTEST:O"
     );
 }
@@ -0,0 +1,281 @@
+//! Offline `config build` and claim pipeline runs against the in-memory lightwalletd server.
+
+#![allow(
+    clippy::indexing_slicing,
+    reason = "Test code should panic on invalid data"
+)]
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use tempfile::tempdir;
+use test_lightwalletd::{MockChain, TestLightwalletd};
+#[cfg(feature = "deterministic-rng")]
+use zair_core::base::Pool;
+use zair_core::schema::config::{AirdropConfiguration, AirdropNetwork};
+#[cfg(feature = "deterministic-rng")]
+use zair_testgen::{FixtureSpec, NoteSpec, generate};
+
+/// Testnet Orchard activation height (NU5).
+const ORCHARD_ACTIVATION: u64 = 1_842_420;
+const SNAPSHOT_HEIGHT: u64 = 1_842_500;
+
+fn orchard_nf(v: u8) -> [u8; 32] {
+    let mut bytes = [0_u8; 32];
+    bytes[0] = v;
+    bytes
+}
+
+fn chain() -> MockChain {
+    let mut chain = MockChain::new();
+    chain.push_nullifier_block(300_000, &[[0x11; 32], [0x22; 32]], &[]);
+    chain.push_nullifier_block(
+        ORCHARD_ACTIVATION.saturating_add(1),
+        &[[0x33; 32]],
+        &[orchard_nf(5), orchard_nf(9)],
+    );
+    // Beyond the snapshot height: must not be included.
+    chain.push_nullifier_block(
+        SNAPSHOT_HEIGHT.saturating_add(1),
+        &[[0x44; 32]],
+        &[orchard_nf(12)],
+    );
+    chain
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn config_build_against_mock_lightwalletd() {
+    let server = TestLightwalletd::spawn(chain())
+        .await
+        .expect("server should start");
+    let dir = tempdir().expect("temp dir");
+    let path = dir.path().to_path_buf();
+    let url = server.url();
+
+    tokio::task::spawn_blocking(move || {
+        cargo_bin_cmd!("zair")
+            .current_dir(&path)
+            .args([
+                "config",
+                "build",
+                "--network",
+                "testnet",
+                "--height",
+                &SNAPSHOT_HEIGHT.to_string(),
+                "--lightwalletd",
+                &url,
+                "--snapshot-out-sapling",
+                "snapshot-sapling.bin",
+                "--snapshot-out-orchard",
+                "snapshot-orchard.bin",
+                "--gap-tree-out-sapling",
+                "gaptree-sapling.bin",
+                "--gap-tree-out-orchard",
+                "gaptree-orchard.bin",
+            ])
+            .assert()
+            .success();
+    })
+    .await
+    .expect("config build task should complete");
+
+    let config: AirdropConfiguration = serde_json::from_str(
+        &std::fs::read_to_string(dir.path().join("config.json")).expect("config.json exists"),
+    )
+    .expect("config.json is valid");
+    assert_eq!(config.network, AirdropNetwork::Testnet);
+    assert_eq!(config.snapshot_height, SNAPSHOT_HEIGHT);
+    assert!(config.sapling.is_some());
+    assert!(config.orchard.is_some());
+
+    let sapling = std::fs::read(dir.path().join("snapshot-sapling.bin")).expect("sapling snapshot");
+    let orchard = std::fs::read(dir.path().join("snapshot-orchard.bin")).expect("orchard snapshot");
+    assert_eq!(
+        sapling.len(),
+        3 * 32,
+        "three Sapling nullifiers up to the snapshot"
+    );
+    assert_eq!(
+        orchard.len(),
+        2 * 32,
+        "two Orchard nullifiers up to the snapshot"
+    );
+}
+
+/// Seed of the fixture wallet, written as the claimant's seed file.
+#[cfg(feature = "deterministic-rng")]
+const FIXTURE_SEED: [u8; 64] = [7_u8; 64];
+
+#[cfg(feature = "deterministic-rng")]
+fn fixture_spec() -> FixtureSpec {
+    FixtureSpec {
+        network: AirdropNetwork::Testnet,
+        account: 0,
+        start_height: 1_900_001,
+        snapshot_height: 1_900_010,
+        tip_height: 1_900_012,
+        rng_seed: 42,
+        notes: vec![
+            NoteSpec {
+                pool: Pool::Sapling,
+                value: 100_000,
+                height: 1_900_002,
+                spent_at: None,
+            },
+            NoteSpec {
+                pool: Pool::Sapling,
+                value: 25_000,
+                height: 1_900_003,
+                spent_at: Some(1_900_005),
+            },
+            NoteSpec {
+                pool: Pool::Orchard,
+                value: 70_000,
+                height: 1_900_004,
+                spent_at: None,
+            },
+        ],
+    }
+}
+
+#[cfg(feature = "deterministic-rng")]
+#[allow(
+    clippy::too_many_lines,
+    reason = "End-to-end test drives every pipeline step"
+)]
+#[tokio::test(flavor = "multi_thread")]
+async fn claim_run_verifies_against_mock_lightwalletd() {
+    let spec = fixture_spec();
+    let fixture = generate(&spec, &FIXTURE_SEED).expect("fixture should generate");
+    let server = TestLightwalletd::spawn(fixture.mock_chain())
+        .await
+        .expect("server should start");
+    let dir = tempdir().expect("temp dir");
+    let path = dir.path().to_path_buf();
+    let url = server.url();
+
+    std::fs::write(dir.path().join("seed.txt"), hex::encode(FIXTURE_SEED)).expect("write seed");
+    std::fs::write(dir.path().join("claim-message.bin"), b"mock-pipeline").expect("write message");
+
+    tokio::task::spawn_blocking(move || {
+        cargo_bin_cmd!("zair")
+            .current_dir(&path)
+            .args([
+                "config",
+                "build",
+                "--network",
+                "testnet",
+                "--height",
+                &spec.snapshot_height.to_string(),
+                "--lightwalletd",
+                &url,
+                "--pool",
+                "both",
+                "--scheme-sapling",
+                "native",
+                "--scheme-orchard",
+                "native",
+                "--snapshot-out-sapling",
+                "snapshot-sapling.bin",
+                "--snapshot-out-orchard",
+                "snapshot-orchard.bin",
+                "--gap-tree-out-sapling",
+                "gaptree-sapling.bin",
+                "--gap-tree-out-orchard",
+                "gaptree-orchard.bin",
+            ])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("zair")
+            .current_dir(&path)
+            .args([
+                "setup",
+                "sapling",
+                "--scheme",
+                "native",
+                "--pk-out",
+                "setup-sapling-pk.params",
+                "--vk-out",
+                "setup-sapling-vk.params",
+            ])
+            .assert()
+            .success();
+        cargo_bin_cmd!("zair")
+            .current_dir(&path)
+            .args([
+                "setup",
+                "orchard",
+                "--scheme",
+                "native",
+                "--params-out",
+                "setup-orchard-params.bin",
+            ])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("zair")
+            .current_dir(&path)
+            .args([
+                "claim",
+                "run",
+                "--seed",
+                "seed.txt",
+                "--birthday",
+                &spec.start_height.to_string(),
+                "--lightwalletd",
+                &url,
+                "--message",
+                "claim-message.bin",
+                "--snapshot-sapling",
+                "snapshot-sapling.bin",
+                "--snapshot-orchard",
+                "snapshot-orchard.bin",
+                "--gap-tree-sapling",
+                "gaptree-sapling.bin",
+                "--gap-tree-orchard",
+                "gaptree-orchard.bin",
+                "--sapling-pk",
+                "setup-sapling-pk.params",
+                "--orchard-params",
+                "setup-orchard-params.bin",
+                "--deterministic-rng",
+                "7",
+            ])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("zair")
+            .current_dir(&path)
+            .args([
+                "verify",
+                "run",
+                "--sapling-vk",
+                "setup-sapling-vk.params",
+                "--orchard-params",
+                "setup-orchard-params.bin",
+                "--submission-in",
+                "claim-submission.json",
+                "--message",
+                "claim-message.bin",
+            ])
+            .assert()
+            .success();
+    })
+    .await
+    .expect("claim pipeline task should complete");
+
+    let submission: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(dir.path().join("claim-submission.json"))
+            .expect("claim-submission.json exists"),
+    )
+    .expect("claim-submission.json is valid");
+    assert_eq!(
+        submission["sapling"].as_array().map(Vec::len),
+        Some(1),
+        "only the unspent Sapling note is claimed"
+    );
+    assert_eq!(
+        submission["orchard"].as_array().map(Vec::len),
+        Some(1),
+        "the Orchard note is claimed"
+    );
+}
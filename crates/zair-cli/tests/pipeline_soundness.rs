@@ -111,6 +111,42 @@ fn soundness_tamper_spend_auth_sig() {
     );
 }
 
+#[test]
+#[ignore = "Full pipeline: requires network access + heavy computation"]
+fn soundness_tamper_submission_nonce() {
+    ensure_claim_run("native");
+    let mut data = load_json(&submission_path("native"));
+    flip_hex_byte(&mut data["nonce"], 0);
+    let tampered = write_tampered("tampered-submission-nonce.json", &data);
+    let stderr = verify_signature_failure_stderr("native", &tampered, &message_path());
+    assert_contains(&stderr, "submission signatures failed verification");
+}
+
+#[test]
+#[ignore = "Full pipeline: requires network access + heavy computation"]
+fn soundness_tamper_submission_claim_id() {
+    ensure_claim_run("native");
+    let mut data = load_json(&submission_path("native"));
+    flip_hex_byte(&mut data["sapling"][0]["claim_id"], 0);
+    let tampered = write_tampered("tampered-submission-claim-id.json", &data);
+    let stderr = verify_signature_failure_stderr("native", &tampered, &message_path());
+    assert_contains(&stderr, "Sapling claim id mismatch at index 0");
+}
+
+#[test]
+#[ignore = "Full pipeline: requires network access + heavy computation"]
+fn soundness_tamper_submission_expiry_height() {
+    ensure_claim_run("native");
+    let mut data = load_json(&submission_path("native"));
+    let expiry_height = data["expiry_height"]
+        .as_u64()
+        .expect("expiry_height is a number");
+    data["expiry_height"] = expiry_height.saturating_sub(1).into();
+    let tampered = write_tampered("tampered-submission-expiry-height.json", &data);
+    let stderr = verify_signature_failure_stderr("native", &tampered, &message_path());
+    assert_contains(&stderr, "submission signatures failed verification");
+}
+
 #[test]
 #[ignore = "Full pipeline: requires network access + heavy computation"]
 fn soundness_tamper_orchard_spend_auth_sig() {
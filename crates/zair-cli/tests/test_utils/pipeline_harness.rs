@@ -27,6 +27,14 @@ fn test_height() -> String {
     test_env("ZAIR_TEST_HEIGHT")
 }
 
+/// End of the claim window, far enough above the snapshot height to never expire in tests.
+fn test_claim_expiry_height() -> String {
+    let height: u64 = test_height()
+        .parse()
+        .expect("ZAIR_TEST_HEIGHT is a block height");
+    height.saturating_add(1_000_000).to_string()
+}
+
 fn test_birthday() -> String {
     test_env("ZAIR_TEST_BIRTHDAY")
 }
@@ -114,6 +122,7 @@ fn ensure_config() {
     let network = test_network();
     let height = test_height();
     let lightwalletd = test_lightwalletd();
+    let claim_expiry_height = test_claim_expiry_height();
 
     zair()
         .args([
@@ -123,6 +132,8 @@ fn ensure_config() {
             &network,
             "--height",
             &height,
+            "--claim-expiry-height",
+            &claim_expiry_height,
             "--lightwalletd",
             &lightwalletd,
             "--pool",
@@ -388,6 +399,14 @@ pub fn run_pipeline(scheme: &str) {
     }
 
     let submission = load_json(&submission_out);
+    assert!(
+        submission.get("nonce").is_some_and(Value::is_string),
+        "submission should have a nonce"
+    );
+    assert!(
+        submission.get("expiry_height").is_some_and(Value::is_u64),
+        "submission should have an expiry_height"
+    );
     let sapling_subs = submission["sapling"]
         .as_array()
         .expect("sapling submissions should be an array");
@@ -420,6 +439,10 @@ pub fn run_pipeline(scheme: &str) {
             claim.get("airdrop_nullifier").is_some_and(Value::is_string),
             "signed claim should have airdrop_nullifier"
         );
+        assert!(
+            claim.get("claim_id").is_some_and(Value::is_string),
+            "signed claim should have claim_id"
+        );
     }
 }
 
@@ -0,0 +1,10 @@
+//! Parameters the claim circuits are compiled with.
+
+/// Depth of the Sapling and Orchard note commitment trees.
+pub const NOTE_COMMITMENT_TREE_DEPTH: u8 = 32;
+
+/// Depth of the nullifier non-membership trees.
+pub const NULLIFIER_GAP_TREE_DEPTH: u8 = 32;
+
+/// Personalization of the Sapling hiding nullifier hash.
+pub const HIDING_NF_PERSONALIZATION: [u8; 8] = *b"ZAIRTEST";
@@ -3,21 +3,35 @@
 use blake2b_simd::Params;
 use thiserror::Error;
 
-use super::Pool;
+use super::{Nullifier, Pool};
 
 /// Domain marker prepended to submission-signature digest preimages.
 pub const SIGNATURE_PREIMAGE_TAG: &[u8; 8] = b"ZAIR_SIG";
 /// Protocol version byte included in signature digest preimages.
-pub const SIGNATURE_VERSION: u8 = 1;
+pub const SIGNATURE_VERSION: u8 = 3;
+/// Domain marker prepended to signature domain preimages.
+pub const SIGNATURE_DOMAIN_PREIMAGE_TAG: &[u8; 8] = b"ZAIR_DOM";
+/// Domain marker prepended to detached-signature digest preimages.
+pub const DETACHED_SIGNATURE_PREIMAGE_TAG: &[u8; 8] = b"ZAIR_DET";
+/// Domain marker prepended to claim identifier preimages.
+pub const CLAIM_ID_PREIMAGE_TAG: &[u8; 8] = b"ZAIR_CID";
+/// Domain marker prepended to transparent claim signature digest preimages.
+pub const TRANSPARENT_SIGNATURE_PREIMAGE_TAG: &[u8; 8] = b"ZAIR_TSG";
+/// Domain marker prepended to viewing key fingerprint salt preimages.
+pub const KEY_FINGERPRINT_SALT_PREIMAGE_TAG: &[u8; 8] = b"ZAIR_KFS";
+/// Domain marker prepended to viewing key fingerprint preimages.
+pub const KEY_FINGERPRINT_PREIMAGE_TAG: &[u8; 8] = b"ZAIR_KFP";
+/// Domain marker prepended to organizer-signature digest preimages.
+pub const ORGANIZER_SIGNATURE_PREIMAGE_TAG: &[u8; 8] = b"ZAIR_ORG";
 
 #[derive(Debug, Error)]
 pub enum DigestError {
     /// The target ID length exceeds 255 bytes.
     #[error("target ID length exceeds 255 bytes")]
     TargetIdTooLong,
-    /// The proof length exceeds [`u32::MAX`].
-    #[error("proof length exceeds u32::MAX")]
-    ProofTooLong,
+    /// The chain ID length exceeds 255 bytes.
+    #[error("chain ID length exceeds 255 bytes")]
+    ChainIdTooLong,
 }
 
 /// Hash arbitrary bytes to 32 bytes with `BLAKE2b`.
@@ -35,30 +49,250 @@ pub fn hash_message(message: &[u8]) -> [u8; 32] {
     hash_bytes(message)
 }
 
-/// Build the 32-byte message signed by spend authorization keys.
+/// Derive the stable identifier of a claim.
 ///
 /// Preimage layout:
-/// `ZAIR_SIG_V1 || version:u8 || pool:u8 || target_id_len:u8 || target_id || proof_hash ||
-/// message_hash`
+/// `ZAIR_CID || pool:u8 || target_id_len:u8 || target_id || nullifier_gap_root ||
+/// airdrop_nullifier`
+///
+/// The identifier depends only on the airdrop nullifier and the snapshot it is claimed against,
+/// so the prepared, proof and submission entries of one claim share it, and two submissions of
+/// the same note in the same airdrop collide.
 ///
 /// # Errors
 /// Returns an error if the target ID length exceeds 255 bytes.
-pub fn signature_digest(
+pub fn claim_id(
+    pool: Pool,
+    target_id: &[u8],
+    nullifier_gap_root: &[u8; 32],
+    airdrop_nullifier: &Nullifier,
+) -> Result<[u8; 32], DigestError> {
+    let target_len = u8::try_from(target_id.len()).map_err(|_| DigestError::TargetIdTooLong)?;
+
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(CLAIM_ID_PREIMAGE_TAG);
+    preimage.push(pool.as_byte());
+    preimage.push(target_len);
+    preimage.extend_from_slice(target_id);
+    preimage.extend_from_slice(nullifier_gap_root);
+    preimage.extend_from_slice(airdrop_nullifier.as_ref());
+
+    Ok(hash_bytes(&preimage))
+}
+
+/// Derive the domain tag that separates the claim signatures of one airdrop deployment.
+///
+/// Preimage layout:
+/// `ZAIR_DOM || pool:u8 || target_id_len:u8 || target_id || chain_id_len:u8 || chain_id`
+///
+/// The chain ID names the chain the claims are submitted to, so signatures made for a testnet
+/// rehearsal of an airdrop are never valid for its mainnet deployment. An empty chain ID leaves
+/// the domain to the target ID alone.
+///
+/// # Errors
+/// Returns an error if the target ID or chain ID length exceeds 255 bytes.
+pub fn signature_domain(
     pool: Pool,
     target_id: &[u8],
+    chain_id: &[u8],
+) -> Result<[u8; 32], DigestError> {
+    let target_len = u8::try_from(target_id.len()).map_err(|_| DigestError::TargetIdTooLong)?;
+    let chain_len = u8::try_from(chain_id.len()).map_err(|_| DigestError::ChainIdTooLong)?;
+
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(SIGNATURE_DOMAIN_PREIMAGE_TAG);
+    preimage.push(pool.as_byte());
+    preimage.push(target_len);
+    preimage.extend_from_slice(target_id);
+    preimage.push(chain_len);
+    preimage.extend_from_slice(chain_id);
+
+    Ok(hash_bytes(&preimage))
+}
+
+/// Build the 32-byte message signed by spend authorization keys.
+///
+/// Preimage layout:
+/// `ZAIR_SIG || version:u8 || domain || proof_hash || message_hash || nonce ||
+/// expiry_height:u64le`
+///
+/// The `domain` from [`signature_domain`] binds the pool, target ID and chain ID. The submission
+/// `nonce` and `expiry_height` make every signature specific to one submission and claim window,
+/// so a captured submission cannot be replayed once it has expired.
+#[must_use]
+pub fn signature_digest(
+    domain: &[u8; 32],
+    proof_hash: &[u8; 32],
+    message_hash: &[u8; 32],
+    nonce: &[u8; 32],
+    expiry_height: u64,
+) -> [u8; 32] {
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(SIGNATURE_PREIMAGE_TAG);
+    preimage.push(SIGNATURE_VERSION);
+    preimage.extend_from_slice(domain);
+    preimage.extend_from_slice(proof_hash);
+    preimage.extend_from_slice(message_hash);
+    preimage.extend_from_slice(nonce);
+    preimage.extend_from_slice(&expiry_height.to_le_bytes());
+
+    hash_bytes(&preimage)
+}
+
+/// Build the 32-byte digest of an external payload signed by spend authorization keys.
+///
+/// Preimage layout:
+/// `ZAIR_DET || version:u8 || domain || proof_hash || payload_hash`
+///
+/// A detached signature carries no nonce or expiry height: the party that constructs the payload
+/// is responsible for making it unique. The distinct tag keeps a detached signature from ever
+/// verifying as a submission signature.
+#[must_use]
+pub fn detached_signature_digest(
+    domain: &[u8; 32],
     proof_hash: &[u8; 32],
+    payload_hash: &[u8; 32],
+) -> [u8; 32] {
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(DETACHED_SIGNATURE_PREIMAGE_TAG);
+    preimage.push(SIGNATURE_VERSION);
+    preimage.extend_from_slice(domain);
+    preimage.extend_from_slice(proof_hash);
+    preimage.extend_from_slice(payload_hash);
+
+    hash_bytes(&preimage)
+}
+
+/// Build the 32-byte digest a transparent address key signs to claim its snapshot balance.
+///
+/// Preimage layout:
+/// `ZAIR_TSG || version:u8 || target_id_len:u8 || target_id || chain_id_len:u8 || chain_id ||
+/// utxo_set_hash || BLAKE2b(address) || value:u64le || message_hash || nonce ||
+/// expiry_height:u64le`
+///
+/// The UTXO set hash binds the signature to one transparent snapshot, and the target ID and chain
+/// ID to one airdrop deployment, as [`signature_domain`] does for the shielded pools. The nonce and
+/// expiry height are bound as in [`signature_digest`].
+///
+/// # Errors
+/// Returns an error if the target ID or chain ID length exceeds 255 bytes.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Every preimage field is an explicit parameter"
+)]
+pub fn transparent_signature_digest(
+    target_id: &[u8],
+    chain_id: &[u8],
+    utxo_set_hash: &[u8; 32],
+    address: &str,
+    value: u64,
     message_hash: &[u8; 32],
+    nonce: &[u8; 32],
+    expiry_height: u64,
 ) -> Result<[u8; 32], DigestError> {
     let target_len = u8::try_from(target_id.len()).map_err(|_| DigestError::TargetIdTooLong)?;
+    let chain_len = u8::try_from(chain_id.len()).map_err(|_| DigestError::ChainIdTooLong)?;
 
     let mut preimage = Vec::new();
-    preimage.extend_from_slice(SIGNATURE_PREIMAGE_TAG);
+    preimage.extend_from_slice(TRANSPARENT_SIGNATURE_PREIMAGE_TAG);
     preimage.push(SIGNATURE_VERSION);
-    preimage.push(pool.as_byte());
     preimage.push(target_len);
     preimage.extend_from_slice(target_id);
-    preimage.extend_from_slice(proof_hash);
+    preimage.push(chain_len);
+    preimage.extend_from_slice(chain_id);
+    preimage.extend_from_slice(utxo_set_hash);
+    preimage.extend_from_slice(&hash_bytes(address.as_bytes()));
+    preimage.extend_from_slice(&value.to_le_bytes());
     preimage.extend_from_slice(message_hash);
+    preimage.extend_from_slice(nonce);
+    preimage.extend_from_slice(&expiry_height.to_le_bytes());
 
     Ok(hash_bytes(&preimage))
 }
+
+/// Derive the salt of the viewing key fingerprints of one airdrop snapshot.
+///
+/// Preimage layout:
+/// `ZAIR_KFS || BLAKE2b(network) || snapshot_height:u64le || BLAKE2b(chain_id)`
+///
+/// The salt keeps the fingerprints of one key from linking its claims across airdrops.
+#[must_use]
+pub fn key_fingerprint_salt(network: &str, snapshot_height: u64, chain_id: &[u8]) -> [u8; 32] {
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(KEY_FINGERPRINT_SALT_PREIMAGE_TAG);
+    preimage.extend_from_slice(&hash_bytes(network.as_bytes()));
+    preimage.extend_from_slice(&snapshot_height.to_le_bytes());
+    preimage.extend_from_slice(&hash_bytes(chain_id));
+
+    hash_bytes(&preimage)
+}
+
+/// Fingerprint the encoded viewing key of `pool` under a salt from [`key_fingerprint_salt`].
+///
+/// Preimage layout:
+/// `ZAIR_KFP || salt || pool:u8 || viewing_key`
+///
+/// The fingerprint identifies the wallet that prepared a claims file without revealing its
+/// viewing key.
+#[must_use]
+pub fn key_fingerprint(salt: &[u8; 32], pool: Pool, viewing_key: &[u8]) -> [u8; 32] {
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(KEY_FINGERPRINT_PREIMAGE_TAG);
+    preimage.extend_from_slice(salt);
+    preimage.push(pool.as_byte());
+    preimage.extend_from_slice(viewing_key);
+
+    hash_bytes(&preimage)
+}
+
+/// Build the 32-byte digest of a published airdrop signed by the organizer key.
+///
+/// Preimage layout:
+/// `ZAIR_ORG || config_hash || has_manifest:u8 || artifacts_manifest_hash?`
+///
+/// Both hashes are [`hash_bytes`] of the exact file bytes, so the signature binds the snapshot
+/// roots and target IDs of the configuration and every file the manifest lists.
+#[must_use]
+pub fn organizer_signature_digest(
+    config_hash: &[u8; 32],
+    artifacts_manifest_hash: Option<&[u8; 32]>,
+) -> [u8; 32] {
+    let mut preimage = Vec::new();
+    preimage.extend_from_slice(ORGANIZER_SIGNATURE_PREIMAGE_TAG);
+    preimage.extend_from_slice(config_hash);
+    match artifacts_manifest_hash {
+        Some(hash) => {
+            preimage.push(1);
+            preimage.extend_from_slice(hash);
+        }
+        None => preimage.push(0),
+    }
+
+    hash_bytes(&preimage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_domain_separates_chains_and_pools() {
+        let testnet = signature_domain(Pool::Sapling, b"ZAIRTEST", b"namada-testnet").expect("ok");
+        let mainnet = signature_domain(Pool::Sapling, b"ZAIRTEST", b"namada-mainnet").expect("ok");
+        let orchard = signature_domain(Pool::Orchard, b"ZAIRTEST", b"namada-mainnet").expect("ok");
+        assert_ne!(testnet, mainnet);
+        assert_ne!(mainnet, orchard);
+
+        let proof_hash = [1_u8; 32];
+        let message_hash = [2_u8; 32];
+        let nonce = [3_u8; 32];
+        assert_ne!(
+            signature_digest(&testnet, &proof_hash, &message_hash, &nonce, 100),
+            signature_digest(&mainnet, &proof_hash, &message_hash, &nonce, 100)
+        );
+        assert!(matches!(
+            signature_domain(Pool::Sapling, b"ZAIRTEST", &[0_u8; 256]),
+            Err(DigestError::ChainIdTooLong)
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+//! Airdrop (hiding) nullifier derivation.
+//!
+//! These are the derivations the claim circuits constrain, exposed so that a verifier without the
+//! circuits can recompute an airdrop nullifier bit for bit. The known-answer vectors in
+//! `test-vectors/hiding-nullifier.json` are checked by the tests below.
+
+use ff::{Field as _, FromUniformBytes as _, PrimeField as _};
+use group::{Curve as _, GroupEncoding as _};
+use halo2_gadgets::poseidon::primitives as poseidon;
+use pasta_curves::arithmetic::{CurveAffine as _, CurveExt as _};
+use pasta_curves::pallas;
+use thiserror::Error;
+
+use super::{Nullifier, ORCHARD_HIDING_NF_TAG, Pool, TargetId, TargetIdError};
+
+/// Input to an airdrop nullifier derivation that is not valid.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum HidingNullifierError {
+    /// The target ID is not valid for the pool.
+    #[error(transparent)]
+    TargetId(#[from] TargetIdError),
+    /// A field element is not a canonical Pallas base field encoding.
+    #[error("{0} is not a canonical Pallas base field element")]
+    NonCanonical(&'static str),
+    /// The note commitment is not a valid Pallas point encoding.
+    #[error("cm is not a valid Pallas point")]
+    InvalidCommitment,
+}
+
+/// Derive the Sapling airdrop nullifier of a note.
+///
+/// `BLAKE2s-256(personalization = target_id, repr_J(nk) || repr_J(rho))`, the Sapling nullifier
+/// PRF with the target ID in place of `Zcash_nf`. `nk` and `rho` are the 32-byte Jubjub encodings
+/// of the nullifier deriving key and of the note's position-dependent `rho`.
+///
+/// # Errors
+/// Returns an error if `target_id` is not a valid Sapling target ID.
+pub fn derive_hiding_nullifier_sapling(
+    target_id: &TargetId,
+    nk: &[u8; 32],
+    rho: &[u8; 32],
+) -> Result<Nullifier, HidingNullifierError> {
+    let personalization = target_id.sapling_personalization()?;
+    let hash = blake2s_simd::Params::new()
+        .hash_length(32)
+        .personal(&personalization)
+        .to_state()
+        .update(nk)
+        .update(rho)
+        .finalize();
+    Ok(Nullifier::new(*hash.as_array()))
+}
+
+/// Derive the Orchard airdrop nullifier of a note.
+///
+/// `Extract_P([(Poseidon(nk, rho) + psi) mod q] * K + cm)` with
+/// `K = GroupHash(target_id, "K")`, the Orchard `DeriveNullifier` with the target ID in place of
+/// the `z.cash:Orchard` domain. `nk`, `rho` and `psi` are canonical Pallas base field encodings
+/// and `cm` is the encoded note commitment point.
+///
+/// # Errors
+/// Returns an error if `target_id` is not a valid Orchard target ID, a field element is not
+/// canonical, or `cm` is not a point.
+pub fn derive_hiding_nullifier_orchard(
+    target_id: &TargetId,
+    nk: &[u8; 32],
+    rho: &[u8; 32],
+    psi: &[u8; 32],
+    cm: &[u8; 32],
+) -> Result<Nullifier, HidingNullifierError> {
+    target_id.ensure_pool(Pool::Orchard)?;
+    let base = |name, bytes: &[u8; 32]| {
+        Option::<pallas::Base>::from(pallas::Base::from_repr(*bytes))
+            .ok_or(HidingNullifierError::NonCanonical(name))
+    };
+    let nk = base("nk", nk)?;
+    let rho = base("rho", rho)?;
+    let psi = base("psi", psi)?;
+    let cm = Option::<pallas::Point>::from(pallas::Point::from_bytes(cm))
+        .ok_or(HidingNullifierError::InvalidCommitment)?;
+
+    let prf = poseidon::Hash::<_, poseidon::P128Pow5T3, poseidon::ConstantLength<2>, 3, 2>::init()
+        .hash([nk, rho]);
+    #[allow(
+        clippy::arithmetic_side_effects,
+        reason = "Pallas field and group operations are modular"
+    )]
+    let point = {
+        // The base field is smaller than the scalar field, so reducing mod q keeps the value.
+        let mut wide = [0_u8; 64];
+        for (slot, byte) in wide.iter_mut().zip((prf + psi).to_repr()) {
+            *slot = byte;
+        }
+        let scalar = pallas::Scalar::from_uniform_bytes(&wide);
+        let k = pallas::Point::hash_to_curve(target_id.orchard_domain())(ORCHARD_HIDING_NF_TAG);
+        (k * scalar + cm).to_affine()
+    };
+    let x = Option::<pallas::Base>::from(point.coordinates().map(|coordinates| *coordinates.x()))
+        .unwrap_or(pallas::Base::ZERO);
+    Ok(Nullifier::new(x.to_repr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use group::Group as _;
+    use serde::Deserialize;
+
+    use super::*;
+
+    const VECTORS: &str = include_str!("../../test-vectors/hiding-nullifier.json");
+
+    #[derive(Deserialize)]
+    struct Vectors {
+        sapling: Vec<SaplingVector>,
+        orchard: Vec<OrchardVector>,
+    }
+
+    #[derive(Deserialize)]
+    struct SaplingVector {
+        target_id: String,
+        nk: String,
+        rho: String,
+        airdrop_nullifier: String,
+    }
+
+    #[derive(Deserialize)]
+    struct OrchardVector {
+        target_id: String,
+        nk: String,
+        rho: String,
+        psi: String,
+        cm: String,
+        airdrop_nullifier: String,
+    }
+
+    fn bytes(hex: &str) -> [u8; 32] {
+        let mut bytes = [0_u8; 32];
+        hex::decode_to_slice(hex, &mut bytes).expect("32 hex-encoded bytes");
+        bytes
+    }
+
+    fn vectors() -> Vectors {
+        serde_json::from_str(VECTORS).expect("parse test vectors")
+    }
+
+    #[test]
+    fn sapling_known_answers() {
+        for vector in vectors().sapling {
+            let target_id = TargetId::new(Pool::Sapling, vector.target_id).expect("target ID");
+            let nf = derive_hiding_nullifier_sapling(
+                &target_id,
+                &bytes(&vector.nk),
+                &bytes(&vector.rho),
+            )
+            .expect("derive");
+            assert_eq!(*nf, bytes(&vector.airdrop_nullifier), "{target_id}");
+        }
+    }
+
+    #[test]
+    fn orchard_known_answers() {
+        for vector in vectors().orchard {
+            let target_id = TargetId::new(Pool::Orchard, vector.target_id).expect("target ID");
+            let nf = derive_hiding_nullifier_orchard(
+                &target_id,
+                &bytes(&vector.nk),
+                &bytes(&vector.rho),
+                &bytes(&vector.psi),
+                &bytes(&vector.cm),
+            )
+            .expect("derive");
+            assert_eq!(*nf, bytes(&vector.airdrop_nullifier), "{target_id}");
+        }
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        let short = TargetId::new(Pool::Orchard, "ZAIR").expect("target ID");
+        assert_eq!(
+            derive_hiding_nullifier_sapling(&short, &[0; 32], &[0; 32]),
+            Err(HidingNullifierError::TargetId(
+                TargetIdError::SaplingLength(4)
+            ))
+        );
+
+        let orchard = TargetId::new(Pool::Orchard, "ZAIRTEST:O").expect("target ID");
+        let cm = pallas::Point::generator().to_bytes();
+        assert_eq!(
+            derive_hiding_nullifier_orchard(&orchard, &[0xff; 32], &[0; 32], &[0; 32], &cm),
+            Err(HidingNullifierError::NonCanonical("nk"))
+        );
+        assert_eq!(
+            derive_hiding_nullifier_orchard(&orchard, &[0; 32], &[0; 32], &[0; 32], &[0xff; 32]),
+            Err(HidingNullifierError::InvalidCommitment)
+        );
+    }
+}
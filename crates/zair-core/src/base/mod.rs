@@ -4,13 +4,31 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+mod circuit;
 mod digest;
+mod hiding_nullifier;
 mod nullifier;
+mod target_id;
 mod utils;
 mod value_commitment;
 
-pub use digest::{hash_bytes, hash_message, signature_digest};
+pub use circuit::{
+    HIDING_NF_PERSONALIZATION, NOTE_COMMITMENT_TREE_DEPTH, NULLIFIER_GAP_TREE_DEPTH,
+};
+pub use digest::{
+    DigestError, claim_id, detached_signature_digest, hash_bytes, hash_message, key_fingerprint,
+    key_fingerprint_salt, organizer_signature_digest, signature_digest, signature_domain,
+    transparent_signature_digest,
+};
+pub use hiding_nullifier::{
+    HidingNullifierError, derive_hiding_nullifier_orchard, derive_hiding_nullifier_sapling,
+};
 pub use nullifier::{NULLIFIER_SIZE, Nullifier, SanitiseNullifiers};
+pub use target_id::{
+    MAX_TARGET_ID_LEN, ORCHARD_HIDING_NF_TAG, SAPLING_TARGET_ID_LEN, TargetId, TargetIdError,
+    ZCASH_ORCHARD_DOMAINS, ZCASH_ORCHARD_PREFIX, ZCASH_SAPLING_PERSONALIZATIONS,
+    ZCASH_SAPLING_PREFIX,
+};
 pub use utils::{ReverseBytes, ReversedHex};
 pub use value_commitment::{VALUE_COMMIT_SHA256_PREFIX, cv_sha256, cv_sha256_preimage};
 
@@ -3,6 +3,7 @@
 //! A nullifier is a 32-byte value that uniquely identifies a spent note
 //! in Zcash's Sapling and Orchard shielded pools.
 
+use std::cmp::Ordering;
 use std::ops::Deref;
 
 use bytemuck::{Pod, Zeroable};
@@ -133,6 +134,39 @@ impl SanitiseNullifiers {
 
         Self { nullifiers }
     }
+
+    /// Merge two sanitised sets into one with a single linear pass.
+    ///
+    /// Both inputs are already sorted and deduplicated, so the result is built without
+    /// re-sorting. Returns the merged set and the number of nullifiers present in both inputs
+    /// (i.e. duplicates dropped by the merge).
+    #[must_use]
+    pub fn merge(self, other: Self) -> (Self, usize) {
+        let mut merged = Vec::with_capacity(self.nullifiers.len().saturating_add(other.len()));
+        let mut duplicates = 0_usize;
+        let mut lhs = self.nullifiers.into_iter().peekable();
+        let mut rhs = other.nullifiers.into_iter().peekable();
+
+        loop {
+            let next = match (lhs.peek(), rhs.peek()) {
+                (Some(l), Some(r)) => match l.cmp(r) {
+                    Ordering::Less => lhs.next(),
+                    Ordering::Greater => rhs.next(),
+                    Ordering::Equal => {
+                        duplicates = duplicates.saturating_add(1);
+                        rhs.next();
+                        lhs.next()
+                    }
+                },
+                (Some(_), None) => lhs.next(),
+                (None, Some(_)) => rhs.next(),
+                (None, None) => break,
+            };
+            merged.extend(next);
+        }
+
+        (Self { nullifiers: merged }, duplicates)
+    }
 }
 
 impl Deref for SanitiseNullifiers {
@@ -180,6 +214,27 @@ mod tests {
         assert_eq!(*sanitised, expected);
     }
 
+    #[test]
+    fn merge_interleaves_and_counts_duplicates() {
+        let lhs = SanitiseNullifiers::new(nfs![1_u8, 3_u8, 5_u8, 7_u8]);
+        let rhs = SanitiseNullifiers::new(nfs![2_u8, 3_u8, 7_u8, 9_u8]);
+
+        let (merged, duplicates) = lhs.merge(rhs);
+
+        assert_eq!(*merged, nfs![1_u8, 2_u8, 3_u8, 5_u8, 7_u8, 9_u8]);
+        assert_eq!(duplicates, 2);
+    }
+
+    #[test]
+    fn merge_with_empty_is_identity() {
+        let set = SanitiseNullifiers::new(nfs![4_u8, 8_u8]);
+
+        let (merged, duplicates) = set.merge(SanitiseNullifiers::new(vec![]));
+
+        assert_eq!(*merged, nfs![4_u8, 8_u8]);
+        assert_eq!(duplicates, 0);
+    }
+
     #[test]
     fn display_outputs_reversed_hex() {
         let mut bytes = [0u8; NULLIFIER_SIZE];
@@ -0,0 +1,278 @@
+//! Target IDs: the per-pool namespace of an airdrop.
+//!
+//! The target ID personalizes the hiding nullifier of every claimed note, so two airdrops with
+//! different target IDs publish unlinkable airdrop nullifiers for the same note. It is also bound
+//! into claim IDs and signature domains. The Sapling target ID is the 8-byte BLAKE2s
+//! personalization of the hiding nullifier hash and is fixed when the circuit is compiled; the
+//! Orchard target ID is the hash-to-curve domain of the hiding nullifier, at most 32 bytes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::Pool;
+
+/// Length of a Sapling target ID in bytes.
+pub const SAPLING_TARGET_ID_LEN: usize = 8;
+
+/// Maximum length of an Orchard target ID in bytes.
+pub const MAX_TARGET_ID_LEN: usize = 32;
+
+/// Message hashed to the curve with the Orchard target ID as domain for the hiding nullifier.
+pub const ORCHARD_HIDING_NF_TAG: &[u8] = b"K";
+
+/// BLAKE2s personalizations of the Sapling protocol, with what they are used for.
+///
+/// A Sapling target ID equal to one of these makes the airdrop nullifier a hash the protocol
+/// already computes; `Zcash_nf` makes it the note's Zcash nullifier.
+pub const ZCASH_SAPLING_PERSONALIZATIONS: &[(&str, &str)] = &[
+    ("Zcash_nf", "the Sapling nullifier PRF"),
+    ("Zcashivk", "the incoming viewing key hash"),
+    ("Zcash_gd", "DiversifyHash"),
+    ("Zcash_PH", "the Pedersen hash generators"),
+    ("Zcash_J_", "the nullifier position generator"),
+    ("Zcash_G_", "the spend authorization generator"),
+    ("Zcash_H_", "the proof authorization generator"),
+    ("Zcash_cv", "the value commitment generators"),
+];
+
+/// Hash-to-curve domains of the Orchard protocol, with what they are used for.
+///
+/// An Orchard target ID equal to one of these hashes the airdrop nullifier base to a point the
+/// protocol already uses; `z.cash:Orchard` makes the airdrop nullifier the note's Zcash nullifier.
+pub const ZCASH_ORCHARD_DOMAINS: &[(&str, &str)] = &[
+    (
+        "z.cash:Orchard",
+        "the Orchard nullifier base and spend authorization generator",
+    ),
+    ("z.cash:Orchard-gd", "DiversifyHash"),
+    ("z.cash:Orchard-cv", "the value commitment generators"),
+    ("z.cash:SinsemillaQ", "the Sinsemilla initial points"),
+    ("z.cash:SinsemillaS", "the Sinsemilla generators"),
+    ("z.cash:Orchard-MerkleCRH", "the note commitment tree hash"),
+    ("z.cash:Orchard-NoteCommit", "the note commitment"),
+    ("z.cash:Orchard-NoteCommit-M", "the note commitment"),
+    (
+        "z.cash:Orchard-NoteCommit-r",
+        "the note commitment randomness base",
+    ),
+    (
+        "z.cash:Orchard-CommitIvk",
+        "the incoming viewing key commitment",
+    ),
+    (
+        "z.cash:Orchard-CommitIvk-M",
+        "the incoming viewing key commitment",
+    ),
+    (
+        "z.cash:Orchard-CommitIvk-r",
+        "the incoming viewing key commitment randomness base",
+    ),
+];
+
+/// Prefix of the Sapling personalizations reserved by the Zcash protocol.
+pub const ZCASH_SAPLING_PREFIX: &str = "Zcash";
+
+/// Prefix of the Orchard hash-to-curve domains reserved by the Zcash protocol.
+pub const ZCASH_ORCHARD_PREFIX: &str = "z.cash:";
+
+/// A target ID that is not valid for its pool.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum TargetIdError {
+    /// The target ID is empty.
+    #[error("target_id must not be empty")]
+    Empty,
+    /// The target ID is longer than any pool accepts.
+    #[error("target_id must be at most {MAX_TARGET_ID_LEN} bytes, got {0}")]
+    TooLong(usize),
+    /// A Sapling target ID is not exactly [`SAPLING_TARGET_ID_LEN`] bytes.
+    #[error("Sapling target_id must be exactly {SAPLING_TARGET_ID_LEN} bytes, got {0}")]
+    SaplingLength(usize),
+}
+
+/// Namespace of an airdrop in one pool.
+///
+/// A target ID is a UTF-8 string of 1 to [`MAX_TARGET_ID_LEN`] bytes, serialized as a JSON string.
+/// Use [`TargetId::new`] to also check the rules of a pool.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TargetId(String);
+
+impl TargetId {
+    /// Target ID for `pool`.
+    ///
+    /// # Errors
+    /// Returns an error if `target_id` is empty, too long, or not [`SAPLING_TARGET_ID_LEN`] bytes
+    /// for Sapling.
+    pub fn new(pool: Pool, target_id: impl Into<String>) -> Result<Self, TargetIdError> {
+        let target_id = Self::try_from(target_id.into())?;
+        target_id.ensure_pool(pool)?;
+        Ok(target_id)
+    }
+
+    /// Check the rules of `pool`.
+    ///
+    /// # Errors
+    /// Returns an error if a Sapling target ID is not [`SAPLING_TARGET_ID_LEN`] bytes.
+    pub fn ensure_pool(&self, pool: Pool) -> Result<(), TargetIdError> {
+        match pool {
+            Pool::Sapling => self.sapling_personalization().map(|_| ()),
+            Pool::Orchard => Ok(()),
+        }
+    }
+
+    /// The target ID as a string.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The target ID bytes, as bound into claim IDs and signature domains.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+
+    /// BLAKE2s personalization of the Sapling hiding nullifier.
+    ///
+    /// # Errors
+    /// Returns an error if the target ID is not [`SAPLING_TARGET_ID_LEN`] bytes.
+    pub fn sapling_personalization(&self) -> Result<[u8; SAPLING_TARGET_ID_LEN], TargetIdError> {
+        self.as_bytes()
+            .try_into()
+            .map_err(|_| TargetIdError::SaplingLength(self.0.len()))
+    }
+
+    /// Hash-to-curve domain of the Orchard hiding nullifier.
+    #[must_use]
+    pub fn orchard_domain(&self) -> &str {
+        &self.0
+    }
+
+    /// The Zcash protocol use of this target ID in `pool`, if it collides with one.
+    #[must_use]
+    pub fn zcash_collision(&self, pool: Pool) -> Option<&'static str> {
+        let registry = match pool {
+            Pool::Sapling => ZCASH_SAPLING_PERSONALIZATIONS,
+            Pool::Orchard => ZCASH_ORCHARD_DOMAINS,
+        };
+        registry
+            .iter()
+            .find(|(reserved, _)| *reserved == self.0)
+            .map(|(_, usage)| *usage)
+    }
+
+    /// Whether this target ID is in the namespace the Zcash protocol reserves in `pool`.
+    #[must_use]
+    pub fn has_zcash_prefix(&self, pool: Pool) -> bool {
+        match pool {
+            Pool::Sapling => self.0.starts_with(ZCASH_SAPLING_PREFIX),
+            Pool::Orchard => self.0.starts_with(ZCASH_ORCHARD_PREFIX),
+        }
+    }
+
+    /// The Orchard target ID as the circuit takes it: zero-padded bytes and their length.
+    #[must_use]
+    pub fn orchard_padded(&self) -> ([u8; MAX_TARGET_ID_LEN], u8) {
+        let mut padded = [0_u8; MAX_TARGET_ID_LEN];
+        let mut len = 0_u8;
+        for (slot, byte) in padded.iter_mut().zip(self.as_bytes()) {
+            *slot = *byte;
+            len = len.saturating_add(1);
+        }
+        (padded, len)
+    }
+}
+
+impl TryFrom<String> for TargetId {
+    type Error = TargetIdError;
+
+    fn try_from(target_id: String) -> Result<Self, Self::Error> {
+        if target_id.is_empty() {
+            return Err(TargetIdError::Empty);
+        }
+        if target_id.len() > MAX_TARGET_ID_LEN {
+            return Err(TargetIdError::TooLong(target_id.len()));
+        }
+        Ok(Self(target_id))
+    }
+}
+
+impl FromStr for TargetId {
+    type Err = TargetIdError;
+
+    fn from_str(target_id: &str) -> Result<Self, Self::Err> {
+        Self::try_from(target_id.to_owned())
+    }
+}
+
+impl From<TargetId> for String {
+    fn from(target_id: TargetId) -> Self {
+        target_id.0
+    }
+}
+
+impl fmt::Display for TargetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_ids_follow_the_rules_of_their_pool() {
+        let sapling = TargetId::new(Pool::Sapling, "ZAIRTEST").expect("8 bytes");
+        assert_eq!(sapling.sapling_personalization(), Ok(*b"ZAIRTEST"));
+        assert_eq!(
+            TargetId::new(Pool::Sapling, "ZAIR"),
+            Err(TargetIdError::SaplingLength(4))
+        );
+        assert_eq!(TargetId::new(Pool::Orchard, ""), Err(TargetIdError::Empty));
+        assert_eq!(
+            TargetId::new(Pool::Orchard, "Z".repeat(33)),
+            Err(TargetIdError::TooLong(33))
+        );
+
+        let orchard = TargetId::new(Pool::Orchard, "ZAIRTEST:O").expect("at most 32 bytes");
+        let (padded, len) = orchard.orchard_padded();
+        assert_eq!(len, 10);
+        assert_eq!(padded.get(..10), Some(&b"ZAIRTEST:O"[..]));
+        assert!(padded.iter().skip(10).all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn zcash_domains_are_reported() {
+        let nf = TargetId::new(Pool::Sapling, "Zcash_nf").expect("8 bytes");
+        assert_eq!(
+            nf.zcash_collision(Pool::Sapling),
+            Some("the Sapling nullifier PRF")
+        );
+        let orchard = TargetId::new(Pool::Orchard, "z.cash:Orchard").expect("at most 32 bytes");
+        assert!(orchard.zcash_collision(Pool::Orchard).is_some());
+        assert_eq!(orchard.zcash_collision(Pool::Sapling), None);
+
+        let default = TargetId::new(Pool::Orchard, "ZAIRTEST:O").expect("at most 32 bytes");
+        assert_eq!(default.zcash_collision(Pool::Orchard), None);
+        assert!(!default.has_zcash_prefix(Pool::Orchard));
+        assert!(
+            TargetId::new(Pool::Orchard, "z.cash:Airdrop")
+                .expect("at most 32 bytes")
+                .has_zcash_prefix(Pool::Orchard)
+        );
+    }
+
+    #[test]
+    fn target_ids_serialize_as_validated_strings() {
+        let target_id: TargetId = serde_json::from_str(r#""ZAIRTEST""#).expect("parse");
+        assert_eq!(
+            serde_json::to_string(&target_id).expect("serialize"),
+            r#""ZAIRTEST""#
+        );
+        assert!(serde_json::from_str::<TargetId>(r#""""#).is_err());
+    }
+}
@@ -4,3 +4,5 @@
 pub mod base;
 /// Serialized/public schema models used across the workspace.
 pub mod schema;
+/// Canonical claim messages covered by claim signatures.
+pub mod sighash;
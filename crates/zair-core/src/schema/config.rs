@@ -1,12 +1,23 @@
+use std::collections::BTreeMap;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_with::hex::Hex;
 use serde_with::serde_as;
+use thiserror::Error;
 
-use crate::base::ReversedHex;
+use crate::base::{
+    DigestError, HIDING_NF_PERSONALIZATION, NOTE_COMMITMENT_TREE_DEPTH, NULLIFIER_GAP_TREE_DEPTH,
+    Nullifier, Pool, ReversedHex, TargetId, claim_id, key_fingerprint_salt, signature_domain,
+};
 
 /// Configuration for an airdrop snapshot.
-#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+///
+/// The top-level snapshot fields describe the first epoch of the airdrop. A phased airdrop lists
+/// its later snapshots in `epochs`; claims name the epoch they target, and [`Self::for_epoch`]
+/// gives the configuration they are prepared, proven and verified against.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
 pub struct AirdropConfiguration {
     /// Zcash network this snapshot belongs to.
     pub network: AirdropNetwork,
@@ -18,6 +29,189 @@ pub struct AirdropConfiguration {
     /// Orchard snapshot configuration. Present when Orchard pool is enabled.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub orchard: Option<OrchardSnapshot>,
+    /// Handling of the Sprout pool, recorded so auditors see that Sprout was considered.
+    /// Configurations written before the field existed read as excluded.
+    #[serde(default)]
+    pub sprout: SproutPolicy,
+    /// Transparent snapshot configuration. Present when transparent addresses can claim.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transparent: Option<TransparentSnapshot>,
+    /// Smallest note value in zatoshis that is claimed. Smaller notes are left out of the claims.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_note_value: Option<u64>,
+    /// Largest number of claims, over both pools, that one submission may carry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_claims_per_submission: Option<u32>,
+    /// Circuit parameters that claim proving and verification must agree on.
+    #[serde(default)]
+    pub circuit: CircuitConfiguration,
+    /// Activation heights by consensus branch ID (8 hex digits, as in zcashd's `-nuparams`) of a
+    /// private devnet. Only valid on `regtest`, where it replaces the default activation heights.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_upgrades: Option<BTreeMap<String, u32>>,
+    /// First block height of the claim window. Submissions are rejected before it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claim_start_height: Option<u64>,
+    /// Last block height of the claim window. Signed submissions must expire at or before it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claim_expiry_height: Option<u64>,
+    /// Ed25519 operator public keys that must countersign every claim of a submission.
+    #[serde_as(as = "Vec<Hex>")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(with = "Vec<String>")]
+    pub countersigners: Vec<[u8; 32]>,
+    /// Chain ID of the chain the claims are submitted to. It is bound into every claim signature,
+    /// so signatures for a testnet rehearsal are never valid for the mainnet airdrop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+    /// Later snapshot epochs of a phased airdrop, each with its own snapshot and claim window.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub epochs: Vec<AirdropEpoch>,
+}
+
+/// A later snapshot epoch of a phased airdrop.
+///
+/// Network, circuit, claim policy, countersigners, chain ID and the enabled pools with their value
+/// commitment schemes are shared with the base configuration, so one set of setup files serves
+/// every epoch.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct AirdropEpoch {
+    /// Epoch number, above 0; epoch 0 is the base configuration.
+    pub epoch: u32,
+    /// Snapshot block height (inclusive) of the epoch.
+    pub snapshot_height: u64,
+    /// Sapling snapshot of the epoch. Present when the Sapling pool is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sapling: Option<SaplingSnapshot>,
+    /// Orchard snapshot of the epoch. Present when the Orchard pool is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orchard: Option<OrchardSnapshot>,
+    /// Transparent snapshot of the epoch. Present when transparent addresses can claim.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transparent: Option<TransparentSnapshot>,
+    /// First block height of the epoch's claim window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claim_start_height: Option<u64>,
+    /// Last block height of the epoch's claim window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claim_expiry_height: Option<u64>,
+}
+
+/// Circuit parameters shared by the organizer and claimers.
+///
+/// `claim prove` and `verify` read them from the configuration instead of taking flags, so both
+/// sides always use the same circuit. Value commitment schemes are set per pool.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Clone)]
+pub struct CircuitConfiguration {
+    /// Depth of the Sapling and Orchard note commitment trees.
+    pub note_commitment_tree_depth: u8,
+    /// Depth of the nullifier non-membership trees.
+    pub nullifier_gap_tree_depth: u8,
+    /// Personalization of the Sapling hiding nullifier hash.
+    pub hiding_nf_personalization: String,
+}
+
+impl Default for CircuitConfiguration {
+    /// The parameters the claim circuits are compiled with.
+    fn default() -> Self {
+        Self {
+            note_commitment_tree_depth: NOTE_COMMITMENT_TREE_DEPTH,
+            nullifier_gap_tree_depth: NULLIFIER_GAP_TREE_DEPTH,
+            hiding_nf_personalization: String::from_utf8_lossy(&HIDING_NF_PERSONALIZATION)
+                .into_owned(),
+        }
+    }
+}
+
+/// A circuit parameter of the configuration that the compiled circuits do not support.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum UnsupportedCircuit {
+    /// The note commitment tree depth differs from the compiled circuits.
+    #[error("note commitment tree depth {0} differs from the compiled circuits")]
+    NoteCommitmentTreeDepth(u8),
+    /// The nullifier non-membership tree depth differs from the compiled circuits.
+    #[error("nullifier gap tree depth {0} differs from the compiled circuits")]
+    NullifierGapTreeDepth(u8),
+    /// The Sapling hiding nullifier personalization differs from the compiled circuit.
+    #[error("hiding nullifier personalization {0:?} differs from the compiled Sapling circuit")]
+    HidingNfPersonalization(String),
+}
+
+/// A claim identifier or signature domain that cannot be derived from the configuration.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ClaimIdError {
+    /// The configuration has no snapshot for the claim's pool.
+    #[error("airdrop configuration has no {0} pool")]
+    MissingPool(Pool),
+    /// The pool's target ID is longer than 255 bytes.
+    #[error("{0} target ID length exceeds 255 bytes")]
+    TargetIdTooLong(Pool),
+    /// The chain ID is longer than 255 bytes.
+    #[error("chain ID length exceeds 255 bytes")]
+    ChainIdTooLong,
+}
+
+/// An epoch that a configuration does not have or cannot take.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EpochError {
+    /// The configuration has no such epoch.
+    #[error("airdrop configuration has no epoch {0}")]
+    UnknownEpoch(u32),
+    /// The epoch is 0, the base configuration, or is already listed.
+    #[error("epoch {0} is already in the airdrop configuration")]
+    DuplicateEpoch(u32),
+    /// The configuration of the epoch differs from the base in more than its snapshot.
+    #[error(
+        "epoch {0} differs from the base configuration in more than its snapshot and claim window; \
+         the network, pools and value commitment schemes must match"
+    )]
+    SharedFieldsDiffer(u32),
+}
+
+/// A claim or submission that the claim policy of the configuration rejects.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// More claims than one submission may carry.
+    #[error("{claims} claims exceed the limit of {max} claims per submission")]
+    TooManyClaims {
+        /// Number of claims.
+        claims: usize,
+        /// Configured limit.
+        max: u32,
+    },
+    /// The claim window has not opened yet.
+    #[error("the claim window opens at height {start}, current height is {current}")]
+    WindowNotOpen {
+        /// First height of the claim window.
+        start: u64,
+        /// Current chain height.
+        current: u64,
+    },
+}
+
+impl CircuitConfiguration {
+    /// Check that the compiled claim circuits match these parameters.
+    ///
+    /// # Errors
+    /// Returns the first parameter that differs from the compiled circuits.
+    pub fn ensure_supported(&self) -> Result<(), UnsupportedCircuit> {
+        if self.note_commitment_tree_depth != NOTE_COMMITMENT_TREE_DEPTH {
+            return Err(UnsupportedCircuit::NoteCommitmentTreeDepth(
+                self.note_commitment_tree_depth,
+            ));
+        }
+        if self.nullifier_gap_tree_depth != NULLIFIER_GAP_TREE_DEPTH {
+            return Err(UnsupportedCircuit::NullifierGapTreeDepth(
+                self.nullifier_gap_tree_depth,
+            ));
+        }
+        if self.hiding_nf_personalization.as_bytes() != HIDING_NF_PERSONALIZATION {
+            return Err(UnsupportedCircuit::HidingNfPersonalization(
+                self.hiding_nf_personalization.clone(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// Value commitment scheme selection.
@@ -29,6 +223,22 @@ pub enum ValueCommitmentScheme {
     Native,
     /// SHA-256 commitment (`cv_sha256`).
     Sha256,
+    /// Poseidon commitment (`cv_poseidon`), cheap to verify in SNARK-friendly target chains.
+    /// Orchard only.
+    Poseidon,
+}
+
+/// Handling of the Sprout pool.
+///
+/// Sprout notes are not collected: the pool holds little value, its notes use a different
+/// commitment and nullifier scheme than the claim circuits, and holders can migrate them to
+/// Sapling or Orchard before the snapshot.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SproutPolicy {
+    /// Sprout notes cannot claim.
+    #[default]
+    Excluded,
 }
 
 /// Network identifier for an airdrop snapshot.
@@ -39,6 +249,8 @@ pub enum AirdropNetwork {
     Mainnet,
     /// Zcash testnet.
     Testnet,
+    /// Local regtest network, or a private devnet with custom `network_upgrades`.
+    Regtest,
 }
 
 /// Sapling-specific snapshot data.
@@ -54,10 +266,16 @@ pub struct SaplingSnapshot {
     #[schemars(with = "String")]
     pub nullifier_gap_root: [u8; 32],
     /// Domain-separation identifier used for Sapling hiding nullifiers.
-    pub target_id: String,
+    #[schemars(with = "String")]
+    pub target_id: TargetId,
     /// Value commitment scheme used by Sapling proofs.
     #[serde(default)]
     pub value_commitment_scheme: ValueCommitmentScheme,
+    /// Fingerprint of the Groth16 verifying key proofs must verify against, if bound.
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub verifying_key_fingerprint: Option<[u8; 32]>,
 }
 
 /// Orchard-specific snapshot data.
@@ -73,16 +291,43 @@ pub struct OrchardSnapshot {
     #[schemars(with = "String")]
     pub nullifier_gap_root: [u8; 32],
     /// Domain-separation identifier used for Orchard hiding nullifiers.
-    pub target_id: String,
+    #[schemars(with = "String")]
+    pub target_id: TargetId,
     /// Value commitment scheme used by Orchard proofs.
     #[serde(default)]
     pub value_commitment_scheme: ValueCommitmentScheme,
+    /// Fingerprint of the Halo2 params proofs must verify against, if bound.
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub params_fingerprint: Option<[u8; 32]>,
+}
+
+/// Transparent snapshot data.
+///
+/// Transparent addresses claim their balance with a signature by the address key instead of a
+/// proof. The UTXOs unspent at `snapshot_height` are published in a
+/// [`TransparentUtxoSet`](super::transparent::TransparentUtxoSet) file, which this snapshot binds
+/// by hash.
+#[serde_as]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Clone)]
+pub struct TransparentSnapshot {
+    /// Hash of the published UTXO set, see
+    /// [`TransparentUtxoSet::hash`](super::transparent::TransparentUtxoSet::hash).
+    #[serde_as(as = "Hex")]
+    #[schemars(with = "String")]
+    pub utxo_set_hash: [u8; 32],
+    /// Total value of the UTXO set in zatoshis.
+    pub total_value: u64,
+    /// Domain-separation identifier bound into transparent claim signatures.
+    #[schemars(with = "String")]
+    pub target_id: TargetId,
 }
 
 impl AirdropConfiguration {
-    /// Create a new airdrop configuration.
+    /// Create a new airdrop configuration for the compiled circuits.
     #[must_use]
-    pub const fn new(
+    pub fn new(
         network: AirdropNetwork,
         snapshot_height: u64,
         sapling: Option<SaplingSnapshot>,
@@ -93,6 +338,179 @@ impl AirdropConfiguration {
             snapshot_height,
             sapling,
             orchard,
+            sprout: SproutPolicy::Excluded,
+            transparent: None,
+            min_note_value: None,
+            max_claims_per_submission: None,
+            circuit: CircuitConfiguration::default(),
+            network_upgrades: None,
+            claim_start_height: None,
+            claim_expiry_height: None,
+            countersigners: Vec::new(),
+            chain_id: None,
+            epochs: Vec::new(),
+        }
+    }
+
+    /// Configuration of `epoch`, or of the base epoch when `None`.
+    ///
+    /// The snapshot and claim window of the epoch replace those of the base configuration, and
+    /// the result lists no further epochs.
+    ///
+    /// # Errors
+    /// Returns an error if the configuration has no such epoch.
+    pub fn for_epoch(&self, epoch: Option<u32>) -> Result<Self, EpochError> {
+        let mut config = self.clone();
+        config.epochs = Vec::new();
+        let Some(epoch) = epoch.filter(|epoch| *epoch != 0) else {
+            return Ok(config);
+        };
+        let selected = self
+            .epochs
+            .iter()
+            .find(|candidate| candidate.epoch == epoch)
+            .ok_or(EpochError::UnknownEpoch(epoch))?;
+        config.snapshot_height = selected.snapshot_height;
+        config.sapling.clone_from(&selected.sapling);
+        config.orchard.clone_from(&selected.orchard);
+        config.transparent.clone_from(&selected.transparent);
+        config.claim_start_height = selected.claim_start_height;
+        config.claim_expiry_height = selected.claim_expiry_height;
+        Ok(config)
+    }
+
+    /// Add `later`, the configuration of a later snapshot, as epoch `epoch`.
+    ///
+    /// # Errors
+    /// Returns an error if `epoch` is 0 or already listed, or `later` differs from this
+    /// configuration in anything but its snapshot roots, target IDs and claim window.
+    pub fn add_epoch(&mut self, epoch: u32, later: Self) -> Result<(), EpochError> {
+        if epoch == 0 || self.epochs.iter().any(|existing| existing.epoch == epoch) {
+            return Err(EpochError::DuplicateEpoch(epoch));
+        }
+        let shared = Self {
+            snapshot_height: self.snapshot_height,
+            sapling: self.sapling.clone(),
+            orchard: self.orchard.clone(),
+            transparent: self.transparent.clone(),
+            claim_start_height: self.claim_start_height,
+            claim_expiry_height: self.claim_expiry_height,
+            epochs: self.epochs.clone(),
+            ..later.clone()
+        };
+        let schemes = |config: &Self| {
+            (
+                config
+                    .sapling
+                    .as_ref()
+                    .map(|pool| pool.value_commitment_scheme),
+                config
+                    .orchard
+                    .as_ref()
+                    .map(|pool| pool.value_commitment_scheme),
+                config.transparent.is_some(),
+            )
+        };
+        if shared != *self || schemes(self) != schemes(&later) {
+            return Err(EpochError::SharedFieldsDiffer(epoch));
+        }
+        self.epochs.push(AirdropEpoch {
+            epoch,
+            snapshot_height: later.snapshot_height,
+            sapling: later.sapling,
+            orchard: later.orchard,
+            transparent: later.transparent,
+            claim_start_height: later.claim_start_height,
+            claim_expiry_height: later.claim_expiry_height,
+        });
+        self.epochs.sort_by_key(|existing| existing.epoch);
+        Ok(())
+    }
+
+    /// Stable identifier of the claim of `airdrop_nullifier` in `pool`, see [`claim_id`].
+    ///
+    /// # Errors
+    /// Returns an error if the pool is not configured or its target ID is too long.
+    pub fn claim_id(
+        &self,
+        pool: Pool,
+        airdrop_nullifier: &Nullifier,
+    ) -> Result<[u8; 32], ClaimIdError> {
+        let (target_id, nullifier_gap_root) = match pool {
+            Pool::Sapling => self
+                .sapling
+                .as_ref()
+                .map(|pool| (pool.target_id.as_bytes(), &pool.nullifier_gap_root)),
+            Pool::Orchard => self
+                .orchard
+                .as_ref()
+                .map(|pool| (pool.target_id.as_bytes(), &pool.nullifier_gap_root)),
+        }
+        .ok_or(ClaimIdError::MissingPool(pool))?;
+        claim_id(pool, target_id, nullifier_gap_root, airdrop_nullifier)
+            .map_err(|_| ClaimIdError::TargetIdTooLong(pool))
+    }
+
+    /// Domain tag of the claim signatures of `pool`, see [`signature_domain`].
+    ///
+    /// # Errors
+    /// Returns an error if the pool is not configured or its target ID or the chain ID is too
+    /// long.
+    pub fn signature_domain(&self, pool: Pool) -> Result<[u8; 32], ClaimIdError> {
+        let target_id = match pool {
+            Pool::Sapling => self.sapling.as_ref().map(|pool| pool.target_id.as_bytes()),
+            Pool::Orchard => self.orchard.as_ref().map(|pool| pool.target_id.as_bytes()),
+        }
+        .ok_or(ClaimIdError::MissingPool(pool))?;
+        let chain_id = self.chain_id.as_deref().unwrap_or_default();
+        signature_domain(pool, target_id, chain_id.as_bytes()).map_err(|e| match e {
+            DigestError::TargetIdTooLong => ClaimIdError::TargetIdTooLong(pool),
+            DigestError::ChainIdTooLong => ClaimIdError::ChainIdTooLong,
+        })
+    }
+
+    /// Salt of the viewing key fingerprints of claims against this snapshot, see
+    /// [`key_fingerprint_salt`].
+    ///
+    /// The salt does not depend on the configured pools, so a configuration narrowed to one pool
+    /// fingerprints keys the same way.
+    #[must_use]
+    pub fn key_fingerprint_salt(&self) -> [u8; 32] {
+        let chain_id = self.chain_id.as_deref().unwrap_or_default();
+        key_fingerprint_salt(
+            self.network.as_str(),
+            self.snapshot_height,
+            chain_id.as_bytes(),
+        )
+    }
+
+    /// Check `claims`, the number of claims of one submission, against
+    /// `max_claims_per_submission`.
+    ///
+    /// # Errors
+    /// Returns [`PolicyViolation::TooManyClaims`] if the submission carries too many claims.
+    pub fn ensure_claim_count(&self, claims: usize) -> Result<(), PolicyViolation> {
+        match self.max_claims_per_submission {
+            Some(max) if usize::try_from(max).is_ok_and(|max| claims > max) => {
+                Err(PolicyViolation::TooManyClaims { claims, max })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the claim window is open at `current_height`.
+    ///
+    /// The end of the window is enforced through the submission expiry height.
+    ///
+    /// # Errors
+    /// Returns [`PolicyViolation::WindowNotOpen`] if the window starts after `current_height`.
+    pub fn ensure_claim_window_open(&self, current_height: u64) -> Result<(), PolicyViolation> {
+        match self.claim_start_height {
+            Some(start) if current_height < start => Err(PolicyViolation::WindowNotOpen {
+                start,
+                current: current_height,
+            }),
+            _ => Ok(()),
         }
     }
 
@@ -155,14 +573,161 @@ pub struct NonMembershipTreeAnchors {
     pub orchard: [u8; 32],
 }
 
+impl ValueCommitmentScheme {
+    /// Name of the scheme, as in the configuration.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Sha256 => "sha256",
+            Self::Poseidon => "poseidon",
+        }
+    }
+}
+
 impl AirdropNetwork {
+    /// Name of the network, as in the configuration.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Mainnet => "mainnet",
+            Self::Testnet => "testnet",
+            Self::Regtest => "regtest",
+        }
+    }
+
     /// Parse from CLI/network string.
     #[must_use]
     pub fn from_str_name(s: &str) -> Option<Self> {
         match s {
             "mainnet" => Some(Self::Mainnet),
             "testnet" => Some(Self::Testnet),
+            "regtest" => Some(Self::Regtest),
             _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configuration_without_circuit_section_uses_compiled_circuit() {
+        let config: AirdropConfiguration =
+            serde_json::from_str(r#"{"network":"testnet","snapshot_height":1}"#)
+                .expect("configuration should parse");
+        assert_eq!(config.circuit, CircuitConfiguration::default());
+        assert_eq!(config.circuit.ensure_supported(), Ok(()));
+        assert_eq!(config.circuit.hiding_nf_personalization, "ZAIRTEST");
+        assert_eq!(config.sprout, SproutPolicy::Excluded);
+        assert!(
+            serde_json::to_string(&config)
+                .expect("configuration should serialize")
+                .contains(r#""sprout":"excluded""#)
+        );
+
+        let other = CircuitConfiguration {
+            hiding_nf_personalization: "OTHERAIR".to_owned(),
+            ..CircuitConfiguration::default()
+        };
+        assert_eq!(
+            other.ensure_supported(),
+            Err(UnsupportedCircuit::HidingNfPersonalization(
+                "OTHERAIR".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn claim_policy_limits_claims_and_window() {
+        let mut config = AirdropConfiguration::new(AirdropNetwork::Testnet, 1, None, None);
+        assert_eq!(config.ensure_claim_count(1_000), Ok(()));
+        assert_eq!(config.ensure_claim_window_open(0), Ok(()));
+
+        config.max_claims_per_submission = Some(2);
+        config.claim_start_height = Some(100);
+        assert_eq!(config.ensure_claim_count(2), Ok(()));
+        assert_eq!(
+            config.ensure_claim_count(3),
+            Err(PolicyViolation::TooManyClaims { claims: 3, max: 2 })
+        );
+        assert_eq!(config.ensure_claim_window_open(100), Ok(()));
+        assert_eq!(
+            config.ensure_claim_window_open(99),
+            Err(PolicyViolation::WindowNotOpen {
+                start: 100,
+                current: 99
+            })
+        );
+    }
+
+    #[test]
+    fn epochs_replace_the_snapshot_of_the_base_configuration() {
+        let snapshot = |nullifier_gap_root| SaplingSnapshot {
+            note_commitment_root: [0_u8; 32],
+            nullifier_gap_root,
+            target_id: TargetId::new(Pool::Sapling, "ZAIRTEST").expect("valid target ID"),
+            value_commitment_scheme: ValueCommitmentScheme::Native,
+            verifying_key_fingerprint: None,
+        };
+        let mut config =
+            AirdropConfiguration::new(AirdropNetwork::Testnet, 100, Some(snapshot([1; 32])), None);
+        let later =
+            AirdropConfiguration::new(AirdropNetwork::Testnet, 200, Some(snapshot([2; 32])), None);
+        config.add_epoch(1, later).expect("add epoch");
+
+        let base = config.for_epoch(None).expect("base epoch");
+        assert_eq!(base.snapshot_height, 100);
+        assert!(base.epochs.is_empty());
+        assert_eq!(config.for_epoch(Some(0)), Ok(base));
+        let second = config.for_epoch(Some(1)).expect("epoch 1");
+        assert_eq!(second.snapshot_height, 200);
+        assert_eq!(
+            second.sapling.map(|pool| pool.nullifier_gap_root),
+            Some([2; 32])
+        );
+        assert_eq!(config.for_epoch(Some(2)), Err(EpochError::UnknownEpoch(2)));
+
+        let mainnet = AirdropConfiguration::new(AirdropNetwork::Mainnet, 300, None, None);
+        assert_eq!(
+            config.add_epoch(2, mainnet),
+            Err(EpochError::SharedFieldsDiffer(2))
+        );
+        let again = AirdropConfiguration::new(AirdropNetwork::Testnet, 300, None, None);
+        assert_eq!(
+            config.add_epoch(1, again),
+            Err(EpochError::DuplicateEpoch(1))
+        );
+    }
+
+    #[test]
+    fn claim_id_depends_on_nullifier_and_snapshot() {
+        let snapshot = |nullifier_gap_root| SaplingSnapshot {
+            note_commitment_root: [0_u8; 32],
+            nullifier_gap_root,
+            target_id: TargetId::new(Pool::Sapling, "ZAIRTEST").expect("valid target ID"),
+            value_commitment_scheme: ValueCommitmentScheme::Native,
+            verifying_key_fingerprint: None,
+        };
+        let config =
+            AirdropConfiguration::new(AirdropNetwork::Testnet, 1, Some(snapshot([1; 32])), None);
+        let other_snapshot =
+            AirdropConfiguration::new(AirdropNetwork::Testnet, 1, Some(snapshot([2; 32])), None);
+        let nullifier = Nullifier::new([3; 32]);
+
+        let id = config
+            .claim_id(Pool::Sapling, &nullifier)
+            .expect("claim id");
+        assert_eq!(config.claim_id(Pool::Sapling, &nullifier), Ok(id));
+        assert_ne!(
+            config.claim_id(Pool::Sapling, &Nullifier::new([4; 32])),
+            Ok(id)
+        );
+        assert_ne!(other_snapshot.claim_id(Pool::Sapling, &nullifier), Ok(id));
+        assert_eq!(
+            config.claim_id(Pool::Orchard, &nullifier),
+            Err(ClaimIdError::MissingPool(Pool::Orchard))
+        );
+    }
+}
@@ -6,3 +6,5 @@ pub mod config;
 pub mod proof_inputs;
 /// Submission/signature schema types.
 pub mod submission;
+/// Transparent snapshot schema types.
+pub mod transparent;
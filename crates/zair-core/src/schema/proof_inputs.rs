@@ -5,7 +5,7 @@ use serde_with::hex::Hex;
 use serde_with::serde_as;
 use zip32::Scope;
 
-use crate::base::Nullifier;
+use crate::base::{Nullifier, Pool};
 
 /// Serializable version of `zip32::Scope`.
 ///
@@ -43,6 +43,78 @@ pub struct AirdropClaimInputs {
     pub sapling_claim_input: Vec<ClaimInput<SaplingPrivateInputs>>,
     /// Orchard claim inputs
     pub orchard_claim_input: Vec<ClaimInput<OrchardPrivateInputs>>,
+    /// Notes found by the scan that have no claim input
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_notes: Vec<ExcludedNote>,
+    /// Airdrop epoch the claims target; `None` for the base epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u32>,
+    /// Fingerprints of the viewing key that prepared the claims; absent in claims prepared by
+    /// earlier versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_fingerprints: Option<KeyFingerprints>,
+}
+
+/// Salted fingerprints of the viewing keys of a wallet, see [`crate::base::key_fingerprint`].
+///
+/// Claims and secrets files carry the fingerprints of the wallet that prepared them, so claims
+/// and keys of different wallets are not mixed by accident.
+#[serde_as]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyFingerprints {
+    /// Fingerprint of the Sapling full viewing key
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sapling: Option<[u8; 32]>,
+    /// Fingerprint of the Orchard full viewing key
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orchard: Option<[u8; 32]>,
+}
+
+impl KeyFingerprints {
+    /// Whether both fingerprints name the same wallet: they share a pool and agree on every pool
+    /// they share.
+    #[must_use]
+    pub fn matches(&self, other: &Self) -> bool {
+        let shared: Vec<bool> = [(self.sapling, other.sapling), (self.orchard, other.orchard)]
+            .into_iter()
+            .filter_map(|pool| match pool {
+                (Some(ours), Some(theirs)) => Some(ours == theirs),
+                _ => None,
+            })
+            .collect();
+        !shared.is_empty() && shared.into_iter().all(|equal| equal)
+    }
+}
+
+/// Why a note found by the scan has no claim input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExclusionReason {
+    /// The note's nullifier is in the snapshot: it was spent before the snapshot height.
+    SpentBySnapshot,
+    /// The note's value is below the configured minimum note value.
+    BelowThreshold,
+    /// The scan has no note commitment witness for the note's position.
+    MissingPosition,
+}
+
+/// A note found by the scan that cannot be claimed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExcludedNote {
+    /// Pool of the note
+    pub pool: Pool,
+    /// Nullifier of the note
+    pub nullifier: Nullifier,
+    /// Note value in zatoshis
+    pub value: u64,
+    /// Block height where the note was created
+    pub block_height: u64,
+    /// Note position in the commitment tree
+    pub note_position: u64,
+    /// Reason the note is excluded
+    pub reason: ExclusionReason,
 }
 
 /// A non-membership proof demonstrating that a nullifier is not in the snapshot.
@@ -56,10 +128,18 @@ pub struct AirdropClaimInputs {
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaimInput<P> {
+    /// Stable identifier of the claim, carried into its proof and submission entries.
+    #[serde_as(as = "Hex")]
+    pub claim_id: [u8; 32],
     /// The public inputs for the non-membership proof.
     pub public_inputs: PublicInputs,
     /// The private inputs for the non-membership proof.
     pub private_inputs: P,
+    /// The note's nullifier on chain, which reveals when the claimed note is spent.
+    ///
+    /// Absent in claims prepared by earlier versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nullifier: Option<Nullifier>,
 }
 
 /// Private inputs for a Sapling airdrop claim proof.
@@ -170,8 +250,8 @@ pub struct PublicInputs {
 
 #[cfg(test)]
 mod tests {
-    use super::PublicInputs;
-    use crate::base::Nullifier;
+    use super::{ExcludedNote, ExclusionReason, KeyFingerprints, PublicInputs};
+    use crate::base::{Nullifier, Pool};
 
     #[test]
     fn public_inputs_serializes_nullifier_in_reversed_hex() {
@@ -188,4 +268,49 @@ mod tests {
             format!(r#"{{"airdrop_nullifier":"cd{}ab"}}"#, "00".repeat(30))
         );
     }
+
+    #[test]
+    fn excluded_note_serializes_reason_in_kebab_case() {
+        let note = ExcludedNote {
+            pool: Pool::Orchard,
+            nullifier: Nullifier::new([0; 32]),
+            value: 1_000,
+            block_height: 2_000_000,
+            note_position: 42,
+            reason: ExclusionReason::SpentBySnapshot,
+        };
+
+        let json = serde_json::to_value(&note).expect("serialize excluded note");
+        assert_eq!(json["reason"], "spent-by-snapshot");
+        let back: ExcludedNote = serde_json::from_value(json).expect("deserialize excluded note");
+        assert_eq!(back, note);
+    }
+
+    #[test]
+    fn key_fingerprints_match_on_shared_pools() {
+        let both = KeyFingerprints {
+            sapling: Some([1; 32]),
+            orchard: Some([2; 32]),
+        };
+        let sapling_only = KeyFingerprints {
+            sapling: Some([1; 32]),
+            orchard: None,
+        };
+        let orchard_only = KeyFingerprints {
+            sapling: None,
+            orchard: Some([2; 32]),
+        };
+        assert!(both.matches(&both));
+        assert!(both.matches(&sapling_only));
+        assert!(orchard_only.matches(&both));
+        assert!(!sapling_only.matches(&orchard_only));
+        assert!(!both.matches(&KeyFingerprints {
+            orchard: Some([3; 32]),
+            ..both
+        }));
+        assert!(!KeyFingerprints::default().matches(&KeyFingerprints::default()));
+
+        let json = serde_json::to_value(sapling_only).expect("serialize fingerprints");
+        assert_eq!(json, serde_json::json!({ "sapling": "01".repeat(32) }));
+    }
 }
@@ -6,10 +6,26 @@ use serde_with::serde_as;
 
 use crate::base::Nullifier;
 
+/// An Ed25519 signature by an operator key over the same digest as a claim's spend-auth
+/// signature.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Countersignature {
+    /// Ed25519 public key of the operator.
+    #[serde_as(as = "Hex")]
+    pub public_key: [u8; 32],
+    /// Ed25519 signature over the claim's submission digest.
+    #[serde_as(as = "Hex")]
+    pub signature: [u8; 64],
+}
+
 /// A signed Sapling claim entry ready for target-chain submission.
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SaplingSignedClaim {
+    /// Stable identifier of the claim.
+    #[serde_as(as = "Hex")]
+    pub claim_id: [u8; 32],
     /// The Groth16 proof bytes.
     #[serde_as(as = "Hex")]
     pub zkproof: [u8; 192],
@@ -35,12 +51,18 @@ pub struct SaplingSignedClaim {
     /// Spend authorization signature over the submission digest.
     #[serde_as(as = "Hex")]
     pub spend_auth_sig: [u8; 64],
+    /// Operator countersignatures over the same digest.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub countersignatures: Vec<Countersignature>,
 }
 
 /// A signed Orchard claim entry ready for target-chain submission.
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrchardSignedClaim {
+    /// Stable identifier of the claim.
+    #[serde_as(as = "Hex")]
+    pub claim_id: [u8; 32],
     /// The Halo2 proof bytes.
     #[serde_as(as = "Hex")]
     pub zkproof: Vec<u8>,
@@ -55,6 +77,10 @@ pub struct OrchardSignedClaim {
     #[serde_as(as = "Option<Hex>")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cv_sha256: Option<[u8; 32]>,
+    /// Poseidon value commitment bytes, if the active scheme is poseidon.
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cv_poseidon: Option<[u8; 32]>,
     /// Airdrop nullifier used for double-claim prevention.
     pub airdrop_nullifier: Nullifier,
     /// Hash of this claim's unsigned proof fields.
@@ -66,16 +92,29 @@ pub struct OrchardSignedClaim {
     /// Spend authorization signature over the submission digest.
     #[serde_as(as = "Hex")]
     pub spend_auth_sig: [u8; 64],
+    /// Operator countersignatures over the same digest.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub countersignatures: Vec<Countersignature>,
 }
 
 /// Signed claims grouped by pool for submission.
+///
+/// Every claim signature covers the submission `nonce` and `expiry_height`.
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaimSubmission {
+    /// Random per-submission nonce.
+    #[serde_as(as = "Hex")]
+    pub nonce: [u8; 32],
+    /// Last block height at which the submission may be accepted.
+    pub expiry_height: u64,
     /// Signed Sapling claims.
     #[serde(default)]
     pub sapling: Vec<SaplingSignedClaim>,
     /// Signed Orchard claims.
     #[serde(default)]
     pub orchard: Vec<OrchardSignedClaim>,
+    /// Airdrop epoch the claims target; `None` for the base epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u32>,
 }
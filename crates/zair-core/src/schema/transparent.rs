@@ -0,0 +1,174 @@
+//! Transparent snapshot schema.
+//!
+//! The organizer publishes the transparent outputs that count towards the airdrop as a
+//! [`TransparentUtxoSet`], and the configuration binds it by [`TransparentUtxoSet::hash`]. A
+//! transparent claim is a signature by the key of an address over its balance in the set.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use thiserror::Error;
+
+use crate::base::{ReversedHex, hash_bytes};
+
+/// Domain marker prepended to the UTXO set hash preimage.
+pub const TRANSPARENT_UTXO_SET_TAG: &[u8; 8] = b"ZAIR_TUX";
+
+/// A UTXO set that cannot be hashed or summed.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TransparentUtxoSetError {
+    /// An address is longer than 255 bytes.
+    #[error("transparent address {0:?} exceeds 255 bytes")]
+    AddressTooLong(String),
+    /// The values of the set do not fit in a `u64`.
+    #[error("total value of the transparent UTXO set overflows")]
+    ValueOverflow,
+}
+
+/// A transparent output unspent at the snapshot.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TransparentUtxo {
+    /// Encoded transparent address the output pays to.
+    pub address: String,
+    /// ID of the transaction that created the output, in RPC byte order.
+    #[serde_as(as = "ReversedHex")]
+    pub txid: [u8; 32],
+    /// Index of the output in its transaction.
+    pub index: u32,
+    /// Value of the output in zatoshis.
+    pub value: u64,
+    /// Height of the block that mined the output.
+    pub height: u64,
+}
+
+/// Transparent outputs unspent at the snapshot, as published by the organizer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransparentUtxoSet {
+    /// Snapshot block height (inclusive) of the set.
+    pub snapshot_height: u64,
+    /// Outputs sorted by address, then outpoint.
+    pub utxos: Vec<TransparentUtxo>,
+}
+
+impl TransparentUtxoSet {
+    /// UTXO set at `snapshot_height`, in canonical order and without duplicate outputs.
+    #[must_use]
+    pub fn new(snapshot_height: u64, mut utxos: Vec<TransparentUtxo>) -> Self {
+        utxos.sort();
+        utxos.dedup_by(|a, b| a.txid == b.txid && a.index == b.index);
+        Self {
+            snapshot_height,
+            utxos,
+        }
+    }
+
+    /// Hash of the set, as bound into the configuration and every transparent claim signature.
+    ///
+    /// Preimage layout:
+    /// `ZAIR_TUX || snapshot_height:u64le || count:u64le || (address_len:u8 || address || txid ||
+    /// index:u32le || value:u64le || height:u64le)*`, over the outputs in file order.
+    ///
+    /// # Errors
+    /// Returns an error if an address is longer than 255 bytes.
+    pub fn hash(&self) -> Result<[u8; 32], TransparentUtxoSetError> {
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(TRANSPARENT_UTXO_SET_TAG);
+        preimage.extend_from_slice(&self.snapshot_height.to_le_bytes());
+        preimage.extend_from_slice(
+            &u64::try_from(self.utxos.len())
+                .unwrap_or(u64::MAX)
+                .to_le_bytes(),
+        );
+        for utxo in &self.utxos {
+            let address_len = u8::try_from(utxo.address.len())
+                .map_err(|_| TransparentUtxoSetError::AddressTooLong(utxo.address.clone()))?;
+            preimage.push(address_len);
+            preimage.extend_from_slice(utxo.address.as_bytes());
+            preimage.extend_from_slice(&utxo.txid);
+            preimage.extend_from_slice(&utxo.index.to_le_bytes());
+            preimage.extend_from_slice(&utxo.value.to_le_bytes());
+            preimage.extend_from_slice(&utxo.height.to_le_bytes());
+        }
+        Ok(hash_bytes(&preimage))
+    }
+
+    /// Claimable balance of every address in the set.
+    ///
+    /// # Errors
+    /// Returns an error if a balance overflows.
+    pub fn balances(&self) -> Result<BTreeMap<&str, u64>, TransparentUtxoSetError> {
+        let mut balances = BTreeMap::<&str, u64>::new();
+        for utxo in &self.utxos {
+            let balance = balances.entry(utxo.address.as_str()).or_default();
+            *balance = balance
+                .checked_add(utxo.value)
+                .ok_or(TransparentUtxoSetError::ValueOverflow)?;
+        }
+        Ok(balances)
+    }
+
+    /// Total value of the set in zatoshis.
+    ///
+    /// # Errors
+    /// Returns an error if the total overflows.
+    pub fn total_value(&self) -> Result<u64, TransparentUtxoSetError> {
+        self.utxos.iter().try_fold(0_u64, |total, utxo| {
+            total
+                .checked_add(utxo.value)
+                .ok_or(TransparentUtxoSetError::ValueOverflow)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utxo(address: &str, txid: u8, value: u64) -> TransparentUtxo {
+        TransparentUtxo {
+            address: address.to_owned(),
+            txid: [txid; 32],
+            index: 0,
+            value,
+            height: 100,
+        }
+    }
+
+    #[test]
+    fn utxo_sets_are_canonical() {
+        let set = TransparentUtxoSet::new(
+            200,
+            vec![
+                utxo("tmB", 1, 5),
+                utxo("tmA", 2, 7),
+                utxo("tmB", 3, 6),
+                utxo("tmA", 2, 7),
+            ],
+        );
+        let reordered = TransparentUtxoSet::new(
+            200,
+            vec![utxo("tmB", 3, 6), utxo("tmA", 2, 7), utxo("tmB", 1, 5)],
+        );
+        assert_eq!(set, reordered);
+        assert_eq!(set.hash(), reordered.hash());
+        assert_eq!(set.total_value(), Ok(18));
+        assert_eq!(
+            set.balances()
+                .expect("balances")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![("tmA", 7), ("tmB", 11)]
+        );
+
+        let later = TransparentUtxoSet::new(201, reordered.utxos);
+        assert_ne!(set.hash(), later.hash());
+        let overflow =
+            TransparentUtxoSet::new(200, vec![utxo("tmA", 1, u64::MAX), utxo("tmA", 2, 1)]);
+        assert_eq!(
+            overflow.total_value(),
+            Err(TransparentUtxoSetError::ValueOverflow)
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Canonical claim messages.
+//!
+//! A claim signature covers the hash of a message the target chain interprets. Instead of a
+//! free-form file, [`ClaimMessage`] fixes what the message says and how it is serialized, so the
+//! claimant and the target chain derive the same bytes from the same fields:
+//!
+//! `ZAIR_MSG || version:u8 || config_hash || claim_id || recipient_len:u8 || recipient ||
+//! amount_commitment || nonce`
+//!
+//! The bytes of [`ClaimMessage::to_bytes`] are what `claim sign` reads as a message file, so the
+//! submission's `message_hash` is [`ClaimMessage::hash`].
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use thiserror::Error;
+
+use crate::base::{hash_bytes, hash_message};
+
+/// Domain marker prepended to canonical claim messages.
+pub const CLAIM_MESSAGE_TAG: &[u8; 8] = b"ZAIR_MSG";
+/// Version byte of the canonical claim message layout.
+pub const CLAIM_MESSAGE_VERSION: u8 = 1;
+
+/// A claim message that cannot be serialized.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SighashError {
+    /// The recipient is longer than 255 bytes.
+    #[error("recipient length exceeds 255 bytes")]
+    RecipientTooLong,
+}
+
+/// What a claimant authorizes the target chain to do with one claim.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ClaimMessage {
+    /// Hash of the published airdrop configuration file, see [`config_hash`].
+    #[serde_as(as = "Hex")]
+    #[schemars(with = "String")]
+    pub config_hash: [u8; 32],
+    /// Stable identifier of the claim.
+    #[serde_as(as = "Hex")]
+    #[schemars(with = "String")]
+    pub claim_id: [u8; 32],
+    /// Target-chain address that receives the claimed amount.
+    pub recipient: String,
+    /// Value commitment of the claim proof (`cv`, `cv_sha256` or `cv_poseidon`).
+    #[serde_as(as = "Hex")]
+    #[schemars(with = "String")]
+    pub amount_commitment: [u8; 32],
+    /// Random nonce that makes the message unique.
+    #[serde_as(as = "Hex")]
+    #[schemars(with = "String")]
+    pub nonce: [u8; 32],
+}
+
+impl ClaimMessage {
+    /// Canonical serialization of the message.
+    ///
+    /// # Errors
+    /// Returns an error if the recipient is longer than 255 bytes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SighashError> {
+        let recipient_len =
+            u8::try_from(self.recipient.len()).map_err(|_| SighashError::RecipientTooLong)?;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(CLAIM_MESSAGE_TAG);
+        bytes.push(CLAIM_MESSAGE_VERSION);
+        bytes.extend_from_slice(&self.config_hash);
+        bytes.extend_from_slice(&self.claim_id);
+        bytes.push(recipient_len);
+        bytes.extend_from_slice(self.recipient.as_bytes());
+        bytes.extend_from_slice(&self.amount_commitment);
+        bytes.extend_from_slice(&self.nonce);
+        Ok(bytes)
+    }
+
+    /// Message hash covered by the claim signature.
+    ///
+    /// # Errors
+    /// Returns an error if the recipient is longer than 255 bytes.
+    pub fn hash(&self) -> Result<[u8; 32], SighashError> {
+        Ok(hash_message(&self.to_bytes()?))
+    }
+}
+
+/// Hash of the published airdrop configuration file, over its exact bytes.
+#[must_use]
+pub fn config_hash(configuration_file: &[u8]) -> [u8; 32] {
+    hash_bytes(configuration_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector() -> ClaimMessage {
+        ClaimMessage {
+            config_hash: [0x11; 32],
+            claim_id: [0x22; 32],
+            recipient: "tnam1qxgzu3ynnd0e6l8mh3zk7w7yd9ynjgw5jqqzmsdx".to_owned(),
+            amount_commitment: [0x33; 32],
+            nonce: [0x44; 32],
+        }
+    }
+
+    #[test]
+    fn claim_message_matches_test_vector() {
+        let message = vector();
+        let bytes = message.to_bytes().expect("serialize");
+        assert_eq!(bytes.len(), 8 + 1 + 32 + 32 + 1 + 45 + 32 + 32);
+        assert_eq!(
+            hex::encode(message.hash().expect("hash")),
+            "7346dfe5583c744868a2d88f1b07ef99b1f8e761435d98c369447f731e103fe5"
+        );
+        assert_eq!(
+            hex::encode(config_hash(br#"{"network":"testnet"}"#)),
+            "b27d233be4738ee467245ee1d7e1108583c3894e73df18c29829d2e3da5f3cd6"
+        );
+    }
+
+    #[test]
+    fn claim_message_round_trips_through_json() {
+        let message = vector();
+        let json = serde_json::to_string(&message).expect("serialize");
+        assert_eq!(
+            serde_json::from_str::<ClaimMessage>(&json).expect("deserialize"),
+            message
+        );
+
+        let schema = serde_json::to_value(schemars::schema_for!(ClaimMessage)).expect("schema");
+        assert!(schema["properties"]["recipient"].is_object(), "{schema}");
+
+        let too_long = ClaimMessage {
+            recipient: "a".repeat(256),
+            ..message
+        };
+        assert_eq!(too_long.to_bytes(), Err(SighashError::RecipientTooLong));
+    }
+}
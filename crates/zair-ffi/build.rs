@@ -0,0 +1,17 @@
+//! Generates the C header `include/zair.h` from the exported functions.
+
+use std::path::PathBuf;
+
+fn main() {
+    let crate_dir = PathBuf::from(
+        std::env::var_os("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR"),
+    );
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("cbindgen.toml should be valid");
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("C header should generate")
+        .write_to_file(crate_dir.join("include").join("zair.h"));
+}
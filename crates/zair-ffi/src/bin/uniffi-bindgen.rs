@@ -0,0 +1,5 @@
+//! Generates the Kotlin and Swift bindings of `zair-ffi`.
+
+fn main() {
+    uniffi::uniffi_bindgen_main();
+}
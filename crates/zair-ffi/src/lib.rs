@@ -0,0 +1,450 @@
+//! C bindings for the ZAIR claim pipeline.
+//!
+//! Exposes the in-memory [`zair_sdk::pipeline`] stages (prepare, prove, sign, verify) as
+//! `extern "C"` functions over byte buffers, so mobile wallets (Swift, Kotlin through JNI) can
+//! embed claim generation. The build script writes the matching header to `include/zair.h`.
+//!
+//! Conventions:
+//! - Configuration, claims, proofs, secrets and submissions cross the boundary as UTF-8 JSON, in
+//!   the same format as the files written by the CLI.
+//! - Inputs are borrowed [`ZairSlice`]s. Outputs are [`ZairBuffer`]s owned by the caller and
+//!   released with [`zair_buffer_free`].
+//! - Every function returns a [`ZairStatus`]. On failure, [`zair_last_error_message`] returns the
+//!   message of the last error raised on the calling thread.
+//!
+//! With the `uniffi` feature, the [`mobile`] module additionally exports the same stages through
+//! UniFFI, which generates Kotlin and Swift bindings directly.
+
+#![allow(unsafe_code, reason = "The C ABI boundary requires raw pointers")]
+
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+mod stages;
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use stages::{Failure, parse_utf8};
+use zair_sdk::error::ZairError;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+/// Status code returned by every `zair_*` function.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZairStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A pointer, length or encoding passed by the caller is invalid.
+    InvalidArgument = 1,
+    /// Invalid configuration, key material or JSON input.
+    Config = 2,
+    /// Lightwalletd or HTTP communication failed.
+    Network = 3,
+    /// Snapshot collection or non-membership tree construction failed.
+    Snapshot = 4,
+    /// Claim proof generation failed.
+    Proving = 5,
+    /// Claim submission signing failed.
+    Signing = 6,
+    /// Proof or signature verification failed.
+    Verification = 7,
+    /// The library panicked; the panic was caught at the boundary.
+    Panic = 8,
+}
+
+impl From<&ZairError> for ZairStatus {
+    fn from(error: &ZairError) -> Self {
+        match error {
+            ZairError::Config(_) => Self::Config,
+            ZairError::Network(_) => Self::Network,
+            ZairError::Snapshot(_) => Self::Snapshot,
+            ZairError::Proving(_) => Self::Proving,
+            ZairError::Signing(_) => Self::Signing,
+            ZairError::Verification(_) => Self::Verification,
+        }
+    }
+}
+
+/// Borrowed input bytes. A null `data` pointer is read as "not provided".
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ZairSlice {
+    /// Start of the bytes, or null.
+    pub data: *const u8,
+    /// Number of bytes at `data`.
+    pub len: usize,
+}
+
+impl ZairSlice {
+    /// # Safety
+    /// `data` must be null or point to `len` readable bytes that outlive the call.
+    const unsafe fn as_option<'a>(self) -> Option<&'a [u8]> {
+        if self.data.is_null() {
+            None
+        } else {
+            // SAFETY: upheld by the caller.
+            Some(unsafe { std::slice::from_raw_parts(self.data, self.len) })
+        }
+    }
+
+    /// # Safety
+    /// Same as [`Self::as_option`].
+    const unsafe fn as_bytes<'a>(self) -> &'a [u8] {
+        match unsafe { self.as_option() } {
+            Some(bytes) => bytes,
+            None => &[],
+        }
+    }
+}
+
+/// Bytes allocated by the library and owned by the caller.
+///
+/// Release with [`zair_buffer_free`]. An empty buffer has a null `data` pointer.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ZairBuffer {
+    /// Start of the bytes, or null when empty.
+    pub data: *mut u8,
+    /// Number of bytes at `data`.
+    pub len: usize,
+}
+
+impl ZairBuffer {
+    const fn empty() -> Self {
+        Self {
+            data: std::ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        if bytes.is_empty() {
+            return Self::empty();
+        }
+        let boxed = bytes.into_boxed_slice();
+        let len = boxed.len();
+        Self {
+            data: Box::into_raw(boxed).cast::<u8>(),
+            len,
+        }
+    }
+}
+
+/// Release a buffer returned by this library. Passing an empty buffer is a no-op.
+///
+/// # Safety
+/// `buffer` must have been returned by this library and not freed before.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zair_buffer_free(buffer: ZairBuffer) {
+    if buffer.data.is_null() {
+        return;
+    }
+    let slice = std::ptr::slice_from_raw_parts_mut(buffer.data, buffer.len);
+    // SAFETY: the buffer was created by `ZairBuffer::from_vec` from a boxed slice of this length.
+    drop(unsafe { Box::from_raw(slice) });
+}
+
+/// Message of the last error raised on the calling thread, as UTF-8 bytes.
+///
+/// Returns an empty buffer if the last call succeeded. The caller owns the returned buffer.
+#[must_use]
+#[unsafe(no_mangle)]
+pub extern "C" fn zair_last_error_message() -> ZairBuffer {
+    LAST_ERROR.with_borrow(|message| {
+        message.as_ref().map_or_else(ZairBuffer::empty, |message| {
+            ZairBuffer::from_vec(message.clone().into_bytes())
+        })
+    })
+}
+
+/// Scan the chain for the notes of a viewing key and build the claim inputs.
+///
+/// - `config_json`: airdrop configuration JSON.
+/// - `unified_full_viewing_key`: UTF-8 encoded UFVK.
+/// - `lightwalletd_url`: UTF-8 URL; null uses the network's default endpoint.
+/// - `sapling_snapshot`, `orchard_snapshot`: concatenated 32-byte snapshot nullifiers; null for
+///   pools disabled in the configuration.
+/// - `out_claims_json`: receives the claim inputs JSON on success.
+///
+/// # Safety
+/// Every non-null slice must point to `len` readable bytes. `out_claims_json` must point to
+/// writable memory for one [`ZairBuffer`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zair_prepare(
+    config_json: ZairSlice,
+    unified_full_viewing_key: ZairSlice,
+    birthday_height: u64,
+    lightwalletd_url: ZairSlice,
+    sapling_snapshot: ZairSlice,
+    orchard_snapshot: ZairSlice,
+    out_claims_json: *mut ZairBuffer,
+) -> ZairStatus {
+    run(|| {
+        ensure_out(out_claims_json)?;
+        let ufvk = parse_utf8(
+            unsafe { unified_full_viewing_key.as_bytes() },
+            "viewing key",
+        )?;
+        let lightwalletd_url = unsafe { lightwalletd_url.as_option() }
+            .map(|bytes| parse_utf8(bytes, "lightwalletd URL").map(str::to_owned))
+            .transpose()?;
+        let claims = stages::prepare(
+            unsafe { config_json.as_bytes() },
+            ufvk,
+            birthday_height,
+            lightwalletd_url,
+            unsafe { sapling_snapshot.as_option() },
+            unsafe { orchard_snapshot.as_option() },
+        )?;
+        unsafe { write_output(out_claims_json, claims) };
+        Ok(())
+    })
+}
+
+/// Generate claim proofs and the local-only secrets needed to sign them.
+///
+/// - `claims_json`: claim inputs JSON from [`zair_prepare`].
+/// - `config_json`: airdrop configuration JSON.
+/// - `seed`: 64-byte wallet seed.
+/// - `sapling_proving_key`: Sapling Groth16 proving parameters; null if there are no Sapling
+///   claims.
+/// - `orchard_params`: Orchard Halo2 params; null if there are no Orchard claims.
+/// - `out_proofs_json`, `out_secrets_json`: receive the proofs and secrets JSON on success.
+///
+/// # Safety
+/// Every non-null slice must point to `len` readable bytes. Both out pointers must point to
+/// writable memory for one [`ZairBuffer`].
+#[cfg(feature = "prove")]
+#[allow(
+    clippy::too_many_arguments,
+    reason = "C callers pass every input as a separate parameter"
+)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zair_prove(
+    claims_json: ZairSlice,
+    config_json: ZairSlice,
+    seed: ZairSlice,
+    account_id: u32,
+    sapling_proving_key: ZairSlice,
+    orchard_params: ZairSlice,
+    out_proofs_json: *mut ZairBuffer,
+    out_secrets_json: *mut ZairBuffer,
+) -> ZairStatus {
+    run(|| {
+        ensure_out(out_proofs_json)?;
+        ensure_out(out_secrets_json)?;
+        let (proofs, secrets) = stages::prove(
+            unsafe { claims_json.as_bytes() },
+            unsafe { config_json.as_bytes() },
+            unsafe { seed.as_bytes() },
+            account_id,
+            unsafe { sapling_proving_key.as_option() },
+            unsafe { orchard_params.as_option() },
+        )?;
+        unsafe {
+            write_output(out_proofs_json, proofs);
+            write_output(out_secrets_json, secrets);
+        }
+        Ok(())
+    })
+}
+
+/// Sign claim proofs into a submission package.
+///
+/// - `proofs_json`, `secrets_json`: outputs of [`zair_prove`].
+/// - `config_json`: airdrop configuration JSON.
+/// - `seed`: 64-byte wallet seed.
+/// - `message`: message bound into every claim signature.
+/// - `expiry_height`: last block height at which the submission may be accepted; `0` uses the end
+///   of the claim window in the configuration.
+/// - `out_submission_json`: receives the submission JSON on success.
+///
+/// # Safety
+/// Every non-null slice must point to `len` readable bytes. `out_submission_json` must point to
+/// writable memory for one [`ZairBuffer`].
+#[allow(
+    clippy::too_many_arguments,
+    reason = "C callers pass every input as a separate parameter"
+)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zair_sign(
+    proofs_json: ZairSlice,
+    secrets_json: ZairSlice,
+    config_json: ZairSlice,
+    seed: ZairSlice,
+    account_id: u32,
+    message: ZairSlice,
+    expiry_height: u64,
+    out_submission_json: *mut ZairBuffer,
+) -> ZairStatus {
+    run(|| {
+        ensure_out(out_submission_json)?;
+        let submission = stages::sign(
+            unsafe { proofs_json.as_bytes() },
+            unsafe { secrets_json.as_bytes() },
+            unsafe { config_json.as_bytes() },
+            unsafe { seed.as_bytes() },
+            account_id,
+            unsafe { message.as_bytes() },
+            (expiry_height != 0).then_some(expiry_height),
+        )?;
+        unsafe { write_output(out_submission_json, submission) };
+        Ok(())
+    })
+}
+
+/// Verify a submission end to end: signatures first, then the carried proofs.
+///
+/// - `submission_json`: output of [`zair_sign`].
+/// - `config_json`: airdrop configuration JSON.
+/// - `message`: message the claims were signed with.
+/// - `sapling_verifying_key`: Sapling Groth16 verifying key; null if there are no Sapling claims.
+/// - `orchard_params`: Orchard Halo2 params; null if there are no Orchard claims.
+/// - `current_height`: current chain height, rejecting the submission if it has expired; `0` skips
+///   the check.
+///
+/// Returns [`ZairStatus::Ok`] only if every signature and proof is valid.
+///
+/// # Safety
+/// Every non-null slice must point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zair_verify(
+    submission_json: ZairSlice,
+    config_json: ZairSlice,
+    message: ZairSlice,
+    sapling_verifying_key: ZairSlice,
+    orchard_params: ZairSlice,
+    current_height: u64,
+) -> ZairStatus {
+    run(|| {
+        stages::verify(
+            unsafe { submission_json.as_bytes() },
+            unsafe { config_json.as_bytes() },
+            unsafe { message.as_bytes() },
+            unsafe { sapling_verifying_key.as_option() },
+            unsafe { orchard_params.as_option() },
+            (current_height != 0).then_some(current_height),
+        )
+    })
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Run an exported function body, record its error and catch panics at the boundary.
+fn run(body: impl FnOnce() -> Result<(), Failure>) -> ZairStatus {
+    let result = catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        Err(Failure {
+            status: ZairStatus::Panic,
+            message: panic_message(payload.as_ref()),
+        })
+    });
+    match result {
+        Ok(()) => {
+            LAST_ERROR.set(None);
+            ZairStatus::Ok
+        }
+        Err(failure) => {
+            LAST_ERROR.set(Some(failure.message));
+            failure.status
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| (*message).to_owned())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic".to_owned())
+}
+
+fn ensure_out(out: *mut ZairBuffer) -> Result<(), Failure> {
+    if out.is_null() {
+        return Err(Failure::invalid_argument("Output pointer must not be null"));
+    }
+    Ok(())
+}
+
+/// # Safety
+/// `out` must be non-null and point to writable memory for one [`ZairBuffer`].
+unsafe fn write_output(out: *mut ZairBuffer, json: String) {
+    // SAFETY: upheld by the caller.
+    unsafe { out.write(ZairBuffer::from_vec(json.into_bytes())) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slice(bytes: &[u8]) -> ZairSlice {
+        ZairSlice {
+            data: bytes.as_ptr(),
+            len: bytes.len(),
+        }
+    }
+
+    fn last_error() -> String {
+        let buffer = zair_last_error_message();
+        let message = if buffer.data.is_null() {
+            String::new()
+        } else {
+            let bytes = unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) };
+            String::from_utf8(bytes.to_vec()).expect("error message is UTF-8")
+        };
+        unsafe { zair_buffer_free(buffer) };
+        message
+    }
+
+    #[test]
+    fn invalid_json_reports_config_status() {
+        let mut out = ZairBuffer::empty();
+        let status = unsafe {
+            zair_sign(
+                slice(b"not json"),
+                slice(b"{}"),
+                slice(b"{}"),
+                slice(&[0_u8; 64]),
+                0,
+                slice(b""),
+                0,
+                &raw mut out,
+            )
+        };
+
+        assert_eq!(status, ZairStatus::Config);
+        assert!(out.data.is_null());
+        assert!(last_error().starts_with("Invalid claim proofs JSON"));
+    }
+
+    #[test]
+    fn null_out_pointer_is_rejected() {
+        let status = unsafe {
+            zair_sign(
+                slice(b"{}"),
+                slice(b"{}"),
+                slice(b"{}"),
+                slice(&[0_u8; 64]),
+                0,
+                slice(b""),
+                0,
+                std::ptr::null_mut(),
+            )
+        };
+
+        assert_eq!(status, ZairStatus::InvalidArgument);
+    }
+
+    #[test]
+    fn buffer_round_trips_and_frees() {
+        let buffer = ZairBuffer::from_vec(b"zair".to_vec());
+        let bytes = unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) };
+        assert_eq!(bytes, b"zair");
+        unsafe { zair_buffer_free(buffer) };
+        unsafe { zair_buffer_free(ZairBuffer::empty()) };
+    }
+}
@@ -0,0 +1,185 @@
+//! UniFFI bindings for Kotlin and Swift wallets.
+//!
+//! Exposes the prepare (scan), prove and sign stages as idiomatic functions with typed errors, so
+//! wallets such as Zashi can integrate the airdrop without hand-written JNI or C glue. The
+//! functions block until the stage completes; call them off the UI thread.
+//!
+//! Generate the bindings from the built library with the bundled `uniffi-bindgen` binary:
+//!
+//! ```sh
+//! cargo build --release -p zair-ffi --features uniffi
+//! cargo run -p zair-ffi --features uniffi --bin uniffi-bindgen -- generate \
+//!   --library target/release/libzair_ffi.so --language kotlin --out-dir bindings
+//! ```
+
+#![allow(
+    clippy::needless_pass_by_value,
+    reason = "UniFFI passes every argument by value"
+)]
+
+use crate::ZairStatus;
+use crate::stages::{self, Failure};
+
+/// Failure of a claim pipeline stage, by category.
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum ClaimError {
+    /// An argument is malformed, such as a seed of the wrong length.
+    #[error("{message}")]
+    InvalidArgument {
+        /// Error message.
+        message: String,
+    },
+    /// Invalid configuration, key material or JSON input.
+    #[error("{message}")]
+    Config {
+        /// Error message.
+        message: String,
+    },
+    /// Lightwalletd or HTTP communication failed.
+    #[error("{message}")]
+    Network {
+        /// Error message.
+        message: String,
+    },
+    /// Snapshot collection or non-membership tree construction failed.
+    #[error("{message}")]
+    Snapshot {
+        /// Error message.
+        message: String,
+    },
+    /// Claim proof generation failed.
+    #[error("{message}")]
+    Proving {
+        /// Error message.
+        message: String,
+    },
+    /// Claim submission signing failed.
+    #[error("{message}")]
+    Signing {
+        /// Error message.
+        message: String,
+    },
+    /// Proof or signature verification failed.
+    #[error("{message}")]
+    Verification {
+        /// Error message.
+        message: String,
+    },
+    /// Unexpected internal failure.
+    #[error("{message}")]
+    Internal {
+        /// Error message.
+        message: String,
+    },
+}
+
+impl From<Failure> for ClaimError {
+    fn from(failure: Failure) -> Self {
+        let message = failure.message;
+        match failure.status {
+            ZairStatus::InvalidArgument => Self::InvalidArgument { message },
+            ZairStatus::Config => Self::Config { message },
+            ZairStatus::Network => Self::Network { message },
+            ZairStatus::Snapshot => Self::Snapshot { message },
+            ZairStatus::Proving => Self::Proving { message },
+            ZairStatus::Signing => Self::Signing { message },
+            ZairStatus::Verification => Self::Verification { message },
+            ZairStatus::Ok | ZairStatus::Panic => Self::Internal { message },
+        }
+    }
+}
+
+/// Proofs and local-only secrets produced by [`prove_claims`].
+#[derive(Debug, uniffi::Record)]
+pub struct ClaimProofs {
+    /// Claim proofs JSON, safe to share.
+    pub proofs_json: String,
+    /// Claim secrets JSON, needed for signing. Keep it on the device.
+    pub secrets_json: String,
+}
+
+/// Scan the chain for the notes of a viewing key and build the claim inputs JSON.
+///
+/// `lightwalletd_url` defaults to the network's public endpoint. The snapshots are the raw
+/// `snapshot-*.bin` contents and are required for every pool enabled in the configuration.
+///
+/// # Errors
+/// Returns an error if an input is invalid, scanning fails, or a snapshot root does not match the
+/// configuration.
+#[uniffi::export]
+pub fn prepare_claims(
+    config_json: String,
+    unified_full_viewing_key: String,
+    birthday_height: u64,
+    lightwalletd_url: Option<String>,
+    sapling_snapshot: Option<Vec<u8>>,
+    orchard_snapshot: Option<Vec<u8>>,
+) -> Result<String, ClaimError> {
+    Ok(stages::prepare(
+        config_json.as_bytes(),
+        &unified_full_viewing_key,
+        birthday_height,
+        lightwalletd_url,
+        sapling_snapshot.as_deref(),
+        orchard_snapshot.as_deref(),
+    )?)
+}
+
+/// Generate claim proofs for the claim inputs JSON of [`prepare_claims`].
+///
+/// `seed` is the 64-byte wallet seed. The setup artifacts are the raw file contents and are only
+/// needed for pools that have claims.
+///
+/// # Errors
+/// Returns an error if an input is invalid, the seed does not match the claims, or proving fails.
+#[cfg(feature = "prove")]
+#[uniffi::export]
+pub fn prove_claims(
+    claims_json: String,
+    config_json: String,
+    seed: Vec<u8>,
+    account_id: u32,
+    sapling_proving_key: Option<Vec<u8>>,
+    orchard_params: Option<Vec<u8>>,
+) -> Result<ClaimProofs, ClaimError> {
+    let (proofs_json, secrets_json) = stages::prove(
+        claims_json.as_bytes(),
+        config_json.as_bytes(),
+        &seed,
+        account_id,
+        sapling_proving_key.as_deref(),
+        orchard_params.as_deref(),
+    )?;
+    Ok(ClaimProofs {
+        proofs_json,
+        secrets_json,
+    })
+}
+
+/// Sign the output of [`prove_claims`] into a submission JSON bound to `message`.
+///
+/// The submission expires at `expiry_height`, or at the end of the configured claim window if
+/// `None`.
+///
+/// # Errors
+/// Returns an error if an input is invalid, proofs and secrets do not match, no expiry height is
+/// known, or signing fails.
+#[uniffi::export]
+pub fn sign_submission(
+    proofs: ClaimProofs,
+    config_json: String,
+    seed: Vec<u8>,
+    account_id: u32,
+    message: Vec<u8>,
+    expiry_height: Option<u64>,
+) -> Result<String, ClaimError> {
+    Ok(stages::sign(
+        proofs.proofs_json.as_bytes(),
+        proofs.secrets_json.as_bytes(),
+        config_json.as_bytes(),
+        &seed,
+        account_id,
+        &message,
+        expiry_height,
+    )?)
+}
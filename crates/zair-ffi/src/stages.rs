@@ -0,0 +1,245 @@
+//! Pipeline stages over serialized inputs, shared by the C and UniFFI bindings.
+//!
+//! Structured values are parsed from and serialized to the JSON formats of the CLI files; setup
+//! artifacts and snapshots are the raw file contents.
+
+use std::io::Cursor;
+use std::sync::{Arc, OnceLock};
+
+#[cfg(feature = "prove")]
+use bellman::groth16::Parameters;
+use bellman::groth16::VerifyingKey;
+use bls12_381::Bls12;
+use halo2_proofs::poly::commitment::Params;
+use pasta_curves::vesta;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::runtime::Runtime;
+#[cfg(feature = "prove")]
+use zair_sapling_proofs::prover::ClaimParameters;
+use zair_sdk::error::ZairError;
+#[cfg(feature = "prove")]
+use zair_sdk::pipeline::{AirdropClaimInputs, OsProofRng, ProvingParams};
+use zair_sdk::pipeline::{
+    AirdropConfiguration, ClaimMessages, ClaimProofsOutput, ClaimSecretsOutput, ClaimSubmission,
+    Nullifier, VerifyingParams,
+};
+
+use crate::ZairStatus;
+
+/// Failure of a stage, before it is reported to the caller.
+pub struct Failure {
+    pub status: ZairStatus,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Self {
+            status: ZairStatus::InvalidArgument,
+            message: message.into(),
+        }
+    }
+
+    fn config(message: impl Into<String>) -> Self {
+        Self {
+            status: ZairStatus::Config,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<ZairError> for Failure {
+    fn from(error: ZairError) -> Self {
+        Self {
+            status: ZairStatus::from(&error),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Scan the chain and build the claim inputs JSON.
+pub fn prepare(
+    config_json: &[u8],
+    unified_full_viewing_key: &str,
+    birthday_height: u64,
+    lightwalletd_url: Option<String>,
+    sapling_snapshot: Option<&[u8]>,
+    orchard_snapshot: Option<&[u8]>,
+) -> Result<String, Failure> {
+    let config: AirdropConfiguration = parse_json(config_json, "configuration")?;
+    let claims = runtime().block_on(async {
+        let sapling = match sapling_snapshot {
+            Some(bytes) => Some(read_snapshot(bytes, "Sapling").await?),
+            None => None,
+        };
+        let orchard = match orchard_snapshot {
+            Some(bytes) => Some(read_snapshot(bytes, "Orchard").await?),
+            None => None,
+        };
+        zair_sdk::pipeline::prepare_claims(
+            &config,
+            unified_full_viewing_key,
+            birthday_height,
+            lightwalletd_url,
+            sapling,
+            orchard,
+        )
+        .await
+        .map_err(Failure::from)
+    })?;
+    to_json(&claims)
+}
+
+/// Generate the proofs and secrets JSON for the claim inputs JSON.
+#[cfg(feature = "prove")]
+pub fn prove(
+    claims_json: &[u8],
+    config_json: &[u8],
+    seed: &[u8],
+    account_id: u32,
+    sapling_proving_key: Option<&[u8]>,
+    orchard_params: Option<&[u8]>,
+) -> Result<(String, String), Failure> {
+    let claims: AirdropClaimInputs = parse_json(claims_json, "claim inputs")?;
+    let config: AirdropConfiguration = parse_json(config_json, "configuration")?;
+    let seed = parse_seed(seed)?;
+    let sapling = sapling_proving_key
+        .map(|bytes| {
+            Parameters::<Bls12>::read(bytes, false)
+                .map(|params| Arc::new(ClaimParameters(params)))
+                .map_err(|e| Failure::config(format!("Failed to read Sapling proving key: {e}")))
+        })
+        .transpose()?;
+    let params = ProvingParams {
+        sapling,
+        orchard: read_orchard_params(orchard_params)?,
+    };
+
+    let (proofs, secrets) = runtime()
+        .block_on(zair_sdk::pipeline::prove_claims(
+            claims,
+            &config,
+            seed,
+            account_id,
+            &params,
+            Arc::new(OsProofRng),
+        ))
+        .map_err(Failure::from)?;
+    Ok((to_json(&proofs)?, to_json(&secrets)?))
+}
+
+/// Sign the proofs JSON into a submission JSON.
+pub fn sign(
+    proofs_json: &[u8],
+    secrets_json: &[u8],
+    config_json: &[u8],
+    seed: &[u8],
+    account_id: u32,
+    message: &[u8],
+    expiry_height: Option<u64>,
+) -> Result<String, Failure> {
+    let proofs: ClaimProofsOutput = parse_json(proofs_json, "claim proofs")?;
+    let secrets: ClaimSecretsOutput = parse_json(secrets_json, "claim secrets")?;
+    let config: AirdropConfiguration = parse_json(config_json, "configuration")?;
+    let seed = parse_seed(seed)?;
+    let messages = ClaimMessages::shared(message);
+
+    let submission = zair_sdk::pipeline::sign_submission(
+        &proofs,
+        secrets,
+        &config,
+        seed,
+        account_id,
+        &messages,
+        expiry_height,
+    )
+    .map_err(Failure::from)?;
+    to_json(&submission)
+}
+
+/// Verify the signatures and proofs of a submission JSON.
+pub fn verify(
+    submission_json: &[u8],
+    config_json: &[u8],
+    message: &[u8],
+    sapling_verifying_key: Option<&[u8]>,
+    orchard_params: Option<&[u8]>,
+    current_height: Option<u64>,
+) -> Result<(), Failure> {
+    let submission: ClaimSubmission = parse_json(submission_json, "submission")?;
+    let config: AirdropConfiguration = parse_json(config_json, "configuration")?;
+    let messages = ClaimMessages::shared(message);
+    let sapling_verifying_key = sapling_verifying_key
+        .map(|bytes| {
+            VerifyingKey::<Bls12>::read(bytes)
+                .map_err(|e| Failure::config(format!("Failed to read Sapling verifying key: {e}")))
+        })
+        .transpose()?;
+    let params = VerifyingParams {
+        sapling_verifying_key,
+        orchard_params: read_orchard_params(orchard_params)?,
+    };
+
+    runtime()
+        .block_on(zair_sdk::pipeline::verify_submission(
+            &submission,
+            &config,
+            &messages,
+            &params,
+            current_height,
+        ))
+        .map_err(Failure::from)
+}
+
+pub fn parse_utf8<'a>(bytes: &'a [u8], what: &str) -> Result<&'a str, Failure> {
+    std::str::from_utf8(bytes)
+        .map_err(|e| Failure::invalid_argument(format!("Invalid {what}: {e}")))
+}
+
+/// Shared runtime driving the async pipeline stages.
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        // Lightwalletd connections need a process-wide rustls provider. The host application may
+        // already have installed one, which is fine.
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to start the tokio runtime")
+    })
+}
+
+fn to_json(value: &impl Serialize) -> Result<String, Failure> {
+    serde_json::to_string(value)
+        .map_err(|e| Failure::config(format!("Failed to serialize output: {e}")))
+}
+
+fn parse_json<T: DeserializeOwned>(bytes: &[u8], what: &str) -> Result<T, Failure> {
+    serde_json::from_slice(bytes).map_err(|e| Failure::config(format!("Invalid {what} JSON: {e}")))
+}
+
+fn parse_seed(bytes: &[u8]) -> Result<&[u8; 64], Failure> {
+    bytes.try_into().map_err(|_| {
+        Failure::invalid_argument(format!("Seed must be 64 bytes, got {}", bytes.len()))
+    })
+}
+
+async fn read_snapshot(bytes: &[u8], pool: &str) -> Result<Vec<Nullifier>, Failure> {
+    zair_scan::read_nullifiers(bytes)
+        .await
+        .map_err(|e| Failure::invalid_argument(format!("Invalid {pool} snapshot: {e}")))
+}
+
+fn read_orchard_params(
+    bytes: Option<&[u8]>,
+) -> Result<Option<Arc<Params<vesta::Affine>>>, Failure> {
+    bytes
+        .map(|bytes| {
+            Params::<vesta::Affine>::read(&mut Cursor::new(bytes))
+                .map(Arc::new)
+                .map_err(|e| Failure::config(format!("Failed to read Orchard params: {e}")))
+        })
+        .transpose()
+}
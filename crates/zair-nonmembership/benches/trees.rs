@@ -0,0 +1,95 @@
+//! Gap tree construction and witness generation, per pool and snapshot size.
+#![allow(missing_docs)]
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use ff::{Field as _, PrimeField as _};
+use pasta_curves::pallas;
+use rand_core::{RngCore as _, SeedableRng as _};
+use rand_xorshift::XorShiftRng;
+use zair_core::base::{Nullifier, SanitiseNullifiers};
+use zair_nonmembership::{
+    OrchardGapTree, SaplingGapTree, map_orchard_user_positions, map_sapling_user_positions,
+};
+
+/// Snapshot sizes in chain nullifiers.
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+fn sapling_nullifiers(seed: u64, count: usize) -> SanitiseNullifiers {
+    let mut rng = XorShiftRng::seed_from_u64(seed);
+    SanitiseNullifiers::new(
+        (0..count)
+            .map(|_| {
+                let mut bytes = [0_u8; 32];
+                rng.fill_bytes(&mut bytes);
+                Nullifier::from(bytes)
+            })
+            .collect(),
+    )
+}
+
+fn orchard_nullifiers(seed: u64, count: usize) -> SanitiseNullifiers {
+    let mut rng = XorShiftRng::seed_from_u64(seed);
+    SanitiseNullifiers::new(
+        (0..count)
+            .map(|_| Nullifier::from(pallas::Base::random(&mut rng).to_repr()))
+            .collect(),
+    )
+}
+
+fn tree_construction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gap_tree_construction");
+    group.sample_size(10);
+    for size in SIZES {
+        let sapling = sapling_nullifiers(0, size);
+        group.bench_with_input(BenchmarkId::new("sapling", size), &sapling, |b, chain| {
+            b.iter(|| {
+                SaplingGapTree::from_nullifiers(black_box(chain)).expect("tree should build")
+            });
+        });
+
+        let orchard = orchard_nullifiers(0, size);
+        group.bench_with_input(BenchmarkId::new("orchard", size), &orchard, |b, chain| {
+            b.iter(|| {
+                OrchardGapTree::from_nullifiers_with_progress(black_box(chain), |_, _| {})
+                    .expect("tree should build")
+            });
+        });
+    }
+    group.finish();
+}
+
+fn witness_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gap_tree_witness");
+    for size in SIZES {
+        let chain = sapling_nullifiers(0, size);
+        let tree = SaplingGapTree::from_nullifiers(&chain).expect("tree should build");
+        let positions = map_sapling_user_positions(&chain, &sapling_nullifiers(1, 1))
+            .expect("mapping should build");
+        let position = positions.first().expect("one user nullifier").leaf_position;
+        group.bench_with_input(BenchmarkId::new("sapling", size), &tree, |b, tree| {
+            b.iter(|| {
+                tree.witness_bytes(black_box(u64::from(position)))
+                    .expect("witness should build")
+            });
+        });
+
+        let chain = orchard_nullifiers(0, size);
+        let tree = OrchardGapTree::from_nullifiers_with_progress(&chain, |_, _| {})
+            .expect("tree should build");
+        let positions = map_orchard_user_positions(&chain, &orchard_nullifiers(1, 1))
+            .expect("mapping should build");
+        let position = positions.first().expect("one user nullifier").leaf_position;
+        group.bench_with_input(BenchmarkId::new("orchard", size), &tree, |b, tree| {
+            b.iter(|| {
+                tree.witness_bytes(black_box(u64::from(position)))
+                    .expect("witness should build")
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tree_construction, witness_generation);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zair_core::base::{Nullifier, SanitiseNullifiers};
+use zair_nonmembership::{
+    SaplingGapTree, map_sapling_user_positions, verify_sapling_non_membership,
+};
+
+fuzz_target!(|input: (Vec<[u8; 32]>, Vec<[u8; 32]>)| {
+    let (chain, user) = input;
+    let chain = SanitiseNullifiers::new(chain.into_iter().map(Nullifier::from).collect());
+    // The sentinel bounds themselves can never be proven absent.
+    let user = SanitiseNullifiers::new(
+        user.into_iter()
+            .map(Nullifier::from)
+            .filter(|nf| *nf != Nullifier::MIN && *nf != Nullifier::MAX)
+            .collect(),
+    );
+
+    let Ok(tree) = SaplingGapTree::from_nullifiers(&chain) else {
+        return;
+    };
+    let mapping = map_sapling_user_positions(&chain, &user).expect("mapping must succeed");
+    for position in mapping {
+        let leaf_position = u64::from(position.leaf_position);
+        let path = tree
+            .witness_bytes(leaf_position)
+            .expect("mapped position must have a witness");
+        verify_sapling_non_membership(
+            tree.root_bytes(),
+            &position.nullifier,
+            &position.left_bound,
+            &position.right_bound,
+            leaf_position,
+            &path.concat(),
+        )
+        .expect("mapped position must verify");
+    }
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zair_nonmembership::{OrchardGapTree, SaplingGapTree};
+
+fuzz_target!(|data: &[u8]| {
+    // Decoding untrusted cache bytes must never panic, and anything that decodes must
+    // re-encode to the same bytes.
+    if let Ok(tree) = SaplingGapTree::from_bytes(data) {
+        assert_eq!(tree.to_bytes(), data);
+        for leaf_position in [0_u64, 1, u64::from(u32::MAX)] {
+            let _ = tree.witness_bytes(leaf_position);
+        }
+    }
+    if let Ok(tree) = OrchardGapTree::from_bytes(data) {
+        assert_eq!(tree.to_bytes(), data);
+    }
+});
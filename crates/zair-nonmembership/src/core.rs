@@ -59,12 +59,46 @@ pub enum MerklePathError {
     /// Orchard nullifier bytes are not a canonical `pallas::Base` encoding.
     #[error("Non-canonical Orchard nullifier at index {index} in {set} set")]
     NonCanonicalOrchardNullifier {
-        /// The set being parsed (`chain` or `user`).
+        /// The set being parsed (`chain`, `user`, `proof` or `gap`).
         set: &'static str,
         /// Index in the original set.
         index: usize,
     },
 
+    /// Nullifier does not lie strictly between the gap bounds.
+    #[error("Nullifier is not strictly inside the claimed gap")]
+    NullifierNotInGap,
+
+    /// Authentication path does not have one sibling per tree level.
+    #[error("Invalid authentication path length: expected {expected}, got {actual}")]
+    InvalidPathLength {
+        /// Expected number of siblings.
+        expected: usize,
+        /// Provided number of siblings.
+        actual: usize,
+    },
+
+    /// Proof bytes are not one 32-byte sibling per tree level.
+    #[error("Invalid non-membership proof length: expected {expected} bytes, got {actual}")]
+    InvalidProofLength {
+        /// Expected number of bytes.
+        expected: usize,
+        /// Provided number of bytes.
+        actual: usize,
+    },
+
+    /// Leaf position does not fit in the tree.
+    #[error("Leaf position {0} exceeds tree capacity")]
+    LeafPositionOutOfRange(u64),
+
+    /// Authentication path sibling is not a canonical Orchard node encoding.
+    #[error("Non-canonical Orchard node in authentication path")]
+    NonCanonicalOrchardNode,
+
+    /// Recomputed root does not match the expected root.
+    #[error("Recomputed non-membership root does not match the expected root")]
+    RootMismatch,
+
     /// Unexpected error.
     #[error("Unexpected error: {0}")]
     Unexpected(&'static str),
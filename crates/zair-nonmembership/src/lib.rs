@@ -5,6 +5,7 @@ mod gap_tree;
 mod node;
 mod pool;
 mod sparse;
+mod verify;
 
 pub use core::{MerklePathError, TreePosition};
 
@@ -13,3 +14,7 @@ pub use gap_tree::{
 };
 pub use node::{NON_MEMBERSHIP_TREE_DEPTH, NonMembershipNode};
 pub use sparse::{NonMembershipTree, OrchardNonMembershipTree};
+pub use verify::{
+    orchard_gap_root, sapling_gap_root, verify_orchard_non_membership,
+    verify_sapling_non_membership,
+};
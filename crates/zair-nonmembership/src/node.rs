@@ -11,7 +11,7 @@ use std::sync::LazyLock;
 use incrementalmerkletree::{Hashable, Level};
 use sapling::merkle_hash;
 use sapling::pedersen_hash::{Personalization, pedersen_hash};
-use zair_core::base::{NULLIFIER_SIZE, Nullifier};
+use zair_core::base::{NULLIFIER_GAP_TREE_DEPTH, NULLIFIER_SIZE, Nullifier};
 
 /// Level used for hashing nullifier pairs into leaves.
 ///
@@ -24,7 +24,7 @@ const LEAF_HASH_LEVEL: u8 = 62;
 ///
 /// With 32 levels, the tree can hold up to 2^32 leaves.
 /// This matches the Sapling note commitment tree depth.
-pub const NON_MEMBERSHIP_TREE_DEPTH: u8 = 32;
+pub const NON_MEMBERSHIP_TREE_DEPTH: u8 = NULLIFIER_GAP_TREE_DEPTH;
 
 /// A node in the non-membership Merkle tree.
 ///
@@ -0,0 +1,366 @@
+//! Standalone non-membership proof verification.
+//!
+//! These helpers recompute a gap leaf from its bounds and fold the authentication path up to
+//! the root, without building any tree. They are intended for the verify pipeline and for
+//! third-party auditors who only hold a published gap root and a claimant's witness.
+
+use std::cmp::Ordering;
+
+use incrementalmerkletree::{Hashable, Level};
+use orchard::tree::MerkleHashOrchard;
+use zair_core::base::Nullifier;
+
+use crate::core::MerklePathError;
+use crate::node::{NON_MEMBERSHIP_TREE_DEPTH, NonMembershipNode};
+use crate::pool::orchard::{ORCHARD_LEAF_HASH_LEVEL, orchard_cmp, orchard_node_from_bytes};
+
+/// Verify a Sapling non-membership proof against a gap-tree root.
+///
+/// Checks that `left < nullifier < right`, recomputes the gap leaf from `(left, right)` and
+/// folds the authentication path using the leaf position bits. `proof_bytes` is the path as
+/// concatenated 32-byte siblings from the leaf to the root, e.g. a gap-tree witness joined with
+/// `concat()`.
+///
+/// # Errors
+/// - `MerklePathError::InvalidProofLength` if `proof_bytes` is not one sibling per tree level.
+/// - `MerklePathError::NullifierNotInGap` if the nullifier is not strictly inside the gap.
+/// - `MerklePathError::LeafPositionOutOfRange` if `leaf_position` exceeds the tree capacity.
+/// - `MerklePathError::RootMismatch` if the recomputed root differs from `root`.
+pub fn verify_sapling_non_membership(
+    root: [u8; 32],
+    nullifier: &Nullifier,
+    left: &Nullifier,
+    right: &Nullifier,
+    leaf_position: u64,
+    proof_bytes: &[u8],
+) -> Result<(), MerklePathError> {
+    let path = path_from_bytes(proof_bytes)?;
+    if !(left < nullifier && nullifier < right) {
+        return Err(MerklePathError::NullifierNotInGap);
+    }
+    if sapling_gap_root(left, right, leaf_position, &path)? == root {
+        Ok(())
+    } else {
+        Err(MerklePathError::RootMismatch)
+    }
+}
+
+/// Recompute the Sapling gap-tree root from the gap `(left, right)` and its authentication path.
+///
+/// No nullifier is needed, so a claimant can check that a gap witness belongs to a published root
+/// before looking at the note inside the gap.
+///
+/// # Errors
+/// - `MerklePathError::InvalidPathLength` if `path` does not have one sibling per tree level.
+/// - `MerklePathError::LeafPositionOutOfRange` if `leaf_position` exceeds the tree capacity.
+pub fn sapling_gap_root(
+    left: &Nullifier,
+    right: &Nullifier,
+    leaf_position: u64,
+    path: &[[u8; 32]],
+) -> Result<[u8; 32], MerklePathError> {
+    validate_path_shape(leaf_position, path)?;
+    let leaf = NonMembershipNode::leaf_from_nullifiers(left, right);
+    let computed = fold_path(leaf, leaf_position, path, |sibling| {
+        Ok(NonMembershipNode::from(sibling))
+    })?;
+    Ok(computed.to_bytes())
+}
+
+/// Verify an Orchard non-membership proof against a gap-tree root.
+///
+/// Bounds, nullifier and path siblings must be canonical `pallas::Base` encodings. Ordering is
+/// field ordering, matching the Orchard gap-tree construction. `proof_bytes` is encoded as for
+/// [`verify_sapling_non_membership`].
+///
+/// # Errors
+/// - `MerklePathError::InvalidProofLength` if `proof_bytes` is not one sibling per tree level.
+/// - `MerklePathError::NonCanonicalOrchardNullifier` if a nullifier or bound is not canonical.
+/// - `MerklePathError::NonCanonicalOrchardNode` if a path sibling is not canonical.
+/// - `MerklePathError::NullifierNotInGap` if the nullifier is not strictly inside the gap.
+/// - `MerklePathError::LeafPositionOutOfRange` if `leaf_position` exceeds the tree capacity.
+/// - `MerklePathError::RootMismatch` if the recomputed root differs from `root`.
+pub fn verify_orchard_non_membership(
+    root: [u8; 32],
+    nullifier: &Nullifier,
+    left: &Nullifier,
+    right: &Nullifier,
+    leaf_position: u64,
+    proof_bytes: &[u8],
+) -> Result<(), MerklePathError> {
+    let path = path_from_bytes(proof_bytes)?;
+    let parse = |index: usize, nf: &Nullifier| {
+        orchard_node_from_bytes(*nf.as_ref()).ok_or(MerklePathError::NonCanonicalOrchardNullifier {
+            set: "proof",
+            index,
+        })
+    };
+    parse(0, left)?;
+    parse(1, nullifier)?;
+    parse(2, right)?;
+
+    if orchard_cmp(left, nullifier) != Ordering::Less ||
+        orchard_cmp(nullifier, right) != Ordering::Less
+    {
+        return Err(MerklePathError::NullifierNotInGap);
+    }
+    if orchard_gap_root(left, right, leaf_position, &path)? == root {
+        Ok(())
+    } else {
+        Err(MerklePathError::RootMismatch)
+    }
+}
+
+/// Recompute the Orchard gap-tree root from the gap `(left, right)` and its authentication path.
+///
+/// # Errors
+/// - `MerklePathError::NonCanonicalOrchardNullifier` if a bound is not canonical.
+/// - `MerklePathError::NonCanonicalOrchardNode` if a path sibling is not canonical.
+/// - `MerklePathError::InvalidPathLength` if `path` does not have one sibling per tree level.
+/// - `MerklePathError::LeafPositionOutOfRange` if `leaf_position` exceeds the tree capacity.
+pub fn orchard_gap_root(
+    left: &Nullifier,
+    right: &Nullifier,
+    leaf_position: u64,
+    path: &[[u8; 32]],
+) -> Result<[u8; 32], MerklePathError> {
+    let parse = |index: usize, nf: &Nullifier| {
+        orchard_node_from_bytes(*nf.as_ref())
+            .ok_or(MerklePathError::NonCanonicalOrchardNullifier { set: "gap", index })
+    };
+    let left_node = parse(0, left)?;
+    let right_node = parse(1, right)?;
+    validate_path_shape(leaf_position, path)?;
+
+    let leaf = MerkleHashOrchard::combine(ORCHARD_LEAF_HASH_LEVEL.into(), &left_node, &right_node);
+    let computed = fold_path(leaf, leaf_position, path, |sibling| {
+        orchard_node_from_bytes(sibling).ok_or(MerklePathError::NonCanonicalOrchardNode)
+    })?;
+    Ok(computed.to_bytes())
+}
+
+/// Split `proof_bytes` into one 32-byte sibling per tree level.
+fn path_from_bytes(proof_bytes: &[u8]) -> Result<Vec<[u8; 32]>, MerklePathError> {
+    let expected = usize::from(NON_MEMBERSHIP_TREE_DEPTH).saturating_mul(32);
+    if proof_bytes.len() != expected {
+        return Err(MerklePathError::InvalidProofLength {
+            expected,
+            actual: proof_bytes.len(),
+        });
+    }
+    proof_bytes
+        .chunks_exact(32)
+        .map(|sibling| {
+            <[u8; 32]>::try_from(sibling)
+                .map_err(|_| MerklePathError::Unexpected("proof sibling is not 32 bytes"))
+        })
+        .collect()
+}
+
+fn validate_path_shape(leaf_position: u64, path: &[[u8; 32]]) -> Result<(), MerklePathError> {
+    let expected = usize::from(NON_MEMBERSHIP_TREE_DEPTH);
+    if path.len() != expected {
+        return Err(MerklePathError::InvalidPathLength {
+            expected,
+            actual: path.len(),
+        });
+    }
+    if u32::try_from(leaf_position).is_err() {
+        return Err(MerklePathError::LeafPositionOutOfRange(leaf_position));
+    }
+    Ok(())
+}
+
+fn fold_path<H: Hashable>(
+    leaf: H,
+    leaf_position: u64,
+    path: &[[u8; 32]],
+    parse_sibling: impl Fn([u8; 32]) -> Result<H, MerklePathError>,
+) -> Result<H, MerklePathError> {
+    let mut current = leaf;
+    let mut pos = leaf_position;
+    for (level, sibling) in path.iter().enumerate() {
+        let sibling = parse_sibling(*sibling)?;
+        let level = Level::from(u8::try_from(level)?);
+        current = if pos % 2 == 0 {
+            H::combine(level, &current, &sibling)
+        } else {
+            H::combine(level, &sibling, &current)
+        };
+        pos /= 2;
+    }
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::indexing_slicing, reason = "Test code")]
+
+    use ff::PrimeField as _;
+    use pasta_curves::pallas;
+    use zair_core::base::SanitiseNullifiers;
+
+    use super::*;
+    use crate::{
+        OrchardGapTree, SaplingGapTree, map_orchard_user_positions, map_sapling_user_positions,
+    };
+
+    fn orchard_nf(v: u64) -> Nullifier {
+        Nullifier::from(pallas::Base::from(v).to_repr())
+    }
+
+    #[test]
+    fn sapling_proof_from_gap_tree_verifies() {
+        let chain = SanitiseNullifiers::new(vec![
+            Nullifier::from([10_u8; 32]),
+            Nullifier::from([20_u8; 32]),
+            Nullifier::from([30_u8; 32]),
+        ]);
+        let user = SanitiseNullifiers::new(vec![Nullifier::from([25_u8; 32])]);
+        let tree = SaplingGapTree::from_nullifiers(&chain).expect("tree should build");
+        let mapping = map_sapling_user_positions(&chain, &user).expect("mapping should succeed");
+        let position = &mapping[0];
+        let leaf_position = u64::from(position.leaf_position);
+        let path = tree
+            .witness_bytes(leaf_position)
+            .expect("witness should exist");
+
+        verify_sapling_non_membership(
+            tree.root_bytes(),
+            &position.nullifier,
+            &position.left_bound,
+            &position.right_bound,
+            leaf_position,
+            &path.concat(),
+        )
+        .expect("proof should verify");
+
+        let mut tampered = path.clone();
+        tampered[3] = [7_u8; 32];
+        assert_eq!(
+            verify_sapling_non_membership(
+                tree.root_bytes(),
+                &position.nullifier,
+                &position.left_bound,
+                &position.right_bound,
+                leaf_position,
+                &tampered.concat(),
+            ),
+            Err(MerklePathError::RootMismatch)
+        );
+    }
+
+    #[test]
+    fn sapling_gap_root_needs_no_nullifier() {
+        let chain = SanitiseNullifiers::new(vec![
+            Nullifier::from([10_u8; 32]),
+            Nullifier::from([20_u8; 32]),
+        ]);
+        let tree = SaplingGapTree::from_nullifiers(&chain).expect("tree should build");
+        let path = tree.witness_bytes(1).expect("witness should exist");
+
+        assert_eq!(
+            sapling_gap_root(
+                &Nullifier::from([10_u8; 32]),
+                &Nullifier::from([20_u8; 32]),
+                1,
+                &path,
+            ),
+            Ok(tree.root_bytes())
+        );
+    }
+
+    #[test]
+    fn sapling_rejects_nullifier_outside_gap() {
+        let chain = SanitiseNullifiers::new(vec![Nullifier::from([10_u8; 32])]);
+        let tree = SaplingGapTree::from_nullifiers(&chain).expect("tree should build");
+        let path = tree.witness_bytes(0).expect("witness should exist");
+
+        assert_eq!(
+            verify_sapling_non_membership(
+                tree.root_bytes(),
+                &Nullifier::from([10_u8; 32]),
+                &Nullifier::MIN,
+                &Nullifier::from([10_u8; 32]),
+                0,
+                &path.concat(),
+            ),
+            Err(MerklePathError::NullifierNotInGap)
+        );
+    }
+
+    #[test]
+    fn sapling_rejects_short_proof() {
+        assert_eq!(
+            verify_sapling_non_membership(
+                [0_u8; 32],
+                &Nullifier::from([5_u8; 32]),
+                &Nullifier::MIN,
+                &Nullifier::MAX,
+                0,
+                &[0_u8; 96],
+            ),
+            Err(MerklePathError::InvalidProofLength {
+                expected: 1024,
+                actual: 96
+            })
+        );
+    }
+
+    #[test]
+    fn orchard_proof_from_gap_tree_verifies() {
+        let chain = SanitiseNullifiers::new(vec![orchard_nf(5), orchard_nf(256), orchard_nf(900)]);
+        let user = SanitiseNullifiers::new(vec![orchard_nf(300)]);
+        let tree = OrchardGapTree::from_nullifiers_with_progress(&chain, |_, _| {})
+            .expect("tree should build");
+        let mapping = map_orchard_user_positions(&chain, &user).expect("mapping should succeed");
+        let position = &mapping[0];
+        let leaf_position = u64::from(position.leaf_position);
+        let path = tree
+            .witness_bytes(leaf_position)
+            .expect("witness should exist");
+
+        verify_orchard_non_membership(
+            tree.root_bytes(),
+            &position.nullifier,
+            &position.left_bound,
+            &position.right_bound,
+            leaf_position,
+            &path.concat(),
+        )
+        .expect("proof should verify");
+
+        assert_eq!(
+            verify_orchard_non_membership(
+                tree.root_bytes(),
+                &position.nullifier,
+                &position.left_bound,
+                &position.right_bound,
+                leaf_position.saturating_add(1),
+                &path.concat(),
+            ),
+            Err(MerklePathError::RootMismatch)
+        );
+    }
+
+    #[test]
+    fn orchard_rejects_non_canonical_nullifier() {
+        let mut bytes = [0xff_u8; 32];
+        bytes[31] = 0x7f;
+
+        assert_eq!(
+            verify_orchard_non_membership(
+                [0_u8; 32],
+                &Nullifier::from(bytes),
+                &Nullifier::MIN,
+                &orchard_nf(1),
+                0,
+                &[0_u8; 1024],
+            ),
+            Err(MerklePathError::NonCanonicalOrchardNullifier {
+                set: "proof",
+                index: 1
+            })
+        );
+    }
+}
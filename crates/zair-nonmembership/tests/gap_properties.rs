@@ -0,0 +1,112 @@
+#![allow(missing_docs)]
+
+use ff::PrimeField as _;
+use pasta_curves::pallas;
+use proptest::prelude::*;
+use zair_core::base::{Nullifier, SanitiseNullifiers};
+use zair_nonmembership::{
+    OrchardGapTree, SaplingGapTree, map_orchard_user_positions, map_sapling_user_positions,
+    verify_orchard_non_membership, verify_sapling_non_membership,
+};
+
+/// Tree construction hashes every level, so keep the case count modest.
+const CASES: u32 = 32;
+
+fn sapling_nullifiers(max_len: usize) -> impl Strategy<Value = Vec<Nullifier>> {
+    prop::collection::vec(any::<[u8; 32]>().prop_map(Nullifier::from), 0..max_len)
+}
+
+fn orchard_nullifiers(max_len: usize) -> impl Strategy<Value = Vec<Nullifier>> {
+    prop::collection::vec(
+        any::<[u64; 4]>()
+            .prop_map(|limbs| Nullifier::from(pallas::Base::from_raw(limbs).to_repr())),
+        0..max_len,
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    #[test]
+    fn sapling_user_nullifiers_map_to_exactly_one_verifiable_gap(
+        chain in sapling_nullifiers(48),
+        user in sapling_nullifiers(12),
+    ) {
+        let chain = SanitiseNullifiers::new(chain);
+        let user = SanitiseNullifiers::new(user);
+        let tree = SaplingGapTree::from_nullifiers(&chain).expect("tree should build");
+        let mapping = map_sapling_user_positions(&chain, &user).expect("mapping should build");
+
+        let unspent: Vec<Nullifier> = user
+            .iter()
+            .copied()
+            .filter(|nf| chain.binary_search(nf).is_err())
+            .collect();
+        prop_assert_eq!(mapping.len(), unspent.len());
+
+        for (position, expected) in mapping.iter().zip(&unspent) {
+            prop_assert_eq!(&position.nullifier, expected);
+            prop_assert!(position.left_bound < position.nullifier);
+            prop_assert!(position.nullifier < position.right_bound);
+
+            let leaf_position = u64::from(position.leaf_position);
+            let gap_idx = usize::try_from(leaf_position).expect("position fits usize");
+            prop_assert_eq!(gap_idx, chain.partition_point(|nf| nf < &position.nullifier));
+
+            let path = tree.witness_bytes(leaf_position).expect("witness should build");
+            prop_assert!(verify_sapling_non_membership(
+                tree.root_bytes(),
+                &position.nullifier,
+                &position.left_bound,
+                &position.right_bound,
+                leaf_position,
+                &path.concat(),
+            )
+            .is_ok());
+        }
+    }
+
+    #[test]
+    fn orchard_user_nullifiers_map_to_exactly_one_verifiable_gap(
+        chain in orchard_nullifiers(48),
+        user in orchard_nullifiers(12),
+    ) {
+        let chain = SanitiseNullifiers::new(chain);
+        let user = SanitiseNullifiers::new(user);
+        let tree = OrchardGapTree::from_nullifiers_with_progress(&chain, |_, _| {})
+            .expect("tree should build");
+        let mapping = map_orchard_user_positions(&chain, &user).expect("mapping should build");
+
+        let unspent = user.iter().filter(|nf| !chain.contains(nf)).count();
+        prop_assert_eq!(mapping.len(), unspent);
+
+        for position in &mapping {
+            let leaf_position = u64::from(position.leaf_position);
+            let path = tree.witness_bytes(leaf_position).expect("witness should build");
+            prop_assert!(verify_orchard_non_membership(
+                tree.root_bytes(),
+                &position.nullifier,
+                &position.left_bound,
+                &position.right_bound,
+                leaf_position,
+                &path.concat(),
+            )
+            .is_ok());
+        }
+    }
+
+    #[test]
+    fn gap_tree_serialization_roundtrips(chain in sapling_nullifiers(48)) {
+        let chain = SanitiseNullifiers::new(chain);
+        let tree = SaplingGapTree::from_nullifiers(&chain).expect("tree should build");
+        let decoded = SaplingGapTree::from_bytes(&tree.to_bytes()).expect("tree should decode");
+        prop_assert_eq!(decoded.root_bytes(), tree.root_bytes());
+        prop_assert_eq!(decoded.to_bytes(), tree.to_bytes());
+    }
+
+    #[test]
+    fn gap_tree_from_bytes_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        let _ = SaplingGapTree::from_bytes(&bytes);
+        let _ = OrchardGapTree::from_bytes(&bytes);
+    }
+}
@@ -6,8 +6,10 @@
 //! - It keeps the **standard Orchard nullifier** private.
 //! - It exposes an **airdrop nullifier** derived with a domain-separated base point.
 //! - It verifies a non-membership ("gap tree") Merkle path over spent nullifiers.
-//! - It exposes a value commitment `cv` for binding to a target chain.
+//! - It exposes a value commitment for binding to a target chain: the native `cv` point, a SHA-256
+//!   digest `cv_sha256`, or a Poseidon hash `cv_poseidon`.
 
+use std::sync::OnceLock;
 use std::vec::Vec;
 
 use ff::PrimeField as _;
@@ -37,6 +39,7 @@ use pasta_curves::{pallas, vesta};
 use zair_core::base::VALUE_COMMIT_SHA256_PREFIX;
 
 use super::gadget::{AddChip, AddConfig, AddInstruction, assign_free_advice};
+use super::rows::minimal_k;
 use crate::constants::{
     MERKLE_DEPTH_ORCHARD, OrchardCommitDomains, OrchardFixedBases, OrchardFixedBasesFull,
     OrchardHashDomains, T_P,
@@ -44,15 +47,6 @@ use crate::constants::{
 use crate::note::{RandomSeed, Rho};
 use crate::value::{NoteValue, ValueCommitTrapdoor, ValueCommitment};
 
-/// Circuit size parameter for the native value-commitment scheme (2^12 rows).
-pub const K_AIRDROP_NATIVE: u32 = 12;
-
-/// Circuit size parameter for the SHA-256 value-commitment scheme (2^17 rows).
-///
-/// The SHA-256 table chip requires significantly more rows than the native Pedersen
-/// commitment.
-pub const K_AIRDROP_SHA256: u32 = 17;
-
 // Public input offsets.
 //
 // Ordering mirrors Sapling: rk first, then value commitment(s), then anchors, then airdrop
@@ -72,6 +66,11 @@ const NOTE_ANCHOR_SHA: usize = 10;
 const GAP_ROOT_SHA: usize = 11;
 const AIRDROP_NF_SHA: usize = 12;
 
+const CV_POSEIDON: usize = 2;
+const NOTE_ANCHOR_POSEIDON: usize = 3;
+const GAP_ROOT_POSEIDON: usize = 4;
+const AIRDROP_NF_POSEIDON: usize = 5;
+
 /// Value commitment scheme selection for the Orchard airdrop circuit.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub enum ValueCommitmentScheme {
@@ -80,19 +79,49 @@ pub enum ValueCommitmentScheme {
     Native,
     /// Expose only `cv_sha256` (standard SHA-256 digest bytes).
     Sha256,
+    /// Expose only `cv_poseidon` (a single `pallas::Base` element).
+    Poseidon,
 }
 
 impl ValueCommitmentScheme {
     /// Circuit size parameter `k` (the circuit uses 2^k rows).
-    #[must_use]
-    pub const fn k(self) -> u32 {
-        match self {
-            Self::Native => K_AIRDROP_NATIVE,
-            Self::Sha256 => K_AIRDROP_SHA256,
+    ///
+    /// This is the smallest `k` that fits the circuit variant, derived from its layout once per
+    /// process. The SHA-256 table chip needs far more rows than the native and Poseidon
+    /// commitments.
+    ///
+    /// Returns an error if the circuit cannot be laid out.
+    pub fn k(self) -> Result<u32, plonk::Error> {
+        static K_NATIVE: OnceLock<u32> = OnceLock::new();
+        static K_SHA256: OnceLock<u32> = OnceLock::new();
+        static K_POSEIDON: OnceLock<u32> = OnceLock::new();
+
+        let cached = match self {
+            Self::Native => &K_NATIVE,
+            Self::Sha256 => &K_SHA256,
+            Self::Poseidon => &K_POSEIDON,
+        };
+        if let Some(k) = cached.get() {
+            return Ok(*k);
         }
+        let k = minimal_k(&Circuit {
+            value_commitment_scheme: self,
+            ..Circuit::default()
+        })?;
+        Ok(*cached.get_or_init(|| k))
     }
 }
 
+/// Compute `cv_poseidon = Poseidon(value, rcv_poseidon)`.
+///
+/// Uses the `P128Pow5T3` instance over the Pallas base field with a constant-length-2 domain,
+/// matching the in-circuit commitment.
+#[must_use]
+pub fn cv_poseidon(value: u64, rcv_poseidon: pallas::Base) -> pallas::Base {
+    poseidon::Hash::<_, poseidon::P128Pow5T3, poseidon::ConstantLength<2>, 3, 2>::init()
+        .hash([pallas::Base::from(value), rcv_poseidon])
+}
+
 type OrchardMerkleConfig =
     MerkleConfig<OrchardHashDomains, OrchardCommitDomains, OrchardFixedBases>;
 type OrchardSinsemillaConfig =
@@ -216,6 +245,8 @@ pub struct Circuit {
     pub value_commitment_scheme: ValueCommitmentScheme,
     /// Randomness `rcv_sha256` for SHA-256 value commitment preimage.
     pub rcv_sha256: Value<[u8; 32]>,
+    /// Randomness `rcv_poseidon` for the Poseidon value commitment.
+    pub rcv_poseidon: Value<pallas::Base>,
 
     // Gap tree membership for (left, right).
     /// Left boundary of the gap (as a field element).
@@ -245,16 +276,23 @@ pub struct Instance {
     pub value_commitment_scheme: ValueCommitmentScheme,
     /// SHA-256 value commitment digest bytes, when enabled.
     pub cv_sha256: Option<[u8; 32]>,
+    /// Poseidon value commitment, when enabled.
+    pub cv_poseidon: Option<pallas::Base>,
 }
 
 impl Instance {
-    fn to_halo2_instance(&self) -> [Vec<vesta::Scalar>; 1] {
+    /// Lay out the public inputs for the configured value commitment scheme.
+    ///
+    /// Returns `plonk::Error::InvalidInstances` if `rk` is the identity or the scheme's value
+    /// commitment is missing.
+    fn to_halo2_instance(&self) -> Result<[Vec<vesta::Scalar>; 1], plonk::Error> {
         let mut instance = match self.value_commitment_scheme {
             ValueCommitmentScheme::Native => vec![vesta::Scalar::zero(); 7],
             ValueCommitmentScheme::Sha256 => vec![vesta::Scalar::zero(); 13],
+            ValueCommitmentScheme::Poseidon => vec![vesta::Scalar::zero(); 6],
         };
 
-        let rk = self.rk.coordinates().expect("rk is non-identity");
+        let rk = Option::<_>::from(self.rk.coordinates()).ok_or(plonk::Error::InvalidInstances)?;
         instance[RK_X] = *rk.x();
         instance[RK_Y] = *rk.y();
 
@@ -267,7 +305,7 @@ impl Instance {
                 instance[AIRDROP_NF_NATIVE] = self.airdrop_nf;
             }
             ValueCommitmentScheme::Sha256 => {
-                let digest = self.cv_sha256.expect("sha256 scheme requires digest");
+                let digest = self.cv_sha256.ok_or(plonk::Error::InvalidInstances)?;
                 for (i, word) in digest.chunks_exact(4).enumerate() {
                     let w: [u8; 4] = word.try_into().expect("chunk length");
                     instance[DIGEST_0_SHA + i] = vesta::Scalar::from(u32::from_be_bytes(w) as u64);
@@ -276,9 +314,15 @@ impl Instance {
                 instance[GAP_ROOT_SHA] = self.gap_root;
                 instance[AIRDROP_NF_SHA] = self.airdrop_nf;
             }
+            ValueCommitmentScheme::Poseidon => {
+                instance[CV_POSEIDON] = self.cv_poseidon.ok_or(plonk::Error::InvalidInstances)?;
+                instance[NOTE_ANCHOR_POSEIDON] = self.note_anchor;
+                instance[GAP_ROOT_POSEIDON] = self.gap_root;
+                instance[AIRDROP_NF_POSEIDON] = self.airdrop_nf;
+            }
         }
 
-        [instance]
+        Ok([instance])
     }
 }
 
@@ -611,6 +655,9 @@ impl plonk::Circuit<pallas::Base> for Circuit {
                 (NOTE_ANCHOR_NATIVE, GAP_ROOT_NATIVE, AIRDROP_NF_NATIVE)
             }
             ValueCommitmentScheme::Sha256 => (NOTE_ANCHOR_SHA, GAP_ROOT_SHA, AIRDROP_NF_SHA),
+            ValueCommitmentScheme::Poseidon => {
+                (NOTE_ANCHOR_POSEIDON, GAP_ROOT_POSEIDON, AIRDROP_NF_POSEIDON)
+            }
         };
 
         // === Witness note preimage + keys ===
@@ -785,7 +832,7 @@ impl plonk::Circuit<pallas::Base> for Circuit {
 
             let digest_start = match scheme {
                 ValueCommitmentScheme::Sha256 => DIGEST_0_SHA,
-                ValueCommitmentScheme::Native => unreachable!(),
+                ValueCommitmentScheme::Native | ValueCommitmentScheme::Poseidon => unreachable!(),
             };
 
             let mut digest_cells: Vec<
@@ -851,6 +898,47 @@ impl plonk::Circuit<pallas::Base> for Circuit {
             }
         }
 
+        // === Value commitment: Poseidon ===
+        if scheme == ValueCommitmentScheme::Poseidon {
+            // Re-witness the note value as a field element, bound to the 64-bit value cell used
+            // by the note commitment.
+            let v_base = layouter.assign_region(
+                || "value as base",
+                |mut region| {
+                    let cell = region.assign_advice(
+                        || "value",
+                        config.advices[0],
+                        0,
+                        || self.value.map(|v| pallas::Base::from(v.inner())),
+                    )?;
+                    region.constrain_equal(cell.cell(), v.cell())?;
+                    Ok(cell)
+                },
+            )?;
+            let rcv_poseidon = assign_free_advice(
+                layouter.namespace(|| "witness rcv_poseidon"),
+                config.advices[0],
+                self.rcv_poseidon,
+            )?;
+
+            let poseidon_hasher = PoseidonHash::<
+                pallas::Base,
+                PoseidonChip<pallas::Base, 3, 2>,
+                poseidon::P128Pow5T3,
+                poseidon::ConstantLength<2>,
+                3,
+                2,
+            >::init(
+                config.poseidon_chip(),
+                layouter.namespace(|| "Poseidon init (cv)"),
+            )?;
+            let cv_poseidon = poseidon_hasher.hash(
+                layouter.namespace(|| "Poseidon hash (value, rcv_poseidon)"),
+                [v_base, rcv_poseidon],
+            )?;
+            layouter.constrain_instance(cv_poseidon.cell(), config.primary, CV_POSEIDON)?;
+        }
+
         // === Standard nullifier (private) ===
         let nf_old = super::gadget::derive_nullifier(
             layouter.namespace(|| "nf_old"),
@@ -1236,6 +1324,7 @@ impl Circuit {
             rcv: Value::known(rcv),
             value_commitment_scheme: ValueCommitmentScheme::Native,
             rcv_sha256: Value::unknown(),
+            rcv_poseidon: Value::unknown(),
             left: Value::known(left),
             right: Value::known(right),
             gap_path: Value::known(gap_path),
@@ -0,0 +1,135 @@
+//! Minimal Halo2 circuit size.
+//!
+//! The circuit size `k` is derived from the layout instead of being hard-coded: the circuit is
+//! laid out without witnesses by its own floor planner, and the smallest `2^k` rows that hold the
+//! used rows plus the blinding rows reserved by keygen is the answer. The layout does not depend
+//! on witnesses, so every prover and verifier derives the same `k`.
+
+use halo2_proofs::arithmetic::Field;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::plonk::{
+    Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+    FloorPlanner, Instance, Selector,
+};
+
+/// Smallest `k` such that `circuit` fits in `2^k` rows.
+///
+/// # Errors
+/// Returns an error if the circuit fails to lay out without witnesses.
+pub fn minimal_k<F: Field, C: Circuit<F>>(circuit: &C) -> Result<u32, Error> {
+    let mut meta = ConstraintSystem::default();
+    let config = C::configure(&mut meta);
+
+    let mut rows = RowCounter::default();
+    C::FloorPlanner::synthesize(&mut rows, circuit, config, meta.constants().clone())?;
+
+    // Keygen reserves the last `blinding_factors + 1` rows and needs at least `minimum_rows`.
+    let needed = (rows.used + meta.blinding_factors() + 1).max(meta.minimum_rows());
+    Ok(needed.next_power_of_two().trailing_zeros())
+}
+
+/// Assignment that only records how many rows the layout uses.
+#[derive(Debug, Default)]
+struct RowCounter {
+    used: usize,
+}
+
+impl RowCounter {
+    fn touch(&mut self, row: usize) {
+        self.used = self.used.max(row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        _annotation: A,
+        _selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _column: Column<Instance>, _row: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _annotation: A,
+        _column: Column<Advice>,
+        row: usize,
+        _to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _annotation: A,
+        _column: Column<Fixed>,
+        row: usize,
+        _to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _left_column: Column<Any>,
+        left_row: usize,
+        _right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.touch(left_row.max(right_row));
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _column: Column<Fixed>,
+        row: usize,
+        _to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        // Keygen fills the column up to the last usable row, but `row` itself must be usable.
+        self.touch(row);
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _gadget_name: Option<String>) {}
+}
@@ -26,6 +26,8 @@ pub mod constants {
     pub const T_Q: u128 = 45_560_315_531_506_369_815_346_746_415_080_538_113;
     /// Orchard note commitment tree depth.
     pub const MERKLE_DEPTH_ORCHARD: usize = orchard::NOTE_COMMITMENT_TREE_DEPTH;
+    const _: () =
+        assert!(MERKLE_DEPTH_ORCHARD == zair_core::base::NOTE_COMMITMENT_TREE_DEPTH as usize);
 
     /// The Pallas base field modulus is `p = 2^254 + t_p`.
     pub const T_P: u128 = 45_560_315_531_419_706_090_280_762_371_685_220_353;
@@ -35,6 +35,12 @@ pub enum ClaimProofError {
     /// Unexpected SHA-256 value commitment randomness in native scheme mode.
     #[error("unexpected rcv_sha256 for native value commitment scheme")]
     UnexpectedRcvSha256,
+    /// Missing Poseidon value commitment randomness in Poseidon scheme mode.
+    #[error("missing rcv_poseidon for poseidon value commitment scheme")]
+    MissingRcvPoseidon,
+    /// Poseidon value commitment randomness given for another scheme.
+    #[error("unexpected rcv_poseidon for {0} value commitment scheme")]
+    UnexpectedRcvPoseidon(crate::types::ValueCommitmentScheme),
     /// Halo2 params `k` does not match the configured scheme.
     #[error("Orchard params k mismatch: expected {expected}, got {actual}")]
     InvalidParamsK { expected: u32, actual: u32 },
@@ -5,12 +5,14 @@ use pasta_curves::arithmetic::{Coordinates, CurveAffine as _};
 use pasta_curves::{pallas, vesta};
 
 use crate::error::ClaimProofError;
-use crate::types::ValueCommitmentScheme;
+use crate::types::{ClaimProofOutput, ValueCommitmentScheme};
 
 /// Number of public instance scalars for the Native value commitment scheme.
 const NATIVE_INSTANCE_COUNT: usize = 7;
 /// Number of public instance scalars for the SHA-256 value commitment scheme.
 const SHA256_INSTANCE_COUNT: usize = 13;
+/// Number of public instance scalars for the Poseidon value commitment scheme.
+const POSEIDON_INSTANCE_COUNT: usize = 6;
 
 pub(crate) fn base_from_repr(bytes: [u8; 32]) -> Result<pallas::Base, ClaimProofError> {
     Option::<pallas::Base>::from(pallas::Base::from_repr(bytes))
@@ -57,10 +59,15 @@ fn coords_or_zero(p: pallas::Point) -> (pallas::Base, pallas::Base) {
     }
 }
 
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Instance layout takes each public proof field explicitly"
+)]
 pub(crate) fn to_instance(
     note_commitment_root: [u8; 32],
     cv: Option<[u8; 32]>,
     cv_sha256: Option<[u8; 32]>,
+    cv_poseidon: Option<[u8; 32]>,
     airdrop_nf: [u8; 32],
     rk_bytes: [u8; 32],
     nullifier_gap_root: [u8; 32],
@@ -69,6 +76,7 @@ pub(crate) fn to_instance(
     let mut instance: Vec<vesta::Scalar> = Vec::with_capacity(match scheme {
         ValueCommitmentScheme::Native => NATIVE_INSTANCE_COUNT,
         ValueCommitmentScheme::Sha256 => SHA256_INSTANCE_COUNT,
+        ValueCommitmentScheme::Poseidon => POSEIDON_INSTANCE_COUNT,
     });
 
     let rk_point = Option::<pallas::Point>::from(pallas::Point::from_bytes(&rk_bytes))
@@ -99,7 +107,107 @@ pub(crate) fn to_instance(
             instance.push(base_from_repr(nullifier_gap_root)?);
             instance.push(base_from_repr(airdrop_nf)?);
         }
+        ValueCommitmentScheme::Poseidon => {
+            let cv_poseidon = cv_poseidon.ok_or(plonk::Error::Synthesis)?;
+            instance.push(base_from_repr(cv_poseidon)?);
+            instance.push(base_from_repr(note_commitment_root)?);
+            instance.push(base_from_repr(nullifier_gap_root)?);
+            instance.push(base_from_repr(airdrop_nf)?);
+        }
     }
 
     Ok([instance])
 }
+
+/// Public inputs of an Orchard claim proof.
+///
+/// Fields use the same encodings as [`ClaimProofOutput`]; the instance column is derived from
+/// them with [`Self::to_vec`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimPublicInputs {
+    /// Which value commitment scheme the proof exposes.
+    pub value_commitment_scheme: ValueCommitmentScheme,
+    /// Randomized spend validating key `rk` (`RedPallas` verification key encoding).
+    pub rk: [u8; 32],
+    /// Orchard value commitment `cv` (Pallas point encoding), for the native scheme.
+    pub cv: Option<[u8; 32]>,
+    /// SHA-256 value commitment digest bytes, for the `sha256` scheme.
+    pub cv_sha256: Option<[u8; 32]>,
+    /// Poseidon value commitment (canonical `pallas::Base` encoding), for the `poseidon` scheme.
+    pub cv_poseidon: Option<[u8; 32]>,
+    /// Orchard note commitment tree root (canonical `pallas::Base` encoding).
+    pub note_commitment_root: [u8; 32],
+    /// Orchard spent-nullifier gap tree root (canonical `pallas::Base` encoding).
+    pub nullifier_gap_root: [u8; 32],
+    /// Airdrop nullifier (canonical `pallas::Base` encoding).
+    pub airdrop_nullifier: [u8; 32],
+}
+
+impl ClaimPublicInputs {
+    /// Instance column of the proof, in the order the verifier consumes it.
+    ///
+    /// # Errors
+    /// Returns an error if a point or field element does not decode, or the commitment of the
+    /// scheme is missing.
+    pub fn to_vec(&self) -> Result<Vec<vesta::Scalar>, ClaimProofError> {
+        let [instance] = to_instance(
+            self.note_commitment_root,
+            self.cv,
+            self.cv_sha256,
+            self.cv_poseidon,
+            self.airdrop_nullifier,
+            self.rk,
+            self.nullifier_gap_root,
+            self.value_commitment_scheme,
+        )?;
+        Ok(instance)
+    }
+
+    /// Canonical byte encoding of the public inputs.
+    ///
+    /// Every element of [`Self::to_vec`] is written as its 32-byte little-endian canonical
+    /// encoding, so external verifiers can reproduce the exact instance layout.
+    ///
+    /// # Errors
+    /// Returns an error if the instance column cannot be derived.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ClaimProofError> {
+        Ok(self
+            .to_vec()?
+            .iter()
+            .flat_map(|element| element.to_repr())
+            .collect())
+    }
+}
+
+impl ClaimProofOutput {
+    /// Value commitment scheme of the proof, read from the commitment it exposes.
+    #[must_use]
+    pub const fn value_commitment_scheme(&self) -> ValueCommitmentScheme {
+        if self.cv_poseidon.is_some() {
+            ValueCommitmentScheme::Poseidon
+        } else if self.cv_sha256.is_some() {
+            ValueCommitmentScheme::Sha256
+        } else {
+            ValueCommitmentScheme::Native
+        }
+    }
+
+    /// Public inputs of the proof, given the roots of the airdrop it was proven against.
+    #[must_use]
+    pub const fn public_inputs(
+        &self,
+        note_commitment_root: [u8; 32],
+        nullifier_gap_root: [u8; 32],
+    ) -> ClaimPublicInputs {
+        ClaimPublicInputs {
+            value_commitment_scheme: self.value_commitment_scheme(),
+            rk: self.rk,
+            cv: self.cv,
+            cv_sha256: self.cv_sha256,
+            cv_poseidon: self.cv_poseidon,
+            note_commitment_root,
+            nullifier_gap_root,
+            airdrop_nullifier: self.airdrop_nullifier,
+        }
+    }
+}
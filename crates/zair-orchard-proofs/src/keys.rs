@@ -58,7 +58,7 @@ pub(crate) fn keys_for(
     target_id_len: u8,
 ) -> Result<Arc<Keys>, ClaimProofError> {
     let circuit_scheme: CircuitValueCommitmentScheme = scheme.into();
-    let expected = circuit_scheme.k();
+    let expected = circuit_scheme.k()?;
     let actual = params.k();
     if expected != actual {
         return Err(ClaimProofError::InvalidParamsK { expected, actual });
@@ -5,23 +5,39 @@ mod instance;
 mod keys;
 mod types;
 
-use zair_orchard_circuit::circuit::airdrop::ValueCommitmentScheme as CircuitValueCommitmentScheme;
+use ff::PrimeField as _;
+use zair_orchard_circuit::circuit::airdrop::{
+    ValueCommitmentScheme as CircuitValueCommitmentScheme, cv_poseidon,
+};
 
 pub use crate::error::ClaimProofError;
+pub use crate::instance::ClaimPublicInputs;
 pub use crate::types::{ClaimProofInputs, ClaimProofOutput, ValueCommitmentScheme};
 
 /// Return the Halo2 `k` parameter for the given scheme.
-#[must_use]
-pub fn k_for_scheme(scheme: ValueCommitmentScheme) -> u32 {
+///
+/// This is the smallest `k` the circuit variant fits in, derived from the circuit layout.
+///
+/// # Errors
+/// Returns an error if the circuit cannot be laid out.
+pub fn k_for_scheme(scheme: ValueCommitmentScheme) -> Result<u32, ClaimProofError> {
     let circuit_scheme: CircuitValueCommitmentScheme = scheme.into();
-    circuit_scheme.k()
+    Ok(circuit_scheme.k()?)
+}
+
+/// Compute the encoded `cv_poseidon` of `value` under the encoded `rcv_poseidon`.
+///
+/// # Errors
+/// Returns an error if `rcv_poseidon` is not a canonical Pallas base field element.
+pub fn cv_poseidon_bytes(value: u64, rcv_poseidon: [u8; 32]) -> Result<[u8; 32], ClaimProofError> {
+    Ok(cv_poseidon(value, instance::base_from_repr(rcv_poseidon)?).to_repr())
 }
 
 #[cfg(feature = "prove")]
 pub mod prover;
 
 #[cfg(feature = "prove")]
-pub use prover::generate_claim_proof;
+pub use prover::{generate_claim_proof, generate_claim_proof_with_rng};
 
 #[cfg(feature = "verify")]
 pub mod verifier;
@@ -1,3 +1,4 @@
+use ff::PrimeField as _;
 use halo2_proofs::plonk;
 use halo2_proofs::poly::commitment::Params;
 use halo2_proofs::transcript::Blake2bWrite;
@@ -5,7 +6,8 @@ use orchard::note::{RandomSeed, Rho};
 use orchard::primitives::redpallas::{SpendAuth, VerificationKey as RedPallasVerificationKey};
 use orchard::value::{NoteValue, ValueCommitTrapdoor, ValueCommitment};
 use pasta_curves::{pallas, vesta};
-use zair_orchard_circuit::circuit::airdrop::Circuit;
+use rand::RngCore;
+use zair_orchard_circuit::circuit::airdrop::{Circuit, cv_poseidon};
 
 use crate::error::ClaimProofError;
 use crate::instance::{
@@ -22,10 +24,30 @@ use crate::types::{ClaimProofInputs, ClaimProofOutput, ValueCommitmentScheme};
 pub fn generate_claim_proof(
     params: &Params<vesta::Affine>,
     inputs: &ClaimProofInputs,
+) -> Result<ClaimProofOutput, ClaimProofError> {
+    generate_claim_proof_with_rng(params, inputs, &mut rand::rngs::OsRng)
+}
+
+/// Generate an Orchard claim proof using the caller's source of proof randomness.
+///
+/// Identical to [`generate_claim_proof`], except that the Halo2 blinding factors are drawn from
+/// `rng`. A seeded `rng` makes the proof bytes reproducible, which is only suitable for tests and
+/// known-answer vectors.
+///
+/// # Errors
+/// See [`generate_claim_proof`].
+pub fn generate_claim_proof_with_rng<R: RngCore>(
+    params: &Params<vesta::Affine>,
+    inputs: &ClaimProofInputs,
+    rng: &mut R,
 ) -> Result<ClaimProofOutput, ClaimProofError> {
     let _target_id = target_id_slice(&inputs.target_id, inputs.target_id_len)?;
 
-    let (rcv_sha256, cv_sha256) = match inputs.value_commitment_scheme {
+    let scheme = inputs.value_commitment_scheme;
+    if scheme != ValueCommitmentScheme::Poseidon && inputs.rcv_poseidon.is_some() {
+        return Err(ClaimProofError::UnexpectedRcvPoseidon(scheme));
+    }
+    let (rcv_sha256, cv_sha256) = match scheme {
         ValueCommitmentScheme::Native => {
             if inputs.rcv_sha256.is_some() {
                 return Err(ClaimProofError::UnexpectedRcvSha256);
@@ -39,6 +61,21 @@ pub fn generate_claim_proof(
                 Some(zair_core::base::cv_sha256(inputs.value, rcv_sha256)),
             )
         }
+        ValueCommitmentScheme::Poseidon => (None, None),
+    };
+    let (rcv_poseidon, cv_poseidon) = match scheme {
+        ValueCommitmentScheme::Poseidon => {
+            let rcv_poseidon = base_from_repr(
+                inputs
+                    .rcv_poseidon
+                    .ok_or(ClaimProofError::MissingRcvPoseidon)?,
+            )?;
+            (
+                Some(rcv_poseidon),
+                Some(cv_poseidon(inputs.value, rcv_poseidon).to_repr()),
+            )
+        }
+        ValueCommitmentScheme::Native | ValueCommitmentScheme::Sha256 => (None, None),
     };
 
     // Parse note randomness (rho/rseed) and derive (psi, rcm).
@@ -104,6 +141,10 @@ pub fn generate_claim_proof(
             Some(bytes) => halo2_proofs::circuit::Value::known(bytes),
             None => halo2_proofs::circuit::Value::unknown(),
         },
+        rcv_poseidon: match rcv_poseidon {
+            Some(rcv) => halo2_proofs::circuit::Value::known(rcv),
+            None => halo2_proofs::circuit::Value::unknown(),
+        },
         left: halo2_proofs::circuit::Value::known(left),
         right: halo2_proofs::circuit::Value::known(right),
         gap_path: halo2_proofs::circuit::Value::known(gap_path),
@@ -125,7 +166,7 @@ pub fn generate_claim_proof(
             let value_sum = NoteValue::from_raw(inputs.value) - NoteValue::from_raw(0);
             Some(ValueCommitment::derive(value_sum, rcv).to_bytes())
         }
-        ValueCommitmentScheme::Sha256 => None,
+        ValueCommitmentScheme::Sha256 | ValueCommitmentScheme::Poseidon => None,
     };
 
     // Instances for proof creation.
@@ -133,6 +174,7 @@ pub fn generate_claim_proof(
         inputs.note_commitment_root,
         cv,
         cv_sha256,
+        cv_poseidon,
         inputs.airdrop_nullifier,
         rk_bytes,
         inputs.nullifier_gap_root,
@@ -154,7 +196,7 @@ pub fn generate_claim_proof(
         &keys.pk,
         &[circuit],
         &instances,
-        &mut rand::rngs::OsRng,
+        rng,
         &mut transcript,
     )?;
     let proof = transcript.finalize();
@@ -164,6 +206,7 @@ pub fn generate_claim_proof(
         rk: rk_bytes,
         cv,
         cv_sha256,
+        cv_poseidon,
         airdrop_nullifier: inputs.airdrop_nullifier,
     })
 }
@@ -1,3 +1,4 @@
+use ff::{Field as _, PrimeField as _};
 use group::{Group as _, GroupEncoding as _};
 use halo2_proofs::poly::commitment::Params;
 use pasta_curves::{pallas, vesta};
@@ -31,6 +32,7 @@ fn cv_sha256_test_vector() {
         note_commitment_root,
         None,
         Some(expected),
+        None,
         airdrop_nf,
         rk_bytes,
         nullifier_gap_root,
@@ -69,6 +71,7 @@ fn to_instance_lengths_match_scheme() {
         note_commitment_root,
         Some(cv_bytes),
         None,
+        None,
         airdrop_nf,
         rk_bytes,
         nullifier_gap_root,
@@ -83,6 +86,7 @@ fn to_instance_lengths_match_scheme() {
         note_commitment_root,
         None,
         Some(digest),
+        None,
         airdrop_nf,
         rk_bytes,
         nullifier_gap_root,
@@ -92,6 +96,66 @@ fn to_instance_lengths_match_scheme() {
         Err(e) => panic!("sha instance: {e}"),
     };
     assert_eq!(sha.len(), 13);
+
+    let cv_poseidon = zair_orchard_circuit::circuit::airdrop::cv_poseidon(1, pallas::Base::one());
+    let [poseidon] = match to_instance(
+        note_commitment_root,
+        None,
+        None,
+        Some(cv_poseidon.to_repr()),
+        airdrop_nf,
+        rk_bytes,
+        nullifier_gap_root,
+        ValueCommitmentScheme::Poseidon,
+    ) {
+        Ok(v) => v,
+        Err(e) => panic!("poseidon instance: {e}"),
+    };
+    assert_eq!(poseidon.len(), 6);
+    assert_eq!(poseidon[2], cv_poseidon);
+}
+
+#[test]
+fn public_input_bytes_follow_the_instance_layout() {
+    let cv_poseidon = zair_orchard_circuit::circuit::airdrop::cv_poseidon(1, pallas::Base::one());
+    let output = ClaimProofOutput {
+        zkproof: Vec::new(),
+        rk: pallas::Point::generator().to_bytes(),
+        cv: None,
+        cv_sha256: None,
+        cv_poseidon: Some(cv_poseidon.to_repr()),
+        airdrop_nullifier: [3_u8; 32],
+    };
+
+    let public_inputs = output.public_inputs([1_u8; 32], [2_u8; 32]);
+    assert_eq!(
+        public_inputs.value_commitment_scheme,
+        ValueCommitmentScheme::Poseidon
+    );
+
+    let bytes = public_inputs.to_bytes().expect("encode public inputs");
+    assert_eq!(bytes.len(), 6 * 32);
+    assert_eq!(bytes[64..96], cv_poseidon.to_repr());
+    assert_eq!(bytes[96..128], [1_u8; 32]);
+    assert_eq!(bytes[128..160], [2_u8; 32]);
+    assert_eq!(bytes[160..], [3_u8; 32]);
+}
+
+#[test]
+fn cv_poseidon_depends_on_value_and_randomness() {
+    use zair_orchard_circuit::circuit::airdrop::cv_poseidon;
+
+    let rcv = pallas::Base::from(7);
+    assert_ne!(cv_poseidon(1, rcv), cv_poseidon(2, rcv));
+    assert_ne!(cv_poseidon(1, rcv), cv_poseidon(1, pallas::Base::from(8)));
+    assert_eq!(
+        crate::cv_poseidon_bytes(1, rcv.to_repr()).expect("canonical randomness"),
+        cv_poseidon(1, rcv).to_repr()
+    );
+    assert!(matches!(
+        crate::cv_poseidon_bytes(1, [0xff; 32]),
+        Err(crate::ClaimProofError::NonCanonicalBase)
+    ));
 }
 
 #[test]
@@ -108,6 +172,7 @@ fn dummy_output() -> ClaimProofOutput {
         rk: [0_u8; 32],
         cv: Some([0_u8; 32]),
         cv_sha256: None,
+        cv_poseidon: None,
         airdrop_nullifier: [0_u8; 32],
     }
 }
@@ -146,3 +211,61 @@ fn verify_rejects_non_utf8_target_id() {
     .unwrap_err();
     assert!(matches!(err, crate::ClaimProofError::InvalidTargetIdUtf8));
 }
+
+/// Keygen succeeds with the derived `k` of `scheme` and runs out of rows one `k` below it.
+fn assert_k_is_the_smallest_that_fits(scheme: ValueCommitmentScheme) {
+    use halo2_proofs::plonk;
+    use zair_orchard_circuit::circuit::airdrop::Circuit;
+
+    let k = crate::k_for_scheme(scheme).expect("circuit lays out");
+    let circuit = Circuit {
+        value_commitment_scheme: scheme.into(),
+        ..Circuit::default()
+    };
+
+    plonk::keygen_vk(&Params::<vesta::Affine>::new(k), &circuit).expect("circuit fits in 2^k rows");
+    let err = plonk::keygen_vk(&Params::<vesta::Affine>::new(k - 1), &circuit)
+        .expect_err("circuit does not fit in 2^(k-1) rows");
+    assert!(matches!(err, plonk::Error::NotEnoughRowsAvailable { .. }));
+}
+
+#[test]
+fn native_k_is_the_smallest_that_fits() {
+    assert_k_is_the_smallest_that_fits(ValueCommitmentScheme::Native);
+}
+
+#[test]
+fn sha256_k_is_the_smallest_that_fits() {
+    assert_k_is_the_smallest_that_fits(ValueCommitmentScheme::Sha256);
+}
+
+#[test]
+fn k_matches_published_params() {
+    // Params files in the wild were generated with these sizes: a layout change that moves `k`
+    // makes them unusable.
+    for (scheme, k) in [
+        (ValueCommitmentScheme::Native, 12),
+        (ValueCommitmentScheme::Sha256, 17),
+        (ValueCommitmentScheme::Poseidon, 12),
+    ] {
+        assert_eq!(
+            crate::k_for_scheme(scheme).expect("circuit lays out"),
+            k,
+            "{scheme:?}"
+        );
+    }
+}
+
+#[test]
+fn keys_reject_params_of_another_k() {
+    let native_k = crate::k_for_scheme(ValueCommitmentScheme::Native).expect("circuit lays out");
+    let sha256_k = crate::k_for_scheme(ValueCommitmentScheme::Sha256).expect("circuit lays out");
+    let params = Params::<vesta::Affine>::new(native_k);
+
+    let result = crate::keys::keys_for(&params, ValueCommitmentScheme::Sha256, [0_u8; 32], 0);
+    assert!(matches!(
+        result,
+        Err(crate::ClaimProofError::InvalidParamsK { expected, actual })
+            if expected == sha256_k && actual == native_k
+    ));
+}
@@ -9,6 +9,8 @@ pub enum ValueCommitmentScheme {
     Native,
     /// Expose only `cv_sha256` (standard SHA-256 digest bytes).
     Sha256,
+    /// Expose only `cv_poseidon` (canonical `pallas::Base` encoding).
+    Poseidon,
 }
 
 impl fmt::Display for ValueCommitmentScheme {
@@ -16,6 +18,7 @@ impl fmt::Display for ValueCommitmentScheme {
         match self {
             Self::Native => f.write_str("native"),
             Self::Sha256 => f.write_str("sha256"),
+            Self::Poseidon => f.write_str("poseidon"),
         }
     }
 }
@@ -25,6 +28,7 @@ impl From<zair_core::schema::config::ValueCommitmentScheme> for ValueCommitmentS
         match scheme {
             zair_core::schema::config::ValueCommitmentScheme::Native => Self::Native,
             zair_core::schema::config::ValueCommitmentScheme::Sha256 => Self::Sha256,
+            zair_core::schema::config::ValueCommitmentScheme::Poseidon => Self::Poseidon,
         }
     }
 }
@@ -34,6 +38,7 @@ impl From<ValueCommitmentScheme> for CircuitValueCommitmentScheme {
         match scheme {
             ValueCommitmentScheme::Native => Self::Native,
             ValueCommitmentScheme::Sha256 => Self::Sha256,
+            ValueCommitmentScheme::Poseidon => Self::Poseidon,
         }
     }
 }
@@ -52,6 +57,8 @@ pub struct ClaimProofOutput {
     pub cv: Option<[u8; 32]>,
     /// SHA-256 value commitment digest bytes, when enabled.
     pub cv_sha256: Option<[u8; 32]>,
+    /// Poseidon value commitment (canonical `pallas::Base` encoding), when enabled.
+    pub cv_poseidon: Option<[u8; 32]>,
     /// Airdrop nullifier (canonical `pallas::Base` encoding).
     pub airdrop_nullifier: [u8; 32],
 }
@@ -74,6 +81,9 @@ pub struct ClaimProofInputs {
     pub value_commitment_scheme: ValueCommitmentScheme,
     /// Randomness `rcv_sha256` for the SHA-256 value commitment, when enabled.
     pub rcv_sha256: Option<[u8; 32]>,
+    /// Randomness `rcv_poseidon` for the Poseidon value commitment (canonical `pallas::Base`
+    /// encoding), when enabled.
+    pub rcv_poseidon: Option<[u8; 32]>,
 
     /// Note preimage / identity.
     /// Note commitment randomness input `rho` (canonical Pallas base encoding).
@@ -38,6 +38,7 @@ pub fn verify_claim_proof(
     zkproof: &[u8],
     cv: &Option<[u8; 32]>,
     cv_sha256: &Option<[u8; 32]>,
+    cv_poseidon: &Option<[u8; 32]>,
     airdrop_nullifier: &[u8; 32],
     rk: &[u8; 32],
     note_commitment_root: &[u8; 32],
@@ -57,6 +58,7 @@ pub fn verify_claim_proof(
         *note_commitment_root,
         *cv,
         *cv_sha256,
+        *cv_poseidon,
         *airdrop_nullifier,
         *rk,
         *nullifier_gap_root,
@@ -91,6 +93,7 @@ pub fn verify_claim_proof_output(
         rk,
         cv,
         cv_sha256,
+        cv_poseidon,
         airdrop_nullifier,
     }: &ClaimProofOutput,
     note_commitment_root: [u8; 32],
@@ -103,6 +106,7 @@ pub fn verify_claim_proof_output(
         zkproof,
         cv,
         cv_sha256,
+        cv_poseidon,
         airdrop_nullifier,
         rk,
         &note_commitment_root,
@@ -127,11 +131,15 @@ pub fn verify_signature(
 }
 
 /// Hashes and returns the digest for Orchard proof fields.
+///
+/// `cv_poseidon` is only part of the preimage when present, so digests of native and SHA-256
+/// proofs are unchanged by its introduction.
 pub fn hash_orchard_proof_fields(
     zkproof: &[u8],
     rk: &[u8; 32],
     cv: Option<[u8; 32]>,
     cv_sha256: Option<[u8; 32]>,
+    cv_poseidon: Option<[u8; 32]>,
     airdrop_nullifier: Nullifier,
 ) -> Result<[u8; 32], ClaimProofError> {
     let mut preimage = Vec::new();
@@ -155,6 +163,10 @@ pub fn hash_orchard_proof_fields(
         }
         None => preimage.push(0),
     }
+    if let Some(bytes) = cv_poseidon {
+        preimage.push(2);
+        preimage.extend_from_slice(&bytes);
+    }
     let nf: [u8; 32] = airdrop_nullifier.into();
     preimage.extend_from_slice(&nf);
     Ok(hash_bytes(&preimage))
@@ -0,0 +1,178 @@
+//! Claim preparation from scanned notes.
+//!
+//! Turns the notes found by [`zair_scan`] into claim inputs: derives each note's metadata, maps the
+//! user's nullifiers into the snapshot gap tree and extracts both Merkle witnesses. Nothing here
+//! performs I/O or spawns tasks, so the same code backs the native SDK and the `wasm32` bindings.
+
+mod note_metadata;
+mod note_position;
+mod pool_processor;
+mod sparse_tree;
+
+use std::collections::HashMap;
+
+use eyre::{Context as _, ensure};
+pub use note_metadata::{
+    NoteMetadata, NoteMetadataError, OrchardNoteMetadata, SaplingNoteMetadata,
+    orchard_g_d_from_diversifier,
+};
+pub use note_position::{
+    NotePositionError, SaplingTreeState, sapling_note, verify_sapling_note_position,
+    verify_sapling_note_root,
+};
+pub use pool_processor::{
+    CollectedNotes, OrchardPool, PoolClaimResult, PoolProcessor, SaplingPool,
+};
+pub use sparse_tree::SparsePoolTree;
+use tracing::{debug, warn};
+use zair_core::base::{Nullifier, Pool, SanitiseNullifiers};
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::proof_inputs::{ClaimInput, PublicInputs};
+use zair_nonmembership::{MerklePathError, TreePosition};
+use zair_scan::scanner::AccountNotesVisitor;
+use zair_scan::{NullifierFilter, ViewingKeys};
+
+/// Generate airdrop claims for the user's notes.
+///
+/// `witness_bytes` returns the non-membership witness of a gap leaf position. This generic
+/// function works with any metadata type implementing `NoteMetadata`, producing claim inputs with
+/// the appropriate pool-specific private inputs. Each claim gets its identifier in `pool` from
+/// `airdrop_config`.
+///
+/// # Errors
+/// Returns an error if a note's metadata or witness is missing, or `pool` is not configured.
+pub fn generate_claims<M: NoteMetadata>(
+    mut witness_bytes: impl FnMut(u64) -> Result<Vec<[u8; 32]>, MerklePathError>,
+    user_nullifiers: &[TreePosition],
+    note_metadata_map: &HashMap<Nullifier, M>,
+    viewing_keys: &ViewingKeys,
+    pool: Pool,
+    airdrop_config: &AirdropConfiguration,
+) -> eyre::Result<Vec<ClaimInput<M::PoolPrivateInputs>>> {
+    user_nullifiers
+        .iter()
+        .enumerate()
+        .map(|(index, tree_position)| {
+            let metadata = note_metadata_map
+                .get(&tree_position.nullifier)
+                .ok_or_else(|| {
+                    eyre::eyre!(
+                        "Missing note metadata for nullifier {} at claim index {}",
+                        tree_position.nullifier,
+                        index
+                    )
+                })?;
+
+            let nf_merkle_proof = witness_bytes(tree_position.leaf_position.into())?;
+
+            debug!(
+                index,
+                "Generated proof for nullifier {} at block height {}",
+                tree_position.nullifier,
+                metadata.block_height()
+            );
+
+            let private_inputs =
+                metadata.to_private_inputs(tree_position, nf_merkle_proof, viewing_keys)?;
+            let airdrop_nullifier = metadata.hiding_nullifier();
+            Ok(ClaimInput {
+                claim_id: airdrop_config.claim_id(pool, &airdrop_nullifier)?,
+                public_inputs: PublicInputs { airdrop_nullifier },
+                private_inputs,
+                nullifier: Some(tree_position.nullifier),
+            })
+        })
+        .collect()
+}
+
+/// Check a pool's non-membership tree root against the airdrop configuration.
+///
+/// # Errors
+/// Returns an error if the pool is missing from the configuration or the roots differ.
+pub fn ensure_root_matches<P: PoolProcessor>(
+    airdrop_config: &AirdropConfiguration,
+    root: [u8; 32],
+) -> eyre::Result<()> {
+    let Some(expected_root) = P::expected_root(airdrop_config) else {
+        return Err(eyre::eyre!(
+            "{} pool is unexpectedly missing in the airdrop configuration",
+            P::POOL
+        ));
+    };
+    ensure!(
+        expected_root == root,
+        "{} merkle root mismatch with airdrop configuration",
+        P::POOL
+    );
+    Ok(())
+}
+
+/// Split the user's nullifiers into those absent from the snapshot and those in it, whose notes
+/// were spent before the snapshot.
+///
+/// With a `filter` over the snapshot, only the nullifiers it cannot rule out are searched.
+#[must_use]
+pub fn split_spent_nullifiers(
+    chain_nullifiers: &SanitiseNullifiers,
+    user_nullifiers: &SanitiseNullifiers,
+    filter: Option<&NullifierFilter>,
+) -> (SanitiseNullifiers, Vec<Nullifier>) {
+    let (spent, unspent): (Vec<Nullifier>, Vec<Nullifier>) =
+        user_nullifiers.iter().copied().partition(|nullifier| {
+            filter.is_none_or(|filter| filter.may_contain(nullifier)) &&
+                chain_nullifiers.binary_search(nullifier).is_ok()
+        });
+    (SanitiseNullifiers::new(unspent), spent)
+}
+
+/// Build one pool's claims from in-memory snapshot nullifiers using a sparse tree.
+///
+/// Returns no claims if the pool is disabled in the configuration or the viewing key has no
+/// component for it. `on_progress` receives `(processed, total)` chain nullifier counts while the
+/// tree is built.
+///
+/// # Errors
+/// Returns an error if the snapshot is missing for an enabled pool, the tree root does not match
+/// the configuration, or a claim cannot be built.
+pub fn prepare_pool_claims<P: PoolProcessor>(
+    visitor: &AccountNotesVisitor,
+    viewing_keys: &ViewingKeys,
+    airdrop_config: &AirdropConfiguration,
+    chain_nullifiers: Option<&SanitiseNullifiers>,
+    on_progress: impl FnMut(usize, usize),
+) -> eyre::Result<PoolClaimResult<P::PrivateInputs>> {
+    if P::expected_root(airdrop_config).is_none() {
+        return Ok(PoolClaimResult::empty());
+    }
+    let Some(chain_nullifiers) = chain_nullifiers else {
+        return Err(eyre::eyre!(
+            "{} snapshot nullifiers are required by the airdrop configuration",
+            P::POOL
+        ));
+    };
+    let Some(mut collected) = P::collect_notes(visitor, viewing_keys, airdrop_config)? else {
+        warn!("UFVK has no {} viewing key; skipping", P::POOL);
+        return Ok(PoolClaimResult::empty());
+    };
+
+    let user_nullifiers = SanitiseNullifiers::new(collected.notes.keys().copied().collect());
+    let (user_nullifiers, spent) = split_spent_nullifiers(chain_nullifiers, &user_nullifiers, None);
+    collected.exclude_spent(P::POOL, &spent);
+    let (tree, user_positions) =
+        SparsePoolTree::build(P::POOL, chain_nullifiers, &user_nullifiers, on_progress)?;
+    ensure_root_matches::<P>(airdrop_config, tree.root_bytes())?;
+
+    let claims = generate_claims(
+        |position| tree.witness_bytes(position),
+        &user_positions,
+        &collected.notes,
+        viewing_keys,
+        P::POOL,
+        airdrop_config,
+    )
+    .with_context(|| format!("Failed to generate {} claims", P::POOL))?;
+    Ok(PoolClaimResult {
+        claims,
+        excluded: collected.excluded,
+    })
+}
@@ -34,6 +34,12 @@ pub trait NoteMetadata {
     /// Returns the block height where this note was created.
     fn block_height(&self) -> u64;
 
+    /// Returns the note value in zatoshis.
+    fn value(&self) -> u64;
+
+    /// Returns the note position in the commitment tree.
+    fn note_position(&self) -> u64;
+
     /// Builds the private inputs for this note type.
     ///
     /// # Errors
@@ -80,6 +86,14 @@ impl NoteMetadata for SaplingNoteMetadata {
         self.block_height
     }
 
+    fn value(&self) -> u64 {
+        self.value
+    }
+
+    fn note_position(&self) -> u64 {
+        self.note_position
+    }
+
     fn to_private_inputs(
         &self,
         tree_position: &TreePosition,
@@ -156,6 +170,14 @@ impl NoteMetadata for OrchardNoteMetadata {
         self.block_height
     }
 
+    fn value(&self) -> u64 {
+        self.value
+    }
+
+    fn note_position(&self) -> u64 {
+        self.note_position
+    }
+
     fn to_private_inputs(
         &self,
         tree_position: &TreePosition,
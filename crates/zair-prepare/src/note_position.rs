@@ -0,0 +1,112 @@
+//! Independent check of Sapling note positions.
+//!
+//! A claim's `note_commitment_position` feeds the nullifier derivation, so a fabricated position
+//! only shows up deep inside proving. Recomputing the note commitment tree root from the claimed
+//! note, position and Merkle path against a tree state fetched from lightwalletd rejects such
+//! claims before the prover runs.
+
+use incrementalmerkletree::{MerklePath, Position};
+use sapling::{NOTE_COMMITMENT_TREE_DEPTH, Node, Note, PaymentAddress, Rseed};
+use zair_core::schema::proof_inputs::SaplingPrivateInputs;
+
+/// Sapling note commitment tree at the snapshot height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaplingTreeState {
+    /// Root of the note commitment tree.
+    pub root: [u8; 32],
+    /// Number of note commitments in the tree.
+    pub size: u64,
+}
+
+/// Errors from checking a Sapling note position.
+#[derive(Debug, thiserror::Error)]
+pub enum NotePositionError {
+    /// The position is not below the number of note commitments in the tree.
+    #[error("note position {position} is outside the note commitment tree of {size} notes")]
+    OutOfRange {
+        /// The claimed note position.
+        position: u64,
+        /// Number of note commitments in the tree.
+        size: u64,
+    },
+    /// The note's payment address or commitment randomness does not decode.
+    #[error("note at position {0} has an invalid payment address or commitment randomness")]
+    InvalidNote(u64),
+    /// The Merkle path has a bad node or length.
+    #[error("note at position {0} has an invalid note commitment Merkle path")]
+    InvalidMerklePath(u64),
+    /// The note, position and Merkle path do not hash to the tree root.
+    #[error("note at position {0} does not hash to the note commitment tree root")]
+    RootMismatch(u64),
+}
+
+/// Check that a Sapling claim's note sits at its claimed position in `tree`.
+///
+/// # Errors
+/// Returns an error if the position is out of range, the note or path does not decode, or the
+/// recomputed root differs from the tree root.
+pub fn verify_sapling_note_position(
+    inputs: &SaplingPrivateInputs,
+    tree: &SaplingTreeState,
+) -> Result<(), NotePositionError> {
+    let position = inputs.note_commitment_position;
+    if position >= tree.size {
+        return Err(NotePositionError::OutOfRange {
+            position,
+            size: tree.size,
+        });
+    }
+    verify_sapling_note_root(inputs, tree.root)
+}
+
+/// Check that a Sapling claim's note, position and Merkle path hash to `root`.
+///
+/// # Errors
+/// Returns an error if the note or path does not decode, or the recomputed root differs from
+/// `root`.
+pub fn verify_sapling_note_root(
+    inputs: &SaplingPrivateInputs,
+    root: [u8; 32],
+) -> Result<(), NotePositionError> {
+    let position = inputs.note_commitment_position;
+    let note = sapling_note(inputs)?;
+
+    let path_elems = inputs
+        .note_commitment_merkle_path
+        .iter()
+        .map(|node| Node::from_bytes(*node).into_option())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(NotePositionError::InvalidMerklePath(position))?;
+    let path = MerklePath::<Node, NOTE_COMMITMENT_TREE_DEPTH>::from_parts(
+        path_elems,
+        Position::from(position),
+    )
+    .map_err(|()| NotePositionError::InvalidMerklePath(position))?;
+
+    if path.root(Node::from_cmu(&note.cmu())).to_bytes() != root {
+        return Err(NotePositionError::RootMismatch(position));
+    }
+    Ok(())
+}
+
+/// Rebuild the Sapling note of a claim from its private inputs.
+///
+/// # Errors
+/// Returns an error if the payment address or commitment randomness does not decode.
+pub fn sapling_note(inputs: &SaplingPrivateInputs) -> Result<Note, NotePositionError> {
+    let position = inputs.note_commitment_position;
+    let mut address = [0_u8; 43];
+    let (diversifier, pk_d) = address.split_at_mut(inputs.diversifier.len());
+    diversifier.copy_from_slice(&inputs.diversifier);
+    pk_d.copy_from_slice(&inputs.pk_d);
+    let recipient =
+        PaymentAddress::from_bytes(&address).ok_or(NotePositionError::InvalidNote(position))?;
+    let rcm = jubjub::Fr::from_bytes(&inputs.rcm)
+        .into_option()
+        .ok_or(NotePositionError::InvalidNote(position))?;
+    Ok(Note::from_parts(
+        recipient,
+        sapling::value::NoteValue::from_raw(inputs.value),
+        Rseed::BeforeZip212(rcm),
+    ))
+}
@@ -5,14 +5,16 @@
 
 use std::collections::HashMap;
 
-use zair_core::base::{Nullifier, Pool};
+use zair_core::base::{Nullifier, ORCHARD_HIDING_NF_TAG, Pool};
 use zair_core::schema::config::AirdropConfiguration;
-use zair_core::schema::proof_inputs::{ClaimInput, OrchardPrivateInputs, SaplingPrivateInputs};
+use zair_core::schema::proof_inputs::{
+    ClaimInput, ExcludedNote, ExclusionReason, OrchardPrivateInputs, SaplingPrivateInputs,
+};
 use zair_scan::ViewingKeys;
 use zair_scan::scanner::AccountNotesVisitor;
 use zair_scan::user_nullifiers::NoteNullifier as _;
 
-use super::note_metadata::{
+use crate::note_metadata::{
     NoteMetadata, OrchardNoteMetadata, SaplingNoteMetadata, orchard_g_d_from_diversifier,
 };
 
@@ -20,12 +22,44 @@ use super::note_metadata::{
 pub struct PoolClaimResult<P> {
     /// The claim inputs for this pool.
     pub claims: Vec<ClaimInput<P>>,
+    /// The notes of this pool that cannot be claimed.
+    pub excluded: Vec<ExcludedNote>,
 }
 
 impl<P> PoolClaimResult<P> {
     /// Create an empty result for when a pool has no claims.
+    #[must_use]
     pub const fn empty() -> Self {
-        Self { claims: Vec::new() }
+        Self {
+            claims: Vec::new(),
+            excluded: Vec::new(),
+        }
+    }
+}
+
+/// Notes of one pool found by the scan.
+pub struct CollectedNotes<M> {
+    /// Claimable notes keyed by nullifier.
+    pub notes: HashMap<Nullifier, M>,
+    /// Notes that cannot be claimed.
+    pub excluded: Vec<ExcludedNote>,
+}
+
+impl<M: NoteMetadata> CollectedNotes<M> {
+    /// Move the notes whose nullifier is in the snapshot to the excluded notes.
+    pub fn exclude_spent(&mut self, pool: Pool, spent: &[Nullifier]) {
+        for nullifier in spent {
+            if let Some(metadata) = self.notes.remove(nullifier) {
+                self.excluded.push(ExcludedNote {
+                    pool,
+                    nullifier: *nullifier,
+                    value: metadata.value(),
+                    block_height: metadata.block_height(),
+                    note_position: metadata.note_position(),
+                    reason: ExclusionReason::SpentBySnapshot,
+                });
+            }
+        }
     }
 }
 
@@ -46,12 +80,13 @@ pub trait PoolProcessor {
     fn expected_root(config: &AirdropConfiguration) -> Option<[u8; 32]>;
 
     /// Collects note metadata from the visitor.
+    /// Notes below the configured minimum value or without a commitment witness are excluded.
     /// Returns `None` if the viewing key is not available.
     fn collect_notes(
         visitor: &AccountNotesVisitor,
         viewing_keys: &ViewingKeys,
         airdrop_config: &AirdropConfiguration,
-    ) -> eyre::Result<Option<HashMap<Nullifier, Self::Metadata>>>;
+    ) -> eyre::Result<Option<CollectedNotes<Self::Metadata>>>;
 }
 
 /// Sapling pool processor.
@@ -71,7 +106,7 @@ impl PoolProcessor for SaplingPool {
         visitor: &AccountNotesVisitor,
         viewing_keys: &ViewingKeys,
         airdrop_config: &AirdropConfiguration,
-    ) -> eyre::Result<Option<HashMap<Nullifier, Self::Metadata>>> {
+    ) -> eyre::Result<Option<CollectedNotes<Self::Metadata>>> {
         let Some(sapling_key) = viewing_keys.sapling() else {
             return Ok(None);
         };
@@ -85,26 +120,35 @@ impl PoolProcessor for SaplingPool {
         };
 
         let mut notes = HashMap::new();
+        let mut excluded = Vec::new();
         for found_note in visitor.sapling_notes() {
             let nullifier = found_note.nullifier(sapling_key);
+            let value = found_note.note.note.value().inner();
+            let excluded_note = |reason| ExcludedNote {
+                pool: Self::POOL,
+                nullifier,
+                value,
+                block_height: found_note.metadata.height,
+                note_position: found_note.note.position,
+                reason,
+            };
+            if is_below_threshold(airdrop_config, value) {
+                excluded.push(excluded_note(ExclusionReason::BelowThreshold));
+                continue;
+            }
+            let Some(cm_merkle_proof) = visitor.sapling_witness(found_note.note.position)? else {
+                excluded.push(excluded_note(ExclusionReason::MissingPosition));
+                continue;
+            };
             let hiding_nullifier = found_note.hiding_nullifier(sapling_key, &hiding_factor)?;
 
-            let cm_merkle_proof = visitor
-                .sapling_witness(found_note.note.position)?
-                .ok_or_else(|| {
-                    eyre::eyre!(
-                        "Missing Sapling witness for position {}",
-                        found_note.note.position
-                    )
-                })?;
-
             notes.insert(
                 nullifier,
                 SaplingNoteMetadata {
                     diversifier: found_note.note.diversifier(),
                     hiding_nullifier,
                     pk_d: found_note.note.pk_d(),
-                    value: found_note.note.note.value().inner(),
+                    value,
                     rcm: found_note.note.rcm(),
                     note_position: found_note.note.position,
                     scope: found_note.note.scope,
@@ -113,7 +157,7 @@ impl PoolProcessor for SaplingPool {
                 },
             );
         }
-        Ok(Some(notes))
+        Ok(Some(CollectedNotes { notes, excluded }))
     }
 }
 
@@ -134,7 +178,7 @@ impl PoolProcessor for OrchardPool {
         visitor: &AccountNotesVisitor,
         viewing_keys: &ViewingKeys,
         airdrop_config: &AirdropConfiguration,
-    ) -> eyre::Result<Option<HashMap<Nullifier, Self::Metadata>>> {
+    ) -> eyre::Result<Option<CollectedNotes<Self::Metadata>>> {
         let Some(orchard_key) = viewing_keys.orchard() else {
             return Ok(None);
         };
@@ -144,24 +188,34 @@ impl PoolProcessor for OrchardPool {
         };
 
         let hiding_factor = zair_scan::user_nullifiers::OrchardHidingFactor {
-            domain: &orchard_config.target_id,
-            tag: b"K",
+            domain: orchard_config.target_id.orchard_domain(),
+            tag: ORCHARD_HIDING_NF_TAG,
         };
 
         let mut notes = HashMap::new();
+        let mut excluded = Vec::new();
         for found_note in visitor.orchard_notes() {
             let nullifier = found_note.nullifier(orchard_key);
+            let value = found_note.note.value().inner();
+            let excluded_note = |reason| ExcludedNote {
+                pool: Self::POOL,
+                nullifier,
+                value,
+                block_height: found_note.metadata.height,
+                note_position: found_note.metadata.position,
+                reason,
+            };
+            if is_below_threshold(airdrop_config, value) {
+                excluded.push(excluded_note(ExclusionReason::BelowThreshold));
+                continue;
+            }
+            let Some(cm_merkle_proof) = visitor.orchard_witness(found_note.metadata.position)?
+            else {
+                excluded.push(excluded_note(ExclusionReason::MissingPosition));
+                continue;
+            };
             let hiding_nullifier = found_note.hiding_nullifier(orchard_key, &hiding_factor)?;
 
-            let cm_merkle_proof = visitor
-                .orchard_witness(found_note.metadata.position)?
-                .ok_or_else(|| {
-                    eyre::eyre!(
-                        "Missing Orchard witness for position {}",
-                        found_note.metadata.position
-                    )
-                })?;
-
             let address = found_note.note.recipient();
             let diversifier = address.diversifier();
             let raw_addr = address.to_raw_address_bytes();
@@ -178,7 +232,7 @@ impl PoolProcessor for OrchardPool {
                     rseed: *found_note.note.rseed().as_bytes(),
                     g_d,
                     pk_d,
-                    value: found_note.note.value().inner(),
+                    value,
                     note_position: found_note.metadata.position,
                     scope: found_note.metadata.scope,
                     block_height: found_note.metadata.height,
@@ -186,6 +240,12 @@ impl PoolProcessor for OrchardPool {
                 },
             );
         }
-        Ok(Some(notes))
+        Ok(Some(CollectedNotes { notes, excluded }))
     }
 }
+
+fn is_below_threshold(airdrop_config: &AirdropConfiguration, value: u64) -> bool {
+    airdrop_config
+        .min_note_value
+        .is_some_and(|min_note_value| value < min_note_value)
+}
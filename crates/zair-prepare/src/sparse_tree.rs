@@ -0,0 +1,69 @@
+//! Sparse in-memory non-membership trees built directly from snapshot nullifiers.
+
+use zair_core::base::{Pool, SanitiseNullifiers};
+use zair_nonmembership::{
+    MerklePathError, NonMembershipTree, OrchardNonMembershipTree, TreePosition,
+};
+
+/// Sparse non-membership tree of one pool, keeping only the user's witnesses.
+pub enum SparsePoolTree {
+    /// Sapling sparse tree.
+    Sapling(NonMembershipTree),
+    /// Orchard sparse tree.
+    Orchard(OrchardNonMembershipTree),
+}
+
+impl SparsePoolTree {
+    /// Build the tree for `pool` and map the user's nullifiers into their gap leaves.
+    ///
+    /// `on_progress` receives `(processed, total)` chain nullifier counts.
+    ///
+    /// # Errors
+    /// Returns an error if a nullifier is not canonical for the pool or a user nullifier is part
+    /// of the snapshot.
+    pub fn build(
+        pool: Pool,
+        chain_nullifiers: &SanitiseNullifiers,
+        user_nullifiers: &SanitiseNullifiers,
+        on_progress: impl FnMut(usize, usize),
+    ) -> Result<(Self, Vec<TreePosition>), MerklePathError> {
+        match pool {
+            Pool::Sapling => NonMembershipTree::from_chain_and_user_nullifiers_with_progress(
+                chain_nullifiers,
+                user_nullifiers,
+                on_progress,
+            )
+            .map(|(tree, positions)| (Self::Sapling(tree), positions)),
+            Pool::Orchard => {
+                OrchardNonMembershipTree::from_chain_and_user_nullifiers_with_progress(
+                    chain_nullifiers,
+                    user_nullifiers,
+                    on_progress,
+                )
+                .map(|(tree, positions)| (Self::Orchard(tree), positions))
+            }
+        }
+    }
+
+    /// Tree root bytes.
+    #[must_use]
+    pub fn root_bytes(&self) -> [u8; 32] {
+        match self {
+            Self::Sapling(tree) => tree.root().to_bytes(),
+            Self::Orchard(tree) => tree.root_bytes(),
+        }
+    }
+
+    /// Witness of the gap leaf at `position`, leaf to root.
+    ///
+    /// # Errors
+    /// Returns an error if the leaf was not marked for witnessing while building the tree.
+    pub fn witness_bytes(&self, position: u64) -> Result<Vec<[u8; 32]>, MerklePathError> {
+        match self {
+            Self::Sapling(tree) => tree
+                .witness(position.into())
+                .map(|path| path.into_iter().map(|node| node.to_bytes()).collect()),
+            Self::Orchard(tree) => tree.witness_bytes(position.into()),
+        }
+    }
+}
@@ -23,7 +23,10 @@ use crate::gadgets::enforce_less_than;
 
 /// Personalization for the hiding nullifier (airdrop-specific).
 /// This is used to derive a nullifier that doesn't reveal the Zcash nullifier.
-pub const HIDING_NF_PERSONALIZATION: &[u8; 8] = b"ZAIRTEST";
+pub const HIDING_NF_PERSONALIZATION: &[u8; 8] = &zair_core::base::HIDING_NF_PERSONALIZATION;
+
+const _: () =
+    assert!(sapling::NOTE_COMMITMENT_TREE_DEPTH == zair_core::base::NOTE_COMMITMENT_TREE_DEPTH);
 
 /// Prefix for SHA-256 value commitments (`cv_sha256`).
 pub const VALUE_COMMIT_SHA256_PREFIX: &[u8; 4] = &zair_core::base::VALUE_COMMIT_SHA256_PREFIX;
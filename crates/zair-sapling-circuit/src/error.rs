@@ -0,0 +1,140 @@
+//! User-facing causes of unsatisfied claim circuit constraints.
+//!
+//! Bellman creates a proof for an unsatisfied circuit without complaint; the proof only fails to
+//! verify. Replaying the synthesis in a test constraint system names the first unsatisfied
+//! constraint by its namespace path, and the diagnostic table below maps that path back to what
+//! is wrong with the claim.
+
+use bellman::gadgets::test::TestConstraintSystem;
+use bellman::{Circuit as _, SynthesisError};
+
+use crate::Claim;
+
+/// Cause of an unsatisfied claim circuit constraint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintFailure {
+    /// The note value or the value commitment randomness is inconsistent.
+    ValueMismatch,
+    /// The note does not hash to the note commitment tree anchor.
+    AnchorMismatch,
+    /// The note nullifier is not strictly inside the claimed gap.
+    NullifierOutOfGap,
+    /// The gap does not hash to the nullifier gap tree anchor.
+    GapAnchorMismatch,
+    /// The spend validating key or the note address is a small-order point.
+    InvalidKey,
+}
+
+/// Namespace fragments of the claim circuit and the cause an unsatisfied constraint under them
+/// reveals. More specific fragments come first, as `merkle tree hash` also matches the gap tree.
+const DIAGNOSTICS: &[(&str, ConstraintFailure)] = &[
+    (
+        "enforce correct nm root",
+        ConstraintFailure::GapAnchorMismatch,
+    ),
+    ("nm merkle tree hash", ConstraintFailure::GapAnchorMismatch),
+    ("nm leaf hash", ConstraintFailure::GapAnchorMismatch),
+    ("left_nf < nf", ConstraintFailure::NullifierOutOfGap),
+    ("nf < right_nf", ConstraintFailure::NullifierOutOfGap),
+    ("enforce correct root", ConstraintFailure::AnchorMismatch),
+    ("merkle tree hash", ConstraintFailure::AnchorMismatch),
+    ("value commitment", ConstraintFailure::ValueMismatch),
+    ("ak not small order", ConstraintFailure::InvalidKey),
+    ("g_d not small order", ConstraintFailure::InvalidKey),
+];
+
+impl ConstraintFailure {
+    /// Cause of an unsatisfied constraint at `path`, if the diagnostic table knows it.
+    #[must_use]
+    pub fn from_constraint_path(path: &str) -> Option<Self> {
+        DIAGNOSTICS
+            .iter()
+            .find(|(fragment, _)| path.contains(fragment))
+            .map(|(_, failure)| *failure)
+    }
+}
+
+impl std::fmt::Display for ConstraintFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::ValueMismatch => "the note value does not match its value commitment randomness",
+            Self::AnchorMismatch => {
+                "the note does not hash to the note commitment root; the snapshot is stale or the \
+                 note position is wrong"
+            }
+            Self::NullifierOutOfGap => {
+                "the note nullifier is not inside the claimed gap; the note was spent before the \
+                 snapshot or the gap belongs to another note"
+            }
+            Self::GapAnchorMismatch => {
+                "the gap does not hash to the nullifier gap root; the claims were prepared from \
+                 another snapshot"
+            }
+            Self::InvalidKey => "the spend validating key or the note address is malformed",
+        })
+    }
+}
+
+/// An unsatisfied constraint of the claim circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint {
+    /// Namespace path of the constraint.
+    pub path: String,
+    /// Cause of the failure, if the diagnostic table knows it.
+    pub cause: Option<ConstraintFailure>,
+}
+
+impl std::fmt::Display for UnsatisfiedConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.cause {
+            Some(cause) => write!(f, "unsatisfied constraint `{}`: {cause}", self.path),
+            None => write!(f, "unsatisfied constraint `{}`", self.path),
+        }
+    }
+}
+
+impl std::error::Error for UnsatisfiedConstraint {}
+
+/// Synthesize `circuit` in a test constraint system and return its first unsatisfied constraint.
+///
+/// The replay keeps every constraint in memory and is far slower than proving, so use it to
+/// explain a proof that failed verification, not on every claim.
+///
+/// # Errors
+/// Returns an error if a witness is missing or malformed.
+pub fn find_unsatisfied_constraint(
+    circuit: Claim,
+) -> Result<Option<UnsatisfiedConstraint>, SynthesisError> {
+    let mut cs = TestConstraintSystem::new();
+    circuit.synthesize(&mut cs)?;
+    Ok(cs.which_is_unsatisfied().map(|path| UnsatisfiedConstraint {
+        path: path.to_owned(),
+        cause: ConstraintFailure::from_constraint_path(path),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gap_tree_paths_are_not_mistaken_for_the_note_tree() {
+        assert_eq!(
+            ConstraintFailure::from_constraint_path(
+                "nm merkle tree hash 3/computation of pedersen hash"
+            ),
+            Some(ConstraintFailure::GapAnchorMismatch)
+        );
+        assert_eq!(
+            ConstraintFailure::from_constraint_path(
+                "merkle tree hash 3/computation of pedersen hash"
+            ),
+            Some(ConstraintFailure::AnchorMismatch)
+        );
+        assert_eq!(
+            ConstraintFailure::from_constraint_path("nf < right_nf/bit 7/a_xor_b"),
+            Some(ConstraintFailure::NullifierOutOfGap)
+        );
+        assert_eq!(ConstraintFailure::from_constraint_path("rk"), None);
+    }
+}
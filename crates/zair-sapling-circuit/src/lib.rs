@@ -1,6 +1,10 @@
 //! Sapling claim circuit.
 
 pub mod circuit;
+pub mod error;
 pub mod gadgets;
+pub mod report;
 
 pub use circuit::{Claim, ValueCommitmentOpening, ValueCommitmentScheme};
+pub use error::{ConstraintFailure, UnsatisfiedConstraint, find_unsatisfied_constraint};
+pub use report::{ConstraintReport, NamespaceCount, count_constraints};
@@ -0,0 +1,200 @@
+//! Constraint counts of the claim circuit.
+//!
+//! Synthesizing the circuit in a counting constraint system, which keeps no constraints and
+//! evaluates no witnesses, tallies the constraints under every top-level namespace. Reviewers
+//! compare the report across changes to track how much each gadget adds to the circuit.
+
+use bellman::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use bls12_381::Scalar;
+
+/// Constraint count of one namespace of the circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceCount {
+    /// Top-level namespace, with the index of numbered namespaces such as
+    /// `merkle tree hash 3` dropped so that all levels add up under one name.
+    pub namespace: String,
+    /// Constraints enforced under the namespace.
+    pub constraints: usize,
+}
+
+/// Constraint counts of a circuit per top-level namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintReport {
+    /// Namespaces in synthesis order.
+    pub namespaces: Vec<NamespaceCount>,
+    /// Constraints of the whole circuit.
+    pub constraints: usize,
+    /// Public inputs, including the constant one.
+    pub inputs: usize,
+    /// Private auxiliary variables.
+    pub aux: usize,
+}
+
+/// Synthesize `circuit` without witnesses and count its constraints per top-level namespace.
+///
+/// # Errors
+/// Returns an error if the circuit fails to synthesize.
+pub fn count_constraints<C: Circuit<Scalar>>(
+    circuit: C,
+) -> Result<ConstraintReport, SynthesisError> {
+    let mut cs = CountingConstraintSystem::default();
+    circuit.synthesize(&mut cs)?;
+    Ok(cs.report)
+}
+
+/// Constraint system that only counts what the circuit allocates and enforces.
+struct CountingConstraintSystem {
+    report: ConstraintReport,
+    namespaces: Vec<String>,
+}
+
+impl Default for CountingConstraintSystem {
+    fn default() -> Self {
+        Self {
+            report: ConstraintReport {
+                namespaces: Vec::new(),
+                constraints: 0,
+                // The constant one is the first public input.
+                inputs: 1,
+                aux: 0,
+            },
+            namespaces: Vec::new(),
+        }
+    }
+}
+
+impl ConstraintSystem<Scalar> for CountingConstraintSystem {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _annotation: A, _f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let index = self.report.aux;
+        self.report.aux = index.saturating_add(1);
+        Ok(Variable::new_unchecked(Index::Aux(index)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _annotation: A, _f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Scalar, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let index = self.report.inputs;
+        self.report.inputs = index.saturating_add(1);
+        Ok(Variable::new_unchecked(Index::Input(index)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, _a: LA, _b: LB, _c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+        LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+        // Constraints enforced at the root are reported under their own annotation.
+        let namespace = match self.namespaces.first() {
+            Some(namespace) => namespace.clone(),
+            None => group_name(&annotation().into()).to_owned(),
+        };
+        self.report.constraints = self.report.constraints.saturating_add(1);
+        match self
+            .report
+            .namespaces
+            .iter_mut()
+            .find(|count| count.namespace == namespace)
+        {
+            Some(count) => count.constraints = count.constraints.saturating_add(1),
+            None => self.report.namespaces.push(NamespaceCount {
+                namespace,
+                constraints: 1,
+            }),
+        }
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name = name_fn().into();
+        self.namespaces.push(if self.namespaces.is_empty() {
+            group_name(&name).to_owned()
+        } else {
+            name
+        });
+    }
+
+    fn pop_namespace(&mut self) {
+        self.namespaces.pop();
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+/// Namespace name without a trailing index, so `merkle tree hash 3` becomes `merkle tree hash`.
+fn group_name(name: &str) -> &str {
+    match name.rsplit_once(' ') {
+        Some((prefix, index)) if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) => {
+            prefix
+        }
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Squares;
+
+    impl Circuit<Scalar> for Squares {
+        fn synthesize<CS: ConstraintSystem<Scalar>>(
+            self,
+            cs: &mut CS,
+        ) -> Result<(), SynthesisError> {
+            for i in 0..3 {
+                let mut cs = cs.namespace(|| format!("square {i}"));
+                let x = cs.alloc(|| "x", || Err(SynthesisError::AssignmentMissing))?;
+                let y = cs.alloc(|| "y", || Err(SynthesisError::AssignmentMissing))?;
+                cs.enforce(|| "y = x * x", |lc| lc + x, |lc| lc + x, |lc| lc + y);
+            }
+            let out = cs.alloc_input(|| "out", || Err(SynthesisError::AssignmentMissing))?;
+            cs.enforce(
+                || "out is one",
+                |lc| lc + out,
+                |lc| lc + CS::one(),
+                |lc| lc + CS::one(),
+            );
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn numbered_namespaces_add_up_under_one_name() {
+        let report = count_constraints(Squares).expect("synthesize");
+
+        assert_eq!(
+            report.namespaces,
+            vec![
+                NamespaceCount {
+                    namespace: "square".to_owned(),
+                    constraints: 3,
+                },
+                NamespaceCount {
+                    namespace: "out is one".to_owned(),
+                    constraints: 1,
+                },
+            ]
+        );
+        assert_eq!(report.constraints, 4);
+        assert_eq!(report.inputs, 2);
+        assert_eq!(report.aux, 6);
+    }
+}
@@ -0,0 +1,145 @@
+//! Sapling claim proving and verification.
+//!
+//! Generating the parameters takes minutes; set `ZAIR_SAPLING_PK_FILE` to a native-scheme proving
+//! key from `zair setup sapling` to load it instead.
+#![allow(missing_docs)]
+
+use std::hint::black_box;
+use std::path::Path;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use ff::Field as _;
+use incrementalmerkletree::{Hashable as _, Level, Position};
+use rand::SeedableRng as _;
+use rand_xorshift::XorShiftRng;
+use sapling::value::NoteValue;
+use sapling::zip32::ExtendedSpendingKey;
+use sapling::{MerklePath, Node, Note, ProofGenerationKey, Rseed};
+use zair_core::base::{HIDING_NF_PERSONALIZATION, Nullifier, SanitiseNullifiers};
+use zair_nonmembership::{SaplingGapTree, map_sapling_user_positions};
+use zair_sapling_proofs::prepare_verifying_key;
+use zair_sapling_proofs::prover::{
+    ClaimParameters, ClaimProofInputs, ValueCommitmentScheme, generate_claim_proof_with_rng,
+    generate_parameters, load_parameters,
+};
+use zair_sapling_proofs::verifier::verify_claim_proof_output;
+
+/// Chain nullifiers in the snapshot the claimed note is checked against.
+const SNAPSHOT_SIZE: u64 = 1_000;
+
+fn parameters() -> ClaimParameters {
+    match std::env::var("ZAIR_SAPLING_PK_FILE") {
+        Ok(path) => load_parameters(Path::new(&path), false).expect("proving key should load"),
+        Err(_) => {
+            generate_parameters(ValueCommitmentScheme::Native).expect("parameters should generate")
+        }
+    }
+}
+
+/// A claim of one note at position 0 of an otherwise empty note commitment tree.
+fn claim_inputs(rng: &mut XorShiftRng) -> (ClaimProofInputs, ProofGenerationKey) {
+    let extsk = ExtendedSpendingKey::master(&[7_u8; 32]);
+    let proof_generation_key = extsk.expsk.proof_generation_key();
+    let nk = proof_generation_key.to_viewing_key().nk;
+    let address = extsk
+        .to_diversifiable_full_viewing_key()
+        .default_address()
+        .1;
+
+    let rcm = jubjub::Fr::random(&mut *rng);
+    let note = Note::from_parts(
+        address,
+        NoteValue::from_raw(1_000),
+        Rseed::BeforeZip212(rcm),
+    );
+    let merkle_path: Vec<Node> = (0..sapling::NOTE_COMMITMENT_TREE_DEPTH)
+        .map(|level| Node::empty_root(Level::from(level)))
+        .collect();
+    let note_commitment_root = MerklePath::from_parts(merkle_path.clone(), Position::from(0))
+        .expect("path has the tree depth")
+        .root(Node::from_cmu(&note.cmu()))
+        .to_bytes();
+
+    let chain = SanitiseNullifiers::new(
+        (0..SNAPSHOT_SIZE)
+            .map(|_| Nullifier::from(jubjub::Fr::random(&mut *rng).to_bytes()))
+            .collect(),
+    );
+    let tree = SaplingGapTree::from_nullifiers(&chain).expect("tree should build");
+    let nullifier = Nullifier::from(note.nf(&nk, 0).0);
+    let positions = map_sapling_user_positions(&chain, &SanitiseNullifiers::new(vec![nullifier]))
+        .expect("mapping should build");
+    let gap = positions.first().expect("one user nullifier");
+    let gap_position = u64::from(gap.leaf_position);
+    let nm_merkle_path = tree
+        .witness_bytes(gap_position)
+        .expect("witness should build")
+        .into_iter()
+        .enumerate()
+        .map(|(i, sibling)| (sibling, (gap_position >> i) & 1 == 1))
+        .collect();
+
+    let address_bytes = address.to_bytes();
+    let (diversifier, pk_d) = address_bytes
+        .split_first_chunk::<11>()
+        .expect("addresses are 43 bytes");
+    let inputs = ClaimProofInputs {
+        diversifier: *diversifier,
+        pk_d: pk_d.try_into().expect("addresses are 43 bytes"),
+        value: 1_000,
+        rcm: rcm.to_bytes(),
+        position: 0,
+        merkle_path: merkle_path.iter().map(Node::to_bytes).collect(),
+        note_commitment_root,
+        airdrop_nullifier: note.nf_hiding(&nk, 0, &HIDING_NF_PERSONALIZATION).0,
+        nm_left_nf: gap.left_bound.into(),
+        nm_right_nf: gap.right_bound.into(),
+        nm_merkle_path,
+        nullifier_gap_root: tree.root_bytes(),
+        value_commitment_scheme: ValueCommitmentScheme::Native,
+        alpha: jubjub::Fr::random(&mut *rng).to_bytes(),
+        rcv: jubjub::Fr::random(&mut *rng).to_bytes(),
+        rcv_sha256: None,
+    };
+    (inputs, proof_generation_key)
+}
+
+fn claim(c: &mut Criterion) {
+    let params = parameters();
+    let pvk = prepare_verifying_key(&params.0.vk);
+    let mut rng = XorShiftRng::seed_from_u64(0);
+    let (inputs, proof_generation_key) = claim_inputs(&mut rng);
+
+    let mut group = c.benchmark_group("sapling_claim");
+    group.sample_size(10);
+    group.bench_function("prove", |b| {
+        b.iter(|| {
+            generate_claim_proof_with_rng(
+                &params,
+                black_box(&inputs),
+                &proof_generation_key,
+                &mut rng,
+            )
+            .expect("proof should generate")
+        });
+    });
+
+    let proof = generate_claim_proof_with_rng(&params, &inputs, &proof_generation_key, &mut rng)
+        .expect("proof should generate");
+    group.bench_function("verify", |b| {
+        b.iter(|| {
+            verify_claim_proof_output(
+                black_box(&proof),
+                &pvk,
+                ValueCommitmentScheme::Native,
+                &inputs.note_commitment_root,
+                &inputs.nullifier_gap_root,
+            )
+            .expect("proof should verify");
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, claim);
+criterion_main!(benches);
@@ -5,11 +5,12 @@
 
 use std::path::Path;
 
+use bellman::SynthesisError;
 use bellman::groth16::{Parameters, generate_random_parameters};
 use bls12_381::Bls12;
 use rand::rngs::OsRng;
 use zair_nonmembership::NON_MEMBERSHIP_TREE_DEPTH;
-use zair_sapling_circuit::Claim;
+use zair_sapling_circuit::{Claim, ConstraintReport, count_constraints};
 
 use crate::prover::proving::ClaimParameters;
 use crate::types::ValueCommitmentScheme;
@@ -47,8 +48,26 @@ pub fn generate_parameters(
 ) -> Result<ClaimParameters, ParameterError> {
     let mut rng = OsRng;
 
-    // Create empty circuit for parameter generation
-    let empty_circuit = Claim {
+    let params =
+        generate_random_parameters::<Bls12, _, _>(empty_circuit(value_commitment_scheme), &mut rng)
+            .map_err(ParameterError::Generation)?;
+
+    Ok(ClaimParameters(params))
+}
+
+/// Count the constraints of the Claim circuit per top-level namespace.
+///
+/// # Errors
+/// Returns an error if the circuit fails to synthesize.
+pub fn constraint_report(
+    value_commitment_scheme: ValueCommitmentScheme,
+) -> Result<ConstraintReport, SynthesisError> {
+    count_constraints(empty_circuit(value_commitment_scheme))
+}
+
+/// Claim circuit without witnesses, as used for parameter generation and constraint counting.
+fn empty_circuit(value_commitment_scheme: ValueCommitmentScheme) -> Claim {
+    Claim {
         value_commitment_opening: None,
         proof_generation_key: None,
         payment_address: None,
@@ -62,12 +81,7 @@ pub fn generate_parameters(
         nm_anchor: None,
         value_commitment_scheme: value_commitment_scheme.into(),
         rcv_sha256: None,
-    };
-
-    let params = generate_random_parameters::<Bls12, _, _>(empty_circuit, &mut rng)
-        .map_err(ParameterError::Generation)?;
-
-    Ok(ClaimParameters(params))
+    }
 }
 
 /// Save parameters to files.
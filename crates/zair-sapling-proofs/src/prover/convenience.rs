@@ -10,9 +10,11 @@
 use ff::PrimeField;
 use group::Curve;
 use incrementalmerkletree::Position;
+use rand::RngCore;
 use rand::rngs::OsRng;
 use sapling::value::{NoteValue, ValueCommitTrapdoor};
 use sapling::{Diversifier, Note, PaymentAddress, ProofGenerationKey, Rseed};
+use zair_sapling_circuit::{Claim, UnsatisfiedConstraint, find_unsatisfied_constraint};
 
 use crate::error::ClaimProofError;
 use crate::prover::proving::{
@@ -65,17 +67,93 @@ pub fn bytes_less_than(a: &[u8; 32], b: &[u8; 32]) -> bool {
 ///
 /// # Errors
 /// Returns an error if proof generation fails.
-#[allow(
-    clippy::too_many_lines,
-    reason = "End-to-end witness preparation and proving"
-)]
 pub fn generate_claim_proof(
     params: &ClaimParameters,
     inputs: &ClaimProofInputs,
     proof_generation_key: &ProofGenerationKey,
 ) -> Result<ClaimProofOutput, ClaimProofError> {
-    let mut rng = OsRng;
+    generate_claim_proof_with_rng(params, inputs, proof_generation_key, &mut OsRng)
+}
+
+/// Generate a claim proof using the caller's source of proof randomness.
+///
+/// Identical to [`generate_claim_proof`], except that the Groth16 blinding factors are drawn
+/// from `rng`. A seeded `rng` makes the proof bytes reproducible, which is only suitable for
+/// tests and known-answer vectors.
+///
+/// # Errors
+/// Returns an error if proof generation fails.
+pub fn generate_claim_proof_with_rng<R: RngCore>(
+    params: &ClaimParameters,
+    inputs: &ClaimProofInputs,
+    proof_generation_key: &ProofGenerationKey,
+    rng: &mut R,
+) -> Result<ClaimProofOutput, ClaimProofError> {
+    let PreparedClaim {
+        circuit,
+        value,
+        alpha,
+        rcv,
+        rcv_sha256,
+    } = prepare_claim(inputs, proof_generation_key)?;
+
+    // Create and encode the proof
+    let proof = create_proof(params, circuit, rng);
+    let zkproof = encode_proof(&proof);
+
+    // Note: We intentionally do NOT compute or expose the Zcash nullifier
+    // to preserve privacy. The circuit proves knowledge of it internally.
+
+    // Compute value commitment(s) deterministically from value and trapdoor/randomness.
+    let rk_bytes: [u8; 32] = proof_generation_key.to_viewing_key().rk(alpha).into();
+    let cv_bytes: [u8; 32] = sapling::value::ValueCommitment::derive(value, rcv).to_bytes();
+    let cv_sha256 = rcv_sha256.map(|r| zair_core::base::cv_sha256(inputs.value, r));
 
+    let proof_output = ClaimProofOutput {
+        zkproof,
+        rk: rk_bytes,
+        cv: match inputs.value_commitment_scheme {
+            ValueCommitmentScheme::Native => Some(cv_bytes),
+            ValueCommitmentScheme::Sha256 => None,
+        },
+        cv_sha256,
+        airdrop_nullifier: inputs.airdrop_nullifier,
+    };
+    Ok(proof_output)
+}
+
+/// Find the claim circuit constraint that `inputs` do not satisfy, with its user-facing cause.
+///
+/// Bellman proves an unsatisfied circuit without error, so a bad witness only shows up when the
+/// proof fails to verify. This replays the circuit synthesis to name the failing constraint. It is
+/// much slower than proving and meant for explaining such a failure.
+///
+/// # Errors
+/// Returns an error if the inputs cannot be parsed or the circuit cannot be synthesized.
+pub fn diagnose_claim_proof(
+    inputs: &ClaimProofInputs,
+    proof_generation_key: &ProofGenerationKey,
+) -> Result<Option<UnsatisfiedConstraint>, ClaimProofError> {
+    let prepared = prepare_claim(inputs, proof_generation_key)?;
+    find_unsatisfied_constraint(prepared.circuit)
+        .map_err(|e| ClaimProofError::ProofCreation(e.to_string()))
+}
+
+/// Claim circuit and the witness values the proof output is derived from.
+struct PreparedClaim {
+    circuit: Claim,
+    value: NoteValue,
+    alpha: jubjub::Fr,
+    rcv: ValueCommitTrapdoor,
+    rcv_sha256: Option<[u8; 32]>,
+}
+
+/// Parse `inputs` and build the claim circuit.
+#[allow(clippy::too_many_lines, reason = "End-to-end witness preparation")]
+fn prepare_claim(
+    inputs: &ClaimProofInputs,
+    proof_generation_key: &ProofGenerationKey,
+) -> Result<PreparedClaim, ClaimProofError> {
     // Parse inputs
     let value = NoteValue::from_raw(inputs.value);
     let rcm = jubjub::Fr::from_bytes(&inputs.rcm)
@@ -190,29 +268,13 @@ pub fn generate_claim_proof(
         rcv_sha256,
     )?;
 
-    // Create and encode the proof
-    let proof = create_proof(params, circuit, &mut rng);
-    let zkproof = encode_proof(&proof);
-
-    // Note: We intentionally do NOT compute or expose the Zcash nullifier
-    // to preserve privacy. The circuit proves knowledge of it internally.
-
-    // Compute value commitment(s) deterministically from value and trapdoor/randomness.
-    let rk_bytes: [u8; 32] = proof_generation_key.to_viewing_key().rk(alpha).into();
-    let cv_bytes: [u8; 32] = sapling::value::ValueCommitment::derive(value, rcv).to_bytes();
-    let cv_sha256 = rcv_sha256.map(|r| zair_core::base::cv_sha256(inputs.value, r));
-
-    let proof_output = ClaimProofOutput {
-        zkproof,
-        rk: rk_bytes,
-        cv: match inputs.value_commitment_scheme {
-            ValueCommitmentScheme::Native => Some(cv_bytes),
-            ValueCommitmentScheme::Sha256 => None,
-        },
-        cv_sha256,
-        airdrop_nullifier: inputs.airdrop_nullifier,
-    };
-    Ok(proof_output)
+    Ok(PreparedClaim {
+        circuit,
+        value,
+        alpha,
+        rcv,
+        rcv_sha256,
+    })
 }
 
 /// Compute the merkle root from a note commitment and merkle path.
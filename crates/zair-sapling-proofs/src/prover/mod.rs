@@ -5,9 +5,14 @@ mod builder;
 mod convenience;
 mod proving;
 
-pub use builder::{ParameterError, generate_parameters, load_parameters, save_parameters};
-pub use convenience::generate_claim_proof;
+pub use builder::{
+    ParameterError, constraint_report, generate_parameters, load_parameters, save_parameters,
+};
+pub use convenience::{diagnose_claim_proof, generate_claim_proof, generate_claim_proof_with_rng};
 pub use proving::ClaimParameters;
+pub use zair_sapling_circuit::{
+    ConstraintFailure, ConstraintReport, NamespaceCount, UnsatisfiedConstraint,
+};
 
 pub use crate::error::ClaimProofError;
-pub use crate::types::{ClaimProofInputs, ValueCommitmentScheme};
+pub use crate::types::{ClaimProofInputs, UnsupportedScheme, ValueCommitmentScheme};
@@ -25,11 +25,23 @@ impl From<ValueCommitmentScheme> for zair_sapling_circuit::ValueCommitmentScheme
     }
 }
 
-impl From<zair_core::schema::config::ValueCommitmentScheme> for ValueCommitmentScheme {
-    fn from(scheme: zair_core::schema::config::ValueCommitmentScheme) -> Self {
+/// A configured value commitment scheme that the Sapling circuit does not implement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("the Sapling claim circuit does not support the {0:?} value commitment scheme")]
+pub struct UnsupportedScheme(pub zair_core::schema::config::ValueCommitmentScheme);
+
+impl TryFrom<zair_core::schema::config::ValueCommitmentScheme> for ValueCommitmentScheme {
+    type Error = UnsupportedScheme;
+
+    fn try_from(
+        scheme: zair_core::schema::config::ValueCommitmentScheme,
+    ) -> Result<Self, Self::Error> {
         match scheme {
-            zair_core::schema::config::ValueCommitmentScheme::Native => Self::Native,
-            zair_core::schema::config::ValueCommitmentScheme::Sha256 => Self::Sha256,
+            zair_core::schema::config::ValueCommitmentScheme::Native => Ok(Self::Native),
+            zair_core::schema::config::ValueCommitmentScheme::Sha256 => Ok(Self::Sha256),
+            zair_core::schema::config::ValueCommitmentScheme::Poseidon => {
+                Err(UnsupportedScheme(scheme))
+            }
         }
     }
 }
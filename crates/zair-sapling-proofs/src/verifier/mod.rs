@@ -10,7 +10,7 @@ use zair_core::base::{Nullifier, hash_bytes};
 
 pub use crate::error::ClaimProofError;
 pub use crate::types::{
-    ClaimProofOutput, GROTH_PROOF_SIZE, GrothProofBytes, ValueCommitmentScheme,
+    ClaimProofOutput, GROTH_PROOF_SIZE, GrothProofBytes, UnsupportedScheme, ValueCommitmentScheme,
 };
 
 /// Domain tag for Sapling proof-hash preimages.
@@ -176,6 +176,56 @@ impl ClaimPublicInputs {
     }
 }
 
+impl ClaimPublicInputs {
+    /// Canonical byte encoding of the public inputs.
+    ///
+    /// Every scalar of [`Self::to_vec`] is written as its 32-byte little-endian encoding, in the
+    /// order the verifier consumes them, so external verifiers can reproduce the exact input
+    /// layout.
+    ///
+    /// # Errors
+    /// Returns an error if the public inputs cannot be packed into scalars.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, VerificationError> {
+        Ok(self
+            .to_vec()?
+            .iter()
+            .flat_map(bls12_381::Scalar::to_bytes)
+            .collect())
+    }
+}
+
+impl ClaimProofOutput {
+    /// Value commitment scheme of the proof, read from the commitment it exposes.
+    #[must_use]
+    pub const fn value_commitment_scheme(&self) -> ValueCommitmentScheme {
+        if self.cv_sha256.is_some() {
+            ValueCommitmentScheme::Sha256
+        } else {
+            ValueCommitmentScheme::Native
+        }
+    }
+
+    /// Typed public inputs of the proof, given the roots of the airdrop it was proven against.
+    ///
+    /// # Errors
+    /// Returns an error if a point or root does not decode.
+    pub fn public_inputs(
+        &self,
+        note_commitment_root: &[u8; 32],
+        nullifier_gap_root: &[u8; 32],
+    ) -> Result<ClaimPublicInputs, VerificationError> {
+        ClaimPublicInputs::from_bytes(
+            self.value_commitment_scheme(),
+            &self.rk,
+            self.cv.as_ref(),
+            self.cv_sha256.as_ref(),
+            note_commitment_root,
+            &self.airdrop_nullifier,
+            nullifier_gap_root,
+        )
+    }
+}
+
 /// Verify a claim proof with typed inputs.
 ///
 /// # Arguments
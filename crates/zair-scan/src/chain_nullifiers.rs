@@ -2,11 +2,11 @@
 //! `ChainNullifiers` provides a streaming interface to read nullifiers from various sources.
 
 use std::ops::RangeInclusive;
-use std::pin::Pin;
+use std::pin::{Pin, pin};
 
-use futures::Stream;
+use futures::{Stream, StreamExt as _};
 
-use crate::{Nullifier, Pool};
+use crate::{Nullifier, Pool, SanitiseNullifiers};
 
 /// A boxed stream of nullifiers with the given error type.
 pub type BoxedNullifierStream<E> = Pin<Box<dyn Stream<Item = Result<PoolNullifier, E>> + Send>>;
@@ -42,3 +42,27 @@ pub trait ChainNullifiers: Sized {
     /// for details on cleanup behavior.
     fn nullifiers_stream(&self, range: &RangeInclusive<u64>) -> Self::Stream;
 }
+
+/// Collect the nullifiers revealed in `range` from `source`, as sorted Sapling and Orchard sets.
+///
+/// # Errors
+/// Returns the first error of the source's stream.
+pub async fn collect_nullifiers<C: ChainNullifiers>(
+    source: &C,
+    range: &RangeInclusive<u64>,
+) -> Result<(SanitiseNullifiers, SanitiseNullifiers), C::Error> {
+    let mut sapling = Vec::new();
+    let mut orchard = Vec::new();
+    let mut stream = pin!(source.nullifiers_stream(range));
+    while let Some(entry) = stream.next().await {
+        let entry = entry?;
+        match entry.pool {
+            Pool::Sapling => sapling.push(entry.nullifier),
+            Pool::Orchard => orchard.push(entry.nullifier),
+        }
+    }
+    Ok((
+        SanitiseNullifiers::new(sapling),
+        SanitiseNullifiers::new(orchard),
+    ))
+}
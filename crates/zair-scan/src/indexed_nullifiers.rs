@@ -0,0 +1,276 @@
+//! Height-indexed snapshot format.
+//!
+//! The plain snapshot is a bare list of nullifiers. The indexed format additionally records the
+//! scanned block range and the height at which each nullifier was revealed, so the range
+//! boundaries can be audited and an incremental update can resume right after the last scanned
+//! block.
+//!
+//! Layout (all integers little-endian):
+//!
+//! | Bytes  | Field                                          |
+//! |--------|------------------------------------------------|
+//! | 8      | magic `ZAIRIDX1`                               |
+//! | 8      | first scanned height                           |
+//! | 8      | last scanned height                            |
+//! | 40 × n | records sorted by nullifier: nullifier, height |
+
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::ops::RangeInclusive;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use zair_core::base::{NULLIFIER_SIZE, Nullifier, SanitiseNullifiers};
+
+use crate::Pool;
+use crate::scanner::BlockNullifiers;
+
+/// Magic prefix of an indexed snapshot file.
+pub const INDEXED_SNAPSHOT_MAGIC: [u8; 8] = *b"ZAIRIDX1";
+
+const HEADER_SIZE: usize = 24;
+const RECORD_SIZE: usize = NULLIFIER_SIZE + 8;
+
+/// Nullifiers of one pool with the height each was revealed at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedSnapshot {
+    range: RangeInclusive<u64>,
+    entries: Vec<(Nullifier, u64)>,
+}
+
+impl IndexedSnapshot {
+    /// Build an indexed snapshot of the scanned `range`.
+    ///
+    /// Entries are sorted by nullifier; a repeated nullifier keeps its earliest height.
+    #[must_use]
+    pub fn new(range: RangeInclusive<u64>, mut entries: Vec<(Nullifier, u64)>) -> Self {
+        entries.sort_unstable();
+        entries.dedup_by_key(|(nullifier, _)| *nullifier);
+        Self { range, entries }
+    }
+
+    /// Build the indexed snapshot of `pool` from per-block nullifiers.
+    #[must_use]
+    pub fn from_blocks(
+        range: RangeInclusive<u64>,
+        blocks: &BTreeMap<u64, BlockNullifiers>,
+        pool: Pool,
+    ) -> Self {
+        let entries = blocks
+            .iter()
+            .flat_map(|(&height, block)| {
+                let nullifiers = match pool {
+                    Pool::Sapling => &block.sapling,
+                    Pool::Orchard => &block.orchard,
+                };
+                nullifiers.iter().map(move |&nullifier| (nullifier, height))
+            })
+            .collect();
+        Self::new(range, entries)
+    }
+
+    /// The scanned block range.
+    #[must_use]
+    pub const fn range(&self) -> &RangeInclusive<u64> {
+        &self.range
+    }
+
+    /// First height not covered by this snapshot, where an incremental update resumes.
+    #[must_use]
+    pub const fn resume_height(&self) -> u64 {
+        self.range.end().saturating_add(1)
+    }
+
+    /// Nullifiers with their heights, sorted by nullifier.
+    #[must_use]
+    pub fn entries(&self) -> &[(Nullifier, u64)] {
+        &self.entries
+    }
+
+    /// Height at which `nullifier` was revealed, if it is part of the snapshot.
+    #[must_use]
+    pub fn height_of(&self, nullifier: &Nullifier) -> Option<u64> {
+        self.entries
+            .binary_search_by_key(nullifier, |(nf, _)| *nf)
+            .ok()
+            .and_then(|index| self.entries.get(index))
+            .map(|(_, height)| *height)
+    }
+
+    /// The nullifiers without their heights.
+    #[must_use]
+    pub fn nullifiers(&self) -> SanitiseNullifiers {
+        SanitiseNullifiers::new(self.entries.iter().map(|(nf, _)| *nf).collect())
+    }
+}
+
+/// Write an indexed snapshot to an async writer.
+///
+/// # Errors
+/// If write fails
+pub async fn write_indexed_nullifiers(
+    snapshot: &IndexedSnapshot,
+    mut writer: impl AsyncWriteExt + Unpin,
+) -> std::io::Result<()> {
+    writer.write_all(&INDEXED_SNAPSHOT_MAGIC).await?;
+    writer
+        .write_all(&snapshot.range.start().to_le_bytes())
+        .await?;
+    writer
+        .write_all(&snapshot.range.end().to_le_bytes())
+        .await?;
+    for (nullifier, height) in &snapshot.entries {
+        writer.write_all(nullifier.as_ref()).await?;
+        writer.write_all(&height.to_le_bytes()).await?;
+    }
+    writer.flush().await?;
+
+    Ok(())
+}
+
+/// Read an indexed snapshot from an async reader.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Reading from the file fails
+/// - The magic prefix is missing or the records are truncated
+/// - The records are not strictly sorted by nullifier or a height is outside the scanned range
+pub async fn read_indexed_nullifiers(
+    mut reader: impl AsyncReadExt + Unpin,
+) -> std::io::Result<IndexedSnapshot> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).await?;
+
+    let (header, records) = buf
+        .split_at_checked(HEADER_SIZE)
+        .ok_or_else(|| invalid_data("file is too short for an indexed snapshot header"))?;
+    let (magic, bounds) = header.split_at(INDEXED_SNAPSHOT_MAGIC.len());
+    if magic != INDEXED_SNAPSHOT_MAGIC {
+        return Err(invalid_data("file is not an indexed snapshot"));
+    }
+    let (start, end) = bounds.split_at(8);
+    let range = read_u64(start)?..=read_u64(end)?;
+    if range.is_empty() {
+        return Err(invalid_data(format!(
+            "scanned range {}..={} is empty",
+            range.start(),
+            range.end()
+        )));
+    }
+
+    let chunks = records.chunks_exact(RECORD_SIZE);
+    if !chunks.remainder().is_empty() {
+        return Err(invalid_data(format!(
+            "records have {} bytes which is not a multiple of the record size ({RECORD_SIZE})",
+            records.len()
+        )));
+    }
+
+    let mut entries: Vec<(Nullifier, u64)> = Vec::with_capacity(chunks.len());
+    for record in chunks {
+        let (nullifier, height) = record.split_at(NULLIFIER_SIZE);
+        let nullifier = Nullifier::try_from(nullifier).map_err(invalid_data)?;
+        let height = read_u64(height)?;
+        if !range.contains(&height) {
+            return Err(invalid_data(format!(
+                "nullifier {nullifier} has height {height} outside the scanned range"
+            )));
+        }
+        if entries.last().is_some_and(|(last, _)| *last >= nullifier) {
+            return Err(invalid_data("records are not strictly sorted by nullifier"));
+        }
+        entries.push((nullifier, height));
+    }
+
+    Ok(IndexedSnapshot { range, entries })
+}
+
+fn read_u64(bytes: &[u8]) -> std::io::Result<u64> {
+    bytes.try_into().map(u64::from_le_bytes).map_err(|_| {
+        invalid_data(format!(
+            "expected 8 bytes for an integer, got {}",
+            bytes.len()
+        ))
+    })
+}
+
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Error {
+    Error::new(ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::io::Cursor;
+    use tokio_util::compat::FuturesAsyncReadCompatExt as _;
+
+    use super::*;
+
+    fn nf(v: u8) -> Nullifier {
+        Nullifier::new([v; 32])
+    }
+
+    async fn encode(snapshot: &IndexedSnapshot) -> Vec<u8> {
+        let mut writer = Cursor::new(Vec::new()).compat();
+        write_indexed_nullifiers(snapshot, &mut writer)
+            .await
+            .expect("Failed to write indexed snapshot");
+        writer.into_inner().into_inner()
+    }
+
+    #[test]
+    fn from_blocks_keeps_earliest_height() {
+        let mut blocks = BTreeMap::new();
+        blocks.insert(
+            10,
+            BlockNullifiers {
+                sapling: vec![nf(2)],
+                orchard: vec![nf(9)],
+            },
+        );
+        blocks.insert(
+            12,
+            BlockNullifiers {
+                sapling: vec![nf(1), nf(2)],
+                orchard: Vec::new(),
+            },
+        );
+
+        let snapshot = IndexedSnapshot::from_blocks(5..=20, &blocks, Pool::Sapling);
+        assert_eq!(snapshot.entries(), &[(nf(1), 12), (nf(2), 10)]);
+        assert_eq!(snapshot.height_of(&nf(2)), Some(10));
+        assert_eq!(snapshot.height_of(&nf(9)), None);
+        assert_eq!(snapshot.resume_height(), 21);
+    }
+
+    #[tokio::test]
+    async fn write_read_roundtrip() {
+        let original = IndexedSnapshot::new(5..=20, vec![(nf(3), 20), (nf(1), 5), (nf(2), 11)]);
+
+        let buf = encode(&original).await;
+        assert_eq!(buf.len(), HEADER_SIZE + 3 * RECORD_SIZE);
+
+        let read_back = read_indexed_nullifiers(Cursor::new(buf).compat())
+            .await
+            .expect("Failed to read indexed snapshot");
+        assert_eq!(read_back, original);
+    }
+
+    #[tokio::test]
+    async fn read_rejects_invalid_data() {
+        let valid = encode(&IndexedSnapshot::new(5..=20, vec![(nf(1), 5), (nf(2), 6)])).await;
+
+        let mut plain_snapshot = valid.clone();
+        plain_snapshot.splice(..8, [0_u8; 8]);
+        let mut truncated = valid.clone();
+        truncated.pop();
+        let out_of_range = encode(&IndexedSnapshot::new(5..=20, vec![(nf(1), 21)])).await;
+
+        for data in [plain_snapshot, truncated, out_of_range, vec![0_u8; 3]] {
+            let result = read_indexed_nullifiers(Cursor::new(data).compat()).await;
+            assert!(
+                matches!(result, Err(ref e) if e.kind() == ErrorKind::InvalidData),
+                "Expected InvalidData error, got {result:?}"
+            );
+        }
+    }
+}
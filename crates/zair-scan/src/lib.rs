@@ -1,11 +1,20 @@
 //! Chain scanning and lightwalletd integration.
 
 pub mod chain_nullifiers;
+pub mod indexed_nullifiers;
+#[cfg(feature = "lightwalletd")]
 pub mod light_walletd;
+pub mod network;
+pub mod nullifier_filter;
 pub mod scanner;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_nullifiers;
 pub mod user_nullifiers;
 pub mod viewing_keys;
 
+pub use indexed_nullifiers::{IndexedSnapshot, read_indexed_nullifiers, write_indexed_nullifiers};
+pub use network::Network;
+pub use nullifier_filter::{NullifierFilter, read_nullifier_filter, write_nullifier_filter};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 pub use viewing_keys::{OrchardViewingKeys, SaplingViewingKeys, ViewingKeys};
 pub use zair_core::base::Pool;
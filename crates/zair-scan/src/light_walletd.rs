@@ -4,23 +4,32 @@ mod config;
 mod error;
 mod retry;
 
+use std::collections::HashMap;
 use std::ops::RangeInclusive;
+use std::sync::Mutex;
 use std::time::Duration;
 
-pub use config::LightWalletdConfig;
+pub use config::{Backend, LightWalletdConfig};
+pub use error::LightWalletdError;
 use futures::{Stream, StreamExt as _};
 use tonic::transport::{Channel, ClientTlsConfig, Uri};
 use tracing::warn;
+use zair_core::schema::transparent::TransparentUtxo;
 use zcash_client_backend::data_api::BlockMetadata;
 use zcash_client_backend::proto::compact_formats::CompactBlock;
 use zcash_client_backend::proto::service::compact_tx_streamer_client::CompactTxStreamerClient;
-use zcash_client_backend::proto::service::{BlockId, BlockRange, TreeState};
-use zcash_protocol::consensus::BlockHeight;
+use zcash_client_backend::proto::service::{
+    BlockId, BlockRange, ChainSpec, Empty, GetAddressUtxosArg, GetAddressUtxosReply, TreeState,
+};
+use zcash_primitives::transaction::Transaction;
+use zcash_protocol::consensus::{BlockHeight, BranchId};
 
+use crate::chain_nullifiers::PoolNullifier;
 use crate::light_walletd::config::ValidatedLightWalletdConfig;
 use crate::light_walletd::error::LightWalletdError;
 use crate::light_walletd::retry::retry_with_backoff;
 use crate::scanner::{BlockScanner, ScanVisitor, extract_nullifiers};
+use crate::{Nullifier, Pool};
 
 /// Default connection timeout in seconds
 const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
@@ -36,14 +45,21 @@ const MAX_RETRY_DELAY_MS: u64 = 10000;
 const BACKOFF_FACTOR: u32 = 2;
 /// Timeout for receiving stream messages in seconds
 const STREAM_MESSAGE_TIMEOUT_SECS: u64 = 60;
+/// How long to wait for the next mempool transaction before the mempool is considered read
+const MEMPOOL_IDLE_TIMEOUT_MS: u64 = 2000;
+/// Counter of blocks processed by the scanners, labelled by `scan` (`nullifiers` or `notes`).
+pub const SCANNED_BLOCKS_METRIC: &str = "zair_scanned_blocks_total";
 
 /// A lightwalletd client
 pub struct LightWalletd {
     client: CompactTxStreamerClient<Channel>,
     config: ValidatedLightWalletdConfig,
+    /// Anchors already fetched, keyed by block height.
+    anchors: Mutex<HashMap<u64, CommitmentTreeAnchors>>,
 }
 
 /// Commitment tree anchors for Sapling and Orchard at a specific block height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CommitmentTreeAnchors {
     /// Sapling commitment tree anchor
     pub sapling: [u8; 32],
@@ -63,7 +79,30 @@ impl LightWalletd {
     ///
     /// Returns an error if the connection to the endpoint fails.
     pub async fn connect(endpoint: Uri) -> Result<Self, LightWalletdError> {
-        Self::connect_with_config(endpoint, LightWalletdConfig::default().validate()?).await
+        Self::connect_backend(endpoint, Backend::default()).await
+    }
+
+    /// Connect to an endpoint served by `backend`, with the default configuration otherwise
+    ///
+    /// # Prerequisite
+    ///
+    /// `rustls::crypto::ring::default_provider().install_default()` needs to be called
+    /// before this function is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection to the endpoint fails.
+    pub async fn connect_backend(
+        endpoint: Uri,
+        backend: Backend,
+    ) -> Result<Self, LightWalletdError> {
+        Self::connect_with_config(
+            endpoint,
+            LightWalletdConfig::default()
+                .with_backend(backend)
+                .validate()?,
+        )
+        .await
     }
 
     /// Connect to lightwallerd endpoint with custom configuration
@@ -98,7 +137,17 @@ impl LightWalletd {
         let channel = channel.connect().await?;
         let client = CompactTxStreamerClient::new(channel);
 
-        Ok(Self { client, config })
+        Ok(Self {
+            client,
+            config,
+            anchors: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Indexer behind the endpoint.
+    #[must_use]
+    pub fn backend(&self) -> Backend {
+        self.config.backend
     }
 
     /// Creates a block range stream with retry logic.
@@ -109,16 +158,7 @@ impl LightWalletd {
     ) -> Result<tonic::Streaming<CompactBlock>, LightWalletdError> {
         retry_with_backoff(config, || {
             let mut client = client.clone();
-            let request = BlockRange {
-                start: Some(BlockId {
-                    height: *range.start(),
-                    hash: vec![],
-                }),
-                end: Some(BlockId {
-                    height: *range.end(),
-                    hash: vec![],
-                }),
-            };
+            let request = block_range(range);
             async move {
                 client
                     .get_block_range(request)
@@ -129,32 +169,56 @@ impl LightWalletd {
         .await
     }
 
+    /// Creates a stream of the blocks in `range` for a nullifier scan, with retry logic.
+    ///
+    /// Zaino serves `GetBlockRangeNullifiers`, which leaves out note commitments and
+    /// ciphertexts; lightwalletd is scanned with the full `GetBlockRange`.
+    async fn get_nullifiers_stream(
+        &self,
+        range: &RangeInclusive<u64>,
+    ) -> Result<tonic::Streaming<CompactBlock>, LightWalletdError> {
+        match self.config.backend {
+            Backend::Lightwalletd => {
+                Self::get_block_range_stream(&self.client, &self.config, range).await
+            }
+            Backend::Zaino => {
+                retry_with_backoff(&self.config, || {
+                    let mut client = self.client.clone();
+                    let request = block_range(range);
+                    async move {
+                        client
+                            .get_block_range_nullifiers(request)
+                            .await
+                            .map(tonic::Response::into_inner)
+                    }
+                })
+                .await
+            }
+        }
+    }
+
     /// Get commitment tree anchor at a specific block height for Sapling and Orchard.
     ///
+    /// Anchors are fetched with `GetTreeState` once per height and cached on this client.
+    ///
     /// # Errors
-    /// todo write errors
+    /// Returns an error if the request fails or a tree state cannot be parsed.
     pub async fn commitment_tree_anchors(
         &self,
         height: BlockHeight,
     ) -> Result<CommitmentTreeAnchors, LightWalletdError> {
-        let request = BlockId {
-            height: height.into(),
-            hash: vec![],
-        };
-
-        let response = retry_with_backoff(&self.config, || {
-            let mut client = self.client.clone();
-            let request = request.clone();
-            async move {
-                client
-                    .get_tree_state(request)
-                    .await
-                    .map(tonic::Response::into_inner)
-            }
-        })
-        .await?;
+        let height = u64::from(height);
+        if let Some(anchors) = self
+            .anchors
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(&height).copied())
+        {
+            return Ok(anchors);
+        }
 
-        Ok(CommitmentTreeAnchors {
+        let response = self.get_tree_state(height).await?;
+        let anchors = CommitmentTreeAnchors {
             sapling: response
                 .sapling_tree()
                 .map_err(|e| LightWalletdError::CommitRootToString(format!("Sapling: {e}")))?
@@ -165,7 +229,69 @@ impl LightWalletd {
                 .map_err(|e| LightWalletdError::CommitRootToString(format!("Orchard: {e}")))?
                 .root()
                 .to_bytes(),
+        };
+        if let Ok(mut cache) = self.anchors.lock() {
+            cache.insert(height, anchors);
+        }
+        Ok(anchors)
+    }
+
+    /// Get the Sapling note commitment tree anchor at a block height.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the tree state cannot be parsed.
+    pub async fn get_sapling_anchor(
+        &self,
+        height: BlockHeight,
+    ) -> Result<[u8; 32], LightWalletdError> {
+        Ok(self.commitment_tree_anchors(height).await?.sapling)
+    }
+
+    /// Get the Orchard note commitment tree anchor at a block height.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the tree state cannot be parsed.
+    pub async fn get_orchard_anchor(
+        &self,
+        height: BlockHeight,
+    ) -> Result<[u8; 32], LightWalletdError> {
+        Ok(self.commitment_tree_anchors(height).await?.orchard)
+    }
+
+    /// Transparent outputs of `addresses` mined at or below `max_height` and unspent at the tip.
+    ///
+    /// `GetAddressUtxos` only serves the current UTXO set, so an output spent between
+    /// `max_height` and the tip is missing from the result.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or an output cannot be parsed.
+    pub async fn address_utxos(
+        &self,
+        addresses: &[String],
+        max_height: u64,
+    ) -> Result<Vec<TransparentUtxo>, LightWalletdError> {
+        let request = GetAddressUtxosArg {
+            addresses: addresses.to_vec(),
+            start_height: 0,
+            max_entries: 0,
+        };
+        let reply = retry_with_backoff(&self.config, || {
+            let mut client = self.client.clone();
+            let request = request.clone();
+            async move {
+                client
+                    .get_address_utxos(request)
+                    .await
+                    .map(tonic::Response::into_inner)
+            }
         })
+        .await?;
+        reply
+            .address_utxos
+            .into_iter()
+            .filter(|utxo| utxo.height <= max_height)
+            .map(transparent_utxo)
+            .collect()
     }
 
     /// Scan blocks using a channel-based approach that keeps non-Send types off async tasks.
@@ -189,10 +315,37 @@ impl LightWalletd {
     pub async fn scan_blocks_spawned<V: ScanVisitor + Send + 'static>(
         &self,
         ufvk: zcash_keys::keys::UnifiedFullViewingKey,
-        network: zcash_protocol::consensus::Network,
+        network: crate::Network,
         visitor: V,
         range: &RangeInclusive<u64>,
         initial_metadata: Option<BlockMetadata>,
+    ) -> Result<(V, Option<BlockMetadata>), LightWalletdError> {
+        self.scan_blocks_spawned_with_progress(
+            ufvk,
+            network,
+            visitor,
+            range,
+            initial_metadata,
+            |_, _, _| {},
+        )
+        .await
+    }
+
+    /// Scan blocks like [`Self::scan_blocks_spawned`], with progress callback.
+    ///
+    /// Calls `on_progress(height, scanned, total)` from the scanner task after each block is
+    /// scanned.
+    ///
+    /// # Errors
+    /// Returns an error if block fetching or scanning fails
+    pub async fn scan_blocks_spawned_with_progress<V: ScanVisitor + Send + 'static>(
+        &self,
+        ufvk: zcash_keys::keys::UnifiedFullViewingKey,
+        network: crate::Network,
+        visitor: V,
+        range: &RangeInclusive<u64>,
+        initial_metadata: Option<BlockMetadata>,
+        mut on_progress: impl FnMut(u64, usize, usize) + Send + 'static,
     ) -> Result<(V, Option<BlockMetadata>), LightWalletdError> {
         const CHANNEL_BUFFER_SIZE: usize = 100;
 
@@ -201,6 +354,8 @@ impl LightWalletd {
         let client = self.client.clone();
         let config = self.config.clone();
         let range_clone = range.clone();
+        let total_blocks_u64 = range.end().saturating_sub(*range.start()).saturating_add(1);
+        let total_blocks = usize::try_from(total_blocks_u64).unwrap_or(usize::MAX);
 
         let fetcher_handle = tokio::spawn(async move {
             let mut stream = Self::get_block_range_stream(&client, &config, &range_clone).await?;
@@ -218,10 +373,16 @@ impl LightWalletd {
             let scanner = BlockScanner::from_ufvk(ufvk, network);
             let mut visitor = visitor;
             let mut prior_metadata = initial_metadata;
+            let mut scanned_blocks = 0usize;
 
             while let Some(block) = rx.blocking_recv() {
+                let height = block.height;
                 let metadata = scanner.scan_block(block, &mut visitor, prior_metadata.as_ref())?;
                 prior_metadata = Some(metadata);
+                scanned_blocks = scanned_blocks.saturating_add(1);
+                #[cfg(feature = "metrics")]
+                metrics::counter!(SCANNED_BLOCKS_METRIC, "scan" => "notes").increment(1);
+                on_progress(height, scanned_blocks, total_blocks);
             }
 
             Ok::<_, LightWalletdError>((visitor, prior_metadata))
@@ -238,15 +399,19 @@ impl LightWalletd {
 
     /// Get tree state at a height
     ///
+    /// The returned state must be for the requested height, so an indexer that answers for
+    /// another block (such as its own tip while it is still syncing) is not mistaken for the
+    /// snapshot anchor.
+    ///
     /// # Errors
-    /// Returns an error if the request fails
+    /// Returns an error if the request fails or the tree state is for another height
     pub async fn get_tree_state(&self, height: u64) -> Result<TreeState, LightWalletdError> {
         let request = BlockId {
             height,
             hash: vec![],
         };
 
-        retry_with_backoff(&self.config, || {
+        let tree_state = retry_with_backoff(&self.config, || {
             let mut client = self.client.clone();
             let request = request.clone();
             async move {
@@ -256,7 +421,15 @@ impl LightWalletd {
                     .map(tonic::Response::into_inner)
             }
         })
-        .await
+        .await?;
+        if tree_state.height != height {
+            return Err(LightWalletdError::TreeStateHeight {
+                requested: height,
+                returned: tree_state.height,
+                backend: self.config.backend,
+            });
+        }
+        Ok(tree_state)
     }
 
     /// Scan blocks for nullifiers only (no decryption needed)
@@ -284,7 +457,7 @@ impl LightWalletd {
         range: &RangeInclusive<u64>,
         mut on_progress: impl FnMut(u64, usize, usize),
     ) -> Result<(), LightWalletdError> {
-        let mut stream = Self::get_block_range_stream(&self.client, &self.config, range).await?;
+        let mut stream = self.get_nullifiers_stream(range).await?;
         let total_blocks_u64 = range.end().saturating_sub(*range.start()).saturating_add(1);
         let total_blocks = usize::try_from(total_blocks_u64).unwrap_or(usize::MAX);
         let mut scanned_blocks = 0usize;
@@ -294,11 +467,119 @@ impl LightWalletd {
         {
             extract_nullifiers(&block, visitor);
             scanned_blocks = scanned_blocks.saturating_add(1);
+            #[cfg(feature = "metrics")]
+            metrics::counter!(SCANNED_BLOCKS_METRIC, "scan" => "nullifiers").increment(1);
             on_progress(block.height, scanned_blocks, total_blocks);
         }
 
         Ok(())
     }
+
+    /// Height of the chain tip.
+    ///
+    /// # Errors
+    /// Returns an error if the latest block cannot be fetched.
+    pub async fn latest_height(&self) -> Result<u64, LightWalletdError> {
+        let tip = retry_with_backoff(&self.config, || {
+            let mut client = self.client.clone();
+            async move {
+                client
+                    .get_latest_block(ChainSpec {})
+                    .await
+                    .map(tonic::Response::into_inner)
+            }
+        })
+        .await?;
+        Ok(tip.height)
+    }
+
+    /// Nullifiers spent by the transactions waiting in the mempool.
+    ///
+    /// Reads `GetMempoolStream`, which sends the current mempool and then each new transaction
+    /// until the next block. The read stops at that block, or once no transaction has arrived for
+    /// two seconds. Transactions that cannot be parsed are skipped.
+    ///
+    /// # Errors
+    /// Returns an error if the chain tip or the mempool cannot be fetched.
+    pub async fn mempool_nullifiers(
+        &self,
+        network: crate::Network,
+    ) -> Result<Vec<PoolNullifier>, LightWalletdError> {
+        let tip = self.latest_height().await?;
+        // Mempool transactions are parsed for the next block; v4 transactions do not encode it.
+        let next_height = u32::try_from(tip.saturating_add(1))?;
+        let branch_id = BranchId::for_height(&network, BlockHeight::from_u32(next_height));
+
+        let mut stream = retry_with_backoff(&self.config, || {
+            let mut client = self.client.clone();
+            async move {
+                client
+                    .get_mempool_stream(Empty {})
+                    .await
+                    .map(tonic::Response::into_inner)
+            }
+        })
+        .await?;
+
+        let idle_timeout = Duration::from_millis(MEMPOOL_IDLE_TIMEOUT_MS);
+        let mut nullifiers = Vec::new();
+        while let Ok(Some(raw)) = tokio::time::timeout(idle_timeout, stream.next()).await {
+            match Transaction::read(raw?.data.as_slice(), branch_id) {
+                Ok(tx) => nullifiers.extend(transaction_nullifiers(&tx)),
+                Err(e) => warn!(error = %e, "Skipping mempool transaction that cannot be parsed"),
+            }
+        }
+        Ok(nullifiers)
+    }
+}
+
+fn transparent_utxo(reply: GetAddressUtxosReply) -> Result<TransparentUtxo, LightWalletdError> {
+    let invalid = |reason| LightWalletdError::InvalidUtxo {
+        address: reply.address.clone(),
+        reason,
+    };
+    Ok(TransparentUtxo {
+        txid: reply
+            .txid
+            .as_slice()
+            .try_into()
+            .map_err(|_| invalid("txid is not 32 bytes"))?,
+        index: u32::try_from(reply.index).map_err(|_| invalid("negative output index"))?,
+        value: u64::try_from(reply.value_zat).map_err(|_| invalid("negative value"))?,
+        height: reply.height,
+        address: reply.address,
+    })
+}
+
+/// Sapling and Orchard nullifiers revealed by `tx`.
+fn transaction_nullifiers(tx: &Transaction) -> impl Iterator<Item = PoolNullifier> + '_ {
+    let sapling = tx.sapling_bundle().into_iter().flat_map(|bundle| {
+        bundle.shielded_spends().iter().map(|spend| PoolNullifier {
+            pool: Pool::Sapling,
+            nullifier: Nullifier::from(&spend.nullifier().0),
+        })
+    });
+    let orchard = tx.orchard_bundle().into_iter().flat_map(|bundle| {
+        bundle.actions().iter().map(|action| PoolNullifier {
+            pool: Pool::Orchard,
+            nullifier: Nullifier::from(action.nullifier().to_bytes()),
+        })
+    });
+    sapling.chain(orchard)
+}
+
+/// Request for the blocks in `range`, identified by height only.
+const fn block_range(range: &RangeInclusive<u64>) -> BlockRange {
+    BlockRange {
+        start: Some(BlockId {
+            height: *range.start(),
+            hash: vec![],
+        }),
+        end: Some(BlockId {
+            height: *range.end(),
+            hash: vec![],
+        }),
+    }
 }
 
 /// Receives the next block from a stream with timeout.
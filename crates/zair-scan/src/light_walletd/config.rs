@@ -1,3 +1,4 @@
+use std::fmt;
 use std::time::Duration;
 
 use thiserror::Error;
@@ -20,6 +21,40 @@ pub enum ConfigError {
     BackoffFactorTooLow,
 }
 
+/// Indexer serving the `CompactTxStreamer` endpoint.
+///
+/// Zaino implements the lightwalletd gRPC protocol, so both are reached through [`LightWalletd`];
+/// the backend only selects the requests where the implementations diverge.
+///
+/// [`LightWalletd`]: crate::light_walletd::LightWalletd
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// lightwalletd. Nullifier scans stream full compact blocks with `GetBlockRange`, which
+    /// every lightwalletd release serves.
+    #[default]
+    Lightwalletd,
+    /// Zaino. Nullifier scans use `GetBlockRangeNullifiers`, which Zaino serves from its own
+    /// index with compact blocks reduced to their nullifiers.
+    Zaino,
+}
+
+impl Backend {
+    /// Name of the backend, as accepted by `--backend`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lightwalletd => "lightwalletd",
+            Self::Zaino => "zaino",
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Configuration for `LightWalletd` connection and retry behavior
 #[derive(Debug, Clone)]
 pub struct LightWalletdConfig {
@@ -37,6 +72,8 @@ pub struct LightWalletdConfig {
     pub backoff_factor: u32,
     /// Timeout for receiving stream messages. (Minimum: 1 second)
     pub stream_message_timeout: Duration,
+    /// Indexer behind the endpoint
+    pub backend: Backend,
 }
 
 /// Validated Configuration for `LightWalletd`
@@ -65,6 +102,7 @@ impl Default for LightWalletdConfig {
             max_retry_delay: Duration::from_millis(MAX_RETRY_DELAY_MS),
             backoff_factor: BACKOFF_FACTOR,
             stream_message_timeout: Duration::from_secs(STREAM_MESSAGE_TIMEOUT_SECS),
+            backend: Backend::Lightwalletd,
         }
     }
 }
@@ -89,9 +127,16 @@ impl LightWalletdConfig {
             max_retry_delay,
             backoff_factor,
             stream_message_timeout,
+            backend: Backend::Lightwalletd,
         }
     }
 
+    /// Sets the indexer behind the endpoint.
+    #[must_use]
+    pub const fn with_backend(self, backend: Backend) -> Self {
+        Self { backend, ..self }
+    }
+
     /// Validates the configuration parameters.
     ///
     /// # Errors
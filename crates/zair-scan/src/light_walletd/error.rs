@@ -1,4 +1,4 @@
-use crate::light_walletd::config::ConfigError;
+use crate::light_walletd::config::{Backend, ConfigError};
 
 /// Errors that can occur when interacting with lightwalletd
 #[derive(Debug, thiserror::Error)]
@@ -48,6 +48,24 @@ pub enum LightWalletdError {
     /// Configuration error
     #[error("Configuration error: {0}")]
     Configuration(#[from] ConfigError),
+    /// Tree state returned for another height than requested
+    #[error("{backend} returned the tree state at height {returned} for height {requested}")]
+    TreeStateHeight {
+        /// The requested block height
+        requested: u64,
+        /// The block height of the returned tree state
+        returned: u64,
+        /// The indexer behind the endpoint
+        backend: Backend,
+    },
+    /// Transparent output that cannot be parsed
+    #[error("Invalid transparent output of {address}: {reason}")]
+    InvalidUtxo {
+        /// The address the output pays to
+        address: String,
+        /// What is wrong with the output
+        reason: &'static str,
+    },
     /// Failed to convert commit root to string
     #[error("Failed to convert commit root to string, {0}")]
     CommitRootToString(String),
@@ -0,0 +1,286 @@
+//! Zcash networks supported by the scanner and the tools built on it.
+//!
+//! `zcash_protocol`'s [`Network`](zcash_protocol::consensus::Network) only knows mainnet and
+//! testnet. This type adds a local regtest network, so integration environments can run the real
+//! pipeline against a local node, and private devnets with their own activation heights.
+
+use thiserror::Error;
+use zair_core::schema::config::{AirdropConfiguration, AirdropNetwork};
+use zcash_protocol::consensus::{self, BlockHeight, NetworkType, NetworkUpgrade, Parameters};
+
+/// Height at which every network upgrade activates on regtest.
+pub const REGTEST_ACTIVATION_HEIGHT: u32 = 1;
+
+/// Network upgrades a custom network can activate, in activation order, with their consensus
+/// branch IDs from [ZIP 200](https://zips.z.cash/zip-0200).
+const UPGRADES: [(NetworkUpgrade, u32); 7] = [
+    (NetworkUpgrade::Overwinter, 0x5ba8_1b19),
+    (NetworkUpgrade::Sapling, 0x76b8_09bb),
+    (NetworkUpgrade::Blossom, 0x2bb4_0e60),
+    (NetworkUpgrade::Heartwood, 0xf5b9_230b),
+    (NetworkUpgrade::Canopy, 0xe9ff_75a6),
+    (NetworkUpgrade::Nu5, 0xc2d6_d0b4),
+    (NetworkUpgrade::Nu6, 0xc8e7_1055),
+];
+
+/// A Zcash network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Network {
+    /// Zcash mainnet.
+    MainNetwork,
+    /// Zcash testnet.
+    TestNetwork,
+    /// Local regtest network with every upgrade active from [`REGTEST_ACTIVATION_HEIGHT`].
+    RegtestNetwork,
+    /// Private devnet with custom activation heights and regtest encodings.
+    Custom(CustomNetwork),
+}
+
+impl Network {
+    /// Network of an airdrop configuration, including the activation heights of a custom devnet.
+    ///
+    /// # Errors
+    /// Returns an error if custom activation heights are set on a standard network or are invalid.
+    pub fn from_configuration(config: &AirdropConfiguration) -> Result<Self, CustomNetworkError> {
+        match (config.network, &config.network_upgrades) {
+            (AirdropNetwork::Mainnet, None) => Ok(Self::MainNetwork),
+            (AirdropNetwork::Testnet, None) => Ok(Self::TestNetwork),
+            (AirdropNetwork::Regtest, None) => Ok(Self::RegtestNetwork),
+            (AirdropNetwork::Regtest, Some(upgrades)) => {
+                let activations = upgrades
+                    .iter()
+                    .map(|(branch_id, height)| Ok((parse_branch_id(branch_id)?, *height)))
+                    .collect::<Result<Vec<_>, CustomNetworkError>>()?;
+                CustomNetwork::new(activations).map(Self::Custom)
+            }
+            (AirdropNetwork::Mainnet | AirdropNetwork::Testnet, Some(_)) => {
+                Err(CustomNetworkError::NotRegtest)
+            }
+        }
+    }
+}
+
+impl Parameters for Network {
+    fn network_type(&self) -> NetworkType {
+        match self {
+            Self::MainNetwork => NetworkType::Main,
+            Self::TestNetwork => NetworkType::Test,
+            Self::RegtestNetwork | Self::Custom(_) => NetworkType::Regtest,
+        }
+    }
+
+    fn activation_height(&self, nu: NetworkUpgrade) -> Option<BlockHeight> {
+        match self {
+            Self::MainNetwork => consensus::MAIN_NETWORK.activation_height(nu),
+            Self::TestNetwork => consensus::TEST_NETWORK.activation_height(nu),
+            Self::RegtestNetwork => Some(BlockHeight::from_u32(REGTEST_ACTIVATION_HEIGHT)),
+            Self::Custom(custom) => custom.activation_height(nu),
+        }
+    }
+}
+
+/// An invalid set of custom network upgrade activations.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CustomNetworkError {
+    /// Custom activation heights are set on mainnet or testnet.
+    #[error("custom network upgrades are only supported on regtest")]
+    NotRegtest,
+    /// A branch ID is not 8 hex digits.
+    #[error("invalid consensus branch ID {0:?}, expected 8 hex digits")]
+    InvalidBranchId(String),
+    /// A branch ID does not belong to a supported network upgrade.
+    #[error("unknown consensus branch ID {0:08x}")]
+    UnknownBranchId(u32),
+    /// NU5 is not activated, so the network has no Orchard pool.
+    #[error("custom networks must activate NU5")]
+    MissingNu5,
+    /// An upgrade activates before one that precedes it.
+    #[error("{later} activates before {earlier}")]
+    OutOfOrder {
+        /// The preceding upgrade.
+        earlier: NetworkUpgrade,
+        /// The upgrade with the lower activation height.
+        later: NetworkUpgrade,
+    },
+}
+
+/// Activation heights of a private devnet.
+///
+/// Activations are given by consensus branch ID, as with zcashd's `-nuparams`. Upgrades up to NU5
+/// that are not listed activate together with the next listed upgrade; NU6 activates only if
+/// listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomNetwork {
+    heights: [Option<u32>; UPGRADES.len()],
+}
+
+impl CustomNetwork {
+    /// Build a custom network from `(branch ID, activation height)` pairs.
+    ///
+    /// # Errors
+    /// Returns an error if a branch ID is unknown, NU5 is not activated or the heights are not
+    /// in upgrade order.
+    pub fn new(
+        activations: impl IntoIterator<Item = (u32, u32)>,
+    ) -> Result<Self, CustomNetworkError> {
+        let mut heights = [None; UPGRADES.len()];
+        for (branch_id, height) in activations {
+            let slot = UPGRADES
+                .iter()
+                .position(|(_, id)| *id == branch_id)
+                .and_then(|index| heights.get_mut(index))
+                .ok_or(CustomNetworkError::UnknownBranchId(branch_id))?;
+            *slot = Some(height);
+        }
+
+        let mut previous: Option<(NetworkUpgrade, u32)> = None;
+        for ((nu, _), height) in UPGRADES.iter().zip(&heights) {
+            let Some(height) = *height else { continue };
+            match previous {
+                Some((earlier, earlier_height)) if height < earlier_height => {
+                    return Err(CustomNetworkError::OutOfOrder {
+                        earlier,
+                        later: *nu,
+                    });
+                }
+                _ => previous = Some((*nu, height)),
+            }
+        }
+
+        // Walking from the last upgrade back, the upgrades visited after NU5 precede it.
+        let mut next = None;
+        let mut before_nu5 = false;
+        for ((nu, _), height) in UPGRADES.iter().zip(heights.iter_mut()).rev() {
+            if before_nu5 && height.is_none() {
+                *height = next;
+            }
+            if height.is_some() {
+                next = *height;
+            }
+            before_nu5 |= *nu == NetworkUpgrade::Nu5;
+        }
+
+        let network = Self { heights };
+        if network.activation_height(NetworkUpgrade::Nu5).is_none() {
+            return Err(CustomNetworkError::MissingNu5);
+        }
+        Ok(network)
+    }
+
+    /// Activated upgrades as `(branch ID, activation height)` pairs, in upgrade order.
+    pub fn activations(&self) -> impl Iterator<Item = (u32, u32)> {
+        UPGRADES
+            .iter()
+            .zip(&self.heights)
+            .filter_map(|((_, branch_id), height)| height.map(|height| (*branch_id, height)))
+    }
+
+    /// Activation height of `nu`, if the network activates it.
+    #[must_use]
+    pub fn activation_height(&self, nu: NetworkUpgrade) -> Option<BlockHeight> {
+        UPGRADES
+            .iter()
+            .zip(&self.heights)
+            .find(|((upgrade, _), _)| *upgrade == nu)
+            .and_then(|(_, height)| *height)
+            .map(BlockHeight::from_u32)
+    }
+}
+
+/// Parse a consensus branch ID written as 8 hex digits, e.g. `c2d6d0b4`.
+///
+/// # Errors
+/// Returns an error if `s` is not 8 hex digits.
+pub fn parse_branch_id(s: &str) -> Result<u32, CustomNetworkError> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    if digits.len() != 8 {
+        return Err(CustomNetworkError::InvalidBranchId(s.to_owned()));
+    }
+    u32::from_str_radix(digits, 16).map_err(|_| CustomNetworkError::InvalidBranchId(s.to_owned()))
+}
+
+impl From<consensus::Network> for Network {
+    fn from(network: consensus::Network) -> Self {
+        match network {
+            consensus::Network::MainNetwork => Self::MainNetwork,
+            consensus::Network::TestNetwork => Self::TestNetwork,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zcash_protocol::consensus::NetworkConstants as _;
+
+    use super::*;
+
+    #[test]
+    fn regtest_activates_everything_early_and_uses_regtest_encodings() {
+        let network = Network::RegtestNetwork;
+        assert!(network.is_nu_active(NetworkUpgrade::Nu5, BlockHeight::from_u32(1)));
+        assert_eq!(network.coin_type(), 1);
+        assert_eq!(network.hrp_sapling_payment_address(), "zregtestsapling");
+    }
+
+    #[test]
+    fn standard_networks_match_zcash_protocol() {
+        for network in [
+            consensus::Network::MainNetwork,
+            consensus::Network::TestNetwork,
+        ] {
+            let ours = Network::from(network);
+            assert_eq!(ours.network_type(), network.network_type());
+            assert_eq!(
+                ours.activation_height(NetworkUpgrade::Sapling),
+                network.activation_height(NetworkUpgrade::Sapling)
+            );
+        }
+    }
+
+    #[test]
+    fn custom_network_fills_skipped_upgrades() {
+        let sapling = parse_branch_id("76b809bb").expect("valid branch ID");
+        let nu5 = parse_branch_id("0xc2d6d0b4").expect("valid branch ID");
+        let custom = CustomNetwork::new([(sapling, 10), (nu5, 50)]).expect("valid activations");
+        let network = Network::Custom(custom);
+
+        assert_eq!(
+            network.activation_height(NetworkUpgrade::Overwinter),
+            Some(BlockHeight::from_u32(10))
+        );
+        assert_eq!(
+            network.activation_height(NetworkUpgrade::Canopy),
+            Some(BlockHeight::from_u32(50))
+        );
+        assert_eq!(network.activation_height(NetworkUpgrade::Nu6), None);
+        assert_eq!(custom.activations().count(), 6);
+        assert_eq!(
+            CustomNetwork::new(custom.activations()),
+            Ok(custom),
+            "Listed activations rebuild the same network"
+        );
+    }
+
+    #[test]
+    fn custom_network_rejects_invalid_activations() {
+        assert_eq!(
+            parse_branch_id("c2d6d0"),
+            Err(CustomNetworkError::InvalidBranchId("c2d6d0".to_owned()))
+        );
+        assert_eq!(
+            CustomNetwork::new([(0xdead_beef, 1)]),
+            Err(CustomNetworkError::UnknownBranchId(0xdead_beef))
+        );
+        assert_eq!(
+            CustomNetwork::new([(0x76b8_09bb, 10)]),
+            Err(CustomNetworkError::MissingNu5)
+        );
+        assert_eq!(
+            CustomNetwork::new([(0x76b8_09bb, 100), (0xc2d6_d0b4, 50)]),
+            Err(CustomNetworkError::OutOfOrder {
+                earlier: NetworkUpgrade::Sapling,
+                later: NetworkUpgrade::Nu5,
+            })
+        );
+    }
+}
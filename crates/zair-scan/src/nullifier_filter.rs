@@ -0,0 +1,247 @@
+//! Bloom filter over snapshot nullifiers.
+//!
+//! A filter answers "is this nullifier possibly in the snapshot?" from a file of about 4% of the
+//! snapshot size. It never rules out a nullifier that is present, so a negative answer lets a
+//! caller skip the search of the full snapshot.
+//!
+//! Nullifiers are PRF outputs, so the bit positions are taken from their bytes directly instead of
+//! hashing them again.
+//!
+//! Layout (all integers little-endian):
+//!
+//! | Bytes | Field                         |
+//! |-------|-------------------------------|
+//! | 8     | magic `ZAIRBLM1`              |
+//! | 8     | number of filtered nullifiers |
+//! | 8     | bit positions per nullifier   |
+//! | 8 × w | filter bits as 64-bit words   |
+
+use std::io::{Error, ErrorKind};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use zair_core::base::Nullifier;
+
+/// Magic prefix of a nullifier filter file.
+pub const NULLIFIER_FILTER_MAGIC: [u8; 8] = *b"ZAIRBLM1";
+
+/// Filter bits per nullifier; with [`HASHES`] positions this gives about 1% false positives.
+const BITS_PER_NULLIFIER: u64 = 10;
+/// Bit positions set per nullifier.
+const HASHES: u64 = 7;
+/// Upper bound on the positions per nullifier accepted when reading a filter.
+const MAX_HASHES: u64 = 32;
+const HEADER_SIZE: usize = 24;
+
+/// Bloom filter over the nullifiers of one snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NullifierFilter {
+    count: u64,
+    hashes: u64,
+    words: Vec<u64>,
+}
+
+impl NullifierFilter {
+    /// Build the filter of `nullifiers`.
+    #[must_use]
+    pub fn build(nullifiers: &[Nullifier]) -> Self {
+        let count = u64::try_from(nullifiers.len()).unwrap_or(u64::MAX);
+        let word_count = count.saturating_mul(BITS_PER_NULLIFIER).div_ceil(64).max(1);
+        let mut filter = Self {
+            count,
+            hashes: HASHES,
+            words: vec![0; usize::try_from(word_count).expect("Filter fits in memory")],
+        };
+        let bits = filter.bits();
+        for nullifier in nullifiers {
+            for position in positions(nullifier, HASHES, bits) {
+                let (word, mask) = word_and_mask(position);
+                if let Some(word) = filter.words.get_mut(word) {
+                    *word |= mask;
+                }
+            }
+        }
+        filter
+    }
+
+    /// Number of nullifiers the filter was built from.
+    #[must_use]
+    pub const fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns `false` if `nullifier` is certainly not in the filtered set.
+    #[must_use]
+    pub fn may_contain(&self, nullifier: &Nullifier) -> bool {
+        positions(nullifier, self.hashes, self.bits()).all(|position| {
+            let (word, mask) = word_and_mask(position);
+            self.words.get(word).is_some_and(|word| word & mask != 0)
+        })
+    }
+
+    fn bits(&self) -> u64 {
+        u64::try_from(self.words.len())
+            .unwrap_or(u64::MAX)
+            .saturating_mul(64)
+    }
+}
+
+/// Bit positions of `nullifier`, by double hashing on two 64-bit words of its bytes.
+fn positions(nullifier: &Nullifier, hashes: u64, bits: u64) -> impl Iterator<Item = u64> {
+    let (first, rest) = nullifier
+        .split_first_chunk::<8>()
+        .expect("Nullifiers are 32 bytes");
+    let (second, _) = rest
+        .split_first_chunk::<8>()
+        .expect("Nullifiers are 32 bytes");
+    let first = u64::from_le_bytes(*first);
+    // A zero step would set the same position for every hash.
+    let step = u64::from_le_bytes(*second) | 1;
+    (0..hashes).map(move |i| {
+        first
+            .wrapping_add(i.wrapping_mul(step))
+            .checked_rem(bits)
+            .expect("Filters have at least one word")
+    })
+}
+
+#[allow(
+    clippy::arithmetic_side_effects,
+    reason = "Division and remainder by the non-zero word size"
+)]
+fn word_and_mask(position: u64) -> (usize, u64) {
+    let word = usize::try_from(position / 64).unwrap_or(usize::MAX);
+    let bit = u32::try_from(position % 64).expect("Bit index is below 64");
+    (word, 1_u64.rotate_left(bit))
+}
+
+/// Write a nullifier filter to an async writer.
+///
+/// # Errors
+/// If write fails
+pub async fn write_nullifier_filter(
+    filter: &NullifierFilter,
+    mut writer: impl AsyncWriteExt + Unpin,
+) -> std::io::Result<()> {
+    writer.write_all(&NULLIFIER_FILTER_MAGIC).await?;
+    writer.write_all(&filter.count.to_le_bytes()).await?;
+    writer.write_all(&filter.hashes.to_le_bytes()).await?;
+    for word in &filter.words {
+        writer.write_all(&word.to_le_bytes()).await?;
+    }
+    writer.flush().await?;
+
+    Ok(())
+}
+
+/// Read a nullifier filter from an async reader.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Reading from the file fails
+/// - The magic prefix is missing, the header is invalid or the filter bits are truncated
+pub async fn read_nullifier_filter(
+    mut reader: impl AsyncReadExt + Unpin,
+) -> std::io::Result<NullifierFilter> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).await?;
+
+    let (header, body) = buf
+        .split_at_checked(HEADER_SIZE)
+        .ok_or_else(|| invalid_data("file is too short for a nullifier filter header"))?;
+    let (magic, fields) = header.split_at(NULLIFIER_FILTER_MAGIC.len());
+    if magic != NULLIFIER_FILTER_MAGIC {
+        return Err(invalid_data("file is not a nullifier filter"));
+    }
+    let (count, hashes) = fields.split_at(8);
+    let count = read_u64(count)?;
+    let hashes = read_u64(hashes)?;
+    if !(1..=MAX_HASHES).contains(&hashes) {
+        return Err(invalid_data(format!(
+            "filter uses {hashes} positions per nullifier, expected 1 to {MAX_HASHES}"
+        )));
+    }
+
+    let chunks = body.chunks_exact(8);
+    if body.is_empty() || !chunks.remainder().is_empty() {
+        return Err(invalid_data(format!(
+            "filter has {} bytes of bits which is not a positive multiple of 8",
+            body.len()
+        )));
+    }
+    let words = chunks.map(read_u64).collect::<std::io::Result<_>>()?;
+
+    Ok(NullifierFilter {
+        count,
+        hashes,
+        words,
+    })
+}
+
+fn read_u64(bytes: &[u8]) -> std::io::Result<u64> {
+    bytes.try_into().map(u64::from_le_bytes).map_err(|_| {
+        invalid_data(format!(
+            "expected 8 bytes for an integer, got {}",
+            bytes.len()
+        ))
+    })
+}
+
+fn invalid_data(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Error {
+    Error::new(ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::io::Cursor;
+    use tokio_util::compat::FuturesAsyncReadCompatExt as _;
+
+    use super::*;
+
+    fn random_nullifiers(count: usize) -> Vec<Nullifier> {
+        (0..count).map(|_| Nullifier::new(rand::random())).collect()
+    }
+
+    #[test]
+    fn filter_has_no_false_negatives_and_few_false_positives() {
+        let members = random_nullifiers(2_000);
+        let filter = NullifierFilter::build(&members);
+        assert_eq!(filter.count(), 2_000);
+        assert!(members.iter().all(|nf| filter.may_contain(nf)));
+
+        let false_positives = random_nullifiers(2_000)
+            .iter()
+            .filter(|nf| filter.may_contain(nf))
+            .count();
+        assert!(false_positives < 100, "{false_positives} false positives");
+    }
+
+    #[test]
+    fn empty_filter_contains_nothing() {
+        let filter = NullifierFilter::build(&[]);
+        assert!(!filter.may_contain(&Nullifier::new([7; 32])));
+    }
+
+    #[tokio::test]
+    async fn write_read_roundtrip() {
+        let original = NullifierFilter::build(&random_nullifiers(100));
+
+        let mut writer = Cursor::new(Vec::new()).compat();
+        write_nullifier_filter(&original, &mut writer)
+            .await
+            .expect("Failed to write nullifier filter");
+        let mut buf = writer.into_inner().into_inner();
+
+        let read_back = read_nullifier_filter(Cursor::new(buf.clone()).compat())
+            .await
+            .expect("Failed to read nullifier filter");
+        assert_eq!(read_back, original);
+
+        buf.pop();
+        let result = read_nullifier_filter(Cursor::new(buf).compat()).await;
+        assert!(
+            matches!(result, Err(ref e) if e.kind() == ErrorKind::InvalidData),
+            "Expected InvalidData error, got {result:?}"
+        );
+    }
+}
@@ -10,6 +10,7 @@ pub use error::ScannerError;
 pub use trees::CommitmentTrees;
 pub use visitor::ScanVisitor;
 pub use visitor::account_notes::AccountNotesVisitor;
+pub use visitor::block_nullifiers::{BlockNullifiers, BlockNullifiersVisitor};
 pub use visitor::chain_nullifiers::ChainNullifiersVisitor;
 use zcash_client_backend::proto::compact_formats::CompactBlock;
 
@@ -28,4 +29,6 @@ pub fn extract_nullifiers<V: ScanVisitor>(block: &CompactBlock, visitor: &mut V)
             }
         }
     }
+
+    visitor.on_nullifiers_extracted(block.height);
 }
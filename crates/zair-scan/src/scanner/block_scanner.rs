@@ -6,10 +6,11 @@ use zcash_client_backend::proto::service::TreeState;
 use zcash_client_backend::scanning::{Nullifiers, ScanningKeys, scan_block};
 use zcash_keys::keys::UnifiedFullViewingKey;
 use zcash_primitives::block::BlockHash;
-use zcash_protocol::consensus::{BlockHeight, Network};
+use zcash_protocol::consensus::BlockHeight;
 use zip32::{AccountId, Scope};
 
 use super::{ScanVisitor, ScannerError};
+use crate::Network;
 use crate::user_nullifiers::{FoundNote, NoteMetadata, SaplingNote};
 
 /// Stateless block scanner using visitor pattern
@@ -8,6 +8,7 @@ use zcash_protocol::consensus::BlockHeight;
 use crate::user_nullifiers::{FoundNote, SaplingNote};
 
 pub mod account_notes;
+pub mod block_nullifiers;
 pub mod chain_nullifiers;
 
 /// Visitor trait for processing scan events.
@@ -35,6 +36,9 @@ pub trait ScanVisitor {
     /// Called when an Orchard nullifier is found.
     fn on_orchard_nullifier(&mut self, _nullifier: &[u8; 32]) {}
 
+    /// Called after all nullifiers of a block have been extracted (nullifier-only scans).
+    fn on_nullifiers_extracted(&mut self, _height: u64) {}
+
     /// Called after a block is fully processed.
     fn on_block_scanned(&mut self, _height: BlockHeight, _metadata: &BlockMetadata) {}
 }
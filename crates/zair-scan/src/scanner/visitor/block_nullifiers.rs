@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+
+use crate::Nullifier;
+use crate::scanner::ScanVisitor;
+
+/// Nullifiers revealed in a single block.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BlockNullifiers {
+    /// Sapling nullifiers in block order.
+    pub sapling: Vec<Nullifier>,
+    /// Orchard nullifiers in block order.
+    pub orchard: Vec<Nullifier>,
+}
+
+impl BlockNullifiers {
+    /// Returns `true` if the block revealed no nullifiers.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sapling.is_empty() && self.orchard.is_empty()
+    }
+}
+
+/// Chain nullifier visitor that keeps the height at which each nullifier was revealed.
+#[derive(Default)]
+pub struct BlockNullifiersVisitor {
+    current: BlockNullifiers,
+    blocks: BTreeMap<u64, BlockNullifiers>,
+}
+
+impl BlockNullifiersVisitor {
+    /// Collected nullifiers keyed by block height. Blocks without nullifiers are omitted.
+    #[must_use]
+    pub fn into_blocks(self) -> BTreeMap<u64, BlockNullifiers> {
+        self.blocks
+    }
+}
+
+impl ScanVisitor for BlockNullifiersVisitor {
+    fn on_sapling_nullifier(&mut self, nullifier: &[u8; 32]) {
+        self.current.sapling.push(Nullifier::from(nullifier));
+    }
+
+    fn on_orchard_nullifier(&mut self, nullifier: &[u8; 32]) {
+        self.current.orchard.push(Nullifier::from(nullifier));
+    }
+
+    fn on_nullifiers_extracted(&mut self, height: u64) {
+        if self.current.is_empty() {
+            return;
+        }
+        let block = std::mem::take(&mut self.current);
+        let entry = self.blocks.entry(height).or_default();
+        entry.sapling.extend(block.sapling);
+        entry.orchard.extend(block.orchard);
+    }
+}
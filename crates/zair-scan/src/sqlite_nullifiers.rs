@@ -0,0 +1,232 @@
+//! SQLite nullifier source.
+//!
+//! [`SqliteNullifiers`] reads nullifiers from a database that an existing chain indexer maintains,
+//! instead of scanning compact blocks from lightwalletd. The database needs a `nullifiers` table
+//! (or view) with the columns of [`NULLIFIERS_SCHEMA`]:
+//!
+//! ```sql
+//! CREATE TABLE nullifiers (
+//!     pool TEXT NOT NULL,       -- 'sapling' or 'orchard'
+//!     nullifier BLOB NOT NULL,  -- 32 bytes, in transaction encoding order
+//!     height INTEGER NOT NULL   -- height of the block that revealed the nullifier
+//! );
+//! CREATE INDEX nullifiers_height ON nullifiers (height);
+//! ```
+//!
+//! Nullifier bytes are in the order they appear in transactions and compact blocks, as returned by
+//! lightwalletd. An indexer with a different layout can expose its data through a view with this
+//! name and these columns.
+
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, OpenFlags, params};
+use tokio::sync::mpsc;
+
+use crate::chain_nullifiers::{BoxedNullifierStream, ChainNullifiers, PoolNullifier};
+use crate::{Nullifier, Pool};
+
+/// Schema of the `nullifiers` table read by [`SqliteNullifiers`].
+pub const NULLIFIERS_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS nullifiers (
+    pool TEXT NOT NULL CHECK (pool IN ('sapling', 'orchard')),
+    nullifier BLOB NOT NULL CHECK (length(nullifier) = 32),
+    height INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS nullifiers_height ON nullifiers (height);
+";
+
+/// Nullifiers buffered between the database reader and the stream consumer.
+const CHANNEL_CAPACITY: usize = 4096;
+
+/// Errors that can occur when reading nullifiers from SQLite
+#[derive(Debug, thiserror::Error)]
+pub enum SqliteNullifiersError {
+    /// SQLite error
+    #[error("SQLite: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    /// Unknown pool name
+    #[error("Unknown pool {pool:?} at height {height}; expected 'sapling' or 'orchard'")]
+    UnknownPool {
+        /// The pool name in the database
+        pool: String,
+        /// The block height of the row
+        height: i64,
+    },
+    /// Invalid nullifier length
+    #[error("Invalid nullifier length: expected 32, got {length} bytes. Block height: {height}")]
+    InvalidLength {
+        /// The block height of the row
+        height: i64,
+        /// The invalid length
+        length: usize,
+    },
+    /// Height range exceeds SQLite integers
+    #[error("Height range {0:?} does not fit in SQLite integers")]
+    HeightRange(RangeInclusive<u64>),
+}
+
+/// Nullifier source backed by a SQLite database with the [`NULLIFIERS_SCHEMA`] layout.
+#[derive(Debug, Clone)]
+pub struct SqliteNullifiers {
+    path: PathBuf,
+}
+
+impl SqliteNullifiers {
+    /// Read nullifiers from the database at `path`, which is opened read-only.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Create the database at `path` with the [`NULLIFIERS_SCHEMA`] table if it does not exist.
+    ///
+    /// # Errors
+    /// Returns an error if the database cannot be opened or the schema cannot be created.
+    pub fn create(path: impl Into<PathBuf>) -> Result<Self, SqliteNullifiersError> {
+        let path = path.into();
+        Connection::open(&path)?.execute_batch(NULLIFIERS_SCHEMA)?;
+        Ok(Self { path })
+    }
+
+    /// Path of the database.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl ChainNullifiers for SqliteNullifiers {
+    type Error = SqliteNullifiersError;
+    type Stream = BoxedNullifierStream<SqliteNullifiersError>;
+
+    /// Stream the nullifiers revealed in `range`.
+    ///
+    /// The query runs on a blocking thread. Dropping the stream stops it after the row in flight.
+    fn nullifiers_stream(&self, range: &RangeInclusive<u64>) -> Self::Stream {
+        let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+        let path = self.path.clone();
+        let range = range.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = send_nullifiers(&path, &range, &sender) {
+                let _ = sender.blocking_send(Err(e));
+            }
+        });
+        Box::pin(futures::stream::unfold(
+            receiver,
+            |mut receiver| async move { receiver.recv().await.map(|item| (item, receiver)) },
+        ))
+    }
+}
+
+fn send_nullifiers(
+    path: &Path,
+    range: &RangeInclusive<u64>,
+    sender: &mpsc::Sender<Result<PoolNullifier, SqliteNullifiersError>>,
+) -> Result<(), SqliteNullifiersError> {
+    let start = i64::try_from(*range.start())
+        .map_err(|_| SqliteNullifiersError::HeightRange(range.clone()))?;
+    let end = i64::try_from(*range.end())
+        .map_err(|_| SqliteNullifiersError::HeightRange(range.clone()))?;
+
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut statement = connection
+        .prepare("SELECT pool, nullifier, height FROM nullifiers WHERE height BETWEEN ?1 AND ?2")?;
+    let mut rows = statement.query(params![start, end])?;
+    while let Some(row) = rows.next()? {
+        let pool: String = row.get(0)?;
+        let nullifier: Vec<u8> = row.get(1)?;
+        let height: i64 = row.get(2)?;
+        let pool = match pool.as_str() {
+            "sapling" => Pool::Sapling,
+            "orchard" => Pool::Orchard,
+            _ => return Err(SqliteNullifiersError::UnknownPool { pool, height }),
+        };
+        let nullifier: [u8; 32] =
+            nullifier
+                .as_slice()
+                .try_into()
+                .map_err(|_| SqliteNullifiersError::InvalidLength {
+                    height,
+                    length: nullifier.len(),
+                })?;
+        let item = PoolNullifier {
+            pool,
+            nullifier: Nullifier::from(&nullifier),
+        };
+        if sender.blocking_send(Ok(item)).is_err() {
+            // The stream was dropped.
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt as _;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn nullifiers_in_range_are_streamed() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("nullifiers.sqlite");
+        let source = SqliteNullifiers::create(&path).expect("create database");
+        let connection = Connection::open(&path).expect("open database");
+        for (pool, byte, height) in [
+            ("sapling", 1_u8, 10_i64),
+            ("orchard", 2, 11),
+            ("sapling", 3, 20),
+        ] {
+            connection
+                .execute(
+                    "INSERT INTO nullifiers (pool, nullifier, height) VALUES (?1, ?2, ?3)",
+                    params![pool, [byte; 32].to_vec(), height],
+                )
+                .expect("insert nullifier");
+        }
+
+        let mut nullifiers: Vec<PoolNullifier> = source
+            .nullifiers_stream(&(10..=15))
+            .try_collect()
+            .await
+            .expect("stream nullifiers");
+        nullifiers.sort_by_key(|entry| entry.pool.as_byte());
+        assert_eq!(nullifiers.len(), 2);
+        assert!(
+            matches!(nullifiers.first(), Some(PoolNullifier { pool: Pool::Sapling, nullifier }) if *nullifier == Nullifier::from(&[1; 32]))
+        );
+        assert!(
+            matches!(nullifiers.get(1), Some(PoolNullifier { pool: Pool::Orchard, nullifier }) if *nullifier == Nullifier::from(&[2; 32]))
+        );
+    }
+
+    #[tokio::test]
+    async fn malformed_rows_are_rejected() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("nullifiers.sqlite");
+        let connection = Connection::open(&path).expect("open database");
+        connection
+            .execute_batch(
+                "CREATE TABLE nullifiers (pool TEXT, nullifier BLOB, height INTEGER);
+                 INSERT INTO nullifiers VALUES ('sapling', x'0102', 5);",
+            )
+            .expect("create table");
+
+        let result: Result<Vec<PoolNullifier>, _> = SqliteNullifiers::new(&path)
+            .nullifiers_stream(&(0..=10))
+            .try_collect()
+            .await;
+        assert!(
+            matches!(
+                result,
+                Err(SqliteNullifiersError::InvalidLength {
+                    height: 5,
+                    length: 2
+                })
+            ),
+            "{result:?}"
+        );
+    }
+}
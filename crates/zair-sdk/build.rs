@@ -0,0 +1,97 @@
+//! Embeds the build metadata read by `build_info`: the git commit, the build flags and the hash of
+//! the dependency lockfile. No timestamp is embedded, so rebuilding the same commit with the same
+//! flags gives the same binary.
+
+#![allow(clippy::print_stdout, reason = "Cargo reads build script directives from stdout")]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use sha2::{Digest as _, Sha256};
+
+fn main() {
+    let manifest_dir = PathBuf::from(
+        std::env::var_os("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR"),
+    );
+
+    // Builds outside a git checkout, such as from a source archive, can name the commit.
+    println!("cargo:rerun-if-env-changed=ZAIR_GIT_COMMIT");
+    let git_commit = std::env::var("ZAIR_GIT_COMMIT")
+        .ok()
+        .or_else(|| git(&manifest_dir, &["rev-parse", "HEAD"]))
+        .unwrap_or_default();
+    if let Some(git_dir) = git(&manifest_dir, &["rev-parse", "--absolute-git-dir"]) {
+        let git_dir = PathBuf::from(git_dir);
+        println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+        if let Some(head_ref) = git(&manifest_dir, &["symbolic-ref", "-q", "HEAD"]) {
+            println!(
+                "cargo:rerun-if-changed={}",
+                git_dir.join(head_ref).display()
+            );
+        }
+    }
+
+    let lockfile_sha256 = manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lockfile| lockfile.is_file())
+        .map(|lockfile| {
+            println!("cargo:rerun-if-changed={}", lockfile.display());
+            let bytes = std::fs::read(&lockfile).expect("Cargo.lock should be readable");
+            hex::encode(Sha256::digest(bytes))
+        })
+        .unwrap_or_default();
+
+    let rustc = std::env::var_os("RUSTC")
+        .and_then(|rustc| Command::new(rustc).arg("--version").output().ok())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_owned())
+        .unwrap_or_default();
+    let rustflags = std::env::var("CARGO_ENCODED_RUSTFLAGS")
+        .unwrap_or_default()
+        .split('\u{1f}')
+        .filter(|flag| !flag.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut features = std::env::vars()
+        .filter_map(|(name, _)| {
+            name.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<_>>();
+    features.sort();
+
+    for (name, value) in [
+        ("ZAIR_BUILD_GIT_COMMIT", git_commit),
+        ("ZAIR_BUILD_LOCKFILE_SHA256", lockfile_sha256),
+        ("ZAIR_BUILD_PROFILE", env("PROFILE")),
+        ("ZAIR_BUILD_OPT_LEVEL", env("OPT_LEVEL")),
+        ("ZAIR_BUILD_TARGET", env("TARGET")),
+        ("ZAIR_BUILD_RUSTC", rustc),
+        ("ZAIR_BUILD_RUSTFLAGS", rustflags),
+        ("ZAIR_BUILD_FEATURES", features.join(",")),
+    ] {
+        println!("cargo:rustc-env={name}={value}");
+    }
+}
+
+/// Trimmed output of a successful git command run in `dir`.
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|out| out.trim().to_owned())
+        .filter(|out| !out.is_empty())
+}
+
+fn env(name: &str) -> String {
+    std::env::var(name).unwrap_or_default()
+}
@@ -0,0 +1,324 @@
+//! Hash-chained audit trail of command invocations.
+//!
+//! An organizer passes `--audit-trail <FILE>` to every pipeline command. Each command then appends
+//! one JSON line to the trail with its arguments, its exit status and the SHA-256 of the files it
+//! read and wrote, so the artifacts that fed into a published configuration can be reconstructed
+//! later. Every entry carries the hash of the previous one: editing, removing or reordering
+//! entries breaks the chain, which [`verify_audit_trail`] detects.
+//!
+//! Files are recorded as [`crate::stdio`] reads them and as [`crate::paths`] moves outputs into
+//! place. Stdin and stdout are not recorded, and neither are files read in place, such as
+//! memory-mapped snapshots.
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use sha2::{Digest as _, Sha256};
+use tokio::io::AsyncWriteExt as _;
+
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Domain marker prepended to the entry hash preimage.
+const AUDIT_ENTRY_TAG: &[u8; 8] = b"ZAIR_AUD";
+
+/// Files this process read and wrote.
+static RECORDED_FILES: Mutex<RecordedFiles> = Mutex::new(RecordedFiles {
+    inputs: BTreeSet::new(),
+    outputs: BTreeSet::new(),
+});
+
+struct RecordedFiles {
+    inputs: BTreeSet<PathBuf>,
+    outputs: BTreeSet<PathBuf>,
+}
+
+/// Record that this process read `path`.
+pub(crate) fn record_input(path: &Path) {
+    if stdio::is_stdio(path) {
+        return;
+    }
+    if let Ok(mut files) = RECORDED_FILES.lock() {
+        files.inputs.insert(path.to_path_buf());
+    }
+}
+
+/// Record that this process wrote `path`.
+pub(crate) fn record_output(path: &Path) {
+    if let Ok(mut files) = RECORDED_FILES.lock() {
+        files.outputs.insert(path.to_path_buf());
+    }
+}
+
+/// A file read or written by an audited command.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditedFile {
+    /// Path of the file, as given to the command.
+    pub path: String,
+    /// SHA-256 of the file when the command finished; absent if it could not be read.
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<[u8; 32]>,
+}
+
+/// The hashed part of an audit trail entry.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position of the entry in the trail, from 0.
+    pub sequence: u64,
+    /// Time the command finished, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Version of the SDK that ran the command.
+    pub version: String,
+    /// Command line of the invocation.
+    pub command: Vec<String>,
+    /// Exit status of the command.
+    pub exit_code: i32,
+    /// Files the command read.
+    pub inputs: Vec<AuditedFile>,
+    /// Files the command wrote.
+    pub outputs: Vec<AuditedFile>,
+    /// Hash of the previous entry; all zeros for the first entry.
+    #[serde_as(as = "Hex")]
+    pub previous_hash: [u8; 32],
+}
+
+impl AuditRecord {
+    /// Hash of the entry, chained into the next one.
+    ///
+    /// Preimage layout: `ZAIR_AUD || JSON(record)`.
+    ///
+    /// # Errors
+    /// Returns an error if the record cannot be serialized.
+    pub fn hash(&self) -> serde_json::Result<[u8; 32]> {
+        let mut hasher = Sha256::new();
+        hasher.update(AUDIT_ENTRY_TAG);
+        hasher.update(serde_json::to_vec(self)?);
+        Ok(hasher.finalize().into())
+    }
+}
+
+/// One line of the audit trail.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// The hashed record.
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    /// [`AuditRecord::hash`] of the record.
+    #[serde_as(as = "Hex")]
+    pub hash: [u8; 32],
+}
+
+/// Append an entry for the command line `command`, which exited with `exit_code`, to the audit
+/// trail at `trail_file`, recording the files this process read and wrote.
+///
+/// # Errors
+/// Returns an error if the trail cannot be read or is malformed, or the entry cannot be written.
+pub async fn append_audit_entry(
+    trail_file: PathBuf,
+    command: Vec<String>,
+    exit_code: i32,
+) -> ZairResult<AuditEntry> {
+    append_audit_entry_inner(trail_file, command, exit_code)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+async fn append_audit_entry_inner(
+    trail_file: PathBuf,
+    command: Vec<String>,
+    exit_code: i32,
+) -> eyre::Result<AuditEntry> {
+    let (sequence, previous_hash) = match read_entries(&trail_file).await?.pop() {
+        Some(last) => (
+            last.record
+                .sequence
+                .checked_add(1)
+                .context("Audit trail sequence overflows")?,
+            last.hash,
+        ),
+        None => (0, [0; 32]),
+    };
+    let files = RECORDED_FILES
+        .lock()
+        .map(|mut files| {
+            (
+                std::mem::take(&mut files.inputs),
+                std::mem::take(&mut files.outputs),
+            )
+        })
+        .unwrap_or_default();
+    let (inputs, outputs) =
+        tokio::task::spawn_blocking(move || (audited_files(files.0), audited_files(files.1)))
+            .await?;
+
+    let record = AuditRecord {
+        sequence,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("System clock is before the Unix epoch")?
+            .as_secs(),
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        command,
+        exit_code,
+        inputs,
+        outputs,
+        previous_hash,
+    };
+    let entry = AuditEntry {
+        hash: record.hash()?,
+        record,
+    };
+    let mut line = serde_json::to_vec(&entry)?;
+    line.push(b'\n');
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&trail_file)
+        .await
+        .with_context(|| format!("Failed to open audit trail {}", trail_file.display()))?;
+    file.write_all(&line).await?;
+    file.sync_all().await?;
+    Ok(entry)
+}
+
+/// Check the hash chain of the audit trail at `trail_file` and return its entries.
+///
+/// # Errors
+/// Returns an error if the trail cannot be read, an entry is malformed, out of sequence, or its
+/// hash does not match its contents or the next entry.
+pub async fn verify_audit_trail(trail_file: PathBuf) -> ZairResult<Vec<AuditEntry>> {
+    verify_audit_trail_inner(&trail_file)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+async fn verify_audit_trail_inner(trail_file: &Path) -> eyre::Result<Vec<AuditEntry>> {
+    let entries = read_entries(trail_file).await?;
+    ensure!(
+        !entries.is_empty(),
+        "Audit trail {} holds no entries",
+        trail_file.display()
+    );
+    let mut previous_hash = [0; 32];
+    for (index, entry) in entries.iter().enumerate() {
+        let line = index.saturating_add(1);
+        ensure!(
+            u64::try_from(index).is_ok_and(|index| index == entry.record.sequence),
+            "Audit trail entry on line {line} has sequence {}; entries are missing or reordered",
+            entry.record.sequence
+        );
+        ensure!(
+            entry.record.previous_hash == previous_hash,
+            "Audit trail entry on line {line} does not chain to the previous entry"
+        );
+        ensure!(
+            entry.record.hash()? == entry.hash,
+            "Audit trail entry on line {line} was modified"
+        );
+        previous_hash = entry.hash;
+    }
+    Ok(entries)
+}
+
+async fn read_entries(trail_file: &Path) -> eyre::Result<Vec<AuditEntry>> {
+    let text = match tokio::fs::read_to_string(trail_file).await {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("Failed to read audit trail {}", trail_file.display()));
+        }
+    };
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).with_context(|| {
+                format!(
+                    "Malformed audit trail entry on line {}",
+                    index.saturating_add(1)
+                )
+            })
+        })
+        .collect()
+}
+
+fn audited_files(paths: BTreeSet<PathBuf>) -> Vec<AuditedFile> {
+    paths
+        .into_iter()
+        .map(|path| AuditedFile {
+            sha256: sha256_file(&path).ok(),
+            path: path.display().to_string(),
+        })
+        .collect()
+}
+
+fn sha256_file(path: &Path) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tampering_breaks_the_chain() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let trail = dir.path().join("audit.log");
+        let output = dir.path().join("config.json");
+        std::fs::write(&output, "{}").expect("write output");
+
+        record_output(&output);
+        append_audit_entry(
+            trail.clone(),
+            vec!["zair".to_owned(), "config".to_owned()],
+            0,
+        )
+        .await
+        .expect("first entry");
+        append_audit_entry(
+            trail.clone(),
+            vec!["zair".to_owned(), "claim".to_owned()],
+            5,
+        )
+        .await
+        .expect("second entry");
+
+        let entries = verify_audit_trail(trail.clone())
+            .await
+            .expect("intact trail");
+        assert_eq!(entries.len(), 2);
+        let first = entries.first().expect("first entry");
+        // Tests running in parallel may record their own files as well.
+        assert!(first.record.outputs.contains(&AuditedFile {
+            path: output.display().to_string(),
+            sha256: Some(Sha256::digest(b"{}").into()),
+        }));
+        assert_eq!(
+            entries.last().expect("second entry").record.previous_hash,
+            first.hash
+        );
+
+        let text = std::fs::read_to_string(&trail).expect("read trail");
+        std::fs::write(&trail, text.replace("\"exit_code\":5", "\"exit_code\":0")).expect("tamper");
+        assert!(verify_audit_trail(trail.clone()).await.is_err());
+
+        let second_line = text.lines().nth(1).expect("second line");
+        std::fs::write(&trail, format!("{second_line}\n")).expect("truncate");
+        assert!(verify_audit_trail(trail).await.is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! Build metadata of this binary.
+//!
+//! The build script embeds the git commit, the build flags and the SHA-256 of the `Cargo.lock`
+//! the binary was built with. [`BuildInfo::current`] reads them back for `zair version --json`,
+//! and [`to_artifact_json`] records them as `generated_by` in the JSON artifacts, so a
+//! supply-chain audit can tie every configuration, claim and submission to the source and
+//! dependencies that produced it. `ZAIR_GIT_COMMIT` names the commit of builds outside a git
+//! checkout.
+
+use serde::{Deserialize, Serialize};
+
+/// Name of the field that carries the [`BuildInfo`] of an artifact's writer.
+pub const GENERATED_BY_FIELD: &str = "generated_by";
+
+/// Source, toolchain and flags of a build.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// Version of the SDK.
+    pub version: String,
+    /// Git commit the build was made from, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+    /// SHA-256 of the `Cargo.lock` the build resolved its dependencies with, as hex.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lockfile_sha256: Option<String>,
+    /// Cargo profile, `release` or `debug`.
+    pub profile: String,
+    /// Optimization level.
+    pub opt_level: String,
+    /// Target triple.
+    pub target: String,
+    /// Version of the compiler.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rustc: Option<String>,
+    /// Extra compiler flags, from `RUSTFLAGS` or the Cargo configuration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rustflags: Option<String>,
+    /// Enabled features of the SDK.
+    pub features: Vec<String>,
+}
+
+impl BuildInfo {
+    /// Metadata of the running build.
+    #[must_use]
+    pub fn current() -> Self {
+        let known = |value: &str| (!value.is_empty()).then(|| value.to_owned());
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            git_commit: known(env!("ZAIR_BUILD_GIT_COMMIT")),
+            lockfile_sha256: known(env!("ZAIR_BUILD_LOCKFILE_SHA256")),
+            profile: env!("ZAIR_BUILD_PROFILE").to_owned(),
+            opt_level: env!("ZAIR_BUILD_OPT_LEVEL").to_owned(),
+            target: env!("ZAIR_BUILD_TARGET").to_owned(),
+            rustc: known(env!("ZAIR_BUILD_RUSTC")),
+            rustflags: known(env!("ZAIR_BUILD_RUSTFLAGS")),
+            features: env!("ZAIR_BUILD_FEATURES")
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        }
+    }
+}
+
+/// An artifact with the [`BuildInfo`] of its writer appended.
+#[derive(Serialize)]
+struct Artifact<'a, T> {
+    #[serde(flatten)]
+    artifact: &'a T,
+    generated_by: BuildInfo,
+}
+
+/// Pretty JSON of `artifact` with the [`BuildInfo`] of this build as its `generated_by` field.
+///
+/// Readers ignore the field, so a file rewritten by a later stage names the build that wrote it
+/// last.
+///
+/// # Errors
+/// Returns an error if `artifact` does not serialize to a JSON object.
+pub fn to_artifact_json<T: Serialize>(artifact: &T) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&Artifact {
+        artifact,
+        generated_by: BuildInfo::current(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn artifacts_carry_the_build_info() {
+        #[derive(Serialize, Deserialize)]
+        struct Config {
+            snapshot_height: u64,
+        }
+        #[derive(Deserialize)]
+        struct Generated {
+            generated_by: BuildInfo,
+        }
+
+        let json = to_artifact_json(&Config { snapshot_height: 7 }).expect("artifact serializes");
+        let generated: Generated = serde_json::from_str(&json).expect("build info parses");
+        assert_eq!(generated.generated_by, BuildInfo::current());
+        assert_eq!(generated.generated_by.version, env!("CARGO_PKG_VERSION"));
+
+        let config: Config = serde_json::from_str(&json).expect("readers ignore the build info");
+        assert_eq!(config.snapshot_height, 7);
+        assert!(to_artifact_json(&[1, 2]).is_err());
+    }
+}
@@ -4,37 +4,189 @@
 
 mod airdrop_claim;
 mod airdrop_configuration;
+mod artifact_lock;
+mod artifact_manifest;
+mod benchmark;
+mod chain_height;
+#[cfg(feature = "prove")]
+mod circuit_report;
+mod claim_message;
+mod claim_package;
+mod claim_pools;
+#[cfg(feature = "serve")]
+mod claim_prepare_server;
 mod claim_proofs;
 #[cfg(feature = "prove")]
 mod claim_proofs_prove;
+mod claim_signing_request;
+mod claim_submission_countersign;
 mod claim_submission_sign;
 mod claim_submission_verify;
+mod claim_uri;
+mod claimed_value;
+mod config_audit;
+mod config_signature;
+mod detached_signature;
+mod disk_space;
+mod doctor;
+#[cfg(feature = "serve")]
+mod grpc_ops;
+mod holdings_attestation;
 mod key;
-mod note_metadata;
+mod key_fingerprint;
+#[cfg(feature = "prove")]
+mod note_positions;
 mod nullifier_uniqueness;
+#[cfg(feature = "object-store")]
+mod object_store_files;
 mod orchard_params;
 #[cfg(feature = "prove")]
 mod orchard_setup;
-mod pool_processor;
+#[cfg(feature = "prove")]
+mod proof_rng;
+mod proving_queue;
+mod self_update;
 mod sensitive_output;
+mod setup_fetch;
+mod setup_fingerprint;
 mod signature_digest;
+mod snapshot_export;
+mod snapshot_fetch;
+mod snapshot_publish;
+mod snapshot_reconcile;
+#[cfg(feature = "serve")]
+mod snapshot_server;
+mod snapshot_torrent;
+mod spend_watch;
 mod submission_auth;
+mod submission_dedupe;
 mod submission_messages;
+mod submission_stats;
+mod transparent_claims;
+mod transparent_snapshot;
+mod verification_report;
+#[cfg(feature = "serve")]
+mod verification_server;
+mod webhook;
+#[cfg(feature = "prove")]
+mod witness_check;
 mod workflows;
 
-pub use airdrop_claim::{GapTreeMode, airdrop_claim};
-pub use airdrop_configuration::build_airdrop_configuration;
-pub use claim_proofs::verify_claim_proofs;
+pub use airdrop_claim::{GapTreeMode, PrepareProgress, PrepareProgressFn, airdrop_claim};
+pub(crate) use airdrop_claim::{PoolTreeSource, prepare_claims_inner};
+pub use airdrop_configuration::{NullifierSource, add_airdrop_epoch, build_airdrop_configuration};
+pub use artifact_manifest::{
+    ARTIFACTS_MANIFEST_FILE, ArtifactEntry, ArtifactKind, ArtifactManifest, ArtifactsOutput,
+    fetch_artifacts,
+};
+pub use benchmark::{BenchmarkReport, MachineProfile, TreeTiming, run_benchmark};
+pub use chain_height::{HeightChain, fetch_chain_height};
+#[cfg(feature = "prove")]
+pub use circuit_report::{ConstraintReport, NamespaceCount, sapling_constraint_report};
+pub use claim_message::{CLAIM_MESSAGES_FILE, ClaimRecipients, build_claim_messages};
+pub use claim_package::{
+    PACKAGE_MANIFEST_FILE, PackageEntry, PackageManifest, package_claim, verify_claim_package,
+};
+pub use claim_pools::{PoolEntries, merge_claim_inputs, merge_claim_proofs};
+#[cfg(feature = "serve")]
+pub use claim_prepare_server::serve_claim_prepare;
+pub(crate) use claim_proofs::verify_proofs_inner;
+pub use claim_proofs::{
+    ClaimProofsOutput, ClaimSecretsOutput, FailedClaimProof, OrchardClaimProofResult,
+    OrchardClaimSecretResult, ProofFilter, ProofVerdict, SaplingClaimProofResult,
+    SaplingClaimSecretResult, ValueBlinding, ensure_all_valid, verify_claim_proofs,
+};
+#[cfg(feature = "prove")]
+pub(crate) use claim_proofs_prove::{ProvingKeys, prove_claims_inner};
 #[cfg(feature = "prove")]
 pub use claim_proofs_prove::{generate_claim_params, generate_claim_proofs};
+pub use claim_signing_request::{
+    ClaimSignature, ClaimSignatures, SigningRequest, SigningRequestEntry,
+    assemble_claim_submission, sign_claim_signing_request, signing_request_inner,
+};
+pub use claim_submission_countersign::countersign_claim_submission;
 pub use claim_submission_sign::sign_claim_submission;
+pub(crate) use claim_submission_sign::sign_submission_inner;
 pub use claim_submission_verify::verify_claim_submission_signature;
-pub use key::{MnemonicSource, key_derive_seed, key_derive_ufvk};
+pub(crate) use claim_submission_verify::verify_submission_signatures_inner;
+pub use claim_uri::{ClaimUri, decode_claim_uri, encode_claim_uri};
+pub use claimed_value::{
+    AggregateValueOpening, CLAIMED_VALUE_FILE, ClaimedValueProof, prove_claimed_value,
+    verify_claimed_value,
+};
+pub use config_audit::{audit_airdrop_configuration, audit_configuration};
+pub use config_signature::{
+    ConfigSignature, sign_airdrop_configuration, verify_airdrop_configuration_signature,
+};
+pub use detached_signature::{
+    DetachedSignature, DetachedSignatures, sign_detached_inner, sign_detached_payload,
+    verify_detached_inner, verify_detached_signatures,
+};
+pub use doctor::{CheckStatus, DoctorCheck, run_doctor};
+pub use holdings_attestation::{
+    HOLDINGS_ATTESTATION_FILE, HoldingsAttestation, ValueOpening, attest_claimed_value,
+    attest_holdings, verify_holdings_attestation,
+};
+pub use key::{
+    MnemonicLanguage, MnemonicSource, PassphraseSource, ProvingKeyFile, WalletExportFormat,
+    key_derive_seed, key_derive_ufvk, key_export_proving_key, key_import,
+};
+#[cfg(feature = "object-store")]
+pub use object_store_files::{
+    ObjectStoreUploads, download_object_store_files, is_object_store_url,
+};
 pub use orchard_params::{
     OrchardParamsMode, generate_orchard_params_file, load_or_prepare_orchard_params,
 };
 #[cfg(feature = "prove")]
 pub use orchard_setup::generate_orchard_params;
+#[cfg(feature = "deterministic-rng")]
+pub use proof_rng::DeterministicProofRng;
+#[cfg(feature = "prove")]
+pub use proof_rng::{ClaimRng, OsProofRng, ProofRngSource};
+#[cfg(feature = "prove")]
+pub use proving_queue::run_proving_worker;
+pub use proving_queue::{
+    DONE_DIR, FAILED_DIR, JOB_CLAIMS_FILE, JOB_ERROR_FILE, JOB_PROOFS_FILE, JOB_PROVING_KEY_FILE,
+    JOB_SECRETS_FILE, PENDING_DIR, RUNNING_DIR, collect_proving_job, finished_proving_job,
+    submit_proving_job,
+};
+pub use self_update::{
+    ReleaseBinary, ReleaseManifest, SelfUpdateOutcome, current_target, self_update,
+};
+pub use setup_fetch::fetch_setup_file;
+pub use snapshot_export::{SnapshotExportFormat, export_snapshot};
+pub use snapshot_fetch::{
+    ManifestFile, SNAPSHOT_MANIFEST_PATH, SnapshotArtifact, SnapshotFiles, SnapshotManifest,
+    fetch_snapshot_files,
+};
+pub use snapshot_publish::publish_snapshot_ipfs;
+pub use snapshot_reconcile::{
+    NullifierDivergence, PoolReconciliation, ReconcileSide, SnapshotReconcileReport,
+    SnapshotSource, reconcile_snapshot_sources,
+};
+#[cfg(feature = "serve")]
+pub use snapshot_server::serve_snapshot_files;
+pub use snapshot_torrent::fetch_snapshot_magnets;
+pub use spend_watch::{SpendAlert, watch_claimed_spends};
+pub use submission_dedupe::{
+    ClaimOccurrence, DedupeReport, DuplicateClaim, dedupe_claim_submissions, find_duplicate_claims,
+};
+pub(crate) use submission_messages::ResolvedMessageHashes;
+pub use submission_stats::{
+    PoolStats, SubmissionStats, ValueBucket, ValueDistribution, submission_statistics,
+};
+pub use transparent_claims::{
+    TransparentClaim, TransparentClaims, sign_transparent_claims, sign_transparent_inner,
+    verify_transparent_claims, verify_transparent_inner,
+};
+pub use transparent_snapshot::add_transparent_snapshot;
+pub use verification_report::{PoolEvidence, VERIFICATION_REPORT_FILE, VerificationReport};
+#[cfg(feature = "serve")]
+pub use verification_server::serve_verification;
+pub use webhook::{CommandEvent, CommandOutcome, run_with_notification};
 #[cfg(feature = "prove")]
 pub use workflows::claim_run;
 pub use workflows::verify_run;
+
+pub use crate::seed::AccountSecret;
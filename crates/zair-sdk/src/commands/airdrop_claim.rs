@@ -3,33 +3,45 @@
 //! This module provides the main `airdrop_claim` function that orchestrates
 //! the claim generation process for both Sapling and Orchard pools.
 
-use std::collections::HashMap;
+use std::collections::HashSet;
 use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::str::FromStr as _;
+use std::sync::Arc;
+use std::time::Instant;
 
 use eyre::{Context as _, ensure};
 use http::Uri;
+use serde::Serialize;
 use tokio::fs::File;
 use tokio::io::BufReader;
 use tracing::{debug, info, instrument, warn};
 use zair_core::base::{Nullifier, Pool, SanitiseNullifiers};
 use zair_core::schema::config::AirdropConfiguration;
-use zair_core::schema::proof_inputs::{AirdropClaimInputs, ClaimInput, PublicInputs};
+use zair_core::schema::proof_inputs::AirdropClaimInputs;
 use zair_nonmembership::{
-    MerklePathError, NonMembershipTree, OrchardGapTree, OrchardNonMembershipTree, SaplingGapTree,
-    TreePosition, map_orchard_user_positions, map_sapling_user_positions,
+    MerklePathError, OrchardGapTree, SaplingGapTree, TreePosition, map_orchard_user_positions,
+    map_sapling_user_positions,
 };
-use zair_scan::ViewingKeys;
+use zair_prepare::{
+    OrchardPool, PoolClaimResult, PoolProcessor, SaplingPool, SparsePoolTree, ensure_root_matches,
+    generate_claims, split_spent_nullifiers,
+};
+use zair_scan::chain_nullifiers::PoolNullifier;
 use zair_scan::light_walletd::LightWalletd;
 use zair_scan::scanner::{AccountNotesVisitor, BlockScanner};
+use zair_scan::{Network, NullifierFilter, ViewingKeys};
 use zcash_keys::keys::UnifiedFullViewingKey;
-use zcash_protocol::consensus::Network;
 
-use super::note_metadata::NoteMetadata;
-use super::pool_processor::{OrchardPool, PoolClaimResult, PoolProcessor, SaplingPool};
+use super::artifact_lock::ArtifactLock;
+use super::claim_pools::select_config_pools;
+use super::disk_space::{ensure_space, gap_tree_bytes};
+use super::key_fingerprint::key_fingerprints;
 use super::sensitive_output::write_sensitive_output;
-use crate::common::{resolve_lightwalletd_url, to_zcash_network};
+use crate::build_info::to_artifact_json;
+use crate::common::{PoolSelection, configured_network, resolve_lightwalletd_url};
+use crate::error::{ZairError, ZairResult};
+use crate::{paths, stdio, telemetry};
 /// 1 MiB buffer for file I/O.
 const FILE_BUF_SIZE: usize = 1024 * 1024;
 /// Default Sapling snapshot path used by claim flows.
@@ -63,7 +75,7 @@ fn resolve_snapshot_path_if_enabled(
     }
 
     provided_path.or_else(|| {
-        let path = PathBuf::from(default_path);
+        let path = paths::default_input_path(paths::snapshots_dir, default_path);
         info!(
             file = ?path,
             %pool,
@@ -85,7 +97,7 @@ fn resolve_gap_tree_path_if_enabled(
     }
 
     provided_path.or_else(|| {
-        let path = PathBuf::from(default_path);
+        let path = paths::default_input_path(paths::gap_trees_dir, default_path);
         info!(
             file = ?path,
             %pool,
@@ -103,11 +115,14 @@ fn resolve_gap_tree_path_if_enabled(
 /// for the provided snapshot nullifiers, and generates non-membership proofs
 /// for the user's notes.
 ///
+/// With a single `pool`, only that pool's notes are claimed and only its snapshot and gap-tree
+/// files are needed. With an `epoch`, the claims target that epoch of a phased airdrop and the
+/// snapshot files must be those of the epoch.
+///
 /// # Errors
 /// Returns error if any step in the process fails,
 /// including scanning for notes, loading nullifiers, building Merkle trees,
 /// or generating proofs.
-#[instrument(level = "debug", skip_all)]
 #[allow(
     clippy::too_many_arguments,
     reason = "CLI command entrypoint carries explicit file/path knobs"
@@ -123,47 +138,248 @@ pub async fn airdrop_claim(
     birthday_height: u64,
     airdrop_claims_output_file: PathBuf,
     airdrop_configuration_file: PathBuf,
-) -> eyre::Result<()> {
-    let airdrop_config: AirdropConfiguration =
-        serde_json::from_str(&tokio::fs::read_to_string(airdrop_configuration_file).await?)?;
-    let sapling_snapshot_nullifiers = resolve_snapshot_path_if_enabled(
-        airdrop_config.sapling.is_some(),
+    pool: PoolSelection,
+    epoch: Option<u32>,
+    check_mempool: bool,
+) -> ZairResult<()> {
+    airdrop_claim_inner(
+        lightwalletd_url,
         sapling_snapshot_nullifiers,
-        DEFAULT_SAPLING_SNAPSHOT_FILE,
-        Pool::Sapling,
-    );
-    let orchard_snapshot_nullifiers = resolve_snapshot_path_if_enabled(
-        airdrop_config.orchard.is_some(),
         orchard_snapshot_nullifiers,
-        DEFAULT_ORCHARD_SNAPSHOT_FILE,
-        Pool::Orchard,
-    );
-    let sapling_gap_tree_file = resolve_gap_tree_path_if_enabled(
-        airdrop_config.sapling.is_some(),
         sapling_gap_tree_file,
-        DEFAULT_SAPLING_GAP_TREE_FILE,
-        Pool::Sapling,
-        gap_tree_mode,
-    );
-    let orchard_gap_tree_file = resolve_gap_tree_path_if_enabled(
-        airdrop_config.orchard.is_some(),
         orchard_gap_tree_file,
-        DEFAULT_ORCHARD_GAP_TREE_FILE,
-        Pool::Orchard,
         gap_tree_mode,
-    );
-    validate_pool_inputs(
+        unified_full_viewing_key,
+        birthday_height,
+        airdrop_claims_output_file,
+        airdrop_configuration_file,
+        pool,
+        epoch,
+        check_mempool,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Snapshot))
+}
+
+#[instrument(level = "debug", skip_all)]
+#[allow(
+    clippy::too_many_arguments,
+    reason = "CLI command entrypoint carries explicit file/path knobs"
+)]
+async fn airdrop_claim_inner(
+    lightwalletd_url: Option<String>,
+    sapling_snapshot_nullifiers: Option<PathBuf>,
+    orchard_snapshot_nullifiers: Option<PathBuf>,
+    sapling_gap_tree_file: Option<PathBuf>,
+    orchard_gap_tree_file: Option<PathBuf>,
+    gap_tree_mode: GapTreeMode,
+    unified_full_viewing_key: String,
+    birthday_height: u64,
+    airdrop_claims_output_file: PathBuf,
+    airdrop_configuration_file: PathBuf,
+    pool: PoolSelection,
+    epoch: Option<u32>,
+    check_mempool: bool,
+) -> eyre::Result<()> {
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(airdrop_configuration_file).await?)?;
+    let mut airdrop_config = airdrop_config.for_epoch(epoch)?;
+    select_config_pools(&mut airdrop_config, pool)?;
+    let pool_files = PoolFiles::resolve(
         &airdrop_config,
-        sapling_snapshot_nullifiers.as_ref(),
-        orchard_snapshot_nullifiers.as_ref(),
-        sapling_gap_tree_file.as_ref(),
-        orchard_gap_tree_file.as_ref(),
+        sapling_snapshot_nullifiers,
+        orchard_snapshot_nullifiers,
+        sapling_gap_tree_file,
+        orchard_gap_tree_file,
         gap_tree_mode,
     )?;
 
-    let network = to_zcash_network(airdrop_config.network);
+    let (sapling, orchard) = pool_files.sources();
+    let mut user_proofs = prepare_claims_inner(
+        lightwalletd_url,
+        &airdrop_config,
+        &unified_full_viewing_key,
+        birthday_height,
+        sapling,
+        orchard,
+        check_mempool,
+        Arc::new(|_| {}),
+    )
+    .await?;
+    user_proofs.epoch = epoch;
+    let total_claims = user_proofs
+        .sapling_claim_input
+        .len()
+        .checked_add(user_proofs.orchard_claim_input.len());
+
+    let json = to_artifact_json(&user_proofs)?;
+    write_sensitive_output(&airdrop_claims_output_file, &json).await?;
+
+    info!(
+        file = ?airdrop_claims_output_file,
+        count = total_claims,
+        excluded = user_proofs.excluded_notes.len(),
+        "airdrop claims written"
+    );
+
+    Ok(())
+}
+
+/// Snapshot and gap-tree files of the pools enabled in a configuration.
+#[derive(Debug, Clone)]
+pub struct PoolFiles {
+    /// Sapling snapshot nullifiers and gap-tree file.
+    sapling: Option<(PathBuf, Option<PathBuf>)>,
+    /// Orchard snapshot nullifiers and gap-tree file.
+    orchard: Option<(PathBuf, Option<PathBuf>)>,
+    gap_tree_mode: GapTreeMode,
+}
+
+impl PoolFiles {
+    /// Resolve the default paths of the enabled pools and check that every required file is set.
+    pub fn resolve(
+        airdrop_config: &AirdropConfiguration,
+        sapling_snapshot_nullifiers: Option<PathBuf>,
+        orchard_snapshot_nullifiers: Option<PathBuf>,
+        sapling_gap_tree_file: Option<PathBuf>,
+        orchard_gap_tree_file: Option<PathBuf>,
+        gap_tree_mode: GapTreeMode,
+    ) -> eyre::Result<Self> {
+        let sapling_snapshot_nullifiers = resolve_snapshot_path_if_enabled(
+            airdrop_config.sapling.is_some(),
+            sapling_snapshot_nullifiers,
+            DEFAULT_SAPLING_SNAPSHOT_FILE,
+            Pool::Sapling,
+        );
+        let orchard_snapshot_nullifiers = resolve_snapshot_path_if_enabled(
+            airdrop_config.orchard.is_some(),
+            orchard_snapshot_nullifiers,
+            DEFAULT_ORCHARD_SNAPSHOT_FILE,
+            Pool::Orchard,
+        );
+        let sapling_gap_tree_file = resolve_gap_tree_path_if_enabled(
+            airdrop_config.sapling.is_some(),
+            sapling_gap_tree_file,
+            DEFAULT_SAPLING_GAP_TREE_FILE,
+            Pool::Sapling,
+            gap_tree_mode,
+        );
+        let orchard_gap_tree_file = resolve_gap_tree_path_if_enabled(
+            airdrop_config.orchard.is_some(),
+            orchard_gap_tree_file,
+            DEFAULT_ORCHARD_GAP_TREE_FILE,
+            Pool::Orchard,
+            gap_tree_mode,
+        );
+        validate_pool_inputs(
+            airdrop_config,
+            sapling_snapshot_nullifiers.as_ref(),
+            orchard_snapshot_nullifiers.as_ref(),
+            sapling_gap_tree_file.as_ref(),
+            orchard_gap_tree_file.as_ref(),
+            gap_tree_mode,
+        )?;
+
+        Ok(Self {
+            sapling: sapling_snapshot_nullifiers.map(|path| (path, sapling_gap_tree_file)),
+            orchard: orchard_snapshot_nullifiers.map(|path| (path, orchard_gap_tree_file)),
+            gap_tree_mode,
+        })
+    }
+
+    /// Tree sources for the Sapling and Orchard pools.
+    pub fn sources(&self) -> (Option<PoolTreeSource>, Option<PoolTreeSource>) {
+        let source = |files: &Option<(PathBuf, Option<PathBuf>)>| {
+            files.clone().map(
+                |(snapshot_nullifiers, gap_tree_file)| PoolTreeSource::Files {
+                    snapshot_nullifiers,
+                    gap_tree_file,
+                    gap_tree_mode: self.gap_tree_mode,
+                },
+            )
+        };
+        (source(&self.sapling), source(&self.orchard))
+    }
+}
+
+/// Progress of a claim preparation.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum PrepareProgress {
+    /// Scanning the chain for the account's notes.
+    Scanning {
+        /// Height of the last scanned block.
+        height: u64,
+        /// Number of blocks scanned so far.
+        scanned: usize,
+        /// Number of blocks to scan.
+        total: usize,
+    },
+    /// Scanning finished.
+    ScanComplete {
+        /// Number of notes found.
+        notes: usize,
+    },
+    /// Building the non-membership tree of a pool.
+    BuildingTree {
+        /// Pool of the tree.
+        pool: Pool,
+    },
+    /// The claims of a pool are ready.
+    PoolReady {
+        /// Pool of the claims.
+        pool: Pool,
+        /// Number of claims.
+        claims: usize,
+    },
+}
+
+/// Callback receiving [`PrepareProgress`] events.
+pub type PrepareProgressFn = Arc<dyn Fn(PrepareProgress) + Send + Sync>;
+
+/// Number of scanned blocks between two [`PrepareProgress::Scanning`] events.
+const SCAN_PROGRESS_INTERVAL: usize = 1000;
+
+/// Source of one pool's snapshot nullifiers and non-membership tree.
+pub enum PoolTreeSource {
+    /// Snapshot nullifiers and gap-tree files on disk.
+    Files {
+        snapshot_nullifiers: PathBuf,
+        gap_tree_file: Option<PathBuf>,
+        gap_tree_mode: GapTreeMode,
+    },
+    /// Snapshot nullifiers already in memory; a sparse tree is built from them.
+    Nullifiers(SanitiseNullifiers),
+}
+
+/// Scan for the account's notes and build claim inputs for every pool enabled in the
+/// configuration.
+///
+/// With `check_mempool`, a warning is logged for every claimed note that a mempool transaction
+/// is spending.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Shared by the prepare command, server and pipeline"
+)]
+pub async fn prepare_claims_inner(
+    lightwalletd_url: Option<String>,
+    airdrop_config: &AirdropConfiguration,
+    unified_full_viewing_key: &str,
+    birthday_height: u64,
+    sapling: Option<PoolTreeSource>,
+    orchard: Option<PoolTreeSource>,
+    check_mempool: bool,
+    on_progress: PrepareProgressFn,
+) -> eyre::Result<AirdropClaimInputs> {
+    ensure!(
+        airdrop_config.sapling.is_some() || airdrop_config.orchard.is_some(),
+        "Airdrop configuration must enable at least one pool (sapling/orchard)"
+    );
+    airdrop_config.circuit.ensure_supported()?;
+
+    let network = configured_network(airdrop_config)?;
     let lightwalletd_url = resolve_lightwalletd_url(network, lightwalletd_url.as_deref());
-    let ufvk = UnifiedFullViewingKey::decode(&network, &unified_full_viewing_key)
+    let ufvk = UnifiedFullViewingKey::decode(&network, unified_full_viewing_key)
         .map_err(|e| eyre::eyre!("Failed to decode Unified Full Viewing Key: {e:?}"))?;
     debug!(birthday_height, "Using user-provided birthday height");
 
@@ -173,10 +389,16 @@ pub async fn airdrop_claim(
         airdrop_config.snapshot_height,
         ufvk.clone(),
         birthday_height,
+        Arc::clone(&on_progress),
     )
     .await?;
 
     let viewing_keys = ViewingKeys::new(&ufvk);
+    let (sapling_pending, orchard_pending) = if check_mempool {
+        mempool_spends(&lightwalletd_url, network).await
+    } else {
+        (HashSet::new(), HashSet::new())
+    };
 
     // Process pools in parallel
     let (sapling_result, orchard_result) = tokio::try_join!(
@@ -184,42 +406,45 @@ pub async fn airdrop_claim(
             airdrop_config.sapling.is_some(),
             &account_notes,
             &viewing_keys,
-            &airdrop_config,
-            sapling_snapshot_nullifiers,
-            sapling_gap_tree_file,
-            gap_tree_mode,
+            airdrop_config,
+            sapling,
+            &sapling_pending,
+            on_progress.as_ref(),
         ),
         process_pool_claims::<OrchardPool>(
             airdrop_config.orchard.is_some(),
             &account_notes,
             &viewing_keys,
-            &airdrop_config,
-            orchard_snapshot_nullifiers,
-            orchard_gap_tree_file,
-            gap_tree_mode,
+            airdrop_config,
+            orchard,
+            &orchard_pending,
+            on_progress.as_ref(),
         ),
     )?;
 
-    let total_claims = sapling_result
-        .claims
-        .len()
-        .checked_add(orchard_result.claims.len());
+    airdrop_config
+        .ensure_claim_count(
+            sapling_result
+                .claims
+                .len()
+                .saturating_add(orchard_result.claims.len()),
+        )
+        .map_err(|e| {
+            eyre::eyre!(
+                "Cannot prepare the claims: {e}. Prepare each pool in a separate --pool run and \
+                 submit the claims separately"
+            )
+        })?;
 
-    let user_proofs = AirdropClaimInputs {
+    let mut excluded_notes = sapling_result.excluded;
+    excluded_notes.extend(orchard_result.excluded);
+    Ok(AirdropClaimInputs {
         sapling_claim_input: sapling_result.claims,
         orchard_claim_input: orchard_result.claims,
-    };
-
-    let json = serde_json::to_string_pretty(&user_proofs)?;
-    write_sensitive_output(&airdrop_claims_output_file, &json).await?;
-
-    info!(
-        file = ?airdrop_claims_output_file,
-        count = total_claims,
-        "airdrop claims written"
-    );
-
-    Ok(())
+        excluded_notes,
+        epoch: None,
+        key_fingerprints: Some(key_fingerprints(airdrop_config, &ufvk)),
+    })
 }
 
 fn validate_pool_inputs(
@@ -260,6 +485,46 @@ fn validate_pool_inputs(
     Ok(())
 }
 
+/// Sapling and Orchard nullifiers spent by transactions in the mempool.
+///
+/// The check is advisory, so a mempool that cannot be read is logged and treated as empty.
+async fn mempool_spends(
+    lightwalletd_url: &str,
+    network: Network,
+) -> (HashSet<Nullifier>, HashSet<Nullifier>) {
+    let mut sapling = HashSet::new();
+    let mut orchard = HashSet::new();
+    let nullifiers = match read_mempool_nullifiers(lightwalletd_url, network).await {
+        Ok(nullifiers) => nullifiers,
+        Err(e) => {
+            warn!(error = %e, "Failed to read the mempool; skipping the pending spend check");
+            return (sapling, orchard);
+        }
+    };
+    for entry in nullifiers {
+        match entry.pool {
+            Pool::Sapling => sapling.insert(entry.nullifier),
+            Pool::Orchard => orchard.insert(entry.nullifier),
+        };
+    }
+    debug!(
+        sapling = sapling.len(),
+        orchard = orchard.len(),
+        "Read mempool nullifiers"
+    );
+    (sapling, orchard)
+}
+
+async fn read_mempool_nullifiers(
+    lightwalletd_url: &str,
+    network: Network,
+) -> eyre::Result<Vec<PoolNullifier>> {
+    let lightwalletd_url =
+        Uri::from_str(lightwalletd_url).context("lightwalletd URL is required")?;
+    let lightwalletd = LightWalletd::connect(lightwalletd_url).await?;
+    Ok(lightwalletd.mempool_nullifiers(network).await?)
+}
+
 /// Scan the blockchain for user notes within the snapshot range.
 #[instrument(level = "debug", skip_all)]
 async fn find_user_notes(
@@ -268,6 +533,7 @@ async fn find_user_notes(
     snapshot_height: u64,
     ufvk: UnifiedFullViewingKey,
     birthday_height: u64,
+    on_progress: PrepareProgressFn,
 ) -> eyre::Result<AccountNotesVisitor> {
     ensure!(
         birthday_height <= snapshot_height,
@@ -292,17 +558,34 @@ async fn find_user_notes(
     let initial_metadata = BlockScanner::parse_tree_state(&tree_state)?;
 
     // Use channel-based scanning to keep non-Send BlockScanner off async tasks
+    let mut next_report = SCAN_PROGRESS_INTERVAL;
+    let scan_progress = Arc::clone(&on_progress);
     let (visitor, _final_metadata) = lightwalletd
-        .scan_blocks_spawned(ufvk, network, visitor, &scan_range, Some(initial_metadata))
+        .scan_blocks_spawned_with_progress(
+            ufvk,
+            network,
+            visitor,
+            &scan_range,
+            Some(initial_metadata),
+            move |height, scanned, total| {
+                if scanned >= next_report || scanned == total {
+                    next_report = scanned.saturating_add(SCAN_PROGRESS_INTERVAL);
+                    scan_progress(PrepareProgress::Scanning {
+                        height,
+                        scanned,
+                        total,
+                    });
+                }
+            },
+        )
         .await?;
 
-    info!(
-        total = visitor
-            .sapling_notes()
-            .len()
-            .checked_add(visitor.orchard_notes().len()),
-        "Scan complete"
-    );
+    let total = visitor
+        .sapling_notes()
+        .len()
+        .saturating_add(visitor.orchard_notes().len());
+    info!(total, "Scan complete");
+    on_progress(PrepareProgress::ScanComplete { notes: total });
 
     Ok(visitor)
 }
@@ -313,14 +596,15 @@ pub struct LoadedPoolData {
     pub tree: PoolMerkleTree,
     /// The user's nullifiers with tree positions needed to generate proofs.
     pub user_nullifiers: Vec<TreePosition>,
+    /// The user's nullifiers found in the snapshot.
+    pub spent_nullifiers: Vec<Nullifier>,
 }
 
 /// Pool-specific non-membership tree variants.
 pub enum PoolMerkleTree {
     Sapling(SaplingGapTree),
     Orchard(OrchardGapTree),
-    SaplingSparse(NonMembershipTree),
-    OrchardSparse(OrchardNonMembershipTree),
+    Sparse(Pool, SparsePoolTree),
 }
 
 impl PoolMerkleTree {
@@ -328,20 +612,27 @@ impl PoolMerkleTree {
         match self {
             Self::Sapling(tree) => tree.root_bytes(),
             Self::Orchard(tree) => tree.root_bytes(),
-            Self::SaplingSparse(tree) => tree.root().to_bytes(),
-            Self::OrchardSparse(tree) => tree.root_bytes(),
+            Self::Sparse(_, tree) => tree.root_bytes(),
         }
     }
 
-    fn witness_bytes(&self, position: u64) -> Result<Vec<[u8; 32]>, MerklePathError> {
+    const fn pool(&self) -> Pool {
         match self {
+            Self::Sapling(_) => Pool::Sapling,
+            Self::Orchard(_) => Pool::Orchard,
+            Self::Sparse(pool, _) => *pool,
+        }
+    }
+
+    fn witness_bytes(&self, position: u64) -> Result<Vec<[u8; 32]>, MerklePathError> {
+        let started = Instant::now();
+        let witness = match self {
             Self::Sapling(tree) => tree.witness_bytes(position),
             Self::Orchard(tree) => tree.witness_bytes(position),
-            Self::SaplingSparse(tree) => tree
-                .witness(position.into())
-                .map(|path| path.into_iter().map(|node| node.to_bytes()).collect()),
-            Self::OrchardSparse(tree) => tree.witness_bytes(position.into()),
-        }
+            Self::Sparse(_, tree) => tree.witness_bytes(position),
+        };
+        telemetry::record_witness(self.pool(), started.elapsed());
+        witness
     }
 }
 
@@ -358,6 +649,7 @@ async fn build_pool_merkle_tree(
     gap_tree_mode: GapTreeMode,
 ) -> eyre::Result<LoadedPoolData> {
     let use_orchard_tree = pool == Pool::Orchard;
+    let snapshot_lock = ArtifactLock::shared(snapshot_nullifiers_path).await?;
     let chain_nullifiers = load_nullifiers_from_file(snapshot_nullifiers_path).await?;
 
     info!(
@@ -365,71 +657,14 @@ async fn build_pool_merkle_tree(
         %pool,
         "Loaded chain nullifiers"
     );
+    let filter = load_nullifier_filter(snapshot_nullifiers_path, chain_nullifiers.len()).await?;
+    drop(snapshot_lock);
+    let (user_nullifiers, spent_nullifiers) =
+        split_spent_nullifiers(&chain_nullifiers, &user_nullifiers, filter.as_ref());
 
     match gap_tree_mode {
         GapTreeMode::Sparse => {
-            info!(
-                %pool,
-                "Building sparse non-membership tree from snapshot nullifiers..."
-            );
-            info!(
-                %pool,
-                progress = "0%",
-                "Building non-membership tree"
-            );
-            let chain_for_build = chain_nullifiers;
-            let user_for_build = user_nullifiers;
-            let (tree, user_positions) = tokio::task::spawn_blocking(move || {
-                let mut last_progress_pct = 0_usize;
-                if use_orchard_tree {
-                    OrchardNonMembershipTree::from_chain_and_user_nullifiers_with_progress(
-                        &chain_for_build,
-                        &user_for_build,
-                        |current, total| {
-                            if total == 0 {
-                                return;
-                            }
-                            #[allow(
-                                clippy::arithmetic_side_effects,
-                                reason = "Progress percentage uses saturating operations and is guarded against total=0"
-                            )]
-                            let pct = current.saturating_mul(100).saturating_div(total);
-                            if pct >= last_progress_pct.saturating_add(10) {
-                                last_progress_pct = pct;
-                                info!(%pool, progress = %format!("{pct}%"), "Building non-membership tree");
-                            }
-                        },
-                    )
-                    .map(|(tree, positions)| (PoolMerkleTree::OrchardSparse(tree), positions))
-                } else {
-                    NonMembershipTree::from_chain_and_user_nullifiers_with_progress(
-                        &chain_for_build,
-                        &user_for_build,
-                        |current, total| {
-                            if total == 0 {
-                                return;
-                            }
-                            #[allow(
-                                clippy::arithmetic_side_effects,
-                                reason = "Progress percentage uses saturating operations and is guarded against total=0"
-                            )]
-                            let pct = current.saturating_mul(100).saturating_div(total);
-                            if pct >= last_progress_pct.saturating_add(10) {
-                                last_progress_pct = pct;
-                                info!(%pool, progress = %format!("{pct}%"), "Building non-membership tree");
-                            }
-                        },
-                    )
-                    .map(|(tree, positions)| (PoolMerkleTree::SaplingSparse(tree), positions))
-                }
-            })
-            .await??;
-
-            info!(%pool, "Non-membership tree ready");
-            Ok(LoadedPoolData {
-                tree,
-                user_nullifiers: user_positions,
-            })
+            build_sparse_pool_tree(chain_nullifiers, user_nullifiers, spent_nullifiers, pool).await
         }
         GapTreeMode::Rebuild | GapTreeMode::None => {
             let user_positions = if use_orchard_tree {
@@ -447,6 +682,10 @@ async fn build_pool_merkle_tree(
             })?;
 
             let tree = if gap_tree_mode == GapTreeMode::Rebuild {
+                // Held until the rebuilt tree is written, so concurrent rebuilds take turns.
+                let _gap_tree_lock = ArtifactLock::exclusive(gap_tree_path).await?;
+                let nullifiers = u64::try_from(chain_nullifiers.len())?;
+                ensure_space(&[(gap_tree_path, gap_tree_bytes(nullifiers))])?;
                 info!(
                     %pool,
                     "Rebuilding gap-tree from snapshot nullifiers..."
@@ -491,23 +730,26 @@ async fn build_pool_merkle_tree(
                 let serialized = match &built_tree {
                     PoolMerkleTree::Sapling(tree) => tree.to_bytes(),
                     PoolMerkleTree::Orchard(tree) => tree.to_bytes(),
-                    PoolMerkleTree::SaplingSparse(_) | PoolMerkleTree::OrchardSparse(_) => {
+                    PoolMerkleTree::Sparse(..) => {
                         unreachable!("sparse variants are not persisted in rebuild mode")
                     }
                 };
-                tokio::fs::write(gap_tree_path, serialized)
+                paths::ensure_parent_dir(gap_tree_path).await?;
+                paths::write_complete(gap_tree_path, serialized)
                     .await
                     .with_context(|| {
                         format!("Failed to write gap-tree to {}", gap_tree_path.display())
                     })?;
                 built_tree
             } else {
+                let gap_tree_lock = ArtifactLock::shared(gap_tree_path).await?;
                 let bytes = tokio::fs::read(gap_tree_path).await.with_context(|| {
                     format!(
                         "Failed to read gap-tree from {}. Retry with --gap-tree-mode rebuild",
                         gap_tree_path.display()
                     )
                 })?;
+                drop(gap_tree_lock);
                 if use_orchard_tree {
                     PoolMerkleTree::Orchard(OrchardGapTree::from_bytes(&bytes).with_context(
                         || {
@@ -534,54 +776,82 @@ async fn build_pool_merkle_tree(
             Ok(LoadedPoolData {
                 tree,
                 user_nullifiers: user_positions,
+                spent_nullifiers,
             })
         }
     }
 }
 
-/// Generate airdrop claims for the user's notes.
-///
-/// This generic function works with any metadata type implementing `NoteMetadata`,
-/// producing claim inputs with the appropriate pool-specific private inputs.
-fn generate_claims<M: NoteMetadata>(
-    tree: &PoolMerkleTree,
-    user_nullifiers: &[TreePosition],
-    note_metadata_map: &HashMap<Nullifier, M>,
-    viewing_keys: &ViewingKeys,
-) -> eyre::Result<Vec<ClaimInput<M::PoolPrivateInputs>>> {
-    user_nullifiers
-        .iter()
-        .enumerate()
-        .map(|(index, tree_position)| {
-            let metadata = note_metadata_map
-                .get(&tree_position.nullifier)
-                .ok_or_else(|| {
-                    eyre::eyre!(
-                        "Missing note metadata for nullifier {} at claim index {}",
-                        tree_position.nullifier,
-                        index
-                    )
-                })?;
-
-            let nf_merkle_proof = tree.witness_bytes(tree_position.leaf_position.into())?;
+/// Load the nullifier filter sidecar of a snapshot, if it exists and matches the snapshot.
+async fn load_nullifier_filter(
+    snapshot_nullifiers_path: &Path,
+    snapshot_len: usize,
+) -> eyre::Result<Option<NullifierFilter>> {
+    let path = paths::nullifier_filter_path(snapshot_nullifiers_path);
+    if !tokio::fs::try_exists(&path).await? {
+        return Ok(None);
+    }
+    let file = File::open(&path).await?;
+    let filter = zair_scan::read_nullifier_filter(BufReader::with_capacity(FILE_BUF_SIZE, file))
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if usize::try_from(filter.count()).ok() != Some(snapshot_len) {
+        warn!(file = %path.display(), "Ignoring nullifier filter built for a different snapshot");
+        return Ok(None);
+    }
+    debug!(file = %path.display(), "Using nullifier filter");
+    Ok(Some(filter))
+}
 
-            debug!(
-                index,
-                "Generated proof for nullifier {:x?} at block height {}",
-                tree_position.nullifier,
-                metadata.block_height()
-            );
+/// Build a sparse in-memory non-membership tree for a pool from its snapshot nullifiers.
+///
+/// `user_nullifiers` must not contain `spent_nullifiers`, which are passed through to the result.
+async fn build_sparse_pool_tree(
+    chain_nullifiers: SanitiseNullifiers,
+    user_nullifiers: SanitiseNullifiers,
+    spent_nullifiers: Vec<Nullifier>,
+    pool: Pool,
+) -> eyre::Result<LoadedPoolData> {
+    info!(
+        %pool,
+        "Building sparse non-membership tree from snapshot nullifiers..."
+    );
+    info!(
+        %pool,
+        progress = "0%",
+        "Building non-membership tree"
+    );
+    let (tree, user_positions) = tokio::task::spawn_blocking(move || {
+        let mut last_progress_pct = 0_usize;
+        SparsePoolTree::build(
+            pool,
+            &chain_nullifiers,
+            &user_nullifiers,
+            |current, total| {
+                if total == 0 {
+                    return;
+                }
+                #[allow(
+                    clippy::arithmetic_side_effects,
+                    reason = "Progress percentage uses saturating operations and is guarded against total=0"
+                )]
+                let pct = current.saturating_mul(100).saturating_div(total);
+                if pct >= last_progress_pct.saturating_add(10) {
+                    last_progress_pct = pct;
+                    info!(%pool, progress = %format!("{pct}%"), "Building non-membership tree");
+                }
+            },
+        )
+        .map(|(tree, positions)| (PoolMerkleTree::Sparse(pool, tree), positions))
+    })
+    .await??;
 
-            let private_inputs =
-                metadata.to_private_inputs(tree_position, nf_merkle_proof, viewing_keys)?;
-            Ok(ClaimInput {
-                public_inputs: PublicInputs {
-                    airdrop_nullifier: metadata.hiding_nullifier(),
-                },
-                private_inputs,
-            })
-        })
-        .collect()
+    info!(%pool, "Non-membership tree ready");
+    Ok(LoadedPoolData {
+        tree,
+        user_nullifiers: user_positions,
+        spent_nullifiers,
+    })
 }
 
 /// Generic pool claim processor.
@@ -593,70 +863,107 @@ async fn process_pool_claims<P: PoolProcessor>(
     visitor: &AccountNotesVisitor,
     viewing_keys: &ViewingKeys,
     airdrop_config: &AirdropConfiguration,
-    snapshot_nullifiers: Option<PathBuf>,
-    gap_tree_file: Option<PathBuf>,
-    gap_tree_mode: GapTreeMode,
+    source: Option<PoolTreeSource>,
+    pending_spends: &HashSet<Nullifier>,
+    on_progress: &(dyn Fn(PrepareProgress) + Send + Sync),
 ) -> eyre::Result<PoolClaimResult<P::PrivateInputs>> {
     if !pool_enabled_in_config {
         return Ok(PoolClaimResult::empty());
     }
 
-    let Some(snapshot_nullifiers) = snapshot_nullifiers else {
+    let Some(source) = source else {
         return Err(eyre::eyre!(
-            "{} snapshot nullifiers path is required by the airdrop configuration",
+            "{} snapshot nullifiers are required by the airdrop configuration",
             P::POOL
         ));
     };
-    if gap_tree_mode != GapTreeMode::Sparse && gap_tree_file.is_none() {
+    if let PoolTreeSource::Files {
+        gap_tree_file: None,
+        gap_tree_mode: GapTreeMode::None | GapTreeMode::Rebuild,
+        ..
+    } = source
+    {
         return Err(eyre::eyre!(
             "{} gap-tree path is required by the airdrop configuration",
             P::POOL
         ));
     }
 
-    let Some(notes) = P::collect_notes(visitor, viewing_keys, airdrop_config)? else {
+    let Some(mut collected) = P::collect_notes(visitor, viewing_keys, airdrop_config)? else {
         warn!("UFVK has no {} viewing key; skipping", P::POOL);
         return Ok(PoolClaimResult::empty());
     };
 
     // Build merkle tree
-    let user_nullifiers = SanitiseNullifiers::new(notes.keys().copied().collect());
-    let pool_data = build_pool_merkle_tree(
-        &snapshot_nullifiers,
-        gap_tree_file.as_deref(),
-        user_nullifiers,
-        P::POOL,
-        gap_tree_mode,
-    )
-    .await?;
+    on_progress(PrepareProgress::BuildingTree { pool: P::POOL });
+    let user_nullifiers = SanitiseNullifiers::new(collected.notes.keys().copied().collect());
+    let pool_data = match source {
+        PoolTreeSource::Files {
+            snapshot_nullifiers,
+            gap_tree_file,
+            gap_tree_mode,
+        } => {
+            build_pool_merkle_tree(
+                &snapshot_nullifiers,
+                gap_tree_file.as_deref(),
+                user_nullifiers,
+                P::POOL,
+                gap_tree_mode,
+            )
+            .await?
+        }
+        PoolTreeSource::Nullifiers(chain_nullifiers) => {
+            let (user_nullifiers, spent_nullifiers) =
+                split_spent_nullifiers(&chain_nullifiers, &user_nullifiers, None);
+            build_sparse_pool_tree(chain_nullifiers, user_nullifiers, spent_nullifiers, P::POOL)
+                .await?
+        }
+    };
+    collected.exclude_spent(P::POOL, &pool_data.spent_nullifiers);
+    for nullifier in collected
+        .notes
+        .keys()
+        .filter(|nf| pending_spends.contains(nf))
+    {
+        warn!(
+            pool = %P::POOL,
+            %nullifier,
+            "A mempool transaction spends this claimed note; the claim will likely be invalid by submission time"
+        );
+    }
+    if !collected.excluded.is_empty() {
+        info!(
+            pool = %P::POOL,
+            excluded = collected.excluded.len(),
+            "Notes excluded from the claims; see excluded_notes"
+        );
+    }
 
     // Verify merkle root
-    let anchor = pool_data.tree.root_bytes();
-    let Some(expected_root) = P::expected_root(airdrop_config) else {
-        return Err(eyre::eyre!(
-            "{} pool is unexpectedly missing in the airdrop configuration",
-            P::POOL
-        ));
-    };
-    ensure!(
-        expected_root == anchor,
-        "{} merkle root mismatch with airdrop configuration",
-        P::POOL
-    );
+    ensure_root_matches::<P>(airdrop_config, pool_data.tree.root_bytes())?;
 
     info!(
         pool = %P::POOL,
         "Extracting witness paths for user nullifiers"
     );
     let claims = generate_claims(
-        &pool_data.tree,
+        |position| pool_data.tree.witness_bytes(position),
         &pool_data.user_nullifiers,
-        &notes,
+        &collected.notes,
         viewing_keys,
+        P::POOL,
+        airdrop_config,
     )
     .with_context(|| format!("Failed to generate {} claims", P::POOL))?;
 
-    Ok(PoolClaimResult { claims })
+    on_progress(PrepareProgress::PoolReady {
+        pool: P::POOL,
+        claims: claims.len(),
+    });
+    Ok(PoolClaimResult {
+        claims,
+        excluded: collected.excluded,
+    })
 }
 
 /// Load nullifiers from a file.
@@ -685,6 +992,7 @@ mod tests {
     use group::ff::PrimeField as _;
     use pasta_curves::pallas;
     use tokio::io::{AsyncWriteExt as _, BufWriter};
+    use zair_core::base::{Nullifier, TargetId};
     use zair_core::schema::config::{
         AirdropConfiguration, AirdropNetwork, OrchardSnapshot, SaplingSnapshot,
         ValueCommitmentScheme,
@@ -741,14 +1049,16 @@ mod tests {
             with_sapling.then_some(SaplingSnapshot {
                 note_commitment_root: [1_u8; 32],
                 nullifier_gap_root: [2_u8; 32],
-                target_id: "ZAIRTEST".to_string(),
+                target_id: TargetId::new(Pool::Sapling, "ZAIRTEST").expect("valid target ID"),
                 value_commitment_scheme: ValueCommitmentScheme::Native,
+                verifying_key_fingerprint: None,
             }),
             with_orchard.then_some(OrchardSnapshot {
                 note_commitment_root: [3_u8; 32],
                 nullifier_gap_root: [4_u8; 32],
-                target_id: "ZAIRTEST:O".to_string(),
+                target_id: TargetId::new(Pool::Orchard, "ZAIRTEST:O").expect("valid target ID"),
                 value_commitment_scheme: ValueCommitmentScheme::Native,
+                params_fingerprint: None,
             }),
         )
     }
@@ -833,6 +1143,44 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn spent_user_nullifiers_are_split_with_and_without_filter() {
+        let snapshot_path = unique_temp_path("snapshot");
+        let filter_path = paths::nullifier_filter_path(&snapshot_path);
+        let chain = chain_nullifiers(Pool::Sapling);
+        let user = || {
+            SanitiseNullifiers::new(vec![
+                Nullifier::from([2_u8; 32]),
+                Nullifier::from([3_u8; 32]),
+            ])
+        };
+        let expected = vec![Nullifier::from([2_u8; 32])];
+
+        let filter = load_nullifier_filter(&snapshot_path, chain.len())
+            .await
+            .expect("missing filter should be ignored");
+        assert!(filter.is_none());
+        let (unspent, spent) = split_spent_nullifiers(&chain, &user(), filter.as_ref());
+        assert_eq!(unspent.to_vec(), expected);
+        assert_eq!(spent, vec![Nullifier::from([3_u8; 32])]);
+
+        let file = File::create(&filter_path)
+            .await
+            .expect("filter file should be created");
+        zair_scan::write_nullifier_filter(&NullifierFilter::build(&chain), file)
+            .await
+            .expect("filter should be written");
+        let filter = load_nullifier_filter(&snapshot_path, chain.len())
+            .await
+            .expect("filter should be loaded");
+        assert!(filter.is_some());
+        let (unspent, spent) = split_spent_nullifiers(&chain, &user(), filter.as_ref());
+        assert_eq!(unspent.to_vec(), expected);
+        assert_eq!(spent, vec![Nullifier::from([3_u8; 32])]);
+
+        cleanup(&[&filter_path]).await;
+    }
+
     #[tokio::test]
     async fn sparse_mode_builds_without_gap_tree_file() {
         for pool in POOLS {
@@ -1,36 +1,91 @@
 use std::ops::RangeInclusive;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr as _;
+use std::time::Instant;
 
 use eyre::{Context as _, ContextCompat as _, ensure};
 use http::Uri;
 use tokio::fs::File;
 use tokio::io::BufWriter;
 use tracing::{info, instrument, warn};
-use zair_core::base::{Pool, SanitiseNullifiers};
+use zair_core::base::{Pool, SanitiseNullifiers, TargetId};
 use zair_core::schema::config::{
     AirdropConfiguration, OrchardSnapshot, SaplingSnapshot, ValueCommitmentScheme,
 };
 use zair_nonmembership::{OrchardGapTree, SaplingGapTree};
+#[cfg(feature = "sqlite")]
+use zair_scan::chain_nullifiers::collect_nullifiers;
 use zair_scan::light_walletd::LightWalletd;
-use zair_scan::scanner::ChainNullifiersVisitor;
-use zair_scan::write_nullifiers;
+use zair_scan::scanner::{BlockNullifiersVisitor, ChainNullifiersVisitor, ScanVisitor};
+#[cfg(feature = "sqlite")]
+use zair_scan::sqlite_nullifiers::SqliteNullifiers;
+use zair_scan::{
+    IndexedSnapshot, NullifierFilter, write_indexed_nullifiers, write_nullifier_filter,
+    write_nullifiers,
+};
 use zcash_protocol::consensus::BlockHeight;
 
-use crate::common::{CommonConfig, PoolSelection, resolve_lightwalletd_url, to_airdrop_network};
+use super::artifact_lock::ArtifactLock;
+use super::artifact_manifest::{ArtifactKind, ArtifactsOutput, write_artifact_manifest};
+use super::disk_space::{NullifierBound, ensure_space, gap_tree_bytes, snapshot_bytes};
+use super::setup_fingerprint::{read_orchard_params_fingerprint, read_sapling_vk_fingerprint};
+use super::snapshot_torrent::write_torrent;
+use crate::build_info::to_artifact_json;
+use crate::common::{
+    CommonConfig, PoolSelection, resolve_lightwalletd_url, to_airdrop_network, to_network_upgrades,
+};
+use crate::error::{ZairError, ZairResult};
 use crate::network_params::{
     orchard_activation_height, sapling_activation_height, scan_start_height,
 };
+use crate::{paths, stdio, telemetry};
 
 /// 1 MiB buffer for file I/O.
 const FILE_BUF_SIZE: usize = 1024 * 1024;
 
-/// Build the airdrop configuration by fetching nullifiers from lightwalletd,
-/// computing the non-membership roots, and exporting snapshot metadata.
+/// Where `config build` reads the snapshot nullifiers from.
+#[derive(Debug, Clone, Default)]
+pub enum NullifierSource {
+    /// Scan compact blocks from lightwalletd.
+    #[default]
+    Lightwalletd,
+    /// Read an indexer's SQLite database with the
+    /// [`NULLIFIERS_SCHEMA`](zair_scan::sqlite_nullifiers::NULLIFIERS_SCHEMA) layout.
+    #[cfg(feature = "sqlite")]
+    Sqlite(PathBuf),
+}
+
+/// Build the airdrop configuration by fetching nullifiers from `nullifier_source`,
+/// computing the non-membership roots, and exporting snapshot metadata. The note commitment
+/// roots always come from lightwalletd.
+///
+/// When `sapling_verifying_key` or `orchard_params` is given, its fingerprint is bound into the
+/// matching pool so that verification refuses any other trusted setup.
+///
+/// With `index_heights`, each snapshot file also gets an indexed sidecar (see
+/// [`paths::indexed_snapshot_path`]) recording the scanned range and the height at which each
+/// nullifier was revealed. With `nullifier_filter`, each also gets a Bloom filter sidecar (see
+/// [`paths::nullifier_filter_path`]) that `claim prepare` uses to skip snapshot searches.
+///
+/// Unless `no_disk_check` is set, the free space for the snapshot and gap-tree files is checked
+/// before the scan, against an upper bound from the note commitment tree growth over the scanned
+/// range.
+///
+/// `min_note_value` sets the smallest note value in zatoshis that `claim prepare` turns into a
+/// claim, and `max_claims_per_submission` the most claims that `claim prepare` produces and
+/// verification accepts in one submission. `claim_start_height` opens the claim window:
+/// verification at an earlier height rejects submissions. `claim_expiry_height` ends it: signed
+/// submissions must expire at or before it. Every claim must be countersigned by each Ed25519
+/// public key in `countersigners`. `chain_id` names the chain the claims are submitted to and is
+/// bound into every claim signature.
+///
+/// With `torrent_web_seeds`, every snapshot and gap-tree file also gets a `.torrent` next to it,
+/// with the given web seeds, and its magnet link is logged. With `artifacts_manifest`, the
+/// configuration, snapshot, gap-tree and setup files are listed with their BLAKE3 hashes in an
+/// [`ArtifactManifest`](super::ArtifactManifest) for `fetch_artifacts`.
 ///
 /// # Errors
 /// Returns an error if fetching nullifiers, validating inputs, or writing files fails.
-#[instrument(level = "debug", skip_all, fields(snapshot_height = config.snapshot_height, ?pool))]
 #[allow(
     clippy::too_many_lines,
     clippy::too_many_arguments,
@@ -39,57 +94,207 @@ const FILE_BUF_SIZE: usize = 1024 * 1024;
 pub async fn build_airdrop_configuration(
     config: CommonConfig,
     pool: PoolSelection,
+    nullifier_source: NullifierSource,
     configuration_output_file: PathBuf,
     sapling_snapshot_nullifiers: PathBuf,
     orchard_snapshot_nullifiers: PathBuf,
     sapling_gap_tree_file: PathBuf,
     orchard_gap_tree_file: PathBuf,
     no_gap_tree: bool,
-    sapling_target_id: String,
+    index_heights: bool,
+    nullifier_filter: bool,
+    no_disk_check: bool,
+    sapling_target_id: TargetId,
     sapling_value_commitment_scheme: ValueCommitmentScheme,
-    orchard_target_id: String,
+    orchard_target_id: TargetId,
     orchard_value_commitment_scheme: ValueCommitmentScheme,
+    sapling_verifying_key: Option<PathBuf>,
+    orchard_params: Option<PathBuf>,
+    min_note_value: Option<u64>,
+    max_claims_per_submission: Option<u32>,
+    claim_start_height: Option<u64>,
+    claim_expiry_height: Option<u64>,
+    countersigners: Vec<[u8; 32]>,
+    chain_id: Option<String>,
+    torrent_web_seeds: Option<Vec<String>>,
+    artifacts_manifest: Option<ArtifactsOutput>,
+) -> ZairResult<()> {
+    build_airdrop_configuration_inner(
+        config,
+        pool,
+        nullifier_source,
+        configuration_output_file,
+        sapling_snapshot_nullifiers,
+        orchard_snapshot_nullifiers,
+        sapling_gap_tree_file,
+        orchard_gap_tree_file,
+        no_gap_tree,
+        index_heights,
+        nullifier_filter,
+        no_disk_check,
+        sapling_target_id,
+        sapling_value_commitment_scheme,
+        orchard_target_id,
+        orchard_value_commitment_scheme,
+        sapling_verifying_key,
+        orchard_params,
+        min_note_value,
+        max_claims_per_submission,
+        claim_start_height,
+        claim_expiry_height,
+        countersigners,
+        chain_id,
+        torrent_web_seeds,
+        artifacts_manifest,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Snapshot))
+}
+
+#[instrument(level = "debug", skip_all, fields(snapshot_height = config.snapshot_height, ?pool))]
+#[allow(
+    clippy::too_many_lines,
+    clippy::too_many_arguments,
+    reason = "CLI-facing command entrypoint mirrors explicit command arguments"
+)]
+async fn build_airdrop_configuration_inner(
+    config: CommonConfig,
+    pool: PoolSelection,
+    nullifier_source: NullifierSource,
+    configuration_output_file: PathBuf,
+    sapling_snapshot_nullifiers: PathBuf,
+    orchard_snapshot_nullifiers: PathBuf,
+    sapling_gap_tree_file: PathBuf,
+    orchard_gap_tree_file: PathBuf,
+    no_gap_tree: bool,
+    index_heights: bool,
+    nullifier_filter: bool,
+    no_disk_check: bool,
+    sapling_target_id: TargetId,
+    sapling_value_commitment_scheme: ValueCommitmentScheme,
+    orchard_target_id: TargetId,
+    orchard_value_commitment_scheme: ValueCommitmentScheme,
+    sapling_verifying_key: Option<PathBuf>,
+    orchard_params: Option<PathBuf>,
+    min_note_value: Option<u64>,
+    max_claims_per_submission: Option<u32>,
+    claim_start_height: Option<u64>,
+    claim_expiry_height: Option<u64>,
+    countersigners: Vec<[u8; 32]>,
+    chain_id: Option<String>,
+    torrent_web_seeds: Option<Vec<String>>,
+    artifacts_manifest: Option<ArtifactsOutput>,
 ) -> eyre::Result<()> {
     validate_target_ids(pool, &sapling_target_id, &orchard_target_id)?;
+    if let Some(chain_id) = &chain_id {
+        ensure!(
+            !chain_id.is_empty() && chain_id.len() <= usize::from(u8::MAX),
+            "Chain ID must be between 1 and 255 bytes"
+        );
+    }
+    if let Some(claim_expiry_height) = claim_expiry_height {
+        ensure!(
+            claim_expiry_height > config.snapshot_height,
+            "Claim expiry height {claim_expiry_height} must be above the snapshot height {}",
+            config.snapshot_height
+        );
+    }
+    if let (Some(claim_start_height), Some(claim_expiry_height)) =
+        (claim_start_height, claim_expiry_height)
+    {
+        ensure!(
+            claim_start_height <= claim_expiry_height,
+            "Claim start height {claim_start_height} must not be above the claim expiry height \
+             {claim_expiry_height}"
+        );
+    }
+    ensure!(
+        max_claims_per_submission != Some(0),
+        "Max claims per submission must be at least 1"
+    );
+    ensure!(
+        !pool.includes_sapling() ||
+            sapling_value_commitment_scheme != ValueCommitmentScheme::Poseidon,
+        "The poseidon value commitment scheme is only supported for Orchard"
+    );
+
+    // Fingerprint the setup artifacts first, so a bad path fails before the long scan.
+    let sapling_verifying_key = sapling_verifying_key.filter(|_| pool.includes_sapling());
+    let orchard_params = orchard_params.filter(|_| pool.includes_orchard());
+    let verifying_key_fingerprint = match &sapling_verifying_key {
+        Some(path) => Some(read_sapling_vk_fingerprint(path).await?),
+        None => None,
+    };
+    let params_fingerprint = match &orchard_params {
+        Some(path) => Some(read_orchard_params_fingerprint(path).await?),
+        None => None,
+    };
 
     let scan_range = resolve_snapshot_scan_range(config.network, pool, config.snapshot_height)?;
     let lightwalletd_url =
         resolve_lightwalletd_url(config.network, config.lightwalletd_url.as_deref());
 
-    info!(?scan_range, "Fetching nullifiers for snapshot range");
+    info!(?scan_range, backend = %config.backend, "Fetching nullifiers for snapshot range");
     let lightwalletd_url = Uri::from_str(&lightwalletd_url).context("Invalid lightwalletd URL")?;
-    let lightwalletd = LightWalletd::connect(lightwalletd_url).await?;
+    let lightwalletd = LightWalletd::connect_backend(lightwalletd_url, config.backend).await?;
 
-    let mut visitor = ChainNullifiersVisitor::default();
-    let mut last_fetch_pct = 0_usize;
-    info!(progress = "0%", "Fetching nullifiers");
-    lightwalletd
-        .scan_nullifiers_with_progress(
-            &mut visitor,
+    if !no_disk_check {
+        ensure_output_space(
+            &lightwalletd,
             &scan_range,
-            |height, scanned, total| {
-                if total == 0 {
-                    return;
-                }
-                #[allow(
-                    clippy::arithmetic_side_effects,
-                    reason = "Fetch progress percentage uses saturating operations and is guarded against total=0"
-                )]
-                let pct = scanned.saturating_mul(100).saturating_div(total);
-                if pct >= last_fetch_pct.saturating_add(10) {
-                    last_fetch_pct = pct;
-                    info!(
-                        progress = %format!("{pct}%"),
-                        current_height = height,
-                        scanned_blocks = scanned,
-                        total_blocks = total,
-                        "Fetching nullifiers"
-                    );
-                }
-            },
+            pool,
+            [
+                sapling_snapshot_nullifiers.as_path(),
+                &orchard_snapshot_nullifiers,
+            ],
+            (!no_gap_tree).then_some([sapling_gap_tree_file.as_path(), &orchard_gap_tree_file]),
         )
-        .await?;
-    let (sapling_nullifiers, orchard_nullifiers) = visitor.sanitise_nullifiers();
+        .await
+        .context("Disk space preflight failed; pass --no-disk-check to skip it")?;
+    }
+
+    let (sapling_nullifiers, orchard_nullifiers) = match nullifier_source {
+        NullifierSource::Lightwalletd => {
+            scan_nullifiers(
+                &lightwalletd,
+                &scan_range,
+                index_heights,
+                pool,
+                &sapling_snapshot_nullifiers,
+                &orchard_snapshot_nullifiers,
+            )
+            .await?
+        }
+        #[cfg(feature = "sqlite")]
+        NullifierSource::Sqlite(database) => {
+            ensure!(
+                !index_heights,
+                "Height indexes are only written for nullifiers scanned from lightwalletd"
+            );
+            info!(database = %database.display(), "Reading nullifiers from SQLite");
+            collect_nullifiers(&SqliteNullifiers::new(database), &scan_range).await?
+        }
+    };
+
+    let mut published_files = Vec::new();
+    if pool.includes_sapling() {
+        published_files.push((
+            ArtifactKind::SnapshotSapling,
+            sapling_snapshot_nullifiers.clone(),
+        ));
+        if !no_gap_tree {
+            published_files.push((ArtifactKind::GapTreeSapling, sapling_gap_tree_file.clone()));
+        }
+    }
+    if pool.includes_orchard() {
+        published_files.push((
+            ArtifactKind::SnapshotOrchard,
+            orchard_snapshot_nullifiers.clone(),
+        ));
+        if !no_gap_tree {
+            published_files.push((ArtifactKind::GapTreeOrchard, orchard_gap_tree_file.clone()));
+        }
+    }
 
     let sapling_handle = tokio::spawn(process_pool(
         pool.includes_sapling(),
@@ -101,6 +306,7 @@ pub async fn build_airdrop_configuration(
         } else {
             Some(sapling_gap_tree_file)
         },
+        nullifier_filter,
     ));
     let orchard_handle = tokio::spawn(process_pool(
         pool.includes_orchard(),
@@ -112,6 +318,7 @@ pub async fn build_airdrop_configuration(
         } else {
             Some(orchard_gap_tree_file)
         },
+        nullifier_filter,
     ));
 
     let (sapling_nf_root, orchard_nf_root) = tokio::try_join!(sapling_handle, orchard_handle)?;
@@ -138,6 +345,7 @@ pub async fn build_airdrop_configuration(
             nullifier_gap_root: sapling_nf_root,
             target_id: sapling_target_id,
             value_commitment_scheme: sapling_value_commitment_scheme,
+            verifying_key_fingerprint,
         })
     } else {
         None
@@ -151,41 +359,230 @@ pub async fn build_airdrop_configuration(
             nullifier_gap_root: orchard_nf_root,
             target_id: orchard_target_id,
             value_commitment_scheme: orchard_value_commitment_scheme,
+            params_fingerprint,
         })
     } else {
         None
     };
 
-    let config_out = AirdropConfiguration::new(
+    let mut config_out = AirdropConfiguration::new(
         to_airdrop_network(config.network),
         config.snapshot_height,
         sapling,
         orchard,
     );
+    config_out.min_note_value = min_note_value;
+    config_out.max_claims_per_submission = max_claims_per_submission;
+    config_out.network_upgrades = to_network_upgrades(config.network);
+    config_out.claim_start_height = claim_start_height;
+    config_out.claim_expiry_height = claim_expiry_height;
+    config_out.countersigners = countersigners;
+    config_out.chain_id = chain_id;
+    info!(
+        sprout = ?config_out.sprout,
+        "Sprout notes are not collected; migrate them to Sapling or Orchard to claim"
+    );
 
-    let json = serde_json::to_string_pretty(&config_out)?;
-    tokio::fs::write(&configuration_output_file, json).await?;
+    let json = to_artifact_json(&config_out)?;
+    stdio::write(&configuration_output_file, json).await?;
 
     info!(file = ?configuration_output_file, "Exported configuration");
+
+    tokio::task::spawn_blocking(move || {
+        if let Some(web_seeds) = torrent_web_seeds {
+            for (_, file) in &published_files {
+                write_torrent(file, &web_seeds)?;
+            }
+        }
+        if let Some(artifacts) = artifacts_manifest {
+            published_files.insert(0, (ArtifactKind::Config, configuration_output_file));
+            published_files.extend(
+                sapling_verifying_key
+                    .into_iter()
+                    .chain(orchard_params)
+                    .chain(artifacts.params)
+                    .map(|path| (ArtifactKind::Params, path)),
+            );
+            write_artifact_manifest(&artifacts.manifest, &published_files)?;
+        }
+        Ok::<_, eyre::Report>(())
+    })
+    .await??;
+    Ok(())
+}
+
+/// Add the configuration of a later snapshot to a phased airdrop as epoch `epoch`.
+///
+/// `epoch_configuration_file` is built with `config build` at the epoch's snapshot height, with
+/// the same network, pools and claim policy as the base configuration. Its snapshot and claim
+/// window are added to `configuration_file`, and the result is written to
+/// `configuration_output_file`.
+///
+/// # Errors
+/// Returns an error if a file cannot be read, parsed or written, or the epoch cannot be added.
+pub async fn add_airdrop_epoch(
+    configuration_file: PathBuf,
+    epoch: u32,
+    epoch_configuration_file: PathBuf,
+    configuration_output_file: PathBuf,
+) -> ZairResult<()> {
+    add_airdrop_epoch_inner(
+        configuration_file,
+        epoch,
+        epoch_configuration_file,
+        configuration_output_file,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+async fn add_airdrop_epoch_inner(
+    configuration_file: PathBuf,
+    epoch: u32,
+    epoch_configuration_file: PathBuf,
+    configuration_output_file: PathBuf,
+) -> eyre::Result<()> {
+    let mut config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+    let later: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&epoch_configuration_file).await?)
+            .context("Failed to parse epoch configuration JSON")?;
+    ensure!(
+        later.epochs.is_empty(),
+        "{} already lists epochs; add the configuration of a single snapshot",
+        epoch_configuration_file.display()
+    );
+    config.add_epoch(epoch, later)?;
+
+    stdio::write(&configuration_output_file, to_artifact_json(&config)?).await?;
+    info!(file = ?configuration_output_file, epoch, "Added airdrop epoch");
+    Ok(())
+}
+
+/// Scan the snapshot nullifiers from lightwalletd, writing the height-indexed sidecars with
+/// `index_heights`.
+/// Check that the `[sapling, orchard]` snapshot and gap-tree files of `pool` fit on disk.
+async fn ensure_output_space(
+    lightwalletd: &LightWalletd,
+    scan_range: &RangeInclusive<u64>,
+    pool: PoolSelection,
+    snapshots: [&Path; 2],
+    gap_trees: Option<[&Path; 2]>,
+) -> eyre::Result<()> {
+    let bound = NullifierBound::fetch_range(lightwalletd, scan_range).await?;
+    let [sapling_snapshot, orchard_snapshot] = snapshots;
+    let mut outputs = Vec::new();
+    if pool.includes_sapling() {
+        outputs.push((sapling_snapshot, snapshot_bytes(bound.sapling)));
+    }
+    if pool.includes_orchard() {
+        outputs.push((orchard_snapshot, snapshot_bytes(bound.orchard)));
+    }
+    if let Some([sapling_gap_tree, orchard_gap_tree]) = gap_trees {
+        if pool.includes_sapling() {
+            outputs.push((sapling_gap_tree, gap_tree_bytes(bound.sapling)));
+        }
+        if pool.includes_orchard() {
+            outputs.push((orchard_gap_tree, gap_tree_bytes(bound.orchard)));
+        }
+    }
+    ensure_space(&outputs)?;
+    info!(
+        sapling_notes = bound.sapling,
+        orchard_notes = bound.orchard,
+        "Disk space preflight passed"
+    );
+    Ok(())
+}
+
+async fn scan_nullifiers(
+    lightwalletd: &LightWalletd,
+    scan_range: &RangeInclusive<u64>,
+    index_heights: bool,
+    pool: PoolSelection,
+    sapling_snapshot_nullifiers: &Path,
+    orchard_snapshot_nullifiers: &Path,
+) -> eyre::Result<(SanitiseNullifiers, SanitiseNullifiers)> {
+    if index_heights {
+        let mut visitor = BlockNullifiersVisitor::default();
+        fetch_nullifiers(lightwalletd, &mut visitor, scan_range).await?;
+        let blocks = visitor.into_blocks();
+        let sapling = IndexedSnapshot::from_blocks(scan_range.clone(), &blocks, Pool::Sapling);
+        let orchard = IndexedSnapshot::from_blocks(scan_range.clone(), &blocks, Pool::Orchard);
+        if pool.includes_sapling() {
+            write_indexed_snapshot(&sapling, sapling_snapshot_nullifiers).await?;
+        }
+        if pool.includes_orchard() {
+            write_indexed_snapshot(&orchard, orchard_snapshot_nullifiers).await?;
+        }
+        Ok((sapling.nullifiers(), orchard.nullifiers()))
+    } else {
+        let mut visitor = ChainNullifiersVisitor::default();
+        fetch_nullifiers(lightwalletd, &mut visitor, scan_range).await?;
+        Ok(visitor.sanitise_nullifiers())
+    }
+}
+
+async fn fetch_nullifiers(
+    lightwalletd: &LightWalletd,
+    visitor: &mut impl ScanVisitor,
+    scan_range: &RangeInclusive<u64>,
+) -> eyre::Result<()> {
+    let mut last_fetch_pct = 0_usize;
+    info!(progress = "0%", "Fetching nullifiers");
+    lightwalletd
+        .scan_nullifiers_with_progress(visitor, scan_range, |height, scanned, total| {
+            if total == 0 {
+                return;
+            }
+            #[allow(
+                clippy::arithmetic_side_effects,
+                reason = "Fetch progress percentage uses saturating operations and is guarded against total=0"
+            )]
+            let pct = scanned.saturating_mul(100).saturating_div(total);
+            if pct >= last_fetch_pct.saturating_add(10) {
+                last_fetch_pct = pct;
+                info!(
+                    progress = %format!("{pct}%"),
+                    current_height = height,
+                    scanned_blocks = scanned,
+                    total_blocks = total,
+                    "Fetching nullifiers"
+                );
+            }
+        })
+        .await?;
+    Ok(())
+}
+
+/// Write the indexed sidecar of the snapshot file `store`.
+async fn write_indexed_snapshot(snapshot: &IndexedSnapshot, store: &Path) -> eyre::Result<()> {
+    let path = paths::indexed_snapshot_path(store);
+    paths::ensure_parent_dir(&path).await?;
+    let partial = paths::begin_partial(&path);
+    let file = File::create(&partial).await?;
+    let mut writer = BufWriter::with_capacity(FILE_BUF_SIZE, file);
+    write_indexed_nullifiers(snapshot, &mut writer).await?;
+    paths::finish_partial(&partial, &path)?;
+    info!(
+        file = %path.display(),
+        resume_height = snapshot.resume_height(),
+        "Saved height-indexed nullifiers"
+    );
     Ok(())
 }
 
 fn validate_target_ids(
     pool: PoolSelection,
-    sapling_target_id: &str,
-    orchard_target_id: &str,
+    sapling_target_id: &TargetId,
+    orchard_target_id: &TargetId,
 ) -> eyre::Result<()> {
     if pool.includes_sapling() {
-        ensure!(
-            sapling_target_id.len() == 8,
-            "Sapling target_id must be exactly 8 bytes"
-        );
+        sapling_target_id.ensure_pool(Pool::Sapling)?;
     }
     if pool.includes_orchard() {
-        ensure!(
-            orchard_target_id.len() <= 32,
-            "Orchard target_id must be at most 32 bytes"
-        );
+        orchard_target_id.ensure_pool(Pool::Orchard)?;
     }
     Ok(())
 }
@@ -194,8 +591,8 @@ fn validate_target_ids(
 ///
 /// For `Both`, scanning starts at min(Sapling start, Orchard start), so one chain
 /// pass covers both pools.
-fn resolve_snapshot_scan_range(
-    network: zcash_protocol::consensus::Network,
+pub(super) fn resolve_snapshot_scan_range(
+    network: zair_scan::Network,
     pool: PoolSelection,
     snapshot_height: u64,
 ) -> eyre::Result<RangeInclusive<u64>> {
@@ -230,6 +627,7 @@ async fn process_pool(
     nullifiers: SanitiseNullifiers,
     store: PathBuf,
     gap_tree_store: Option<PathBuf>,
+    nullifier_filter: bool,
 ) -> eyre::Result<Option<[u8; 32]>> {
     if !enabled {
         return Ok(None);
@@ -240,15 +638,32 @@ async fn process_pool(
     } else {
         info!(count = nullifiers.len(), "Collected nullifiers");
     }
+    telemetry::record_nullifiers_collected(pool, nullifiers.len());
 
-    let file = File::create(&store).await?;
+    paths::ensure_parent_dir(&store).await?;
+    let snapshot_lock = ArtifactLock::exclusive(&store).await?;
+    let partial = paths::begin_partial(&store);
+    let file = File::create(&partial).await?;
     let mut writer = BufWriter::with_capacity(FILE_BUF_SIZE, file);
     write_nullifiers(&nullifiers, &mut writer).await?;
+    paths::finish_partial(&partial, &store)?;
     info!(file = ?store, pool = ?pool, "Saved nullifiers");
 
+    if nullifier_filter {
+        let path = paths::nullifier_filter_path(&store);
+        let partial = paths::begin_partial(&path);
+        let file = File::create(&partial).await?;
+        let mut writer = BufWriter::with_capacity(FILE_BUF_SIZE, file);
+        write_nullifier_filter(&NullifierFilter::build(&nullifiers), &mut writer).await?;
+        paths::finish_partial(&partial, &path)?;
+        info!(file = %path.display(), pool = ?pool, "Saved nullifier filter");
+    }
+    drop(snapshot_lock);
+
     let merkle_root = match pool {
         Pool::Sapling => {
             info!(pool = ?pool, progress = "0%", "Building non-membership tree");
+            let started = Instant::now();
             let sapling_tree = tokio::task::spawn_blocking(move || {
                 SaplingGapTree::from_nullifiers_with_progress(&nullifiers, |current, total| {
                     if total == 0 {
@@ -263,15 +678,19 @@ async fn process_pool(
                 })
             })
             .await??;
+            telemetry::record_gap_tree_build(pool, started.elapsed());
             let root = sapling_tree.root_bytes();
             if let Some(path) = gap_tree_store {
-                tokio::fs::write(&path, sapling_tree.to_bytes()).await?;
+                paths::ensure_parent_dir(&path).await?;
+                let _gap_tree_lock = ArtifactLock::exclusive(&path).await?;
+                paths::write_complete(&path, sapling_tree.to_bytes()).await?;
                 info!(pool = ?pool, file = %path.display(), "Saved gap-tree");
             }
             root
         }
         Pool::Orchard => {
             info!(pool = ?pool, progress = "0%", "Building non-membership tree");
+            let started = Instant::now();
             let orchard_tree = tokio::task::spawn_blocking(move || {
                 OrchardGapTree::from_nullifiers_with_progress(&nullifiers, |current, total| {
                     if total == 0 {
@@ -286,9 +705,12 @@ async fn process_pool(
                 })
             })
             .await??;
+            telemetry::record_gap_tree_build(pool, started.elapsed());
             let root = orchard_tree.root_bytes();
             if let Some(path) = gap_tree_store {
-                tokio::fs::write(&path, orchard_tree.to_bytes()).await?;
+                paths::ensure_parent_dir(&path).await?;
+                let _gap_tree_lock = ArtifactLock::exclusive(&path).await?;
+                paths::write_complete(&path, orchard_tree.to_bytes()).await?;
                 info!(pool = ?pool, file = %path.display(), "Saved gap-tree");
             }
             root
@@ -335,14 +757,16 @@ mod tests {
             Some(SaplingSnapshot {
                 note_commitment_root: [1_u8; 32],
                 nullifier_gap_root: [5_u8; 32],
-                target_id: "ZAIRTEST".to_string(),
+                target_id: TargetId::new(Pool::Sapling, "ZAIRTEST").expect("valid target ID"),
                 value_commitment_scheme: ValueCommitmentScheme::Native,
+                verifying_key_fingerprint: None,
             }),
             Some(OrchardSnapshot {
                 note_commitment_root: [2_u8; 32],
                 nullifier_gap_root: [6_u8; 32],
-                target_id: "ZAIRTEST:O".to_string(),
+                target_id: TargetId::new(Pool::Orchard, "ZAIRTEST:O").expect("valid target ID"),
                 value_commitment_scheme: ValueCommitmentScheme::Sha256,
+                params_fingerprint: None,
             }),
         );
 
@@ -351,9 +775,12 @@ mod tests {
 
     #[test]
     fn orchard_allows_target_id_up_to_32_bytes() {
-        let target = "a".repeat(32);
-        validate_target_ids(PoolSelection::Orchard, "ZAIRTEST", &target)
+        let orchard = TargetId::new(Pool::Orchard, "a".repeat(32))
             .expect("Orchard target_id should be allowed up to 32 bytes");
+        let sapling = TargetId::new(Pool::Orchard, "ZAIR").expect("valid Orchard target ID");
+        validate_target_ids(PoolSelection::Orchard, &sapling, &orchard)
+            .expect("the Sapling target ID is not checked for an Orchard build");
+        assert!(validate_target_ids(PoolSelection::Sapling, &sapling, &orchard).is_err());
     }
 
     #[tokio::test]
@@ -369,7 +796,7 @@ mod tests {
             std::process::id()
         ));
 
-        let root = process_pool(true, Pool::Sapling, nullifiers, path.clone(), None)
+        let root = process_pool(true, Pool::Sapling, nullifiers, path.clone(), None, false)
             .await
             .expect("processing should succeed")
             .expect("enabled pool should produce a root");
@@ -0,0 +1,121 @@
+//! Advisory locks on shared snapshot and gap-tree files.
+//!
+//! Several `zair` processes on one machine, such as claims for different accounts, share the
+//! snapshot and gap-tree files. A writer holds an exclusive lock on `<file>.lock` while it
+//! rebuilds a file, so two writers never interleave in the same `.partial` file, and a reader holds
+//! a shared lock, so it waits for a rebuild in progress instead of failing on a missing file.
+//!
+//! The locks are advisory: only `zair` honours them. The lock file is left in place after use;
+//! removing it would let a waiting process hold a lock on a file that is no longer the lock file.
+//! A reader that cannot create the lock file, for example in a read-only directory, reads without
+//! a lock, since nothing can rebuild the file there either.
+
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use eyre::Context as _;
+use fs4::fs_std::FileExt;
+use tracing::{debug, info};
+
+use crate::paths;
+
+/// Extension appended to a shared file for its lock file.
+const LOCK_EXTENSION: &str = "lock";
+
+/// A held lock on a shared file, released when dropped.
+#[derive(Debug)]
+pub(super) struct ArtifactLock {
+    _file: Option<File>,
+}
+
+impl ArtifactLock {
+    /// Lock `path` for writing, waiting for other readers and writers to finish.
+    pub(super) async fn exclusive(path: &Path) -> eyre::Result<Self> {
+        acquire(path, true).await
+    }
+
+    /// Lock `path` for reading, waiting for a writer to finish.
+    pub(super) async fn shared(path: &Path) -> eyre::Result<Self> {
+        acquire(path, false).await
+    }
+}
+
+/// Lock file of the shared file `path`: `<path>.lock`.
+fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(LOCK_EXTENSION);
+    PathBuf::from(name)
+}
+
+async fn acquire(path: &Path, exclusive: bool) -> eyre::Result<ArtifactLock> {
+    let lock_path = lock_path(path);
+    if exclusive {
+        paths::ensure_parent_dir(&lock_path).await?;
+    }
+    tokio::task::spawn_blocking(move || {
+        let file = match OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+        {
+            Ok(file) => file,
+            Err(e) if !exclusive => {
+                debug!(file = %lock_path.display(), error = %e, "Reading without a lock");
+                return Ok(ArtifactLock { _file: None });
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to open lock file {}", lock_path.display()));
+            }
+        };
+        // Called through the trait: newer toolchains add inherent `File` lock methods.
+        let locked = if exclusive {
+            FileExt::try_lock_exclusive(&file)
+        } else {
+            FileExt::try_lock_shared(&file)
+        }
+        .with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+        if !locked {
+            info!(
+                file = %lock_path.display(),
+                "Waiting for another zair process to release the file"
+            );
+            if exclusive {
+                FileExt::lock_exclusive(&file)
+            } else {
+                FileExt::lock_shared(&file)
+            }
+            .with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+        }
+        Ok(ArtifactLock { _file: Some(file) })
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn writers_exclude_readers_until_released() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let gap_tree = dir.path().join("gaptrees").join("gaptree-sapling.bin");
+        let other = || File::open(lock_path(&gap_tree)).expect("open lock file");
+
+        let writer = ArtifactLock::exclusive(&gap_tree).await.expect("lock");
+        assert!(!FileExt::try_lock_shared(&other()).expect("try lock"));
+        drop(writer);
+
+        let _reader = ArtifactLock::shared(&gap_tree).await.expect("lock");
+        assert!(FileExt::try_lock_shared(&other()).expect("try lock"));
+        assert!(!FileExt::try_lock_exclusive(&other()).expect("try lock"));
+
+        let missing = dir.path().join("missing").join("snapshot-sapling.bin");
+        ArtifactLock::shared(&missing)
+            .await
+            .expect("readers of a missing directory go unlocked");
+        assert!(!missing.parent().expect("parent").exists());
+    }
+}
@@ -0,0 +1,275 @@
+//! Content-addressed manifest of every published airdrop file.
+//!
+//! `config build --artifacts-out` lists the configuration, snapshots, gap trees and setup
+//! parameters with their size and BLAKE3 hash in `artifacts.json`. The organizer publishes the
+//! manifest next to the files, and `zair fetch --manifest <url>` downloads and verifies everything
+//! a claimant needs into the standard directories.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::{info, warn};
+
+use super::setup_fetch::{download, partial_path};
+use super::snapshot_fetch::create_parent_dir;
+use crate::build_info::to_artifact_json;
+use crate::error::{ZairError, ZairResult};
+use crate::paths;
+
+/// Default file name of the artifact manifest.
+pub const ARTIFACTS_MANIFEST_FILE: &str = "artifacts.json";
+
+/// Role of a published file, which selects where `zair fetch` stores it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArtifactKind {
+    /// Airdrop configuration, stored in the working directory.
+    Config,
+    /// Sapling snapshot nullifiers.
+    SnapshotSapling,
+    /// Orchard snapshot nullifiers.
+    SnapshotOrchard,
+    /// Sapling gap tree.
+    GapTreeSapling,
+    /// Orchard gap tree.
+    GapTreeOrchard,
+    /// Setup parameters: proving keys, verifying keys and Halo2 params.
+    Params,
+}
+
+impl ArtifactKind {
+    /// Directory `zair fetch` stores the file in by default.
+    fn default_dir(self) -> eyre::Result<PathBuf> {
+        match self {
+            Self::Config => Ok(PathBuf::new()),
+            Self::SnapshotSapling | Self::SnapshotOrchard => paths::snapshots_dir(),
+            Self::GapTreeSapling | Self::GapTreeOrchard => paths::gap_trees_dir(),
+            Self::Params => paths::params_dir(),
+        }
+    }
+}
+
+/// Where `config build` writes `artifacts.json`.
+#[derive(Debug, Clone)]
+pub struct ArtifactsOutput {
+    /// Manifest output file.
+    pub manifest: PathBuf,
+    /// Setup parameter files to publish besides the ones bound into the configuration, such as
+    /// the Sapling proving key.
+    pub params: Vec<PathBuf>,
+}
+
+/// One file listed in `artifacts.json`.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactEntry {
+    /// File name, also its URL relative to the manifest.
+    pub name: String,
+    /// Role of the file.
+    pub kind: ArtifactKind,
+    /// File size in bytes.
+    pub size: u64,
+    /// BLAKE3 hash of the file.
+    #[serde_as(as = "Hex")]
+    pub blake3: [u8; 32],
+}
+
+/// Manifest of the published airdrop files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    /// Published files.
+    pub files: Vec<ArtifactEntry>,
+}
+
+impl ArtifactManifest {
+    /// Describe `files`, published under their file names.
+    ///
+    /// # Errors
+    /// Returns an error if a file cannot be read, has no UTF-8 file name, or two files share a
+    /// name.
+    pub fn describe(files: &[(ArtifactKind, PathBuf)]) -> eyre::Result<Self> {
+        let mut entries: Vec<ArtifactEntry> = Vec::with_capacity(files.len());
+        for (kind, path) in files {
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .with_context(|| format!("{} has no UTF-8 file name", path.display()))?
+                .to_owned();
+            ensure!(
+                entries.iter().all(|entry| entry.name != name),
+                "Two published files are named {name}"
+            );
+            let (size, blake3) = blake3_file(path)?;
+            entries.push(ArtifactEntry {
+                name,
+                kind: *kind,
+                size,
+                blake3,
+            });
+        }
+        Ok(Self { files: entries })
+    }
+}
+
+/// Write the manifest of `files` to `output`.
+pub(super) fn write_artifact_manifest(
+    output: &Path,
+    files: &[(ArtifactKind, PathBuf)],
+) -> eyre::Result<()> {
+    let manifest = ArtifactManifest::describe(files)?;
+    paths::write_complete_blocking(output, to_artifact_json(&manifest)?)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    info!(file = ?output, files = manifest.files.len(), "Saved artifact manifest");
+    Ok(())
+}
+
+/// Download every file listed in the manifest at `manifest_url` and verify it.
+///
+/// Files are fetched relative to the manifest URL. They are stored in `out_dir` when given, else
+/// the configuration in the working directory and the other files in the standard snapshots,
+/// gap-trees and params directories. Files already present with the listed hash are not
+/// downloaded again, and interrupted downloads are resumed.
+///
+/// # Errors
+/// Returns an error if the manifest cannot be fetched or parsed, lists an unsafe file name, or a
+/// download fails or does not match its size and hash.
+pub async fn fetch_artifacts(manifest_url: String, out_dir: Option<PathBuf>) -> ZairResult<()> {
+    fetch_artifacts_inner(manifest_url, out_dir)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Network))
+}
+
+async fn fetch_artifacts_inner(manifest_url: String, out_dir: Option<PathBuf>) -> eyre::Result<()> {
+    tokio::task::spawn_blocking(move || {
+        info!(url = manifest_url, "Fetching artifact manifest");
+        let manifest: ArtifactManifest = serde_json::from_str(
+            &ureq::get(&manifest_url)
+                .call()
+                .with_context(|| format!("Failed to fetch {manifest_url}"))?
+                .into_body()
+                .read_to_string()
+                .with_context(|| format!("Failed to read {manifest_url}"))?,
+        )
+        .context("Failed to parse artifact manifest")?;
+        let base = manifest_url
+            .rsplit_once('/')
+            .map_or(manifest_url.as_str(), |(base, _)| base);
+
+        for entry in &manifest.files {
+            ensure!(
+                is_plain_file_name(&entry.name),
+                "Artifact manifest lists an invalid file name {:?}",
+                entry.name
+            );
+            let dir = match &out_dir {
+                Some(dir) => dir.clone(),
+                None => entry.kind.default_dir()?,
+            };
+            let destination = dir.join(&entry.name);
+            create_parent_dir(&destination)?;
+            fetch_entry(&format!("{base}/{}", entry.name), entry, &destination)?;
+        }
+        info!(
+            files = manifest.files.len(),
+            "All artifacts fetched and verified"
+        );
+        Ok(())
+    })
+    .await?
+}
+
+/// Download `url` to `destination` unless it already holds the file of `entry`.
+fn fetch_entry(url: &str, entry: &ArtifactEntry, destination: &Path) -> eyre::Result<()> {
+    if destination.exists() {
+        if blake3_file(destination)? == (entry.size, entry.blake3) {
+            info!(file = ?destination, "File already present and verified");
+            return Ok(());
+        }
+        warn!(file = ?destination, "Existing file does not match the manifest, re-downloading");
+    }
+
+    let partial = partial_path(destination);
+    info!(url, file = ?destination, "Downloading file");
+    download(url, &partial)?;
+    let (size, blake3) = blake3_file(&partial)?;
+    if (size, blake3) != (entry.size, entry.blake3) {
+        fs::remove_file(&partial)
+            .with_context(|| format!("Failed to remove {}", partial.display()))?;
+    }
+    ensure!(
+        (size, blake3) == (entry.size, entry.blake3),
+        "Downloaded {} does not match the manifest: expected {} bytes with BLAKE3 {}, got {size} \
+         bytes with BLAKE3 {}",
+        entry.name,
+        entry.size,
+        hex::encode(entry.blake3),
+        hex::encode(blake3),
+    );
+
+    fs::rename(&partial, destination)
+        .with_context(|| format!("Failed to move download to {}", destination.display()))?;
+    info!(file = ?destination, "File downloaded and verified");
+    Ok(())
+}
+
+/// Size and BLAKE3 hash of the file at `path`.
+fn blake3_file(path: &Path) -> eyre::Result<(u64, [u8; 32])> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = blake3::Hasher::new();
+    let size = io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok((size, *hasher.finalize().as_bytes()))
+}
+
+/// Whether `name` is a single path component that stays inside the target directory.
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_lists_size_and_blake3_of_every_file() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let config = dir.path().join("config.json");
+        let snapshot = dir.path().join("snapshot-sapling.bin");
+        fs::write(&config, b"{}").expect("write config");
+        fs::write(&snapshot, [1_u8; 64]).expect("write snapshot");
+
+        let manifest = ArtifactManifest::describe(&[
+            (ArtifactKind::Config, config),
+            (ArtifactKind::SnapshotSapling, snapshot),
+        ])
+        .expect("describe files");
+
+        assert_eq!(manifest.files.len(), 2);
+        let entry = manifest.files.get(1).expect("snapshot entry");
+        assert_eq!(entry.name, "snapshot-sapling.bin");
+        assert_eq!(entry.size, 64);
+        assert_eq!(entry.blake3, *blake3::hash(&[1_u8; 64]).as_bytes());
+
+        let json = serde_json::to_value(&manifest).expect("serialize manifest");
+        assert_eq!(json["files"][1]["kind"], "snapshot-sapling");
+        assert_eq!(
+            serde_json::from_value::<ArtifactManifest>(json).expect("parse manifest"),
+            manifest
+        );
+    }
+
+    #[test]
+    fn file_names_cannot_leave_the_target_directory() {
+        assert!(is_plain_file_name("gaptree-orchard.bin"));
+        assert!(!is_plain_file_name("../config.json"));
+        assert!(!is_plain_file_name(".."));
+        assert!(!is_plain_file_name("params\\pk.params"));
+        assert!(!is_plain_file_name(""));
+    }
+}
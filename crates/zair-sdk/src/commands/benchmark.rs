@@ -0,0 +1,156 @@
+//! Machine profile and gap tree timings for `zair bench`.
+//!
+//! Building the gap trees and their witnesses is the step of `claim prepare` and `config build`
+//! that grows with the snapshot size, so its timings at a few sizes let users estimate the runtime
+//! for a real snapshot. Proving and verification are covered by the criterion benches.
+
+use std::time::{Duration, Instant};
+
+use group::ff::{Field as _, PrimeField as _};
+use pasta_curves::pallas;
+use rand_core::{RngCore as _, SeedableRng as _};
+use rand_xorshift::XorShiftRng;
+use tracing::info;
+use zair_core::base::{Nullifier, Pool, SanitiseNullifiers};
+use zair_nonmembership::{
+    OrchardGapTree, SaplingGapTree, TreePosition, map_orchard_user_positions,
+    map_sapling_user_positions,
+};
+
+use crate::error::{ZairError, ZairResult};
+
+/// Host the benchmark ran on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineProfile {
+    /// Operating system.
+    pub os: &'static str,
+    /// CPU architecture.
+    pub arch: &'static str,
+    /// Threads available to the process.
+    pub threads: usize,
+}
+
+impl MachineProfile {
+    /// Profile of the current host.
+    #[must_use]
+    pub fn current() -> Self {
+        Self {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            threads: std::thread::available_parallelism().map_or(1, usize::from),
+        }
+    }
+}
+
+/// Gap tree timings of one pool at one snapshot size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeTiming {
+    /// Pool of the tree.
+    pub pool: Pool,
+    /// Chain nullifiers in the tree.
+    pub nullifiers: usize,
+    /// Time to build the tree.
+    pub build: Duration,
+    /// Time to compute one witness.
+    pub witness: Duration,
+}
+
+/// Result of `zair bench`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchmarkReport {
+    /// Host the benchmark ran on.
+    pub machine: MachineProfile,
+    /// Timings per pool and snapshot size.
+    pub trees: Vec<TreeTiming>,
+}
+
+/// Time gap tree construction and witness generation of both pools at each of `sizes`.
+///
+/// # Errors
+/// Returns an error if a tree or witness cannot be built.
+pub async fn run_benchmark(sizes: Vec<usize>) -> ZairResult<BenchmarkReport> {
+    run_benchmark_inner(sizes)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Snapshot))
+}
+
+async fn run_benchmark_inner(sizes: Vec<usize>) -> eyre::Result<BenchmarkReport> {
+    let machine = MachineProfile::current();
+    info!(
+        os = machine.os,
+        arch = machine.arch,
+        threads = machine.threads,
+        "Benchmarking gap trees"
+    );
+
+    let trees = tokio::task::spawn_blocking(move || -> eyre::Result<Vec<TreeTiming>> {
+        let mut trees = Vec::with_capacity(sizes.len().saturating_mul(2));
+        for size in sizes {
+            trees.push(time_sapling(size)?);
+            trees.push(time_orchard(size)?);
+        }
+        Ok(trees)
+    })
+    .await??;
+
+    Ok(BenchmarkReport { machine, trees })
+}
+
+fn time_sapling(size: usize) -> eyre::Result<TreeTiming> {
+    let mut rng = XorShiftRng::seed_from_u64(0);
+    let mut random_nullifier = || {
+        let mut bytes = [0_u8; 32];
+        rng.fill_bytes(&mut bytes);
+        Nullifier::from(bytes)
+    };
+    let chain = SanitiseNullifiers::new((0..size).map(|_| random_nullifier()).collect());
+    let user = SanitiseNullifiers::new(vec![random_nullifier()]);
+
+    let started = Instant::now();
+    let tree = SaplingGapTree::from_nullifiers(&chain)?;
+    let build = started.elapsed();
+
+    let position = gap_position(&map_sapling_user_positions(&chain, &user)?)?;
+    let started = Instant::now();
+    tree.witness_bytes(position)?;
+    let witness = started.elapsed();
+
+    info!(pool = "sapling", size, ?build, ?witness, "Gap tree timed");
+    Ok(TreeTiming {
+        pool: Pool::Sapling,
+        nullifiers: size,
+        build,
+        witness,
+    })
+}
+
+fn time_orchard(size: usize) -> eyre::Result<TreeTiming> {
+    let mut rng = XorShiftRng::seed_from_u64(0);
+    let mut random_nullifier = || Nullifier::from(pallas::Base::random(&mut rng).to_repr());
+    let chain = SanitiseNullifiers::new((0..size).map(|_| random_nullifier()).collect());
+    let user = SanitiseNullifiers::new(vec![random_nullifier()]);
+
+    let started = Instant::now();
+    let tree = OrchardGapTree::from_nullifiers_with_progress(&chain, |_, _| {})?;
+    let build = started.elapsed();
+
+    let position = gap_position(&map_orchard_user_positions(&chain, &user)?)?;
+    let started = Instant::now();
+    tree.witness_bytes(position)?;
+    let witness = started.elapsed();
+
+    info!(pool = "orchard", size, ?build, ?witness, "Gap tree timed");
+    Ok(TreeTiming {
+        pool: Pool::Orchard,
+        nullifiers: size,
+        build,
+        witness,
+    })
+}
+
+fn gap_position(positions: &[TreePosition]) -> eyre::Result<u64> {
+    positions
+        .first()
+        .map(|position| u64::from(position.leaf_position))
+        .ok_or_else(|| eyre::eyre!("User nullifier was not mapped to a gap"))
+}
@@ -0,0 +1,131 @@
+//! Chain tip height for the claim window checks.
+//!
+//! `verify run --height-from` reads the current height from the chain the claim window is defined
+//! on, instead of trusting a `--current-height` passed in by the caller: Zcash through
+//! lightwalletd or zaino, or Namada through the CometBFT RPC of a node.
+
+use std::path::PathBuf;
+use std::str::FromStr as _;
+
+use eyre::{Context as _, eyre};
+use http::Uri;
+use serde::Deserialize;
+use tracing::info;
+use zair_core::schema::config::AirdropConfiguration;
+use zair_scan::light_walletd::LightWalletd;
+
+use crate::common::{Backend, configured_network, resolve_lightwalletd_url};
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Chain whose tip height is checked against the claim window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeightChain {
+    /// Zcash, through lightwalletd or zaino.
+    Zcash,
+    /// Namada, through the CometBFT RPC endpoint of a node.
+    Namada,
+}
+
+/// Fetch the tip height of `chain` from `url`.
+///
+/// For Zcash, `url` is a lightwalletd endpoint served by `backend` and defaults to the endpoint of
+/// the configured network. For Namada, `url` is the CometBFT RPC URL of a node, such as
+/// `http://127.0.0.1:26657`, and is required.
+///
+/// # Errors
+/// Returns an error if the configuration cannot be read, no Namada URL is given, or the endpoint
+/// cannot be reached or returns a malformed height.
+pub async fn fetch_chain_height(
+    chain: HeightChain,
+    url: Option<String>,
+    backend: Backend,
+    airdrop_configuration_file: PathBuf,
+) -> ZairResult<u64> {
+    fetch_chain_height_inner(chain, url, backend, airdrop_configuration_file)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Network))
+}
+
+async fn fetch_chain_height_inner(
+    chain: HeightChain,
+    url: Option<String>,
+    backend: Backend,
+    airdrop_configuration_file: PathBuf,
+) -> eyre::Result<u64> {
+    let height = match chain {
+        HeightChain::Zcash => {
+            let config: AirdropConfiguration =
+                serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+                    .context("Failed to parse airdrop configuration JSON")?;
+            let network = configured_network(&config)?;
+            let lightwalletd_url = resolve_lightwalletd_url(network, url.as_deref());
+            let lightwalletd_url =
+                Uri::from_str(&lightwalletd_url).context("Invalid lightwalletd URL")?;
+            LightWalletd::connect_backend(lightwalletd_url, backend)
+                .await?
+                .latest_height()
+                .await?
+        }
+        HeightChain::Namada => {
+            let rpc_url = url.ok_or_else(|| {
+                ZairError::Config(eyre!("Reading the Namada height needs a CometBFT RPC URL"))
+            })?;
+            tokio::task::spawn_blocking(move || namada_height(&rpc_url)).await??
+        }
+    };
+    info!(?chain, height, "Current chain height fetched");
+    Ok(height)
+}
+
+/// Response of the CometBFT `/status` RPC.
+#[derive(Deserialize)]
+struct StatusResponse {
+    result: Status,
+}
+
+#[derive(Deserialize)]
+struct Status {
+    sync_info: SyncInfo,
+}
+
+#[derive(Deserialize)]
+struct SyncInfo {
+    /// Height as a decimal string.
+    latest_block_height: String,
+}
+
+fn namada_height(rpc_url: &str) -> eyre::Result<u64> {
+    let url = format!("{}/status", rpc_url.trim_end_matches('/'));
+    let body = ureq::get(&url)
+        .call()
+        .with_context(|| format!("Failed to fetch {url}"))?
+        .into_body()
+        .read_to_vec()
+        .with_context(|| format!("Failed to read {url}"))?;
+    parse_status(&body)
+}
+
+fn parse_status(body: &[u8]) -> eyre::Result<u64> {
+    // Not a `serde_json::Error` in the chain: a bad node response is a network error, not a bad
+    // input file.
+    let status: StatusResponse = serde_json::from_slice(body)
+        .map_err(|e| eyre!("Malformed CometBFT status response: {e}"))?;
+    let height = status.result.sync_info.latest_block_height;
+    height
+        .parse()
+        .map_err(|_| eyre!("Malformed CometBFT block height {height:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_cometbft_status() {
+        let body = br#"{"jsonrpc":"2.0","id":-1,"result":{"node_info":{"network":"namada.5f5de2dd1b88cba30586420"},"sync_info":{"latest_block_hash":"AB","latest_block_height":"1234567","catching_up":false}}}"#;
+        assert_eq!(parse_status(body).expect("valid status"), 1_234_567);
+        assert!(parse_status(br#"{"result":{"sync_info":{"latest_block_height":"x"}}}"#).is_err());
+        assert!(parse_status(b"<html>").is_err());
+    }
+}
@@ -0,0 +1,58 @@
+//! Constraint-count report of the Sapling claim circuit.
+//!
+//! `zair setup report` synthesizes the claim circuit without witnesses and lists the constraints
+//! of every gadget, so reviewers can see how much a change such as a new non-membership gadget
+//! grows the circuit and its proving time.
+
+use zair_core::schema::config::ValueCommitmentScheme;
+pub use zair_sapling_proofs::prover::{ConstraintReport, NamespaceCount};
+use zair_sapling_proofs::prover::{
+    ValueCommitmentScheme as SaplingValueCommitmentScheme, constraint_report,
+};
+
+use crate::error::{ZairError, ZairResult};
+
+/// Count the constraints of the Sapling claim circuit per gadget for `scheme`.
+///
+/// # Errors
+/// Returns an error if the scheme has no Sapling circuit or the circuit fails to synthesize.
+pub fn sapling_constraint_report(scheme: ValueCommitmentScheme) -> ZairResult<ConstraintReport> {
+    sapling_constraint_report_inner(scheme).map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+fn sapling_constraint_report_inner(
+    scheme: ValueCommitmentScheme,
+) -> eyre::Result<ConstraintReport> {
+    let scheme = SaplingValueCommitmentScheme::try_from(scheme)?;
+    Ok(constraint_report(scheme)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_counts_the_non_membership_gadgets() {
+        let report = sapling_constraint_report(ValueCommitmentScheme::Native).expect("report");
+
+        let namespaces: Vec<&str> = report
+            .namespaces
+            .iter()
+            .map(|count| count.namespace.as_str())
+            .collect();
+        for gadget in ["merkle tree hash", "nm merkle tree hash", "left_nf < nf"] {
+            assert!(
+                namespaces.contains(&gadget),
+                "{gadget} missing from {namespaces:?}"
+            );
+        }
+        assert_eq!(
+            report
+                .namespaces
+                .iter()
+                .map(|count| count.constraints)
+                .sum::<usize>(),
+            report.constraints
+        );
+    }
+}
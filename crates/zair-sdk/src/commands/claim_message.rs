@@ -0,0 +1,278 @@
+//! Canonical claim message construction.
+//!
+//! `claim message` writes one [`ClaimMessage`] per claim proof instead of a free-form message
+//! file: the configuration hash, claim ID, recipient, value commitment and a random nonce. The
+//! canonical bytes go to a message file per claim, and a messages file assigns them to the claims
+//! for `claim sign --messages`.
+//!
+//! The recipient is the same for every claim, or looked up per claim in a [`ClaimRecipients`]
+//! file so that one airdrop can be split across several destination accounts.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use eyre::{Context as _, ContextCompat as _};
+use rand_core::{OsRng, RngCore as _};
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::info;
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::sighash::{ClaimMessage, config_hash};
+
+use super::claim_pools::PoolEntries as _;
+use super::claim_proofs::ClaimProofsOutput;
+use super::submission_messages::{ClaimMessageAssignment, ClaimMessagesFile};
+use crate::build_info::to_artifact_json;
+use crate::common::PoolSelection;
+use crate::error::{ZairError, ZairResult};
+use crate::{paths, stdio};
+
+/// Default file name of the per-claim message assignments.
+pub const CLAIM_MESSAGES_FILE: &str = "claim-messages.json";
+
+/// Recipient address of each claim, keyed by claim ID.
+#[serde_as]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ClaimRecipients {
+    /// Target-chain address per hex claim ID.
+    #[serde_as(as = "BTreeMap<Hex, _>")]
+    pub by_claim_id: BTreeMap<[u8; 32], String>,
+}
+
+impl ClaimRecipients {
+    /// Read a recipients JSON file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed.
+    pub async fn read(recipients_file: &Path) -> eyre::Result<Self> {
+        serde_json::from_str(&stdio::read_to_string(recipients_file).await?).with_context(|| {
+            format!(
+                "Failed to parse recipients JSON from {}",
+                recipients_file.display()
+            )
+        })
+    }
+}
+
+/// Write the canonical claim message of every claim proof.
+///
+/// Each claim pays out to its address in `recipients_file`, or to `recipient` if it is not
+/// listed. The message files are written next to `messages_output_file`, which assigns them to
+/// the claims. Only the proofs of the pools in `pool` get a message.
+///
+/// # Errors
+/// Returns an error if the inputs cannot be read, a claim has no recipient or one that is too
+/// long, or a proof has no value commitment.
+pub async fn build_claim_messages(
+    proofs_file: PathBuf,
+    airdrop_configuration_file: PathBuf,
+    recipient: Option<String>,
+    recipients_file: Option<PathBuf>,
+    messages_output_file: PathBuf,
+    pool: PoolSelection,
+) -> ZairResult<()> {
+    build_claim_messages_inner(
+        proofs_file,
+        airdrop_configuration_file,
+        recipient,
+        recipients_file,
+        messages_output_file,
+        pool,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Signing))
+}
+
+async fn build_claim_messages_inner(
+    proofs_file: PathBuf,
+    airdrop_configuration_file: PathBuf,
+    recipient: Option<String>,
+    recipients_file: Option<PathBuf>,
+    messages_output_file: PathBuf,
+    pool: PoolSelection,
+) -> eyre::Result<()> {
+    info!(file = ?proofs_file, "Loading proofs...");
+    let mut proofs: ClaimProofsOutput =
+        serde_json::from_str(&stdio::read_to_string(&proofs_file).await?)
+            .context("Failed to parse proofs JSON")?;
+    proofs.retain_pools(pool);
+
+    let configuration = stdio::read(&airdrop_configuration_file).await?;
+    // Only a parseable configuration is hashed, so a wrong file is caught before signing.
+    serde_json::from_slice::<AirdropConfiguration>(&configuration)
+        .context("Failed to parse airdrop configuration JSON")?;
+
+    let recipients = match recipients_file {
+        Some(recipients_file) => ClaimRecipients::read(&recipients_file).await?,
+        None => ClaimRecipients::default(),
+    };
+    write_claim_messages(
+        &proofs,
+        &configuration,
+        &recipients,
+        recipient.as_deref(),
+        &messages_output_file,
+    )
+    .await?;
+    Ok(())
+}
+
+/// Write the canonical claim messages of `proofs` and their assignments to
+/// `messages_output_file`.
+///
+/// `configuration` is the exact bytes of the airdrop configuration file. A claim missing from
+/// `recipients` pays out to `default_recipient`.
+pub(super) async fn write_claim_messages(
+    proofs: &ClaimProofsOutput,
+    configuration: &[u8],
+    recipients: &ClaimRecipients,
+    default_recipient: Option<&str>,
+    messages_output_file: &Path,
+) -> eyre::Result<ClaimMessagesFile> {
+    let config_hash = config_hash(configuration);
+    let recipient_of = |claim_id: &[u8; 32]| {
+        recipients
+            .by_claim_id
+            .get(claim_id)
+            .map(String::as_str)
+            .or(default_recipient)
+            .with_context(|| format!("No recipient for claim {}", hex::encode(claim_id)))
+    };
+
+    let message_dir = messages_output_file
+        .parent()
+        .map_or_else(PathBuf::new, Path::to_path_buf);
+    let mut messages = ClaimMessagesFile::default();
+    for proof in &proofs.sapling_proofs {
+        let amount_commitment = proof.cv.or(proof.cv_sha256);
+        let recipient = recipient_of(&proof.claim_id)?;
+        let message = claim_message(config_hash, proof.claim_id, recipient, amount_commitment)?;
+        let message_file = message_dir.join(message_file_name("sapling", &proof.claim_id));
+        write_message(&message_file, &message).await?;
+        messages.sapling.push(ClaimMessageAssignment {
+            airdrop_nullifier: proof.airdrop_nullifier,
+            message_file,
+        });
+    }
+    for proof in &proofs.orchard_proofs {
+        let amount_commitment = proof.cv.or(proof.cv_sha256).or(proof.cv_poseidon);
+        let recipient = recipient_of(&proof.claim_id)?;
+        let message = claim_message(config_hash, proof.claim_id, recipient, amount_commitment)?;
+        let message_file = message_dir.join(message_file_name("orchard", &proof.claim_id));
+        write_message(&message_file, &message).await?;
+        messages.orchard.push(ClaimMessageAssignment {
+            airdrop_nullifier: proof.airdrop_nullifier,
+            message_file,
+        });
+    }
+
+    stdio::write(messages_output_file, to_artifact_json(&messages)?).await?;
+    info!(
+        file = ?messages_output_file,
+        sapling_count = messages.sapling.len(),
+        orchard_count = messages.orchard.len(),
+        "Claim messages written"
+    );
+    Ok(messages)
+}
+
+/// Canonical message of one claim with a fresh random nonce.
+fn claim_message(
+    config_hash: [u8; 32],
+    claim_id: [u8; 32],
+    recipient: &str,
+    amount_commitment: Option<[u8; 32]>,
+) -> eyre::Result<ClaimMessage> {
+    let mut nonce = [0_u8; 32];
+    OsRng.fill_bytes(&mut nonce);
+    Ok(ClaimMessage {
+        config_hash,
+        claim_id,
+        recipient: recipient.to_owned(),
+        amount_commitment: amount_commitment
+            .with_context(|| format!("Claim {} has no value commitment", hex::encode(claim_id)))?,
+        nonce,
+    })
+}
+
+fn message_file_name(pool: &str, claim_id: &[u8; 32]) -> String {
+    format!("claim-message-{pool}-{}.bin", hex::encode(claim_id))
+}
+
+async fn write_message(message_file: &Path, message: &ClaimMessage) -> eyre::Result<()> {
+    paths::write_complete(message_file, message.to_bytes()?)
+        .await
+        .with_context(|| format!("Failed to write {}", message_file.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::base::Nullifier;
+
+    use super::*;
+    use crate::commands::claim_proofs::SaplingClaimProofResult;
+
+    fn sapling_proof(claim_id: u8) -> SaplingClaimProofResult {
+        SaplingClaimProofResult {
+            claim_id: [claim_id; 32],
+            zkproof: [0_u8; 192],
+            rk: [0_u8; 32],
+            cv: Some([claim_id; 32]),
+            cv_sha256: None,
+            airdrop_nullifier: Nullifier::from([claim_id; 32]),
+        }
+    }
+
+    #[tokio::test]
+    async fn claims_pay_out_to_their_listed_recipient() {
+        let dir = std::env::temp_dir().join(format!("zair-claim-messages-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.expect("temp dir");
+        let proofs = ClaimProofsOutput {
+            sapling_proofs: vec![sapling_proof(1), sapling_proof(2)],
+            orchard_proofs: Vec::new(),
+            failed_claims: Vec::new(),
+            epoch: None,
+        };
+        let recipients: ClaimRecipients = serde_json::from_str(&format!(
+            r#"{{"{}": "tnam1first"}}"#,
+            hex::encode([1_u8; 32])
+        ))
+        .expect("recipients JSON");
+
+        let messages = write_claim_messages(
+            &proofs,
+            b"{}",
+            &recipients,
+            Some("tnam1default"),
+            &dir.join(CLAIM_MESSAGES_FILE),
+        )
+        .await
+        .expect("messages");
+
+        let mut listed = Vec::new();
+        for assignment in &messages.sapling {
+            let bytes = tokio::fs::read(&assignment.message_file)
+                .await
+                .expect("message");
+            listed.push(bytes.windows(10).any(|window| window == b"tnam1first"));
+        }
+        assert_eq!(listed, [true, false]);
+
+        let missing = write_claim_messages(
+            &proofs,
+            b"{}",
+            &recipients,
+            None,
+            &dir.join(CLAIM_MESSAGES_FILE),
+        )
+        .await
+        .expect_err("second claim has no recipient");
+        assert!(missing.to_string().contains("No recipient"), "{missing}");
+
+        tokio::fs::remove_dir_all(dir)
+            .await
+            .expect("remove temp dir");
+    }
+}
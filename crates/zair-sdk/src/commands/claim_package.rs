@@ -0,0 +1,431 @@
+//! Single-file claim packages.
+//!
+//! A `.zairpkg` package bundles what a verifier needs to check a claim offline: the airdrop
+//! configuration, the signed submission, the claim proofs, the messages the signatures cover and,
+//! optionally, the claimant's verification report. It is a gzip-compressed tar archive whose
+//! first entry, `zairpkg.json`, lists the SHA-256 digest of every other entry. Unpacking rejects
+//! entries that are missing from the manifest, do not match their digest, or would be written
+//! outside the extraction directory.
+
+use std::collections::BTreeMap;
+use std::io::Read as _;
+use std::path::{Component, Path, PathBuf};
+
+use eyre::{Context as _, ContextCompat as _, bail, ensure};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use sha2::{Digest as _, Sha256};
+use tracing::info;
+use zair_core::schema::submission::ClaimSubmission;
+
+use super::OrchardParamsMode;
+use super::submission_messages::ClaimMessagesFile;
+use super::workflows::verify_run;
+use crate::build_info::to_artifact_json;
+use crate::error::{ZairError, ZairResult};
+use crate::{paths, stdio};
+
+/// Name of the manifest entry of a package.
+pub const PACKAGE_MANIFEST_FILE: &str = "zairpkg.json";
+
+/// Version of the package layout.
+const PACKAGE_VERSION: u32 = 1;
+
+/// Largest entry accepted when unpacking (256 MiB).
+const MAX_ENTRY_SIZE: u64 = 268_435_456;
+
+const CONFIG_ENTRY: &str = "config.json";
+const SUBMISSION_ENTRY: &str = "claim-submission.json";
+const PROOFS_ENTRY: &str = "claim-proofs.json";
+const REPORT_ENTRY: &str = "verification-report.json";
+const MESSAGE_ENTRY: &str = "message.bin";
+const MESSAGES_ENTRY: &str = "claim-messages.json";
+
+/// One file of a package.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageEntry {
+    /// Path of the file inside the package.
+    pub name: String,
+    /// SHA-256 digest of the file.
+    #[serde_as(as = "Hex")]
+    pub sha256: [u8; 32],
+}
+
+/// Contents of `zairpkg.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageManifest {
+    /// Package layout version.
+    pub version: u32,
+    /// Every file of the package except the manifest.
+    pub files: Vec<PackageEntry>,
+}
+
+impl PackageManifest {
+    fn contains(&self, name: &str) -> bool {
+        self.files.iter().any(|entry| entry.name == name)
+    }
+}
+
+/// Bundle a claim into a `.zairpkg` package and return the package's SHA-256 digest.
+///
+/// The submission's signatures cover `message_file` or the per-claim messages of
+/// `messages_file`, so the one given is bundled along with the files it references.
+///
+/// # Errors
+/// Returns an error if an input cannot be read or parsed, or the package cannot be written.
+#[allow(
+    clippy::too_many_arguments,
+    clippy::similar_names,
+    reason = "CLI entrypoint parameters"
+)]
+pub async fn package_claim(
+    airdrop_configuration_file: PathBuf,
+    submission_file: PathBuf,
+    proofs_file: PathBuf,
+    report_file: Option<PathBuf>,
+    message_file: Option<PathBuf>,
+    messages_file: Option<PathBuf>,
+    package_output_file: PathBuf,
+) -> ZairResult<[u8; 32]> {
+    package_claim_inner(
+        airdrop_configuration_file,
+        submission_file,
+        proofs_file,
+        report_file,
+        message_file,
+        messages_file,
+        package_output_file,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+#[allow(
+    clippy::too_many_arguments,
+    clippy::similar_names,
+    reason = "CLI entrypoint parameters"
+)]
+async fn package_claim_inner(
+    airdrop_configuration_file: PathBuf,
+    submission_file: PathBuf,
+    proofs_file: PathBuf,
+    report_file: Option<PathBuf>,
+    message_file: Option<PathBuf>,
+    messages_file: Option<PathBuf>,
+    package_output_file: PathBuf,
+) -> eyre::Result<[u8; 32]> {
+    let submission = read_file(&submission_file).await?;
+    serde_json::from_slice::<ClaimSubmission>(&submission)
+        .context("Failed to parse submission JSON")?;
+
+    let mut files = vec![
+        (
+            CONFIG_ENTRY.to_owned(),
+            read_file(&airdrop_configuration_file).await?,
+        ),
+        (SUBMISSION_ENTRY.to_owned(), submission),
+        (PROOFS_ENTRY.to_owned(), read_file(&proofs_file).await?),
+    ];
+    if let Some(report_file) = report_file {
+        files.push((REPORT_ENTRY.to_owned(), read_file(&report_file).await?));
+    }
+    if let Some(message_file) = message_file {
+        files.push((MESSAGE_ENTRY.to_owned(), read_file(&message_file).await?));
+    }
+    if let Some(messages_file) = messages_file {
+        let mut messages: ClaimMessagesFile =
+            serde_json::from_slice(&read_file(&messages_file).await?)
+                .context("Failed to parse claim messages JSON")?;
+        for (pool, assignments) in [
+            ("sapling", &mut messages.sapling),
+            ("orchard", &mut messages.orchard),
+        ] {
+            for (index, assignment) in assignments.iter_mut().enumerate() {
+                let name = format!("messages/{pool}-{index}.bin");
+                files.push((name.clone(), read_file(&assignment.message_file).await?));
+                assignment.message_file = PathBuf::from(name);
+            }
+        }
+        files.push((
+            MESSAGES_ENTRY.to_owned(),
+            serde_json::to_vec_pretty(&messages)?,
+        ));
+    }
+
+    let package = tokio::task::spawn_blocking(move || write_package(&files)).await??;
+    let sha256: [u8; 32] = Sha256::digest(&package).into();
+    stdio::write(&package_output_file, package)
+        .await
+        .with_context(|| format!("Failed to write {}", package_output_file.display()))?;
+    info!(
+        file = ?package_output_file,
+        sha256 = %hex::encode(sha256),
+        "Claim package written"
+    );
+    Ok(sha256)
+}
+
+/// Unpack a `.zairpkg` package into `extract_dir` and verify the claim end-to-end.
+///
+/// The bundled files must match the manifest. The submission is then verified like `verify run`
+/// against the bundled configuration and messages, writing a fresh report to `report_file`.
+///
+/// # Errors
+/// Returns an error if the package is malformed or tampered with, or verification fails.
+#[allow(clippy::too_many_arguments, reason = "CLI entrypoint parameters")]
+pub async fn verify_claim_package(
+    package_file: PathBuf,
+    extract_dir: PathBuf,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    current_height: Option<u64>,
+    report_file: Option<PathBuf>,
+) -> ZairResult<()> {
+    let manifest = unpack_claim_package(&package_file, &extract_dir)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Verification))?;
+
+    let bundled = |name: &str| manifest.contains(name).then(|| extract_dir.join(name));
+    verify_run(
+        verifying_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+        extract_dir.join(SUBMISSION_ENTRY),
+        bundled(MESSAGE_ENTRY),
+        bundled(MESSAGES_ENTRY),
+        extract_dir.join(CONFIG_ENTRY),
+        current_height,
+        report_file,
+    )
+    .await
+}
+
+async fn unpack_claim_package(
+    package_file: &Path,
+    extract_dir: &Path,
+) -> eyre::Result<PackageManifest> {
+    let package = read_file(package_file).await?;
+    let (manifest, files) = tokio::task::spawn_blocking(move || read_package(&package)).await??;
+
+    for (name, bytes) in &files {
+        let path = extract_dir.join(name);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        paths::write_complete(&path, bytes)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    // Message paths are relative to the package; point them at the extracted files.
+    if manifest.contains(MESSAGES_ENTRY) {
+        let messages_path = extract_dir.join(MESSAGES_ENTRY);
+        let mut messages: ClaimMessagesFile =
+            serde_json::from_str(&tokio::fs::read_to_string(&messages_path).await?)
+                .context("Failed to parse bundled claim messages JSON")?;
+        for assignment in messages
+            .sapling
+            .iter_mut()
+            .chain(messages.orchard.iter_mut())
+        {
+            let name = assignment.message_file.to_string_lossy().into_owned();
+            ensure!(
+                manifest.contains(&name),
+                "Bundled claim messages reference {name}, which is not in the package"
+            );
+            assignment.message_file = extract_dir.join(name);
+        }
+        paths::write_complete(&messages_path, to_artifact_json(&messages)?).await?;
+    }
+
+    info!(
+        file = ?package_file,
+        dir = ?extract_dir,
+        files = manifest.files.len(),
+        "Claim package unpacked and checksums verified"
+    );
+    Ok(manifest)
+}
+
+async fn read_file(path: &Path) -> eyre::Result<Vec<u8>> {
+    stdio::read(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Build the compressed archive of `files`, preceded by their manifest.
+fn write_package(files: &[(String, Vec<u8>)]) -> eyre::Result<Vec<u8>> {
+    let manifest = PackageManifest {
+        version: PACKAGE_VERSION,
+        files: files
+            .iter()
+            .map(|(name, bytes)| PackageEntry {
+                name: name.clone(),
+                sha256: Sha256::digest(bytes).into(),
+            })
+            .collect(),
+    };
+    let manifest = to_artifact_json(&manifest)?.into_bytes();
+
+    let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (name, bytes) in std::iter::once((PACKAGE_MANIFEST_FILE, &manifest))
+        .chain(files.iter().map(|(name, bytes)| (name.as_str(), bytes)))
+    {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(u64::try_from(bytes.len())?);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        header.set_cksum();
+        archive
+            .append_data(&mut header, name, bytes.as_slice())
+            .with_context(|| format!("Failed to add {name} to the package"))?;
+    }
+    Ok(archive.into_inner()?.finish()?)
+}
+
+/// Read a package and check every entry against the manifest.
+fn read_package(package: &[u8]) -> eyre::Result<(PackageManifest, Vec<(String, Vec<u8>)>)> {
+    let mut entries = BTreeMap::new();
+    let mut archive = tar::Archive::new(GzDecoder::new(package));
+    for entry in archive
+        .entries()
+        .context("Package is not a gzip-compressed tar archive")?
+    {
+        let mut entry = entry.context("Failed to read package entry")?;
+        ensure!(
+            entry.header().entry_type().is_file(),
+            "Package entries must be regular files"
+        );
+        let name = entry
+            .path()?
+            .to_str()
+            .context("Package entry name is not UTF-8")?
+            .to_owned();
+        ensure!(
+            entry.size() <= MAX_ENTRY_SIZE,
+            "Package entry {name} exceeds {MAX_ENTRY_SIZE} bytes"
+        );
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        ensure!(
+            entries.insert(name.clone(), bytes).is_none(),
+            "Package contains {name} twice"
+        );
+    }
+
+    let manifest: PackageManifest = serde_json::from_slice(
+        &entries
+            .remove(PACKAGE_MANIFEST_FILE)
+            .with_context(|| format!("Package has no {PACKAGE_MANIFEST_FILE}"))?,
+    )
+    .context("Failed to parse package manifest")?;
+    ensure!(
+        manifest.version == PACKAGE_VERSION,
+        "Unsupported package version {}",
+        manifest.version
+    );
+
+    let mut files = Vec::with_capacity(manifest.files.len());
+    for expected in &manifest.files {
+        ensure_relative(&expected.name)?;
+        let bytes = entries
+            .remove(&expected.name)
+            .with_context(|| format!("Package is missing {}", expected.name))?;
+        let actual: [u8; 32] = Sha256::digest(&bytes).into();
+        ensure!(
+            actual == expected.sha256,
+            "Checksum mismatch for {}: expected sha256 {}, got {}",
+            expected.name,
+            hex::encode(expected.sha256),
+            hex::encode(actual)
+        );
+        files.push((expected.name.clone(), bytes));
+    }
+    if let Some(unexpected) = entries.keys().next() {
+        bail!("Package entry {unexpected} is not listed in the manifest");
+    }
+    for required in [CONFIG_ENTRY, SUBMISSION_ENTRY, PROOFS_ENTRY] {
+        ensure!(manifest.contains(required), "Package is missing {required}");
+    }
+    Ok((manifest, files))
+}
+
+/// Reject entry names that would be written outside the extraction directory.
+fn ensure_relative(name: &str) -> eyre::Result<()> {
+    let path = Path::new(name);
+    ensure!(
+        path.components()
+            .all(|component| matches!(component, Component::Normal(_))),
+        "Package entry name {name} is not a plain relative path"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Vec<(String, Vec<u8>)> {
+        vec![
+            (CONFIG_ENTRY.to_owned(), b"{}".to_vec()),
+            (SUBMISSION_ENTRY.to_owned(), b"{\"sapling\":[]}".to_vec()),
+            (PROOFS_ENTRY.to_owned(), b"{}".to_vec()),
+            ("messages/sapling-0.bin".to_owned(), b"message".to_vec()),
+        ]
+    }
+
+    #[test]
+    fn package_round_trips() {
+        let package = write_package(&files()).expect("write package");
+        let (manifest, unpacked) = read_package(&package).expect("read package");
+        assert_eq!(manifest.files.len(), 4);
+        assert_eq!(unpacked, files());
+    }
+
+    #[test]
+    fn tampered_packages_are_rejected() {
+        let mut files = files();
+        files.push(("../escape.json".to_owned(), Vec::new()));
+        let package = write_package(&files).expect("write package");
+        let error = read_package(&package).expect_err("escaping entry");
+        assert!(error.to_string().contains("plain relative path"), "{error}");
+
+        let manifest = PackageManifest {
+            version: PACKAGE_VERSION,
+            files: vec![PackageEntry {
+                name: CONFIG_ENTRY.to_owned(),
+                sha256: [0; 32],
+            }],
+        };
+        let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (name, bytes) in [
+            (
+                PACKAGE_MANIFEST_FILE,
+                serde_json::to_vec(&manifest).expect("manifest"),
+            ),
+            (CONFIG_ENTRY, b"{}".to_vec()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(u64::try_from(bytes.len()).expect("size"));
+            header.set_cksum();
+            archive
+                .append_data(&mut header, name, bytes.as_slice())
+                .expect("append");
+        }
+        let package = archive
+            .into_inner()
+            .expect("archive")
+            .finish()
+            .expect("gzip");
+        let error = read_package(&package).expect_err("checksum mismatch");
+        assert!(error.to_string().contains("Checksum mismatch"), "{error}");
+    }
+}
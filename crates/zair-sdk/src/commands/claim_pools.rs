@@ -0,0 +1,464 @@
+//! Per-pool claim stages.
+//!
+//! Every claim stage can be limited to one pool, so a claimant can finish the Sapling claim while
+//! the Orchard gap tree is still downloading. The per-pool claims, proofs and secrets files are
+//! merged afterwards, and the merged proofs are signed into a single submission.
+
+use std::path::{Path, PathBuf};
+
+use eyre::{Context as _, ensure};
+use serde::de::DeserializeOwned;
+use tracing::info;
+use zair_core::base::Pool;
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::proof_inputs::{AirdropClaimInputs, KeyFingerprints};
+
+use super::claim_proofs::{ClaimProofsOutput, ClaimSecretsOutput};
+use super::sensitive_output::write_sensitive_output;
+use crate::build_info::to_artifact_json;
+use crate::common::PoolSelection;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// A claim stage file holding Sapling and Orchard entries side by side.
+pub trait PoolEntries {
+    /// Whether the file holds Sapling entries.
+    fn has_sapling(&self) -> bool;
+    /// Whether the file holds Orchard entries.
+    fn has_orchard(&self) -> bool;
+    /// Drop the entries of the pools outside `pool`.
+    fn retain_pools(&mut self, pool: PoolSelection);
+    /// Move the entries of `other` into `self`.
+    fn append(&mut self, other: Self);
+}
+
+impl PoolEntries for AirdropClaimInputs {
+    fn has_sapling(&self) -> bool {
+        !self.sapling_claim_input.is_empty()
+    }
+
+    fn has_orchard(&self) -> bool {
+        !self.orchard_claim_input.is_empty()
+    }
+
+    fn retain_pools(&mut self, pool: PoolSelection) {
+        if !pool.includes_sapling() {
+            self.sapling_claim_input.clear();
+        }
+        if !pool.includes_orchard() {
+            self.orchard_claim_input.clear();
+        }
+        self.excluded_notes
+            .retain(|note| pool_includes(pool, note.pool));
+    }
+
+    fn append(&mut self, mut other: Self) {
+        self.sapling_claim_input
+            .append(&mut other.sapling_claim_input);
+        self.orchard_claim_input
+            .append(&mut other.orchard_claim_input);
+        self.excluded_notes.append(&mut other.excluded_notes);
+        self.key_fingerprints = self.key_fingerprints.or(other.key_fingerprints);
+    }
+}
+
+impl PoolEntries for ClaimProofsOutput {
+    fn has_sapling(&self) -> bool {
+        !self.sapling_proofs.is_empty()
+    }
+
+    fn has_orchard(&self) -> bool {
+        !self.orchard_proofs.is_empty()
+    }
+
+    fn retain_pools(&mut self, pool: PoolSelection) {
+        if !pool.includes_sapling() {
+            self.sapling_proofs.clear();
+        }
+        if !pool.includes_orchard() {
+            self.orchard_proofs.clear();
+        }
+        self.failed_claims
+            .retain(|failure| pool_includes(pool, failure.pool));
+    }
+
+    fn append(&mut self, mut other: Self) {
+        self.sapling_proofs.append(&mut other.sapling_proofs);
+        self.orchard_proofs.append(&mut other.orchard_proofs);
+        self.failed_claims.append(&mut other.failed_claims);
+    }
+}
+
+impl PoolEntries for ClaimSecretsOutput {
+    fn has_sapling(&self) -> bool {
+        !self.sapling.is_empty()
+    }
+
+    fn has_orchard(&self) -> bool {
+        !self.orchard.is_empty()
+    }
+
+    fn retain_pools(&mut self, pool: PoolSelection) {
+        if !pool.includes_sapling() {
+            self.sapling.clear();
+        }
+        if !pool.includes_orchard() {
+            self.orchard.clear();
+        }
+    }
+
+    fn append(&mut self, mut other: Self) {
+        self.sapling.append(&mut other.sapling);
+        self.orchard.append(&mut other.orchard);
+        self.key_fingerprints = self.key_fingerprints.or(other.key_fingerprints);
+    }
+}
+
+const fn pool_includes(selection: PoolSelection, pool: Pool) -> bool {
+    match pool {
+        Pool::Sapling => selection.includes_sapling(),
+        Pool::Orchard => selection.includes_orchard(),
+    }
+}
+
+/// Drop the pools outside `pool` from the configuration, so a stage only needs the files of the
+/// selected pools.
+///
+/// # Errors
+/// Returns an error if a single selected pool is not enabled in the configuration.
+pub(super) fn select_config_pools(
+    airdrop_config: &mut AirdropConfiguration,
+    pool: PoolSelection,
+) -> eyre::Result<()> {
+    match pool {
+        PoolSelection::Sapling => {
+            ensure!(
+                airdrop_config.sapling.is_some(),
+                "--pool sapling selected, but the airdrop configuration does not enable Sapling"
+            );
+            airdrop_config.orchard = None;
+        }
+        PoolSelection::Orchard => {
+            ensure!(
+                airdrop_config.orchard.is_some(),
+                "--pool orchard selected, but the airdrop configuration does not enable Orchard"
+            );
+            airdrop_config.sapling = None;
+        }
+        PoolSelection::Both => {}
+    }
+    Ok(())
+}
+
+/// Merge per-pool claims files from `claim prepare --pool` into one claims file.
+///
+/// # Errors
+/// Returns an error if a file cannot be read or parsed, two files hold claims of the same pool, or
+/// the output cannot be written.
+pub async fn merge_claim_inputs(
+    claims_files: Vec<PathBuf>,
+    output_file: PathBuf,
+) -> ZairResult<()> {
+    merge_claim_inputs_inner(claims_files, output_file)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Snapshot))
+}
+
+async fn merge_claim_inputs_inner(
+    claims_files: Vec<PathBuf>,
+    output_file: PathBuf,
+) -> eyre::Result<()> {
+    let mut parts = Vec::with_capacity(claims_files.len());
+    for file in &claims_files {
+        parts.push((file.as_path(), read_json::<AirdropClaimInputs>(file).await?));
+    }
+    ensure_same_epoch(parts.iter().map(|(file, part)| (*file, part.epoch)))?;
+    ensure_same_key_fingerprints(
+        parts
+            .iter()
+            .map(|(file, part)| (*file, part.key_fingerprints.as_ref())),
+    )?;
+    let merged = merge_parts(parts, "claims")?;
+    write_sensitive_output(&output_file, &to_artifact_json(&merged)?).await?;
+    info!(
+        file = ?output_file,
+        sapling_count = merged.sapling_claim_input.len(),
+        orchard_count = merged.orchard_claim_input.len(),
+        "Merged claims written"
+    );
+    Ok(())
+}
+
+/// Merge per-pool proofs and secrets files from `claim prove` into one proofs file and one secrets
+/// file, ready for `claim sign`.
+///
+/// `proofs_files` and `secrets_files` are paired by position.
+///
+/// # Errors
+/// Returns an error if a file cannot be read or parsed, the proofs and secrets files do not pair
+/// up, two files hold entries of the same pool, or an output cannot be written.
+pub async fn merge_claim_proofs(
+    proofs_files: Vec<PathBuf>,
+    secrets_files: Vec<PathBuf>,
+    proofs_output_file: PathBuf,
+    secrets_output_file: PathBuf,
+) -> ZairResult<()> {
+    merge_claim_proofs_inner(
+        proofs_files,
+        secrets_files,
+        proofs_output_file,
+        secrets_output_file,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+async fn merge_claim_proofs_inner(
+    proofs_files: Vec<PathBuf>,
+    secrets_files: Vec<PathBuf>,
+    proofs_output_file: PathBuf,
+    secrets_output_file: PathBuf,
+) -> eyre::Result<()> {
+    ensure!(
+        proofs_files.len() == secrets_files.len(),
+        "Got {} proofs files but {} secrets files; pass the secrets file of every proofs file",
+        proofs_files.len(),
+        secrets_files.len()
+    );
+    let mut proofs_parts = Vec::with_capacity(proofs_files.len());
+    let mut secrets_parts = Vec::with_capacity(secrets_files.len());
+    for (proofs_file, secrets_file) in proofs_files.iter().zip(&secrets_files) {
+        let proofs: ClaimProofsOutput = read_json(proofs_file).await?;
+        let secrets: ClaimSecretsOutput = read_json(secrets_file).await?;
+        ensure!(
+            proofs.has_sapling() == secrets.has_sapling() &&
+                proofs.has_orchard() == secrets.has_orchard(),
+            "{} and {} hold different pools; they are not from the same `claim prove` run",
+            proofs_file.display(),
+            secrets_file.display()
+        );
+        proofs_parts.push((proofs_file.as_path(), proofs));
+        secrets_parts.push((secrets_file.as_path(), secrets));
+    }
+    ensure_same_epoch(proofs_parts.iter().map(|(file, part)| (*file, part.epoch)))?;
+    ensure_same_key_fingerprints(
+        secrets_parts
+            .iter()
+            .map(|(file, part)| (*file, part.key_fingerprints.as_ref())),
+    )?;
+    let proofs = merge_parts(proofs_parts, "proofs")?;
+    let secrets = merge_parts(secrets_parts, "secrets")?;
+
+    stdio::write(&proofs_output_file, to_artifact_json(&proofs)?).await?;
+    info!(
+        file = ?proofs_output_file,
+        sapling_count = proofs.sapling_proofs.len(),
+        orchard_count = proofs.orchard_proofs.len(),
+        "Merged claim proofs written"
+    );
+    write_sensitive_output(&secrets_output_file, &to_artifact_json(&secrets)?).await?;
+    info!(file = ?secrets_output_file, "Merged claim secrets written");
+    Ok(())
+}
+
+async fn read_json<T: DeserializeOwned>(file: &Path) -> eyre::Result<T> {
+    serde_json::from_str(
+        &stdio::read_to_string(file)
+            .await
+            .with_context(|| format!("Failed to read {}", file.display()))?,
+    )
+    .with_context(|| format!("Failed to parse {}", file.display()))
+}
+
+/// Fail if the files target different airdrop epochs.
+fn ensure_same_epoch<'a>(
+    mut epochs: impl Iterator<Item = (&'a Path, Option<u32>)>,
+) -> eyre::Result<()> {
+    let Some((first_file, first_epoch)) = epochs.next() else {
+        return Ok(());
+    };
+    for (file, epoch) in epochs {
+        ensure!(
+            epoch == first_epoch,
+            "{} and {} target different airdrop epochs",
+            first_file.display(),
+            file.display()
+        );
+    }
+    Ok(())
+}
+
+/// Fail if the files were prepared by different wallets.
+///
+/// Files without key fingerprints, written by earlier versions, are not checked.
+fn ensure_same_key_fingerprints<'a>(
+    files: impl Iterator<Item = (&'a Path, Option<&'a KeyFingerprints>)>,
+) -> eyre::Result<()> {
+    let mut files = files.filter_map(|(file, fingerprints)| Some((file, fingerprints?)));
+    let Some((first_file, first_fingerprints)) = files.next() else {
+        return Ok(());
+    };
+    for (file, fingerprints) in files {
+        ensure!(
+            fingerprints.matches(first_fingerprints),
+            "{} and {} were prepared by different wallets",
+            first_file.display(),
+            file.display()
+        );
+    }
+    Ok(())
+}
+
+/// Merge the entries of `parts`, each pool coming from at most one file.
+fn merge_parts<'a, T: PoolEntries>(
+    parts: impl IntoIterator<Item = (&'a Path, T)>,
+    what: &str,
+) -> eyre::Result<T> {
+    let mut merged: Option<T> = None;
+    let mut sapling_source: Option<&Path> = None;
+    let mut orchard_source: Option<&Path> = None;
+    for (file, part) in parts {
+        for (present, source, pool) in [
+            (part.has_sapling(), &mut sapling_source, "Sapling"),
+            (part.has_orchard(), &mut orchard_source, "Orchard"),
+        ] {
+            if !present {
+                continue;
+            }
+            if let Some(previous) = source {
+                eyre::bail!(
+                    "Both {} and {} hold {pool} {what}; each pool must come from one file",
+                    previous.display(),
+                    file.display()
+                );
+            }
+            *source = Some(file);
+        }
+        match merged.as_mut() {
+            Some(merged) => merged.append(part),
+            None => merged = Some(part),
+        }
+    }
+    merged.ok_or_else(|| eyre::eyre!("No {what} files to merge"))
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::base::Nullifier;
+
+    use super::*;
+    use crate::commands::{OrchardClaimSecretResult, SaplingClaimSecretResult};
+
+    fn sapling_secret(byte: u8) -> SaplingClaimSecretResult {
+        SaplingClaimSecretResult {
+            airdrop_nullifier: Nullifier::new([byte; 32]),
+            alpha: [byte; 32],
+            rcv: Some([byte; 32]),
+            rcv_sha256: None,
+            value_blinding: None,
+        }
+    }
+
+    fn orchard_secret(byte: u8) -> OrchardClaimSecretResult {
+        OrchardClaimSecretResult {
+            airdrop_nullifier: Nullifier::new([byte; 32]),
+            alpha: [byte; 32],
+            rcv: Some([byte; 32]),
+            rcv_sha256: None,
+            rcv_poseidon: None,
+            value_blinding: None,
+        }
+    }
+
+    #[test]
+    fn files_of_different_pools_merge() {
+        let sapling = ClaimSecretsOutput {
+            sapling: vec![sapling_secret(1), sapling_secret(2)],
+            orchard: Vec::new(),
+            key_fingerprints: None,
+        };
+        let orchard = ClaimSecretsOutput {
+            sapling: Vec::new(),
+            orchard: vec![orchard_secret(3)],
+            key_fingerprints: None,
+        };
+
+        let merged = merge_parts(
+            [
+                (Path::new("sapling.json"), sapling),
+                (Path::new("orchard.json"), orchard),
+            ],
+            "secrets",
+        )
+        .expect("disjoint pools merge");
+
+        assert_eq!(merged.sapling.len(), 2);
+        assert_eq!(merged.orchard.len(), 1);
+    }
+
+    #[test]
+    fn a_pool_cannot_come_from_two_files() {
+        let first = ClaimSecretsOutput {
+            sapling: vec![sapling_secret(1)],
+            orchard: Vec::new(),
+            key_fingerprints: None,
+        };
+        let second = ClaimSecretsOutput {
+            sapling: vec![sapling_secret(2)],
+            orchard: vec![orchard_secret(3)],
+            key_fingerprints: None,
+        };
+
+        let err = merge_parts(
+            [(Path::new("a.json"), first), (Path::new("b.json"), second)],
+            "secrets",
+        )
+        .expect_err("overlapping pools are rejected");
+        assert!(err.to_string().contains("Sapling"));
+    }
+
+    #[test]
+    fn files_of_different_wallets_do_not_merge() {
+        let fingerprints = |byte| KeyFingerprints {
+            sapling: Some([byte; 32]),
+            orchard: Some([byte; 32]),
+        };
+        let sapling = ClaimSecretsOutput {
+            sapling: vec![sapling_secret(1)],
+            orchard: Vec::new(),
+            key_fingerprints: Some(fingerprints(7)),
+        };
+        let orchard = ClaimSecretsOutput {
+            sapling: Vec::new(),
+            orchard: vec![orchard_secret(2)],
+            key_fingerprints: Some(fingerprints(8)),
+        };
+        let files = [
+            (Path::new("sapling.json"), sapling),
+            (Path::new("orchard.json"), orchard),
+        ];
+
+        assert!(
+            ensure_same_key_fingerprints(
+                files
+                    .iter()
+                    .map(|(file, part)| (*file, part.key_fingerprints.as_ref()))
+            )
+            .is_err()
+        );
+        let merged = merge_parts(files, "secrets").expect("disjoint pools merge");
+        assert_eq!(merged.key_fingerprints, Some(fingerprints(7)));
+    }
+
+    #[test]
+    fn retaining_a_pool_drops_the_other() {
+        let mut secrets = ClaimSecretsOutput {
+            sapling: vec![sapling_secret(1)],
+            orchard: vec![orchard_secret(2)],
+            key_fingerprints: None,
+        };
+        secrets.retain_pools(PoolSelection::Orchard);
+        assert!(!secrets.has_sapling());
+        assert!(secrets.has_orchard());
+    }
+}
@@ -0,0 +1,249 @@
+//! Local HTTP service for claim preparation.
+//!
+//! `POST /prepare` takes `{"ufvk": "...", "birthday": 1234}` and answers with newline-delimited
+//! JSON events: `progress` events while the chain is scanned and the trees are built, then a single
+//! `claims` event carrying the prepared claims, or an `error` event.
+//!
+//! gRPC health checks report the service as `zair.prepare`.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::{IntoResponse as _, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use eyre::Context as _;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, mpsc};
+use tracing::{info, warn};
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::proof_inputs::AirdropClaimInputs;
+
+use super::airdrop_claim::{GapTreeMode, PoolFiles, PrepareProgress, prepare_claims_inner};
+use super::claim_pools::select_config_pools;
+use super::grpc_ops::grpc_ops_router;
+use crate::common::PoolSelection;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Service name reported by the gRPC health service.
+const PREPARE_SERVICE: &str = "zair.prepare";
+
+/// Body of a `POST /prepare` request.
+#[derive(Deserialize)]
+struct PrepareRequest {
+    ufvk: String,
+    birthday: u64,
+}
+
+/// One line of a `POST /prepare` response.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum PrepareEvent {
+    Progress(PrepareProgress),
+    Claims { claims: AirdropClaimInputs },
+    Error { code: &'static str, message: String },
+}
+
+struct ServerState {
+    config: AirdropConfiguration,
+    pool_files: PoolFiles,
+    lightwalletd_url: Option<String>,
+    /// Held while a preparation runs: a gap-tree rebuild writes shared files.
+    running: Mutex<()>,
+}
+
+/// Serve claim preparation over HTTP on `addr` until the process is stopped.
+///
+/// The configuration, snapshots and gap trees are fixed for the lifetime of the service; each
+/// request supplies the UFVK and birthday height. Requests are handled one at a time. With a
+/// single `pool`, only that pool is claimed.
+///
+/// # Errors
+/// Returns an error if the configuration or pool files are invalid or the address cannot be bound.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "CLI command entrypoint carries explicit file/path knobs"
+)]
+pub async fn serve_claim_prepare(
+    addr: SocketAddr,
+    lightwalletd_url: Option<String>,
+    sapling_snapshot_nullifiers: Option<PathBuf>,
+    orchard_snapshot_nullifiers: Option<PathBuf>,
+    sapling_gap_tree_file: Option<PathBuf>,
+    orchard_gap_tree_file: Option<PathBuf>,
+    gap_tree_mode: GapTreeMode,
+    airdrop_configuration_file: PathBuf,
+    pool: PoolSelection,
+) -> ZairResult<()> {
+    serve_claim_prepare_inner(
+        addr,
+        lightwalletd_url,
+        sapling_snapshot_nullifiers,
+        orchard_snapshot_nullifiers,
+        sapling_gap_tree_file,
+        orchard_gap_tree_file,
+        gap_tree_mode,
+        airdrop_configuration_file,
+        pool,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+#[allow(
+    clippy::too_many_arguments,
+    reason = "CLI command entrypoint carries explicit file/path knobs"
+)]
+async fn serve_claim_prepare_inner(
+    addr: SocketAddr,
+    lightwalletd_url: Option<String>,
+    sapling_snapshot_nullifiers: Option<PathBuf>,
+    orchard_snapshot_nullifiers: Option<PathBuf>,
+    sapling_gap_tree_file: Option<PathBuf>,
+    orchard_gap_tree_file: Option<PathBuf>,
+    gap_tree_mode: GapTreeMode,
+    airdrop_configuration_file: PathBuf,
+    pool: PoolSelection,
+) -> eyre::Result<()> {
+    let mut config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(airdrop_configuration_file).await?)?;
+    select_config_pools(&mut config, pool)?;
+    let pool_files = PoolFiles::resolve(
+        &config,
+        sapling_snapshot_nullifiers,
+        orchard_snapshot_nullifiers,
+        sapling_gap_tree_file,
+        orchard_gap_tree_file,
+        gap_tree_mode,
+    )?;
+
+    let state = Arc::new(ServerState {
+        config,
+        pool_files,
+        lightwalletd_url,
+        running: Mutex::new(()),
+    });
+    let router = Router::new()
+        .route("/prepare", post(prepare))
+        .with_state(state)
+        .merge(grpc_ops_router(PREPARE_SERVICE).await?);
+
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {addr}"))?;
+    if !addr.ip().is_loopback() {
+        warn!(
+            %addr,
+            "Serving on a non-loopback address; responses contain claim secrets"
+        );
+    }
+    info!(%addr, "Serving claim preparation");
+    axum::serve(listener, router)
+        .await
+        .context("Claim preparation service failed")
+}
+
+async fn prepare(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<PrepareRequest>,
+) -> Response {
+    let (events, mut receiver) = mpsc::unbounded_channel();
+    tokio::spawn(run_preparation(state, request, events));
+
+    let lines = futures::stream::poll_fn(move |cx| {
+        receiver
+            .poll_recv(cx)
+            .map(|event| event.map(|event| Ok::<_, Infallible>(event_line(&event))))
+    });
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response()
+}
+
+async fn run_preparation(
+    state: Arc<ServerState>,
+    request: PrepareRequest,
+    events: mpsc::UnboundedSender<PrepareEvent>,
+) {
+    let _running = state.running.lock().await;
+    info!(birthday = request.birthday, "Preparing claims");
+
+    let progress = events.clone();
+    let (sapling, orchard) = state.pool_files.sources();
+    let result = prepare_claims_inner(
+        state.lightwalletd_url.clone(),
+        &state.config,
+        request.ufvk.trim(),
+        request.birthday,
+        sapling,
+        orchard,
+        false,
+        Arc::new(move |event| {
+            let _ = progress.send(PrepareEvent::Progress(event));
+        }),
+    )
+    .await;
+
+    let event = match result {
+        Ok(claims) => {
+            info!("Claims prepared");
+            PrepareEvent::Claims { claims }
+        }
+        Err(e) => {
+            let error = ZairError::classify(e, ZairError::Snapshot);
+            warn!(
+                code = error.code(),
+                "Claim preparation failed: {:?}",
+                error.report()
+            );
+            PrepareEvent::Error {
+                code: error.code(),
+                message: format!("{:#}", error.report()),
+            }
+        }
+    };
+    // A client that disconnected early no longer receives the result.
+    let _ = events.send(event);
+}
+
+fn event_line(event: &PrepareEvent) -> Vec<u8> {
+    let mut line = serde_json::to_vec(event).expect("Prepare events serialize to JSON");
+    line.push(b'\n');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::base::Pool;
+
+    use super::*;
+
+    #[test]
+    fn progress_events_are_flat_json_lines() {
+        let line = event_line(&PrepareEvent::Progress(PrepareProgress::BuildingTree {
+            pool: Pool::Orchard,
+        }));
+        assert_eq!(
+            line,
+            b"{\"event\":\"progress\",\"stage\":\"building_tree\",\"pool\":\"orchard\"}\n"
+        );
+
+        let line = event_line(&PrepareEvent::Error {
+            code: "network",
+            message: "unreachable".to_owned(),
+        });
+        assert_eq!(
+            line,
+            b"{\"event\":\"error\",\"code\":\"network\",\"message\":\"unreachable\"}\n"
+        );
+    }
+}
@@ -7,16 +7,28 @@ use serde::{Deserialize, Serialize};
 use serde_with::hex::Hex;
 use serde_with::serde_as;
 use tracing::{info, warn};
-use zair_core::base::Nullifier;
-use zair_core::schema::config::AirdropConfiguration;
+use zair_core::base::{Nullifier, Pool};
+use zair_core::schema::config::{AirdropConfiguration, ValueCommitmentScheme};
+use zair_core::schema::proof_inputs::KeyFingerprints;
+use zair_core::schema::submission::ClaimSubmission;
 use zair_orchard_proofs::{
     ClaimProofOutput as OrchardClaimProofOutput,
     ValueCommitmentScheme as OrchardValueCommitmentScheme,
     verify_claim_proof_output as verify_orchard_claim_proof_output,
 };
-use zair_sapling_proofs::verifier::verify_claim_proof_bytes;
+use zair_sapling_proofs::verifier::{
+    ValueCommitmentScheme as SaplingValueCommitmentScheme, verify_claim_proof_bytes,
+};
 
-use super::orchard_params::{OrchardParamsMode, load_or_prepare_orchard_params};
+use super::claim_pools::PoolEntries as _;
+use super::orchard_params::{OrchardParamsMode, load_or_prepare_orchard_params_inner};
+use super::setup_fingerprint::{
+    ensure_fingerprint_matches, orchard_params_fingerprint, sapling_vk_fingerprint,
+};
+use crate::common::PoolSelection;
+use crate::error::{ZairError, ZairResult};
+use crate::pipeline::VerifyingParams;
+use crate::stdio;
 
 /// Output format for claim proofs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,12 +37,53 @@ pub struct ClaimProofsOutput {
     pub sapling_proofs: Vec<SaplingClaimProofResult>,
     /// Orchard claim proofs.
     pub orchard_proofs: Vec<OrchardClaimProofResult>,
+    /// Claims that could not be proven, so a failing claim does not hold back the others.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_claims: Vec<FailedClaimProof>,
+    /// Airdrop epoch the claims target; `None` for the base epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u32>,
+}
+
+impl ClaimProofsOutput {
+    /// Fail if claims were attempted but none of them could be proven.
+    pub(crate) fn ensure_any_proven(&self) -> eyre::Result<()> {
+        ensure!(
+            self.failed_claims.is_empty() ||
+                !self.sapling_proofs.is_empty() ||
+                !self.orchard_proofs.is_empty(),
+            "All {} claims failed to prove; first failure: {}",
+            self.failed_claims.len(),
+            self.failed_claims
+                .first()
+                .map_or("", |failure| failure.reason.as_str())
+        );
+        Ok(())
+    }
+}
+
+/// A claim that `claim prove` could not prove.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedClaimProof {
+    /// Stable identifier of the claim.
+    #[serde_as(as = "Hex")]
+    pub claim_id: [u8; 32],
+    /// Pool of the claimed note.
+    pub pool: Pool,
+    /// The airdrop nullifier of the claim.
+    pub airdrop_nullifier: Nullifier,
+    /// Why the proof could not be generated.
+    pub reason: String,
 }
 
 /// Serializable output of a single Sapling claim proof.
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SaplingClaimProofResult {
+    /// Stable identifier of the claim.
+    #[serde_as(as = "Hex")]
+    pub claim_id: [u8; 32],
     /// The Groth16 proof (192 bytes)
     #[serde_as(as = "Hex")]
     pub zkproof: [u8; 192],
@@ -53,6 +106,9 @@ pub struct SaplingClaimProofResult {
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrchardClaimProofResult {
+    /// Stable identifier of the claim.
+    #[serde_as(as = "Hex")]
+    pub claim_id: [u8; 32],
     /// The Halo2 proof bytes.
     #[serde_as(as = "Hex")]
     pub zkproof: Vec<u8>,
@@ -67,10 +123,49 @@ pub struct OrchardClaimProofResult {
     #[serde_as(as = "Option<Hex>")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cv_sha256: Option<[u8; 32]>,
+    /// The Poseidon value commitment (`cv_poseidon`), if the scheme is `poseidon`.
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cv_poseidon: Option<[u8; 32]>,
     /// The airdrop nullifier (airdrop-specific nullifier for double-claim prevention).
     pub airdrop_nullifier: Nullifier,
 }
 
+impl From<&ClaimSubmission> for ClaimProofsOutput {
+    /// Proofs carried by a signed submission, without the signatures.
+    fn from(submission: &ClaimSubmission) -> Self {
+        Self {
+            sapling_proofs: submission
+                .sapling
+                .iter()
+                .map(|entry| SaplingClaimProofResult {
+                    claim_id: entry.claim_id,
+                    zkproof: entry.zkproof,
+                    rk: entry.rk,
+                    cv: entry.cv,
+                    cv_sha256: entry.cv_sha256,
+                    airdrop_nullifier: entry.airdrop_nullifier,
+                })
+                .collect(),
+            orchard_proofs: submission
+                .orchard
+                .iter()
+                .map(|entry| OrchardClaimProofResult {
+                    claim_id: entry.claim_id,
+                    zkproof: entry.zkproof.clone(),
+                    rk: entry.rk,
+                    cv: entry.cv,
+                    cv_sha256: entry.cv_sha256,
+                    cv_poseidon: entry.cv_poseidon,
+                    airdrop_nullifier: entry.airdrop_nullifier,
+                })
+                .collect(),
+            failed_claims: Vec::new(),
+            epoch: submission.epoch,
+        }
+    }
+}
+
 /// Local-only secrets output format.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaimSecretsOutput {
@@ -78,6 +173,10 @@ pub struct ClaimSecretsOutput {
     pub sapling: Vec<SaplingClaimSecretResult>,
     /// Orchard local-only secret material.
     pub orchard: Vec<OrchardClaimSecretResult>,
+    /// Fingerprints of the viewing key of the proven claims; absent in secrets written by
+    /// earlier versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_fingerprints: Option<KeyFingerprints>,
 }
 
 /// Local-only secret material for a single Sapling claim proof.
@@ -97,6 +196,9 @@ pub struct SaplingClaimSecretResult {
     #[serde_as(as = "Option<Hex>")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub rcv_sha256: Option<[u8; 32]>,
+    /// Opening of the proof's value commitment; absent in secrets written by earlier versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_blinding: Option<ValueBlinding>,
 }
 
 /// Local-only secret material for a single Orchard claim proof.
@@ -116,21 +218,152 @@ pub struct OrchardClaimSecretResult {
     #[serde_as(as = "Option<Hex>")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub rcv_sha256: Option<[u8; 32]>,
+    /// Poseidon commitment randomness `rcv_poseidon`, if the scheme is `poseidon`.
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rcv_poseidon: Option<[u8; 32]>,
+    /// Opening of the proof's value commitment; absent in secrets written by earlier versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_blinding: Option<ValueBlinding>,
 }
 
-/// Verify all Sapling claim proofs from a proofs file (output of prove).
+/// Opening of a claim proof's value commitment: the committed value and its blinding factor.
+///
+/// Every proof commits to its note value under a blinding factor drawn fresh for the claim, so
+/// the commitment cannot be linked to the note's own on-chain value commitment. With the opening,
+/// the claimant can later show a third party the value of a claim, or the total of several, without
+/// the prepared claims file.
+#[serde_as]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueBlinding {
+    /// Commitment the opening is for: the proof's `cv`, `cv_sha256` or `cv_poseidon`.
+    pub scheme: ValueCommitmentScheme,
+    /// Committed note value in zatoshis.
+    pub value: u64,
+    /// Blinding factor of the commitment: `rcv`, `rcv_sha256` or `rcv_poseidon`. The `rcv` of a
+    /// native commitment and the `rcv_poseidon` of a Poseidon commitment are canonical field
+    /// element encodings; the `rcv_sha256` of a SHA-256 commitment is hashed as is.
+    #[serde_as(as = "Hex")]
+    pub blinding: [u8; 32],
+}
+
+/// Which proofs of a proofs file to verify.
+///
+/// Indexes count the proofs of each pool separately, as in the verification logs, so combine
+/// `index` with a single pool to pick one proof.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProofFilter {
+    /// Verify only the proof at this position in its pool.
+    pub index: Option<usize>,
+    /// Verify only the proof of this claim.
+    pub claim_id: Option<[u8; 32]>,
+}
+
+impl ProofFilter {
+    /// Whether the filter selects every proof.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.index.is_none() && self.claim_id.is_none()
+    }
+
+    /// Whether the proof at `index` of its pool, proving `claim_id`, is selected.
+    #[must_use]
+    pub fn matches(&self, index: usize, claim_id: &[u8; 32]) -> bool {
+        self.index.is_none_or(|selected| selected == index) &&
+            self.claim_id.is_none_or(|selected| &selected == claim_id)
+    }
+}
+
+/// Verdict on a single claim proof.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofVerdict {
+    /// Pool of the proof.
+    pub pool: Pool,
+    /// Position of the proof in its pool.
+    pub index: usize,
+    /// Stable identifier of the claim.
+    #[serde_as(as = "Hex")]
+    pub claim_id: [u8; 32],
+    /// The airdrop nullifier of the claim.
+    pub airdrop_nullifier: Nullifier,
+    /// Whether the proof verified.
+    pub valid: bool,
+    /// Why the proof did not verify.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ProofVerdict {
+    fn new(
+        pool: Pool,
+        index: usize,
+        claim_id: [u8; 32],
+        airdrop_nullifier: Nullifier,
+        result: Result<(), String>,
+    ) -> Self {
+        match &result {
+            Ok(()) => info!(index, %airdrop_nullifier, "{pool:?} proof VALID"),
+            Err(error) => warn!(index, %airdrop_nullifier, %error, "{pool:?} proof INVALID"),
+        }
+        Self {
+            pool,
+            index,
+            claim_id,
+            airdrop_nullifier,
+            valid: result.is_ok(),
+            reason: result.err(),
+        }
+    }
+}
+
+/// Log the verdict counts and fail if any proof did not verify.
+///
+/// # Errors
+/// Returns an error naming the number of invalid proofs.
+pub fn ensure_all_valid(verdicts: &[ProofVerdict]) -> eyre::Result<()> {
+    let count = |pool: Pool, valid: bool| {
+        verdicts
+            .iter()
+            .filter(|verdict| verdict.pool == pool && verdict.valid == valid)
+            .count()
+    };
+    let sapling_valid = count(Pool::Sapling, true);
+    let sapling_invalid = count(Pool::Sapling, false);
+    let orchard_valid = count(Pool::Orchard, true);
+    let orchard_invalid = count(Pool::Orchard, false);
+    let total = verdicts.len();
+    let invalid_total = sapling_invalid.saturating_add(orchard_invalid);
+    info!(
+        sapling_valid,
+        sapling_invalid, orchard_valid, orchard_invalid, total, "Verification complete"
+    );
+    ensure!(
+        invalid_total == 0,
+        "{invalid_total} proofs failed verification"
+    );
+    info!("All {total} claim proofs are VALID");
+    Ok(())
+}
+
+/// Verify the claim proofs of a proofs file (output of prove) and report a verdict per proof.
+///
+/// Invalid proofs are reported in their verdicts rather than as an error; use
+/// [`ensure_all_valid`] to fail on them.
 ///
 /// # Arguments
 ///
 /// * `proofs_file` - Path to JSON file containing the proofs (`ClaimProofsOutput` format)
 /// * `verifying_key_file` - Path to the verifying key file
 /// * `airdrop_configuration_file` - Airdrop configuration used to bind expected anchors/scheme
+/// * `pool` - Pools whose proofs are verified; the proofs of other pools are skipped
+/// * `filter` - Proofs to verify by index or claim ID; the others are skipped
 ///
 /// # Errors
-/// Returns an error if file I/O, parsing, or proof verification fails.
+/// Returns an error if file I/O, parsing, or setup loading fails, or no proof is selected.
 #[allow(
-    clippy::too_many_lines,
-    reason = "End-to-end verification flow performs config binding, key loading, and batch checks"
+    clippy::too_many_arguments,
+    reason = "Verification takes the setup files and the proof selection explicitly"
 )]
 pub async fn verify_claim_proofs(
     proofs_file: PathBuf,
@@ -138,31 +371,54 @@ pub async fn verify_claim_proofs(
     orchard_params_file: PathBuf,
     orchard_params_mode: OrchardParamsMode,
     airdrop_configuration_file: PathBuf,
-) -> eyre::Result<()> {
+    pool: PoolSelection,
+    filter: ProofFilter,
+) -> ZairResult<Vec<ProofVerdict>> {
+    verify_claim_proofs_file(
+        proofs_file,
+        verifying_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+        airdrop_configuration_file,
+        pool,
+        filter,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+async fn verify_claim_proofs_file(
+    proofs_file: PathBuf,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    airdrop_configuration_file: PathBuf,
+    pool: PoolSelection,
+    filter: ProofFilter,
+) -> eyre::Result<Vec<ProofVerdict>> {
     info!(file = ?proofs_file, "Loading claim proofs for verification...");
 
     // Load proofs from JSON (ClaimProofsOutput format from prove)
-    let proofs: ClaimProofsOutput =
-        serde_json::from_str(&tokio::fs::read_to_string(&proofs_file).await?)
+    let mut proofs: ClaimProofsOutput =
+        serde_json::from_str(&stdio::read_to_string(&proofs_file).await?)
             .context("Failed to parse proofs JSON")?;
-    verify_claim_proofs_inner(
+    proofs.retain_pools(pool);
+    verify_claim_proof_verdicts(
         proofs,
         verifying_key_file,
         orchard_params_file,
         orchard_params_mode,
         airdrop_configuration_file,
+        filter,
     )
     .await
 }
 
-/// Verify all Sapling claim proofs from an in-memory `ClaimProofsOutput`.
+/// Verify claim proofs from an in-memory `ClaimProofsOutput`, loading the setup artifacts from
+/// disk.
 ///
 /// # Errors
 /// Returns an error if parsing, key loading, or proof verification fails.
-#[allow(
-    clippy::too_many_lines,
-    reason = "End-to-end verification flow performs config binding, key loading, and batch checks"
-)]
 pub(super) async fn verify_claim_proofs_inner(
     proofs: ClaimProofsOutput,
     verifying_key_file: PathBuf,
@@ -170,19 +426,171 @@ pub(super) async fn verify_claim_proofs_inner(
     orchard_params_mode: OrchardParamsMode,
     airdrop_configuration_file: PathBuf,
 ) -> eyre::Result<()> {
+    let verdicts = verify_claim_proof_verdicts(
+        proofs,
+        verifying_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+        airdrop_configuration_file,
+        ProofFilter::default(),
+    )
+    .await?;
+    ensure_all_valid(&verdicts)
+}
+
+/// Verify the claim proofs selected by `filter`, loading the setup artifacts from disk, and
+/// report a verdict per proof.
+///
+/// # Errors
+/// Returns an error if parsing or setup loading fails, or no proof is selected.
+pub(super) async fn verify_claim_proof_verdicts(
+    proofs: ClaimProofsOutput,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    airdrop_configuration_file: PathBuf,
+    filter: ProofFilter,
+) -> eyre::Result<Vec<ProofVerdict>> {
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+
+    let sapling_verifying_key = if !proofs.sapling_proofs.is_empty() &&
+        airdrop_config.sapling.is_some()
+    {
+        eyre::ensure!(
+            tokio::fs::try_exists(&verifying_key_file).await?,
+            "Verifying key not found at {}. Run `zair setup sapling --scheme native` or `zair setup sapling --scheme sha256` (matching the airdrop configuration scheme) and use the generated verifying key path.",
+            verifying_key_file.display(),
+        );
+        let bytes = tokio::fs::read(&verifying_key_file).await?;
+        Some(
+            bellman::groth16::VerifyingKey::read(&bytes[..])
+                .context("Failed to read verifying key")?,
+        )
+    } else {
+        None
+    };
+
+    let orchard_scheme = airdrop_config
+        .for_epoch(proofs.epoch)?
+        .orchard
+        .as_ref()
+        .map(|orchard| OrchardValueCommitmentScheme::from(orchard.value_commitment_scheme));
+    let orchard_params = match orchard_scheme {
+        Some(scheme)
+            if proofs
+                .orchard_proofs
+                .iter()
+                .any(|proof_result| orchard_proof_matches_scheme(proof_result, scheme)) =>
+        {
+            Some(
+                load_or_prepare_orchard_params_inner(
+                    orchard_params_file,
+                    scheme,
+                    orchard_params_mode,
+                )
+                .await?,
+            )
+        }
+        Some(_) | None => None,
+    };
+
+    verify_proof_verdicts(
+        proofs,
+        &airdrop_config,
+        &VerifyingParams {
+            sapling_verifying_key,
+            orchard_params,
+        },
+        filter,
+    )
+    .await
+}
+
+/// Whether the commitment fields of an Orchard proof match the configured scheme.
+const fn orchard_proof_matches_scheme(
+    proof_result: &OrchardClaimProofResult,
+    scheme: OrchardValueCommitmentScheme,
+) -> bool {
+    match scheme {
+        OrchardValueCommitmentScheme::Native => {
+            proof_result.cv.is_some() &&
+                proof_result.cv_sha256.is_none() &&
+                proof_result.cv_poseidon.is_none()
+        }
+        OrchardValueCommitmentScheme::Sha256 => {
+            proof_result.cv.is_none() &&
+                proof_result.cv_sha256.is_some() &&
+                proof_result.cv_poseidon.is_none()
+        }
+        OrchardValueCommitmentScheme::Poseidon => {
+            proof_result.cv.is_none() &&
+                proof_result.cv_sha256.is_none() &&
+                proof_result.cv_poseidon.is_some()
+        }
+    }
+}
+
+/// Verify in-memory claim proofs against the airdrop configuration and setup artifacts.
+///
+/// # Errors
+/// Returns an error if the configuration or setup artifacts do not match the proofs, or any
+/// proof is invalid.
+pub async fn verify_proofs_inner(
+    proofs: ClaimProofsOutput,
+    airdrop_config: &AirdropConfiguration,
+    setup: &VerifyingParams,
+) -> eyre::Result<()> {
+    let verdicts =
+        verify_proof_verdicts(proofs, airdrop_config, setup, ProofFilter::default()).await?;
+    ensure_all_valid(&verdicts)
+}
+
+/// Verify the claim proofs selected by `filter` and report a verdict per proof.
+#[allow(
+    clippy::too_many_lines,
+    reason = "Verification flow performs config binding, fingerprint and batch checks"
+)]
+async fn verify_proof_verdicts(
+    proofs: ClaimProofsOutput,
+    airdrop_config: &AirdropConfiguration,
+    setup: &VerifyingParams,
+    filter: ProofFilter,
+) -> eyre::Result<Vec<ProofVerdict>> {
+    airdrop_config.circuit.ensure_supported()?;
     let ClaimProofsOutput {
         sapling_proofs,
         orchard_proofs,
+        failed_claims,
+        epoch,
     } = proofs;
+    let airdrop_config = &airdrop_config.for_epoch(epoch)?;
+    if !failed_claims.is_empty() {
+        warn!(
+            count = failed_claims.len(),
+            "Proofs file lists claims that failed to prove; they are not verified"
+        );
+    }
 
     ensure!(
         !(sapling_proofs.is_empty() && orchard_proofs.is_empty()),
         "No proofs found to verify"
     );
-
-    let airdrop_config: AirdropConfiguration =
-        serde_json::from_str(&tokio::fs::read_to_string(&airdrop_configuration_file).await?)
-            .context("Failed to parse airdrop configuration JSON")?;
+    let sapling_proofs: Vec<_> = sapling_proofs
+        .into_iter()
+        .enumerate()
+        .filter(|(index, proof_result)| filter.matches(*index, &proof_result.claim_id))
+        .collect();
+    let orchard_proofs: Vec<_> = orchard_proofs
+        .into_iter()
+        .enumerate()
+        .filter(|(index, proof_result)| filter.matches(*index, &proof_result.claim_id))
+        .collect();
+    ensure!(
+        !(sapling_proofs.is_empty() && orchard_proofs.is_empty()),
+        "No proof matches the selected index or claim ID"
+    );
 
     let sapling_ctx = if sapling_proofs.is_empty() {
         None
@@ -192,9 +600,10 @@ pub(super) async fn verify_claim_proofs_inner(
             .as_ref()
             .context("Sapling proofs provided, but airdrop configuration has no sapling pool")?;
         Some((
-            sapling.value_commitment_scheme.into(),
+            SaplingValueCommitmentScheme::try_from(sapling.value_commitment_scheme)?,
             sapling.note_commitment_root,
             sapling.nullifier_gap_root,
+            sapling.verifying_key_fingerprint,
         ))
     };
 
@@ -205,16 +614,13 @@ pub(super) async fn verify_claim_proofs_inner(
             .orchard
             .as_ref()
             .context("Orchard proofs provided, but airdrop configuration has no orchard pool")?;
-        ensure!(
-            orchard.target_id.len() <= 32,
-            "Orchard target_id must be at most 32 bytes"
-        );
         let scheme = orchard.value_commitment_scheme.into();
         Some((
             scheme,
             orchard.note_commitment_root,
             orchard.nullifier_gap_root,
             orchard.target_id.clone(),
+            orchard.params_fingerprint,
         ))
     };
 
@@ -224,181 +630,134 @@ pub(super) async fn verify_claim_proofs_inner(
         "Proofs loaded, starting verification..."
     );
 
-    let (sapling_valid, sapling_invalid) = if let Some((
+    let mut verdicts = if let Some((
         sapling_scheme,
         note_commitment_root,
         nullifier_gap_root,
+        verifying_key_fingerprint,
     )) = sapling_ctx
     {
-        eyre::ensure!(
-            tokio::fs::try_exists(&verifying_key_file).await?,
-            "Verifying key not found at {}. Run `zair setup sapling --scheme native` or `zair setup sapling --scheme sha256` (matching the airdrop configuration scheme) and use the generated verifying key path.",
-            verifying_key_file.display(),
-        );
-
-        let bytes = tokio::fs::read(&verifying_key_file).await?;
-        let vk = bellman::groth16::VerifyingKey::read(&bytes[..])
-            .context("Failed to read verifying key")?;
-        let pvk = bellman::groth16::prepare_verifying_key(&vk);
+        let vk = setup
+            .sapling_verifying_key
+            .as_ref()
+            .context("Sapling proofs provided, but no Sapling verifying key was supplied")?;
+        if verifying_key_fingerprint.is_some() {
+            ensure_fingerprint_matches(
+                "Sapling verifying key",
+                verifying_key_fingerprint,
+                sapling_vk_fingerprint(vk)?,
+            )?;
+        }
+        let pvk = bellman::groth16::prepare_verifying_key(vk);
 
         tokio::task::spawn_blocking(move || {
-            let mut valid = 0_usize;
-            let mut invalid = 0_usize;
-            for (index, proof_result) in sapling_proofs.iter().enumerate() {
-                let airdrop_nullifier: [u8; 32] = proof_result.airdrop_nullifier.into();
-                match verify_claim_proof_bytes(
-                    &pvk,
-                    &proof_result.zkproof,
-                    sapling_scheme,
-                    &proof_result.rk,
-                    proof_result.cv.as_ref(),
-                    proof_result.cv_sha256.as_ref(),
-                    &note_commitment_root,
-                    &airdrop_nullifier,
-                    &nullifier_gap_root,
-                ) {
-                    Ok(()) => {
-                        info!(
-                            index,
-                            airdrop_nullifier = %proof_result.airdrop_nullifier,
-                            "Sapling proof VALID"
-                        );
-                        valid = valid.saturating_add(1);
-                    }
-                    Err(error) => {
-                        warn!(
-                            index,
-                            airdrop_nullifier = %proof_result.airdrop_nullifier,
-                            %error,
-                            "Sapling proof INVALID"
-                        );
-                        invalid = invalid.saturating_add(1);
-                    }
-                }
-            }
-            (valid, invalid)
+            sapling_proofs
+                .iter()
+                .map(|(index, proof_result)| {
+                    let airdrop_nullifier: [u8; 32] = proof_result.airdrop_nullifier.into();
+                    let result = verify_claim_proof_bytes(
+                        &pvk,
+                        &proof_result.zkproof,
+                        sapling_scheme,
+                        &proof_result.rk,
+                        proof_result.cv.as_ref(),
+                        proof_result.cv_sha256.as_ref(),
+                        &note_commitment_root,
+                        &airdrop_nullifier,
+                        &nullifier_gap_root,
+                    )
+                    .map_err(|error| error.to_string());
+                    ProofVerdict::new(
+                        Pool::Sapling,
+                        *index,
+                        proof_result.claim_id,
+                        proof_result.airdrop_nullifier,
+                        result,
+                    )
+                })
+                .collect::<Vec<_>>()
         })
         .await?
     } else {
-        (0, 0)
+        Vec::new()
     };
 
-    let (orchard_valid, orchard_invalid) =
-        if let Some((orchard_scheme, note_commitment_root, nullifier_gap_root, target_id)) =
-            orchard_ctx
-        {
-            let needs_halo2 = orchard_proofs
-                .iter()
-                .any(|proof_result| match orchard_scheme {
-                    OrchardValueCommitmentScheme::Native => {
-                        proof_result.cv.is_some() && proof_result.cv_sha256.is_none()
-                    }
-                    OrchardValueCommitmentScheme::Sha256 => {
-                        proof_result.cv.is_none() && proof_result.cv_sha256.is_some()
-                    }
-                });
-            let params = if needs_halo2 {
-                Some(
-                    load_or_prepare_orchard_params(
-                        orchard_params_file,
-                        orchard_scheme,
-                        orchard_params_mode,
-                    )
-                    .await?,
-                )
-            } else {
-                None
-            };
-            tokio::task::spawn_blocking(move || {
-            let mut valid = 0_usize;
-            let mut invalid = 0_usize;
-            for (index, proof_result) in orchard_proofs.iter().enumerate() {
-                let scheme_ok = match orchard_scheme {
-                    OrchardValueCommitmentScheme::Native => {
-                        proof_result.cv.is_some() && proof_result.cv_sha256.is_none()
-                    }
-                    OrchardValueCommitmentScheme::Sha256 => {
-                        proof_result.cv.is_none() && proof_result.cv_sha256.is_some()
-                    }
-                };
-                if !scheme_ok {
-                    warn!(
-                        index,
-                        airdrop_nullifier = %proof_result.airdrop_nullifier,
-                        "Orchard proof commitment fields are incompatible with configured scheme"
-                    );
-                    invalid = invalid.saturating_add(1);
-                    continue;
-                }
-
-                let output = OrchardClaimProofOutput {
-                    zkproof: proof_result.zkproof.clone(),
-                    rk: proof_result.rk,
-                    cv: proof_result.cv,
-                    cv_sha256: proof_result.cv_sha256,
-                    airdrop_nullifier: proof_result.airdrop_nullifier.into(),
-                };
-
-                let Some(ref halo2_params) = params else {
-                    warn!(
-                        index,
-                        airdrop_nullifier = %proof_result.airdrop_nullifier,
-                        "Orchard params not loaded but proof requires halo2 verification"
-                    );
-                    invalid = invalid.saturating_add(1);
-                    continue;
-                };
-
-                match verify_orchard_claim_proof_output(
-                    halo2_params.as_ref(),
-                    &output,
-                    note_commitment_root,
-                    nullifier_gap_root,
-                    orchard_scheme,
-                    target_id.as_bytes(),
-                ) {
-                    Ok(()) => {
-                        info!(
-                            index,
-                            airdrop_nullifier = %proof_result.airdrop_nullifier,
-                            "Orchard proof VALID"
-                        );
-                        valid = valid.saturating_add(1);
-                    }
-                    Err(error) => {
-                        warn!(
-                            index,
-                            airdrop_nullifier = %proof_result.airdrop_nullifier,
-                            %error,
-                            "Orchard proof INVALID"
-                        );
-                        invalid = invalid.saturating_add(1);
-                    }
-                }
+    if let Some((
+        orchard_scheme,
+        note_commitment_root,
+        nullifier_gap_root,
+        target_id,
+        params_fingerprint,
+    )) = orchard_ctx
+    {
+        let needs_halo2 = orchard_proofs
+            .iter()
+            .any(|(_, proof_result)| orchard_proof_matches_scheme(proof_result, orchard_scheme));
+        let params = if needs_halo2 {
+            let params = setup
+                .orchard_params
+                .clone()
+                .context("Orchard proofs provided, but no Orchard params were supplied")?;
+            if params_fingerprint.is_some() {
+                ensure_fingerprint_matches(
+                    "Orchard params",
+                    params_fingerprint,
+                    orchard_params_fingerprint(params.as_ref())?,
+                )?;
             }
-            (valid, invalid)
-        })
-        .await?
+            Some(params)
         } else {
-            (0, 0)
+            None
         };
+        let orchard_verdicts = tokio::task::spawn_blocking(move || {
+            orchard_proofs
+                .iter()
+                .map(|(index, proof_result)| {
+                    let result = if !orchard_proof_matches_scheme(proof_result, orchard_scheme) {
+                        Err(
+                            "Orchard proof commitment fields are incompatible with configured \
+                             scheme"
+                                .to_owned(),
+                        )
+                    } else if let Some(halo2_params) = &params {
+                        let output = OrchardClaimProofOutput {
+                            zkproof: proof_result.zkproof.clone(),
+                            rk: proof_result.rk,
+                            cv: proof_result.cv,
+                            cv_sha256: proof_result.cv_sha256,
+                            cv_poseidon: proof_result.cv_poseidon,
+                            airdrop_nullifier: proof_result.airdrop_nullifier.into(),
+                        };
+                        verify_orchard_claim_proof_output(
+                            halo2_params.as_ref(),
+                            &output,
+                            note_commitment_root,
+                            nullifier_gap_root,
+                            orchard_scheme,
+                            target_id.as_bytes(),
+                        )
+                        .map_err(|error| error.to_string())
+                    } else {
+                        Err(
+                            "Orchard params not loaded but proof requires halo2 verification"
+                                .to_owned(),
+                        )
+                    };
+                    ProofVerdict::new(
+                        Pool::Orchard,
+                        *index,
+                        proof_result.claim_id,
+                        proof_result.airdrop_nullifier,
+                        result,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .await?;
+        verdicts.extend(orchard_verdicts);
+    }
 
-    let total = sapling_valid
-        .saturating_add(sapling_invalid)
-        .saturating_add(orchard_valid)
-        .saturating_add(orchard_invalid);
-    let invalid_total = sapling_invalid.saturating_add(orchard_invalid);
-    info!(
-        sapling_valid,
-        sapling_invalid, orchard_valid, orchard_invalid, total, "Verification complete"
-    );
-    ensure!(
-        invalid_total == 0,
-        "{invalid_total} proofs failed verification"
-    );
-    info!("All {total} claim proofs are VALID");
-
-    Ok(())
+    Ok(verdicts)
 }
 
 #[cfg(test)]
@@ -407,8 +766,10 @@ mod tests {
 
     use serde::Serialize;
     use tempfile::tempdir;
+    use zair_core::base::TargetId;
     use zair_core::schema::config::{
-        AirdropConfiguration, AirdropNetwork, OrchardSnapshot, ValueCommitmentScheme,
+        AirdropConfiguration, AirdropNetwork, CircuitConfiguration, OrchardSnapshot, SproutPolicy,
+        ValueCommitmentScheme,
     };
 
     use super::*;
@@ -429,22 +790,81 @@ mod tests {
             orchard: Some(OrchardSnapshot {
                 note_commitment_root: [0_u8; 32],
                 nullifier_gap_root: [0_u8; 32],
-                target_id: target_id.to_owned(),
+                target_id: TargetId::new(Pool::Orchard, target_id).expect("valid target ID"),
                 value_commitment_scheme,
+                params_fingerprint: None,
             }),
+            sprout: SproutPolicy::Excluded,
+            transparent: None,
+            min_note_value: None,
+            max_claims_per_submission: None,
+            circuit: CircuitConfiguration::default(),
+            network_upgrades: None,
+            claim_start_height: None,
+            claim_expiry_height: None,
+            countersigners: Vec::new(),
+            chain_id: None,
+            epochs: Vec::new(),
         }
     }
 
     fn sample_orchard_proof_native_shape() -> OrchardClaimProofResult {
         OrchardClaimProofResult {
+            claim_id: [0_u8; 32],
             zkproof: vec![1_u8, 2_u8, 3_u8],
             rk: [7_u8; 32],
             cv: Some([9_u8; 32]),
             cv_sha256: None,
+            cv_poseidon: None,
             airdrop_nullifier: Nullifier::from([11_u8; 32]),
         }
     }
 
+    #[test]
+    fn proofs_output_fails_only_when_every_claim_failed() {
+        let failure = FailedClaimProof {
+            claim_id: [1_u8; 32],
+            pool: Pool::Orchard,
+            airdrop_nullifier: Nullifier::from([2_u8; 32]),
+            reason: "Orchard Merkle path is malformed".to_owned(),
+        };
+        let mut proofs = ClaimProofsOutput {
+            sapling_proofs: vec![],
+            orchard_proofs: vec![sample_orchard_proof_native_shape()],
+            failed_claims: vec![failure],
+            epoch: None,
+        };
+        proofs
+            .ensure_any_proven()
+            .expect("one claim was still proven");
+
+        let json = serde_json::to_value(&proofs).expect("serialize proofs");
+        assert_eq!(json["failed_claims"][0]["pool"], "orchard");
+
+        proofs.orchard_proofs.clear();
+        let err = proofs.ensure_any_proven().expect_err("no claim was proven");
+        assert!(err.to_string().contains("malformed"), "{err:?}");
+    }
+
+    #[test]
+    fn proof_filter_selects_by_index_and_claim_id() {
+        assert!(ProofFilter::default().matches(3, &[1_u8; 32]));
+
+        let by_index = ProofFilter {
+            index: Some(3),
+            claim_id: None,
+        };
+        assert!(by_index.matches(3, &[1_u8; 32]));
+        assert!(!by_index.matches(2, &[1_u8; 32]));
+
+        let by_both = ProofFilter {
+            index: Some(3),
+            claim_id: Some([1_u8; 32]),
+        };
+        assert!(by_both.matches(3, &[1_u8; 32]));
+        assert!(!by_both.matches(3, &[2_u8; 32]));
+    }
+
     #[tokio::test]
     async fn verify_allows_orchard_sha256_target_id_up_to_32_bytes() {
         let dir = tempdir().expect("tempdir");
@@ -457,6 +877,8 @@ mod tests {
         let proofs = ClaimProofsOutput {
             sapling_proofs: vec![],
             orchard_proofs: vec![sample_orchard_proof_native_shape()],
+            failed_claims: Vec::new(),
+            epoch: None,
         };
 
         let err = verify_claim_proofs_inner(
@@ -474,6 +896,36 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn verify_rejects_configuration_for_other_circuit() {
+        let dir = tempdir().expect("tempdir");
+        let config_path = dir.path().join("config.json");
+        let mut config = orchard_only_config("ZAIRTEST", ValueCommitmentScheme::Native);
+        config.circuit.nullifier_gap_tree_depth = 16;
+        write_json(&config_path, &config);
+
+        let proofs = ClaimProofsOutput {
+            sapling_proofs: vec![],
+            orchard_proofs: vec![sample_orchard_proof_native_shape()],
+            failed_claims: Vec::new(),
+            epoch: None,
+        };
+
+        let err = verify_claim_proofs_inner(
+            proofs,
+            Path::new("unused").into(),
+            Path::new("unused").into(),
+            OrchardParamsMode::Require,
+            config_path,
+        )
+        .await
+        .expect_err("verification must fail for an unsupported circuit configuration");
+        assert!(
+            err.to_string().contains("nullifier gap tree depth 16"),
+            "{err:?}"
+        );
+    }
+
     #[tokio::test]
     async fn verify_rejects_orchard_proof_shape_mismatch_for_configured_scheme() {
         let dir = tempdir().expect("tempdir");
@@ -490,6 +942,8 @@ mod tests {
                 cv_sha256: Some([5_u8; 32]),
                 ..sample_orchard_proof_native_shape()
             }],
+            failed_claims: Vec::new(),
+            epoch: None,
         };
 
         let err = verify_claim_proofs_inner(
@@ -518,6 +972,7 @@ mod tests {
 
         let proofs = ClaimProofsOutput {
             sapling_proofs: vec![SaplingClaimProofResult {
+                claim_id: [0_u8; 32],
                 zkproof: [1_u8; 192],
                 rk: [2_u8; 32],
                 cv: Some([3_u8; 32]),
@@ -525,6 +980,8 @@ mod tests {
                 airdrop_nullifier: Nullifier::from([4_u8; 32]),
             }],
             orchard_proofs: vec![],
+            failed_claims: Vec::new(),
+            epoch: None,
         };
 
         let err = verify_claim_proofs_inner(
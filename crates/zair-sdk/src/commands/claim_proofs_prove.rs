@@ -2,6 +2,7 @@
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use bellman::groth16::PreparedVerifyingKey;
 use bls12_381::Bls12;
@@ -9,14 +10,12 @@ use eyre::{Context as _, ContextCompat as _, ensure};
 use group::GroupEncoding as _;
 use group::ff::{Field as _, FromUniformBytes as _, PrimeField as _};
 use halo2_proofs::poly::commitment::Params;
-use orchard::keys::{
-    FullViewingKey as OrchardFullViewingKey, SpendAuthorizingKey, SpendValidatingKey,
-};
+use orchard::keys::FullViewingKey as OrchardFullViewingKey;
 use orchard::value::ValueCommitTrapdoor as OrchardValueCommitTrapdoor;
 use pasta_curves::{pallas, vesta};
-use secrecy::ExposeSecret;
-use tracing::info;
-use zair_core::base::Nullifier;
+use rand_core::RngCore;
+use tracing::{info, warn};
+use zair_core::base::{Nullifier, Pool, TargetId};
 use zair_core::schema::config::{AirdropConfiguration, ValueCommitmentScheme};
 use zair_core::schema::proof_inputs::{
     AirdropClaimInputs, ClaimInput, OrchardPrivateInputs, SaplingPrivateInputs, SerializableScope,
@@ -24,27 +23,39 @@ use zair_core::schema::proof_inputs::{
 use zair_orchard_proofs::{
     ClaimProofInputs as OrchardClaimProofInputs,
     ValueCommitmentScheme as OrchardValueCommitmentScheme,
-    generate_claim_proof as generate_orchard_claim_proof,
+    generate_claim_proof_with_rng as generate_orchard_claim_proof,
     verify_claim_proof_output as verify_orchard_claim_proof_output,
 };
 use zair_sapling_proofs::prover::{
     ClaimParameters, ClaimProofInputs, ValueCommitmentScheme as SaplingValueCommitmentScheme,
-    generate_claim_proof, generate_parameters, load_parameters, save_parameters,
+    diagnose_claim_proof, generate_claim_proof_with_rng, generate_parameters, load_parameters,
+    save_parameters,
 };
 use zair_sapling_proofs::verifier::{ClaimProofOutput, verify_claim_proof_output};
-use zcash_keys::keys::UnifiedSpendingKey;
-use zcash_protocol::consensus::Network;
+use zair_scan::{Network, SaplingViewingKeys};
+use zcash_keys::keys::{UnifiedFullViewingKey, UnifiedSpendingKey};
 use zcash_spec::PrfExpand;
-use zip32::AccountId;
 
+use super::claim_pools::PoolEntries as _;
 use super::claim_proofs::{
-    ClaimProofsOutput, ClaimSecretsOutput, OrchardClaimProofResult, OrchardClaimSecretResult,
-    SaplingClaimProofResult, SaplingClaimSecretResult,
+    ClaimProofsOutput, ClaimSecretsOutput, FailedClaimProof, OrchardClaimProofResult,
+    OrchardClaimSecretResult, SaplingClaimProofResult, SaplingClaimSecretResult, ValueBlinding,
 };
-use super::orchard_params::{OrchardParamsMode, load_or_prepare_orchard_params};
+use super::claim_signing_request::write_signing_request;
+use super::key::ProvingKeyFile;
+use super::key_fingerprint::ensure_same_wallet;
+use super::note_positions::verify_sapling_note_positions;
+use super::orchard_params::{OrchardParamsMode, load_or_prepare_orchard_params_inner};
+use super::proof_rng::ProofRngSource;
 use super::sensitive_output::write_sensitive_output;
-use crate::common::to_zcash_network;
-use crate::seed::read_seed_file;
+use super::setup_fingerprint::{SetupArtifact, bind_fingerprint, read_sapling_vk_fingerprint};
+use super::witness_check::{check_orchard_witness, check_sapling_witness};
+use crate::build_info::to_artifact_json;
+use crate::common::{PoolSelection, configured_network};
+use crate::error::{ZairError, ZairResult};
+use crate::pipeline::ProvingParams;
+use crate::seed::read_account_secret;
+use crate::{paths, stdio, telemetry};
 
 /// Maximum number of concurrent outer Sapling proving tasks.
 ///
@@ -52,22 +63,115 @@ use crate::seed::read_seed_file;
 /// outer task fan-out is intentionally conservative to avoid oversubscription.
 const MAX_SAPLING_PROVING_TASKS: usize = 2;
 
+/// Interval between elapsed-time logs while a Sapling parameter set is generated.
+const SETUP_ELAPSED_LOG_INTERVAL: Duration = Duration::from_secs(30);
+
 fn sapling_proving_task_limit() -> usize {
     std::thread::available_parallelism()
         .map_or(1, usize::from)
         .min(MAX_SAPLING_PROVING_TASKS)
 }
 
+/// Caller-generated witness randomness for one claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ClaimRandomness {
+    alpha: [u8; 32],
+    rcv: [u8; 32],
+    rcv_sha256: Option<[u8; 32]>,
+    rcv_poseidon: Option<[u8; 32]>,
+}
+
+impl ClaimRandomness {
+    /// Opening of the `scheme` value commitment of a claim worth `value`.
+    fn value_blinding(
+        &self,
+        scheme: ValueCommitmentScheme,
+        value: u64,
+    ) -> eyre::Result<ValueBlinding> {
+        let blinding = match scheme {
+            ValueCommitmentScheme::Native => Some(self.rcv),
+            ValueCommitmentScheme::Sha256 => self.rcv_sha256,
+            ValueCommitmentScheme::Poseidon => self.rcv_poseidon,
+        }
+        .with_context(|| format!("No {scheme:?} commitment randomness was drawn"))?;
+        Ok(ValueBlinding {
+            scheme,
+            value,
+            blinding,
+        })
+    }
+}
+
+/// Draw the Sapling witness randomness for one claim, in a fixed order.
+fn sample_sapling_randomness<R: RngCore>(
+    rng: &mut R,
+    scheme: SaplingValueCommitmentScheme,
+) -> ClaimRandomness {
+    let alpha = jubjub::Fr::random(&mut *rng).to_repr();
+    let rcv = sapling::value::ValueCommitTrapdoor::random(&mut *rng)
+        .inner()
+        .to_repr();
+    let rcv_sha256 = match scheme {
+        SaplingValueCommitmentScheme::Native => None,
+        SaplingValueCommitmentScheme::Sha256 => {
+            let mut rcv_sha256 = [0_u8; 32];
+            rng.fill_bytes(&mut rcv_sha256);
+            Some(rcv_sha256)
+        }
+    };
+    ClaimRandomness {
+        alpha,
+        rcv,
+        rcv_sha256,
+        rcv_poseidon: None,
+    }
+}
+
+/// Draw the Orchard witness randomness for one claim, in a fixed order.
+fn sample_orchard_randomness<R: RngCore>(
+    rng: &mut R,
+    scheme: OrchardValueCommitmentScheme,
+) -> ClaimRandomness {
+    let alpha = pallas::Scalar::random(&mut *rng).to_repr();
+    let rcv = loop {
+        let mut b = [0_u8; 32];
+        rng.fill_bytes(&mut b);
+        if Option::<OrchardValueCommitTrapdoor>::from(OrchardValueCommitTrapdoor::from_bytes(b))
+            .is_some()
+        {
+            break b;
+        }
+    };
+    let rcv_sha256 = match scheme {
+        OrchardValueCommitmentScheme::Native | OrchardValueCommitmentScheme::Poseidon => None,
+        OrchardValueCommitmentScheme::Sha256 => {
+            let mut bytes = [0_u8; 32];
+            rng.fill_bytes(&mut bytes);
+            Some(bytes)
+        }
+    };
+    let rcv_poseidon = match scheme {
+        OrchardValueCommitmentScheme::Native | OrchardValueCommitmentScheme::Sha256 => None,
+        OrchardValueCommitmentScheme::Poseidon => Some(pallas::Base::random(&mut *rng).to_repr()),
+    };
+    ClaimRandomness {
+        alpha,
+        rcv,
+        rcv_sha256,
+        rcv_poseidon,
+    }
+}
+
 fn setup_targets(
     proving_key_file: &Path,
     verifying_key_file: &Path,
     scheme: ValueCommitmentScheme,
-) -> Vec<(SaplingValueCommitmentScheme, PathBuf, PathBuf)> {
-    vec![(
-        SaplingValueCommitmentScheme::from(scheme),
+) -> eyre::Result<Vec<(SaplingValueCommitmentScheme, PathBuf, PathBuf)>> {
+    Ok(vec![(
+        SaplingValueCommitmentScheme::try_from(scheme)?,
         proving_key_file.to_path_buf(),
         verifying_key_file.to_path_buf(),
-    )]
+    )])
 }
 
 /// Generate or load the claim circuit parameters with custom paths.
@@ -93,18 +197,36 @@ async fn load_params(proving_key_path: PathBuf) -> eyre::Result<ClaimParameters>
 ///
 /// * `proving_key_file` - Path to write the proving key
 /// * `verifying_key_file` - Path to write the verifying key
+/// * `bind_configuration_file` - Optional airdrop configuration to bind the verifying key into
 ///
 /// # Errors
-/// Returns an error if parameter generation or file I/O fails.
+/// Returns an error if parameter generation, binding, or file I/O fails.
 pub async fn generate_claim_params(
     proving_key_file: PathBuf,
     verifying_key_file: PathBuf,
     scheme: ValueCommitmentScheme,
+    bind_configuration_file: Option<PathBuf>,
+) -> ZairResult<()> {
+    generate_claim_params_inner(
+        proving_key_file,
+        verifying_key_file,
+        scheme,
+        bind_configuration_file,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+async fn generate_claim_params_inner(
+    proving_key_file: PathBuf,
+    verifying_key_file: PathBuf,
+    scheme: ValueCommitmentScheme,
+    bind_configuration_file: Option<PathBuf>,
 ) -> eyre::Result<()> {
     info!("Generating claim circuit parameters...");
     info!("This creates Groth16 proving and verifying keys for the Sapling claim circuit.");
 
-    let targets = setup_targets(&proving_key_file, &verifying_key_file, scheme);
+    let targets = setup_targets(&proving_key_file, &verifying_key_file, scheme)?;
     for (scheme, proving_key_path, verifying_key_path) in targets {
         info!(
             scheme = ?scheme,
@@ -113,17 +235,21 @@ pub async fn generate_claim_params(
             "Generating parameter set"
         );
 
-        let params = tokio::task::spawn_blocking(move || generate_parameters(scheme))
-            .await?
-            .map_err(|e| eyre::eyre!("Parameter generation failed for {:?}: {e}", scheme))?;
+        let params = generate_parameters_logging_elapsed(scheme).await?;
 
+        paths::ensure_parent_dir(&proving_key_path).await?;
+        paths::ensure_parent_dir(&verifying_key_path).await?;
+        let proving_partial = paths::begin_partial(&proving_key_path);
+        let verifying_partial = paths::begin_partial(&verifying_key_path);
         tokio::task::spawn_blocking({
-            let proving_key_path = proving_key_path.clone();
-            let verifying_key_path = verifying_key_path.clone();
-            move || save_parameters(&params, &proving_key_path, &verifying_key_path)
+            let proving_partial = proving_partial.clone();
+            let verifying_partial = verifying_partial.clone();
+            move || save_parameters(&params, &proving_partial, &verifying_partial)
         })
         .await?
         .context("Failed to save parameters")?;
+        paths::finish_partial(&proving_partial, &proving_key_path)?;
+        paths::finish_partial(&verifying_partial, &verifying_key_path)?;
 
         let proving_size = tokio::fs::metadata(&proving_key_path).await?.len();
         let verifying_size = tokio::fs::metadata(&verifying_key_path).await?.len();
@@ -136,34 +262,143 @@ pub async fn generate_claim_params(
             verifying_size_kb = verifying_size / 1024,
             "Parameter set generated successfully"
         );
+
+        if let Some(configuration_file) = &bind_configuration_file {
+            let fingerprint = read_sapling_vk_fingerprint(&verifying_key_path).await?;
+            bind_fingerprint(
+                configuration_file,
+                SetupArtifact::SaplingVerifyingKey,
+                fingerprint,
+            )
+            .await?;
+        }
     }
 
     Ok(())
 }
 
+/// Generate a Sapling parameter set on the blocking pool, logging the elapsed time every
+/// [`SETUP_ELAPSED_LOG_INTERVAL`] until it is done.
+///
+/// A setup takes minutes and bellman reports nothing while it runs, so the log only shows that
+/// generation is still going, not how far it has got.
+async fn generate_parameters_logging_elapsed(
+    scheme: SaplingValueCommitmentScheme,
+) -> eyre::Result<ClaimParameters> {
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+    info!(
+        threads,
+        "Synthesizing the circuit and computing the parameters"
+    );
+
+    let started = Instant::now();
+    let mut generation = tokio::task::spawn_blocking(move || generate_parameters(scheme));
+    let mut elapsed_log = tokio::time::interval(SETUP_ELAPSED_LOG_INTERVAL);
+    // The first tick completes immediately.
+    elapsed_log.tick().await;
+    loop {
+        tokio::select! {
+            params = &mut generation => {
+                let params = params?
+                    .map_err(|e| eyre::eyre!("Parameter generation failed for {scheme:?}: {e}"))?;
+                info!(
+                    elapsed_secs = started.elapsed().as_secs(),
+                    "Parameter set computed"
+                );
+                return Ok(params);
+            }
+            _ = elapsed_log.tick() => {
+                info!(
+                    elapsed_secs = started.elapsed().as_secs(),
+                    "Still generating parameter set"
+                );
+            }
+        }
+    }
+}
+
 /// Sapling proof generation keys for both external and internal scopes.
+#[derive(Clone)]
 struct SaplingProofGenerationKeys {
     external: sapling::ProofGenerationKey,
     internal: sapling::ProofGenerationKey,
 }
 
-/// Derive Sapling proof generation keys from a seed.
-fn derive_sapling_proof_generation_keys(
-    network: Network,
-    seed: &[u8; 64],
-    account_id: u32,
-) -> eyre::Result<SaplingProofGenerationKeys> {
-    let account_id =
-        AccountId::try_from(account_id).map_err(|_| eyre::eyre!("Invalid account-id"))?;
-
-    let usk = UnifiedSpendingKey::from_seed(&network, seed, account_id)
-        .map_err(|e| eyre::eyre!("Failed to derive spending key: {e:?}"))?;
-
+/// Derive Sapling proof generation keys from a unified spending key.
+fn derive_sapling_proof_generation_keys(usk: &UnifiedSpendingKey) -> SaplingProofGenerationKeys {
     let extsk = usk.sapling();
-    Ok(SaplingProofGenerationKeys {
+    SaplingProofGenerationKeys {
         external: extsk.expsk.proof_generation_key(),
         internal: extsk.derive_internal().expsk.proof_generation_key(),
-    })
+    }
+}
+
+/// Key material that proves the claims of one account.
+///
+/// Proving needs the Sapling proof generation keys and the Orchard full viewing key, but not the
+/// spend authorizing keys, so it can be derived from a seed or loaded from a [`ProvingKeyFile`].
+pub(crate) struct ProvingKeys {
+    sapling: SaplingProofGenerationKeys,
+    sapling_viewing: SaplingViewingKeys,
+    orchard: OrchardFullViewingKey,
+    viewing_key: UnifiedFullViewingKey,
+}
+
+impl ProvingKeys {
+    /// Proving keys of the account of `usk`.
+    pub(crate) fn from_spending_key(usk: &UnifiedSpendingKey) -> Self {
+        info!("Deriving spending keys...");
+        let sapling = derive_sapling_proof_generation_keys(usk);
+        info!("Derived Sapling proof generation keys (external + internal)");
+        Self {
+            sapling,
+            sapling_viewing: SaplingViewingKeys::from_dfvk(
+                &usk.sapling().to_diversifiable_full_viewing_key(),
+            ),
+            orchard: usk.orchard().into(),
+            viewing_key: usk.to_unified_full_viewing_key(),
+        }
+    }
+
+    /// Proving keys loaded from an exported [`ProvingKeyFile`].
+    pub(crate) fn from_key_file(network: Network, file: &ProvingKeyFile) -> eyre::Result<Self> {
+        let ufvk = UnifiedFullViewingKey::decode(&network, &file.ufvk)
+            .map_err(|e| eyre::eyre!("Failed to decode the proving key UFVK: {e:?}"))?;
+        let dfvk = ufvk
+            .sapling()
+            .context("The proving key UFVK has no Sapling key")?;
+        let orchard = ufvk
+            .orchard()
+            .context("The proving key UFVK has no Orchard key")?
+            .clone();
+        let nsk = |bytes: &[u8; 32]| {
+            Option::<jubjub::Fr>::from(jubjub::Fr::from_bytes(bytes))
+                .context("Invalid Sapling nsk in the proving key")
+        };
+        let ak = dfvk.fvk().vk.ak.clone();
+        let sapling = SaplingProofGenerationKeys {
+            external: sapling::ProofGenerationKey {
+                ak: ak.clone(),
+                nsk: nsk(&file.sapling_nsk)?,
+            },
+            internal: sapling::ProofGenerationKey {
+                ak,
+                nsk: nsk(&file.sapling_internal_nsk)?,
+            },
+        };
+        let sapling_viewing = SaplingViewingKeys::from_dfvk(dfvk);
+        ensure!(
+            sapling.external.to_viewing_key().nk.0 == sapling_viewing.nk_external.0 &&
+                sapling.internal.to_viewing_key().nk.0 == sapling_viewing.nk_internal.0,
+            "The Sapling nsk in the proving key does not match its UFVK"
+        );
+        Ok(Self {
+            sapling,
+            sapling_viewing,
+            orchard,
+            viewing_key: ufvk,
+        })
+    }
 }
 
 /// Returns true when claim key material matches seed-derived key material for its scope.
@@ -184,6 +419,10 @@ fn claim_matches_seed_keys(
 }
 
 /// Generate and verify a single Sapling claim proof.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Proving context is threaded through from the caller"
+)]
 fn generate_single_sapling_proof(
     claim_input: &ClaimInput<SaplingPrivateInputs>,
     params: &ClaimParameters,
@@ -192,13 +431,15 @@ fn generate_single_sapling_proof(
     note_commitment_root: [u8; 32],
     nullifier_gap_root: [u8; 32],
     value_commitment_scheme: SaplingValueCommitmentScheme,
+    rng_source: &dyn ProofRngSource,
 ) -> eyre::Result<(SaplingClaimProofResult, SaplingClaimSecretResult)> {
     info!(
         value = claim_input.private_inputs.value,
         "Generating claim proof..."
     );
 
-    let mut rng = rand_core::OsRng;
+    let started = Instant::now();
+    let mut rng = rng_source.rng_for_claim(&claim_input.public_inputs.airdrop_nullifier);
 
     let proof_generation_key = match claim_input.private_inputs.scope {
         SerializableScope::External => keys.external.clone(),
@@ -206,20 +447,20 @@ fn generate_single_sapling_proof(
     };
 
     // Caller-generated witness randomness (Sapling-style).
-    let alpha = jubjub::Fr::random(&mut rng);
-    let alpha_bytes = alpha.to_repr();
-
-    let rcv = sapling::value::ValueCommitTrapdoor::random(&mut rng);
-    let rcv_bytes = rcv.inner().to_repr();
-
-    let rcv_sha256 = match value_commitment_scheme {
-        SaplingValueCommitmentScheme::Native => None,
-        SaplingValueCommitmentScheme::Sha256 => {
-            let mut rcv_sha256 = [0_u8; 32];
-            rand_core::RngCore::fill_bytes(&mut rng, &mut rcv_sha256);
-            Some(rcv_sha256)
-        }
-    };
+    let randomness = sample_sapling_randomness(&mut rng, value_commitment_scheme);
+    let ClaimRandomness {
+        alpha: alpha_bytes,
+        rcv: rcv_bytes,
+        rcv_sha256,
+        ..
+    } = randomness;
+    let value_blinding = randomness.value_blinding(
+        match value_commitment_scheme {
+            SaplingValueCommitmentScheme::Native => ValueCommitmentScheme::Native,
+            SaplingValueCommitmentScheme::Sha256 => ValueCommitmentScheme::Sha256,
+        },
+        claim_input.private_inputs.value,
+    )?;
 
     let airdrop_nullifier: [u8; 32] = claim_input.public_inputs.airdrop_nullifier.into();
     let claim_inputs = to_claim_proof_inputs(
@@ -233,8 +474,9 @@ fn generate_single_sapling_proof(
         rcv_sha256,
     );
 
-    let proof_output = generate_claim_proof(params, &claim_inputs, &proof_generation_key)
-        .map_err(|e| eyre::eyre!("Failed to generate Sapling proof: {e}"))?;
+    let proof_output =
+        generate_claim_proof_with_rng(params, &claim_inputs, &proof_generation_key, &mut rng)
+            .map_err(|e| eyre::eyre!("Failed to generate Sapling proof: {e}"))?;
 
     verify_claim_proof_output(
         &proof_output,
@@ -243,11 +485,26 @@ fn generate_single_sapling_proof(
         &note_commitment_root,
         &nullifier_gap_root,
     )
-    .map_err(|e| eyre::eyre!("Generated Sapling proof failed self-verification: {e}"))?;
+    .map_err(|e| {
+        // Name the unsatisfied constraint behind the failure instead of the bare verifier error.
+        match diagnose_claim_proof(&claim_inputs, &proof_generation_key) {
+            Ok(Some(unsatisfied)) => {
+                eyre::eyre!("Generated Sapling proof failed self-verification: {unsatisfied}")
+            }
+            Ok(None) | Err(_) => {
+                eyre::eyre!("Generated Sapling proof failed self-verification: {e}")
+            }
+        }
+    })?;
 
+    telemetry::record_proof(Pool::Sapling, started.elapsed());
     info!("Proof generated and verified successfully");
     Ok((
-        to_proof_result(&proof_output, claim_input.public_inputs.airdrop_nullifier),
+        to_proof_result(
+            &proof_output,
+            claim_input.claim_id,
+            claim_input.public_inputs.airdrop_nullifier,
+        ),
         SaplingClaimSecretResult {
             airdrop_nullifier: claim_input.public_inputs.airdrop_nullifier,
             alpha: alpha_bytes,
@@ -256,11 +513,19 @@ fn generate_single_sapling_proof(
                 SaplingValueCommitmentScheme::Sha256 => None,
             },
             rcv_sha256,
+            value_blinding: Some(value_blinding),
         },
     ))
 }
 
 /// Generate Sapling proofs in parallel using tokio's blocking thread pool.
+///
+/// Results are returned in input order. A claim that fails to prove is recorded in the returned
+/// failures instead of aborting the batch.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Proving context is threaded through to each task"
+)]
 async fn generate_sapling_proofs_parallel(
     sapling_inputs: Vec<ClaimInput<SaplingPrivateInputs>>,
     params: Arc<ClaimParameters>,
@@ -269,21 +534,27 @@ async fn generate_sapling_proofs_parallel(
     note_commitment_root: [u8; 32],
     nullifier_gap_root: [u8; 32],
     value_commitment_scheme: SaplingValueCommitmentScheme,
-) -> eyre::Result<(Vec<SaplingClaimProofResult>, Vec<SaplingClaimSecretResult>)> {
+    rng_source: Arc<dyn ProofRngSource>,
+) -> eyre::Result<(
+    Vec<SaplingClaimProofResult>,
+    Vec<SaplingClaimSecretResult>,
+    Vec<FailedClaimProof>,
+)> {
     let mut join_set = tokio::task::JoinSet::new();
     let task_limit = sapling_proving_task_limit();
-    let mut pending_inputs = sapling_inputs.into_iter();
+    let mut pending_inputs = sapling_inputs.into_iter().enumerate();
 
-    for _ in 0..task_limit {
-        let Some(claim_input) = pending_inputs.next() else {
-            break;
+    let mut spawn_next = |join_set: &mut tokio::task::JoinSet<_>| {
+        let Some((index, claim_input)) = pending_inputs.next() else {
+            return false;
         };
         let params = Arc::clone(&params);
         let pvk = Arc::clone(&pvk);
         let keys = Arc::clone(&keys);
+        let rng_source = Arc::clone(&rng_source);
 
         join_set.spawn_blocking(move || {
-            generate_single_sapling_proof(
+            let result = generate_single_sapling_proof(
                 &claim_input,
                 &params,
                 &pvk,
@@ -291,41 +562,81 @@ async fn generate_sapling_proofs_parallel(
                 note_commitment_root,
                 nullifier_gap_root,
                 value_commitment_scheme,
+                rng_source.as_ref(),
             )
+            .map_err(|e| failed_claim(Pool::Sapling, &claim_input, &e));
+            (index, result)
         });
+        true
+    };
+
+    for _ in 0..task_limit {
+        if !spawn_next(&mut join_set) {
+            break;
+        }
     }
 
-    let mut proofs = Vec::new();
-    let mut secrets = Vec::new();
+    let mut results = Vec::new();
     while let Some(result) = join_set.join_next().await {
         match result {
-            Ok(Ok((proof, secret))) => {
+            Ok(indexed) => results.push(indexed),
+            Err(e) => return Err(eyre::eyre!("Sapling proving task failed: {e}")),
+        }
+        spawn_next(&mut join_set);
+    }
+
+    results.sort_unstable_by_key(|(index, _)| *index);
+    let mut proofs = Vec::with_capacity(results.len());
+    let mut secrets = Vec::with_capacity(results.len());
+    let mut failures = Vec::new();
+    for (_, result) in results {
+        match result {
+            Ok((proof, secret)) => {
                 proofs.push(proof);
                 secrets.push(secret);
             }
-            Ok(Err(e)) => return Err(e),
-            Err(e) => return Err(eyre::eyre!("Sapling proving task failed: {e}")),
+            Err(failure) => failures.push(failure),
         }
+    }
+    Ok((proofs, secrets, failures))
+}
 
-        if let Some(claim_input) = pending_inputs.next() {
-            let params = Arc::clone(&params);
-            let pvk = Arc::clone(&pvk);
-            let keys = Arc::clone(&keys);
-
-            join_set.spawn_blocking(move || {
-                generate_single_sapling_proof(
-                    &claim_input,
-                    &params,
-                    &pvk,
-                    &keys,
-                    note_commitment_root,
-                    nullifier_gap_root,
-                    value_commitment_scheme,
-                )
-            });
+/// Keep the claims that pass `check`, recording the others in `failed_claims`.
+fn checked_claims<P>(
+    claims: Vec<ClaimInput<P>>,
+    pool: Pool,
+    check: impl Fn(&ClaimInput<P>) -> eyre::Result<()>,
+    failed_claims: &mut Vec<FailedClaimProof>,
+) -> Vec<ClaimInput<P>> {
+    let mut passed = Vec::with_capacity(claims.len());
+    for claim in claims {
+        match check(&claim) {
+            Ok(()) => passed.push(claim),
+            Err(e) => failed_claims.push(failed_claim(pool, &claim, &e)),
         }
     }
-    Ok((proofs, secrets))
+    passed
+}
+
+/// Record that `claim_input` failed to prove with `error`.
+fn failed_claim<P>(
+    pool: Pool,
+    claim_input: &ClaimInput<P>,
+    error: &eyre::Report,
+) -> FailedClaimProof {
+    let reason = format!("{error:#}");
+    warn!(
+        ?pool,
+        claim_id = hex::encode(claim_input.claim_id),
+        %reason,
+        "Claim failed to prove, continuing with the other claims"
+    );
+    FailedClaimProof {
+        claim_id: claim_input.claim_id,
+        pool,
+        airdrop_nullifier: claim_input.public_inputs.airdrop_nullifier,
+        reason,
+    }
 }
 
 fn vec_to_orchard_depth_array(
@@ -343,11 +654,9 @@ fn vec_to_orchard_depth_array(
 }
 
 fn derive_orchard_key_material_bytes(
-    usk: &UnifiedSpendingKey,
+    fvk: &OrchardFullViewingKey,
     scope: SerializableScope,
 ) -> eyre::Result<([u8; 32], [u8; 32], [u8; 32])> {
-    let sk = usk.orchard();
-    let fvk: OrchardFullViewingKey = sk.into();
     let fvk_bytes = fvk.to_bytes();
 
     let ak: [u8; 32] = fvk_bytes
@@ -374,60 +683,38 @@ fn derive_orchard_key_material_bytes(
     Ok((ak, nk, rivk))
 }
 
-fn orchard_target_id_bytes(target_id: &str) -> eyre::Result<([u8; 32], u8)> {
-    ensure!(
-        target_id.len() <= 32,
-        "Orchard target_id must be at most 32 bytes"
-    );
-    let mut bytes = [0_u8; 32];
-    let prefix = bytes
-        .get_mut(..target_id.len())
-        .context("Orchard target_id length exceeds byte buffer")?;
-    prefix.copy_from_slice(target_id.as_bytes());
-    let len = u8::try_from(target_id.len())
-        .map_err(|_| eyre::eyre!("Orchard target_id length does not fit in u8"))?;
-    Ok((bytes, len))
-}
-
 #[allow(
     clippy::too_many_lines,
+    clippy::too_many_arguments,
     reason = "Per-claim Orchard proving needs explicit material"
 )]
 fn generate_single_orchard_proof(
     params: &Params<vesta::Affine>,
     claim_input: &ClaimInput<OrchardPrivateInputs>,
-    usk: &UnifiedSpendingKey,
+    fvk: &OrchardFullViewingKey,
     orchard_note_root: [u8; 32],
     orchard_gap_root: [u8; 32],
-    orchard_target_id: &str,
+    orchard_target_id: &TargetId,
     orchard_scheme: OrchardValueCommitmentScheme,
+    rng_source: &dyn ProofRngSource,
 ) -> eyre::Result<(OrchardClaimProofResult, OrchardClaimSecretResult)> {
-    let mut rng = rand_core::OsRng;
-    let alpha = pallas::Scalar::random(&mut rng);
-    let alpha_bytes = alpha.to_repr();
-
-    let rcv_bytes = loop {
-        let mut b = [0_u8; 32];
-        rand_core::RngCore::fill_bytes(&mut rng, &mut b);
-        if Option::<OrchardValueCommitTrapdoor>::from(OrchardValueCommitTrapdoor::from_bytes(b))
-            .is_some()
-        {
-            break b;
-        }
-    };
-
-    let ask = SpendAuthorizingKey::from(usk.orchard());
-    let ak = SpendValidatingKey::from(&ask);
-    let ak_p_bytes = pallas::Point::from(&ak).to_bytes();
-
-    let rcv_sha256 = match orchard_scheme {
-        OrchardValueCommitmentScheme::Native => None,
-        OrchardValueCommitmentScheme::Sha256 => {
-            let mut bytes = [0_u8; 32];
-            rand_core::RngCore::fill_bytes(&mut rng, &mut bytes);
-            Some(bytes)
-        }
-    };
+    let started = Instant::now();
+    let mut rng = rng_source.rng_for_claim(&claim_input.public_inputs.airdrop_nullifier);
+    let randomness = sample_orchard_randomness(&mut rng, orchard_scheme);
+    let ClaimRandomness {
+        alpha: alpha_bytes,
+        rcv: rcv_bytes,
+        rcv_sha256,
+        rcv_poseidon,
+    } = randomness;
+    let value_blinding = randomness.value_blinding(
+        match orchard_scheme {
+            OrchardValueCommitmentScheme::Native => ValueCommitmentScheme::Native,
+            OrchardValueCommitmentScheme::Sha256 => ValueCommitmentScheme::Sha256,
+            OrchardValueCommitmentScheme::Poseidon => ValueCommitmentScheme::Poseidon,
+        },
+        claim_input.private_inputs.value,
+    )?;
 
     let cm_merkle_path =
         vec_to_orchard_depth_array(&claim_input.private_inputs.note_commitment_merkle_path)?;
@@ -437,10 +724,11 @@ fn generate_single_orchard_proof(
         .map_err(|_| eyre::eyre!("Orchard note position does not fit in u32"))?;
     let nf_leaf_position = u32::try_from(claim_input.private_inputs.nullifier_gap_position)
         .map_err(|_| eyre::eyre!("Orchard non-membership leaf position does not fit in u32"))?;
-    let (_ak_bytes, nk_bytes, rivk_bytes) =
-        derive_orchard_key_material_bytes(usk, claim_input.private_inputs.scope)?;
+    // `ak` is encoded as its compressed point, the encoding the circuit takes.
+    let (ak_p_bytes, nk_bytes, rivk_bytes) =
+        derive_orchard_key_material_bytes(fvk, claim_input.private_inputs.scope)?;
 
-    let (target_id, target_id_len) = orchard_target_id_bytes(orchard_target_id)?;
+    let (target_id, target_id_len) = orchard_target_id.orchard_padded();
 
     let inputs = OrchardClaimProofInputs {
         target_id,
@@ -450,6 +738,7 @@ fn generate_single_orchard_proof(
         nullifier_gap_root: orchard_gap_root,
         value_commitment_scheme: orchard_scheme,
         rcv_sha256,
+        rcv_poseidon,
         rho: claim_input.private_inputs.rho,
         rseed: claim_input.private_inputs.rseed,
         g_d: claim_input.private_inputs.g_d,
@@ -468,25 +757,25 @@ fn generate_single_orchard_proof(
         nf_merkle_path,
     };
 
-    let proof_output = generate_orchard_claim_proof(params, &inputs)?;
-    let target_id_slice = target_id
-        .get(..usize::from(target_id_len))
-        .context("Orchard target_id length exceeds padded target_id buffer")?;
+    let proof_output = generate_orchard_claim_proof(params, &inputs, &mut rng)?;
     verify_orchard_claim_proof_output(
         params,
         &proof_output,
         orchard_note_root,
         orchard_gap_root,
         orchard_scheme,
-        target_id_slice,
+        orchard_target_id.as_bytes(),
     )
     .map_err(|e| eyre::eyre!("Generated Orchard proof failed self-verification: {e}"))?;
+    telemetry::record_proof(Pool::Orchard, started.elapsed());
 
     let proof = OrchardClaimProofResult {
+        claim_id: claim_input.claim_id,
         zkproof: proof_output.zkproof,
         rk: proof_output.rk,
         cv: proof_output.cv,
         cv_sha256: proof_output.cv_sha256,
+        cv_poseidon: proof_output.cv_poseidon,
         airdrop_nullifier: claim_input.public_inputs.airdrop_nullifier,
     };
     let secret = OrchardClaimSecretResult {
@@ -494,9 +783,11 @@ fn generate_single_orchard_proof(
         alpha: alpha_bytes,
         rcv: match orchard_scheme {
             OrchardValueCommitmentScheme::Native => Some(rcv_bytes),
-            OrchardValueCommitmentScheme::Sha256 => None,
+            OrchardValueCommitmentScheme::Sha256 | OrchardValueCommitmentScheme::Poseidon => None,
         },
         rcv_sha256,
+        rcv_poseidon,
+        value_blinding: Some(value_blinding),
     };
     Ok((proof, secret))
 }
@@ -513,11 +804,15 @@ fn generate_single_orchard_proof(
 /// * `orchard_params_file` - Path to the Orchard Halo2 params file
 /// * `secrets_output_file` - Path to local-only secrets output file
 /// * `airdrop_configuration_file` - Path to airdrop configuration JSON
+/// * `rng_source` - Source of all claim randomness, normally [`OsProofRng`](super::OsProofRng)
+/// * `check_note_positions` - Check Sapling note positions against lightwalletd before proving
+/// * `lightwalletd_url` - Optional lightwalletd endpoint override for the note position check
+/// * `signing_request_output_file` - Optional signing request output for `claim sign --offline`
+/// * `pool` - Pools whose claims are proven; the claims of other pools are skipped
 ///
 /// # Errors
 /// Returns an error if file I/O, parsing, key derivation, or proof generation fails.
 #[allow(
-    clippy::too_many_lines,
     clippy::too_many_arguments,
     reason = "Public SDK entrypoints, parameters map to CLI arguments"
 )]
@@ -531,16 +826,162 @@ pub async fn generate_claim_proofs(
     orchard_params_mode: OrchardParamsMode,
     secrets_output_file: PathBuf,
     airdrop_configuration_file: PathBuf,
+    rng_source: Arc<dyn ProofRngSource>,
+    check_note_positions: bool,
+    lightwalletd_url: Option<String>,
+    signing_request_output_file: Option<PathBuf>,
+    pool: PoolSelection,
+) -> ZairResult<()> {
+    generate_claim_proofs_inner(
+        claim_inputs_file,
+        proofs_output_file,
+        seed_file,
+        account_id,
+        proving_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+        secrets_output_file,
+        airdrop_configuration_file,
+        rng_source,
+        check_note_positions,
+        lightwalletd_url,
+        signing_request_output_file,
+        pool,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Public SDK entrypoints, parameters map to CLI arguments"
+)]
+async fn generate_claim_proofs_inner(
+    claim_inputs_file: PathBuf,
+    proofs_output_file: PathBuf,
+    seed_file: PathBuf,
+    account_id: u32,
+    proving_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    secrets_output_file: PathBuf,
+    airdrop_configuration_file: PathBuf,
+    rng_source: Arc<dyn ProofRngSource>,
+    check_note_positions: bool,
+    lightwalletd_url: Option<String>,
+    signing_request_output_file: Option<PathBuf>,
+    pool: PoolSelection,
 ) -> eyre::Result<()> {
     info!(file = ?claim_inputs_file, "Reading claim inputs...");
-    let inputs: AirdropClaimInputs =
-        serde_json::from_str(&tokio::fs::read_to_string(&claim_inputs_file).await?)?;
+    let mut inputs: AirdropClaimInputs =
+        serde_json::from_str(&stdio::read_to_string(&claim_inputs_file).await?)?;
+    inputs.retain_pools(pool);
 
     let airdrop_config: AirdropConfiguration =
-        serde_json::from_str(&tokio::fs::read_to_string(&airdrop_configuration_file).await?)
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
             .context("Failed to parse airdrop configuration JSON")?;
+    if check_note_positions {
+        verify_sapling_note_positions(&inputs, &airdrop_config, lightwalletd_url.as_deref())
+            .await?;
+    }
+
+    info!(file = ?seed_file, "Reading seed from file...");
+    let account_secret = read_account_secret(&seed_file).await?;
+    let keys = ProvingKeys::from_spending_key(
+        &account_secret.spending_key(configured_network(&airdrop_config)?, account_id)?,
+    );
+
+    let setup = load_proving_params(
+        &airdrop_config,
+        !inputs.sapling_claim_input.is_empty(),
+        !inputs.orchard_claim_input.is_empty(),
+        proving_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+    )
+    .await?;
+
+    let (output, secrets) =
+        prove_claims_inner(inputs, &airdrop_config, &keys, &setup, &rng_source).await?;
+
+    let json = to_artifact_json(&output)?;
+    stdio::write(&proofs_output_file, json).await?;
 
-    let network = to_zcash_network(airdrop_config.network);
+    info!(
+        file = ?proofs_output_file,
+        sapling_count = output.sapling_proofs.len(),
+        orchard_count = output.orchard_proofs.len(),
+        failed_count = output.failed_claims.len(),
+        "Claim proofs written"
+    );
+    output.ensure_any_proven()?;
+
+    let secrets_json = to_artifact_json(&secrets)?;
+    write_sensitive_output(&secrets_output_file, &secrets_json).await?;
+    info!(file = ?secrets_output_file, "Claim secrets written");
+
+    if let Some(signing_request_output_file) = signing_request_output_file {
+        write_signing_request(&output, &secrets, &signing_request_output_file).await?;
+    }
+
+    Ok(())
+}
+
+/// Load the setup files needed to prove the claims of the selected pools.
+pub(super) async fn load_proving_params(
+    airdrop_config: &AirdropConfiguration,
+    sapling: bool,
+    orchard: bool,
+    proving_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+) -> eyre::Result<ProvingParams> {
+    let sapling = if sapling {
+        Some(Arc::new(load_params(proving_key_file).await?))
+    } else {
+        None
+    };
+    let orchard = match airdrop_config.orchard.as_ref() {
+        Some(config) if orchard => Some(
+            load_or_prepare_orchard_params_inner(
+                orchard_params_file,
+                config.value_commitment_scheme.into(),
+                orchard_params_mode,
+            )
+            .await?,
+        ),
+        Some(_) | None => None,
+    };
+    Ok(ProvingParams { sapling, orchard })
+}
+
+/// Generate claim proofs and local-only secrets for in-memory claim inputs.
+#[allow(
+    clippy::too_many_lines,
+    reason = "Proving flow keeps config binding, key checks and both pools in one place"
+)]
+pub async fn prove_claims_inner(
+    inputs: AirdropClaimInputs,
+    airdrop_config: &AirdropConfiguration,
+    keys: &ProvingKeys,
+    setup: &ProvingParams,
+    rng_source: &Arc<dyn ProofRngSource>,
+) -> eyre::Result<(ClaimProofsOutput, ClaimSecretsOutput)> {
+    if rng_source.is_deterministic() {
+        warn!(
+            "Deterministic RNG enabled: proofs and secrets are reproducible, use for testing only"
+        );
+    }
+    airdrop_config.circuit.ensure_supported()?;
+    let epoch = inputs.epoch;
+    let airdrop_config = &airdrop_config.for_epoch(epoch)?;
+    ensure_same_wallet(
+        inputs.key_fingerprints.as_ref(),
+        airdrop_config,
+        &keys.viewing_key,
+        "claims",
+    )?;
+    let key_fingerprints = inputs.key_fingerprints;
     let sapling_config = if inputs.sapling_claim_input.is_empty() {
         None
     } else {
@@ -551,9 +992,9 @@ pub async fn generate_claim_proofs(
                 .context("Sapling claims present but airdrop configuration has no sapling pool")?,
         )
     };
-    let sapling_scheme = sapling_config.map_or(SaplingValueCommitmentScheme::Native, |s| {
-        s.value_commitment_scheme.into()
-    });
+    let sapling_scheme = sapling_config.map_or(Ok(SaplingValueCommitmentScheme::Native), |s| {
+        s.value_commitment_scheme.try_into()
+    })?;
     let orchard_config = if inputs.orchard_claim_input.is_empty() {
         None
     } else {
@@ -568,115 +1009,114 @@ pub async fn generate_claim_proofs(
         o.value_commitment_scheme.into()
     });
 
-    info!(file = ?seed_file, "Reading seed from file...");
-    let seed = read_seed_file(&seed_file).await?;
-    let zip32_account =
-        AccountId::try_from(account_id).map_err(|_| eyre::eyre!("Invalid account-id"))?;
-    let usk = UnifiedSpendingKey::from_seed(&network, seed.expose_secret(), zip32_account)
-        .map_err(|e| eyre::eyre!("Failed to derive spending key: {e:?}"))?;
-
-    info!("Deriving spending keys...");
-    let keys = derive_sapling_proof_generation_keys(network, seed.expose_secret(), account_id)?;
-    info!("Derived Sapling proof generation keys (external + internal)");
-
     ensure!(
         inputs
             .sapling_claim_input
             .iter()
-            .all(|claim| claim_matches_seed_keys(claim, &keys)),
-        "Seed mismatch: seed-derived Sapling keys do not match claim file"
+            .all(|claim| claim_matches_seed_keys(claim, &keys.sapling)),
+        "Key mismatch: the Sapling proving keys do not match the claim file"
     );
 
-    let (sapling_proofs, sapling_secrets) = if inputs.sapling_claim_input.is_empty() {
+    info!(
+        sapling_count = inputs.sapling_claim_input.len(),
+        orchard_count = inputs.orchard_claim_input.len(),
+        "Loaded claim inputs"
+    );
+
+    // Claims whose witness does not match the configuration are reported without proving them.
+    let mut failed_claims = Vec::new();
+    let sapling_claims = checked_claims(
+        inputs.sapling_claim_input,
+        Pool::Sapling,
+        |claim| check_sapling_witness(claim, airdrop_config, &keys.sapling_viewing),
+        &mut failed_claims,
+    );
+    let orchard_claims = checked_claims(
+        inputs.orchard_claim_input,
+        Pool::Orchard,
+        |claim| check_orchard_witness(claim, airdrop_config),
+        &mut failed_claims,
+    );
+
+    let (sapling_proofs, sapling_secrets) = if sapling_claims.is_empty() {
         (Vec::new(), Vec::new())
     } else {
-        let params = load_params(proving_key_file).await?;
+        let params = setup
+            .sapling
+            .clone()
+            .context("Sapling claims present, but no Sapling proving parameters were supplied")?;
         let pvk = params.prepared_verifying_key();
         info!("Sapling parameters ready");
 
-        let expected_sapling_count = inputs.sapling_claim_input.len();
-        let (sapling_proofs, sapling_secrets) = generate_sapling_proofs_parallel(
-            inputs.sapling_claim_input.clone(),
-            Arc::new(params),
+        let expected_sapling_count = sapling_claims.len();
+        let (sapling_proofs, sapling_secrets, failures) = generate_sapling_proofs_parallel(
+            sapling_claims,
+            params,
             Arc::new(pvk),
-            Arc::new(keys),
+            Arc::new(keys.sapling.clone()),
             sapling_config.map_or([0_u8; 32], |s| s.note_commitment_root),
             sapling_config.map_or([0_u8; 32], |s| s.nullifier_gap_root),
             sapling_scheme,
+            Arc::clone(rng_source),
         )
         .await?;
 
         ensure!(
-            sapling_proofs.len() == expected_sapling_count,
-            "Expected {expected_sapling_count} Sapling proofs, generated {}",
-            sapling_proofs.len()
+            sapling_proofs.len().saturating_add(failures.len()) == expected_sapling_count,
+            "Expected {expected_sapling_count} Sapling proofs, generated {} and {} failed",
+            sapling_proofs.len(),
+            failures.len()
         );
         ensure!(
-            sapling_secrets.len() == expected_sapling_count,
-            "Expected {expected_sapling_count} Sapling secrets, generated {}",
+            sapling_secrets.len() == sapling_proofs.len(),
+            "Expected {} Sapling secrets, generated {}",
+            sapling_proofs.len(),
             sapling_secrets.len()
         );
+        failed_claims.extend(failures);
         (sapling_proofs, sapling_secrets)
     };
 
-    info!(
-        sapling_count = inputs.sapling_claim_input.len(),
-        orchard_count = inputs.orchard_claim_input.len(),
-        "Loaded claim inputs"
-    );
-
-    let mut orchard_proofs = Vec::with_capacity(inputs.orchard_claim_input.len());
-    let mut orchard_secrets = Vec::with_capacity(inputs.orchard_claim_input.len());
+    let mut orchard_proofs = Vec::with_capacity(orchard_claims.len());
+    let mut orchard_secrets = Vec::with_capacity(orchard_claims.len());
     if let Some(orchard) = orchard_config {
-        ensure!(
-            orchard.target_id.len() <= 32,
-            "Orchard target_id must be at most 32 bytes"
-        );
-        let params = load_or_prepare_orchard_params(
-            orchard_params_file,
-            orchard_scheme,
-            orchard_params_mode,
-        )
-        .await?;
-        for claim_input in &inputs.orchard_claim_input {
-            let (proof, secret) = generate_single_orchard_proof(
+        let params = setup
+            .orchard
+            .as_ref()
+            .context("Orchard claims present, but no Orchard params were supplied")?;
+        for claim_input in &orchard_claims {
+            match generate_single_orchard_proof(
                 params.as_ref(),
                 claim_input,
-                &usk,
+                &keys.orchard,
                 orchard.note_commitment_root,
                 orchard.nullifier_gap_root,
                 &orchard.target_id,
                 orchard_scheme,
-            )?;
-            orchard_proofs.push(proof);
-            orchard_secrets.push(secret);
+                rng_source.as_ref(),
+            ) {
+                Ok((proof, secret)) => {
+                    orchard_proofs.push(proof);
+                    orchard_secrets.push(secret);
+                }
+                Err(e) => failed_claims.push(failed_claim(Pool::Orchard, claim_input, &e)),
+            }
         }
     }
 
     let output = ClaimProofsOutput {
         sapling_proofs,
         orchard_proofs,
+        failed_claims,
+        epoch,
     };
 
-    let json = serde_json::to_string_pretty(&output)?;
-    tokio::fs::write(&proofs_output_file, json).await?;
-
-    info!(
-        file = ?proofs_output_file,
-        sapling_count = output.sapling_proofs.len(),
-        orchard_count = output.orchard_proofs.len(),
-        "Claim proofs written"
-    );
-
     let secrets = ClaimSecretsOutput {
         sapling: sapling_secrets,
         orchard: orchard_secrets,
+        key_fingerprints,
     };
-    let secrets_json = serde_json::to_string_pretty(&secrets)?;
-    write_sensitive_output(&secrets_output_file, &secrets_json).await?;
-    info!(file = ?secrets_output_file, "Claim secrets written");
-
-    Ok(())
+    Ok((output, secrets))
 }
 
 /// Convert `SaplingPrivateInputs` to `ClaimProofInputs`.
@@ -729,9 +1169,11 @@ fn to_claim_proof_inputs(
 /// Convert `ClaimProofOutput` to `SaplingClaimProofResult`.
 const fn to_proof_result(
     output: &ClaimProofOutput,
+    claim_id: [u8; 32],
     airdrop_nullifier: Nullifier,
 ) -> SaplingClaimProofResult {
     SaplingClaimProofResult {
+        claim_id,
         zkproof: output.zkproof,
         rk: output.rk,
         cv: output.cv,
@@ -741,3 +1183,238 @@ const fn to_proof_result(
 }
 
 // Sapling secrets are generated in the SDK and written directly.
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    use orchard::keys::{SpendAuthorizingKey, SpendValidatingKey};
+    use orchard::primitives::redpallas::{SpendAuth, VerificationKey as RedPallasVerificationKey};
+    use serde::{Deserialize, Serialize};
+    use zair_sapling_proofs::verifier::ClaimPublicInputs;
+    use zip32::AccountId;
+
+    use super::super::proof_rng::{ClaimRng, DeterministicProofRng};
+    use super::*;
+
+    /// Golden vectors, relative to the crate root. Regenerate with `ZAIR_UPDATE_GOLDEN=1`.
+    const GOLDEN_FILE: &str = "testdata/claim_public_inputs.json";
+    const TEST_SEED: [u8; 64] = [7_u8; 64];
+    const RNG_SEED: u64 = 42;
+    const VALUE: u64 = 1_234_567;
+    const ROOT: [u8; 32] = [1_u8; 32];
+
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct GoldenVector {
+        alpha: String,
+        rcv: String,
+        rcv_sha256: Option<String>,
+        rk: String,
+        cv: Option<String>,
+        cv_sha256: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        public_inputs: Vec<String>,
+    }
+
+    fn airdrop_nullifier() -> Nullifier {
+        Nullifier::from([0x11_u8; 32])
+    }
+
+    fn seeded_rng() -> ClaimRng {
+        DeterministicProofRng::new(RNG_SEED).rng_for_claim(&airdrop_nullifier())
+    }
+
+    fn sapling_vector(scheme: SaplingValueCommitmentScheme) -> GoldenVector {
+        let randomness = sample_sapling_randomness(&mut seeded_rng(), scheme);
+        let usk = UnifiedSpendingKey::from_seed(&Network::TestNetwork, &TEST_SEED, AccountId::ZERO)
+            .expect("keys should derive");
+        let keys = derive_sapling_proof_generation_keys(&usk);
+        let alpha = jubjub::Fr::from_bytes(&randomness.alpha)
+            .into_option()
+            .expect("alpha is canonical");
+        let rk: [u8; 32] = keys.external.to_viewing_key().rk(alpha).into();
+        let cv = match scheme {
+            SaplingValueCommitmentScheme::Native => {
+                let rcv = sapling::value::ValueCommitTrapdoor::from_bytes(randomness.rcv)
+                    .into_option()
+                    .expect("rcv is canonical");
+                Some(
+                    sapling::value::ValueCommitment::derive(
+                        sapling::value::NoteValue::from_raw(VALUE),
+                        rcv,
+                    )
+                    .to_bytes(),
+                )
+            }
+            SaplingValueCommitmentScheme::Sha256 => None,
+        };
+        let cv_sha256 = randomness
+            .rcv_sha256
+            .map(|r| zair_core::base::cv_sha256(VALUE, r));
+
+        let airdrop_nullifier: [u8; 32] = airdrop_nullifier().into();
+        let public_inputs = ClaimPublicInputs::from_bytes(
+            scheme,
+            &rk,
+            cv.as_ref(),
+            cv_sha256.as_ref(),
+            &ROOT,
+            &airdrop_nullifier,
+            &ROOT,
+        )
+        .expect("public inputs should parse")
+        .to_vec()
+        .expect("public inputs should encode");
+
+        GoldenVector {
+            alpha: hex::encode(randomness.alpha),
+            rcv: hex::encode(randomness.rcv),
+            rcv_sha256: randomness.rcv_sha256.map(hex::encode),
+            rk: hex::encode(rk),
+            cv: cv.map(hex::encode),
+            cv_sha256: cv_sha256.map(hex::encode),
+            public_inputs: public_inputs
+                .iter()
+                .map(|scalar| hex::encode(scalar.to_bytes()))
+                .collect(),
+        }
+    }
+
+    fn orchard_vector(scheme: OrchardValueCommitmentScheme) -> GoldenVector {
+        let randomness = sample_orchard_randomness(&mut seeded_rng(), scheme);
+        let usk = UnifiedSpendingKey::from_seed(&Network::TestNetwork, &TEST_SEED, AccountId::ZERO)
+            .expect("keys should derive");
+        let ak = SpendValidatingKey::from(&SpendAuthorizingKey::from(usk.orchard()));
+        let alpha = Option::<pallas::Scalar>::from(pallas::Scalar::from_repr(randomness.alpha))
+            .expect("alpha is canonical");
+        let rk: [u8; 32] =
+            (&RedPallasVerificationKey::<SpendAuth>::try_from(pallas::Point::from(&ak).to_bytes())
+                .expect("ak is a valid point")
+                .randomize(&alpha))
+                .into();
+        let cv = match scheme {
+            OrchardValueCommitmentScheme::Native => {
+                let rcv = Option::<OrchardValueCommitTrapdoor>::from(
+                    OrchardValueCommitTrapdoor::from_bytes(randomness.rcv),
+                )
+                .expect("rcv is canonical");
+                #[allow(
+                    clippy::arithmetic_side_effects,
+                    reason = "Orchard value commitment API requires NoteValue subtraction to produce ValueSum"
+                )]
+                let value_sum = orchard::value::NoteValue::from_raw(VALUE) -
+                    orchard::value::NoteValue::from_raw(0);
+                Some(orchard::value::ValueCommitment::derive(value_sum, rcv).to_bytes())
+            }
+            OrchardValueCommitmentScheme::Sha256 | OrchardValueCommitmentScheme::Poseidon => None,
+        };
+
+        GoldenVector {
+            alpha: hex::encode(randomness.alpha),
+            rcv: hex::encode(randomness.rcv),
+            rcv_sha256: randomness.rcv_sha256.map(hex::encode),
+            rk: hex::encode(rk),
+            cv: cv.map(hex::encode),
+            cv_sha256: randomness
+                .rcv_sha256
+                .map(|r| hex::encode(zair_core::base::cv_sha256(VALUE, r))),
+            public_inputs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn exported_proving_keys_match_the_seed() {
+        let network = Network::TestNetwork;
+        let usk = UnifiedSpendingKey::from_seed(&network, &TEST_SEED, AccountId::ZERO)
+            .expect("keys should derive");
+        let from_seed = ProvingKeys::from_spending_key(&usk);
+        let file = ProvingKeyFile::from_seed(network, &TEST_SEED, 0).expect("key file");
+        let from_file = ProvingKeys::from_key_file(network, &file).expect("keys");
+
+        for (seed_key, file_key) in [
+            (&from_seed.sapling.external, &from_file.sapling.external),
+            (&from_seed.sapling.internal, &from_file.sapling.internal),
+        ] {
+            assert_eq!(seed_key.ak.to_bytes(), file_key.ak.to_bytes());
+            assert_eq!(seed_key.nsk, file_key.nsk);
+        }
+        assert_eq!(from_seed.orchard.to_bytes(), from_file.orchard.to_bytes());
+
+        // The full viewing key encodes `ak` as the point the circuit takes.
+        let ak = SpendValidatingKey::from(&SpendAuthorizingKey::from(usk.orchard()));
+        let (ak_p, _, _) =
+            derive_orchard_key_material_bytes(&from_file.orchard, SerializableScope::Internal)
+                .expect("key material");
+        assert_eq!(ak_p, pallas::Point::from(&ak).to_bytes());
+
+        let mut mismatched = file;
+        mismatched.sapling_internal_nsk = mismatched.sapling_nsk;
+        assert!(ProvingKeys::from_key_file(network, &mismatched).is_err());
+    }
+
+    #[test]
+    fn seeded_rng_depends_only_on_seed_and_nullifier() {
+        let scheme = SaplingValueCommitmentScheme::Sha256;
+        let first = sample_sapling_randomness(&mut seeded_rng(), scheme);
+        let second = sample_sapling_randomness(&mut seeded_rng(), scheme);
+        let other_claim = sample_sapling_randomness(
+            &mut DeterministicProofRng::new(RNG_SEED)
+                .rng_for_claim(&Nullifier::from([0x22_u8; 32])),
+            scheme,
+        );
+        let other_seed = sample_sapling_randomness(
+            &mut DeterministicProofRng::new(RNG_SEED.saturating_add(1))
+                .rng_for_claim(&airdrop_nullifier()),
+            scheme,
+        );
+
+        assert_eq!(first, second);
+        assert_ne!(first, other_claim);
+        assert_ne!(first, other_seed);
+    }
+
+    #[test]
+    fn public_inputs_match_golden_vectors() {
+        let actual = BTreeMap::from([
+            (
+                "sapling_native",
+                sapling_vector(SaplingValueCommitmentScheme::Native),
+            ),
+            (
+                "sapling_sha256",
+                sapling_vector(SaplingValueCommitmentScheme::Sha256),
+            ),
+            (
+                "orchard_native",
+                orchard_vector(OrchardValueCommitmentScheme::Native),
+            ),
+            (
+                "orchard_sha256",
+                orchard_vector(OrchardValueCommitmentScheme::Sha256),
+            ),
+        ]);
+        let mut json = serde_json::to_string_pretty(&actual).expect("serialize golden vectors");
+        json.push('\n');
+
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_FILE);
+        if std::env::var_os("ZAIR_UPDATE_GOLDEN").is_some() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).expect("create golden directory");
+            }
+            std::fs::write(&path, json).expect("write golden vectors");
+            return;
+        }
+
+        let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!(
+                "Failed to read golden vectors {}: {e}; generate them with ZAIR_UPDATE_GOLDEN=1",
+                path.display()
+            )
+        });
+        assert_eq!(
+            expected, json,
+            "Claim public inputs changed; rerun with ZAIR_UPDATE_GOLDEN=1 if this is intended"
+        );
+    }
+}
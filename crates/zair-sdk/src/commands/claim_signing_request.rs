@@ -0,0 +1,580 @@
+//! Air-gapped claim signing.
+//!
+//! `claim prove --export-signing-request` writes a signing request holding, per claim, only the
+//! values a spend-auth signature needs: the airdrop nullifier, the randomized key `rk`, the proof
+//! hash and the randomizer `alpha`. `claim sign --offline` signs the request on another machine
+//! with the seed and writes the signatures, which `claim assemble` merges with the proofs into a
+//! submission.
+
+use std::collections::BTreeMap;
+#[cfg(feature = "prove")]
+use std::path::Path;
+use std::path::PathBuf;
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use rand_core::RngCore as _;
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::info;
+use zair_core::base::{Nullifier, Pool, signature_digest};
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::submission::{ClaimSubmission, OrchardSignedClaim, SaplingSignedClaim};
+
+use super::claim_proofs::{ClaimProofsOutput, ClaimSecretsOutput};
+use super::claim_submission_sign::resolve_expiry_height;
+use super::nullifier_uniqueness::ensure_unique_airdrop_nullifiers;
+#[cfg(feature = "prove")]
+use super::sensitive_output::write_sensitive_output;
+use super::signature_digest::{hash_orchard_proof, hash_sapling_proof};
+use super::submission_auth::{orchard, sapling};
+use super::submission_messages::{ResolvedMessageHashes, resolve_message_hashes};
+use crate::build_info::to_artifact_json;
+use crate::common::configured_network;
+use crate::error::{ZairError, ZairResult};
+use crate::seed::{AccountSecret, read_account_secret};
+use crate::stdio;
+
+/// Claims to sign on an air-gapped machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningRequest {
+    /// Sapling claims.
+    pub sapling: Vec<SigningRequestEntry>,
+    /// Orchard claims.
+    pub orchard: Vec<SigningRequestEntry>,
+    /// Airdrop epoch the claims target; `None` for the base epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u32>,
+}
+
+/// Signing inputs of a single claim.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningRequestEntry {
+    /// The airdrop nullifier of the claim.
+    pub airdrop_nullifier: Nullifier,
+    /// Randomized spend verification key of the proof.
+    #[serde_as(as = "Hex")]
+    pub rk: [u8; 32],
+    /// Hash of the proof fields.
+    #[serde_as(as = "Hex")]
+    pub proof_hash: [u8; 32],
+    /// Spend authorization randomizer used for rk/signature binding.
+    #[serde_as(as = "Hex")]
+    pub alpha: [u8; 32],
+}
+
+/// Spend-auth signatures produced from a signing request.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimSignatures {
+    /// Random nonce bound into every signature.
+    #[serde_as(as = "Hex")]
+    pub nonce: [u8; 32],
+    /// Last block height at which the submission may be accepted.
+    pub expiry_height: u64,
+    /// Sapling claim signatures.
+    pub sapling: Vec<ClaimSignature>,
+    /// Orchard claim signatures.
+    pub orchard: Vec<ClaimSignature>,
+}
+
+/// Spend-auth signature of a single claim.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimSignature {
+    /// The airdrop nullifier of the claim.
+    pub airdrop_nullifier: Nullifier,
+    /// Hash of the signed proof fields.
+    #[serde_as(as = "Hex")]
+    pub proof_hash: [u8; 32],
+    /// Hash of the signed message.
+    #[serde_as(as = "Hex")]
+    pub message_hash: [u8; 32],
+    /// Spend authorization signature over the submission digest.
+    #[serde_as(as = "Hex")]
+    pub spend_auth_sig: [u8; 64],
+}
+
+/// Build the signing request of in-memory proofs and their local-only secrets.
+pub fn signing_request_inner(
+    proofs: &ClaimProofsOutput,
+    secrets: &ClaimSecretsOutput,
+) -> eyre::Result<SigningRequest> {
+    let sapling_alpha: BTreeMap<_, _> = secrets
+        .sapling
+        .iter()
+        .map(|secret| (secret.airdrop_nullifier, secret.alpha))
+        .collect();
+    let orchard_alpha: BTreeMap<_, _> = secrets
+        .orchard
+        .iter()
+        .map(|secret| (secret.airdrop_nullifier, secret.alpha))
+        .collect();
+
+    let sapling = proofs
+        .sapling_proofs
+        .iter()
+        .map(|proof| {
+            Ok(SigningRequestEntry {
+                airdrop_nullifier: proof.airdrop_nullifier,
+                rk: proof.rk,
+                proof_hash: hash_sapling_proof(proof),
+                alpha: *sapling_alpha
+                    .get(&proof.airdrop_nullifier)
+                    .context("Missing secret material for Sapling proof entry")?,
+            })
+        })
+        .collect::<eyre::Result<_>>()?;
+    let orchard = proofs
+        .orchard_proofs
+        .iter()
+        .map(|proof| {
+            Ok(SigningRequestEntry {
+                airdrop_nullifier: proof.airdrop_nullifier,
+                rk: proof.rk,
+                proof_hash: hash_orchard_proof(proof)?,
+                alpha: *orchard_alpha
+                    .get(&proof.airdrop_nullifier)
+                    .context("Missing secret material for Orchard proof entry")?,
+            })
+        })
+        .collect::<eyre::Result<_>>()?;
+
+    Ok(SigningRequest {
+        sapling,
+        orchard,
+        epoch: proofs.epoch,
+    })
+}
+
+/// Write the signing request of in-memory proofs and secrets.
+#[cfg(feature = "prove")]
+pub async fn write_signing_request(
+    proofs: &ClaimProofsOutput,
+    secrets: &ClaimSecretsOutput,
+    signing_request_output_file: &Path,
+) -> eyre::Result<()> {
+    let request = signing_request_inner(proofs, secrets)?;
+    let json = to_artifact_json(&request)?;
+    write_sensitive_output(signing_request_output_file, &json).await?;
+    info!(
+        file = ?signing_request_output_file,
+        sapling_count = request.sapling.len(),
+        orchard_count = request.orchard.len(),
+        "Signing request written"
+    );
+    Ok(())
+}
+
+/// Sign a signing request from `claim prove --export-signing-request` into claim signatures.
+///
+/// The signatures expire at `expiry_height`, or at the end of the configured claim window if
+/// `None`.
+///
+/// # Errors
+/// Returns an error if inputs are invalid, no expiry height is known, key derivation fails, or
+/// a claim does not belong to the seed.
+#[allow(
+    clippy::too_many_arguments,
+    clippy::similar_names,
+    reason = "CLI entrypoint parameters"
+)]
+pub async fn sign_claim_signing_request(
+    signing_request_file: PathBuf,
+    seed_file: PathBuf,
+    account_id: u32,
+    airdrop_configuration_file: PathBuf,
+    message_file: Option<PathBuf>,
+    messages_file: Option<PathBuf>,
+    expiry_height: Option<u64>,
+    signatures_output_file: PathBuf,
+) -> ZairResult<()> {
+    sign_claim_signing_request_inner(
+        signing_request_file,
+        seed_file,
+        account_id,
+        airdrop_configuration_file,
+        message_file,
+        messages_file,
+        expiry_height,
+        signatures_output_file,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Signing))
+}
+
+#[allow(
+    clippy::too_many_arguments,
+    clippy::similar_names,
+    reason = "CLI entrypoint parameters"
+)]
+async fn sign_claim_signing_request_inner(
+    signing_request_file: PathBuf,
+    seed_file: PathBuf,
+    account_id: u32,
+    airdrop_configuration_file: PathBuf,
+    message_file: Option<PathBuf>,
+    messages_file: Option<PathBuf>,
+    expiry_height: Option<u64>,
+    signatures_output_file: PathBuf,
+) -> eyre::Result<()> {
+    info!(file = ?signing_request_file, "Loading signing request...");
+    let request: SigningRequest =
+        serde_json::from_str(&stdio::read_to_string(&signing_request_file).await?)
+            .context("Failed to parse signing request JSON")?;
+
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+
+    info!(file = ?seed_file, "Reading seed from file...");
+    let account_secret = read_account_secret(&seed_file).await?;
+
+    let message_hashes =
+        resolve_message_hashes(message_file.as_ref(), messages_file.as_ref()).await?;
+
+    let signatures = sign_signing_request_inner(
+        &request,
+        &airdrop_config,
+        &account_secret,
+        account_id,
+        &message_hashes,
+        expiry_height,
+    )?;
+
+    let json = to_artifact_json(&signatures)?;
+    stdio::write(&signatures_output_file, json).await?;
+    info!(
+        file = ?signatures_output_file,
+        sapling_count = signatures.sapling.len(),
+        orchard_count = signatures.orchard.len(),
+        expiry_height = signatures.expiry_height,
+        "Claim signatures written"
+    );
+
+    Ok(())
+}
+
+/// Sign an in-memory signing request.
+pub fn sign_signing_request_inner(
+    request: &SigningRequest,
+    airdrop_config: &AirdropConfiguration,
+    account_secret: &AccountSecret,
+    account_id: u32,
+    message_hashes: &ResolvedMessageHashes,
+    expiry_height: Option<u64>,
+) -> eyre::Result<ClaimSignatures> {
+    ensure!(
+        !(request.sapling.is_empty() && request.orchard.is_empty()),
+        "Signing request contains no claims"
+    );
+    ensure_unique_airdrop_nullifiers(
+        request.sapling.iter().map(|entry| entry.airdrop_nullifier),
+        "Sapling signing request",
+    )?;
+    ensure_unique_airdrop_nullifiers(
+        request.orchard.iter().map(|entry| entry.airdrop_nullifier),
+        "Orchard signing request",
+    )?;
+    let airdrop_config = &airdrop_config.for_epoch(request.epoch)?;
+
+    let expiry_height = resolve_expiry_height(airdrop_config, expiry_height)?;
+    let mut nonce = [0_u8; 32];
+    rand_core::OsRng.fill_bytes(&mut nonce);
+    let network = configured_network(airdrop_config)?;
+
+    let mut sapling_signatures = Vec::with_capacity(request.sapling.len());
+    if !request.sapling.is_empty() {
+        let domain = airdrop_config
+            .signature_domain(Pool::Sapling)
+            .context("Sapling claims requested, but airdrop configuration has no sapling pool")?;
+        let keys = sapling::derive_spend_auth_keys(network, account_secret, account_id)?;
+        for entry in &request.sapling {
+            let message_hash = message_hashes
+                .sapling_hash(entry.airdrop_nullifier)
+                .with_context(|| {
+                    format!(
+                        "No message provided for Sapling claim with airdrop nullifier {}. Provide --message or --messages entry",
+                        entry.airdrop_nullifier
+                    )
+                })?;
+            let digest = signature_digest(
+                &domain,
+                &entry.proof_hash,
+                &message_hash,
+                &nonce,
+                expiry_height,
+            );
+            sapling_signatures.push(ClaimSignature {
+                airdrop_nullifier: entry.airdrop_nullifier,
+                proof_hash: entry.proof_hash,
+                message_hash,
+                spend_auth_sig: sapling::sign_randomized(&entry.rk, &entry.alpha, &keys, &digest)?,
+            });
+        }
+    }
+
+    let mut orchard_signatures = Vec::with_capacity(request.orchard.len());
+    if !request.orchard.is_empty() {
+        let domain = airdrop_config
+            .signature_domain(Pool::Orchard)
+            .context("Orchard claims requested, but airdrop configuration has no orchard pool")?;
+        let key = orchard::derive_spend_auth_key(network, account_secret, account_id)?;
+        for entry in &request.orchard {
+            let message_hash = message_hashes
+                .orchard_hash(entry.airdrop_nullifier)
+                .with_context(|| {
+                    format!(
+                        "No message provided for Orchard claim with airdrop nullifier {}. Provide --message or --messages entry",
+                        entry.airdrop_nullifier
+                    )
+                })?;
+            let digest = signature_digest(
+                &domain,
+                &entry.proof_hash,
+                &message_hash,
+                &nonce,
+                expiry_height,
+            );
+            orchard_signatures.push(ClaimSignature {
+                airdrop_nullifier: entry.airdrop_nullifier,
+                proof_hash: entry.proof_hash,
+                message_hash,
+                spend_auth_sig: orchard::sign_randomized(&entry.rk, &entry.alpha, &key, &digest)?,
+            });
+        }
+    }
+
+    Ok(ClaimSignatures {
+        nonce,
+        expiry_height,
+        sapling: sapling_signatures,
+        orchard: orchard_signatures,
+    })
+}
+
+/// Merge claim proofs with the signatures from `claim sign --offline` into a submission package.
+///
+/// # Errors
+/// Returns an error if the inputs cannot be read, a proof has no signature, or a signature was
+/// made over a different proof.
+pub async fn assemble_claim_submission(
+    proofs_file: PathBuf,
+    signatures_file: PathBuf,
+    submission_output_file: PathBuf,
+) -> ZairResult<()> {
+    assemble_claim_submission_inner(proofs_file, signatures_file, submission_output_file)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Signing))
+}
+
+async fn assemble_claim_submission_inner(
+    proofs_file: PathBuf,
+    signatures_file: PathBuf,
+    submission_output_file: PathBuf,
+) -> eyre::Result<()> {
+    info!(file = ?proofs_file, "Loading proofs...");
+    let proofs: ClaimProofsOutput =
+        serde_json::from_str(&stdio::read_to_string(&proofs_file).await?)
+            .context("Failed to parse proofs JSON")?;
+
+    info!(file = ?signatures_file, "Loading claim signatures...");
+    let signatures: ClaimSignatures =
+        serde_json::from_str(&stdio::read_to_string(&signatures_file).await?)
+            .context("Failed to parse claim signatures JSON")?;
+
+    let submission = assemble_submission_inner(&proofs, signatures)?;
+
+    let json = to_artifact_json(&submission)?;
+    stdio::write(&submission_output_file, json).await?;
+    info!(
+        file = ?submission_output_file,
+        sapling_count = submission.sapling.len(),
+        orchard_count = submission.orchard.len(),
+        expiry_height = submission.expiry_height,
+        "Signed claim submission written"
+    );
+
+    Ok(())
+}
+
+/// Merge in-memory claim proofs and signatures into a submission package.
+pub fn assemble_submission_inner(
+    proofs: &ClaimProofsOutput,
+    signatures: ClaimSignatures,
+) -> eyre::Result<ClaimSubmission> {
+    ensure!(
+        proofs.sapling_proofs.len() == signatures.sapling.len(),
+        "Proof/signature count mismatch for Sapling entries"
+    );
+    ensure!(
+        proofs.orchard_proofs.len() == signatures.orchard.len(),
+        "Proof/signature count mismatch for Orchard entries"
+    );
+    let sapling_by_nf: BTreeMap<_, _> = signatures
+        .sapling
+        .into_iter()
+        .map(|signature| (signature.airdrop_nullifier, signature))
+        .collect();
+    let orchard_by_nf: BTreeMap<_, _> = signatures
+        .orchard
+        .into_iter()
+        .map(|signature| (signature.airdrop_nullifier, signature))
+        .collect();
+
+    let mut sapling = Vec::with_capacity(proofs.sapling_proofs.len());
+    for proof in &proofs.sapling_proofs {
+        let signature = sapling_by_nf
+            .get(&proof.airdrop_nullifier)
+            .with_context(|| {
+                format!(
+                    "No signature for Sapling claim with airdrop nullifier {}",
+                    proof.airdrop_nullifier
+                )
+            })?;
+        ensure!(
+            hash_sapling_proof(proof) == signature.proof_hash,
+            "Sapling signature for airdrop nullifier {} was made over a different proof",
+            proof.airdrop_nullifier
+        );
+        sapling.push(SaplingSignedClaim {
+            claim_id: proof.claim_id,
+            zkproof: proof.zkproof,
+            rk: proof.rk,
+            cv: proof.cv,
+            cv_sha256: proof.cv_sha256,
+            airdrop_nullifier: proof.airdrop_nullifier,
+            proof_hash: signature.proof_hash,
+            message_hash: signature.message_hash,
+            spend_auth_sig: signature.spend_auth_sig,
+            countersignatures: Vec::new(),
+        });
+    }
+
+    let mut orchard = Vec::with_capacity(proofs.orchard_proofs.len());
+    for proof in &proofs.orchard_proofs {
+        let signature = orchard_by_nf
+            .get(&proof.airdrop_nullifier)
+            .with_context(|| {
+                format!(
+                    "No signature for Orchard claim with airdrop nullifier {}",
+                    proof.airdrop_nullifier
+                )
+            })?;
+        ensure!(
+            hash_orchard_proof(proof)? == signature.proof_hash,
+            "Orchard signature for airdrop nullifier {} was made over a different proof",
+            proof.airdrop_nullifier
+        );
+        orchard.push(OrchardSignedClaim {
+            claim_id: proof.claim_id,
+            zkproof: proof.zkproof.clone(),
+            rk: proof.rk,
+            cv: proof.cv,
+            cv_sha256: proof.cv_sha256,
+            cv_poseidon: proof.cv_poseidon,
+            airdrop_nullifier: proof.airdrop_nullifier,
+            proof_hash: signature.proof_hash,
+            message_hash: signature.message_hash,
+            spend_auth_sig: signature.spend_auth_sig,
+            countersignatures: Vec::new(),
+        });
+    }
+
+    Ok(ClaimSubmission {
+        nonce: signatures.nonce,
+        expiry_height: signatures.expiry_height,
+        sapling,
+        orchard,
+        epoch: proofs.epoch,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::claim_proofs::{SaplingClaimProofResult, SaplingClaimSecretResult};
+
+    fn sapling_proofs() -> ClaimProofsOutput {
+        ClaimProofsOutput {
+            sapling_proofs: vec![SaplingClaimProofResult {
+                claim_id: [0_u8; 32],
+                zkproof: [1_u8; 192],
+                rk: [2_u8; 32],
+                cv: Some([3_u8; 32]),
+                cv_sha256: None,
+                airdrop_nullifier: Nullifier::from([4_u8; 32]),
+            }],
+            orchard_proofs: vec![],
+            failed_claims: Vec::new(),
+            epoch: None,
+        }
+    }
+
+    #[test]
+    fn signing_request_carries_proof_hash_and_alpha_only() {
+        let proofs = sapling_proofs();
+        let secrets = ClaimSecretsOutput {
+            sapling: vec![SaplingClaimSecretResult {
+                airdrop_nullifier: Nullifier::from([4_u8; 32]),
+                alpha: [5_u8; 32],
+                rcv: Some([6_u8; 32]),
+                rcv_sha256: None,
+                value_blinding: None,
+            }],
+            orchard: vec![],
+            key_fingerprints: None,
+        };
+
+        let request = signing_request_inner(&proofs, &secrets).expect("request");
+        let entry = request.sapling.first().expect("one entry");
+        let proof = proofs.sapling_proofs.first().expect("one proof");
+        assert_eq!(entry.proof_hash, hash_sapling_proof(proof));
+        assert_eq!(entry.rk, [2_u8; 32]);
+        assert_eq!(entry.alpha, [5_u8; 32]);
+        let json = serde_json::to_string(&request).expect("serialize");
+        assert!(
+            !json.contains(&hex::encode([6_u8; 32])),
+            "rcv must not be exported"
+        );
+    }
+
+    #[test]
+    fn assemble_rejects_signature_over_a_different_proof() {
+        let proofs = sapling_proofs();
+        let proof = proofs.sapling_proofs.first().expect("one proof");
+        let signature = ClaimSignature {
+            airdrop_nullifier: proof.airdrop_nullifier,
+            proof_hash: hash_sapling_proof(proof),
+            message_hash: [7_u8; 32],
+            spend_auth_sig: [8_u8; 64],
+        };
+        let signatures = |signature: ClaimSignature| ClaimSignatures {
+            nonce: [9_u8; 32],
+            expiry_height: 100,
+            sapling: vec![signature],
+            orchard: vec![],
+        };
+
+        let submission =
+            assemble_submission_inner(&proofs, signatures(signature.clone())).expect("assemble");
+        let claim = submission.sapling.first().expect("one claim");
+        assert_eq!(submission.nonce, [9_u8; 32]);
+        assert_eq!(claim.spend_auth_sig, [8_u8; 64]);
+        assert_eq!(claim.zkproof, proof.zkproof);
+
+        let err = assemble_submission_inner(
+            &proofs,
+            signatures(ClaimSignature {
+                proof_hash: [0_u8; 32],
+                ..signature
+            }),
+        )
+        .expect_err("must reject a different proof");
+        assert!(
+            err.to_string().contains("was made over a different proof"),
+            "{err:?}"
+        );
+    }
+}
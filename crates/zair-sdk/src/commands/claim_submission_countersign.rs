@@ -0,0 +1,295 @@
+//! Operator countersignatures of claim submissions.
+//!
+//! Custodial deployments can require every claim to be signed by one or more Ed25519 operator
+//! keys in addition to its spend-auth signature. Countersignatures cover the same digest, so they
+//! are bound to the proof, message, target, nonce and expiry of the claim.
+
+use std::path::{Path, PathBuf};
+
+use ed25519_dalek::{Signature, Signer as _, SigningKey, VerifyingKey};
+use eyre::{Context as _, ensure};
+use tracing::{info, warn};
+use zair_core::base::{Pool, signature_digest};
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::submission::{ClaimSubmission, Countersignature};
+
+use crate::build_info::to_artifact_json;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Add the countersignature of an operator key to every claim of a signed submission.
+///
+/// An existing countersignature by the same key is replaced.
+///
+/// # Errors
+/// Returns an error if the inputs cannot be read, the operator key is invalid, or the
+/// configuration has no pool for the signed claims.
+pub async fn countersign_claim_submission(
+    submission_file: PathBuf,
+    operator_key_file: PathBuf,
+    airdrop_configuration_file: PathBuf,
+    submission_output_file: PathBuf,
+) -> ZairResult<()> {
+    countersign_claim_submission_inner(
+        submission_file,
+        operator_key_file,
+        airdrop_configuration_file,
+        submission_output_file,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Signing))
+}
+
+async fn countersign_claim_submission_inner(
+    submission_file: PathBuf,
+    operator_key_file: PathBuf,
+    airdrop_configuration_file: PathBuf,
+    submission_output_file: PathBuf,
+) -> eyre::Result<()> {
+    info!(file = ?submission_file, "Loading signed submission...");
+    let mut submission: ClaimSubmission =
+        serde_json::from_str(&stdio::read_to_string(&submission_file).await?)
+            .context("Failed to parse submission JSON")?;
+
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+
+    info!(file = ?operator_key_file, "Reading operator key...");
+    let key = read_signing_key(&operator_key_file, "operator").await?;
+
+    countersign_submission_inner(&mut submission, &airdrop_config, &key)?;
+
+    let json = to_artifact_json(&submission)?;
+    stdio::write(&submission_output_file, json).await?;
+    info!(
+        file = ?submission_output_file,
+        public_key = hex::encode(key.verifying_key().as_bytes()),
+        sapling_count = submission.sapling.len(),
+        orchard_count = submission.orchard.len(),
+        "Countersigned claim submission written"
+    );
+
+    Ok(())
+}
+
+/// Read the hex-encoded 32-byte Ed25519 secret key of `role`, such as `operator`.
+pub(crate) async fn read_signing_key(path: &Path, role: &str) -> eyre::Result<SigningKey> {
+    let key_hex = zeroize::Zeroizing::new(
+        stdio::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read {role} key file {}", path.display()))?,
+    );
+    let key_bytes = zeroize::Zeroizing::new(
+        hex::decode(key_hex.trim()).with_context(|| format!("Invalid hex {role} key"))?,
+    );
+    let key_bytes: &[u8; 32] = key_bytes.as_slice().try_into().map_err(|_| {
+        eyre::eyre!(
+            "The {role} key must be exactly 32 bytes (64 hex characters), got {} bytes",
+            key_bytes.len()
+        )
+    })?;
+    Ok(SigningKey::from_bytes(key_bytes))
+}
+
+/// Countersign every claim of an in-memory submission with `key`.
+pub(crate) fn countersign_submission_inner(
+    submission: &mut ClaimSubmission,
+    airdrop_config: &AirdropConfiguration,
+    key: &SigningKey,
+) -> eyre::Result<()> {
+    ensure!(
+        !(submission.sapling.is_empty() && submission.orchard.is_empty()),
+        "Submission contains no signed claims"
+    );
+    let airdrop_config = &airdrop_config.for_epoch(submission.epoch)?;
+    let public_key = key.verifying_key().to_bytes();
+
+    for entry in &mut submission.sapling {
+        let domain = airdrop_config.signature_domain(Pool::Sapling).context(
+            "Sapling signed claims provided, but airdrop configuration has no sapling pool",
+        )?;
+        let digest = signature_digest(
+            &domain,
+            &entry.proof_hash,
+            &entry.message_hash,
+            &submission.nonce,
+            submission.expiry_height,
+        );
+        add_countersignature(&mut entry.countersignatures, key, public_key, &digest);
+    }
+
+    for entry in &mut submission.orchard {
+        let domain = airdrop_config.signature_domain(Pool::Orchard).context(
+            "Orchard signed claims provided, but airdrop configuration has no orchard pool",
+        )?;
+        let digest = signature_digest(
+            &domain,
+            &entry.proof_hash,
+            &entry.message_hash,
+            &submission.nonce,
+            submission.expiry_height,
+        );
+        add_countersignature(&mut entry.countersignatures, key, public_key, &digest);
+    }
+
+    Ok(())
+}
+
+fn add_countersignature(
+    countersignatures: &mut Vec<Countersignature>,
+    key: &SigningKey,
+    public_key: [u8; 32],
+    digest: &[u8; 32],
+) {
+    countersignatures.retain(|existing| existing.public_key != public_key);
+    countersignatures.push(Countersignature {
+        public_key,
+        signature: key.sign(digest).to_bytes(),
+    });
+}
+
+/// Check the countersignatures of one claim against its digest and the required countersigners.
+///
+/// Returns `false` and logs the reason if any countersignature is invalid or a required
+/// countersigner is missing.
+pub(crate) fn check_countersignatures(
+    pool: Pool,
+    index: usize,
+    countersignatures: &[Countersignature],
+    countersigners: &[[u8; 32]],
+    digest: &[u8; 32],
+) -> bool {
+    let mut valid = true;
+    for countersignature in countersignatures {
+        let verified = VerifyingKey::from_bytes(&countersignature.public_key).is_ok_and(|key| {
+            key.verify_strict(digest, &Signature::from_bytes(&countersignature.signature))
+                .is_ok()
+        });
+        if !verified {
+            valid = false;
+            warn!(
+                %pool,
+                index,
+                public_key = hex::encode(countersignature.public_key),
+                "Countersignature INVALID"
+            );
+        }
+    }
+    for countersigner in countersigners {
+        if !countersignatures
+            .iter()
+            .any(|countersignature| countersignature.public_key == *countersigner)
+        {
+            valid = false;
+            warn!(
+                %pool,
+                index,
+                public_key = hex::encode(countersigner),
+                "Countersignature MISSING"
+            );
+        }
+    }
+    valid
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::base::{Nullifier, TargetId};
+    use zair_core::schema::config::{
+        AirdropNetwork, CircuitConfiguration, SaplingSnapshot, SproutPolicy, ValueCommitmentScheme,
+    };
+    use zair_core::schema::submission::SaplingSignedClaim;
+
+    use super::*;
+
+    fn sapling_config(countersigners: Vec<[u8; 32]>) -> AirdropConfiguration {
+        AirdropConfiguration {
+            network: AirdropNetwork::Testnet,
+            snapshot_height: 1,
+            sapling: Some(SaplingSnapshot {
+                note_commitment_root: [0_u8; 32],
+                nullifier_gap_root: [0_u8; 32],
+                target_id: TargetId::new(Pool::Sapling, "ZAIRTEST").expect("valid target ID"),
+                value_commitment_scheme: ValueCommitmentScheme::Native,
+                verifying_key_fingerprint: None,
+            }),
+            orchard: None,
+            sprout: SproutPolicy::Excluded,
+            transparent: None,
+            min_note_value: None,
+            max_claims_per_submission: None,
+            circuit: CircuitConfiguration::default(),
+            network_upgrades: None,
+            claim_start_height: None,
+            claim_expiry_height: None,
+            countersigners,
+            chain_id: None,
+            epochs: Vec::new(),
+        }
+    }
+
+    fn submission() -> ClaimSubmission {
+        ClaimSubmission {
+            nonce: [1_u8; 32],
+            expiry_height: 100,
+            sapling: vec![SaplingSignedClaim {
+                claim_id: [0_u8; 32],
+                zkproof: [11_u8; 192],
+                rk: [22_u8; 32],
+                cv: Some([33_u8; 32]),
+                cv_sha256: None,
+                airdrop_nullifier: Nullifier::from([44_u8; 32]),
+                proof_hash: [55_u8; 32],
+                message_hash: [66_u8; 32],
+                spend_auth_sig: [0_u8; 64],
+                countersignatures: Vec::new(),
+            }],
+            orchard: vec![],
+            epoch: None,
+        }
+    }
+
+    fn check(submission: &ClaimSubmission, config: &AirdropConfiguration) -> bool {
+        let entry = submission.sapling.first().expect("one claim");
+        let digest = signature_digest(
+            &config.signature_domain(Pool::Sapling).expect("domain"),
+            &entry.proof_hash,
+            &entry.message_hash,
+            &submission.nonce,
+            submission.expiry_height,
+        );
+        check_countersignatures(
+            Pool::Sapling,
+            0,
+            &entry.countersignatures,
+            &config.countersigners,
+            &digest,
+        )
+    }
+
+    #[test]
+    fn countersignatures_cover_required_keys_and_bind_the_digest() {
+        let operator = SigningKey::from_bytes(&[7_u8; 32]);
+        let other = SigningKey::from_bytes(&[8_u8; 32]);
+        let config = sapling_config(vec![operator.verifying_key().to_bytes()]);
+
+        let mut submission = submission();
+        assert!(!check(&submission, &config), "missing countersignature");
+
+        countersign_submission_inner(&mut submission, &config, &other).expect("countersign");
+        assert!(!check(&submission, &config), "wrong countersigner");
+
+        countersign_submission_inner(&mut submission, &config, &operator).expect("countersign");
+        countersign_submission_inner(&mut submission, &config, &operator).expect("countersign");
+        let entry = submission.sapling.first().expect("one claim");
+        assert_eq!(entry.countersignatures.len(), 2, "re-signing replaces");
+        assert!(check(&submission, &config));
+
+        submission.expiry_height = 101;
+        assert!(
+            !check(&submission, &config),
+            "countersignature binds expiry"
+        );
+    }
+}
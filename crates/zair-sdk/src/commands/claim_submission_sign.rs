@@ -1,29 +1,41 @@
 //! Claim submission signing command implementation.
 
 use std::collections::BTreeMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use eyre::{Context as _, ContextCompat as _, ensure};
-use secrecy::ExposeSecret;
+use rand_core::RngCore as _;
 use tracing::info;
 use zair_core::base::{Pool, signature_digest};
 use zair_core::schema::config::AirdropConfiguration;
 use zair_core::schema::submission::{ClaimSubmission, OrchardSignedClaim, SaplingSignedClaim};
 
+use super::claim_message::{CLAIM_MESSAGES_FILE, ClaimRecipients, write_claim_messages};
+use super::claim_pools::PoolEntries as _;
 use super::claim_proofs::{ClaimProofsOutput, ClaimSecretsOutput};
+use super::key_fingerprint::ensure_same_wallet;
 use super::nullifier_uniqueness::ensure_unique_airdrop_nullifiers;
 use super::signature_digest::{hash_orchard_proof, hash_sapling_proof};
 use super::submission_auth::{orchard, sapling};
-use super::submission_messages::resolve_message_hashes;
-use crate::common::to_zcash_network;
-use crate::seed::read_seed_file;
+use super::submission_messages::{ResolvedMessageHashes, resolve_message_hashes};
+use crate::build_info::to_artifact_json;
+use crate::common::{PoolSelection, configured_network};
+use crate::error::{ZairError, ZairResult};
+use crate::seed::{AccountSecret, read_account_secret};
+use crate::stdio;
 
 /// Sign claim proofs into a submission package.
 ///
+/// The submission expires at `expiry_height`, or at the end of the configured claim window if
+/// `None`. Only the proofs of the pools in `pool` are signed.
+///
+/// With `recipients_file`, each claim signs a canonical claim message paying out to its own
+/// address instead of `messages_file`; the messages are written next to the submission.
+///
 /// # Errors
-/// Returns an error if inputs are invalid, key derivation fails, or signing fails.
+/// Returns an error if inputs are invalid, no expiry height is known, key derivation fails, or
+/// signing fails.
 #[allow(
-    clippy::too_many_lines,
     clippy::too_many_arguments,
     clippy::similar_names,
     reason = "CLI entrypoint parameters"
@@ -36,18 +48,119 @@ pub async fn sign_claim_submission(
     airdrop_configuration_file: PathBuf,
     message_file: Option<PathBuf>,
     messages_file: Option<PathBuf>,
+    recipients_file: Option<PathBuf>,
+    expiry_height: Option<u64>,
+    submission_output_file: PathBuf,
+    pool: PoolSelection,
+) -> ZairResult<()> {
+    sign_claim_submission_inner(
+        proofs_file,
+        secrets_file,
+        seed_file,
+        account_id,
+        airdrop_configuration_file,
+        message_file,
+        messages_file,
+        recipients_file,
+        expiry_height,
+        submission_output_file,
+        pool,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Signing))
+}
+
+#[allow(
+    clippy::too_many_arguments,
+    clippy::similar_names,
+    reason = "CLI entrypoint parameters"
+)]
+async fn sign_claim_submission_inner(
+    proofs_file: PathBuf,
+    secrets_file: PathBuf,
+    seed_file: PathBuf,
+    account_id: u32,
+    airdrop_configuration_file: PathBuf,
+    message_file: Option<PathBuf>,
+    messages_file: Option<PathBuf>,
+    recipients_file: Option<PathBuf>,
+    expiry_height: Option<u64>,
     submission_output_file: PathBuf,
+    pool: PoolSelection,
 ) -> eyre::Result<()> {
     info!(file = ?proofs_file, "Loading proofs for signing...");
-    let proofs: ClaimProofsOutput =
-        serde_json::from_str(&tokio::fs::read_to_string(&proofs_file).await?)
+    let mut proofs: ClaimProofsOutput =
+        serde_json::from_str(&stdio::read_to_string(&proofs_file).await?)
             .context("Failed to parse proofs JSON")?;
+    proofs.retain_pools(pool);
 
     info!(file = ?secrets_file, "Loading local secrets...");
-    let secrets: ClaimSecretsOutput =
-        serde_json::from_str(&tokio::fs::read_to_string(&secrets_file).await?)
+    let mut secrets: ClaimSecretsOutput =
+        serde_json::from_str(&stdio::read_to_string(&secrets_file).await?)
             .context("Failed to parse secrets JSON")?;
+    secrets.retain_pools(pool);
+
+    let configuration = stdio::read(&airdrop_configuration_file).await?;
+    let airdrop_config: AirdropConfiguration = serde_json::from_slice(&configuration)
+        .context("Failed to parse airdrop configuration JSON")?;
+
+    info!(file = ?seed_file, "Reading seed from file...");
+    let account_secret = read_account_secret(&seed_file).await?;
+
+    let messages_file = match recipients_file {
+        Some(recipients_file) => {
+            info!(file = ?recipients_file, "Building per-recipient claim messages...");
+            let recipients = ClaimRecipients::read(&recipients_file).await?;
+            let messages_file = submission_output_file
+                .parent()
+                .map_or_else(PathBuf::new, Path::to_path_buf)
+                .join(CLAIM_MESSAGES_FILE);
+            write_claim_messages(&proofs, &configuration, &recipients, None, &messages_file)
+                .await?;
+            Some(messages_file)
+        }
+        None => messages_file,
+    };
+    let message_hashes =
+        resolve_message_hashes(message_file.as_ref(), messages_file.as_ref()).await?;
+
+    let submission = sign_submission_inner(
+        &proofs,
+        secrets,
+        &airdrop_config,
+        &account_secret,
+        account_id,
+        &message_hashes,
+        expiry_height,
+    )?;
+
+    let json = to_artifact_json(&submission)?;
+    stdio::write(&submission_output_file, json).await?;
+    info!(
+        file = ?submission_output_file,
+        sapling_count = submission.sapling.len(),
+        orchard_count = submission.orchard.len(),
+        expiry_height = submission.expiry_height,
+        "Signed claim submission written"
+    );
+
+    Ok(())
+}
 
+/// Sign in-memory claim proofs into a submission package.
+#[allow(
+    clippy::too_many_lines,
+    reason = "Signing keeps all pool/message checks in one flow"
+)]
+pub fn sign_submission_inner(
+    proofs: &ClaimProofsOutput,
+    secrets: ClaimSecretsOutput,
+    airdrop_config: &AirdropConfiguration,
+    account_secret: &AccountSecret,
+    account_id: u32,
+    message_hashes: &ResolvedMessageHashes,
+    expiry_height: Option<u64>,
+) -> eyre::Result<ClaimSubmission> {
     ensure!(
         !(proofs.sapling_proofs.is_empty() && proofs.orchard_proofs.is_empty()),
         "No proofs found to sign"
@@ -82,46 +195,44 @@ pub async fn sign_claim_submission(
             .map(|proof| proof.airdrop_nullifier),
         "Orchard proof",
     )?;
+    let airdrop_config = &airdrop_config.for_epoch(proofs.epoch)?;
 
-    let airdrop_config: AirdropConfiguration =
-        serde_json::from_str(&tokio::fs::read_to_string(&airdrop_configuration_file).await?)
-            .context("Failed to parse airdrop configuration JSON")?;
-
-    let sapling_target_id = if proofs.sapling_proofs.is_empty() {
-        None
-    } else {
-        Some(
-            airdrop_config
-                .sapling
-                .as_ref()
-                .context("Sapling proofs provided, but airdrop configuration has no sapling pool")?
-                .target_id
-                .clone(),
-        )
-    };
-    let orchard_target_id = if proofs.orchard_proofs.is_empty() {
-        None
-    } else {
-        Some(
-            airdrop_config
-                .orchard
-                .as_ref()
-                .context("Orchard proofs provided, but airdrop configuration has no orchard pool")?
-                .target_id
-                .clone(),
-        )
-    };
+    let sapling_domain =
+        if proofs.sapling_proofs.is_empty() {
+            None
+        } else {
+            Some(airdrop_config.signature_domain(Pool::Sapling).context(
+                "Sapling proofs provided, but airdrop configuration has no sapling pool",
+            )?)
+        };
+    let orchard_domain =
+        if proofs.orchard_proofs.is_empty() {
+            None
+        } else {
+            Some(airdrop_config.signature_domain(Pool::Orchard).context(
+                "Orchard proofs provided, but airdrop configuration has no orchard pool",
+            )?)
+        };
 
-    info!(file = ?seed_file, "Reading seed from file...");
-    let seed = read_seed_file(&seed_file).await?;
+    let expiry_height = resolve_expiry_height(airdrop_config, expiry_height)?;
+    let mut nonce = [0_u8; 32];
+    rand_core::OsRng.fill_bytes(&mut nonce);
 
-    let network = to_zcash_network(airdrop_config.network);
+    let network = configured_network(airdrop_config)?;
+    ensure_same_wallet(
+        secrets.key_fingerprints.as_ref(),
+        airdrop_config,
+        &account_secret
+            .spending_key(network, account_id)?
+            .to_unified_full_viewing_key(),
+        "secrets",
+    )?;
     let sapling_keys = if proofs.sapling_proofs.is_empty() {
         None
     } else {
         Some(sapling::derive_spend_auth_keys(
             network,
-            seed.expose_secret(),
+            account_secret,
             account_id,
         )?)
     };
@@ -130,14 +241,11 @@ pub async fn sign_claim_submission(
     } else {
         Some(orchard::derive_spend_auth_key(
             network,
-            seed.expose_secret(),
+            account_secret,
             account_id,
         )?)
     };
 
-    let message_hashes =
-        resolve_message_hashes(message_file.as_ref(), messages_file.as_ref()).await?;
-
     let mut sapling_secret_by_nf = BTreeMap::new();
     for secret in secrets.sapling {
         let existing = sapling_secret_by_nf.insert(secret.airdrop_nullifier, secret);
@@ -152,9 +260,9 @@ pub async fn sign_claim_submission(
         let secret = sapling_secret_by_nf
             .get(&proof.airdrop_nullifier)
             .context("Missing secret material for Sapling proof entry")?;
-        let target_id = sapling_target_id
-            .as_deref()
-            .context("Sapling target_id must be present for Sapling signing")?;
+        let domain = sapling_domain
+            .as_ref()
+            .context("Sapling signature domain must be present for Sapling signing")?;
         let message_hash = message_hashes
             .sapling_hash(proof.airdrop_nullifier)
             .with_context(|| {
@@ -164,18 +272,14 @@ pub async fn sign_claim_submission(
                 )
             })?;
         let proof_hash = hash_sapling_proof(proof);
-        let digest = signature_digest(
-            Pool::Sapling,
-            target_id.as_bytes(),
-            &proof_hash,
-            &message_hash,
-        )?;
+        let digest = signature_digest(domain, &proof_hash, &message_hash, &nonce, expiry_height);
 
         let keys = sapling_keys
             .as_ref()
             .context("Sapling signing key should be initialized")?;
         let spend_auth_sig = sapling::sign_claim(proof, secret, keys, &digest)?;
         sapling.push(SaplingSignedClaim {
+            claim_id: proof.claim_id,
             zkproof: proof.zkproof,
             rk: proof.rk,
             cv: proof.cv,
@@ -184,6 +288,7 @@ pub async fn sign_claim_submission(
             proof_hash,
             message_hash,
             spend_auth_sig,
+            countersignatures: Vec::new(),
         });
     }
 
@@ -201,9 +306,9 @@ pub async fn sign_claim_submission(
         let secret = orchard_secret_by_nf
             .get(&proof.airdrop_nullifier)
             .context("Missing secret material for Orchard proof entry")?;
-        let target_id = orchard_target_id
-            .as_deref()
-            .context("Orchard target_id must be present for Orchard signing")?;
+        let domain = orchard_domain
+            .as_ref()
+            .context("Orchard signature domain must be present for Orchard signing")?;
         let message_hash = message_hashes
             .orchard_hash(proof.airdrop_nullifier)
             .with_context(|| {
@@ -213,39 +318,57 @@ pub async fn sign_claim_submission(
                 )
             })?;
         let proof_hash = hash_orchard_proof(proof)?;
-        let digest = signature_digest(
-            Pool::Orchard,
-            target_id.as_bytes(),
-            &proof_hash,
-            &message_hash,
-        )?;
+        let digest = signature_digest(domain, &proof_hash, &message_hash, &nonce, expiry_height);
 
         let key = orchard_key
             .as_ref()
             .context("Orchard signing key should be initialized")?;
         let spend_auth_sig = orchard::sign_claim(proof, secret, key, &digest)?;
         orchard.push(OrchardSignedClaim {
+            claim_id: proof.claim_id,
             zkproof: proof.zkproof.clone(),
             rk: proof.rk,
             cv: proof.cv,
             cv_sha256: proof.cv_sha256,
+            cv_poseidon: proof.cv_poseidon,
             airdrop_nullifier: proof.airdrop_nullifier,
             proof_hash,
             message_hash,
             spend_auth_sig,
+            countersignatures: Vec::new(),
         });
     }
 
-    let submission = ClaimSubmission { sapling, orchard };
+    Ok(ClaimSubmission {
+        nonce,
+        expiry_height,
+        sapling,
+        orchard,
+        epoch: proofs.epoch,
+    })
+}
 
-    let json = serde_json::to_string_pretty(&submission)?;
-    tokio::fs::write(&submission_output_file, json).await?;
-    info!(
-        file = ?submission_output_file,
-        sapling_count = submission.sapling.len(),
-        orchard_count = submission.orchard.len(),
-        "Signed claim submission written"
+/// Expiry height of a new submission: the requested one, or the end of the claim window.
+pub(super) fn resolve_expiry_height(
+    airdrop_config: &AirdropConfiguration,
+    expiry_height: Option<u64>,
+) -> eyre::Result<u64> {
+    let expiry_height = expiry_height
+        .or(airdrop_config.claim_expiry_height)
+        .context(
+            "No expiry height for the submission. Provide --expiry-height or a configuration with \
+             claim_expiry_height",
+        )?;
+    ensure!(
+        expiry_height > airdrop_config.snapshot_height,
+        "Expiry height {expiry_height} must be above the snapshot height {}",
+        airdrop_config.snapshot_height
     );
-
-    Ok(())
+    if let Some(window_end) = airdrop_config.claim_expiry_height {
+        ensure!(
+            expiry_height <= window_end,
+            "Expiry height {expiry_height} is past the claim window ending at height {window_end}"
+        );
+    }
+    Ok(expiry_height)
 }
@@ -8,36 +8,118 @@ use zair_core::base::{Pool, signature_digest};
 use zair_core::schema::config::AirdropConfiguration;
 use zair_core::schema::submission::ClaimSubmission;
 
+use super::claim_submission_countersign::check_countersignatures;
 use super::nullifier_uniqueness::ensure_unique_airdrop_nullifiers;
 use super::signature_digest::hash_sapling_signed_claim_proof;
-use super::submission_messages::resolve_message_hashes;
+use super::submission_messages::{ResolvedMessageHashes, resolve_message_hashes};
 use crate::commands::signature_digest::hash_orchard_signed_claim_proof;
+use crate::common::PoolSelection;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
 
 /// Verify spend-auth signatures in a submission package.
 ///
+/// With `current_height`, a submission whose expiry height is below it is rejected. Claims must
+/// carry a valid countersignature by every countersigner of the configuration, and any other
+/// countersignature present must be valid as well. Only the claims of the pools in `pool` are
+/// verified.
+///
 /// # Errors
 /// Returns an error if parsing fails, digest mismatches are found, config-binding checks fail,
-/// or any signature is invalid.
+/// the submission has expired, or any signature is invalid.
 #[allow(
-    clippy::too_many_lines,
     clippy::similar_names,
-    reason = "Verification entrypoint intentionally keeps all pool/message checks in one flow"
+    reason = "message_file vs messages_file are distinct CLI args"
 )]
 pub async fn verify_claim_submission_signature(
     submission_file: PathBuf,
     message_file: Option<PathBuf>,
     messages_file: Option<PathBuf>,
     airdrop_configuration_file: PathBuf,
+    current_height: Option<u64>,
+    pool: PoolSelection,
+) -> ZairResult<()> {
+    verify_claim_submission_signature_inner(
+        submission_file,
+        message_file,
+        messages_file,
+        airdrop_configuration_file,
+        current_height,
+        pool,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+#[allow(
+    clippy::similar_names,
+    reason = "message_file vs messages_file are distinct CLI args"
+)]
+async fn verify_claim_submission_signature_inner(
+    submission_file: PathBuf,
+    message_file: Option<PathBuf>,
+    messages_file: Option<PathBuf>,
+    airdrop_configuration_file: PathBuf,
+    current_height: Option<u64>,
+    pool: PoolSelection,
 ) -> eyre::Result<()> {
     info!(file = ?submission_file, "Loading signed submission...");
-    let submission: ClaimSubmission =
-        serde_json::from_str(&tokio::fs::read_to_string(&submission_file).await?)
+    let mut submission: ClaimSubmission =
+        serde_json::from_str(&stdio::read_to_string(&submission_file).await?)
             .context("Failed to parse submission JSON")?;
+    if !pool.includes_sapling() {
+        submission.sapling.clear();
+    }
+    if !pool.includes_orchard() {
+        submission.orchard.clear();
+    }
+
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
 
+    let message_hashes =
+        resolve_message_hashes(message_file.as_ref(), messages_file.as_ref()).await?;
+
+    verify_submission_signatures_inner(
+        &submission,
+        &airdrop_config,
+        &message_hashes,
+        current_height,
+    )
+}
+
+/// Verify spend-auth signatures of an in-memory submission package.
+#[allow(
+    clippy::too_many_lines,
+    reason = "Verification intentionally keeps all pool/message checks in one flow"
+)]
+pub fn verify_submission_signatures_inner(
+    submission: &ClaimSubmission,
+    airdrop_config: &AirdropConfiguration,
+    message_hashes: &ResolvedMessageHashes,
+    current_height: Option<u64>,
+) -> eyre::Result<()> {
     ensure!(
         !(submission.sapling.is_empty() && submission.orchard.is_empty()),
         "Submission contains no signed claims"
     );
+    let airdrop_config = &airdrop_config.for_epoch(submission.epoch)?;
+    ensure_not_expired(
+        "Submission",
+        submission.expiry_height,
+        airdrop_config,
+        current_height,
+    )?;
+    airdrop_config.ensure_claim_count(
+        submission
+            .sapling
+            .len()
+            .saturating_add(submission.orchard.len()),
+    )?;
+    if let Some(current_height) = current_height {
+        airdrop_config.ensure_claim_window_open(current_height)?;
+    }
     ensure_unique_airdrop_nullifiers(
         submission
             .sapling
@@ -53,44 +135,28 @@ pub async fn verify_claim_submission_signature(
         "Orchard signed claim",
     )?;
 
-    let airdrop_config: AirdropConfiguration =
-        serde_json::from_str(&tokio::fs::read_to_string(&airdrop_configuration_file).await?)
-            .context("Failed to parse airdrop configuration JSON")?;
-    let sapling_target_id = if submission.sapling.is_empty() {
+    let sapling_domain = if submission.sapling.is_empty() {
         None
     } else {
-        Some(
-            airdrop_config
-                .sapling
-                .as_ref()
-                .context(
-                    "Sapling signed claims provided, but airdrop configuration has no sapling pool",
-                )?
-                .target_id
-                .clone(),
-        )
+        Some(airdrop_config.signature_domain(Pool::Sapling).context(
+            "Sapling signed claims provided, but airdrop configuration has no sapling pool",
+        )?)
     };
-    let orchard_target_id = if submission.orchard.is_empty() {
+    let orchard_domain = if submission.orchard.is_empty() {
         None
     } else {
-        Some(
-            airdrop_config
-                .orchard
-                .as_ref()
-                .context(
-                    "Orchard signed claims provided, but airdrop configuration has no orchard pool",
-                )?
-                .target_id
-                .clone(),
-        )
+        Some(airdrop_config.signature_domain(Pool::Orchard).context(
+            "Orchard signed claims provided, but airdrop configuration has no orchard pool",
+        )?)
     };
 
-    let message_hashes =
-        resolve_message_hashes(message_file.as_ref(), messages_file.as_ref()).await?;
-
     let mut invalid_count = 0_usize;
 
     for (idx, entry) in submission.sapling.iter().enumerate() {
+        ensure!(
+            entry.claim_id == airdrop_config.claim_id(Pool::Sapling, &entry.airdrop_nullifier)?,
+            "Sapling claim id mismatch at index {idx}"
+        );
         let expected_proof_hash = hash_sapling_signed_claim_proof(entry);
         ensure!(
             expected_proof_hash == entry.proof_hash,
@@ -110,21 +176,29 @@ pub async fn verify_claim_submission_signature(
             "Sapling message hash mismatch at index {idx}"
         );
 
-        let target_id = sapling_target_id
-            .as_deref()
-            .context("Sapling target_id must be present for Sapling signature verification")?;
+        let domain = sapling_domain.as_ref().context(
+            "Sapling signature domain must be present for Sapling signature verification",
+        )?;
         let digest = signature_digest(
-            Pool::Sapling,
-            target_id.as_bytes(),
+            domain,
             &entry.proof_hash,
             &entry.message_hash,
-        )?;
+            &submission.nonce,
+            submission.expiry_height,
+        );
 
         let is_valid =
             zair_sapling_proofs::verify_signature(entry.rk, entry.spend_auth_sig, &digest)
                 .with_context(|| format!("Invalid Sapling signature encoding at index {idx}"))
                 .is_ok();
-        if is_valid {
+        let countersigned = check_countersignatures(
+            Pool::Sapling,
+            idx,
+            &entry.countersignatures,
+            &airdrop_config.countersigners,
+            &digest,
+        );
+        if is_valid && countersigned {
             info!(
                 index = idx,
                 airdrop_nullifier = %entry.airdrop_nullifier,
@@ -132,6 +206,8 @@ pub async fn verify_claim_submission_signature(
             );
         } else {
             invalid_count = invalid_count.saturating_add(1);
+        }
+        if !is_valid {
             warn!(
                 index = idx,
                 airdrop_nullifier = %entry.airdrop_nullifier,
@@ -141,6 +217,10 @@ pub async fn verify_claim_submission_signature(
     }
 
     for (idx, entry) in submission.orchard.iter().enumerate() {
+        ensure!(
+            entry.claim_id == airdrop_config.claim_id(Pool::Orchard, &entry.airdrop_nullifier)?,
+            "Orchard claim id mismatch at index {idx}"
+        );
         let expected_proof_hash = hash_orchard_signed_claim_proof(entry)?;
         ensure!(
             expected_proof_hash == entry.proof_hash,
@@ -160,21 +240,29 @@ pub async fn verify_claim_submission_signature(
             "Orchard message hash mismatch at index {idx}"
         );
 
-        let target_id = orchard_target_id
-            .as_deref()
-            .context("Orchard target_id must be present for Orchard signature verification")?;
+        let domain = orchard_domain.as_ref().context(
+            "Orchard signature domain must be present for Orchard signature verification",
+        )?;
         let digest = signature_digest(
-            Pool::Orchard,
-            target_id.as_bytes(),
+            domain,
             &entry.proof_hash,
             &entry.message_hash,
-        )?;
+            &submission.nonce,
+            submission.expiry_height,
+        );
 
         let is_valid =
             zair_orchard_proofs::verify_signature(entry.rk, entry.spend_auth_sig, &digest)
                 .with_context(|| format!("Invalid Orchard signature encoding at index {idx}"))
                 .is_ok();
-        if is_valid {
+        let countersigned = check_countersignatures(
+            Pool::Orchard,
+            idx,
+            &entry.countersignatures,
+            &airdrop_config.countersigners,
+            &digest,
+        );
+        if is_valid && countersigned {
             info!(
                 index = idx,
                 airdrop_nullifier = %entry.airdrop_nullifier,
@@ -182,6 +270,8 @@ pub async fn verify_claim_submission_signature(
             );
         } else {
             invalid_count = invalid_count.saturating_add(1);
+        }
+        if !is_valid {
             warn!(
                 index = idx,
                 airdrop_nullifier = %entry.airdrop_nullifier,
@@ -203,15 +293,44 @@ pub async fn verify_claim_submission_signature(
     Ok(())
 }
 
+/// Check the signed expiry height of `what` against the claim window and the current chain height.
+pub(super) fn ensure_not_expired(
+    what: &str,
+    expiry_height: u64,
+    airdrop_config: &AirdropConfiguration,
+    current_height: Option<u64>,
+) -> eyre::Result<()> {
+    ensure!(
+        expiry_height > airdrop_config.snapshot_height,
+        "{what} expiry height {expiry_height} is not above the snapshot height {}",
+        airdrop_config.snapshot_height
+    );
+    if let Some(window_end) = airdrop_config.claim_expiry_height {
+        ensure!(
+            expiry_height <= window_end,
+            "{what} expiry height {expiry_height} is past the claim window ending at height \
+             {window_end}"
+        );
+    }
+    if let Some(current_height) = current_height {
+        ensure!(
+            current_height <= expiry_height,
+            "{what} expired at height {expiry_height}, current height is {current_height}"
+        );
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::Path;
 
     use serde::Serialize;
     use tempfile::tempdir;
-    use zair_core::base::{Nullifier, hash_message};
+    use zair_core::base::{Nullifier, TargetId, hash_message};
     use zair_core::schema::config::{
-        AirdropConfiguration, AirdropNetwork, SaplingSnapshot, ValueCommitmentScheme,
+        AirdropConfiguration, AirdropNetwork, CircuitConfiguration, SaplingSnapshot, SproutPolicy,
+        ValueCommitmentScheme,
     };
     use zair_core::schema::submission::{ClaimSubmission, OrchardSignedClaim, SaplingSignedClaim};
 
@@ -230,15 +349,28 @@ mod tests {
             sapling: Some(SaplingSnapshot {
                 note_commitment_root: [0_u8; 32],
                 nullifier_gap_root: [0_u8; 32],
-                target_id: "ZAIRTEST".to_owned(),
+                target_id: TargetId::new(Pool::Sapling, "ZAIRTEST").expect("valid target ID"),
                 value_commitment_scheme: ValueCommitmentScheme::Native,
+                verifying_key_fingerprint: None,
             }),
             orchard: None,
+            sprout: SproutPolicy::Excluded,
+            transparent: None,
+            min_note_value: None,
+            max_claims_per_submission: None,
+            circuit: CircuitConfiguration::default(),
+            network_upgrades: None,
+            claim_start_height: None,
+            claim_expiry_height: None,
+            countersigners: Vec::new(),
+            chain_id: None,
+            epochs: Vec::new(),
         }
     }
 
     fn sample_sapling_claim() -> SaplingSignedClaim {
         let mut claim = SaplingSignedClaim {
+            claim_id: [0_u8; 32],
             zkproof: [11_u8; 192],
             rk: [22_u8; 32],
             cv: Some([33_u8; 32]),
@@ -247,8 +379,12 @@ mod tests {
             proof_hash: [0_u8; 32],
             message_hash: [0_u8; 32],
             spend_auth_sig: [0_u8; 64],
+            countersignatures: Vec::new(),
         };
         claim.proof_hash = hash_sapling_signed_claim_proof(&claim);
+        claim.claim_id = sapling_config()
+            .claim_id(Pool::Sapling, &claim.airdrop_nullifier)
+            .expect("claim id");
         claim
     }
 
@@ -259,15 +395,25 @@ mod tests {
         let config_path = dir.path().join("config.json");
 
         let submission = ClaimSubmission {
+            nonce: [1_u8; 32],
+            expiry_height: 100,
             sapling: vec![sample_sapling_claim()],
             orchard: vec![],
+            epoch: None,
         };
         write_json(&submission_path, &submission);
         write_json(&config_path, &sapling_config());
 
-        let err = verify_claim_submission_signature(submission_path, None, None, config_path)
-            .await
-            .expect_err("verification must fail without a message");
+        let err = verify_claim_submission_signature(
+            submission_path,
+            None,
+            None,
+            config_path,
+            None,
+            PoolSelection::Both,
+        )
+        .await
+        .expect_err("verification must fail without a message");
 
         assert!(
             err.to_string()
@@ -289,8 +435,11 @@ mod tests {
         claim.message_hash = hash_message(b"test-message");
 
         let submission = ClaimSubmission {
+            nonce: [1_u8; 32],
+            expiry_height: 100,
             sapling: vec![claim],
             orchard: vec![],
+            epoch: None,
         };
         write_json(&submission_path, &submission);
         write_json(&config_path, &sapling_config());
@@ -300,6 +449,8 @@ mod tests {
             Some(message_path),
             None,
             config_path,
+            None,
+            PoolSelection::Both,
         )
         .await
         .expect_err("verification must fail for proof hash mismatch");
@@ -318,24 +469,37 @@ mod tests {
         let config_path = dir.path().join("config.json");
 
         let submission = ClaimSubmission {
+            nonce: [1_u8; 32],
+            expiry_height: 100,
             sapling: vec![],
             orchard: vec![OrchardSignedClaim {
+                claim_id: [0_u8; 32],
                 zkproof: vec![1_u8, 2_u8, 3_u8],
                 rk: [3_u8; 32],
                 cv: Some([4_u8; 32]),
                 cv_sha256: None,
+                cv_poseidon: None,
                 airdrop_nullifier: Nullifier::from([5_u8; 32]),
                 proof_hash: [6_u8; 32],
                 message_hash: [7_u8; 32],
                 spend_auth_sig: [8_u8; 64],
+                countersignatures: Vec::new(),
             }],
+            epoch: None,
         };
         write_json(&submission_path, &submission);
         write_json(&config_path, &sapling_config());
 
-        let err = verify_claim_submission_signature(submission_path, None, None, config_path)
-            .await
-            .expect_err("verification must fail when orchard config is missing");
+        let err = verify_claim_submission_signature(
+            submission_path,
+            None,
+            None,
+            config_path,
+            None,
+            PoolSelection::Both,
+        )
+        .await
+        .expect_err("verification must fail when orchard config is missing");
 
         assert!(
             err.to_string().contains(
@@ -344,4 +508,112 @@ mod tests {
             "{err:?}"
         );
     }
+
+    #[tokio::test]
+    async fn verify_rejects_expired_submission_and_expiry_past_claim_window() {
+        let dir = tempdir().expect("tempdir");
+        let submission_path = dir.path().join("submission.json");
+        let config_path = dir.path().join("config.json");
+
+        let submission = ClaimSubmission {
+            nonce: [1_u8; 32],
+            expiry_height: 100,
+            sapling: vec![sample_sapling_claim()],
+            orchard: vec![],
+            epoch: None,
+        };
+        write_json(&submission_path, &submission);
+        write_json(&config_path, &sapling_config());
+
+        let err = verify_claim_submission_signature(
+            submission_path.clone(),
+            None,
+            None,
+            config_path.clone(),
+            Some(101),
+            PoolSelection::Both,
+        )
+        .await
+        .expect_err("verification must fail after the expiry height");
+        assert!(
+            err.to_string()
+                .contains("Submission expired at height 100, current height is 101"),
+            "{err:?}"
+        );
+
+        let mut config = sapling_config();
+        config.claim_expiry_height = Some(50);
+        write_json(&config_path, &config);
+
+        let err = verify_claim_submission_signature(
+            submission_path,
+            None,
+            None,
+            config_path,
+            None,
+            PoolSelection::Both,
+        )
+        .await
+        .expect_err("verification must fail for an expiry past the claim window");
+        assert!(
+            err.to_string()
+                .contains("past the claim window ending at height 50"),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_enforces_claim_policy_of_configuration() {
+        let dir = tempdir().expect("tempdir");
+        let submission_path = dir.path().join("submission.json");
+        let config_path = dir.path().join("config.json");
+
+        let submission = ClaimSubmission {
+            nonce: [1_u8; 32],
+            expiry_height: 100,
+            sapling: vec![sample_sapling_claim(), sample_sapling_claim()],
+            orchard: vec![],
+            epoch: None,
+        };
+        write_json(&submission_path, &submission);
+        let mut config = sapling_config();
+        config.max_claims_per_submission = Some(1);
+        write_json(&config_path, &config);
+
+        let err = verify_claim_submission_signature(
+            submission_path.clone(),
+            None,
+            None,
+            config_path.clone(),
+            None,
+            PoolSelection::Both,
+        )
+        .await
+        .expect_err("verification must fail above the claim limit");
+        assert!(
+            err.to_string()
+                .contains("2 claims exceed the limit of 1 claims per submission"),
+            "{err:?}"
+        );
+
+        config.max_claims_per_submission = None;
+        config.claim_start_height = Some(80);
+        write_json(&config_path, &config);
+
+        let err = verify_claim_submission_signature(
+            submission_path,
+            None,
+            None,
+            config_path,
+            Some(79),
+            PoolSelection::Both,
+        )
+        .await
+        .expect_err("verification must fail before the claim window opens");
+        assert!(
+            err.to_string()
+                .contains("the claim window opens at height 80, current height is 79"),
+            "{err:?}"
+        );
+    }
 }
@@ -0,0 +1,247 @@
+//! `zair:` claim URIs.
+//!
+//! A claim URI moves a signed submission between devices as text, for example as a QR code. It
+//! either carries the submission inline or points to where it can be downloaded, with its digest:
+//!
+//! - `zair:submission?v=1&data=<base64url compact submission JSON>`
+//! - `zair:submission?v=1&url=<percent-encoded URL>&sha256=<hex digest of the file>`
+//!
+//! Inline URIs grow with the number of claims, so a submission with more than a few claims is
+//! better published and referenced.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use eyre::{Context as _, ContextCompat as _, bail, ensure};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+use sha2::{Digest as _, Sha256};
+use tracing::{info, warn};
+use zair_core::schema::submission::ClaimSubmission;
+
+use super::setup_fetch::fetch_verified_blocking;
+use crate::build_info::to_artifact_json;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Prefix of every claim URI.
+const CLAIM_URI_PREFIX: &str = "zair:submission?";
+
+/// Version of the claim URI format.
+const CLAIM_URI_VERSION: &str = "1";
+
+/// Inline URIs longer than this no longer fit a QR code of version 40 in byte mode.
+const QR_BYTE_CAPACITY: usize = 2_953;
+
+/// Characters escaped in claim URI parameters.
+const URI_ESCAPE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// A signed submission carried by a `zair:` URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimUri {
+    /// The compact submission JSON itself.
+    Inline(Vec<u8>),
+    /// Where the submission file can be downloaded, and its SHA-256 digest.
+    Reference {
+        /// Download URL of the submission file.
+        url: String,
+        /// SHA-256 digest of the submission file.
+        sha256: [u8; 32],
+    },
+}
+
+impl ClaimUri {
+    /// Encode as a `zair:` URI.
+    #[must_use]
+    pub fn encode(&self) -> String {
+        let mut uri = format!("{CLAIM_URI_PREFIX}v={CLAIM_URI_VERSION}");
+        match self {
+            Self::Inline(submission) => {
+                let _ = write!(uri, "&data={}", URL_SAFE_NO_PAD.encode(submission));
+            }
+            Self::Reference { url, sha256 } => {
+                let _ = write!(
+                    uri,
+                    "&url={}&sha256={}",
+                    utf8_percent_encode(url, URI_ESCAPE),
+                    hex::encode(sha256)
+                );
+            }
+        }
+        uri
+    }
+
+    /// Parse a `zair:` URI.
+    ///
+    /// # Errors
+    /// Returns an error if the URI is malformed, has an unsupported version, or carries neither
+    /// inline data nor a reference.
+    pub fn parse(uri: &str) -> eyre::Result<Self> {
+        let query = uri
+            .trim()
+            .strip_prefix(CLAIM_URI_PREFIX)
+            .with_context(|| format!("Not a zair claim URI: {uri}"))?;
+        let mut version = None;
+        let mut data = None;
+        let mut url = None;
+        let mut sha256 = None;
+        for pair in query.split('&') {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match key {
+                "v" => version = Some(value),
+                "data" => {
+                    data = Some(
+                        URL_SAFE_NO_PAD
+                            .decode(value)
+                            .context("Claim URI data is not base64url")?,
+                    );
+                }
+                "url" => {
+                    url = Some(
+                        percent_decode_str(value)
+                            .decode_utf8()
+                            .context("Claim URI url is not UTF-8")?
+                            .into_owned(),
+                    );
+                }
+                "sha256" => {
+                    let mut digest = [0_u8; 32];
+                    hex::decode_to_slice(value, &mut digest)
+                        .context("Claim URI sha256 is not a 32-byte hex digest")?;
+                    sha256 = Some(digest);
+                }
+                _ => {}
+            }
+        }
+        ensure!(
+            version == Some(CLAIM_URI_VERSION),
+            "Unsupported claim URI version {}",
+            version.unwrap_or("(missing)")
+        );
+        match (data, url, sha256) {
+            (Some(data), None, None) => Ok(Self::Inline(data)),
+            (None, Some(url), Some(sha256)) => Ok(Self::Reference { url, sha256 }),
+            (None, Some(_), None) => bail!("Claim URI has a url but no sha256"),
+            _ => bail!("Claim URI must carry either data or a url with its sha256"),
+        }
+    }
+}
+
+/// Encode a signed submission as a `zair:` URI.
+///
+/// With `url`, the URI references the submission published at `url` by the digest of
+/// `submission_file`; otherwise it carries the submission inline.
+///
+/// # Errors
+/// Returns an error if the submission cannot be read or parsed.
+pub async fn encode_claim_uri(submission_file: PathBuf, url: Option<String>) -> ZairResult<String> {
+    encode_claim_uri_inner(&submission_file, url)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+async fn encode_claim_uri_inner(
+    submission_file: &Path,
+    url: Option<String>,
+) -> eyre::Result<String> {
+    let bytes = stdio::read(submission_file)
+        .await
+        .with_context(|| format!("Failed to read {}", submission_file.display()))?;
+    let submission: serde_json::Value =
+        serde_json::from_slice(&bytes).context("Failed to parse submission JSON")?;
+    serde_json::from_value::<ClaimSubmission>(submission.clone())
+        .context("File is not a claim submission")?;
+
+    let uri = match url {
+        Some(url) => ClaimUri::Reference {
+            url,
+            sha256: Sha256::digest(&bytes).into(),
+        },
+        None => ClaimUri::Inline(serde_json::to_vec(&submission)?),
+    }
+    .encode();
+    if uri.len() > QR_BYTE_CAPACITY {
+        warn!(
+            length = uri.len(),
+            "Claim URI does not fit in a single QR code; publish the submission and pass its URL"
+        );
+    }
+    Ok(uri)
+}
+
+/// Write the submission carried by a `zair:` URI to `submission_output_file`.
+///
+/// A referenced submission is downloaded and checked against the URI's digest.
+///
+/// # Errors
+/// Returns an error if the URI is malformed, the download fails or does not match the digest, or
+/// the result is not a claim submission.
+pub async fn decode_claim_uri(uri: String, submission_output_file: PathBuf) -> ZairResult<()> {
+    decode_claim_uri_inner(&uri, submission_output_file)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Network))
+}
+
+async fn decode_claim_uri_inner(uri: &str, submission_output_file: PathBuf) -> eyre::Result<()> {
+    match ClaimUri::parse(uri)? {
+        ClaimUri::Inline(submission) => {
+            let submission: serde_json::Value =
+                serde_json::from_slice(&submission).context("Claim URI data is not JSON")?;
+            serde_json::from_value::<ClaimSubmission>(submission.clone())
+                .context("Claim URI data is not a claim submission")?;
+            stdio::write(&submission_output_file, to_artifact_json(&submission)?).await?;
+        }
+        ClaimUri::Reference { url, sha256 } => {
+            let destination = submission_output_file.clone();
+            tokio::task::spawn_blocking(move || {
+                fetch_verified_blocking(&url, sha256, &destination)
+            })
+            .await??;
+            serde_json::from_str::<ClaimSubmission>(
+                &tokio::fs::read_to_string(&submission_output_file).await?,
+            )
+            .context("Downloaded file is not a claim submission")?;
+        }
+    }
+    info!(file = ?submission_output_file, "Submission written from claim URI");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claim_uris_round_trip() {
+        let inline = ClaimUri::Inline(br#"{"sapling":[]}"#.to_vec());
+        assert_eq!(
+            inline.encode(),
+            "zair:submission?v=1&data=eyJzYXBsaW5nIjpbXX0"
+        );
+        assert_eq!(ClaimUri::parse(&inline.encode()).expect("parse"), inline);
+
+        let reference = ClaimUri::Reference {
+            url: "https://example.org/claims/a b.json".to_owned(),
+            sha256: [0xab; 32],
+        };
+        let encoded = reference.encode();
+        assert!(
+            encoded.starts_with("zair:submission?v=1&url=https%3A%2F%2Fexample.org%2Fclaims%2Fa%20b.json&sha256=abab"),
+            "{encoded}"
+        );
+        assert_eq!(ClaimUri::parse(&encoded).expect("parse"), reference);
+    }
+
+    #[test]
+    fn malformed_claim_uris_are_rejected() {
+        assert!(ClaimUri::parse("magnet:?xt=urn:btih:00").is_err());
+        assert!(ClaimUri::parse("zair:submission?v=2&data=e30").is_err());
+        assert!(ClaimUri::parse("zair:submission?v=1&url=https%3A%2F%2Fexample.org").is_err());
+        assert!(ClaimUri::parse("zair:submission?v=1").is_err());
+    }
+}
@@ -0,0 +1,466 @@
+//! Proofs of the total claimed value.
+//!
+//! A claimant who must report their airdrop size to an auditor proves the total value of their
+//! claims against the value commitments of the published submission. Native value commitments are
+//! Pedersen commitments, so the commitments of a pool add up to a commitment to the total value
+//! under the sum of the blinding factors: opening the sum reveals the total, not the value of any
+//! single claim. SHA-256 and Poseidon commitments do not add up and are opened one by one.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use group::GroupEncoding as _;
+use group::ff::PrimeField as _;
+use pasta_curves::pallas;
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::info;
+use zair_core::base::{Nullifier, Pool};
+use zair_core::schema::proof_inputs::AirdropClaimInputs;
+use zair_core::schema::submission::ClaimSubmission;
+
+use super::claim_pools::PoolEntries as _;
+use super::claim_proofs::{ClaimProofsOutput, ClaimSecretsOutput};
+use super::holdings_attestation::{
+    HoldingsAttestation, ValueOpening, attest_claimed_value, opens_orchard, opens_sapling,
+    total_value,
+};
+use crate::build_info::to_artifact_json;
+use crate::common::PoolSelection;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Default file name of a claimed value proof.
+pub const CLAIMED_VALUE_FILE: &str = "claimed-value.json";
+
+/// Opening of the sum of the native value commitments of one pool's claims.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateValueOpening {
+    /// Pool of the claims.
+    pub pool: Pool,
+    /// Airdrop nullifiers of the claims whose commitments are summed.
+    pub airdrop_nullifiers: Vec<Nullifier>,
+    /// Total value of the claims in zatoshis.
+    pub value: u64,
+    /// Sum of the claims' `rcv`, which opens the sum of their `cv`.
+    #[serde_as(as = "Hex")]
+    pub blinding: [u8; 32],
+}
+
+/// Proof of the total value of a set of claims.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClaimedValueProof {
+    /// Total value of the claims in zatoshis.
+    pub total_value: u64,
+    /// Native commitments, opened together per pool.
+    pub aggregates: Vec<AggregateValueOpening>,
+    /// SHA-256 and Poseidon commitments, opened one by one.
+    pub openings: Vec<ValueOpening>,
+}
+
+/// Write a proof of the total value of the proven claims.
+///
+/// The values and blinding factors come from the value blindings in `secrets_file`, or for
+/// secrets written by earlier versions, the values from the prepared claims in `claims_file`.
+/// Only the claims of the pools in `pool` are included.
+///
+/// # Errors
+/// Returns an error if the inputs cannot be read, a proof has no matching secrets or value, or a
+/// blinding factor is not a canonical scalar.
+pub async fn prove_claimed_value(
+    claims_file: Option<PathBuf>,
+    proofs_file: PathBuf,
+    secrets_file: PathBuf,
+    output_file: PathBuf,
+    pool: PoolSelection,
+) -> ZairResult<()> {
+    prove_claimed_value_inner(claims_file, proofs_file, secrets_file, output_file, pool)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+async fn prove_claimed_value_inner(
+    claims_file: Option<PathBuf>,
+    proofs_file: PathBuf,
+    secrets_file: PathBuf,
+    output_file: PathBuf,
+    pool: PoolSelection,
+) -> eyre::Result<()> {
+    let claims: Option<AirdropClaimInputs> = match &claims_file {
+        Some(file) => Some(
+            serde_json::from_str(&stdio::read_to_string(file).await?)
+                .context("Failed to parse prepared claims JSON")?,
+        ),
+        None => None,
+    };
+    let mut proofs: ClaimProofsOutput =
+        serde_json::from_str(&stdio::read_to_string(&proofs_file).await?)
+            .context("Failed to parse proofs JSON")?;
+    proofs.retain_pools(pool);
+    let secrets: ClaimSecretsOutput =
+        serde_json::from_str(&stdio::read_to_string(&secrets_file).await?)
+            .context("Failed to parse secrets JSON")?;
+
+    let attestation = attest_claimed_value(proofs, &secrets, claims.as_ref())?;
+    let proof = aggregate_openings(attestation)?;
+    stdio::write(&output_file, to_artifact_json(&proof)?)
+        .await
+        .with_context(|| format!("Failed to write {}", output_file.display()))?;
+    info!(
+        file = ?output_file,
+        total_value = proof.total_value,
+        aggregated = proof.aggregates.len(),
+        opened = proof.openings.len(),
+        "Claimed value proof written"
+    );
+    Ok(())
+}
+
+/// Verify a claimed value proof against the value commitments of a signed submission and return
+/// the proven total value.
+///
+/// Only the commitments are checked: verify the submission itself, its proofs and signatures,
+/// with `verify run`.
+///
+/// # Errors
+/// Returns an error if the files cannot be read, a claim is not in the submission or is counted
+/// twice, an opening does not match its commitments, or the openings do not sum to the total.
+pub async fn verify_claimed_value(
+    proof_file: PathBuf,
+    submission_file: PathBuf,
+) -> ZairResult<u64> {
+    verify_claimed_value_inner(proof_file, submission_file)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+async fn verify_claimed_value_inner(
+    proof_file: PathBuf,
+    submission_file: PathBuf,
+) -> eyre::Result<u64> {
+    let proof: ClaimedValueProof = serde_json::from_str(&stdio::read_to_string(&proof_file).await?)
+        .context("Failed to parse claimed value proof JSON")?;
+    let submission: ClaimSubmission =
+        serde_json::from_str(&stdio::read_to_string(&submission_file).await?)
+            .context("Failed to parse claim submission JSON")?;
+
+    ensure_claimed_value(&proof, &ClaimProofsOutput::from(&submission))?;
+    info!(
+        total_value = proof.total_value,
+        "Claimed value proof verified"
+    );
+    Ok(proof.total_value)
+}
+
+/// Sum the openings of native commitments per pool, keeping the others as they are.
+fn aggregate_openings(attestation: HoldingsAttestation) -> eyre::Result<ClaimedValueProof> {
+    let HoldingsAttestation {
+        total_value: total,
+        proofs,
+        openings,
+    } = attestation;
+    let is_native = |opening: &ValueOpening| match opening.pool {
+        Pool::Sapling => proofs.sapling_proofs.iter().any(|proof| {
+            proof.airdrop_nullifier == opening.airdrop_nullifier && proof.cv.is_some()
+        }),
+        Pool::Orchard => proofs.orchard_proofs.iter().any(|proof| {
+            proof.airdrop_nullifier == opening.airdrop_nullifier && proof.cv.is_some()
+        }),
+    };
+    let (native, openings): (Vec<_>, Vec<_>) = openings.into_iter().partition(is_native);
+
+    let mut aggregates = Vec::new();
+    for pool in [Pool::Sapling, Pool::Orchard] {
+        let pool_openings: Vec<ValueOpening> = native
+            .iter()
+            .filter(|opening| opening.pool == pool)
+            .cloned()
+            .collect();
+        if pool_openings.is_empty() {
+            continue;
+        }
+        aggregates.push(AggregateValueOpening {
+            pool,
+            airdrop_nullifiers: pool_openings
+                .iter()
+                .map(|opening| opening.airdrop_nullifier)
+                .collect(),
+            value: total_value(&pool_openings)?,
+            blinding: blinding_sum(
+                pool,
+                pool_openings.iter().map(|opening| &opening.randomness),
+            )?,
+        });
+    }
+
+    Ok(ClaimedValueProof {
+        total_value: total,
+        aggregates,
+        openings,
+    })
+}
+
+/// Sum of the `rcv` scalars of `pool`.
+fn blinding_sum<'a>(
+    pool: Pool,
+    blindings: impl Iterator<Item = &'a [u8; 32]>,
+) -> eyre::Result<[u8; 32]> {
+    Ok(match pool {
+        Pool::Sapling => blindings
+            .map(|rcv| {
+                Option::<jubjub::Fr>::from(jubjub::Fr::from_repr(*rcv))
+                    .context("Sapling rcv is not a canonical scalar")
+            })
+            .collect::<eyre::Result<Vec<_>>>()?
+            .iter()
+            .sum::<jubjub::Fr>()
+            .to_repr(),
+        Pool::Orchard => blindings
+            .map(|rcv| {
+                Option::<pallas::Scalar>::from(pallas::Scalar::from_repr(*rcv))
+                    .context("Orchard rcv is not a canonical scalar")
+            })
+            .collect::<eyre::Result<Vec<_>>>()?
+            .iter()
+            .sum::<pallas::Scalar>()
+            .to_repr(),
+    })
+}
+
+/// Check every opening of `proof` against the value commitments of `commitments`.
+pub(super) fn ensure_claimed_value(
+    proof: &ClaimedValueProof,
+    commitments: &ClaimProofsOutput,
+) -> eyre::Result<()> {
+    let opened: Vec<Nullifier> = proof
+        .aggregates
+        .iter()
+        .flat_map(|aggregate| aggregate.airdrop_nullifiers.iter().copied())
+        .chain(
+            proof
+                .openings
+                .iter()
+                .map(|opening| opening.airdrop_nullifier),
+        )
+        .collect();
+    ensure!(
+        opened.iter().collect::<BTreeSet<_>>().len() == opened.len(),
+        "Claimed value proof counts the same claim more than once"
+    );
+
+    for aggregate in &proof.aggregates {
+        ensure!(
+            !aggregate.airdrop_nullifiers.is_empty(),
+            "Aggregate {} opening covers no claims",
+            aggregate.pool
+        );
+        let cvs = aggregate
+            .airdrop_nullifiers
+            .iter()
+            .map(|nullifier| native_commitment(commitments, aggregate.pool, nullifier))
+            .collect::<eyre::Result<Vec<_>>>()?;
+        ensure!(
+            opens_commitment_sum(aggregate, &cvs)?,
+            "Aggregate {} opening does not match the sum of the value commitments",
+            aggregate.pool
+        );
+    }
+
+    for opening in &proof.openings {
+        let opens = match opening.pool {
+            Pool::Sapling => commitments
+                .sapling_proofs
+                .iter()
+                .find(|proof| proof.airdrop_nullifier == opening.airdrop_nullifier)
+                .map(|proof| opens_sapling(proof, opening)),
+            Pool::Orchard => commitments
+                .orchard_proofs
+                .iter()
+                .find(|proof| proof.airdrop_nullifier == opening.airdrop_nullifier)
+                .map(|proof| opens_orchard(proof, opening)),
+        }
+        .with_context(|| {
+            format!(
+                "Airdrop nullifier {} is not in the submission",
+                opening.airdrop_nullifier
+            )
+        })?;
+        ensure!(
+            opens,
+            "Opening for airdrop nullifier {} does not match its value commitment",
+            opening.airdrop_nullifier
+        );
+    }
+
+    let total =
+        proof
+            .aggregates
+            .iter()
+            .try_fold(total_value(&proof.openings)?, |total, aggregate| {
+                total
+                    .checked_add(aggregate.value)
+                    .context("Total claimed value overflows u64")
+            })?;
+    ensure!(
+        total == proof.total_value,
+        "Openings do not sum to the claimed total value {}",
+        proof.total_value
+    );
+    Ok(())
+}
+
+/// Native value commitment `cv` of the claim with `nullifier` in `pool`.
+fn native_commitment(
+    commitments: &ClaimProofsOutput,
+    pool: Pool,
+    nullifier: &Nullifier,
+) -> eyre::Result<[u8; 32]> {
+    let cv = match pool {
+        Pool::Sapling => commitments
+            .sapling_proofs
+            .iter()
+            .find(|proof| proof.airdrop_nullifier == *nullifier)
+            .map(|proof| proof.cv),
+        Pool::Orchard => commitments
+            .orchard_proofs
+            .iter()
+            .find(|proof| proof.airdrop_nullifier == *nullifier)
+            .map(|proof| proof.cv),
+    }
+    .with_context(|| format!("Airdrop nullifier {nullifier} is not in the submission"))?;
+    cv.with_context(|| format!("Claim {nullifier} has no native value commitment"))
+}
+
+/// Whether `aggregate` opens the sum of the commitments `cvs`.
+fn opens_commitment_sum(aggregate: &AggregateValueOpening, cvs: &[[u8; 32]]) -> eyre::Result<bool> {
+    Ok(match aggregate.pool {
+        Pool::Sapling => {
+            let sum = cvs
+                .iter()
+                .map(|cv| {
+                    Option::<jubjub::ExtendedPoint>::from(jubjub::ExtendedPoint::from_bytes(cv))
+                        .context("Invalid Sapling value commitment")
+                })
+                .collect::<eyre::Result<Vec<_>>>()?
+                .iter()
+                .sum::<jubjub::ExtendedPoint>();
+            let Some(rcv) = Option::<sapling::value::ValueCommitTrapdoor>::from(
+                sapling::value::ValueCommitTrapdoor::from_bytes(aggregate.blinding),
+            ) else {
+                return Ok(false);
+            };
+            let value = sapling::value::NoteValue::from_raw(aggregate.value);
+            sapling::value::ValueCommitment::derive(value, rcv).to_bytes() == sum.to_bytes()
+        }
+        Pool::Orchard => {
+            let sum = cvs
+                .iter()
+                .map(|cv| {
+                    Option::<pallas::Point>::from(pallas::Point::from_bytes(cv))
+                        .context("Invalid Orchard value commitment")
+                })
+                .collect::<eyre::Result<Vec<_>>>()?
+                .iter()
+                .sum::<pallas::Point>();
+            let Some(rcv) = Option::<orchard::value::ValueCommitTrapdoor>::from(
+                orchard::value::ValueCommitTrapdoor::from_bytes(aggregate.blinding),
+            ) else {
+                return Ok(false);
+            };
+            #[allow(
+                clippy::arithmetic_side_effects,
+                reason = "Orchard value commitment API requires NoteValue subtraction to produce ValueSum"
+            )]
+            let value = orchard::value::NoteValue::from_raw(aggregate.value) -
+                orchard::value::NoteValue::from_raw(0);
+            orchard::value::ValueCommitment::derive(value, rcv).to_bytes() == sum.to_bytes()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use group::ff::Field as _;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::commands::SaplingClaimProofResult;
+
+    fn sapling_commitment(value: u64, rcv: &jubjub::Fr) -> [u8; 32] {
+        let rcv = sapling::value::ValueCommitTrapdoor::from_bytes(rcv.to_repr())
+            .into_option()
+            .expect("canonical rcv");
+        sapling::value::ValueCommitment::derive(sapling::value::NoteValue::from_raw(value), rcv)
+            .to_bytes()
+    }
+
+    #[test]
+    fn aggregate_opening_reveals_only_the_total() {
+        let claims = [
+            (1_000_u64, [1_u8; 32]),
+            (2_500, [2_u8; 32]),
+            (40, [3_u8; 32]),
+        ];
+        let mut proofs = Vec::new();
+        let mut openings = Vec::new();
+        for (value, nullifier) in claims {
+            let rcv = jubjub::Fr::random(OsRng);
+            proofs.push(SaplingClaimProofResult {
+                claim_id: nullifier,
+                zkproof: [0_u8; 192],
+                rk: [0_u8; 32],
+                cv: Some(sapling_commitment(value, &rcv)),
+                cv_sha256: None,
+                airdrop_nullifier: Nullifier::from(nullifier),
+            });
+            openings.push(ValueOpening {
+                pool: Pool::Sapling,
+                airdrop_nullifier: Nullifier::from(nullifier),
+                value,
+                randomness: rcv.to_repr(),
+            });
+        }
+        let commitments = ClaimProofsOutput {
+            sapling_proofs: proofs,
+            orchard_proofs: Vec::new(),
+            failed_claims: Vec::new(),
+            epoch: None,
+        };
+
+        let proof = aggregate_openings(HoldingsAttestation {
+            total_value: 3_540,
+            proofs: commitments.clone(),
+            openings,
+        })
+        .expect("openings aggregate");
+        assert!(proof.openings.is_empty(), "native openings are summed");
+        ensure_claimed_value(&proof, &commitments).expect("valid aggregate");
+
+        let mut inflated = proof.clone();
+        for aggregate in &mut inflated.aggregates {
+            aggregate.value = aggregate.value.saturating_add(1);
+        }
+        inflated.total_value = 3_541;
+        assert!(ensure_claimed_value(&inflated, &commitments).is_err());
+
+        let mut partial = proof.clone();
+        for aggregate in &mut partial.aggregates {
+            aggregate.airdrop_nullifiers.pop();
+        }
+        assert!(
+            ensure_claimed_value(&partial, &commitments).is_err(),
+            "dropping a claim breaks the sum"
+        );
+
+        let mut duplicated = proof;
+        for aggregate in &mut duplicated.aggregates {
+            aggregate
+                .airdrop_nullifiers
+                .push(Nullifier::from([1_u8; 32]));
+        }
+        assert!(ensure_claimed_value(&duplicated, &commitments).is_err());
+    }
+}
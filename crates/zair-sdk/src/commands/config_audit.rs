@@ -0,0 +1,226 @@
+//! Domain separation review of an airdrop configuration.
+//!
+//! `zair config audit` checks the target IDs that personalize the airdrop nullifiers against the
+//! personalizations and hash-to-curve domains the Zcash protocol already uses. A target ID equal
+//! to one of them can make the published airdrop nullifiers the notes' Zcash nullifiers, and a
+//! target ID shared with another airdrop makes claims of the same note linkable across both.
+
+use std::path::PathBuf;
+
+use zair_core::base::{Pool, TargetId};
+use zair_core::schema::config::AirdropConfiguration;
+
+use super::doctor::{CheckStatus, DoctorCheck, read_config};
+
+/// Sapling personalization of the published circuit and default of `config build`.
+const DEFAULT_SAPLING_TARGET_ID: &str = "ZAIRTEST";
+/// Default Orchard target ID of `config build`.
+const DEFAULT_ORCHARD_TARGET_ID: &str = "ZAIRTEST:O";
+
+/// Audit the target IDs of the airdrop in `config_file`, and of each of its epochs.
+///
+/// A configuration that cannot be read is reported as a failed check.
+pub async fn audit_airdrop_configuration(config_file: PathBuf) -> Vec<DoctorCheck> {
+    let config = match read_config(&config_file).await {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![DoctorCheck::new(
+                "config",
+                CheckStatus::Fail,
+                format!("{e:#}"),
+            )];
+        }
+    };
+    audit_configuration(&config)
+}
+
+/// Audit the target IDs of `config` and of each of its epochs.
+#[must_use]
+pub fn audit_configuration(config: &AirdropConfiguration) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+    let epochs = std::iter::once(None).chain(config.epochs.iter().map(|epoch| Some(epoch.epoch)));
+    for epoch in epochs {
+        let view = match config.for_epoch(epoch) {
+            Ok(view) => view,
+            Err(e) => {
+                checks.push(DoctorCheck::new("config", CheckStatus::Fail, e.to_string()));
+                continue;
+            }
+        };
+        let label = epoch.map_or_else(String::new, |epoch| format!("Epoch {epoch}: "));
+        if let Some(sapling) = &view.sapling {
+            let mut check = sapling_check(&sapling.target_id, &view);
+            check.detail.insert_str(0, &label);
+            checks.push(check);
+        }
+        if let Some(orchard) = &view.orchard {
+            let mut check = orchard_check(&orchard.target_id);
+            check.detail.insert_str(0, &label);
+            checks.push(check);
+        }
+    }
+    if checks.is_empty() {
+        checks.push(DoctorCheck::new(
+            "config",
+            CheckStatus::Fail,
+            "The configuration has no pool",
+        ));
+    }
+    checks
+}
+
+fn sapling_check(target_id: &TargetId, config: &AirdropConfiguration) -> DoctorCheck {
+    const NAME: &str = "sapling target";
+    if let Err(e) = target_id.ensure_pool(Pool::Sapling) {
+        return DoctorCheck::new(NAME, CheckStatus::Fail, e.to_string());
+    }
+    if let Some(usage) = target_id.zcash_collision(Pool::Sapling) {
+        return DoctorCheck::new(NAME, CheckStatus::Fail, collision(target_id, usage));
+    }
+    if target_id.as_str() != config.circuit.hiding_nf_personalization {
+        return DoctorCheck::new(
+            NAME,
+            CheckStatus::Fail,
+            format!(
+                "{target_id:?} differs from the circuit personalization {:?}; Sapling proofs would \
+                 not verify",
+                config.circuit.hiding_nf_personalization
+            ),
+        );
+    }
+    if target_id.has_zcash_prefix(Pool::Sapling) {
+        return DoctorCheck::new(NAME, CheckStatus::Warn, reserved(target_id));
+    }
+    if target_id.as_str() == DEFAULT_SAPLING_TARGET_ID {
+        return DoctorCheck::new(
+            NAME,
+            CheckStatus::Warn,
+            format!(
+                "{target_id:?} is the default personalization, shared by every airdrop that keeps \
+                 it; compile the Sapling circuit with a personalization of your own"
+            ),
+        );
+    }
+    DoctorCheck::new(NAME, CheckStatus::Pass, unique(target_id))
+}
+
+fn orchard_check(target_id: &TargetId) -> DoctorCheck {
+    const NAME: &str = "orchard target";
+    if let Some(usage) = target_id.zcash_collision(Pool::Orchard) {
+        return DoctorCheck::new(NAME, CheckStatus::Fail, collision(target_id, usage));
+    }
+    if target_id.has_zcash_prefix(Pool::Orchard) {
+        return DoctorCheck::new(NAME, CheckStatus::Warn, reserved(target_id));
+    }
+    if target_id.as_str() == DEFAULT_ORCHARD_TARGET_ID {
+        return DoctorCheck::new(
+            NAME,
+            CheckStatus::Warn,
+            format!(
+                "{target_id:?} is the default of `config build`, shared by every airdrop that \
+                 keeps it; pass --target-orchard"
+            ),
+        );
+    }
+    DoctorCheck::new(NAME, CheckStatus::Pass, unique(target_id))
+}
+
+fn collision(target_id: &TargetId, usage: &str) -> String {
+    format!(
+        "{target_id:?} is the Zcash domain of {usage}; airdrop nullifiers would not be separated \
+         from the protocol"
+    )
+}
+
+fn reserved(target_id: &TargetId) -> String {
+    format!("{target_id:?} is in the namespace the Zcash protocol reserves for its own domains")
+}
+
+fn unique(target_id: &TargetId) -> String {
+    format!("{target_id:?} is not a Zcash domain")
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::schema::config::{
+        AirdropNetwork, OrchardSnapshot, SaplingSnapshot, ValueCommitmentScheme,
+    };
+
+    use super::*;
+
+    fn config(sapling: &str, orchard: &str) -> AirdropConfiguration {
+        AirdropConfiguration::new(
+            AirdropNetwork::Testnet,
+            1,
+            Some(SaplingSnapshot {
+                note_commitment_root: [0; 32],
+                nullifier_gap_root: [0; 32],
+                target_id: TargetId::new(Pool::Sapling, sapling).expect("target ID"),
+                value_commitment_scheme: ValueCommitmentScheme::Native,
+                verifying_key_fingerprint: None,
+            }),
+            Some(OrchardSnapshot {
+                note_commitment_root: [0; 32],
+                nullifier_gap_root: [0; 32],
+                target_id: TargetId::new(Pool::Orchard, orchard).expect("target ID"),
+                value_commitment_scheme: ValueCommitmentScheme::Native,
+                params_fingerprint: None,
+            }),
+        )
+    }
+
+    fn statuses(config: &AirdropConfiguration) -> Vec<(&'static str, CheckStatus)> {
+        audit_configuration(config)
+            .into_iter()
+            .map(|check| (check.name, check.status))
+            .collect()
+    }
+
+    #[test]
+    fn zcash_domains_fail_the_audit() {
+        assert_eq!(
+            statuses(&config("ZAIRTEST", "NAMADA:ORCHARD")),
+            vec![
+                ("sapling target", CheckStatus::Warn),
+                ("orchard target", CheckStatus::Pass)
+            ]
+        );
+        assert_eq!(
+            statuses(&config("Zcash_nf", "z.cash:Orchard")),
+            vec![
+                ("sapling target", CheckStatus::Fail),
+                ("orchard target", CheckStatus::Fail)
+            ]
+        );
+        assert_eq!(
+            statuses(&config("NAMADA_S", "z.cash:Airdrop")),
+            vec![
+                ("sapling target", CheckStatus::Fail),
+                ("orchard target", CheckStatus::Warn)
+            ]
+        );
+    }
+
+    #[test]
+    fn epochs_are_audited() {
+        let mut base = config("ZAIRTEST", "NAMADA:ORCHARD");
+        let mut later = base.clone();
+        later.snapshot_height = 2;
+        if let Some(orchard) = later.orchard.as_mut() {
+            orchard.target_id = TargetId::new(Pool::Orchard, "z.cash:Orchard").expect("target ID");
+        }
+        base.add_epoch(1, later).expect("add epoch");
+
+        let checks = audit_configuration(&base);
+        let failed: Vec<_> = checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .collect();
+        assert_eq!(failed.len(), 1);
+        assert!(
+            failed
+                .iter()
+                .all(|check| check.detail.starts_with("Epoch 1: "))
+        );
+    }
+}
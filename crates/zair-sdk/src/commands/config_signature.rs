@@ -0,0 +1,263 @@
+//! Organizer signatures of a published airdrop.
+//!
+//! `config sign` signs the exact bytes of the airdrop configuration, and optionally of its
+//! artifact manifest, with the organizer's Ed25519 key. Claimants who obtained the organizer's
+//! public key out of band run `config verify-signature` before trusting the snapshot roots,
+//! target IDs and setup fingerprints of a configuration fetched from a mirror.
+
+use std::path::PathBuf;
+
+use ed25519_dalek::{Signature, Signer as _, SigningKey, VerifyingKey};
+use eyre::{Context as _, ensure, eyre};
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::{info, warn};
+use zair_core::base::{hash_bytes, organizer_signature_digest};
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::sighash::config_hash;
+
+use super::artifact_manifest::ArtifactManifest;
+use super::claim_submission_countersign::read_signing_key;
+use crate::build_info::to_artifact_json;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Organizer signature over an airdrop configuration and its artifact manifest.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigSignature {
+    /// Ed25519 public key of the organizer.
+    #[serde_as(as = "Hex")]
+    pub public_key: [u8; 32],
+    /// Hash of the signed configuration file, over its exact bytes.
+    #[serde_as(as = "Hex")]
+    pub config_hash: [u8; 32],
+    /// Hash of the signed artifact manifest, over its exact bytes; absent if none was signed.
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifacts_manifest_hash: Option<[u8; 32]>,
+    /// Ed25519 signature over [`organizer_signature_digest`] of both hashes.
+    #[serde_as(as = "Hex")]
+    pub signature: [u8; 64],
+}
+
+/// Sign the airdrop configuration, and the artifact manifest if given, with the organizer key.
+///
+/// Any later change to either file, such as binding setup keys into the configuration, breaks the
+/// signature, so sign once both are final.
+///
+/// # Errors
+/// Returns an error if the files cannot be read or parsed, the organizer key is invalid, or the
+/// signature cannot be written.
+pub async fn sign_airdrop_configuration(
+    airdrop_configuration_file: PathBuf,
+    artifacts_manifest_file: Option<PathBuf>,
+    organizer_key_file: PathBuf,
+    signature_output_file: PathBuf,
+) -> ZairResult<()> {
+    sign_airdrop_configuration_inner(
+        airdrop_configuration_file,
+        artifacts_manifest_file,
+        organizer_key_file,
+        signature_output_file,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Signing))
+}
+
+async fn sign_airdrop_configuration_inner(
+    airdrop_configuration_file: PathBuf,
+    artifacts_manifest_file: Option<PathBuf>,
+    organizer_key_file: PathBuf,
+    signature_output_file: PathBuf,
+) -> eyre::Result<()> {
+    let configuration = stdio::read(&airdrop_configuration_file).await?;
+    serde_json::from_slice::<AirdropConfiguration>(&configuration)
+        .context("Failed to parse airdrop configuration JSON")?;
+    let manifest = match &artifacts_manifest_file {
+        Some(file) => {
+            let manifest = stdio::read(file).await?;
+            serde_json::from_slice::<ArtifactManifest>(&manifest)
+                .context("Failed to parse artifact manifest JSON")?;
+            Some(manifest)
+        }
+        None => None,
+    };
+
+    info!(file = ?organizer_key_file, "Reading organizer key...");
+    let key = read_signing_key(&organizer_key_file, "organizer").await?;
+    let signature = sign_config(&configuration, manifest.as_deref(), &key);
+
+    stdio::write(&signature_output_file, to_artifact_json(&signature)?).await?;
+    info!(
+        file = ?signature_output_file,
+        public_key = hex::encode(signature.public_key),
+        manifest_signed = signature.artifacts_manifest_hash.is_some(),
+        "Configuration signature written"
+    );
+    Ok(())
+}
+
+/// Verify the organizer signature of an airdrop configuration, and of the artifact manifest if
+/// given, under `organizer_key`.
+///
+/// A signature that covers an artifact manifest still verifies without it, but the manifest is
+/// then not checked.
+///
+/// # Errors
+/// Returns an error if the files cannot be read, the signature was made by another key, either
+/// file differs from the signed one, or the signature does not verify.
+pub async fn verify_airdrop_configuration_signature(
+    airdrop_configuration_file: PathBuf,
+    artifacts_manifest_file: Option<PathBuf>,
+    signature_file: PathBuf,
+    organizer_key: [u8; 32],
+) -> ZairResult<()> {
+    verify_airdrop_configuration_signature_inner(
+        airdrop_configuration_file,
+        artifacts_manifest_file,
+        signature_file,
+        organizer_key,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+async fn verify_airdrop_configuration_signature_inner(
+    airdrop_configuration_file: PathBuf,
+    artifacts_manifest_file: Option<PathBuf>,
+    signature_file: PathBuf,
+    organizer_key: [u8; 32],
+) -> eyre::Result<()> {
+    let signature: ConfigSignature =
+        serde_json::from_str(&stdio::read_to_string(&signature_file).await?)
+            .context("Failed to parse configuration signature JSON")?;
+    let configuration = stdio::read(&airdrop_configuration_file).await?;
+    let manifest = match &artifacts_manifest_file {
+        Some(file) => Some(stdio::read(file).await?),
+        None => None,
+    };
+    if manifest.is_none() && signature.artifacts_manifest_hash.is_some() {
+        warn!("The signature covers an artifact manifest, but none was given to check");
+    }
+
+    verify_config(
+        &signature,
+        &configuration,
+        manifest.as_deref(),
+        &organizer_key,
+    )?;
+    info!(
+        file = ?airdrop_configuration_file,
+        public_key = hex::encode(organizer_key),
+        manifest_checked = manifest.is_some(),
+        "Configuration signature valid"
+    );
+    Ok(())
+}
+
+/// Sign the configuration bytes, and the manifest bytes if given, with `key`.
+fn sign_config(configuration: &[u8], manifest: Option<&[u8]>, key: &SigningKey) -> ConfigSignature {
+    let config_hash = config_hash(configuration);
+    let artifacts_manifest_hash = manifest.map(hash_bytes);
+    let digest = organizer_signature_digest(&config_hash, artifacts_manifest_hash.as_ref());
+    ConfigSignature {
+        public_key: key.verifying_key().to_bytes(),
+        config_hash,
+        artifacts_manifest_hash,
+        signature: key.sign(&digest).to_bytes(),
+    }
+}
+
+/// Check `signature` over the configuration bytes, and the manifest bytes if given, under
+/// `organizer_key`.
+fn verify_config(
+    signature: &ConfigSignature,
+    configuration: &[u8],
+    manifest: Option<&[u8]>,
+    organizer_key: &[u8; 32],
+) -> eyre::Result<()> {
+    ensure!(
+        signature.public_key == *organizer_key,
+        "Configuration was signed by {}, not by the organizer key",
+        hex::encode(signature.public_key)
+    );
+    ensure!(
+        config_hash(configuration) == signature.config_hash,
+        "Configuration differs from the signed configuration"
+    );
+    if let Some(manifest) = manifest {
+        let signed = signature
+            .artifacts_manifest_hash
+            .ok_or_else(|| eyre!("The signature does not cover an artifact manifest"))?;
+        ensure!(
+            hash_bytes(manifest) == signed,
+            "Artifact manifest differs from the signed manifest"
+        );
+    }
+
+    let key = VerifyingKey::from_bytes(organizer_key).context("Invalid organizer key")?;
+    let digest = organizer_signature_digest(
+        &signature.config_hash,
+        signature.artifacts_manifest_hash.as_ref(),
+    );
+    key.verify_strict(&digest, &Signature::from_bytes(&signature.signature))
+        .map_err(|_| eyre!("Configuration signature does not verify under the organizer key"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_binds_the_configuration_and_manifest() {
+        let organizer = SigningKey::from_bytes(&[7_u8; 32]);
+        let organizer_key = organizer.verifying_key().to_bytes();
+        let configuration = br#"{"network":"testnet","snapshot_height":1}"#;
+        let manifest = br#"{"files":[]}"#;
+
+        let signature = sign_config(configuration, Some(manifest), &organizer);
+        verify_config(&signature, configuration, Some(manifest), &organizer_key)
+            .expect("valid signature");
+        verify_config(&signature, configuration, None, &organizer_key)
+            .expect("manifest is optional for the claimant");
+
+        assert!(
+            verify_config(
+                &signature,
+                br#"{"network":"testnet","snapshot_height":2}"#,
+                None,
+                &organizer_key
+            )
+            .is_err(),
+            "configuration changed"
+        );
+        assert!(
+            verify_config(&signature, configuration, Some(b"{}"), &organizer_key).is_err(),
+            "manifest changed"
+        );
+        let other_key = SigningKey::from_bytes(&[8_u8; 32])
+            .verifying_key()
+            .to_bytes();
+        assert!(verify_config(&signature, configuration, None, &other_key).is_err());
+
+        let mut forged = signature;
+        forged.artifacts_manifest_hash = None;
+        assert!(
+            verify_config(&forged, configuration, None, &organizer_key).is_err(),
+            "dropping the manifest breaks the signature"
+        );
+
+        let unsigned_manifest = sign_config(configuration, None, &organizer);
+        assert!(
+            verify_config(
+                &unsigned_manifest,
+                configuration,
+                Some(manifest),
+                &organizer_key
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Detached spend-auth signatures over external payloads.
+//!
+//! When the target chain constructs the message itself, `claim sign --detached` signs the
+//! payload with the randomized spend-auth key of every proven claim and writes only the
+//! signatures, instead of a submission. Each signature covers
+//! [`detached_signature_digest`] of the claim's proof hash and the payload hash, so it verifies
+//! under the claim's `rk` and cannot be replayed as a submission signature.
+
+use std::path::PathBuf;
+
+use eyre::{Context as _, ensure};
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::{info, warn};
+use zair_core::base::{Nullifier, Pool, detached_signature_digest, hash_message};
+use zair_core::schema::config::AirdropConfiguration;
+
+use super::claim_pools::PoolEntries as _;
+use super::claim_proofs::{ClaimProofsOutput, ClaimSecretsOutput};
+use super::claim_signing_request::{SigningRequestEntry, signing_request_inner};
+use super::nullifier_uniqueness::ensure_unique_airdrop_nullifiers;
+use super::submission_auth::{orchard, sapling};
+use crate::build_info::to_artifact_json;
+use crate::common::{PoolSelection, configured_network};
+use crate::error::{ZairError, ZairResult};
+use crate::seed::{AccountSecret, read_account_secret};
+use crate::stdio;
+
+/// Detached signatures of every claim over one external payload.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetachedSignatures {
+    /// Hash of the signed payload.
+    #[serde_as(as = "Hex")]
+    pub payload_hash: [u8; 32],
+    /// Sapling claim signatures.
+    pub sapling: Vec<DetachedSignature>,
+    /// Orchard claim signatures.
+    pub orchard: Vec<DetachedSignature>,
+    /// Airdrop epoch of the claims; `None` for the base epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u32>,
+}
+
+/// Detached spend-auth signature of a single claim.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetachedSignature {
+    /// Stable identifier of the claim.
+    #[serde_as(as = "Hex")]
+    pub claim_id: [u8; 32],
+    /// The airdrop nullifier of the claim.
+    pub airdrop_nullifier: Nullifier,
+    /// Randomized spend verification key of the proof, which verifies the signature.
+    #[serde_as(as = "Hex")]
+    pub rk: [u8; 32],
+    /// Hash of the proof fields bound into the signature.
+    #[serde_as(as = "Hex")]
+    pub proof_hash: [u8; 32],
+    /// Spend authorization signature over the detached digest.
+    #[serde_as(as = "Hex")]
+    pub spend_auth_sig: [u8; 64],
+}
+
+/// Sign an external payload with the spend-auth key of every claim proof.
+///
+/// Only the proofs of the pools in `pool` are signed.
+///
+/// # Errors
+/// Returns an error if inputs are invalid, key derivation fails, or a claim does not belong to
+/// the seed.
+#[allow(clippy::too_many_arguments, reason = "CLI entrypoint parameters")]
+pub async fn sign_detached_payload(
+    proofs_file: PathBuf,
+    secrets_file: PathBuf,
+    seed_file: PathBuf,
+    account_id: u32,
+    airdrop_configuration_file: PathBuf,
+    payload_file: PathBuf,
+    signatures_output_file: PathBuf,
+    pool: PoolSelection,
+) -> ZairResult<()> {
+    sign_detached_payload_inner(
+        proofs_file,
+        secrets_file,
+        seed_file,
+        account_id,
+        airdrop_configuration_file,
+        payload_file,
+        signatures_output_file,
+        pool,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Signing))
+}
+
+#[allow(clippy::too_many_arguments, reason = "CLI entrypoint parameters")]
+async fn sign_detached_payload_inner(
+    proofs_file: PathBuf,
+    secrets_file: PathBuf,
+    seed_file: PathBuf,
+    account_id: u32,
+    airdrop_configuration_file: PathBuf,
+    payload_file: PathBuf,
+    signatures_output_file: PathBuf,
+    pool: PoolSelection,
+) -> eyre::Result<()> {
+    info!(file = ?proofs_file, "Loading proofs for signing...");
+    let mut proofs: ClaimProofsOutput =
+        serde_json::from_str(&stdio::read_to_string(&proofs_file).await?)
+            .context("Failed to parse proofs JSON")?;
+    proofs.retain_pools(pool);
+
+    info!(file = ?secrets_file, "Loading local secrets...");
+    let mut secrets: ClaimSecretsOutput =
+        serde_json::from_str(&stdio::read_to_string(&secrets_file).await?)
+            .context("Failed to parse secrets JSON")?;
+    secrets.retain_pools(pool);
+
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+
+    info!(file = ?seed_file, "Reading seed from file...");
+    let account_secret = read_account_secret(&seed_file).await?;
+
+    let payload = stdio::read(&payload_file)
+        .await
+        .with_context(|| format!("Failed to read payload {}", payload_file.display()))?;
+
+    let signatures = sign_detached_inner(
+        &proofs,
+        &secrets,
+        &airdrop_config,
+        &account_secret,
+        account_id,
+        &hash_message(&payload),
+    )?;
+
+    let json = to_artifact_json(&signatures)?;
+    stdio::write(&signatures_output_file, json).await?;
+    info!(
+        file = ?signatures_output_file,
+        sapling_count = signatures.sapling.len(),
+        orchard_count = signatures.orchard.len(),
+        "Detached signatures written"
+    );
+
+    Ok(())
+}
+
+/// Sign a payload hash with the spend-auth key of every in-memory claim proof.
+pub fn sign_detached_inner(
+    proofs: &ClaimProofsOutput,
+    secrets: &ClaimSecretsOutput,
+    airdrop_config: &AirdropConfiguration,
+    account_secret: &AccountSecret,
+    account_id: u32,
+    payload_hash: &[u8; 32],
+) -> eyre::Result<DetachedSignatures> {
+    let request = signing_request_inner(proofs, secrets)?;
+    ensure!(
+        !(request.sapling.is_empty() && request.orchard.is_empty()),
+        "No proofs found to sign"
+    );
+    ensure_unique_airdrop_nullifiers(
+        request.sapling.iter().map(|entry| entry.airdrop_nullifier),
+        "Sapling proof",
+    )?;
+    ensure_unique_airdrop_nullifiers(
+        request.orchard.iter().map(|entry| entry.airdrop_nullifier),
+        "Orchard proof",
+    )?;
+    let airdrop_config = &airdrop_config.for_epoch(request.epoch)?;
+    let network = configured_network(airdrop_config)?;
+
+    let mut sapling_signatures = Vec::with_capacity(request.sapling.len());
+    if !request.sapling.is_empty() {
+        let domain = airdrop_config
+            .signature_domain(Pool::Sapling)
+            .context("Sapling proofs provided, but airdrop configuration has no sapling pool")?;
+        let keys = sapling::derive_spend_auth_keys(network, account_secret, account_id)?;
+        for entry in &request.sapling {
+            let digest = detached_signature_digest(&domain, &entry.proof_hash, payload_hash);
+            let spend_auth_sig = sapling::sign_randomized(&entry.rk, &entry.alpha, &keys, &digest)?;
+            sapling_signatures.push(detached_signature(
+                Pool::Sapling,
+                entry,
+                airdrop_config,
+                spend_auth_sig,
+            )?);
+        }
+    }
+
+    let mut orchard_signatures = Vec::with_capacity(request.orchard.len());
+    if !request.orchard.is_empty() {
+        let domain = airdrop_config
+            .signature_domain(Pool::Orchard)
+            .context("Orchard proofs provided, but airdrop configuration has no orchard pool")?;
+        let key = orchard::derive_spend_auth_key(network, account_secret, account_id)?;
+        for entry in &request.orchard {
+            let digest = detached_signature_digest(&domain, &entry.proof_hash, payload_hash);
+            let spend_auth_sig = orchard::sign_randomized(&entry.rk, &entry.alpha, &key, &digest)?;
+            orchard_signatures.push(detached_signature(
+                Pool::Orchard,
+                entry,
+                airdrop_config,
+                spend_auth_sig,
+            )?);
+        }
+    }
+
+    Ok(DetachedSignatures {
+        payload_hash: *payload_hash,
+        sapling: sapling_signatures,
+        orchard: orchard_signatures,
+        epoch: request.epoch,
+    })
+}
+
+/// Verify detached signatures over an external payload.
+///
+/// Only the signatures of the pools in `pool` are verified. The signatures are bound to the
+/// claims' `rk` and proof hash, so the target chain must also check that they match the proofs
+/// it verified.
+///
+/// # Errors
+/// Returns an error if parsing fails, the payload or a claim ID does not match, or any signature
+/// is invalid.
+pub async fn verify_detached_signatures(
+    signatures_file: PathBuf,
+    payload_file: PathBuf,
+    airdrop_configuration_file: PathBuf,
+    pool: PoolSelection,
+) -> ZairResult<()> {
+    verify_detached_signatures_file(
+        signatures_file,
+        payload_file,
+        airdrop_configuration_file,
+        pool,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+async fn verify_detached_signatures_file(
+    signatures_file: PathBuf,
+    payload_file: PathBuf,
+    airdrop_configuration_file: PathBuf,
+    pool: PoolSelection,
+) -> eyre::Result<()> {
+    info!(file = ?signatures_file, "Loading detached signatures...");
+    let mut signatures: DetachedSignatures =
+        serde_json::from_str(&stdio::read_to_string(&signatures_file).await?)
+            .context("Failed to parse detached signatures JSON")?;
+    if !pool.includes_sapling() {
+        signatures.sapling.clear();
+    }
+    if !pool.includes_orchard() {
+        signatures.orchard.clear();
+    }
+
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+
+    let payload = stdio::read(&payload_file)
+        .await
+        .with_context(|| format!("Failed to read payload {}", payload_file.display()))?;
+
+    verify_detached_inner(&signatures, &airdrop_config, &hash_message(&payload))
+}
+
+/// Verify in-memory detached signatures against a payload hash.
+pub fn verify_detached_inner(
+    signatures: &DetachedSignatures,
+    airdrop_config: &AirdropConfiguration,
+    payload_hash: &[u8; 32],
+) -> eyre::Result<()> {
+    ensure!(
+        !(signatures.sapling.is_empty() && signatures.orchard.is_empty()),
+        "Detached signatures file contains no signatures"
+    );
+    ensure!(
+        signatures.payload_hash == *payload_hash,
+        "Payload hash mismatch: the signatures were made over another payload"
+    );
+    let airdrop_config = &airdrop_config.for_epoch(signatures.epoch)?;
+
+    let mut invalid_count = 0_usize;
+    for (pool, entries) in [
+        (Pool::Sapling, &signatures.sapling),
+        (Pool::Orchard, &signatures.orchard),
+    ] {
+        if entries.is_empty() {
+            continue;
+        }
+        let domain = airdrop_config.signature_domain(pool)?;
+        for (idx, entry) in entries.iter().enumerate() {
+            ensure!(
+                entry.claim_id == airdrop_config.claim_id(pool, &entry.airdrop_nullifier)?,
+                "{pool:?} claim id mismatch at index {idx}"
+            );
+            let digest = detached_signature_digest(&domain, &entry.proof_hash, payload_hash);
+            let is_valid = match pool {
+                Pool::Sapling => {
+                    zair_sapling_proofs::verify_signature(entry.rk, entry.spend_auth_sig, &digest)
+                        .is_ok()
+                }
+                Pool::Orchard => {
+                    zair_orchard_proofs::verify_signature(entry.rk, entry.spend_auth_sig, &digest)
+                        .is_ok()
+                }
+            };
+            if is_valid {
+                info!(
+                    index = idx,
+                    airdrop_nullifier = %entry.airdrop_nullifier,
+                    "{pool:?} detached signature VALID"
+                );
+            } else {
+                invalid_count = invalid_count.saturating_add(1);
+                warn!(
+                    index = idx,
+                    airdrop_nullifier = %entry.airdrop_nullifier,
+                    "{pool:?} detached signature INVALID"
+                );
+            }
+        }
+    }
+
+    ensure!(
+        invalid_count == 0,
+        "{invalid_count} detached signatures failed verification"
+    );
+    info!(
+        sapling_count = signatures.sapling.len(),
+        orchard_count = signatures.orchard.len(),
+        "All detached signatures are VALID"
+    );
+    Ok(())
+}
+
+fn detached_signature(
+    pool: Pool,
+    entry: &SigningRequestEntry,
+    airdrop_config: &AirdropConfiguration,
+    spend_auth_sig: [u8; 64],
+) -> eyre::Result<DetachedSignature> {
+    Ok(DetachedSignature {
+        claim_id: airdrop_config.claim_id(pool, &entry.airdrop_nullifier)?,
+        airdrop_nullifier: entry.airdrop_nullifier,
+        rk: entry.rk,
+        proof_hash: entry.proof_hash,
+        spend_auth_sig,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::base::TargetId;
+    use zair_core::schema::config::{
+        AirdropNetwork, CircuitConfiguration, SaplingSnapshot, SproutPolicy, ValueCommitmentScheme,
+    };
+
+    use super::*;
+
+    #[test]
+    fn signatures_over_another_payload_are_rejected() {
+        let signatures = DetachedSignatures {
+            payload_hash: hash_message(b"namada transfer"),
+            sapling: vec![DetachedSignature {
+                claim_id: [0_u8; 32],
+                airdrop_nullifier: Nullifier::from([1_u8; 32]),
+                rk: [2_u8; 32],
+                proof_hash: [3_u8; 32],
+                spend_auth_sig: [4_u8; 64],
+            }],
+            orchard: Vec::new(),
+
+            epoch: None,
+        };
+        let airdrop_config = AirdropConfiguration {
+            network: AirdropNetwork::Testnet,
+            snapshot_height: 1,
+            sapling: Some(SaplingSnapshot {
+                note_commitment_root: [0_u8; 32],
+                nullifier_gap_root: [0_u8; 32],
+                target_id: TargetId::new(Pool::Sapling, "ZAIRTEST").expect("valid target ID"),
+                value_commitment_scheme: ValueCommitmentScheme::Native,
+                verifying_key_fingerprint: None,
+            }),
+            orchard: None,
+            sprout: SproutPolicy::Excluded,
+            transparent: None,
+            min_note_value: None,
+            max_claims_per_submission: None,
+            circuit: CircuitConfiguration::default(),
+            network_upgrades: None,
+            claim_start_height: None,
+            claim_expiry_height: None,
+            countersigners: Vec::new(),
+            chain_id: None,
+            epochs: Vec::new(),
+        };
+
+        let err = verify_detached_inner(
+            &signatures,
+            &airdrop_config,
+            &hash_message(b"another transfer"),
+        )
+        .expect_err("payload differs");
+        assert!(
+            err.to_string().starts_with("Payload hash mismatch"),
+            "{err:?}"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Disk space estimates for snapshot and gap-tree files.
+//!
+//! Every spent note reveals one nullifier, so the note commitment tree size at the snapshot height
+//! bounds the number of snapshot nullifiers of a pool. `GetTreeState` returns that size in one
+//! request, without scanning the chain. Commands that write snapshots or gap trees check the free
+//! space against this bound first, so a full disk fails the command before the scan instead of
+//! in the middle of a write.
+
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use eyre::{Context as _, ensure};
+use zair_scan::light_walletd::LightWalletd;
+
+/// Bytes per nullifier in a snapshot file.
+const SNAPSHOT_BYTES_PER_NULLIFIER: u64 = 32;
+/// Bytes per nullifier in a gap-tree file: the leaves and inner nodes of the dense tree.
+const GAP_TREE_BYTES_PER_NULLIFIER: u64 = 64;
+
+/// Upper bound on the nullifiers of each pool at a height.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) struct NullifierBound {
+    /// Sapling notes created up to the height.
+    pub sapling: u64,
+    /// Orchard notes created up to the height.
+    pub orchard: u64,
+}
+
+impl NullifierBound {
+    /// Read the note commitment tree sizes at `height`.
+    pub(super) async fn fetch(lightwalletd: &LightWalletd, height: u64) -> eyre::Result<Self> {
+        let tree_state = lightwalletd.get_tree_state(height).await?;
+        let sapling = tree_state
+            .sapling_tree()
+            .context("Failed to parse the Sapling tree state")?
+            .size();
+        let orchard = tree_state
+            .orchard_tree()
+            .context("Failed to parse the Orchard tree state")?
+            .size();
+        Ok(Self {
+            sapling: u64::try_from(sapling)?,
+            orchard: u64::try_from(orchard)?,
+        })
+    }
+
+    /// Notes created in the blocks of `range`: the growth of the note commitment trees over it.
+    pub(super) async fn fetch_range(
+        lightwalletd: &LightWalletd,
+        range: &RangeInclusive<u64>,
+    ) -> eyre::Result<Self> {
+        let end = Self::fetch(lightwalletd, *range.end()).await?;
+        let Some(before) = range.start().checked_sub(1) else {
+            return Ok(end);
+        };
+        let start = Self::fetch(lightwalletd, before).await?;
+        Ok(Self {
+            sapling: end.sapling.saturating_sub(start.sapling),
+            orchard: end.orchard.saturating_sub(start.orchard),
+        })
+    }
+
+    /// Bytes of the snapshot files of the selected pools.
+    pub(super) const fn snapshot_bytes(self, sapling: bool, orchard: bool) -> u64 {
+        snapshot_bytes(self.nullifiers(sapling, orchard))
+    }
+
+    /// Bytes of the gap-tree files of the selected pools.
+    pub(super) const fn gap_tree_bytes(self, sapling: bool, orchard: bool) -> u64 {
+        gap_tree_bytes(self.nullifiers(sapling, orchard))
+    }
+
+    const fn nullifiers(self, sapling: bool, orchard: bool) -> u64 {
+        let sapling = if sapling { self.sapling } else { 0 };
+        let orchard = if orchard { self.orchard } else { 0 };
+        sapling.saturating_add(orchard)
+    }
+}
+
+/// Bytes of a snapshot file of `nullifiers` nullifiers.
+pub(super) const fn snapshot_bytes(nullifiers: u64) -> u64 {
+    nullifiers.saturating_mul(SNAPSHOT_BYTES_PER_NULLIFIER)
+}
+
+/// Bytes of a gap-tree file of `nullifiers` nullifiers.
+pub(super) const fn gap_tree_bytes(nullifiers: u64) -> u64 {
+    nullifiers.saturating_mul(GAP_TREE_BYTES_PER_NULLIFIER)
+}
+
+/// Bytes available to this user on the file system that holds `path`.
+///
+/// `path` need not exist yet; the space of its nearest existing ancestor is reported.
+pub(super) fn available_space(path: &Path) -> eyre::Result<u64> {
+    let existing = existing_ancestor(path);
+    fs4::available_space(existing)
+        .with_context(|| format!("Failed to read the free space of {}", existing.display()))
+}
+
+/// Check that every file system has room for the files written to it.
+///
+/// `files` pairs each output path with the bytes that will be written there; files on the same
+/// file system share its free space.
+///
+/// # Errors
+/// Returns an error naming the directory and the missing space if a file system is too small.
+pub(super) fn ensure_space(files: &[(&Path, u64)]) -> eyre::Result<()> {
+    let mut needed: BTreeMap<u64, (PathBuf, u64)> = BTreeMap::new();
+    for (path, bytes) in files {
+        let dir = existing_ancestor(path);
+        let entry = needed
+            .entry(file_system_id(dir))
+            .or_insert_with(|| (dir.to_path_buf(), 0));
+        entry.1 = entry.1.saturating_add(*bytes);
+    }
+    for (dir, required) in needed.values() {
+        let available = available_space(dir)?;
+        ensure!(
+            available >= *required,
+            "Not enough disk space in {}: up to {} needed, {} free. Free up space or write the \
+             output to another disk",
+            dir.display(),
+            format_bytes(*required),
+            format_bytes(available),
+        );
+    }
+    Ok(())
+}
+
+fn existing_ancestor(path: &Path) -> &Path {
+    path.ancestors()
+        .find(|dir| !dir.as_os_str().is_empty() && dir.exists())
+        .unwrap_or_else(|| Path::new("."))
+}
+
+#[cfg(unix)]
+fn file_system_id(dir: &Path) -> u64 {
+    use std::os::unix::fs::MetadataExt as _;
+
+    std::fs::metadata(dir).map_or(0, |meta| meta.dev())
+}
+
+/// Without device ids, every path is treated as one file system, which can only overestimate.
+#[cfg(not(unix))]
+const fn file_system_id(_dir: &Path) -> u64 {
+    0
+}
+
+/// Human-readable byte count, such as `1.5 GiB`.
+pub(super) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut unit = 0_usize;
+    let mut tenths = bytes.saturating_mul(10);
+    while tenths >= 10_240 && unit < UNITS.len().saturating_sub(1) {
+        tenths /= 1024;
+        unit = unit.saturating_add(1);
+    }
+    let name = UNITS.get(unit).copied().unwrap_or("B");
+    if unit == 0 {
+        format!("{bytes} {name}")
+    } else {
+        format!("{}.{} {name}", tenths / 10, tenths % 10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimates_cover_the_selected_pools() {
+        let bound = NullifierBound {
+            sapling: 1_000,
+            orchard: 10,
+        };
+        assert_eq!(bound.snapshot_bytes(true, true), 1_010 * 32);
+        assert_eq!(bound.gap_tree_bytes(false, true), 10 * 64);
+        assert_eq!(bound.snapshot_bytes(false, false), 0);
+    }
+
+    #[test]
+    fn files_on_one_file_system_share_its_space() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let available = available_space(dir.path()).expect("free space");
+        let half = (available / 2).saturating_add(1);
+        let snapshot = dir.path().join("snapshot-sapling.bin");
+        let gap_tree = dir.path().join("gaptrees").join("gaptree-sapling.bin");
+
+        ensure_space(&[(&snapshot, 1), (&gap_tree, 1)]).expect("room for small files");
+        let err = ensure_space(&[(&snapshot, half), (&gap_tree, half)])
+            .expect_err("two halves exceed the free space");
+        assert!(err.to_string().contains("Not enough disk space"));
+    }
+
+    #[test]
+    fn byte_counts_are_readable() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1_536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -0,0 +1,377 @@
+//! Environment checks before long-running commands.
+//!
+//! `zair doctor` checks what a multi-hour snapshot, gap-tree or proving run depends on, so a
+//! missing file or an unreachable indexer shows up before the run instead of hours into it.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr as _;
+
+use eyre::Context as _;
+use http::Uri;
+use zair_core::base::Pool;
+use zair_core::schema::config::AirdropConfiguration;
+use zair_scan::light_walletd::LightWalletd;
+
+use super::disk_space::{NullifierBound, available_space, format_bytes};
+use super::setup_fingerprint::{
+    ensure_fingerprint_matches, read_orchard_params_fingerprint, read_sapling_vk_fingerprint,
+};
+use super::verification_report::unix_now;
+use crate::common::{configured_network, resolve_lightwalletd_url};
+use crate::paths::{default_setup_input_path, params_dir, snapshots_dir};
+use crate::stdio;
+
+const DEFAULT_SAPLING_PK_FILE: &str = "setup-sapling-pk.params";
+const DEFAULT_SAPLING_VK_FILE: &str = "setup-sapling-vk.params";
+const DEFAULT_ORCHARD_PARAMS_FILE: &str = "setup-orchard-params.bin";
+
+/// How far a block time may be ahead of the local clock, as allowed by consensus.
+const MAX_BLOCK_TIME_AHEAD_SECS: u64 = 2 * 60 * 60;
+/// How far the local clock may be ahead of the chain tip before it is reported.
+const MAX_TIP_AGE_SECS: u64 = 60 * 60;
+
+/// Outcome of one doctor check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// Nothing to fix.
+    Pass,
+    /// Worth a look, but the run can start.
+    Warn,
+    /// The run would fail.
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pass => "pass",
+            Self::Warn => "warn",
+            Self::Fail => "fail",
+        })
+    }
+}
+
+/// One line of the doctor checklist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctorCheck {
+    /// What was checked, such as `lightwalletd`.
+    pub name: &'static str,
+    /// Outcome of the check.
+    pub status: CheckStatus,
+    /// What was found, or how to fix it.
+    pub detail: String,
+}
+
+impl DoctorCheck {
+    pub(super) fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Run every doctor check for the airdrop in `config_file`.
+///
+/// Setup files default to the ones `claim run` would use, and the disk space is checked in
+/// `data_dir`, or the standard snapshots directory. Checks never abort the run; a check that
+/// cannot be performed is reported as failed or skipped.
+pub async fn run_doctor(
+    config_file: PathBuf,
+    lightwalletd_url: Option<String>,
+    sapling_pk: Option<PathBuf>,
+    sapling_vk: Option<PathBuf>,
+    orchard_params: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+) -> Vec<DoctorCheck> {
+    let config = match read_config(&config_file).await {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![DoctorCheck::new(
+                "config",
+                CheckStatus::Fail,
+                format!("{e:#}"),
+            )];
+        }
+    };
+    let mut checks = vec![config_check(&config)];
+
+    let bound = chain_checks(&config, lightwalletd_url.as_deref(), &mut checks).await;
+    if let Some(sapling) = &config.sapling {
+        let scheme = sapling.value_commitment_scheme.as_str();
+        let pk = sapling_pk.unwrap_or_else(|| {
+            default_setup_input_path(params_dir, DEFAULT_SAPLING_PK_FILE, scheme)
+        });
+        let vk = sapling_vk.unwrap_or_else(|| {
+            default_setup_input_path(params_dir, DEFAULT_SAPLING_VK_FILE, scheme)
+        });
+        checks.push(sapling_setup_check(&pk, &vk, sapling.verifying_key_fingerprint).await);
+    }
+    if let Some(orchard) = &config.orchard {
+        let scheme = orchard.value_commitment_scheme.as_str();
+        let params = orchard_params.unwrap_or_else(|| {
+            default_setup_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE, scheme)
+        });
+        checks.push(orchard_setup_check(&params, orchard.params_fingerprint).await);
+    }
+    checks.push(disk_check(&config, bound, data_dir));
+    checks
+}
+
+pub(super) async fn read_config(config_file: &Path) -> eyre::Result<AirdropConfiguration> {
+    serde_json::from_slice(&stdio::read(config_file).await?)
+        .context("Failed to parse airdrop configuration JSON")
+}
+
+fn config_check(config: &AirdropConfiguration) -> DoctorCheck {
+    const NAME: &str = "config";
+    if let Err(e) = configured_network(config) {
+        return DoctorCheck::new(NAME, CheckStatus::Fail, format!("{e:#}"));
+    }
+    if config.sapling.is_none() && config.orchard.is_none() {
+        return DoctorCheck::new(NAME, CheckStatus::Fail, "The configuration has no pool");
+    }
+    if let Some(Err(e)) = config
+        .sapling
+        .as_ref()
+        .map(|pool| pool.target_id.ensure_pool(Pool::Sapling))
+    {
+        return DoctorCheck::new(NAME, CheckStatus::Fail, e.to_string());
+    }
+    let unbound = config
+        .sapling
+        .as_ref()
+        .is_some_and(|pool| pool.verifying_key_fingerprint.is_none()) ||
+        config
+            .orchard
+            .as_ref()
+            .is_some_and(|pool| pool.params_fingerprint.is_none());
+    if unbound {
+        return DoctorCheck::new(
+            NAME,
+            CheckStatus::Warn,
+            "No trusted setup fingerprint is bound for a pool; proofs are checked against any setup",
+        );
+    }
+    DoctorCheck::new(
+        NAME,
+        CheckStatus::Pass,
+        format!("Snapshot at height {}", config.snapshot_height),
+    )
+}
+
+/// Check the indexer and the local clock, and return the nullifier bound at the snapshot height.
+async fn chain_checks(
+    config: &AirdropConfiguration,
+    lightwalletd_url: Option<&str>,
+    checks: &mut Vec<DoctorCheck>,
+) -> Option<NullifierBound> {
+    const NAME: &str = "lightwalletd";
+    let network = configured_network(config).ok()?;
+    let url = resolve_lightwalletd_url(network, lightwalletd_url);
+    let connected = async {
+        let lightwalletd = LightWalletd::connect(Uri::from_str(&url)?).await?;
+        let tip = lightwalletd.latest_height().await?;
+        eyre::Ok((lightwalletd, tip))
+    }
+    .await;
+    let (lightwalletd, tip) = match connected {
+        Ok(connected) => connected,
+        Err(e) => {
+            checks.push(DoctorCheck::new(
+                NAME,
+                CheckStatus::Fail,
+                format!("Cannot reach {url}: {e:#}"),
+            ));
+            return None;
+        }
+    };
+    if tip < config.snapshot_height {
+        checks.push(DoctorCheck::new(
+            NAME,
+            CheckStatus::Fail,
+            format!(
+                "{url} is at height {tip}, below the snapshot height {}",
+                config.snapshot_height
+            ),
+        ));
+    } else {
+        checks.push(DoctorCheck::new(
+            NAME,
+            CheckStatus::Pass,
+            format!("{url} is at height {tip}"),
+        ));
+    }
+
+    checks.push(match lightwalletd.get_tree_state(tip).await {
+        Ok(state) => match unix_now() {
+            Ok(now) => clock_check(now, u64::from(state.time)),
+            Err(e) => DoctorCheck::new("clock", CheckStatus::Fail, format!("{e:#}")),
+        },
+        Err(e) => DoctorCheck::new(
+            "clock",
+            CheckStatus::Warn,
+            format!("Cannot read the chain tip time: {e}"),
+        ),
+    });
+
+    if tip < config.snapshot_height {
+        return None;
+    }
+    NullifierBound::fetch(&lightwalletd, config.snapshot_height)
+        .await
+        .ok()
+}
+
+/// Compare the local clock with the time of the chain tip.
+fn clock_check(now: u64, tip_time: u64) -> DoctorCheck {
+    const NAME: &str = "clock";
+    if tip_time > now.saturating_add(MAX_BLOCK_TIME_AHEAD_SECS) {
+        return DoctorCheck::new(
+            NAME,
+            CheckStatus::Fail,
+            format!(
+                "The local clock is {}s behind the chain tip",
+                tip_time.saturating_sub(now)
+            ),
+        );
+    }
+    let age = now.saturating_sub(tip_time);
+    if age > MAX_TIP_AGE_SECS {
+        return DoctorCheck::new(
+            NAME,
+            CheckStatus::Warn,
+            format!(
+                "The chain tip is {age}s old; the local clock is ahead or the indexer is stale"
+            ),
+        );
+    }
+    DoctorCheck::new(
+        NAME,
+        CheckStatus::Pass,
+        format!("The chain tip is {age}s old"),
+    )
+}
+
+async fn sapling_setup_check(pk: &Path, vk: &Path, fingerprint: Option<[u8; 32]>) -> DoctorCheck {
+    const NAME: &str = "sapling setup";
+    if !pk.exists() {
+        return DoctorCheck::new(
+            NAME,
+            CheckStatus::Fail,
+            format!(
+                "Proving key {} not found; run `zair setup sapling` or `zair setup fetch`",
+                pk.display()
+            ),
+        );
+    }
+    if !vk.exists() {
+        return DoctorCheck::new(
+            NAME,
+            CheckStatus::Warn,
+            format!(
+                "Verifying key {} not found; it is needed to verify proofs",
+                vk.display()
+            ),
+        );
+    }
+    let checked = async {
+        let actual = read_sapling_vk_fingerprint(vk).await?;
+        ensure_fingerprint_matches("Sapling verifying key", fingerprint, actual)
+    }
+    .await;
+    setup_file_check(NAME, vk, fingerprint.is_some(), checked)
+}
+
+async fn orchard_setup_check(params: &Path, fingerprint: Option<[u8; 32]>) -> DoctorCheck {
+    const NAME: &str = "orchard setup";
+    if !params.exists() {
+        return DoctorCheck::new(
+            NAME,
+            CheckStatus::Warn,
+            format!(
+                "Params {} not found; they are generated on first use, or run `zair setup orchard`",
+                params.display()
+            ),
+        );
+    }
+    let checked = async {
+        let actual = read_orchard_params_fingerprint(params).await?;
+        ensure_fingerprint_matches("Orchard params", fingerprint, actual)
+    }
+    .await;
+    setup_file_check(NAME, params, fingerprint.is_some(), checked)
+}
+
+fn setup_file_check(
+    name: &'static str,
+    file: &Path,
+    bound: bool,
+    checked: eyre::Result<()>,
+) -> DoctorCheck {
+    match checked {
+        Err(e) => DoctorCheck::new(name, CheckStatus::Fail, format!("{e:#}")),
+        Ok(()) if bound => DoctorCheck::new(
+            name,
+            CheckStatus::Pass,
+            format!("{} matches the configuration", file.display()),
+        ),
+        Ok(()) => DoctorCheck::new(name, CheckStatus::Pass, format!("{} found", file.display())),
+    }
+}
+
+fn disk_check(
+    config: &AirdropConfiguration,
+    bound: Option<NullifierBound>,
+    data_dir: Option<PathBuf>,
+) -> DoctorCheck {
+    const NAME: &str = "disk space";
+    let Some(bound) = bound else {
+        return DoctorCheck::new(
+            NAME,
+            CheckStatus::Warn,
+            "Skipped: the snapshot size needs the indexer",
+        );
+    };
+    let dir = match data_dir.map_or_else(snapshots_dir, Ok) {
+        Ok(dir) => dir,
+        Err(e) => return DoctorCheck::new(NAME, CheckStatus::Fail, format!("{e:#}")),
+    };
+    let (sapling, orchard) = (config.sapling.is_some(), config.orchard.is_some());
+    let required = bound
+        .snapshot_bytes(sapling, orchard)
+        .saturating_add(bound.gap_tree_bytes(sapling, orchard));
+    match available_space(&dir) {
+        Ok(available) => DoctorCheck::new(
+            NAME,
+            if available < required {
+                CheckStatus::Fail
+            } else {
+                CheckStatus::Pass
+            },
+            format!(
+                "{} free in {}, up to {} needed for snapshots and gap trees",
+                format_bytes(available),
+                dir.display(),
+                format_bytes(required)
+            ),
+        ),
+        Err(e) => DoctorCheck::new(NAME, CheckStatus::Warn, format!("{e:#}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_check_tolerates_block_time_rules() {
+        const NOW: u64 = 1_760_000_000;
+        assert_eq!(clock_check(NOW, NOW - 75).status, CheckStatus::Pass);
+        assert_eq!(clock_check(NOW, NOW + 600).status, CheckStatus::Pass);
+        assert_eq!(clock_check(NOW, NOW - 7_200).status, CheckStatus::Warn);
+        assert_eq!(clock_check(NOW, NOW + 10_800).status, CheckStatus::Fail);
+    }
+}
@@ -0,0 +1,40 @@
+//! gRPC health checks and reflection for the HTTP services.
+//!
+//! Load balancers and monitoring usually probe services through `grpc.health.v1.Health`. The
+//! services answer it on their HTTP port, next to their own routes, and list it through gRPC
+//! server reflection.
+
+use axum::Router;
+use eyre::Context as _;
+use tonic::service::Routes;
+use tonic_health::ServingStatus;
+
+/// Routes serving the gRPC health and reflection services, with `service` reported as serving.
+///
+/// The overall status (the empty service name) is serving as well.
+pub(super) async fn grpc_ops_router(service: &str) -> eyre::Result<Router> {
+    let (reporter, health) = tonic_health::server::health_reporter();
+    reporter
+        .set_service_status(service, ServingStatus::Serving)
+        .await;
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .build_v1()
+        .context("Failed to build gRPC reflection service")?;
+
+    Ok(Routes::new(health)
+        .add_service(reflection)
+        .into_axum_router())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn health_and_reflection_routes_build() {
+        grpc_ops_router("zair.test")
+            .await
+            .expect("gRPC ops routes should build");
+    }
+}
@@ -0,0 +1,469 @@
+//! Holdings attestations.
+//!
+//! A holdings attestation proves how much unspent value a claimant held at the snapshot without
+//! initiating a claim. It carries the claim proofs, which show that each note existed and was
+//! unspent, and the opening of every proof's value commitment, which shows the note's value. It
+//! has no spend-authorizing signatures, so the attestation cannot be submitted as a claim.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::info;
+use zair_core::base::{Nullifier, Pool, cv_sha256};
+use zair_core::schema::proof_inputs::AirdropClaimInputs;
+
+use super::claim_pools::PoolEntries as _;
+use super::claim_proofs::{
+    ClaimProofsOutput, ClaimSecretsOutput, OrchardClaimProofResult, ProofFilter,
+    SaplingClaimProofResult, ensure_all_valid, verify_claim_proof_verdicts,
+};
+use super::orchard_params::OrchardParamsMode;
+use crate::build_info::to_artifact_json;
+use crate::common::PoolSelection;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Default file name of a holdings attestation.
+pub const HOLDINGS_ATTESTATION_FILE: &str = "holdings-attestation.json";
+
+/// Opening of one claim proof's value commitment.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueOpening {
+    /// Pool of the claim.
+    pub pool: Pool,
+    /// Airdrop nullifier of the claim.
+    pub airdrop_nullifier: Nullifier,
+    /// Note value in zatoshis.
+    pub value: u64,
+    /// Randomness of the commitment scheme the proof uses (`rcv`, `rcv_sha256` or
+    /// `rcv_poseidon`).
+    #[serde_as(as = "Hex")]
+    pub randomness: [u8; 32],
+}
+
+/// Proof of the unspent value held at the snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldingsAttestation {
+    /// Total value of the attested notes in zatoshis.
+    pub total_value: u64,
+    /// Claim proofs of the attested notes.
+    pub proofs: ClaimProofsOutput,
+    /// Value commitment opening of every proof.
+    pub openings: Vec<ValueOpening>,
+}
+
+/// Write a holdings attestation for the proven claims.
+///
+/// The note values and commitment randomness come from the value blindings in `secrets_file`.
+/// Secrets written by earlier versions have none, so their note values are taken from the prepared
+/// claims in `claims_file`. Only the proofs of the pools in `pool` are attested.
+///
+/// # Errors
+/// Returns an error if the inputs cannot be read or a proof has no matching claim or secrets.
+pub async fn attest_holdings(
+    claims_file: Option<PathBuf>,
+    proofs_file: PathBuf,
+    secrets_file: PathBuf,
+    attestation_output_file: PathBuf,
+    pool: PoolSelection,
+) -> ZairResult<()> {
+    attest_holdings_inner(
+        claims_file,
+        proofs_file,
+        secrets_file,
+        attestation_output_file,
+        pool,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+async fn attest_holdings_inner(
+    claims_file: Option<PathBuf>,
+    proofs_file: PathBuf,
+    secrets_file: PathBuf,
+    attestation_output_file: PathBuf,
+    pool: PoolSelection,
+) -> eyre::Result<()> {
+    let claims: Option<AirdropClaimInputs> = match &claims_file {
+        Some(file) => Some(
+            serde_json::from_str(&stdio::read_to_string(file).await?)
+                .context("Failed to parse prepared claims JSON")?,
+        ),
+        None => None,
+    };
+    let mut proofs: ClaimProofsOutput =
+        serde_json::from_str(&stdio::read_to_string(&proofs_file).await?)
+            .context("Failed to parse proofs JSON")?;
+    proofs.retain_pools(pool);
+    let secrets: ClaimSecretsOutput =
+        serde_json::from_str(&stdio::read_to_string(&secrets_file).await?)
+            .context("Failed to parse secrets JSON")?;
+
+    let attestation = build_attestation(claims.as_ref(), proofs, &secrets)?;
+    stdio::write(&attestation_output_file, to_artifact_json(&attestation)?)
+        .await
+        .with_context(|| format!("Failed to write {}", attestation_output_file.display()))?;
+    info!(
+        file = ?attestation_output_file,
+        total_value = attestation.total_value,
+        notes = attestation.openings.len(),
+        "Holdings attestation written"
+    );
+    Ok(())
+}
+
+/// Attest the total value of `proofs` to a third party, after the fact, from the value blindings
+/// in `secrets`.
+///
+/// `claims` supplies the note values of secrets written before value blindings were recorded.
+/// The third party checks the result with [`verify_holdings_attestation`].
+///
+/// # Errors
+/// Returns an error if a proof has no matching secrets, or its note value is neither in the
+/// secrets nor in `claims`.
+pub fn attest_claimed_value(
+    proofs: ClaimProofsOutput,
+    secrets: &ClaimSecretsOutput,
+    claims: Option<&AirdropClaimInputs>,
+) -> ZairResult<HoldingsAttestation> {
+    build_attestation(claims, proofs, secrets)
+        .map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+/// Pair every proof with the value of its claim and the randomness of its commitment.
+fn build_attestation(
+    claims: Option<&AirdropClaimInputs>,
+    mut proofs: ClaimProofsOutput,
+    secrets: &ClaimSecretsOutput,
+) -> eyre::Result<HoldingsAttestation> {
+    proofs.failed_claims.clear();
+    let sapling_values: BTreeMap<[u8; 32], u64> = claims
+        .iter()
+        .flat_map(|claims| &claims.sapling_claim_input)
+        .map(|claim| (claim.claim_id, claim.private_inputs.value))
+        .collect();
+    let orchard_values: BTreeMap<[u8; 32], u64> = claims
+        .iter()
+        .flat_map(|claims| &claims.orchard_claim_input)
+        .map(|claim| (claim.claim_id, claim.private_inputs.value))
+        .collect();
+
+    let mut openings = Vec::new();
+    for proof in &proofs.sapling_proofs {
+        let secret = secrets
+            .sapling
+            .iter()
+            .find(|secret| secret.airdrop_nullifier == proof.airdrop_nullifier)
+            .with_context(|| {
+                format!(
+                    "No Sapling secrets for airdrop nullifier {}",
+                    proof.airdrop_nullifier
+                )
+            })?;
+        let (value, randomness) = match secret.value_blinding {
+            Some(blinding) => (blinding.value, blinding.blinding),
+            None => (
+                claim_value(&sapling_values, &proof.claim_id)?,
+                if proof.cv_sha256.is_some() {
+                    secret.rcv_sha256
+                } else {
+                    secret.rcv
+                }
+                .context("Sapling secrets have no value commitment randomness")?,
+            ),
+        };
+        openings.push(ValueOpening {
+            pool: Pool::Sapling,
+            airdrop_nullifier: proof.airdrop_nullifier,
+            value,
+            randomness,
+        });
+    }
+    for proof in &proofs.orchard_proofs {
+        let secret = secrets
+            .orchard
+            .iter()
+            .find(|secret| secret.airdrop_nullifier == proof.airdrop_nullifier)
+            .with_context(|| {
+                format!(
+                    "No Orchard secrets for airdrop nullifier {}",
+                    proof.airdrop_nullifier
+                )
+            })?;
+        let (value, randomness) = match secret.value_blinding {
+            Some(blinding) => (blinding.value, blinding.blinding),
+            None => (
+                claim_value(&orchard_values, &proof.claim_id)?,
+                if proof.cv_sha256.is_some() {
+                    secret.rcv_sha256
+                } else if proof.cv_poseidon.is_some() {
+                    secret.rcv_poseidon
+                } else {
+                    secret.rcv
+                }
+                .context("Orchard secrets have no value commitment randomness")?,
+            ),
+        };
+        openings.push(ValueOpening {
+            pool: Pool::Orchard,
+            airdrop_nullifier: proof.airdrop_nullifier,
+            value,
+            randomness,
+        });
+    }
+
+    Ok(HoldingsAttestation {
+        total_value: total_value(&openings)?,
+        proofs,
+        openings,
+    })
+}
+
+fn claim_value(values: &BTreeMap<[u8; 32], u64>, claim_id: &[u8; 32]) -> eyre::Result<u64> {
+    values.get(claim_id).copied().with_context(|| {
+        format!(
+            "No value for claim {}: its secrets predate value blindings and no prepared claim \
+             matches it",
+            hex::encode(claim_id)
+        )
+    })
+}
+
+pub(super) fn total_value(openings: &[ValueOpening]) -> eyre::Result<u64> {
+    openings.iter().try_fold(0_u64, |total, opening| {
+        total
+            .checked_add(opening.value)
+            .context("Total attested value overflows u64")
+    })
+}
+
+/// Verify a holdings attestation and return the attested total value.
+///
+/// Every claim proof must verify against the configuration and setup artifacts, every proof must
+/// have exactly one opening that matches its value commitment, and the openings must sum to the
+/// stated total.
+///
+/// # Errors
+/// Returns an error if the attestation cannot be read or any check fails.
+pub async fn verify_holdings_attestation(
+    attestation_file: PathBuf,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    airdrop_configuration_file: PathBuf,
+) -> ZairResult<u64> {
+    verify_holdings_attestation_inner(
+        attestation_file,
+        verifying_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+        airdrop_configuration_file,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+async fn verify_holdings_attestation_inner(
+    attestation_file: PathBuf,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    airdrop_configuration_file: PathBuf,
+) -> eyre::Result<u64> {
+    info!(file = ?attestation_file, "Loading holdings attestation...");
+    let attestation: HoldingsAttestation =
+        serde_json::from_str(&stdio::read_to_string(&attestation_file).await?)
+            .context("Failed to parse holdings attestation JSON")?;
+
+    ensure_openings_match(&attestation)?;
+    let verdicts = verify_claim_proof_verdicts(
+        attestation.proofs,
+        verifying_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+        airdrop_configuration_file,
+        ProofFilter::default(),
+    )
+    .await?;
+    ensure_all_valid(&verdicts)?;
+
+    info!(
+        total_value = attestation.total_value,
+        "Holdings attestation verified"
+    );
+    Ok(attestation.total_value)
+}
+
+/// Check that the openings cover every proof once, open its commitment and sum to the total.
+fn ensure_openings_match(attestation: &HoldingsAttestation) -> eyre::Result<()> {
+    let proof_count = attestation
+        .proofs
+        .sapling_proofs
+        .len()
+        .saturating_add(attestation.proofs.orchard_proofs.len());
+    ensure!(
+        attestation.openings.len() == proof_count,
+        "Attestation has {} openings for {proof_count} proofs",
+        attestation.openings.len()
+    );
+    let opened_nullifiers: BTreeSet<Nullifier> = attestation
+        .openings
+        .iter()
+        .map(|opening| opening.airdrop_nullifier)
+        .collect();
+    ensure!(
+        opened_nullifiers.len() == proof_count,
+        "Attestation opens the same claim more than once"
+    );
+    for opening in &attestation.openings {
+        let opened = match opening.pool {
+            Pool::Sapling => attestation
+                .proofs
+                .sapling_proofs
+                .iter()
+                .find(|proof| proof.airdrop_nullifier == opening.airdrop_nullifier)
+                .is_some_and(|proof| opens_sapling(proof, opening)),
+            Pool::Orchard => attestation
+                .proofs
+                .orchard_proofs
+                .iter()
+                .find(|proof| proof.airdrop_nullifier == opening.airdrop_nullifier)
+                .is_some_and(|proof| opens_orchard(proof, opening)),
+        };
+        ensure!(
+            opened,
+            "Opening for airdrop nullifier {} does not match a value commitment",
+            opening.airdrop_nullifier
+        );
+    }
+    ensure!(
+        total_value(&attestation.openings)? == attestation.total_value,
+        "Openings do not sum to the attested total value {}",
+        attestation.total_value
+    );
+    Ok(())
+}
+
+pub(super) fn opens_sapling(proof: &SaplingClaimProofResult, opening: &ValueOpening) -> bool {
+    if let Some(cv) = proof.cv {
+        let Some(rcv) = Option::<sapling::value::ValueCommitTrapdoor>::from(
+            sapling::value::ValueCommitTrapdoor::from_bytes(opening.randomness),
+        ) else {
+            return false;
+        };
+        let value = sapling::value::NoteValue::from_raw(opening.value);
+        return sapling::value::ValueCommitment::derive(value, rcv).to_bytes() == cv;
+    }
+    proof.cv_sha256 == Some(cv_sha256(opening.value, opening.randomness))
+}
+
+pub(super) fn opens_orchard(proof: &OrchardClaimProofResult, opening: &ValueOpening) -> bool {
+    if let Some(cv) = proof.cv {
+        let Some(rcv) = Option::<orchard::value::ValueCommitTrapdoor>::from(
+            orchard::value::ValueCommitTrapdoor::from_bytes(opening.randomness),
+        ) else {
+            return false;
+        };
+        #[allow(
+            clippy::arithmetic_side_effects,
+            reason = "Orchard value commitment API requires NoteValue subtraction to produce ValueSum"
+        )]
+        let value = orchard::value::NoteValue::from_raw(opening.value) -
+            orchard::value::NoteValue::from_raw(0);
+        return orchard::value::ValueCommitment::derive(value, rcv).to_bytes() == cv;
+    }
+    if let Some(cv_poseidon) = proof.cv_poseidon {
+        return zair_orchard_proofs::cv_poseidon_bytes(opening.value, opening.randomness)
+            .is_ok_and(|opened| opened == cv_poseidon);
+    }
+    proof.cv_sha256 == Some(cv_sha256(opening.value, opening.randomness))
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::schema::config::ValueCommitmentScheme;
+
+    use super::*;
+    use crate::commands::{SaplingClaimSecretResult, ValueBlinding};
+
+    fn sapling_attestation(value: u64, randomness: [u8; 32]) -> HoldingsAttestation {
+        let airdrop_nullifier = Nullifier::from([1_u8; 32]);
+        HoldingsAttestation {
+            total_value: value,
+            proofs: ClaimProofsOutput {
+                sapling_proofs: vec![SaplingClaimProofResult {
+                    claim_id: [2_u8; 32],
+                    zkproof: [0_u8; 192],
+                    rk: [0_u8; 32],
+                    cv: None,
+                    cv_sha256: Some(cv_sha256(1_000, [3_u8; 32])),
+                    airdrop_nullifier,
+                }],
+                orchard_proofs: Vec::new(),
+                failed_claims: Vec::new(),
+                epoch: None,
+            },
+            openings: vec![ValueOpening {
+                pool: Pool::Sapling,
+                airdrop_nullifier,
+                value,
+                randomness,
+            }],
+        }
+    }
+
+    #[test]
+    fn openings_must_match_the_value_commitments() {
+        ensure_openings_match(&sapling_attestation(1_000, [3_u8; 32])).expect("valid opening");
+
+        let inflated = sapling_attestation(2_000, [3_u8; 32]);
+        assert!(ensure_openings_match(&inflated).is_err());
+
+        let mut wrong_total = sapling_attestation(1_000, [3_u8; 32]);
+        wrong_total.total_value = 5_000;
+        assert!(ensure_openings_match(&wrong_total).is_err());
+
+        let mut missing = sapling_attestation(1_000, [3_u8; 32]);
+        missing.openings.clear();
+        assert!(ensure_openings_match(&missing).is_err());
+    }
+
+    #[test]
+    fn value_blindings_open_without_the_prepared_claims() {
+        let proofs = sapling_attestation(1_000, [3_u8; 32]).proofs;
+        let mut secrets = ClaimSecretsOutput {
+            sapling: vec![SaplingClaimSecretResult {
+                airdrop_nullifier: Nullifier::from([1_u8; 32]),
+                alpha: [0_u8; 32],
+                rcv: None,
+                rcv_sha256: Some([3_u8; 32]),
+                value_blinding: Some(ValueBlinding {
+                    scheme: ValueCommitmentScheme::Sha256,
+                    value: 1_000,
+                    blinding: [3_u8; 32],
+                }),
+            }],
+            orchard: Vec::new(),
+            key_fingerprints: None,
+        };
+
+        let attestation =
+            attest_claimed_value(proofs.clone(), &secrets, None).expect("secrets open the proof");
+        assert_eq!(attestation.total_value, 1_000);
+        ensure_openings_match(&attestation).expect("valid opening");
+
+        for secret in &mut secrets.sapling {
+            secret.value_blinding = None;
+        }
+        assert!(
+            attest_claimed_value(proofs, &secrets, None).is_err(),
+            "earlier secrets need the prepared claims"
+        );
+    }
+}
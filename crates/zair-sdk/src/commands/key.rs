@@ -1,16 +1,23 @@
-//! Key derivation utilities.
+//! Key derivation and wallet import utilities.
 
 use std::path::{Path, PathBuf};
 
 use bip39::Language;
 use eyre::Context as _;
 use secrecy::{ExposeSecret as _, SecretBox, SecretString};
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
 use tracing::info;
-use zcash_keys::keys::UnifiedSpendingKey;
-use zcash_protocol::consensus::Network;
-use zip32::AccountId;
+use zair_scan::Network;
+use zcash_keys::address::UnifiedAddress;
+use zcash_keys::keys::{UnifiedFullViewingKey, UnifiedSpendingKey};
+use zcash_protocol::consensus::NetworkConstants as _;
+use zip32::{AccountId, DiversifierIndex};
 
 use super::sensitive_output::write_sensitive_output;
+use crate::build_info::to_artifact_json;
+use crate::error::{ZairError, ZairResult};
 use crate::seed::read_seed_file;
 
 /// Source of a BIP-39 mnemonic.
@@ -25,6 +32,62 @@ pub enum MnemonicSource {
     Stdin,
 }
 
+/// Source of a BIP-39 passphrase.
+#[derive(Debug, Default, Clone)]
+pub enum PassphraseSource {
+    /// Prompt interactively (no echo).
+    #[default]
+    Prompt,
+    /// Use the empty passphrase.
+    Empty,
+    /// Read from file.
+    File(PathBuf),
+    /// Read from the named environment variable.
+    Env(String),
+}
+
+/// Wordlist of a BIP-39 mnemonic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MnemonicLanguage {
+    /// English wordlist.
+    English,
+    /// Simplified Chinese wordlist.
+    SimplifiedChinese,
+    /// Traditional Chinese wordlist.
+    TraditionalChinese,
+    /// Czech wordlist.
+    Czech,
+    /// French wordlist.
+    French,
+    /// Italian wordlist.
+    Italian,
+    /// Japanese wordlist.
+    Japanese,
+    /// Korean wordlist.
+    Korean,
+    /// Portuguese wordlist.
+    Portuguese,
+    /// Spanish wordlist.
+    Spanish,
+}
+
+impl From<MnemonicLanguage> for Language {
+    fn from(language: MnemonicLanguage) -> Self {
+        match language {
+            MnemonicLanguage::English => Self::English,
+            MnemonicLanguage::SimplifiedChinese => Self::SimplifiedChinese,
+            MnemonicLanguage::TraditionalChinese => Self::TraditionalChinese,
+            MnemonicLanguage::Czech => Self::Czech,
+            MnemonicLanguage::French => Self::French,
+            MnemonicLanguage::Italian => Self::Italian,
+            MnemonicLanguage::Japanese => Self::Japanese,
+            MnemonicLanguage::Korean => Self::Korean,
+            MnemonicLanguage::Portuguese => Self::Portuguese,
+            MnemonicLanguage::Spanish => Self::Spanish,
+        }
+    }
+}
+
 async fn prompt_secret(prompt: &'static str) -> eyre::Result<SecretString> {
     tokio::task::spawn_blocking(move || {
         rpassword::prompt_password(prompt)
@@ -66,41 +129,137 @@ async fn read_mnemonic(source: MnemonicSource) -> eyre::Result<SecretString> {
     }
 }
 
-async fn read_passphrase(no_passphrase: bool) -> eyre::Result<SecretString> {
-    if no_passphrase {
-        return Ok(SecretString::new(Box::<str>::from("")));
+async fn read_passphrase(source: PassphraseSource) -> eyre::Result<SecretString> {
+    match source {
+        PassphraseSource::Prompt => prompt_secret("BIP-39 passphrase (optional): ").await,
+        PassphraseSource::Empty => Ok(SecretString::new(Box::<str>::from(""))),
+        PassphraseSource::File(path) => {
+            let text = tokio::fs::read_to_string(&path)
+                .await
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            // Only the line ending is dropped: spaces are part of the passphrase.
+            let line = text.strip_suffix('\n').unwrap_or(&text);
+            let passphrase = line.strip_suffix('\r').unwrap_or(line);
+            Ok(SecretString::new(passphrase.to_owned().into_boxed_str()))
+        }
+        PassphraseSource::Env(name) => std::env::var(&name)
+            .map(String::into_boxed_str)
+            .map(SecretString::new)
+            .with_context(|| format!("Failed to read the passphrase from ${name}")),
     }
-    prompt_secret("BIP-39 passphrase (optional): ").await
 }
 
 async fn derive_seed_from_mnemonic(
     mnemonic_source: MnemonicSource,
-    no_passphrase: bool,
+    language: Option<MnemonicLanguage>,
+    passphrase_source: PassphraseSource,
 ) -> eyre::Result<SecretBox<[u8; 64]>> {
     let mnemonic = read_mnemonic(mnemonic_source).await?;
-    let passphrase = read_passphrase(no_passphrase).await?;
+    let passphrase = read_passphrase(passphrase_source).await?;
+    seed_from_mnemonic(&mnemonic, language, &passphrase)
+}
 
-    let mnemonic =
-        bip39::Mnemonic::parse_in_normalized(Language::English, mnemonic.expose_secret())
-            .context("Failed to parse BIP-39 mnemonic")?;
+/// BIP-39 seed of `mnemonic`, in the wordlist of `language` or the one its words belong to.
+///
+/// The mnemonic and the passphrase are NFKD-normalized, so accented and Japanese mnemonics match
+/// whichever normalization the wallet displayed them in.
+fn seed_from_mnemonic(
+    mnemonic: &SecretString,
+    language: Option<MnemonicLanguage>,
+    passphrase: &SecretString,
+) -> eyre::Result<SecretBox<[u8; 64]>> {
+    let mnemonic = match language {
+        Some(language) => bip39::Mnemonic::parse_in(language.into(), mnemonic.expose_secret()),
+        None => bip39::Mnemonic::parse(mnemonic.expose_secret()),
+    }
+    .context(
+        "Failed to parse BIP-39 mnemonic; pass --mnemonic-language if its words are in several \
+         wordlists",
+    )?;
 
     let seed = mnemonic.to_seed(passphrase.expose_secret());
     Ok(SecretBox::new(Box::new(seed)))
 }
 
+fn derive_ufvk(
+    network: Network,
+    seed: &[u8; 64],
+    account: u32,
+) -> eyre::Result<UnifiedFullViewingKey> {
+    let account = AccountId::try_from(account).map_err(|_| eyre::eyre!("Invalid account-id"))?;
+
+    let usk = UnifiedSpendingKey::from_seed(&network, seed, account)
+        .map_err(|e| eyre::eyre!("Failed to derive spending key: {e:?}"))?;
+    Ok(usk.to_unified_full_viewing_key())
+}
+
+/// ZIP-32 path of the Sapling and Orchard keys of `account`.
+fn derivation_path(network: Network, account: u32) -> String {
+    format!("m/32'/{}'/{account}'", network.coin_type())
+}
+
+/// Unified address of `ufvk` with the Orchard and Sapling receivers at diversifier index `index`.
+fn unified_address(
+    network: Network,
+    ufvk: &UnifiedFullViewingKey,
+    index: u32,
+) -> eyre::Result<String> {
+    let j = DiversifierIndex::from(index);
+    let sapling = ufvk
+        .sapling()
+        .map(|dfvk| {
+            dfvk.address(j).ok_or_else(|| {
+                let next = dfvk
+                    .find_address(j)
+                    .and_then(|(next, _)| u32::try_from(next).ok());
+                next.map_or_else(
+                    || eyre::eyre!("Diversifier index {index} has no Sapling address"),
+                    |next| {
+                        eyre::eyre!(
+                            "Diversifier index {index} has no Sapling address; the next index \
+                             with one is {next}"
+                        )
+                    },
+                )
+            })
+        })
+        .transpose()?;
+    let orchard = ufvk
+        .orchard()
+        .map(|fvk| fvk.address_at(index, orchard::keys::Scope::External));
+    let address = UnifiedAddress::from_receivers(orchard, sapling, None)
+        .ok_or_else(|| eyre::eyre!("The UFVK has no shielded receiver"))?;
+    Ok(address.encode(&network))
+}
+
 /// Derive a 64-byte seed and write it as hex to `output`.
 ///
+/// The mnemonic is parsed in the wordlist of `language`, or in the one its words belong to when
+/// `language` is `None`.
+///
 /// # Errors
 /// Returns an error if mnemonic parsing or file I/O fails.
 pub async fn key_derive_seed(
     output: PathBuf,
     mnemonic_source: MnemonicSource,
-    no_passphrase: bool,
+    language: Option<MnemonicLanguage>,
+    passphrase_source: PassphraseSource,
+) -> ZairResult<()> {
+    key_derive_seed_inner(output, mnemonic_source, language, passphrase_source)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+async fn key_derive_seed_inner(
+    output: PathBuf,
+    mnemonic_source: MnemonicSource,
+    language: Option<MnemonicLanguage>,
+    passphrase_source: PassphraseSource,
 ) -> eyre::Result<()> {
     use zeroize::Zeroize as _;
 
     info!(file = ?output, "Deriving seed...");
-    let seed = derive_seed_from_mnemonic(mnemonic_source, no_passphrase).await?;
+    let seed = derive_seed_from_mnemonic(mnemonic_source, language, passphrase_source).await?;
 
     let mut hex = format!("{}\n", hex::encode(seed.expose_secret()));
     write_sensitive_output(&output, &hex).await?;
@@ -111,32 +270,442 @@ pub async fn key_derive_seed(
 
 /// Derive a UFVK and write it to `output`.
 ///
+/// A mnemonic is parsed as in [`key_derive_seed`]. The ZIP-32 derivation path of the account is
+/// logged, and with `address_index` the unified address at that diversifier index, to compare
+/// with the wallet.
+///
 /// # Errors
-/// Returns an error if seed loading, key derivation, or file I/O fails.
+/// Returns an error if seed loading, key derivation, or file I/O fails, or the Sapling key has no
+/// address at `address_index`.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Key sources are threaded through from the CLI"
+)]
 pub async fn key_derive_ufvk(
     network: Network,
     account: u32,
+    address_index: Option<u32>,
+    seed_file: Option<PathBuf>,
+    mnemonic_source: Option<MnemonicSource>,
+    language: Option<MnemonicLanguage>,
+    passphrase_source: PassphraseSource,
+    output: PathBuf,
+) -> ZairResult<()> {
+    key_derive_ufvk_inner(
+        network,
+        account,
+        address_index,
+        seed_file,
+        mnemonic_source,
+        language,
+        passphrase_source,
+        output,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Key sources are threaded through from the CLI"
+)]
+async fn key_derive_ufvk_inner(
+    network: Network,
+    account: u32,
+    address_index: Option<u32>,
     seed_file: Option<PathBuf>,
     mnemonic_source: Option<MnemonicSource>,
-    no_passphrase: bool,
+    language: Option<MnemonicLanguage>,
+    passphrase_source: PassphraseSource,
     output: PathBuf,
 ) -> eyre::Result<()> {
-    let seed = if let Some(source) = mnemonic_source {
-        derive_seed_from_mnemonic(source, no_passphrase).await?
+    let seed = read_account_seed(seed_file, mnemonic_source, language, passphrase_source).await?;
+
+    let ufvk = derive_ufvk(network, seed.expose_secret(), account)?;
+    info!(
+        account,
+        path = %derivation_path(network, account),
+        "Derived the Sapling and Orchard keys"
+    );
+    if let Some(index) = address_index {
+        let address = unified_address(network, &ufvk, index)?;
+        info!(index, %address, "Unified address");
+    }
+
+    let text = format!("{}\n", ufvk.encode(&network));
+    write_sensitive_output(&output, &text).await?;
+    info!(file = ?output, "UFVK written");
+    Ok(())
+}
+
+/// Read the seed from a mnemonic if one is given, otherwise from `seed_file` (default
+/// `seed.txt`).
+async fn read_account_seed(
+    seed_file: Option<PathBuf>,
+    mnemonic_source: Option<MnemonicSource>,
+    language: Option<MnemonicLanguage>,
+    passphrase_source: PassphraseSource,
+) -> eyre::Result<SecretBox<[u8; 64]>> {
+    if let Some(source) = mnemonic_source {
+        derive_seed_from_mnemonic(source, language, passphrase_source).await
     } else {
         let seed_path = seed_file.unwrap_or_else(|| PathBuf::from("seed.txt"));
         info!(file = ?seed_path, "Reading seed from file...");
-        read_seed_file(&seed_path).await?
+        read_seed_file(&seed_path).await
+    }
+}
+
+/// Key material that lets a proving worker prove the claims of one account without its seed.
+///
+/// The UFVK carries the Orchard full viewing key and the Sapling `ak` and `nk`; the Sapling
+/// `nsk` of each scope completes the Sapling proof generation keys. None of it can authorize a
+/// spend or sign a claim: that takes the spend authorizing keys, which only the seed derives.
+/// It does reveal every note of the account, like the UFVK alone.
+#[serde_as]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvingKeyFile {
+    /// Unified full viewing key of the account.
+    pub ufvk: String,
+    /// Sapling proof generation key `nsk` of the external scope.
+    #[serde_as(as = "Hex")]
+    pub sapling_nsk: [u8; 32],
+    /// Sapling proof generation key `nsk` of the internal (change) scope.
+    #[serde_as(as = "Hex")]
+    pub sapling_internal_nsk: [u8; 32],
+}
+
+impl ProvingKeyFile {
+    /// Proving keys of `account` derived from `seed`.
+    ///
+    /// # Errors
+    /// Returns an error if the account index is invalid or key derivation fails.
+    pub fn from_seed(network: Network, seed: &[u8; 64], account: u32) -> eyre::Result<Self> {
+        let account =
+            AccountId::try_from(account).map_err(|_| eyre::eyre!("Invalid account-id"))?;
+        let usk = UnifiedSpendingKey::from_seed(&network, seed, account)
+            .map_err(|e| eyre::eyre!("Failed to derive spending key: {e:?}"))?;
+        let extsk = usk.sapling();
+        Ok(Self {
+            ufvk: usk.to_unified_full_viewing_key().encode(&network),
+            sapling_nsk: extsk.expsk.nsk.to_bytes(),
+            sapling_internal_nsk: extsk.derive_internal().expsk.nsk.to_bytes(),
+        })
+    }
+}
+
+/// Export the proving keys of an account to `output`, for a proving worker.
+///
+/// A mnemonic is parsed as in [`key_derive_seed`].
+///
+/// # Errors
+/// Returns an error if seed loading, key derivation, or file I/O fails.
+pub async fn key_export_proving_key(
+    network: Network,
+    account: u32,
+    seed_file: Option<PathBuf>,
+    mnemonic_source: Option<MnemonicSource>,
+    language: Option<MnemonicLanguage>,
+    passphrase_source: PassphraseSource,
+    output: PathBuf,
+) -> ZairResult<()> {
+    key_export_proving_key_inner(
+        network,
+        account,
+        seed_file,
+        mnemonic_source,
+        language,
+        passphrase_source,
+        output,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+async fn key_export_proving_key_inner(
+    network: Network,
+    account: u32,
+    seed_file: Option<PathBuf>,
+    mnemonic_source: Option<MnemonicSource>,
+    language: Option<MnemonicLanguage>,
+    passphrase_source: PassphraseSource,
+    output: PathBuf,
+) -> eyre::Result<()> {
+    let seed = read_account_seed(seed_file, mnemonic_source, language, passphrase_source).await?;
+    let keys = ProvingKeyFile::from_seed(network, seed.expose_secret(), account)?;
+
+    let json = to_artifact_json(&keys)?;
+    write_sensitive_output(&output, &json).await?;
+    info!(file = ?output, "Proving key written");
+    Ok(())
+}
+
+/// Wallet export formats accepted by [`key_import`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletExportFormat {
+    /// Ywallet backup: a list of accounts, optionally under an `accounts` key, each with `name`,
+    /// `coin`, `seed` (mnemonic) and `index` (ZIP-32 account) fields.
+    Ywallet,
+    /// Zashi wallet export with `seed_phrase` and `birthday` fields.
+    Zashi,
+}
+
+#[derive(Deserialize)]
+struct YwalletAccount {
+    name: String,
+    /// Ywallet coin type: 0 is Zcash, 1 is Ycash.
+    #[serde(default)]
+    coin: u8,
+    /// Mnemonic, absent for view-only accounts.
+    seed: Option<String>,
+    #[serde(default)]
+    index: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum YwalletBackup {
+    Wrapped { accounts: Vec<YwalletAccount> },
+    List(Vec<YwalletAccount>),
+}
+
+#[derive(Deserialize)]
+struct ZashiExport {
+    seed_phrase: String,
+    birthday: Option<u64>,
+}
+
+/// Key material extracted from a wallet export.
+struct ImportedWallet {
+    mnemonic: SecretString,
+    account: u32,
+    birthday: Option<u64>,
+}
+
+fn parse_ywallet(text: &str, account_name: Option<&str>) -> eyre::Result<ImportedWallet> {
+    let backup: YwalletBackup =
+        serde_json::from_str(text).context("Failed to parse Ywallet backup")?;
+    let accounts = match backup {
+        YwalletBackup::Wrapped { accounts } | YwalletBackup::List(accounts) => accounts,
     };
+    let mut zcash_accounts = accounts.into_iter().filter(|account| account.coin == 0);
 
-    let account = AccountId::try_from(account).map_err(|_| eyre::eyre!("Invalid account-id"))?;
+    let account = if let Some(name) = account_name {
+        zcash_accounts
+            .find(|account| account.name == name)
+            .ok_or_else(|| eyre::eyre!("Ywallet backup has no Zcash account named {name:?}"))?
+    } else {
+        let mut with_seed: Vec<_> = zcash_accounts
+            .filter(|account| account.seed.is_some())
+            .collect();
+        match with_seed.len() {
+            0 => eyre::bail!("Ywallet backup has no Zcash account with a seed"),
+            1 => with_seed.remove(0),
+            _ => {
+                let names: Vec<_> = with_seed.iter().map(|a| a.name.as_str()).collect();
+                eyre::bail!(
+                    "Ywallet backup has several Zcash accounts ({}); select one with --account-name",
+                    names.join(", ")
+                );
+            }
+        }
+    };
 
-    let usk = UnifiedSpendingKey::from_seed(&network, seed.expose_secret(), account)
-        .map_err(|e| eyre::eyre!("Failed to derive spending key: {e:?}"))?;
-    let ufvk = usk.to_unified_full_viewing_key();
+    let seed = account.seed.ok_or_else(|| {
+        eyre::eyre!(
+            "Ywallet account {:?} is view-only and has no seed",
+            account.name
+        )
+    })?;
+    Ok(ImportedWallet {
+        mnemonic: SecretString::new(seed.into_boxed_str()),
+        account: account.index,
+        birthday: None,
+    })
+}
+
+fn parse_zashi(text: &str) -> eyre::Result<ImportedWallet> {
+    let export: ZashiExport = serde_json::from_str(text).context("Failed to parse Zashi export")?;
+    Ok(ImportedWallet {
+        mnemonic: SecretString::new(export.seed_phrase.into_boxed_str()),
+        // Zashi is a single-account wallet.
+        account: 0,
+        birthday: export.birthday,
+    })
+}
 
+fn parse_wallet_export(
+    text: &str,
+    format: Option<WalletExportFormat>,
+    account_name: Option<&str>,
+) -> eyre::Result<ImportedWallet> {
+    match format {
+        Some(WalletExportFormat::Ywallet) => parse_ywallet(text, account_name),
+        Some(WalletExportFormat::Zashi) => parse_zashi(text),
+        None => parse_zashi(text)
+            .or_else(|_| parse_ywallet(text, account_name))
+            .context("Unrecognized wallet export; pass --format to see the parse error"),
+    }
+}
+
+/// Import a Ywallet backup or Zashi export and write its seed and UFVK.
+///
+/// The format is detected when `format` is `None`. `account_name` selects the account of a
+/// multi-account Ywallet backup. The seed is written as hex to `seed_output` and the UFVK of the
+/// wallet account to `ufvk_output`.
+///
+/// # Errors
+/// Returns an error if the export cannot be read or parsed, or key derivation or file I/O fails.
+pub async fn key_import(
+    network: Network,
+    from: PathBuf,
+    format: Option<WalletExportFormat>,
+    account_name: Option<String>,
+    seed_output: PathBuf,
+    ufvk_output: PathBuf,
+) -> ZairResult<()> {
+    key_import_inner(
+        network,
+        from,
+        format,
+        account_name,
+        seed_output,
+        ufvk_output,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+async fn key_import_inner(
+    network: Network,
+    from: PathBuf,
+    format: Option<WalletExportFormat>,
+    account_name: Option<String>,
+    seed_output: PathBuf,
+    ufvk_output: PathBuf,
+) -> eyre::Result<()> {
+    use zeroize::Zeroize as _;
+
+    info!(file = ?from, "Reading wallet export...");
+    let text = read_secret_file(&from).await?;
+    let wallet = parse_wallet_export(text.expose_secret(), format, account_name.as_deref())?;
+
+    // Ywallet and Zashi do not support BIP-39 passphrases.
+    let seed = seed_from_mnemonic(
+        &wallet.mnemonic,
+        None,
+        &SecretString::new(Box::<str>::from("")),
+    )?;
+
+    let mut hex = format!("{}\n", hex::encode(seed.expose_secret()));
+    write_sensitive_output(&seed_output, &hex).await?;
+    hex.zeroize();
+    info!(file = ?seed_output, "Seed written");
+
+    let ufvk = derive_ufvk(network, seed.expose_secret(), wallet.account)?;
     let text = format!("{}\n", ufvk.encode(&network));
-    write_sensitive_output(&output, &text).await?;
-    info!(file = ?output, "UFVK written");
+    write_sensitive_output(&ufvk_output, &text).await?;
+    info!(
+        file = ?ufvk_output,
+        account = wallet.account,
+        path = %derivation_path(network, wallet.account),
+        "UFVK written"
+    );
+
+    match wallet.birthday {
+        Some(birthday) => info!(
+            birthday,
+            "Wallet birthday found; pass it as --birthday to claim prepare"
+        ),
+        None => info!("Export has no birthday height; look it up in the wallet for claim prepare"),
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon \
+                            abandon abandon abandon art";
+
+    #[test]
+    fn mnemonic_seed_uses_the_passphrase_and_wordlist() {
+        let secret = |s: &str| SecretString::new(Box::<str>::from(s));
+        let trezor = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                      abandon abandon about";
+        let seed = seed_from_mnemonic(&secret(trezor), None, &secret("TREZOR")).expect("seed");
+        assert_eq!(
+            hex::encode(seed.expose_secret()),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+
+        let french = bip39::Mnemonic::from_entropy_in(Language::French, &[0x5a; 16])
+            .expect("16 bytes of entropy")
+            .to_string();
+        let detected = seed_from_mnemonic(&secret(&french), None, &secret("")).expect("seed");
+        let explicit = seed_from_mnemonic(
+            &secret(&french),
+            Some(MnemonicLanguage::French),
+            &secret(""),
+        )
+        .expect("seed");
+        assert_eq!(detected.expose_secret(), explicit.expose_secret());
+        assert!(
+            seed_from_mnemonic(
+                &secret(&french),
+                Some(MnemonicLanguage::English),
+                &secret("")
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn derivation_path_uses_the_network_coin_type() {
+        assert_eq!(derivation_path(Network::MainNetwork, 2), "m/32'/133'/2'");
+        assert_eq!(derivation_path(Network::TestNetwork, 0), "m/32'/1'/0'");
+    }
+
+    #[test]
+    fn ywallet_backup_selects_the_only_zcash_account_with_a_seed() {
+        let text = format!(
+            r#"{{"accounts": [
+                {{"name": "watch", "coin": 0, "seed": null, "index": 0}},
+                {{"name": "ycash", "coin": 1, "seed": "{MNEMONIC}", "index": 0}},
+                {{"name": "main", "coin": 0, "seed": "{MNEMONIC}", "index": 2}}
+            ]}}"#
+        );
+
+        let wallet = parse_wallet_export(&text, None, None).expect("valid backup");
+        assert_eq!(wallet.mnemonic.expose_secret(), MNEMONIC);
+        assert_eq!(wallet.account, 2);
+        assert_eq!(wallet.birthday, None);
+    }
+
+    #[test]
+    fn ywallet_backup_with_several_accounts_requires_a_name() {
+        let text = format!(
+            r#"[
+                {{"name": "a", "seed": "{MNEMONIC}", "index": 0}},
+                {{"name": "b", "seed": "{MNEMONIC}", "index": 1}}
+            ]"#
+        );
+
+        let err = parse_ywallet(&text, None).err().expect("ambiguous backup");
+        assert!(err.to_string().contains("--account-name"), "{err:?}");
+
+        let wallet = parse_ywallet(&text, Some("b")).expect("named account");
+        assert_eq!(wallet.account, 1);
+    }
+
+    #[test]
+    fn zashi_export_keeps_the_birthday() {
+        let text = format!(
+            r#"{{"v": 1, "network_ID": 1, "birthday": 2800000, "seed_phrase": "{MNEMONIC}"}}"#
+        );
+
+        let wallet = parse_wallet_export(&text, None, None).expect("valid export");
+        assert_eq!(wallet.account, 0);
+        assert_eq!(wallet.birthday, Some(2_800_000));
+    }
+}
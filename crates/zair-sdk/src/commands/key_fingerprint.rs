@@ -0,0 +1,53 @@
+//! Viewing key fingerprints of claims and secrets files.
+//!
+//! `claim prepare` records salted fingerprints of the UFVK it scanned with, and `claim prove` and
+//! `claim sign` check them against their own keys, so the claims of one wallet are not proven or
+//! signed with the keys of another. The salt is derived from the airdrop snapshot, so the
+//! fingerprints do not link the claims of one wallet across airdrops.
+
+use eyre::ensure;
+use tracing::warn;
+use zair_core::base::{Pool, key_fingerprint};
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::proof_inputs::KeyFingerprints;
+use zcash_keys::keys::UnifiedFullViewingKey;
+
+/// Fingerprints of the Sapling and Orchard viewing keys of `ufvk` in the airdrop of
+/// `airdrop_config`.
+#[must_use]
+pub(crate) fn key_fingerprints(
+    airdrop_config: &AirdropConfiguration,
+    ufvk: &UnifiedFullViewingKey,
+) -> KeyFingerprints {
+    let salt = airdrop_config.key_fingerprint_salt();
+    KeyFingerprints {
+        sapling: ufvk
+            .sapling()
+            .map(|dfvk| key_fingerprint(&salt, Pool::Sapling, &dfvk.to_bytes())),
+        orchard: ufvk
+            .orchard()
+            .map(|fvk| key_fingerprint(&salt, Pool::Orchard, &fvk.to_bytes())),
+    }
+}
+
+/// Check that the fingerprints recorded in a `what` file name the wallet of `ufvk`.
+///
+/// Files written before fingerprints were recorded carry none and are only warned about.
+pub(crate) fn ensure_same_wallet(
+    recorded: Option<&KeyFingerprints>,
+    airdrop_config: &AirdropConfiguration,
+    ufvk: &UnifiedFullViewingKey,
+    what: &str,
+) -> eyre::Result<()> {
+    let Some(recorded) = recorded else {
+        warn!(
+            "The {what} file records no key fingerprints; cannot check it belongs to this wallet"
+        );
+        return Ok(());
+    };
+    ensure!(
+        recorded.matches(&key_fingerprints(airdrop_config, ufvk)),
+        "Key mismatch: the {what} file was prepared by a different wallet"
+    );
+    Ok(())
+}
@@ -0,0 +1,69 @@
+//! Check Sapling claim note positions against lightwalletd before proving.
+
+use std::str::FromStr as _;
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use http::Uri;
+use tracing::info;
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::proof_inputs::AirdropClaimInputs;
+use zair_prepare::{SaplingTreeState, verify_sapling_note_position};
+use zair_scan::light_walletd::LightWalletd;
+
+use crate::common::{configured_network, resolve_lightwalletd_url};
+
+/// Recompute the Sapling note commitment tree anchor of every Sapling claim.
+///
+/// The tree state at the snapshot height is fetched from lightwalletd independently of the
+/// organizer, must match the configured note commitment root, and every claimed note must hash to
+/// it from its claimed position.
+pub(super) async fn verify_sapling_note_positions(
+    inputs: &AirdropClaimInputs,
+    airdrop_config: &AirdropConfiguration,
+    lightwalletd_url: Option<&str>,
+) -> eyre::Result<()> {
+    if inputs.sapling_claim_input.is_empty() {
+        return Ok(());
+    }
+    let airdrop_config = &airdrop_config.for_epoch(inputs.epoch)?;
+    let sapling_config = airdrop_config
+        .sapling
+        .as_ref()
+        .context("Sapling claims present but airdrop configuration has no sapling pool")?;
+
+    let network = configured_network(airdrop_config)?;
+    let lightwalletd_url = resolve_lightwalletd_url(network, lightwalletd_url);
+    let lightwalletd_url =
+        Uri::from_str(&lightwalletd_url).context("lightwalletd URL is required")?;
+    let lightwalletd = LightWalletd::connect(lightwalletd_url).await?;
+
+    info!(
+        height = airdrop_config.snapshot_height,
+        "Fetching Sapling tree state to check note positions"
+    );
+    let tree_state = lightwalletd
+        .get_tree_state(airdrop_config.snapshot_height)
+        .await?;
+    let tree = tree_state
+        .sapling_tree()
+        .context("Failed to parse the Sapling tree state from lightwalletd")?;
+    let tree = SaplingTreeState {
+        root: tree.root().to_bytes(),
+        size: u64::try_from(tree.size())?,
+    };
+    ensure!(
+        tree.root == sapling_config.note_commitment_root,
+        "lightwalletd Sapling note commitment root at height {} does not match the airdrop configuration",
+        airdrop_config.snapshot_height
+    );
+
+    for (index, claim) in inputs.sapling_claim_input.iter().enumerate() {
+        verify_sapling_note_position(&claim.private_inputs, &tree)
+            .with_context(|| format!("Sapling claim {index} failed the note position check"))?;
+    }
+    info!(
+        count = inputs.sapling_claim_input.len(),
+        "Sapling note positions match the note commitment tree"
+    );
+    Ok(())
+}
@@ -0,0 +1,242 @@
+//! Snapshot and gap-tree files in S3 or Google Cloud Storage.
+//!
+//! `config build` and `claim prepare` accept `s3://bucket/key` and `gs://bucket/key` wherever they
+//! take a snapshot or gap-tree path. The files are read in place, so they are staged in the
+//! standard snapshots and gap-trees directories: `claim prepare` downloads them before it starts,
+//! and `config build` writes them locally and uploads them once the configuration is built.
+//! Height-index and nullifier-filter sidecars follow their snapshot when present.
+//!
+//! Credentials and regions come from the environment, as for the providers' own tools
+//! (`AWS_ACCESS_KEY_ID`, `AWS_REGION`, `GOOGLE_APPLICATION_CREDENTIALS`, ...).
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use eyre::{Context as _, ContextCompat as _};
+use futures::StreamExt as _;
+use object_store::ObjectStore;
+use object_store::buffered::BufWriter;
+use object_store::path::Path as ObjectPath;
+use tokio::io::AsyncWriteExt as _;
+use tracing::info;
+use url::Url;
+
+use super::snapshot_fetch::{SnapshotArtifact, SnapshotFiles, create_parent_dir};
+use crate::error::{ZairError, ZairResult};
+use crate::paths;
+
+/// URL schemes handled by the object-store backend.
+const OBJECT_STORE_SCHEMES: [&str; 2] = ["s3://", "gs://"];
+
+/// Whether `path` is an `s3://` or `gs://` URL rather than a local path.
+#[must_use]
+pub fn is_object_store_url(path: &Path) -> bool {
+    path.to_str().is_some_and(|path| {
+        OBJECT_STORE_SCHEMES
+            .iter()
+            .any(|scheme| path.starts_with(scheme))
+    })
+}
+
+/// Download the snapshot and gap-tree files of `files` that are object-store URLs.
+///
+/// Each one is downloaded to the standard directory of its artifact, under the object's file
+/// name, and the returned paths point at the local copies. Local paths are returned unchanged.
+///
+/// # Errors
+/// Returns an error if a URL is malformed or a download fails.
+pub async fn download_object_store_files(mut files: SnapshotFiles) -> ZairResult<SnapshotFiles> {
+    download_object_store_files_inner(&mut files)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Network))?;
+    Ok(files)
+}
+
+async fn download_object_store_files_inner(files: &mut SnapshotFiles) -> eyre::Result<()> {
+    for artifact in SnapshotArtifact::ALL {
+        let slot = files.path_mut(artifact);
+        let Some(url) = slot.take_if(|path| is_object_store_url(path)) else {
+            continue;
+        };
+        let local = staging_path(&url, artifact)?;
+        download(&url, &local, true).await?;
+        if !artifact.is_gap_tree() {
+            download(
+                &paths::indexed_snapshot_path(&url),
+                &paths::indexed_snapshot_path(&local),
+                false,
+            )
+            .await?;
+            download(
+                &paths::nullifier_filter_path(&url),
+                &paths::nullifier_filter_path(&local),
+                false,
+            )
+            .await?;
+        }
+        *slot = Some(local);
+    }
+    Ok(())
+}
+
+/// Object-store destinations of `config build` outputs, staged locally until they are uploaded.
+#[derive(Debug, Default)]
+pub struct ObjectStoreUploads {
+    staged: Vec<(PathBuf, PathBuf)>,
+}
+
+impl ObjectStoreUploads {
+    /// Local path to write `artifact` to instead of `path`.
+    ///
+    /// If `path` is an object-store URL, the returned staging path is uploaded to it by
+    /// [`Self::upload`]; otherwise `path` is returned unchanged.
+    ///
+    /// # Errors
+    /// Returns an error if the URL has no file name.
+    pub fn stage(&mut self, path: PathBuf, artifact: SnapshotArtifact) -> ZairResult<PathBuf> {
+        if !is_object_store_url(&path) {
+            return Ok(path);
+        }
+        let local = staging_path(&path, artifact).map_err(ZairError::Config)?;
+        self.staged.push((local.clone(), path));
+        Ok(local)
+    }
+
+    /// Upload every staged file that was written, with its snapshot sidecars.
+    ///
+    /// # Errors
+    /// Returns an error if a URL is malformed or an upload fails.
+    pub async fn upload(self) -> ZairResult<()> {
+        self.upload_inner()
+            .await
+            .map_err(|e| ZairError::classify(e, ZairError::Network))
+    }
+
+    async fn upload_inner(self) -> eyre::Result<()> {
+        for (local, url) in self.staged {
+            for (local, url) in [
+                (local.clone(), url.clone()),
+                (
+                    paths::indexed_snapshot_path(&local),
+                    paths::indexed_snapshot_path(&url),
+                ),
+                (
+                    paths::nullifier_filter_path(&local),
+                    paths::nullifier_filter_path(&url),
+                ),
+            ] {
+                if tokio::fs::try_exists(&local).await? {
+                    upload(&local, &url).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Local copy of the object at `url`, in the standard directory of `artifact`.
+fn staging_path(url: &Path, artifact: SnapshotArtifact) -> eyre::Result<PathBuf> {
+    let file_name = url
+        .file_name()
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("{} has no file name", url.display()))?;
+    Ok(paths::default_output_path(artifact.dir(), file_name))
+}
+
+/// Object store and object path of an `s3://` or `gs://` URL.
+fn open(url: &Path) -> eyre::Result<(Arc<dyn ObjectStore>, ObjectPath)> {
+    let url = url.to_str().context("Object-store URL is not UTF-8")?;
+    let parsed = Url::parse(url).with_context(|| format!("Invalid object-store URL {url}"))?;
+    let (store, path) = object_store::parse_url(&parsed)
+        .with_context(|| format!("Unsupported object-store URL {url}"))?;
+    Ok((Arc::from(store), path))
+}
+
+/// Download `url` to `local`. A missing optional object is skipped.
+async fn download(url: &Path, local: &Path, required: bool) -> eyre::Result<()> {
+    let (store, path) = open(url)?;
+    let object = match store.get(&path).await {
+        Ok(object) => object,
+        Err(object_store::Error::NotFound { .. }) if !required => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to fetch {}", url.display())),
+    };
+
+    create_parent_dir(local)?;
+    let partial = paths::begin_partial(local);
+    let mut file = tokio::fs::File::create(&partial)
+        .await
+        .with_context(|| format!("Failed to create {}", partial.display()))?;
+    let mut chunks = object.into_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.with_context(|| format!("Failed to download {}", url.display()))?;
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    drop(file);
+    paths::finish_partial(&partial, local)
+        .with_context(|| format!("Failed to write {}", local.display()))?;
+    info!(url = %url.display(), file = ?local, "Downloaded from object store");
+    Ok(())
+}
+
+/// Upload `local` to `url`, in parts for large files.
+async fn upload(local: &Path, url: &Path) -> eyre::Result<()> {
+    let (store, path) = open(url)?;
+    let mut file = tokio::fs::File::open(local)
+        .await
+        .with_context(|| format!("Failed to open {}", local.display()))?;
+    let mut writer = BufWriter::new(store, path);
+    tokio::io::copy(&mut file, &mut writer)
+        .await
+        .with_context(|| format!("Failed to upload {}", url.display()))?;
+    writer
+        .shutdown()
+        .await
+        .with_context(|| format!("Failed to upload {}", url.display()))?;
+    info!(file = ?local, url = %url.display(), "Uploaded to object store");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_store_urls_are_staged_locally() {
+        assert!(is_object_store_url(Path::new(
+            "s3://bucket/snapshot-sapling.bin"
+        )));
+        assert!(is_object_store_url(Path::new(
+            "gs://bucket/a/gaptree-orchard.bin"
+        )));
+        assert!(!is_object_store_url(Path::new("snapshot-sapling.bin")));
+        assert!(!is_object_store_url(Path::new("https://example.org/a.bin")));
+
+        let mut uploads = ObjectStoreUploads::default();
+        let local = uploads
+            .stage(
+                PathBuf::from("gs://bucket/round-2/gaptree-orchard.bin"),
+                SnapshotArtifact::OrchardGapTree,
+            )
+            .expect("stage");
+        assert!(
+            local.ends_with("gaptree-orchard.bin"),
+            "{}",
+            local.display()
+        );
+        assert!(!is_object_store_url(&local));
+        assert_eq!(
+            paths::indexed_snapshot_path(Path::new("s3://bucket/snapshot-sapling.bin")),
+            PathBuf::from("s3://bucket/snapshot-sapling.idx")
+        );
+
+        let unchanged = uploads
+            .stage(
+                PathBuf::from("snapshot-sapling.bin"),
+                SnapshotArtifact::SaplingSnapshot,
+            )
+            .expect("stage");
+        assert_eq!(unchanged, PathBuf::from("snapshot-sapling.bin"));
+        assert_eq!(uploads.staged.len(), 1);
+    }
+}
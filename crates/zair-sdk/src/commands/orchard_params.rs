@@ -10,6 +10,8 @@ use pasta_curves::vesta;
 use tracing::info;
 use zair_orchard_proofs::ValueCommitmentScheme as OrchardValueCommitmentScheme;
 
+use crate::error::{ZairError, ZairResult};
+
 /// How to handle missing Orchard params.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OrchardParamsMode {
@@ -102,8 +104,17 @@ fn write_params_file(
 pub async fn generate_orchard_params_file(
     params_file: PathBuf,
     scheme: OrchardValueCommitmentScheme,
+) -> ZairResult<()> {
+    generate_orchard_params_file_inner(params_file, scheme)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+async fn generate_orchard_params_file_inner(
+    params_file: PathBuf,
+    scheme: OrchardValueCommitmentScheme,
 ) -> eyre::Result<()> {
-    let expected_k = zair_orchard_proofs::k_for_scheme(scheme);
+    let expected_k = zair_orchard_proofs::k_for_scheme(scheme)?;
     tokio::task::spawn_blocking(move || -> eyre::Result<()> {
         let _ = write_params_file(&params_file, true, expected_k)?;
         Ok(())
@@ -120,8 +131,18 @@ pub async fn load_or_prepare_orchard_params(
     params_file: PathBuf,
     scheme: OrchardValueCommitmentScheme,
     mode: OrchardParamsMode,
+) -> ZairResult<Arc<Params<vesta::Affine>>> {
+    load_or_prepare_orchard_params_inner(params_file, scheme, mode)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+pub(super) async fn load_or_prepare_orchard_params_inner(
+    params_file: PathBuf,
+    scheme: OrchardValueCommitmentScheme,
+    mode: OrchardParamsMode,
 ) -> eyre::Result<Arc<Params<vesta::Affine>>> {
-    let expected_k = zair_orchard_proofs::k_for_scheme(scheme);
+    let expected_k = zair_orchard_proofs::k_for_scheme(scheme)?;
 
     if tokio::fs::try_exists(&params_file).await? {
         let bytes = tokio::fs::read(&params_file).await?;
@@ -182,3 +203,28 @@ pub async fn load_or_prepare_orchard_params(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn required_params_of_another_k_are_rejected() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let params_file = dir.path().join("orchard-params.bin");
+        let native_k = zair_orchard_proofs::k_for_scheme(OrchardValueCommitmentScheme::Native)
+            .expect("circuit lays out");
+        write_params_file(&params_file, false, native_k).expect("write params");
+
+        let Err(err) = load_or_prepare_orchard_params(
+            params_file,
+            OrchardValueCommitmentScheme::Sha256,
+            OrchardParamsMode::Require,
+        )
+        .await
+        else {
+            panic!("params of the native k do not fit the sha256 circuit");
+        };
+        assert!(err.to_string().contains("`k` mismatch"), "{err}");
+    }
+}
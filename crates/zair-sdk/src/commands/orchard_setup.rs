@@ -6,24 +6,48 @@ use tracing::info;
 use zair_core::schema::config::ValueCommitmentScheme;
 
 use super::orchard_params::generate_orchard_params_file;
+use super::setup_fingerprint::{SetupArtifact, bind_fingerprint, read_orchard_params_fingerprint};
+use crate::error::{ZairError, ZairResult};
 
 /// Generate and persist Orchard Halo2 params.
 ///
 /// This is a one-time setup step per `k` (which depends on the value commitment scheme).
+/// When `bind_configuration_file` is given, the params fingerprint is stored in its Orchard pool.
 ///
 /// # Errors
-/// Returns an error if param generation fails.
+/// Returns an error if param generation or binding fails.
 pub async fn generate_orchard_params(
     params_out: PathBuf,
     scheme: ValueCommitmentScheme,
+    bind_configuration_file: Option<PathBuf>,
+) -> ZairResult<()> {
+    generate_orchard_params_inner(params_out, scheme, bind_configuration_file)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+async fn generate_orchard_params_inner(
+    params_out: PathBuf,
+    scheme: ValueCommitmentScheme,
+    bind_configuration_file: Option<PathBuf>,
 ) -> eyre::Result<()> {
     let orchard_scheme: zair_orchard_proofs::ValueCommitmentScheme = scheme.into();
-    let k = zair_orchard_proofs::k_for_scheme(orchard_scheme);
+    let k = zair_orchard_proofs::k_for_scheme(orchard_scheme)?;
     info!(?scheme, k, file = ?params_out, "Generating Orchard Halo2 params...");
     info!("This may take a while (especially for sha256).");
 
     generate_orchard_params_file(params_out.clone(), orchard_scheme).await?;
 
     info!(file = ?params_out, "Orchard params ready");
+
+    if let Some(configuration_file) = bind_configuration_file {
+        let fingerprint = read_orchard_params_fingerprint(&params_out).await?;
+        bind_fingerprint(
+            &configuration_file,
+            SetupArtifact::OrchardParams,
+            fingerprint,
+        )
+        .await?;
+    }
     Ok(())
 }
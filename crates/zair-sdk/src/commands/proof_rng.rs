@@ -0,0 +1,110 @@
+//! Randomness for claim proving.
+//!
+//! Proving draws the witness randomness of every claim (`alpha`, `rcv`, `rcv_sha256`,
+//! `rcv_poseidon`) and its proof blinding factors from a [`ProofRngSource`]. [`OsProofRng`] reads
+//! the operating system RNG; integrators with a hardware RNG implement the trait themselves.
+//! `DeterministicProofRng` makes proofs reproducible for golden-vector tests and only exists with
+//! the `deterministic-rng` feature, so a production build cannot be asked for predictable secrets.
+
+#[cfg(any(test, feature = "deterministic-rng"))]
+use rand_core::SeedableRng as _;
+use rand_core::{OsRng, RngCore};
+#[cfg(any(test, feature = "deterministic-rng"))]
+use rand_xorshift::XorShiftRng;
+use zair_core::base::Nullifier;
+#[cfg(any(test, feature = "deterministic-rng"))]
+use zair_core::base::hash_bytes;
+
+/// Source of the randomness used to prove claims.
+///
+/// The randomness hides the claimed value and the note behind each proof: a source must be
+/// cryptographically secure, unless it is only used for tests.
+pub trait ProofRngSource: Send + Sync {
+    /// RNG for the claim with `airdrop_nullifier`.
+    ///
+    /// Claims are proven in parallel, so every claim gets its own RNG.
+    fn rng_for_claim(&self, airdrop_nullifier: &Nullifier) -> ClaimRng;
+
+    /// Whether the source yields the same randomness on every run; proving warns about such
+    /// sources.
+    fn is_deterministic(&self) -> bool {
+        false
+    }
+}
+
+/// RNG of a single claim, handed out by a [`ProofRngSource`].
+pub struct ClaimRng(Box<dyn RngCore + Send>);
+
+impl ClaimRng {
+    /// Wrap `rng`.
+    #[must_use]
+    pub fn new(rng: impl RngCore + Send + 'static) -> Self {
+        Self(Box::new(rng))
+    }
+}
+
+impl RngCore for ClaimRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+/// The operating system RNG, used unless the caller supplies another source.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsProofRng;
+
+impl ProofRngSource for OsProofRng {
+    fn rng_for_claim(&self, _airdrop_nullifier: &Nullifier) -> ClaimRng {
+        ClaimRng::new(OsRng)
+    }
+}
+
+/// Randomness derived from a fixed seed, for golden-vector tests.
+///
+/// The stream of a claim depends only on the seed and the claim's airdrop nullifier, so outputs
+/// do not depend on proving order or parallelism. Anyone who knows the seed can recompute the
+/// secrets: never use it for real claims.
+#[cfg(any(test, feature = "deterministic-rng"))]
+#[derive(Debug, Clone, Copy)]
+pub struct DeterministicProofRng {
+    seed: u64,
+}
+
+#[cfg(any(test, feature = "deterministic-rng"))]
+impl DeterministicProofRng {
+    /// Source seeded with `seed`.
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+#[cfg(any(test, feature = "deterministic-rng"))]
+impl ProofRngSource for DeterministicProofRng {
+    fn rng_for_claim(&self, airdrop_nullifier: &Nullifier) -> ClaimRng {
+        let mut preimage = Vec::with_capacity(40);
+        preimage.extend_from_slice(&self.seed.to_le_bytes());
+        preimage.extend_from_slice(airdrop_nullifier.as_ref());
+        let mut rng_seed = [0_u8; 16];
+        for (dst, src) in rng_seed.iter_mut().zip(hash_bytes(&preimage)) {
+            *dst = src;
+        }
+        ClaimRng::new(XorShiftRng::from_seed(rng_seed))
+    }
+
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,538 @@
+//! Directory-backed proving queue for an organizer-run proving farm.
+//!
+//! Users whose devices cannot run the prover submit their prepared claims with a proving key
+//! (see [`ProvingKeyFile`]) to a queue directory shared with the organizer's workers, for example
+//! over a network file system. A job is a directory that moves through the queue:
+//!
+//! - `incoming/<job>`: being written by [`submit_proving_job`];
+//! - `pending/<job>`: complete and waiting for a worker;
+//! - `running/<job>`: taken by a worker;
+//! - `done/<job>` or `failed/<job>`: finished, with the proofs and secrets or an `error.txt`.
+//!
+//! Every move is a directory rename, which is atomic on one file system, so any number of workers
+//! can share a queue: exactly one of them wins the rename of a pending job. A worker deletes the
+//! proving key of a job once the job finishes.
+
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+#[cfg(feature = "prove")]
+use std::sync::Arc;
+#[cfg(feature = "prove")]
+use std::time::Duration;
+
+use eyre::{Context as _, ensure, eyre};
+use rand_core::{OsRng, RngCore as _};
+use tracing::info;
+#[cfg(feature = "prove")]
+use tracing::warn;
+#[cfg(feature = "prove")]
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::proof_inputs::AirdropClaimInputs;
+#[cfg(feature = "prove")]
+use zair_scan::Network;
+
+use super::claim_proofs::{
+    ClaimProofsOutput, ClaimSecretsOutput, ProofFilter, ProofVerdict, ensure_all_valid,
+    verify_claim_proof_verdicts,
+};
+#[cfg(feature = "prove")]
+use super::claim_proofs_prove::{ProvingKeys, load_proving_params, prove_claims_inner};
+use super::key::ProvingKeyFile;
+use super::orchard_params::OrchardParamsMode;
+#[cfg(feature = "prove")]
+use super::proof_rng::{OsProofRng, ProofRngSource};
+use super::sensitive_output::write_sensitive_output;
+use super::verification_report::unix_now;
+#[cfg(feature = "prove")]
+use crate::build_info::to_artifact_json;
+use crate::common::configured_network;
+use crate::error::{ZairError, ZairResult};
+#[cfg(feature = "prove")]
+use crate::pipeline::ProvingParams;
+use crate::{paths, stdio};
+
+/// Queue subdirectory of jobs being submitted.
+const INCOMING_DIR: &str = "incoming";
+/// Queue subdirectory of jobs waiting for a worker.
+pub const PENDING_DIR: &str = "pending";
+/// Queue subdirectory of jobs taken by a worker.
+pub const RUNNING_DIR: &str = "running";
+/// Queue subdirectory of proven jobs.
+pub const DONE_DIR: &str = "done";
+/// Queue subdirectory of jobs that failed.
+pub const FAILED_DIR: &str = "failed";
+
+/// Prepared claims of a job.
+pub const JOB_CLAIMS_FILE: &str = "claims.json";
+/// Proving key of a job, deleted once the job finishes.
+pub const JOB_PROVING_KEY_FILE: &str = "proving-key.json";
+/// Claim proofs of a proven job.
+pub const JOB_PROOFS_FILE: &str = "claim-proofs.json";
+/// Claim secrets of a proven job, needed to sign the proofs.
+pub const JOB_SECRETS_FILE: &str = "claim-proofs-secrets.json";
+/// Error of a failed job.
+pub const JOB_ERROR_FILE: &str = "error.txt";
+
+/// Submit `claims_file`, with the proving key of its account, to the queue at `queue_dir`.
+///
+/// Returns the job identifier: the results appear in `done/<job>` or `failed/<job>`.
+///
+/// # Errors
+/// Returns an error if the inputs cannot be read or parsed, or the job cannot be written.
+pub async fn submit_proving_job(
+    queue_dir: PathBuf,
+    claims_file: PathBuf,
+    proving_key_file: PathBuf,
+) -> ZairResult<String> {
+    submit_proving_job_inner(queue_dir, claims_file, proving_key_file)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+async fn submit_proving_job_inner(
+    queue_dir: PathBuf,
+    claims_file: PathBuf,
+    proving_key_file: PathBuf,
+) -> eyre::Result<String> {
+    let claims = stdio::read_to_string(&claims_file).await?;
+    serde_json::from_str::<AirdropClaimInputs>(&claims)
+        .context("Failed to parse prepared claims JSON")?;
+    let proving_key = stdio::read_to_string(&proving_key_file).await?;
+    serde_json::from_str::<ProvingKeyFile>(&proving_key)
+        .context("Failed to parse proving key JSON")?;
+
+    let job_id = new_job_id()?;
+    let incoming = queue_dir.join(INCOMING_DIR).join(&job_id);
+    tokio::fs::create_dir_all(&incoming)
+        .await
+        .with_context(|| format!("Failed to create {}", incoming.display()))?;
+    tokio::fs::write(incoming.join(JOB_CLAIMS_FILE), claims).await?;
+    write_sensitive_output(&incoming.join(JOB_PROVING_KEY_FILE), &proving_key).await?;
+
+    let pending = queue_dir.join(PENDING_DIR);
+    tokio::fs::create_dir_all(&pending)
+        .await
+        .with_context(|| format!("Failed to create {}", pending.display()))?;
+    tokio::fs::rename(&incoming, pending.join(&job_id))
+        .await
+        .with_context(|| format!("Failed to queue {}", incoming.display()))?;
+    info!(job = job_id, queue = ?queue_dir, "Submitted proving job");
+    Ok(job_id)
+}
+
+/// Job identifier that sorts by submission time: `<unix seconds>-<random hex>`.
+fn new_job_id() -> eyre::Result<String> {
+    let mut suffix = [0_u8; 8];
+    OsRng.fill_bytes(&mut suffix);
+    Ok(format!("{:012}-{}", unix_now()?, hex::encode(suffix)))
+}
+
+/// Directory of the proven job `job_id` of the queue at `queue_dir`.
+///
+/// # Errors
+/// Returns a proving error with the worker's message if the job failed, and a configuration
+/// error if `job_id` is not a plain name or the job is not finished or not in the queue.
+pub async fn finished_proving_job(queue_dir: PathBuf, job_id: String) -> ZairResult<PathBuf> {
+    ensure_plain_job_id(&job_id).map_err(ZairError::Config)?;
+    let done = queue_dir.join(DONE_DIR).join(&job_id);
+    if tokio::fs::try_exists(&done).await.unwrap_or(false) {
+        return Ok(done);
+    }
+    let failed = queue_dir.join(FAILED_DIR).join(&job_id);
+    if let Ok(error) = tokio::fs::read_to_string(failed.join(JOB_ERROR_FILE)).await {
+        return Err(ZairError::Proving(eyre!(
+            "Job {job_id} failed: {}",
+            error.trim_end()
+        )));
+    }
+    for dir in [PENDING_DIR, RUNNING_DIR] {
+        if tokio::fs::try_exists(queue_dir.join(dir).join(&job_id))
+            .await
+            .unwrap_or(false)
+        {
+            return Err(ZairError::Config(eyre!(
+                "Job {job_id} is not finished yet: it is in {dir}/"
+            )));
+        }
+    }
+    Err(ZairError::Config(eyre!(
+        "No job {job_id} in {}",
+        queue_dir.display()
+    )))
+}
+
+/// Check that `job_id` names a job directory and cannot reach outside the queue.
+fn ensure_plain_job_id(job_id: &str) -> eyre::Result<()> {
+    let mut components = Path::new(job_id).components();
+    let plain = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(name)), None) if name == job_id
+    );
+    ensure!(plain, "Invalid job {job_id:?}: expected a job identifier");
+    Ok(())
+}
+
+/// Verify the proven job in `job_dir` and copy its proofs and secrets to `proofs_out` and
+/// `secrets_out`.
+///
+/// Every proof must belong to a claim of `claims_file`, the prepared claims submitted with the
+/// job, so a prover cannot slip in claims the user did not ask for, and must verify against the
+/// airdrop configuration and setup files. Nothing is written unless every proof is valid.
+///
+/// Returns the verdict of each proof.
+///
+/// # Errors
+/// Returns an error if the files cannot be read or written, a proof is not of a submitted
+/// claim, or a proof is invalid.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Collection takes the job, the verification setup and both outputs"
+)]
+pub async fn collect_proving_job(
+    job_dir: PathBuf,
+    claims_file: PathBuf,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    airdrop_configuration_file: PathBuf,
+    proofs_out: PathBuf,
+    secrets_out: PathBuf,
+) -> ZairResult<Vec<ProofVerdict>> {
+    collect_proving_job_inner(
+        job_dir,
+        claims_file,
+        verifying_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+        airdrop_configuration_file,
+        proofs_out,
+        secrets_out,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Mirrors the public collect_proving_job signature"
+)]
+async fn collect_proving_job_inner(
+    job_dir: PathBuf,
+    claims_file: PathBuf,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    airdrop_configuration_file: PathBuf,
+    proofs_out: PathBuf,
+    secrets_out: PathBuf,
+) -> eyre::Result<Vec<ProofVerdict>> {
+    let inputs: AirdropClaimInputs =
+        serde_json::from_str(&stdio::read_to_string(&claims_file).await?)
+            .context("Failed to parse prepared claims JSON")?;
+    let proofs_file = job_dir.join(JOB_PROOFS_FILE);
+    let proofs_json = tokio::fs::read_to_string(&proofs_file)
+        .await
+        .with_context(|| format!("Failed to read {}", proofs_file.display()))?;
+    let proofs: ClaimProofsOutput =
+        serde_json::from_str(&proofs_json).context("Failed to parse claim proofs JSON")?;
+    let secrets_file = job_dir.join(JOB_SECRETS_FILE);
+    let secrets_json = tokio::fs::read_to_string(&secrets_file)
+        .await
+        .with_context(|| format!("Failed to read {}", secrets_file.display()))?;
+    serde_json::from_str::<ClaimSecretsOutput>(&secrets_json)
+        .context("Failed to parse claim secrets JSON")?;
+
+    ensure_claims_submitted(
+        proofs.sapling_proofs.iter().map(|proof| proof.claim_id),
+        inputs
+            .sapling_claim_input
+            .iter()
+            .map(|claim| claim.claim_id),
+        "Sapling",
+    )?;
+    ensure_claims_submitted(
+        proofs.orchard_proofs.iter().map(|proof| proof.claim_id),
+        inputs
+            .orchard_claim_input
+            .iter()
+            .map(|claim| claim.claim_id),
+        "Orchard",
+    )?;
+    let verdicts = verify_claim_proof_verdicts(
+        proofs.clone(),
+        verifying_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+        airdrop_configuration_file,
+        ProofFilter::default(),
+    )
+    .await?;
+    ensure_all_valid(&verdicts)?;
+
+    paths::write_complete(&proofs_out, proofs_json).await?;
+    write_sensitive_output(&secrets_out, &secrets_json).await?;
+    info!(
+        sapling_count = proofs.sapling_proofs.len(),
+        orchard_count = proofs.orchard_proofs.len(),
+        failed_count = proofs.failed_claims.len(),
+        proofs = ?proofs_out,
+        "Collected proving job"
+    );
+    Ok(verdicts)
+}
+
+/// Check that every proven claim was submitted, and is proven once.
+fn ensure_claims_submitted(
+    proven: impl IntoIterator<Item = [u8; 32]>,
+    submitted: impl IntoIterator<Item = [u8; 32]>,
+    pool: &str,
+) -> eyre::Result<()> {
+    let submitted: BTreeSet<_> = submitted.into_iter().collect();
+    let mut seen = BTreeSet::new();
+    for claim_id in proven {
+        ensure!(
+            submitted.contains(&claim_id),
+            "The job has a {pool} proof of claim {}, which was not submitted",
+            hex::encode(claim_id)
+        );
+        ensure!(
+            seen.insert(claim_id),
+            "The job has two {pool} proofs of claim {}",
+            hex::encode(claim_id)
+        );
+    }
+    Ok(())
+}
+
+/// Prove the jobs of the queue at `queue_dir` against the airdrop configuration in `config_file`.
+///
+/// The setup files are loaded once. Pending jobs are taken oldest first; the queue is checked
+/// again every `poll_interval`, or only once with `once`. A job whose claims fail to prove moves
+/// to `failed/` and the worker carries on with the next job.
+///
+/// # Errors
+/// Returns an error if the configuration or setup files cannot be loaded, or the queue directory
+/// cannot be read or written.
+#[cfg(feature = "prove")]
+pub async fn run_proving_worker(
+    queue_dir: PathBuf,
+    config_file: PathBuf,
+    sapling_pk_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    poll_interval: Duration,
+    once: bool,
+) -> ZairResult<()> {
+    run_proving_worker_inner(
+        queue_dir,
+        config_file,
+        sapling_pk_file,
+        orchard_params_file,
+        orchard_params_mode,
+        poll_interval,
+        once,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Proving))
+}
+
+#[cfg(feature = "prove")]
+async fn run_proving_worker_inner(
+    queue_dir: PathBuf,
+    config_file: PathBuf,
+    sapling_pk_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    poll_interval: Duration,
+    once: bool,
+) -> eyre::Result<()> {
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&config_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+    let network = configured_network(&airdrop_config).map_err(ZairError::Config)?;
+    for dir in [PENDING_DIR, RUNNING_DIR, DONE_DIR, FAILED_DIR] {
+        let dir = queue_dir.join(dir);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let setup = load_proving_params(
+        &airdrop_config,
+        airdrop_config.sapling.is_some(),
+        airdrop_config.orchard.is_some(),
+        sapling_pk_file,
+        orchard_params_file,
+        orchard_params_mode,
+    )
+    .await?;
+    info!(queue = ?queue_dir, "Proving worker ready");
+
+    loop {
+        for job_id in pending_jobs(&queue_dir).await? {
+            let running = queue_dir.join(RUNNING_DIR).join(&job_id);
+            match tokio::fs::rename(queue_dir.join(PENDING_DIR).join(&job_id), &running).await {
+                Ok(()) => {}
+                // Another worker took the job first.
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e).with_context(|| format!("Failed to take job {job_id}")),
+            }
+            info!(job = job_id, "Proving job");
+
+            let result = prove_job(&running, &airdrop_config, network, &setup).await;
+            if let Err(e) = tokio::fs::remove_file(running.join(JOB_PROVING_KEY_FILE)).await {
+                warn!(job = job_id, error = %e, "Failed to delete the proving key of the job");
+            }
+            let finished = match result {
+                Ok(()) => {
+                    info!(job = job_id, "Proving job done");
+                    DONE_DIR
+                }
+                Err(e) => {
+                    let error = format!("{e:#}");
+                    warn!(job = job_id, error, "Proving job failed");
+                    paths::write_complete(&running.join(JOB_ERROR_FILE), format!("{error}\n"))
+                        .await?;
+                    FAILED_DIR
+                }
+            };
+            let destination = queue_dir.join(finished).join(&job_id);
+            tokio::fs::rename(&running, &destination)
+                .await
+                .with_context(|| format!("Failed to move job {job_id} to {finished}"))?;
+        }
+        if once {
+            return Ok(());
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Identifiers of the pending jobs, oldest first.
+#[cfg(feature = "prove")]
+async fn pending_jobs(queue_dir: &Path) -> eyre::Result<Vec<String>> {
+    let pending = queue_dir.join(PENDING_DIR);
+    let mut entries = tokio::fs::read_dir(&pending)
+        .await
+        .with_context(|| format!("Failed to read {}", pending.display()))?;
+    let mut jobs = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_type().await?.is_dir() {
+            jobs.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    jobs.sort_unstable();
+    Ok(jobs)
+}
+
+/// Prove the claims of the job in `job_dir` and write its proofs and secrets next to them.
+#[cfg(feature = "prove")]
+async fn prove_job(
+    job_dir: &Path,
+    airdrop_config: &AirdropConfiguration,
+    network: Network,
+    setup: &ProvingParams,
+) -> eyre::Result<()> {
+    let inputs: AirdropClaimInputs =
+        serde_json::from_str(&tokio::fs::read_to_string(job_dir.join(JOB_CLAIMS_FILE)).await?)
+            .context("Failed to parse prepared claims JSON")?;
+    let proving_key: ProvingKeyFile =
+        serde_json::from_str(&tokio::fs::read_to_string(job_dir.join(JOB_PROVING_KEY_FILE)).await?)
+            .context("Failed to parse proving key JSON")?;
+    let keys = ProvingKeys::from_key_file(network, &proving_key)?;
+
+    let rng_source: Arc<dyn ProofRngSource> = Arc::new(OsProofRng);
+    let (output, secrets) =
+        prove_claims_inner(inputs, airdrop_config, &keys, setup, &rng_source).await?;
+    paths::write_complete(&job_dir.join(JOB_PROOFS_FILE), to_artifact_json(&output)?).await?;
+    write_sensitive_output(
+        &job_dir.join(JOB_SECRETS_FILE),
+        &to_artifact_json(&secrets)?,
+    )
+    .await?;
+    info!(
+        sapling_count = output.sapling_proofs.len(),
+        orchard_count = output.orchard_proofs.len(),
+        failed_count = output.failed_claims.len(),
+        "Claim proofs written"
+    );
+    output.ensure_any_proven()
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_scan::Network;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn submitted_jobs_wait_in_pending() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let claims = dir.path().join("claims.json");
+        std::fs::write(
+            &claims,
+            r#"{"sapling_claim_input":[],"orchard_claim_input":[]}"#,
+        )
+        .expect("write claims");
+        let proving_key = dir.path().join("proving-key.json");
+        let key = ProvingKeyFile::from_seed(Network::TestNetwork, &[7_u8; 64], 0).expect("keys");
+        std::fs::write(
+            &proving_key,
+            serde_json::to_string(&key).expect("serialize"),
+        )
+        .expect("write key");
+        let queue = dir.path().join("queue");
+
+        let first = submit_proving_job(queue.clone(), claims.clone(), proving_key.clone())
+            .await
+            .expect("submit");
+        let second = submit_proving_job(queue.clone(), claims, proving_key)
+            .await
+            .expect("submit");
+
+        assert_ne!(first, second);
+        let job = queue.join(PENDING_DIR).join(&first);
+        assert!(job.join(JOB_CLAIMS_FILE).is_file());
+        assert!(job.join(JOB_PROVING_KEY_FILE).is_file());
+        assert!(!queue.join(INCOMING_DIR).join(&first).exists());
+
+        let err = finished_proving_job(queue.clone(), first)
+            .await
+            .expect_err("pending job is not finished");
+        assert!(err.to_string().contains("not finished"));
+        let failed = queue.join(FAILED_DIR).join(&second);
+        std::fs::create_dir_all(&failed).expect("create failed dir");
+        std::fs::write(failed.join(JOB_ERROR_FILE), "No notes to prove\n").expect("write error");
+        std::fs::remove_dir_all(queue.join(PENDING_DIR).join(&second)).expect("remove job");
+        let err = finished_proving_job(queue, second)
+            .await
+            .expect_err("failed job");
+        assert!(matches!(err, ZairError::Proving(_)));
+        assert!(err.to_string().contains("No notes to prove"));
+    }
+
+    #[test]
+    fn proofs_must_be_of_submitted_claims() {
+        ensure_claims_submitted([[1; 32]], [[1; 32], [2; 32]], "Sapling").expect("submitted");
+        let err =
+            ensure_claims_submitted([[3; 32]], [[1; 32]], "Sapling").expect_err("not submitted");
+        assert!(err.to_string().contains("not submitted"));
+        assert!(ensure_claims_submitted([[1; 32], [1; 32]], [[1; 32]], "Orchard").is_err());
+    }
+
+    #[tokio::test]
+    async fn job_ids_must_be_plain_names() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let queue = dir.path().join("queue");
+        std::fs::create_dir_all(queue.join(DONE_DIR)).expect("create done dir");
+        std::fs::create_dir_all(dir.path().join("outside")).expect("create outside dir");
+
+        for job_id in ["", ".", "..", "../../outside", "a/b", "/tmp"] {
+            let err = finished_proving_job(queue.clone(), job_id.to_owned())
+                .await
+                .expect_err("not a plain job id");
+            assert!(matches!(err, ZairError::Config(_)), "{job_id:?}: {err}");
+            assert!(err.to_string().contains("Invalid job"), "{job_id:?}: {err}");
+        }
+        ensure_plain_job_id("000001700000-0123456789abcdef").expect("plain job id");
+    }
+}
@@ -0,0 +1,291 @@
+//! Replace the running binary with a signed release.
+//!
+//! A release manifest lists the binary of every supported platform with its SHA-256 digest. The
+//! manifest is signed with the release key: `<manifest>.sig` holds the hex Ed25519 signature over
+//! the exact manifest bytes. The binary for this platform is only swapped in after both the
+//! manifest signature and the binary digest check out.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use eyre::{Context as _, ContextCompat as _, eyre};
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::info;
+
+use super::setup_fetch::fetch_verified_blocking;
+use crate::error::{ZairError, ZairResult};
+
+/// Suffix of the detached manifest signature, appended to the manifest URL.
+const SIGNATURE_SUFFIX: &str = ".sig";
+
+/// Signed list of the binaries of a release.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    /// Version of the release, such as `0.2.0`.
+    pub version: String,
+    /// Binary of every supported platform.
+    pub binaries: Vec<ReleaseBinary>,
+}
+
+/// Binary of a release for one platform.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseBinary {
+    /// Platform as `<arch>-<os>`, such as `x86_64-linux` or `aarch64-macos`.
+    pub target: String,
+    /// URL of the binary, absolute or relative to the manifest.
+    pub url: String,
+    /// SHA-256 digest of the binary.
+    #[serde_as(as = "Hex")]
+    pub sha256: [u8; 32],
+}
+
+/// Result of [`self_update`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfUpdateOutcome {
+    /// The running binary is the latest release.
+    UpToDate {
+        /// Version of the latest release.
+        version: String,
+    },
+    /// A newer release exists and was not installed.
+    Available {
+        /// Version of the newer release.
+        version: String,
+    },
+    /// The running binary was replaced.
+    Updated {
+        /// Version now installed.
+        version: String,
+    },
+}
+
+/// Platform of the running binary, as listed in [`ReleaseBinary::target`].
+#[must_use]
+pub fn current_target() -> String {
+    format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS)
+}
+
+/// Update the running binary from the release manifest at `manifest_url`.
+///
+/// The manifest must be signed with `release_key`. When its version is newer than
+/// `current_version`, the binary for [`current_target`] is downloaded, checked against its
+/// digest and swapped in, unless `check_only` is set.
+///
+/// # Errors
+/// Returns an error if the manifest cannot be fetched, its signature does not verify, it lists no
+/// binary for this platform, or the binary cannot be downloaded, verified or installed.
+pub async fn self_update(
+    manifest_url: String,
+    release_key: [u8; 32],
+    current_version: String,
+    check_only: bool,
+) -> ZairResult<SelfUpdateOutcome> {
+    self_update_inner(manifest_url, release_key, current_version, check_only)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Network))
+}
+
+async fn self_update_inner(
+    manifest_url: String,
+    release_key: [u8; 32],
+    current_version: String,
+    check_only: bool,
+) -> eyre::Result<SelfUpdateOutcome> {
+    tokio::task::spawn_blocking(move || {
+        self_update_blocking(&manifest_url, &release_key, &current_version, check_only)
+    })
+    .await?
+}
+
+fn self_update_blocking(
+    manifest_url: &str,
+    release_key: &[u8; 32],
+    current_version: &str,
+    check_only: bool,
+) -> eyre::Result<SelfUpdateOutcome> {
+    info!(url = manifest_url, "Fetching release manifest");
+    let manifest_bytes = fetch(manifest_url)?;
+    let signature_url = format!("{manifest_url}{SIGNATURE_SUFFIX}");
+    let signature = String::from_utf8(fetch(&signature_url)?)
+        .with_context(|| format!("{signature_url} is not text"))?;
+    let manifest = verified_manifest(&manifest_bytes, signature.trim(), release_key)
+        .map_err(|e| ZairError::Verification(e.wrap_err("Release manifest failed verification")))?;
+
+    if !is_newer(&manifest.version, current_version)? {
+        info!(version = current_version, "Already up to date");
+        return Ok(SelfUpdateOutcome::UpToDate {
+            version: manifest.version,
+        });
+    }
+    if check_only {
+        info!(
+            current = current_version,
+            available = manifest.version,
+            "Update available"
+        );
+        return Ok(SelfUpdateOutcome::Available {
+            version: manifest.version,
+        });
+    }
+
+    let target = current_target();
+    let binary = manifest
+        .binaries
+        .iter()
+        .find(|binary| binary.target == target)
+        .ok_or_else(|| {
+            ZairError::Config(eyre!(
+                "Release {} has no binary for {target}",
+                manifest.version
+            ))
+        })?;
+    let url = resolve_url(manifest_url, &binary.url);
+
+    let exe = std::env::current_exe().context("Failed to locate the running binary")?;
+    let exe = fs::canonicalize(&exe).unwrap_or(exe);
+    let staged = sibling_path(&exe, "update")?;
+    fetch_verified_blocking(&url, binary.sha256, &staged)?;
+    replace_executable(&staged, &exe)?;
+
+    info!(
+        from = current_version,
+        to = manifest.version,
+        binary = ?exe,
+        "Updated"
+    );
+    Ok(SelfUpdateOutcome::Updated {
+        version: manifest.version,
+    })
+}
+
+fn fetch(url: &str) -> eyre::Result<Vec<u8>> {
+    ureq::get(url)
+        .call()
+        .with_context(|| format!("Failed to fetch {url}"))?
+        .into_body()
+        .read_to_vec()
+        .with_context(|| format!("Failed to read {url}"))
+}
+
+/// Parse `manifest` after checking `signature_hex` over its bytes under `release_key`.
+fn verified_manifest(
+    manifest: &[u8],
+    signature_hex: &str,
+    release_key: &[u8; 32],
+) -> eyre::Result<ReleaseManifest> {
+    let key = VerifyingKey::from_bytes(release_key).context("Invalid release key")?;
+    let signature = <[u8; 64]>::try_from(
+        hex::decode(signature_hex)
+            .context("Invalid manifest signature")?
+            .as_slice(),
+    )
+    .map_err(|_| eyre!("Manifest signature must be 64 bytes"))?;
+    key.verify_strict(manifest, &Signature::from_bytes(&signature))
+        .map_err(|_| eyre!("Manifest signature does not verify under the release key"))?;
+    serde_json::from_slice(manifest).context("Failed to parse release manifest")
+}
+
+/// Whether `available` is a later version than `current`.
+fn is_newer(available: &str, current: &str) -> eyre::Result<bool> {
+    Ok(parse_version(available)? > parse_version(current)?)
+}
+
+/// Numeric components of a version like `v0.2.1`, ignoring pre-release and build suffixes.
+fn parse_version(version: &str) -> eyre::Result<Vec<u64>> {
+    let core = version
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default();
+    core.split('.')
+        .map(|part| {
+            part.parse()
+                .with_context(|| format!("Invalid version {version:?}"))
+        })
+        .collect()
+}
+
+fn resolve_url(manifest_url: &str, url: &str) -> String {
+    if url.contains("://") {
+        return url.to_owned();
+    }
+    let base = manifest_url
+        .rsplit_once('/')
+        .map_or(manifest_url, |(base, _)| base);
+    format!("{base}/{url}")
+}
+
+/// `<exe>.<suffix>` next to `exe`, so renames stay on one file system.
+fn sibling_path(exe: &Path, suffix: &str) -> eyre::Result<PathBuf> {
+    let mut name = exe
+        .file_name()
+        .context("The running binary has no file name")?
+        .to_owned();
+    name.push(".");
+    name.push(suffix);
+    Ok(exe.with_file_name(name))
+}
+
+/// Move `staged` over `exe`.
+#[cfg(unix)]
+fn replace_executable(staged: &Path, exe: &Path) -> eyre::Result<()> {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    fs::set_permissions(staged, fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("Failed to make {} executable", staged.display()))?;
+    fs::rename(staged, exe).with_context(|| format!("Failed to replace {}", exe.display()))
+}
+
+/// Move `staged` over `exe`.
+///
+/// A running binary cannot be overwritten on Windows but can be renamed, so it is moved aside
+/// first and removed when possible.
+#[cfg(not(unix))]
+fn replace_executable(staged: &Path, exe: &Path) -> eyre::Result<()> {
+    let old = sibling_path(exe, "old")?;
+    fs::rename(exe, &old).with_context(|| format!("Failed to move {} aside", exe.display()))?;
+    if let Err(e) = fs::rename(staged, exe) {
+        let _ = fs::rename(&old, exe);
+        return Err(e).with_context(|| format!("Failed to replace {}", exe.display()));
+    }
+    if fs::remove_file(&old).is_err() {
+        tracing::warn!(file = ?old, "Remove the previous binary once it exits");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer as _, SigningKey};
+
+    use super::*;
+
+    #[test]
+    fn manifest_must_be_signed_by_the_release_key() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let manifest = br#"{"version":"0.2.0","binaries":[]}"#;
+        let signature = hex::encode(key.sign(manifest).to_bytes());
+        let release_key = key.verifying_key().to_bytes();
+
+        let parsed = verified_manifest(manifest, &signature, &release_key).expect("verifies");
+        assert_eq!(parsed.version, "0.2.0");
+
+        let tampered = br#"{"version":"9.0.0","binaries":[]}"#;
+        assert!(verified_manifest(tampered, &signature, &release_key).is_err());
+        let other_key = SigningKey::from_bytes(&[8; 32]).verifying_key().to_bytes();
+        assert!(verified_manifest(manifest, &signature, &other_key).is_err());
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert!(is_newer("0.10.0", "0.9.1").expect("valid"));
+        assert!(is_newer("v1.0.0", "0.9.9-rc.1").expect("valid"));
+        assert!(!is_newer("0.1.0", "0.1.0").expect("valid"));
+        assert!(is_newer("0.1", "0.x").is_err());
+    }
+}
@@ -4,13 +4,23 @@ use std::path::Path;
 
 use tokio::io::AsyncWriteExt as _;
 
+use crate::{paths, stdio};
+
 /// Write a sensitive output file.
 ///
-/// On Unix this enforces owner-only permissions (`0o600`).
+/// On Unix this enforces owner-only permissions (`0o600`). The file is written through a
+/// `.partial` file, created with those permissions, and renamed into place once complete. A `-`
+/// path writes to stdout, where the receiving process is responsible for keeping the contents
+/// private.
 ///
 /// # Errors
 /// Returns an error if the file cannot be created, written, flushed, or permission-adjusted.
 pub(super) async fn write_sensitive_output(path: &Path, contents: &str) -> eyre::Result<()> {
+    if stdio::is_stdio(path) {
+        return Ok(stdio::write(path, contents).await?);
+    }
+
+    let partial = paths::begin_partial(path);
     #[cfg(unix)]
     let mut file = {
         tokio::fs::OpenOptions::new()
@@ -18,7 +28,7 @@ pub(super) async fn write_sensitive_output(path: &Path, contents: &str) -> eyre:
             .truncate(true)
             .write(true)
             .mode(0o600)
-            .open(path)
+            .open(&partial)
             .await?
     };
 
@@ -27,7 +37,7 @@ pub(super) async fn write_sensitive_output(path: &Path, contents: &str) -> eyre:
         .create(true)
         .truncate(true)
         .write(true)
-        .open(path)
+        .open(&partial)
         .await?;
 
     file.write_all(contents.as_bytes()).await?;
@@ -37,8 +47,9 @@ pub(super) async fn write_sensitive_output(path: &Path, contents: &str) -> eyre:
     {
         use std::os::unix::fs::PermissionsExt as _;
 
-        tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).await?;
+        tokio::fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o600)).await?;
     }
 
+    paths::finish_partial(&partial, path)?;
     Ok(())
 }
@@ -0,0 +1,218 @@
+//! Download published trusted-setup parameter files.
+//!
+//! Claimers do not need to run the (expensive) local setup: the organizer publishes the proving
+//! key / Halo2 params together with their SHA-256 digest, and `setup fetch` downloads them into
+//! the standard params directory. Interrupted downloads are resumed from the `.part` file with an
+//! HTTP range request, and nothing is moved into place unless the digest matches.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use http::{StatusCode, Uri};
+use sha2::{Digest as _, Sha256};
+use tracing::{info, warn};
+
+use crate::error::{ZairError, ZairResult};
+use crate::paths;
+
+/// Suffix of the in-progress download next to the destination file.
+const PARTIAL_SUFFIX: &str = "part";
+
+/// Download `url` into `out_dir` (default: the standard params directory) and verify it against
+/// `sha256`.
+///
+/// An existing destination with the expected digest is reused, and an interrupted download is
+/// resumed when the server supports range requests.
+///
+/// # Errors
+/// Returns an error if the download fails, the digest does not match, or the file cannot be
+/// written.
+pub async fn fetch_setup_file(
+    url: String,
+    sha256: [u8; 32],
+    out_dir: Option<PathBuf>,
+    file_name: Option<String>,
+) -> ZairResult<()> {
+    fetch_setup_file_inner(url, sha256, out_dir, file_name)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Network))
+}
+
+async fn fetch_setup_file_inner(
+    url: String,
+    sha256: [u8; 32],
+    out_dir: Option<PathBuf>,
+    file_name: Option<String>,
+) -> eyre::Result<()> {
+    let out_dir = match out_dir {
+        Some(dir) => dir,
+        None => paths::params_dir()?,
+    };
+    let file_name = match file_name {
+        Some(name) => name,
+        None => file_name_from_url(&url)?,
+    };
+    let destination = out_dir.join(&file_name);
+
+    tokio::task::spawn_blocking(move || {
+        fs::create_dir_all(&out_dir)
+            .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+        fetch_verified_blocking(&url, sha256, &destination)
+    })
+    .await?
+}
+
+/// Download `url` to `destination` unless it already holds a file with the `expected` SHA-256
+/// digest, resuming an interrupted download.
+pub(super) fn fetch_verified_blocking(
+    url: &str,
+    expected: [u8; 32],
+    destination: &Path,
+) -> eyre::Result<()> {
+    if destination.exists() {
+        if sha256_file(destination)? == expected {
+            info!(file = ?destination, "File already present and verified");
+            return Ok(());
+        }
+        warn!(file = ?destination, "Existing file does not match the expected digest, re-downloading");
+    }
+
+    let partial = partial_path(destination);
+    info!(url, file = ?destination, "Downloading file");
+    let actual = download(url, &partial)?;
+    if actual != expected {
+        fs::remove_file(&partial)
+            .with_context(|| format!("Failed to remove {}", partial.display()))?;
+    }
+    ensure!(
+        actual == expected,
+        "Downloaded file digest mismatch: expected sha256 {}, got {}",
+        hex::encode(expected),
+        hex::encode(actual),
+    );
+
+    fs::rename(&partial, destination)
+        .with_context(|| format!("Failed to move download to {}", destination.display()))?;
+    info!(file = ?destination, sha256 = %hex::encode(actual), "File downloaded and verified");
+    Ok(())
+}
+
+/// Download `url` into `partial`, resuming from its current length, and return the SHA-256 digest
+/// of the complete file.
+pub(super) fn download(url: &str, partial: &Path) -> eyre::Result<[u8; 32]> {
+    let offset = fs::metadata(partial).map_or(0, |meta| meta.len());
+    let mut request = ureq::get(url);
+    if offset > 0 {
+        request = request.header("Range", format!("bytes={offset}-"));
+    }
+
+    let response = match request.call() {
+        Ok(response) => response,
+        // The partial file already holds the whole body.
+        Err(ureq::Error::StatusCode(416)) if offset > 0 => return sha256_file(partial),
+        Err(err) => return Err(err).with_context(|| format!("Failed to download {url}")),
+    };
+
+    let resumed = offset > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+    let mut writer = if resumed {
+        info!(offset, "Resuming interrupted download");
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(partial)
+            .with_context(|| format!("Failed to open {}", partial.display()))?;
+        let mut hasher = Sha256::new();
+        io::copy(&mut file, &mut hasher)?;
+        HashingWriter {
+            inner: file,
+            hasher,
+        }
+    } else {
+        if offset > 0 {
+            warn!("Server does not support resuming downloads, starting over");
+        }
+        HashingWriter {
+            inner: File::create(partial)
+                .with_context(|| format!("Failed to create {}", partial.display()))?,
+            hasher: Sha256::new(),
+        }
+    };
+
+    io::copy(&mut response.into_body().into_reader(), &mut writer)
+        .with_context(|| format!("Failed to download {url}"))?;
+    writer.inner.sync_all()?;
+    Ok(writer.hasher.finalize().into())
+}
+
+pub(super) fn sha256_file(path: &Path) -> eyre::Result<[u8; 32]> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(hasher.finalize().into())
+}
+
+pub(super) fn partial_path(destination: &Path) -> PathBuf {
+    let mut name = destination.as_os_str().to_owned();
+    name.push(".");
+    name.push(PARTIAL_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Last non-empty path segment of `url`.
+fn file_name_from_url(url: &str) -> eyre::Result<String> {
+    let uri: Uri = url.parse().with_context(|| format!("Invalid URL: {url}"))?;
+    uri.path()
+        .rsplit('/')
+        .find(|segment| !segment.is_empty())
+        .map(str::to_owned)
+        .with_context(|| format!("Cannot derive a file name from {url}; pass --file-name"))
+}
+
+/// Writer that hashes everything written through it.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write_all(buf)?;
+        self.hasher.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_name_is_last_url_segment() {
+        assert_eq!(
+            file_name_from_url("https://example.com/zair/v1/setup-sapling-pk.params")
+                .expect("file name"),
+            "setup-sapling-pk.params"
+        );
+        assert_eq!(
+            file_name_from_url("https://example.com/params/orchard.bin/?raw=1").expect("file name"),
+            "orchard.bin"
+        );
+        assert!(file_name_from_url("https://example.com/").is_err());
+    }
+
+    #[test]
+    fn partial_path_appends_suffix() {
+        assert_eq!(
+            partial_path(Path::new("/tmp/setup-sapling-pk.params")),
+            PathBuf::from("/tmp/setup-sapling-pk.params.part")
+        );
+    }
+}
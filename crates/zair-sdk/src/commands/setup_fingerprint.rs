@@ -0,0 +1,187 @@
+//! Trusted-setup fingerprints bound into the airdrop configuration.
+//!
+//! A fingerprint is a tagged `BLAKE2b` hash of the canonical serialization of the Sapling
+//! verifying key or the Orchard Halo2 params. Binding it in the configuration makes verification
+//! fail loudly when it is pointed at a different trusted setup.
+
+use std::io::Cursor;
+use std::path::Path;
+
+use bellman::groth16::VerifyingKey;
+use bls12_381::Bls12;
+#[cfg(feature = "prove")]
+use eyre::ContextCompat as _;
+use eyre::{Context as _, ensure};
+use halo2_proofs::poly::commitment::Params;
+use pasta_curves::vesta;
+#[cfg(feature = "prove")]
+use tracing::info;
+use zair_core::base::hash_bytes;
+#[cfg(feature = "prove")]
+use zair_core::schema::config::AirdropConfiguration;
+
+#[cfg(feature = "prove")]
+use crate::build_info::to_artifact_json;
+use crate::paths;
+
+/// Domain tag for Sapling verifying-key fingerprints.
+const SAPLING_VK_FINGERPRINT_TAG: &[u8; 18] = b"ZAIR_SAPLING_VK_V1";
+/// Domain tag for Orchard params fingerprints.
+const ORCHARD_PARAMS_FINGERPRINT_TAG: &[u8; 22] = b"ZAIR_ORCHARD_PARAMS_V1";
+
+fn tagged_hash(tag: &[u8], bytes: &[u8]) -> [u8; 32] {
+    let mut preimage = Vec::with_capacity(tag.len().saturating_add(bytes.len()));
+    preimage.extend_from_slice(tag);
+    preimage.extend_from_slice(bytes);
+    hash_bytes(&preimage)
+}
+
+/// Fingerprint of a Sapling Groth16 verifying key.
+pub(super) fn sapling_vk_fingerprint(vk: &VerifyingKey<Bls12>) -> eyre::Result<[u8; 32]> {
+    let mut bytes = Vec::new();
+    vk.write(&mut bytes)
+        .context("Failed to serialize Sapling verifying key")?;
+    Ok(tagged_hash(SAPLING_VK_FINGERPRINT_TAG, &bytes))
+}
+
+/// Fingerprint of Orchard Halo2 params.
+pub(super) fn orchard_params_fingerprint(params: &Params<vesta::Affine>) -> eyre::Result<[u8; 32]> {
+    let mut bytes = Vec::new();
+    params
+        .write(&mut bytes)
+        .context("Failed to serialize Orchard params")?;
+    Ok(tagged_hash(ORCHARD_PARAMS_FINGERPRINT_TAG, &bytes))
+}
+
+/// Read a Sapling verifying key file and return its fingerprint.
+pub(super) async fn read_sapling_vk_fingerprint(path: &Path) -> eyre::Result<[u8; 32]> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("Failed to read Sapling verifying key {}", path.display()))?;
+    let vk = VerifyingKey::<Bls12>::read(&bytes[..]).context("Failed to read verifying key")?;
+    sapling_vk_fingerprint(&vk)
+}
+
+/// Read an Orchard params file and return its fingerprint.
+pub(super) async fn read_orchard_params_fingerprint(path: &Path) -> eyre::Result<[u8; 32]> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("Failed to read Orchard params {}", path.display()))?;
+    tokio::task::spawn_blocking(move || {
+        let params = Params::<vesta::Affine>::read(&mut Cursor::new(bytes))
+            .context("Failed to read Orchard params")?;
+        orchard_params_fingerprint(&params)
+    })
+    .await?
+}
+
+/// Check a computed fingerprint against the one bound in the configuration, if any.
+pub(super) fn ensure_fingerprint_matches(
+    artifact: &str,
+    expected: Option<[u8; 32]>,
+    actual: [u8; 32],
+) -> eyre::Result<()> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    ensure!(
+        expected == actual,
+        "{artifact} does not match the airdrop configuration (expected fingerprint {}, got {}). Use the trusted setup the configuration was built with.",
+        hex::encode(expected),
+        hex::encode(actual),
+    );
+    Ok(())
+}
+
+/// Trusted-setup artifact bound into an existing configuration.
+#[cfg(feature = "prove")]
+#[derive(Debug, Clone, Copy)]
+pub(super) enum SetupArtifact {
+    /// Sapling Groth16 verifying key.
+    SaplingVerifyingKey,
+    /// Orchard Halo2 params.
+    OrchardParams,
+}
+
+/// Store `fingerprint` in the matching pool of an existing configuration file.
+///
+/// # Errors
+/// Returns an error if the configuration cannot be read or written, or has no matching pool.
+#[cfg(feature = "prove")]
+pub(super) async fn bind_fingerprint(
+    configuration_file: &Path,
+    artifact: SetupArtifact,
+    fingerprint: [u8; 32],
+) -> eyre::Result<()> {
+    let mut config: AirdropConfiguration =
+        serde_json::from_str(&tokio::fs::read_to_string(configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+
+    match artifact {
+        SetupArtifact::SaplingVerifyingKey => {
+            config
+                .sapling
+                .as_mut()
+                .context("Airdrop configuration has no sapling pool to bind the verifying key to")?
+                .verifying_key_fingerprint = Some(fingerprint);
+        }
+        SetupArtifact::OrchardParams => {
+            config
+                .orchard
+                .as_mut()
+                .context("Airdrop configuration has no orchard pool to bind the params to")?
+                .params_fingerprint = Some(fingerprint);
+        }
+    }
+
+    let json = to_artifact_json(&config)?;
+    paths::write_complete(configuration_file, json).await?;
+    info!(
+        file = ?configuration_file,
+        ?artifact,
+        fingerprint = %hex::encode(fingerprint),
+        "Bound trusted setup fingerprint into configuration"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_tags_are_domain_separated() {
+        assert_ne!(
+            tagged_hash(SAPLING_VK_FINGERPRINT_TAG, b"setup"),
+            tagged_hash(ORCHARD_PARAMS_FINGERPRINT_TAG, b"setup")
+        );
+    }
+
+    #[test]
+    fn missing_binding_accepts_any_fingerprint() {
+        ensure_fingerprint_matches("Sapling verifying key", None, [1_u8; 32])
+            .expect("unbound configuration should accept any key");
+    }
+
+    #[test]
+    fn mismatched_fingerprint_is_rejected() {
+        let err = ensure_fingerprint_matches("Sapling verifying key", Some([1_u8; 32]), [2_u8; 32])
+            .expect_err("mismatch should be rejected");
+        assert!(err.to_string().contains("does not match"));
+
+        ensure_fingerprint_matches("Sapling verifying key", Some([1_u8; 32]), [1_u8; 32])
+            .expect("matching fingerprint should be accepted");
+    }
+
+    #[test]
+    fn orchard_params_fingerprint_is_stable() {
+        let params = Params::<vesta::Affine>::new(4);
+        let first = orchard_params_fingerprint(&params).expect("fingerprint");
+        let second = orchard_params_fingerprint(&params).expect("fingerprint");
+        assert_eq!(first, second);
+        assert_ne!(
+            first,
+            orchard_params_fingerprint(&Params::<vesta::Affine>::new(5)).expect("fingerprint")
+        );
+    }
+}
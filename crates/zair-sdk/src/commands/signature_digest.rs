@@ -25,6 +25,7 @@ pub fn hash_orchard_proof(proof: &OrchardClaimProofResult) -> eyre::Result<[u8;
         &proof.rk,
         proof.cv,
         proof.cv_sha256,
+        proof.cv_poseidon,
         proof.airdrop_nullifier,
     )?;
     Ok(digest)
@@ -49,6 +50,7 @@ pub fn hash_orchard_signed_claim_proof(claim: &OrchardSignedClaim) -> eyre::Resu
         &claim.rk,
         claim.cv,
         claim.cv_sha256,
+        claim.cv_poseidon,
         claim.airdrop_nullifier,
     )?;
     Ok(digest)
@@ -64,6 +66,7 @@ mod tests {
     #[test]
     fn sapling_proof_hash_is_deterministic_and_sensitive_to_field_changes() {
         let p0 = SaplingClaimProofResult {
+            claim_id: [0_u8; 32],
             zkproof: [1_u8; 192],
             rk: [2_u8; 32],
             cv: Some([3_u8; 32]),
@@ -71,6 +74,7 @@ mod tests {
             airdrop_nullifier: Nullifier::from([4_u8; 32]),
         };
         let p1 = SaplingClaimProofResult {
+            claim_id: [0_u8; 32],
             zkproof: [9_u8; 192],
             rk: [8_u8; 32],
             cv: None,
@@ -87,17 +91,21 @@ mod tests {
     #[test]
     fn orchard_proof_hash_is_deterministic_and_sensitive_to_length() {
         let p0 = OrchardClaimProofResult {
+            claim_id: [0_u8; 32],
             zkproof: vec![1_u8; 5],
             rk: [2_u8; 32],
             cv: Some([3_u8; 32]),
             cv_sha256: None,
+            cv_poseidon: None,
             airdrop_nullifier: Nullifier::from([4_u8; 32]),
         };
         let p1 = OrchardClaimProofResult {
+            claim_id: [0_u8; 32],
             zkproof: vec![1_u8; 6],
             rk: [2_u8; 32],
             cv: Some([3_u8; 32]),
             cv_sha256: None,
+            cv_poseidon: None,
             airdrop_nullifier: Nullifier::from([4_u8; 32]),
         };
         let h0 = hash_orchard_proof(&p0).expect("hash should succeed");
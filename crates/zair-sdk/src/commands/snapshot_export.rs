@@ -0,0 +1,286 @@
+//! Export snapshot nullifiers to analytics-friendly formats.
+//!
+//! The binary `snapshot-*.bin` files are compact but opaque. The export writes one row per
+//! nullifier with its hex encoding, pool and, when a lightwalletd source or a height-indexed
+//! sidecar is available, the height it was revealed at, so auditors can load the snapshot into
+//! standard data tools.
+
+use std::fs::File;
+use std::io::{BufWriter, Write as _};
+use std::path::{Path, PathBuf};
+
+use eyre::Context as _;
+use tracing::{info, instrument, warn};
+use zair_core::base::{Nullifier, Pool};
+
+use super::airdrop_claim::load_nullifiers_from_file;
+use super::airdrop_configuration::resolve_snapshot_scan_range;
+use super::snapshot_reconcile::{IndexedNullifiers, scan_source};
+use crate::common::{CommonConfig, PoolSelection, resolve_lightwalletd_url};
+use crate::error::{ZairError, ZairResult};
+use crate::paths::{self, indexed_snapshot_path};
+
+/// Output format of [`export_snapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotExportFormat {
+    /// Comma-separated values with a `nullifier,pool,height` header.
+    Csv,
+    /// Apache Parquet with `nullifier`, `pool` and optional `height` columns.
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+/// One exported nullifier.
+struct ExportRow {
+    nullifier: Nullifier,
+    pool: &'static str,
+    height: Option<u64>,
+}
+
+/// Export the nullifiers of the selected snapshot files to `output`.
+///
+/// When `origin_heights` is set, the snapshot range is scanned from its lightwalletd endpoint to
+/// recover the height each nullifier was revealed at. Otherwise heights are taken from the
+/// height-indexed sidecar of a snapshot file when one exists, and left empty when not.
+///
+/// # Errors
+/// Returns an error if a snapshot file cannot be read, the scan fails, or `output` cannot be
+/// written.
+pub async fn export_snapshot(
+    format: SnapshotExportFormat,
+    pool: PoolSelection,
+    sapling_snapshot: PathBuf,
+    orchard_snapshot: PathBuf,
+    origin_heights: Option<CommonConfig>,
+    output: PathBuf,
+) -> ZairResult<()> {
+    export_snapshot_inner(
+        format,
+        pool,
+        sapling_snapshot,
+        orchard_snapshot,
+        origin_heights,
+        output,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Snapshot))
+}
+
+#[instrument(level = "debug", skip_all, fields(?format, ?pool))]
+async fn export_snapshot_inner(
+    format: SnapshotExportFormat,
+    pool: PoolSelection,
+    sapling_snapshot: PathBuf,
+    orchard_snapshot: PathBuf,
+    origin_heights: Option<CommonConfig>,
+    output: PathBuf,
+) -> eyre::Result<()> {
+    let (sapling_heights, orchard_heights) = match origin_heights {
+        Some(config) => {
+            let scan_range =
+                resolve_snapshot_scan_range(config.network, pool, config.snapshot_height)?;
+            let url = resolve_lightwalletd_url(config.network, config.lightwalletd_url.as_deref());
+            let scanned = scan_source(&url, config.backend, "origin height", &scan_range).await?;
+            (Some(scanned.sapling), Some(scanned.orchard))
+        }
+        None => (
+            sidecar_heights(pool.includes_sapling(), &sapling_snapshot).await?,
+            sidecar_heights(pool.includes_orchard(), &orchard_snapshot).await?,
+        ),
+    };
+
+    let mut rows = Vec::new();
+    if pool.includes_sapling() {
+        let nullifiers = load_nullifiers_from_file(&sapling_snapshot).await?;
+        let heights = sapling_heights.as_deref();
+        rows.extend(pool_rows(Pool::Sapling, &nullifiers, heights));
+    }
+    if pool.includes_orchard() {
+        let nullifiers = load_nullifiers_from_file(&orchard_snapshot).await?;
+        let heights = orchard_heights.as_deref();
+        rows.extend(pool_rows(Pool::Orchard, &nullifiers, heights));
+    }
+
+    let count = rows.len();
+    let partial = paths::begin_partial(&output);
+    let path = partial.clone();
+    tokio::task::spawn_blocking(move || match format {
+        SnapshotExportFormat::Csv => write_csv(&path, &rows),
+        #[cfg(feature = "parquet")]
+        SnapshotExportFormat::Parquet => write_parquet(&path, &rows),
+    })
+    .await?
+    .and_then(|()| paths::finish_partial(&partial, &output).map_err(Into::into))
+    .with_context(|| format!("Failed to write {}", output.display()))?;
+
+    info!(file = %output.display(), count, "Snapshot exported");
+    Ok(())
+}
+
+/// Heights from the indexed sidecar of `snapshot`, if the pool is enabled and the sidecar exists.
+async fn sidecar_heights(
+    enabled: bool,
+    snapshot: &Path,
+) -> eyre::Result<Option<IndexedNullifiers>> {
+    let path = indexed_snapshot_path(snapshot);
+    if !enabled || !tokio::fs::try_exists(&path).await? {
+        return Ok(None);
+    }
+    let file = tokio::fs::File::open(&path).await?;
+    let indexed = zair_scan::read_indexed_nullifiers(file)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    info!(file = %path.display(), range = ?indexed.range(), "Using height-indexed nullifiers");
+    Ok(Some(
+        indexed
+            .entries()
+            .iter()
+            .map(|&(nullifier, height)| (nullifier, Some(height)))
+            .collect(),
+    ))
+}
+
+/// Rows of one pool, with heights looked up in the scanned nullifiers when available.
+fn pool_rows(
+    pool: Pool,
+    nullifiers: &[Nullifier],
+    heights: Option<&[(Nullifier, Option<u64>)]>,
+) -> Vec<ExportRow> {
+    let pool_name = match pool {
+        Pool::Sapling => "sapling",
+        Pool::Orchard => "orchard",
+    };
+    let rows: Vec<_> = nullifiers
+        .iter()
+        .map(|&nullifier| ExportRow {
+            nullifier,
+            pool: pool_name,
+            height: heights.and_then(|heights| origin_height(heights, nullifier)),
+        })
+        .collect();
+
+    if heights.is_some() {
+        let unknown = rows.iter().filter(|row| row.height.is_none()).count();
+        if unknown > 0 {
+            warn!(%pool, unknown, "Snapshot nullifiers without a known origin height");
+        }
+    }
+    rows
+}
+
+fn origin_height(heights: &[(Nullifier, Option<u64>)], nullifier: Nullifier) -> Option<u64> {
+    heights
+        .binary_search_by_key(&nullifier, |(nf, _)| *nf)
+        .ok()
+        .and_then(|index| heights.get(index))
+        .and_then(|(_, height)| *height)
+}
+
+fn write_csv(path: &Path, rows: &[ExportRow]) -> eyre::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "nullifier,pool,height")?;
+    for row in rows {
+        match row.height {
+            Some(height) => writeln!(writer, "{},{},{height}", row.nullifier, row.pool)?,
+            None => writeln!(writer, "{},{},", row.nullifier, row.pool)?,
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(path: &Path, rows: &[ExportRow]) -> eyre::Result<()> {
+    use std::sync::Arc;
+
+    use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+
+    /// Rows per row group, bounding the memory of one written batch.
+    const ROW_GROUP_SIZE: usize = 1 << 20;
+
+    let schema = Arc::new(parse_message_type(
+        "message snapshot_nullifier {
+            REQUIRED BYTE_ARRAY nullifier (UTF8);
+            REQUIRED BYTE_ARRAY pool (UTF8);
+            OPTIONAL INT64 height;
+        }",
+    )?);
+    let properties = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, properties)?;
+
+    for batch in rows.chunks(ROW_GROUP_SIZE) {
+        let nullifiers: Vec<ByteArray> = batch
+            .iter()
+            .map(|row| ByteArray::from(row.nullifier.to_string().into_bytes()))
+            .collect();
+        let pools: Vec<ByteArray> = batch.iter().map(|row| ByteArray::from(row.pool)).collect();
+        let heights = batch
+            .iter()
+            .filter_map(|row| row.height)
+            .map(i64::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let height_levels: Vec<i16> = batch
+            .iter()
+            .map(|row| i16::from(row.height.is_some()))
+            .collect();
+
+        let mut row_group = writer.next_row_group()?;
+        for values in [&nullifiers, &pools] {
+            let mut column = row_group
+                .next_column()?
+                .ok_or_else(|| eyre::eyre!("Parquet schema is missing a column"))?;
+            column
+                .typed::<ByteArrayType>()
+                .write_batch(values, None, None)?;
+            column.close()?;
+        }
+        let mut column = row_group
+            .next_column()?
+            .ok_or_else(|| eyre::eyre!("Parquet schema is missing the height column"))?;
+        column
+            .typed::<Int64Type>()
+            .write_batch(&heights, Some(&height_levels), None)?;
+        column.close()?;
+        row_group.close()?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nf(v: u8) -> Nullifier {
+        Nullifier::new([v; 32])
+    }
+
+    #[test]
+    fn rows_carry_heights_found_by_the_scan() {
+        let heights = vec![(nf(1), Some(10)), (nf(3), Some(30))];
+        let rows = pool_rows(Pool::Orchard, &[nf(1), nf(2), nf(3)], Some(&heights));
+        let found: Vec<_> = rows.iter().map(|row| row.height).collect();
+        assert_eq!(found, vec![Some(10), None, Some(30)]);
+        assert!(rows.iter().all(|row| row.pool == "orchard"));
+    }
+
+    #[test]
+    fn csv_has_header_and_empty_unknown_heights() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("snapshot.csv");
+        let heights = vec![(nf(1), Some(10))];
+        let rows = pool_rows(Pool::Sapling, &[nf(1), nf(2)], Some(&heights));
+
+        write_csv(&path, &rows).expect("csv written");
+        let csv = std::fs::read_to_string(&path).expect("csv readable");
+        let expected = format!(
+            "nullifier,pool,height\n{},sapling,10\n{},sapling,\n",
+            nf(1),
+            nf(2)
+        );
+        assert_eq!(csv, expected);
+    }
+}
@@ -0,0 +1,344 @@
+//! Download snapshot and gap-tree files from a `snapshot serve` host or an IPFS gateway.
+//!
+//! The host publishes a manifest with the airdrop configuration it serves and the size and
+//! SHA-256 digest of every file. A claimant only downloads from a host whose configuration equals
+//! their own, and every file is checked against its digest before it is used; `claim prepare`
+//! then checks the tree roots against the configuration as usual.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::{info, warn};
+use zair_core::base::Pool;
+use zair_core::schema::config::AirdropConfiguration;
+
+use super::setup_fetch::{fetch_verified_blocking, sha256_file};
+use crate::error::{ZairError, ZairResult};
+use crate::{paths, stdio};
+
+/// Path of the manifest relative to the host URL.
+pub const SNAPSHOT_MANIFEST_PATH: &str = "manifest.json";
+
+/// A file served by `snapshot serve`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotArtifact {
+    /// Sapling snapshot nullifiers.
+    #[serde(rename = "snapshot-sapling")]
+    SaplingSnapshot,
+    /// Orchard snapshot nullifiers.
+    #[serde(rename = "snapshot-orchard")]
+    OrchardSnapshot,
+    /// Sapling gap tree.
+    #[serde(rename = "gaptree-sapling")]
+    SaplingGapTree,
+    /// Orchard gap tree.
+    #[serde(rename = "gaptree-orchard")]
+    OrchardGapTree,
+}
+
+impl SnapshotArtifact {
+    /// Every artifact, snapshots first.
+    pub const ALL: [Self; 4] = [
+        Self::SaplingSnapshot,
+        Self::OrchardSnapshot,
+        Self::SaplingGapTree,
+        Self::OrchardGapTree,
+    ];
+
+    /// Name of the artifact in the manifest and in its `/files/<name>` URL.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::SaplingSnapshot => "snapshot-sapling",
+            Self::OrchardSnapshot => "snapshot-orchard",
+            Self::SaplingGapTree => "gaptree-sapling",
+            Self::OrchardGapTree => "gaptree-orchard",
+        }
+    }
+
+    /// Pool of the artifact.
+    #[must_use]
+    pub const fn pool(self) -> Pool {
+        match self {
+            Self::SaplingSnapshot | Self::SaplingGapTree => Pool::Sapling,
+            Self::OrchardSnapshot | Self::OrchardGapTree => Pool::Orchard,
+        }
+    }
+
+    /// Whether the artifact is a gap tree, which `claim prepare` can also rebuild.
+    #[must_use]
+    pub const fn is_gap_tree(self) -> bool {
+        matches!(self, Self::SaplingGapTree | Self::OrchardGapTree)
+    }
+
+    /// Whether the artifact's pool is enabled in `config`.
+    pub(super) const fn is_configured(self, config: &AirdropConfiguration) -> bool {
+        match self.pool() {
+            Pool::Sapling => config.sapling.is_some(),
+            Pool::Orchard => config.orchard.is_some(),
+        }
+    }
+
+    /// Default file name, as written by `config build`.
+    pub(super) const fn file_name(self) -> &'static str {
+        match self {
+            Self::SaplingSnapshot => "snapshot-sapling.bin",
+            Self::OrchardSnapshot => "snapshot-orchard.bin",
+            Self::SaplingGapTree => "gaptree-sapling.bin",
+            Self::OrchardGapTree => "gaptree-orchard.bin",
+        }
+    }
+
+    /// Standard directory of the artifact.
+    pub(super) fn dir(self) -> fn() -> eyre::Result<PathBuf> {
+        if self.is_gap_tree() {
+            paths::gap_trees_dir
+        } else {
+            paths::snapshots_dir
+        }
+    }
+}
+
+/// One file listed in a snapshot manifest.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// The served artifact.
+    pub artifact: SnapshotArtifact,
+    /// File size in bytes.
+    pub size: u64,
+    /// SHA-256 digest of the file.
+    #[serde_as(as = "Hex")]
+    pub sha256: [u8; 32],
+}
+
+/// Manifest served at `/manifest.json` by `snapshot serve`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Airdrop configuration the files belong to, including the tree roots.
+    pub config: AirdropConfiguration,
+    /// Served files.
+    pub files: Vec<ManifestFile>,
+}
+
+impl SnapshotManifest {
+    /// Manifest entry of `artifact`, if it is served.
+    #[must_use]
+    pub fn file(&self, artifact: SnapshotArtifact) -> Option<&ManifestFile> {
+        self.files.iter().find(|file| file.artifact == artifact)
+    }
+}
+
+/// Snapshot and gap-tree paths to use after a download.
+#[derive(Debug, Default)]
+pub struct SnapshotFiles {
+    /// Sapling snapshot nullifiers file.
+    pub sapling_snapshot: Option<PathBuf>,
+    /// Orchard snapshot nullifiers file.
+    pub orchard_snapshot: Option<PathBuf>,
+    /// Sapling gap-tree file.
+    pub sapling_gap_tree: Option<PathBuf>,
+    /// Orchard gap-tree file.
+    pub orchard_gap_tree: Option<PathBuf>,
+}
+
+impl SnapshotFiles {
+    pub(super) const fn path_mut(&mut self, artifact: SnapshotArtifact) -> &mut Option<PathBuf> {
+        match artifact {
+            SnapshotArtifact::SaplingSnapshot => &mut self.sapling_snapshot,
+            SnapshotArtifact::OrchardSnapshot => &mut self.orchard_snapshot,
+            SnapshotArtifact::SaplingGapTree => &mut self.sapling_gap_tree,
+            SnapshotArtifact::OrchardGapTree => &mut self.orchard_gap_tree,
+        }
+    }
+}
+
+/// Download the snapshot and gap-tree files of the pools in the configuration from `url`.
+///
+/// `files` holds the destinations; unset ones default to the standard snapshots and gap-trees
+/// directories. Files already present with the published digest are not downloaded again. The
+/// returned paths point at the downloaded files, and keep the given path for gap trees the host
+/// does not serve.
+///
+/// # Errors
+/// Returns an error if the manifest cannot be fetched, the host serves a different configuration
+/// or no snapshot for a configured pool, or a download fails or does not match its digest.
+pub async fn fetch_snapshot_files(
+    url: String,
+    airdrop_configuration_file: PathBuf,
+    files: SnapshotFiles,
+) -> ZairResult<SnapshotFiles> {
+    fetch_snapshot_files_inner(url, airdrop_configuration_file, files)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Network))
+}
+
+async fn fetch_snapshot_files_inner(
+    url: String,
+    airdrop_configuration_file: PathBuf,
+    mut files: SnapshotFiles,
+) -> eyre::Result<SnapshotFiles> {
+    let config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+    let base = url.trim_end_matches('/').to_owned();
+
+    tokio::task::spawn_blocking(move || {
+        let manifest_url = format!("{base}/{SNAPSHOT_MANIFEST_PATH}");
+        info!(url = manifest_url, "Fetching snapshot manifest");
+        let manifest: SnapshotManifest = serde_json::from_str(
+            &ureq::get(&manifest_url)
+                .call()
+                .with_context(|| format!("Failed to fetch {manifest_url}"))?
+                .into_body()
+                .read_to_string()
+                .with_context(|| format!("Failed to read {manifest_url}"))?,
+        )
+        .context("Failed to parse snapshot manifest")?;
+        ensure_same_configuration(&manifest, &config)?;
+
+        for artifact in SnapshotArtifact::ALL {
+            if !artifact.is_configured(&config) {
+                continue;
+            }
+            let Some(file) = manifest.file(artifact) else {
+                ensure!(
+                    artifact.is_gap_tree(),
+                    "Snapshot host does not serve {}",
+                    artifact.name()
+                );
+                continue;
+            };
+
+            let path = files.path_mut(artifact);
+            let destination = path.take().unwrap_or_else(|| {
+                paths::default_output_path(artifact.dir(), artifact.file_name())
+            });
+            create_parent_dir(&destination)?;
+            fetch_verified_blocking(
+                &format!("{base}/files/{}", artifact.name()),
+                file.sha256,
+                &destination,
+            )?;
+            *path = Some(destination);
+        }
+        Ok(files)
+    })
+    .await?
+}
+
+/// Locate and hash the files to publish for the pools of `config`.
+///
+/// Unset paths default to the `config build` outputs. Snapshots are required, gap trees are
+/// skipped when missing. Returns each file's manifest entry with its path.
+pub(super) fn collect_snapshot_files(
+    config: &AirdropConfiguration,
+    mut files: SnapshotFiles,
+) -> eyre::Result<Vec<(ManifestFile, PathBuf)>> {
+    let mut collected = Vec::new();
+    for artifact in SnapshotArtifact::ALL {
+        if !artifact.is_configured(config) {
+            continue;
+        }
+        let path = files
+            .path_mut(artifact)
+            .take()
+            .unwrap_or_else(|| paths::default_input_path(artifact.dir(), artifact.file_name()));
+        if !path.exists() {
+            ensure!(
+                artifact.is_gap_tree(),
+                "Snapshot file {} not found",
+                path.display()
+            );
+            warn!(file = ?path, "Gap-tree file not found, skipping it");
+            continue;
+        }
+        let size = fs::metadata(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+            .len();
+        let sha256 = sha256_file(&path)?;
+        info!(
+            artifact = artifact.name(),
+            file = ?path,
+            size,
+            sha256 = %hex::encode(sha256),
+            "Hashed snapshot file"
+        );
+        collected.push((
+            ManifestFile {
+                artifact,
+                size,
+                sha256,
+            },
+            path,
+        ));
+    }
+    Ok(collected)
+}
+
+/// Check that the host serves the files of `config`.
+fn ensure_same_configuration(
+    manifest: &SnapshotManifest,
+    config: &AirdropConfiguration,
+) -> eyre::Result<()> {
+    ensure!(
+        manifest.config == *config,
+        "Snapshot host serves a different airdrop configuration (network {:?}, snapshot height {})",
+        manifest.config.network,
+        manifest.config.snapshot_height
+    );
+    Ok(())
+}
+
+pub(super) fn create_parent_dir(path: &Path) -> eyre::Result<()> {
+    let dir = path
+        .parent()
+        .with_context(|| format!("{} has no parent directory", path.display()))?;
+    if !dir.as_os_str().is_empty() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::schema::config::AirdropNetwork;
+
+    use super::*;
+
+    #[test]
+    fn manifest_round_trips_with_artifact_names() {
+        let manifest = SnapshotManifest {
+            config: AirdropConfiguration::new(AirdropNetwork::Testnet, 1, None, None),
+            files: vec![ManifestFile {
+                artifact: SnapshotArtifact::SaplingGapTree,
+                size: 3,
+                sha256: [0xab; 32],
+            }],
+        };
+        let json = serde_json::to_value(&manifest).expect("serialize manifest");
+        assert_eq!(json["files"][0]["artifact"], "gaptree-sapling");
+
+        let parsed: SnapshotManifest = serde_json::from_value(json).expect("parse manifest");
+        assert_eq!(parsed.files, manifest.files);
+        assert!(parsed.file(SnapshotArtifact::SaplingGapTree).is_some());
+        assert!(parsed.file(SnapshotArtifact::SaplingSnapshot).is_none());
+    }
+
+    #[test]
+    fn other_configurations_are_refused() {
+        let manifest = SnapshotManifest {
+            config: AirdropConfiguration::new(AirdropNetwork::Testnet, 1, None, None),
+            files: Vec::new(),
+        };
+        let same = AirdropConfiguration::new(AirdropNetwork::Testnet, 1, None, None);
+        let other = AirdropConfiguration::new(AirdropNetwork::Testnet, 2, None, None);
+
+        assert!(ensure_same_configuration(&manifest, &same).is_ok());
+        assert!(ensure_same_configuration(&manifest, &other).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! Publication of snapshot and gap-tree files to IPFS.
+//!
+//! The files are added through the HTTP API of an IPFS node as one directory with the same layout
+//! `snapshot serve` uses: `manifest.json`, `config.json` and `files/<name>`. Any IPFS gateway then
+//! serves the directory at `/ipfs/<cid>`, so `claim prepare` downloads and verifies it exactly like
+//! files from a `snapshot serve` host, without depending on a single organizer-run server.
+
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::PathBuf;
+
+use eyre::{Context as _, ContextCompat as _};
+use serde::Deserialize;
+use sha2::{Digest as _, Sha256};
+use tracing::info;
+use ureq::SendBody;
+use zair_core::schema::config::AirdropConfiguration;
+
+use super::snapshot_fetch::{
+    SNAPSHOT_MANIFEST_PATH, SnapshotFiles, SnapshotManifest, collect_snapshot_files,
+};
+use crate::build_info::to_artifact_json;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Name of the airdrop configuration in the published directory.
+const CONFIG_PATH: &str = "config.json";
+
+/// One line of the IPFS `add` response.
+#[derive(Deserialize)]
+struct AddedEntry {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+/// Publish the snapshot and gap-tree files of the configuration to IPFS through the node API at
+/// `api_url`, and return the CID of the published directory.
+///
+/// `files` holds the file paths; unset ones default to the `config build` outputs. Snapshots are
+/// required for every configured pool, gap trees are published when present. The node pins the
+/// directory; other nodes should pin the CID too, so the files stay available.
+///
+/// # Errors
+/// Returns an error if the configuration is invalid, a snapshot is missing, a file cannot be read,
+/// or the IPFS node rejects the upload.
+pub async fn publish_snapshot_ipfs(
+    api_url: String,
+    airdrop_configuration_file: PathBuf,
+    files: SnapshotFiles,
+) -> ZairResult<String> {
+    publish_snapshot_ipfs_inner(api_url, airdrop_configuration_file, files)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Network))
+}
+
+async fn publish_snapshot_ipfs_inner(
+    api_url: String,
+    airdrop_configuration_file: PathBuf,
+    files: SnapshotFiles,
+) -> eyre::Result<String> {
+    let config_json = stdio::read_to_string(&airdrop_configuration_file).await?;
+    let config: AirdropConfiguration =
+        serde_json::from_str(&config_json).context("Failed to parse airdrop configuration JSON")?;
+    let add_url = format!(
+        "{}/api/v0/add?wrap-with-directory=true&cid-version=1&pin=true",
+        api_url.trim_end_matches('/')
+    );
+
+    let cid = tokio::task::spawn_blocking(move || {
+        let collected = collect_snapshot_files(&config, files)?;
+        let (manifest_files, paths): (Vec<_>, Vec<_>) = collected.into_iter().unzip();
+        let manifest = SnapshotManifest {
+            config,
+            files: manifest_files,
+        };
+        let manifest_json = to_artifact_json(&manifest)?.into_bytes();
+
+        let mut body = Multipart::new(&manifest_json);
+        body.file(SNAPSHOT_MANIFEST_PATH, Cursor::new(manifest_json));
+        body.file(CONFIG_PATH, Cursor::new(config_json.into_bytes()));
+        body.directory("files");
+        for (entry, path) in manifest.files.iter().zip(paths) {
+            let file =
+                File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+            body.file(&format!("files/{}", entry.artifact.name()), file);
+        }
+
+        info!(url = add_url, "Publishing snapshot files to IPFS");
+        let content_type = body.content_type();
+        let mut reader = body.finish();
+        let response = ureq::post(&add_url)
+            .header("Content-Type", content_type)
+            .send(SendBody::from_reader(&mut reader))
+            .with_context(|| format!("Failed to publish to {add_url}"))?
+            .into_body()
+            .read_to_string()
+            .with_context(|| format!("Failed to read the response of {add_url}"))?;
+        directory_cid(&response)
+    })
+    .await??;
+
+    info!(cid, "Published snapshot files to IPFS");
+    Ok(cid)
+}
+
+/// CID of the wrapping directory in the newline-delimited JSON of an IPFS `add` response.
+fn directory_cid(response: &str) -> eyre::Result<String> {
+    let mut cid = None;
+    for line in response.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: AddedEntry =
+            serde_json::from_str(line).context("Failed to parse the IPFS add response")?;
+        if entry.name.is_empty() {
+            cid = Some(entry.hash);
+        }
+    }
+    cid.context("IPFS add response does not list the published directory")
+}
+
+/// Streaming `multipart/form-data` body in the format of the IPFS `add` API.
+struct Multipart {
+    boundary: String,
+    parts: Vec<Box<dyn Read>>,
+}
+
+impl Multipart {
+    /// Start a body whose boundary is derived from `seed`, which should identify the content.
+    fn new(seed: &[u8]) -> Self {
+        Self {
+            boundary: format!("zair-{}", hex::encode(Sha256::digest(seed))),
+            parts: Vec::new(),
+        }
+    }
+
+    fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    fn header(&mut self, path: &str, content_type: &str) {
+        let header = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n\
+             Content-Type: {content_type}\r\n\r\n",
+            self.boundary,
+            path.replace('/', "%2F"),
+        );
+        self.parts.push(Box::new(Cursor::new(header.into_bytes())));
+    }
+
+    /// Add an empty directory at `path`. Directories must be added before their files.
+    fn directory(&mut self, path: &str) {
+        self.header(path, "application/x-directory");
+        self.parts.push(Box::new(Cursor::new(b"\r\n".to_vec())));
+    }
+
+    /// Add a file at `path` with the contents of `reader`.
+    fn file(&mut self, path: &str, reader: impl Read + 'static) {
+        self.header(path, "application/octet-stream");
+        self.parts.push(Box::new(reader));
+        self.parts.push(Box::new(Cursor::new(b"\r\n".to_vec())));
+    }
+
+    /// Close the body and return it as one reader.
+    fn finish(mut self) -> impl Read {
+        let end = format!("--{}--\r\n", self.boundary);
+        self.parts.push(Box::new(Cursor::new(end.into_bytes())));
+        self.parts
+            .into_iter()
+            .fold(Box::new(std::io::empty()) as Box<dyn Read>, |body, part| {
+                Box::new(body.chain(part))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directory_cid_is_the_unnamed_entry() {
+        let response = concat!(
+            "{\"Name\":\"manifest.json\",\"Hash\":\"bafkmanifest\",\"Size\":\"10\"}\n",
+            "{\"Name\":\"files/snapshot-sapling\",\"Hash\":\"bafksapling\",\"Size\":\"64\"}\n",
+            "{\"Name\":\"\",\"Hash\":\"bafydirectory\",\"Size\":\"90\"}\n",
+        );
+        assert_eq!(
+            directory_cid(response).expect("directory CID"),
+            "bafydirectory"
+        );
+        assert!(directory_cid("{\"Name\":\"a\",\"Hash\":\"b\"}").is_err());
+    }
+
+    #[test]
+    fn multipart_body_lists_directories_and_files() {
+        let mut body = Multipart::new(b"seed");
+        body.directory("files");
+        body.file("files/snapshot-sapling", Cursor::new(b"data".to_vec()));
+        let boundary = body.boundary.clone();
+
+        let mut text = String::new();
+        body.finish()
+            .read_to_string(&mut text)
+            .expect("read multipart body");
+
+        assert!(text.contains("filename=\"files\"\r\nContent-Type: application/x-directory"));
+        assert!(text.contains("filename=\"files%2Fsnapshot-sapling\""));
+        assert!(text.contains("\r\n\r\ndata\r\n"));
+        assert!(text.ends_with(&format!("--{boundary}--\r\n")));
+    }
+}
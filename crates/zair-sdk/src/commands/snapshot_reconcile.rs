@@ -0,0 +1,424 @@
+//! Cross-check the snapshot nullifier set against two independent sources.
+//!
+//! A single lightwalletd endpoint is a trust point for the organizer: it could omit or inject
+//! nullifiers and shift the gap roots. Reconciliation rebuilds the set from a second source and
+//! reports every nullifier that only one side knows about, grouped by the height it was revealed
+//! at whenever a source can tell.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::str::FromStr as _;
+
+use eyre::{Context as _, ensure};
+use http::Uri;
+use serde::Serialize;
+use tracing::{info, instrument, warn};
+use zair_core::base::{Nullifier, Pool};
+use zair_core::schema::config::AirdropNetwork;
+use zair_scan::Network;
+use zair_scan::light_walletd::LightWalletd;
+use zair_scan::scanner::BlockNullifiersVisitor;
+
+use super::airdrop_claim::load_nullifiers_from_file;
+use super::airdrop_configuration::resolve_snapshot_scan_range;
+use crate::build_info::to_artifact_json;
+use crate::common::{Backend, PoolSelection, to_airdrop_network};
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Independent source of snapshot nullifiers.
+#[derive(Debug, Clone)]
+pub enum SnapshotSource {
+    /// Scan the snapshot range from a lightwalletd endpoint served by the given indexer.
+    Lightwalletd(String, Backend),
+    /// Read previously exported snapshot nullifier files.
+    Files {
+        /// Sapling snapshot nullifiers file.
+        sapling: PathBuf,
+        /// Orchard snapshot nullifiers file.
+        orchard: PathBuf,
+    },
+}
+
+impl fmt::Display for SnapshotSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lightwalletd(url, backend) => write!(f, "{backend} {url}"),
+            Self::Files { sapling, orchard } => {
+                write!(f, "files {} / {}", sapling.display(), orchard.display())
+            }
+        }
+    }
+}
+
+/// Which reconciliation source is missing a nullifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReconcileSide {
+    /// The primary source.
+    Primary,
+    /// The secondary source.
+    Secondary,
+}
+
+/// A nullifier that is known to only one of the two sources.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NullifierDivergence {
+    /// The diverging nullifier.
+    pub nullifier: Nullifier,
+    /// Height at which the other source saw the nullifier, if it carries heights.
+    pub height: Option<u64>,
+    /// The source that does not contain the nullifier.
+    pub missing_from: ReconcileSide,
+}
+
+/// Reconciliation result for a single pool.
+#[derive(Debug, Serialize)]
+pub struct PoolReconciliation {
+    /// Number of unique nullifiers in the primary source.
+    pub primary_count: usize,
+    /// Number of unique nullifiers in the secondary source.
+    pub secondary_count: usize,
+    /// Sorted, de-duplicated heights that have at least one divergence.
+    pub divergent_heights: Vec<u64>,
+    /// Every diverging nullifier, ordered by height (unknown heights last).
+    pub divergences: Vec<NullifierDivergence>,
+}
+
+/// Report written by `snapshot reconcile`.
+#[derive(Debug, Serialize)]
+pub struct SnapshotReconcileReport {
+    /// Zcash network of the snapshot.
+    pub network: AirdropNetwork,
+    /// Snapshot block height (inclusive).
+    pub snapshot_height: u64,
+    /// Description of the primary source.
+    pub primary: String,
+    /// Description of the secondary source.
+    pub secondary: String,
+    /// Sapling reconciliation. Present when Sapling pool is selected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sapling: Option<PoolReconciliation>,
+    /// Orchard reconciliation. Present when Orchard pool is selected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orchard: Option<PoolReconciliation>,
+}
+
+/// Nullifiers paired with the height they were revealed at, sorted and unique by nullifier.
+pub(super) type IndexedNullifiers = Vec<(Nullifier, Option<u64>)>;
+
+#[derive(Default)]
+pub(super) struct SourceNullifiers {
+    pub(super) sapling: IndexedNullifiers,
+    pub(super) orchard: IndexedNullifiers,
+}
+
+/// Rebuild the snapshot nullifier set from two independent sources and report any divergence.
+///
+/// The report is always written to `report_out`, so organizers can inspect it even when the
+/// sources disagree.
+///
+/// # Errors
+/// Returns an error if a source cannot be read, the report cannot be written, or the sources
+/// diverge for any selected pool.
+pub async fn reconcile_snapshot_sources(
+    network: Network,
+    snapshot_height: u64,
+    pool: PoolSelection,
+    primary: SnapshotSource,
+    secondary: SnapshotSource,
+    report_out: PathBuf,
+) -> ZairResult<()> {
+    reconcile_snapshot_sources_inner(
+        network,
+        snapshot_height,
+        pool,
+        primary,
+        secondary,
+        report_out,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Snapshot))
+}
+
+#[instrument(level = "debug", skip_all, fields(snapshot_height = snapshot_height, ?pool))]
+async fn reconcile_snapshot_sources_inner(
+    network: Network,
+    snapshot_height: u64,
+    pool: PoolSelection,
+    primary: SnapshotSource,
+    secondary: SnapshotSource,
+    report_out: PathBuf,
+) -> eyre::Result<()> {
+    let scan_range = resolve_snapshot_scan_range(network, pool, snapshot_height)?;
+    info!(%primary, %secondary, ?scan_range, "Reconciling snapshot sources");
+
+    let (primary_nullifiers, secondary_nullifiers) = tokio::try_join!(
+        load_source(&primary, "primary", pool, &scan_range),
+        load_source(&secondary, "secondary", pool, &scan_range),
+    )?;
+
+    let sapling = pool.includes_sapling().then(|| {
+        reconcile_pool(
+            Pool::Sapling,
+            &primary_nullifiers.sapling,
+            &secondary_nullifiers.sapling,
+        )
+    });
+    let orchard = pool.includes_orchard().then(|| {
+        reconcile_pool(
+            Pool::Orchard,
+            &primary_nullifiers.orchard,
+            &secondary_nullifiers.orchard,
+        )
+    });
+
+    let divergent = [&sapling, &orchard]
+        .into_iter()
+        .flatten()
+        .map(|reconciliation| reconciliation.divergences.len())
+        .fold(0_usize, usize::saturating_add);
+
+    let report = SnapshotReconcileReport {
+        network: to_airdrop_network(network),
+        snapshot_height,
+        primary: primary.to_string(),
+        secondary: secondary.to_string(),
+        sapling,
+        orchard,
+    };
+    let json = to_artifact_json(&report)?;
+    stdio::write(&report_out, json)
+        .await
+        .with_context(|| format!("Failed to write {}", report_out.display()))?;
+    info!(file = %report_out.display(), "Saved reconciliation report");
+
+    ensure!(
+        divergent == 0,
+        "Snapshot sources diverge on {divergent} nullifier(s); see {}",
+        report_out.display()
+    );
+    info!("Snapshot sources agree");
+    Ok(())
+}
+
+async fn load_source(
+    source: &SnapshotSource,
+    label: &'static str,
+    pool: PoolSelection,
+    scan_range: &RangeInclusive<u64>,
+) -> eyre::Result<SourceNullifiers> {
+    match source {
+        SnapshotSource::Lightwalletd(url, backend) => {
+            scan_source(url, *backend, label, scan_range).await
+        }
+        SnapshotSource::Files { sapling, orchard } => {
+            let mut nullifiers = SourceNullifiers::default();
+            if pool.includes_sapling() {
+                nullifiers.sapling =
+                    without_heights(load_nullifiers_from_file(sapling).await?.iter().copied());
+            }
+            if pool.includes_orchard() {
+                nullifiers.orchard =
+                    without_heights(load_nullifiers_from_file(orchard).await?.iter().copied());
+            }
+            Ok(nullifiers)
+        }
+    }
+}
+
+pub(super) async fn scan_source(
+    url: &str,
+    backend: Backend,
+    label: &'static str,
+    scan_range: &RangeInclusive<u64>,
+) -> eyre::Result<SourceNullifiers> {
+    let uri = Uri::from_str(url).with_context(|| format!("Invalid {label} lightwalletd URL"))?;
+    let lightwalletd = LightWalletd::connect_backend(uri, backend).await?;
+
+    let mut visitor = BlockNullifiersVisitor::default();
+    let mut last_fetch_pct = 0_usize;
+    info!(source = label, progress = "0%", "Fetching nullifiers");
+    lightwalletd
+        .scan_nullifiers_with_progress(
+            &mut visitor,
+            scan_range,
+            |height, scanned, total| {
+                if total == 0 {
+                    return;
+                }
+                #[allow(
+                    clippy::arithmetic_side_effects,
+                    reason = "Fetch progress percentage uses saturating operations and is guarded against total=0"
+                )]
+                let pct = scanned.saturating_mul(100).saturating_div(total);
+                if pct >= last_fetch_pct.saturating_add(10) {
+                    last_fetch_pct = pct;
+                    info!(
+                        source = label,
+                        progress = %format!("{pct}%"),
+                        current_height = height,
+                        "Fetching nullifiers"
+                    );
+                }
+            },
+        )
+        .await
+        .with_context(|| format!("Failed to scan {label} lightwalletd"))?;
+
+    let mut nullifiers = SourceNullifiers::default();
+    for (height, block) in visitor.into_blocks() {
+        nullifiers
+            .sapling
+            .extend(block.sapling.into_iter().map(|nf| (nf, Some(height))));
+        nullifiers
+            .orchard
+            .extend(block.orchard.into_iter().map(|nf| (nf, Some(height))));
+    }
+    normalize(&mut nullifiers.sapling);
+    normalize(&mut nullifiers.orchard);
+    Ok(nullifiers)
+}
+
+fn without_heights(nullifiers: impl IntoIterator<Item = Nullifier>) -> IndexedNullifiers {
+    let mut indexed: IndexedNullifiers = nullifiers.into_iter().map(|nf| (nf, None)).collect();
+    normalize(&mut indexed);
+    indexed
+}
+
+/// Sort by nullifier and keep the earliest height for repeated nullifiers.
+fn normalize(nullifiers: &mut IndexedNullifiers) {
+    nullifiers.sort_unstable();
+    nullifiers.dedup_by_key(|(nf, _)| *nf);
+}
+
+fn reconcile_pool(
+    pool: Pool,
+    primary: &[(Nullifier, Option<u64>)],
+    secondary: &[(Nullifier, Option<u64>)],
+) -> PoolReconciliation {
+    let divergences = diff_sources(primary, secondary);
+    let mut divergent_heights: Vec<u64> = divergences.iter().filter_map(|d| d.height).collect();
+    divergent_heights.dedup();
+
+    if divergences.is_empty() {
+        info!(pool = ?pool, count = primary.len(), "Sources agree");
+    } else {
+        let missing_from_primary = divergences
+            .iter()
+            .filter(|d| d.missing_from == ReconcileSide::Primary)
+            .count();
+        warn!(
+            pool = ?pool,
+            primary_count = primary.len(),
+            secondary_count = secondary.len(),
+            missing_from_primary,
+            missing_from_secondary = divergences.len().saturating_sub(missing_from_primary),
+            first_divergent_height = ?divergent_heights.first(),
+            "Sources diverge"
+        );
+        for height in divergent_heights.iter().take(10) {
+            warn!(pool = ?pool, height, "Divergent block");
+        }
+    }
+
+    PoolReconciliation {
+        primary_count: primary.len(),
+        secondary_count: secondary.len(),
+        divergent_heights,
+        divergences,
+    }
+}
+
+/// Linear merge of two sorted, unique nullifier lists, returning every one-sided entry.
+fn diff_sources(
+    primary: &[(Nullifier, Option<u64>)],
+    secondary: &[(Nullifier, Option<u64>)],
+) -> Vec<NullifierDivergence> {
+    let mut divergences = Vec::new();
+    let mut primary = primary.iter().peekable();
+    let mut secondary = secondary.iter().peekable();
+
+    loop {
+        let (nullifier, height, missing_from) = match (primary.peek(), secondary.peek()) {
+            (None, None) => break,
+            (Some(&&(p, p_height)), Some(&&(s, s_height))) => match p.cmp(&s) {
+                Ordering::Equal => {
+                    primary.next();
+                    secondary.next();
+                    continue;
+                }
+                Ordering::Less => {
+                    primary.next();
+                    (p, p_height, ReconcileSide::Secondary)
+                }
+                Ordering::Greater => {
+                    secondary.next();
+                    (s, s_height, ReconcileSide::Primary)
+                }
+            },
+            (Some(&&(p, p_height)), None) => {
+                primary.next();
+                (p, p_height, ReconcileSide::Secondary)
+            }
+            (None, Some(&&(s, s_height))) => {
+                secondary.next();
+                (s, s_height, ReconcileSide::Primary)
+            }
+        };
+        divergences.push(NullifierDivergence {
+            nullifier,
+            height,
+            missing_from,
+        });
+    }
+
+    divergences.sort_by_key(|d| (d.height.is_none(), d.height));
+    divergences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nf(v: u8) -> Nullifier {
+        Nullifier::from([v; 32])
+    }
+
+    #[test]
+    fn identical_sources_have_no_divergence() {
+        let a = vec![(nf(1), Some(10)), (nf(2), Some(11))];
+        let b = without_heights([nf(2), nf(1), nf(2)]);
+
+        assert!(diff_sources(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn divergences_report_missing_side_and_height() {
+        let mut a = vec![(nf(3), Some(12)), (nf(1), Some(10)), (nf(1), Some(15))];
+        normalize(&mut a);
+        let b = vec![(nf(1), Some(10)), (nf(2), Some(11))];
+
+        let divergences = diff_sources(&a, &b);
+
+        assert_eq!(
+            divergences,
+            vec![
+                NullifierDivergence {
+                    nullifier: nf(2),
+                    height: Some(11),
+                    missing_from: ReconcileSide::Primary,
+                },
+                NullifierDivergence {
+                    nullifier: nf(3),
+                    height: Some(12),
+                    missing_from: ReconcileSide::Secondary,
+                },
+            ]
+        );
+        let report = reconcile_pool(Pool::Sapling, &a, &b);
+        assert_eq!(report.divergent_heights, vec![11, 12]);
+    }
+}
@@ -0,0 +1,266 @@
+//! HTTP hosting of snapshot and gap-tree files.
+//!
+//! `GET /manifest.json` returns the [`SnapshotManifest`]: the airdrop configuration with its tree
+//! roots, and the size and SHA-256 digest of every file. `GET /files/<name>` returns a file, with
+//! its digest as `ETag` and single byte-range requests, so interrupted downloads resume.
+
+use std::io::SeekFrom;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse as _, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use eyre::Context as _;
+use tokio::io::{AsyncReadExt as _, AsyncSeekExt as _};
+use tokio::net::TcpListener;
+use tokio_util::io::ReaderStream;
+use tracing::{info, warn};
+use zair_core::schema::config::AirdropConfiguration;
+
+use super::grpc_ops::grpc_ops_router;
+use super::snapshot_fetch::{
+    SNAPSHOT_MANIFEST_PATH, SnapshotFiles, SnapshotManifest, collect_snapshot_files,
+};
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Service name reported by the gRPC health service.
+const SNAPSHOT_SERVICE: &str = "zair.snapshot";
+
+struct ServerState {
+    manifest: SnapshotManifest,
+    /// Path of each file in `manifest.files`.
+    paths: Vec<PathBuf>,
+}
+
+/// Serve the snapshot and gap-tree files of the configuration over HTTP on `addr` until the process
+/// is stopped.
+///
+/// `files` holds the file paths; unset ones default to the `config build` outputs. Snapshots are
+/// required for every configured pool, gap trees are served when present. Files are hashed at
+/// startup and must not change while they are served.
+///
+/// # Errors
+/// Returns an error if the configuration is invalid, a snapshot is missing, a file cannot be read,
+/// or the address cannot be bound.
+pub async fn serve_snapshot_files(
+    addr: SocketAddr,
+    airdrop_configuration_file: PathBuf,
+    files: SnapshotFiles,
+) -> ZairResult<()> {
+    serve_snapshot_files_inner(addr, airdrop_configuration_file, files)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+async fn serve_snapshot_files_inner(
+    addr: SocketAddr,
+    airdrop_configuration_file: PathBuf,
+    files: SnapshotFiles,
+) -> eyre::Result<()> {
+    let config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+
+    let served = tokio::task::spawn_blocking({
+        let config = config.clone();
+        move || collect_snapshot_files(&config, files)
+    })
+    .await??;
+    let (manifest_files, served_paths) = served.into_iter().unzip();
+
+    let state = Arc::new(ServerState {
+        manifest: SnapshotManifest {
+            config,
+            files: manifest_files,
+        },
+        paths: served_paths,
+    });
+    let router = Router::new()
+        .route(&format!("/{SNAPSHOT_MANIFEST_PATH}"), get(manifest))
+        .route("/files/{name}", get(file))
+        .with_state(state)
+        .merge(grpc_ops_router(SNAPSHOT_SERVICE).await?);
+
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {addr}"))?;
+    info!(%addr, "Serving snapshot files");
+    axum::serve(listener, router)
+        .await
+        .context("Snapshot file service failed")
+}
+
+async fn manifest(State(state): State<Arc<ServerState>>) -> Response {
+    Json(&state.manifest).into_response()
+}
+
+async fn file(
+    State(state): State<Arc<ServerState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let Some((entry, path)) = state
+        .manifest
+        .files
+        .iter()
+        .zip(&state.paths)
+        .find(|(entry, _)| entry.artifact.name() == name)
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let etag = format!("\"{}\"", hex::encode(entry.sha256));
+    let etag_header = HeaderValue::from_str(&etag).expect("Hex ETags are valid header values");
+    let header_is_etag = |name: header::HeaderName| {
+        headers
+            .get(name)
+            .is_some_and(|value| value.as_bytes() == etag.as_bytes())
+    };
+    if header_is_etag(header::IF_NONE_MATCH) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag_header)]).into_response();
+    }
+
+    // A range only applies to the current file version.
+    let range = if headers.contains_key(header::IF_RANGE) && !header_is_etag(header::IF_RANGE) {
+        Ok(None)
+    } else {
+        headers
+            .get(header::RANGE)
+            .and_then(|value| value.to_str().ok())
+            .map_or(Ok(None), |value| parse_range(value, entry.size))
+    };
+    let Ok(range) = range else {
+        return (
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            [(header::CONTENT_RANGE, format!("bytes */{}", entry.size))],
+        )
+            .into_response();
+    };
+
+    let (start, length) = range.map_or((0, entry.size), |(start, end)| {
+        (start, end.saturating_sub(start).saturating_add(1))
+    });
+    let mut file = match tokio::fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) => {
+            warn!(file = ?path, "Failed to open served file: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    if let Err(e) = file.seek(SeekFrom::Start(start)).await {
+        warn!(file = ?path, "Failed to seek served file: {e}");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    let body = Body::from_stream(ReaderStream::new(file.take(length)));
+
+    let mut response = (
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/octet-stream"),
+            ),
+            (header::ACCEPT_RANGES, HeaderValue::from_static("bytes")),
+            (header::ETAG, etag_header),
+            (header::CONTENT_LENGTH, HeaderValue::from(length)),
+        ],
+        body,
+    )
+        .into_response();
+    if let Some((start, end)) = range {
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+        response.headers_mut().insert(
+            header::CONTENT_RANGE,
+            HeaderValue::from_str(&format!("bytes {start}-{end}/{}", entry.size))
+                .expect("Content ranges are valid header values"),
+        );
+    }
+    response
+}
+
+/// A byte range that lies outside the file.
+#[derive(Debug, PartialEq, Eq)]
+struct RangeNotSatisfiable;
+
+/// Parse a `Range` header into the inclusive byte range it selects in a file of `size` bytes.
+///
+/// Headers this service does not support (other units, several ranges, malformed values) yield
+/// `None`, and the whole file is served.
+fn parse_range(value: &str, size: u64) -> Result<Option<(u64, u64)>, RangeNotSatisfiable> {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((first, last)) = spec.split_once('-') else {
+        return Ok(None);
+    };
+    let Some(end_of_file) = size.checked_sub(1) else {
+        return Err(RangeNotSatisfiable);
+    };
+
+    if first.is_empty() {
+        // Suffix range: the last `last` bytes.
+        let Ok(suffix) = last.parse::<u64>() else {
+            return Ok(None);
+        };
+        if suffix == 0 {
+            return Err(RangeNotSatisfiable);
+        }
+        return Ok(Some((size.saturating_sub(suffix), end_of_file)));
+    }
+
+    let Ok(start) = first.parse::<u64>() else {
+        return Ok(None);
+    };
+    let end = if last.is_empty() {
+        end_of_file
+    } else {
+        let Ok(end) = last.parse::<u64>() else {
+            return Ok(None);
+        };
+        if end < start {
+            return Ok(None);
+        }
+        end.min(end_of_file)
+    };
+    if start > end_of_file {
+        return Err(RangeNotSatisfiable);
+    }
+    Ok(Some((start, end)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_ranges_are_clamped_to_the_file() {
+        assert_eq!(parse_range("bytes=0-9", 100), Ok(Some((0, 9))));
+        assert_eq!(parse_range("bytes=90-", 100), Ok(Some((90, 99))));
+        assert_eq!(parse_range("bytes=90-500", 100), Ok(Some((90, 99))));
+        assert_eq!(parse_range("bytes=-10", 100), Ok(Some((90, 99))));
+        assert_eq!(parse_range("bytes=-500", 100), Ok(Some((0, 99))));
+    }
+
+    #[test]
+    fn unsupported_ranges_serve_the_whole_file() {
+        assert_eq!(parse_range("items=0-9", 100), Ok(None));
+        assert_eq!(parse_range("bytes=0-9,20-29", 100), Ok(None));
+        assert_eq!(parse_range("bytes=9-0", 100), Ok(None));
+        assert_eq!(parse_range("bytes=abc", 100), Ok(None));
+    }
+
+    #[test]
+    fn ranges_past_the_end_are_not_satisfiable() {
+        assert_eq!(parse_range("bytes=100-", 100), Err(RangeNotSatisfiable));
+        assert_eq!(parse_range("bytes=-0", 100), Err(RangeNotSatisfiable));
+        assert_eq!(parse_range("bytes=0-", 0), Err(RangeNotSatisfiable));
+    }
+}
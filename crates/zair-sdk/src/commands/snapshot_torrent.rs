@@ -0,0 +1,593 @@
+//! BitTorrent distribution of snapshot and gap-tree files.
+//!
+//! `config build --torrent` writes a single-file `.torrent` next to every snapshot and gap-tree
+//! file, with the organizer's HTTP hosts as web seeds (BEP 19), and logs a magnet link for each.
+//! Any BitTorrent client can fetch and seed the files from the magnet links, which takes load off
+//! the organizer's hosts.
+//!
+//! `claim prepare --snapshot-magnet` fetches the files without a peer-to-peer client: it downloads
+//! the `.torrent` from the magnet's exact source (`xs`), checks it against the magnet's info hash,
+//! and downloads the file from a web seed, verifying every piece.
+
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, Read as _};
+use std::path::{Path, PathBuf};
+
+use eyre::{Context as _, ContextCompat as _, bail, ensure};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+use sha1::{Digest as _, Sha1};
+use tracing::{info, warn};
+
+use super::setup_fetch::{download, partial_path};
+use super::snapshot_fetch::{SnapshotArtifact, SnapshotFiles, create_parent_dir};
+use crate::error::{ZairError, ZairResult};
+use crate::paths;
+
+/// Piece length of generated torrents (4 MiB).
+const PIECE_LENGTH: u64 = 4_194_304;
+
+/// Length of a SHA-1 piece hash.
+const PIECE_HASH_LEN: usize = 20;
+
+/// Nesting limit when decoding a downloaded torrent.
+const MAX_DEPTH: usize = 8;
+
+/// Characters escaped in magnet link parameters.
+const MAGNET_ESCAPE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// A single-file torrent.
+#[derive(Debug, PartialEq, Eq)]
+struct Torrent {
+    name: String,
+    length: u64,
+    piece_length: u64,
+    pieces: Vec<[u8; PIECE_HASH_LEN]>,
+    web_seeds: Vec<String>,
+}
+
+impl Torrent {
+    /// Hash the file at `path` into a torrent named after the file.
+    fn from_file(path: &Path, piece_length: u64, web_seeds: Vec<String>) -> eyre::Result<Self> {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .with_context(|| format!("{} has no UTF-8 file name", path.display()))?
+            .to_owned();
+        let mut file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+
+        let mut pieces = Vec::new();
+        let mut length = 0_u64;
+        loop {
+            let mut hasher = Sha1::new();
+            let read = io::copy(&mut (&mut file).take(piece_length), &mut hasher)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            if read == 0 {
+                break;
+            }
+            pieces.push(hasher.finalize().into());
+            length = length.checked_add(read).context("File too large")?;
+        }
+
+        Ok(Self {
+            name,
+            length,
+            piece_length,
+            pieces,
+            web_seeds,
+        })
+    }
+
+    /// Bencoded info dictionary, whose SHA-1 digest is the info hash.
+    fn info_bytes(&self) -> Vec<u8> {
+        let mut out = vec![b'd'];
+        put_bytes(&mut out, b"length");
+        put_int(&mut out, self.length);
+        put_bytes(&mut out, b"name");
+        put_bytes(&mut out, self.name.as_bytes());
+        put_bytes(&mut out, b"piece length");
+        put_int(&mut out, self.piece_length);
+        put_bytes(&mut out, b"pieces");
+        put_bytes(&mut out, &self.pieces.concat());
+        out.push(b'e');
+        out
+    }
+
+    fn info_hash(&self) -> [u8; PIECE_HASH_LEN] {
+        Sha1::digest(self.info_bytes()).into()
+    }
+
+    /// Bencoded `.torrent` file. No trackers are listed; clients find peers through the DHT and
+    /// download from the web seeds.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![b'd'];
+        put_bytes(&mut out, b"created by");
+        put_bytes(&mut out, b"zair");
+        put_bytes(&mut out, b"info");
+        out.extend_from_slice(&self.info_bytes());
+        if !self.web_seeds.is_empty() {
+            put_bytes(&mut out, b"url-list");
+            out.push(b'l');
+            for seed in &self.web_seeds {
+                put_bytes(&mut out, seed.as_bytes());
+            }
+            out.push(b'e');
+        }
+        out.push(b'e');
+        out
+    }
+
+    /// Parse a `.torrent` file, returning the torrent and the SHA-1 digest of its info dictionary
+    /// as encoded in `bytes`.
+    fn parse(bytes: &[u8]) -> eyre::Result<(Self, [u8; PIECE_HASH_LEN])> {
+        let (value, rest) = decode(bytes, 0)?;
+        ensure!(rest.is_empty(), "Trailing data after torrent");
+        let Value::Dict(entries) = value else {
+            bail!("Torrent is not a dictionary");
+        };
+        let (info, info_raw) = entry(&entries, b"info").context("Torrent has no info")?;
+        let Value::Dict(info) = info else {
+            bail!("Torrent info is not a dictionary");
+        };
+
+        let name = std::str::from_utf8(bytes_entry(info, b"name")?)
+            .context("Torrent name is not UTF-8")?
+            .to_owned();
+        ensure!(
+            !name.is_empty() && !name.contains(['/', '\\']) && name != "..",
+            "Invalid torrent name {name:?}"
+        );
+        let length =
+            int_entry(info, b"length").context("Only single-file torrents are supported")?;
+        let piece_length = int_entry(info, b"piece length")?;
+        ensure!(piece_length > 0, "Torrent piece length is zero");
+        let pieces = bytes_entry(info, b"pieces")?.chunks_exact(PIECE_HASH_LEN);
+        ensure!(
+            pieces.remainder().is_empty(),
+            "Torrent piece hashes are truncated"
+        );
+        let pieces: Vec<[u8; PIECE_HASH_LEN]> = pieces
+            .map(|piece| piece.try_into().expect("chunks have the hash length"))
+            .collect();
+        ensure!(
+            u64::try_from(pieces.len()).ok() == Some(length.div_ceil(piece_length)),
+            "Torrent piece count does not match its length"
+        );
+
+        let web_seeds = match entry(&entries, b"url-list") {
+            None => Vec::new(),
+            Some((Value::Bytes(seed), _)) => vec![utf8(seed)?],
+            Some((Value::List(seeds), _)) => seeds
+                .iter()
+                .map(|seed| match seed {
+                    Value::Bytes(seed) => utf8(seed),
+                    _ => bail!("Invalid torrent web seed"),
+                })
+                .collect::<eyre::Result<_>>()?,
+            Some(_) => bail!("Invalid torrent web seeds"),
+        };
+
+        let torrent = Self {
+            name,
+            length,
+            piece_length,
+            pieces,
+            web_seeds,
+        };
+        Ok((torrent, Sha1::digest(info_raw).into()))
+    }
+
+    /// Magnet link of the torrent, with its web seeds and the `.torrent` next to each of them.
+    fn magnet_link(&self) -> String {
+        let mut link = format!(
+            "magnet:?xt=urn:btih:{}&dn={}&xl={}",
+            hex::encode(self.info_hash()),
+            utf8_percent_encode(&self.name, MAGNET_ESCAPE),
+            self.length
+        );
+        for seed in &self.web_seeds {
+            let file_url = web_seed_url(seed, &self.name);
+            let _ = write!(
+                link,
+                "&ws={}&xs={}",
+                utf8_percent_encode(&file_url, MAGNET_ESCAPE),
+                utf8_percent_encode(&format!("{file_url}.torrent"), MAGNET_ESCAPE)
+            );
+        }
+        link
+    }
+}
+
+/// URL of file `name` on web seed `seed`: a seed ending in `/` is a directory (BEP 19).
+fn web_seed_url(seed: &str, name: &str) -> String {
+    if seed.ends_with('/') {
+        format!("{seed}{name}")
+    } else {
+        seed.to_owned()
+    }
+}
+
+/// The parts of a magnet link used to fetch a file.
+#[derive(Debug, PartialEq, Eq)]
+struct Magnet {
+    info_hash: [u8; PIECE_HASH_LEN],
+    web_seeds: Vec<String>,
+    exact_sources: Vec<String>,
+}
+
+fn parse_magnet(link: &str) -> eyre::Result<Magnet> {
+    let query = link
+        .strip_prefix("magnet:?")
+        .with_context(|| format!("Not a magnet link: {link}"))?;
+    let mut info_hash = None;
+    let mut web_seeds = Vec::new();
+    let mut exact_sources = Vec::new();
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode_str(value)
+            .decode_utf8()
+            .context("Magnet link parameter is not UTF-8")?
+            .into_owned();
+        match key {
+            "xt" => {
+                let hash = value
+                    .strip_prefix("urn:btih:")
+                    .context("Only BitTorrent v1 magnet links are supported")?;
+                let mut decoded = [0_u8; PIECE_HASH_LEN];
+                hex::decode_to_slice(hash, &mut decoded)
+                    .context("Only hex-encoded info hashes are supported")?;
+                info_hash = Some(decoded);
+            }
+            "ws" => web_seeds.push(value),
+            "xs" => exact_sources.push(value),
+            _ => {}
+        }
+    }
+    Ok(Magnet {
+        info_hash: info_hash.context("Magnet link has no info hash")?,
+        web_seeds,
+        exact_sources,
+    })
+}
+
+/// Write `<file>.torrent` next to `file` with the given web seeds, and return its magnet link.
+pub(super) fn write_torrent(file: &Path, web_seeds: &[String]) -> eyre::Result<String> {
+    let torrent = Torrent::from_file(file, PIECE_LENGTH, web_seeds.to_vec())?;
+    let mut torrent_file = file.as_os_str().to_owned();
+    torrent_file.push(".torrent");
+    let torrent_file = PathBuf::from(torrent_file);
+    paths::write_complete_blocking(&torrent_file, torrent.to_bytes())
+        .with_context(|| format!("Failed to write {}", torrent_file.display()))?;
+
+    let magnet = torrent.magnet_link();
+    info!(file = %torrent_file.display(), magnet, "Saved torrent");
+    Ok(magnet)
+}
+
+/// Download the snapshot and gap-tree files named by `magnets` from their web seeds.
+///
+/// Each magnet link needs an exact source (`xs`) for its `.torrent` file, as written by
+/// `config build --torrent`, and the torrent's file name selects the snapshot or gap tree it
+/// holds. `files` holds the destinations; unset ones default to the standard snapshots and
+/// gap-trees directories, and files already present with matching pieces are not downloaded
+/// again. The returned paths point at the downloaded files.
+///
+/// # Errors
+/// Returns an error if a magnet link or torrent is invalid or names an unknown file, no source
+/// can be downloaded, or a download does not match the torrent's piece hashes.
+pub async fn fetch_snapshot_magnets(
+    magnets: Vec<String>,
+    files: SnapshotFiles,
+) -> ZairResult<SnapshotFiles> {
+    fetch_snapshot_magnets_inner(magnets, files)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Network))
+}
+
+async fn fetch_snapshot_magnets_inner(
+    magnets: Vec<String>,
+    files: SnapshotFiles,
+) -> eyre::Result<SnapshotFiles> {
+    tokio::task::spawn_blocking(move || fetch_snapshot_magnets_blocking(&magnets, files)).await?
+}
+
+fn fetch_snapshot_magnets_blocking(
+    magnets: &[String],
+    mut files: SnapshotFiles,
+) -> eyre::Result<SnapshotFiles> {
+    for link in magnets {
+        let magnet = parse_magnet(link)?;
+        let torrent = fetch_torrent(&magnet)?;
+        let artifact = SnapshotArtifact::ALL
+            .into_iter()
+            .find(|artifact| artifact.file_name() == torrent.name)
+            .with_context(|| {
+                format!(
+                    "Torrent file {} is not a snapshot or gap-tree file",
+                    torrent.name
+                )
+            })?;
+
+        let path = files.path_mut(artifact);
+        let destination = path
+            .take()
+            .unwrap_or_else(|| paths::default_output_path(artifact.dir(), artifact.file_name()));
+        create_parent_dir(&destination)?;
+        fetch_from_web_seeds(&magnet, &torrent, &destination)?;
+        *path = Some(destination);
+    }
+    Ok(files)
+}
+
+/// Download the `.torrent` of `magnet` from its exact sources and check its info hash.
+fn fetch_torrent(magnet: &Magnet) -> eyre::Result<Torrent> {
+    ensure!(
+        !magnet.exact_sources.is_empty(),
+        "Magnet link has no exact source (xs) for its .torrent file; use a BitTorrent client"
+    );
+    for source in &magnet.exact_sources {
+        let bytes = match ureq::get(source)
+            .call()
+            .and_then(|response| response.into_body().read_to_vec())
+        {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(url = source, "Failed to download torrent: {e}");
+                continue;
+            }
+        };
+        let (torrent, info_hash) =
+            Torrent::parse(&bytes).with_context(|| format!("Invalid torrent at {source}"))?;
+        ensure!(
+            info_hash == magnet.info_hash,
+            "Torrent at {source} does not match the magnet link's info hash"
+        );
+        return Ok(torrent);
+    }
+    bail!("No .torrent source of the magnet link could be downloaded")
+}
+
+/// Download the file of `torrent` to `destination` from the first web seed that works, unless
+/// `destination` already holds it.
+fn fetch_from_web_seeds(
+    magnet: &Magnet,
+    torrent: &Torrent,
+    destination: &Path,
+) -> eyre::Result<()> {
+    if destination.exists() {
+        if verify_pieces(destination, torrent).is_ok() {
+            info!(file = ?destination, "File already present and verified");
+            return Ok(());
+        }
+        warn!(file = ?destination, "Existing file does not match the torrent, re-downloading");
+    }
+
+    let partial = partial_path(destination);
+    for seed in magnet.web_seeds.iter().chain(&torrent.web_seeds) {
+        let url = web_seed_url(seed, &torrent.name);
+        info!(url, file = ?destination, "Downloading file");
+        if let Err(e) = download(&url, &partial) {
+            warn!(url, "Web seed failed: {e:#}");
+            continue;
+        }
+        if let Err(e) = verify_pieces(&partial, torrent) {
+            fs::remove_file(&partial)
+                .with_context(|| format!("Failed to remove {}", partial.display()))?;
+            return Err(e.wrap_err(format!("Download from {url} does not match the torrent")));
+        }
+        fs::rename(&partial, destination)
+            .with_context(|| format!("Failed to move download to {}", destination.display()))?;
+        info!(file = ?destination, "File downloaded and verified");
+        return Ok(());
+    }
+    bail!("No web seed of {} could be downloaded", torrent.name)
+}
+
+/// Check the file at `path` against the length and piece hashes of `torrent`.
+fn verify_pieces(path: &Path, torrent: &Torrent) -> eyre::Result<()> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let length = file.metadata()?.len();
+    ensure!(
+        length == torrent.length,
+        "{} has {length} bytes, the torrent {}",
+        path.display(),
+        torrent.length
+    );
+    for (index, expected) in torrent.pieces.iter().enumerate() {
+        let mut hasher = Sha1::new();
+        io::copy(&mut (&mut file).take(torrent.piece_length), &mut hasher)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        ensure!(
+            hasher.finalize().as_slice() == expected,
+            "Piece {index} of {} does not match the torrent",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+fn put_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(bytes.len().to_string().as_bytes());
+    out.push(b':');
+    out.extend_from_slice(bytes);
+}
+
+fn put_int(out: &mut Vec<u8>, value: u64) {
+    out.push(b'i');
+    out.extend_from_slice(value.to_string().as_bytes());
+    out.push(b'e');
+}
+
+/// A decoded bencode value, borrowing from the input.
+enum Value<'a> {
+    Int(u64),
+    Bytes(&'a [u8]),
+    List(Vec<Value<'a>>),
+    /// Entries with their raw encoded value.
+    Dict(Vec<(&'a [u8], Value<'a>, &'a [u8])>),
+}
+
+/// Decode one value from the start of `input` and return it with the remaining input.
+fn decode(input: &[u8], depth: usize) -> eyre::Result<(Value<'_>, &[u8])> {
+    ensure!(depth < MAX_DEPTH, "Torrent is nested too deeply");
+    let (&tag, rest) = input.split_first().context("Truncated torrent")?;
+    match tag {
+        b'i' => {
+            let (digits, rest) = split_at_byte(rest, b'e')?;
+            Ok((Value::Int(parse_decimal(digits)?), rest))
+        }
+        b'l' => {
+            let mut items = Vec::new();
+            let mut rest = rest;
+            loop {
+                if let Some(rest) = rest.strip_prefix(b"e") {
+                    return Ok((Value::List(items), rest));
+                }
+                let (item, next) = decode(rest, depth.saturating_add(1))?;
+                items.push(item);
+                rest = next;
+            }
+        }
+        b'd' => {
+            let mut entries = Vec::new();
+            let mut rest = rest;
+            loop {
+                if let Some(rest) = rest.strip_prefix(b"e") {
+                    return Ok((Value::Dict(entries), rest));
+                }
+                let (Value::Bytes(key), after_key) = decode(rest, depth.saturating_add(1))? else {
+                    bail!("Torrent dictionary key is not a string");
+                };
+                let (value, next) = decode(after_key, depth.saturating_add(1))?;
+                let (raw, _) = after_key.split_at(after_key.len().saturating_sub(next.len()));
+                entries.push((key, value, raw));
+                rest = next;
+            }
+        }
+        b'0'..=b'9' => {
+            let (len, rest) = split_at_byte(input, b':')?;
+            let len = usize::try_from(parse_decimal(len)?)?;
+            ensure!(len <= rest.len(), "Truncated torrent");
+            let (bytes, rest) = rest.split_at(len);
+            Ok((Value::Bytes(bytes), rest))
+        }
+        _ => bail!("Invalid torrent encoding"),
+    }
+}
+
+/// Split `input` before the first `separator`, dropping the separator.
+fn split_at_byte(input: &[u8], separator: u8) -> eyre::Result<(&[u8], &[u8])> {
+    let position = input
+        .iter()
+        .position(|&byte| byte == separator)
+        .context("Truncated torrent")?;
+    let (head, tail) = input.split_at(position);
+    Ok((head, tail.get(1..).unwrap_or_default()))
+}
+
+fn parse_decimal(digits: &[u8]) -> eyre::Result<u64> {
+    ensure!(!digits.is_empty(), "Empty number in torrent");
+    digits.iter().try_fold(0_u64, |value, &digit| {
+        ensure!(digit.is_ascii_digit(), "Invalid number in torrent");
+        value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(u64::from(digit.saturating_sub(b'0'))))
+            .context("Number in torrent is too large")
+    })
+}
+
+fn entry<'v, 'a>(
+    entries: &'v [(&'a [u8], Value<'a>, &'a [u8])],
+    key: &[u8],
+) -> Option<(&'v Value<'a>, &'a [u8])> {
+    entries
+        .iter()
+        .find(|(name, _, _)| *name == key)
+        .map(|(_, value, raw)| (value, *raw))
+}
+
+fn int_entry(entries: &[(&[u8], Value<'_>, &[u8])], key: &[u8]) -> eyre::Result<u64> {
+    match entry(entries, key) {
+        Some((Value::Int(value), _)) => Ok(*value),
+        _ => bail!("Torrent has no {}", String::from_utf8_lossy(key)),
+    }
+}
+
+fn bytes_entry<'a>(
+    entries: &[(&'a [u8], Value<'a>, &'a [u8])],
+    key: &[u8],
+) -> eyre::Result<&'a [u8]> {
+    match entry(entries, key) {
+        Some((Value::Bytes(value), _)) => Ok(value),
+        _ => bail!("Torrent has no {}", String::from_utf8_lossy(key)),
+    }
+}
+
+fn utf8(bytes: &[u8]) -> eyre::Result<String> {
+    Ok(std::str::from_utf8(bytes)
+        .context("Torrent string is not UTF-8")?
+        .to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent_of(contents: &[u8], piece_length: u64) -> (tempfile::TempDir, PathBuf, Torrent) {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let path = dir.path().join("snapshot-sapling.bin");
+        fs::write(&path, contents).expect("write file");
+        let torrent = Torrent::from_file(
+            &path,
+            piece_length,
+            vec!["https://seed.example/zair/".to_owned()],
+        )
+        .expect("hash file");
+        (dir, path, torrent)
+    }
+
+    #[test]
+    fn torrents_round_trip_with_their_info_hash() {
+        let (_dir, path, torrent) = torrent_of(&[7_u8; 10], 4);
+        assert_eq!(torrent.length, 10);
+        assert_eq!(torrent.pieces.len(), 3);
+
+        let (parsed, info_hash) = Torrent::parse(&torrent.to_bytes()).expect("parse torrent");
+        assert_eq!(parsed, torrent);
+        assert_eq!(info_hash, torrent.info_hash());
+        verify_pieces(&path, &parsed).expect("file matches its torrent");
+
+        fs::write(&path, [7_u8; 9]).expect("truncate file");
+        assert!(verify_pieces(&path, &parsed).is_err());
+    }
+
+    #[test]
+    fn magnet_links_carry_the_web_seed_sources() {
+        let (_dir, _path, torrent) = torrent_of(b"nullifiers", 4);
+        let magnet = parse_magnet(&torrent.magnet_link()).expect("parse magnet");
+
+        assert_eq!(magnet.info_hash, torrent.info_hash());
+        assert_eq!(
+            magnet.web_seeds,
+            vec!["https://seed.example/zair/snapshot-sapling.bin"]
+        );
+        assert_eq!(
+            magnet.exact_sources,
+            vec!["https://seed.example/zair/snapshot-sapling.bin.torrent"]
+        );
+    }
+
+    #[test]
+    fn malformed_torrents_are_rejected() {
+        assert!(Torrent::parse(b"d4:infod").is_err());
+        assert!(Torrent::parse(&[b'l'; 64]).is_err());
+        assert!(Torrent::parse(b"d4:infodee").is_err());
+        assert!(Torrent::parse(b"i99999999999999999999999e").is_err());
+    }
+}
@@ -0,0 +1,268 @@
+//! Watch the chain for spends of claimed notes.
+//!
+//! Prepared claims record the chain nullifier of each claimed note. `zair watch` scans every block
+//! mined after the snapshot for those nullifiers, so a user notices when a wallet spends a note
+//! whose claim is still pending. Claims prepared before the nullifier was recorded are skipped;
+//! prepare them again to watch them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::str::FromStr as _;
+use std::time::Duration;
+
+use eyre::{Context as _, ContextCompat as _, eyre};
+use http::Uri;
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::{info, warn};
+use zair_core::base::{Nullifier, Pool};
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::proof_inputs::AirdropClaimInputs;
+use zair_scan::light_walletd::LightWalletd;
+use zair_scan::scanner::{BlockNullifiers, BlockNullifiersVisitor};
+
+use super::webhook::post_event;
+use crate::common::{configured_network, resolve_lightwalletd_url};
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Event name of the webhook notification sent when claimed notes are spent.
+const SPEND_EVENT: &str = "claimed_notes_spent";
+
+/// A claimed note whose nullifier was revealed on chain.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendAlert {
+    /// Pool of the note.
+    pub pool: Pool,
+    /// Identifier of the claim for the note.
+    #[serde_as(as = "Hex")]
+    pub claim_id: [u8; 32],
+    /// Chain nullifier of the note.
+    pub nullifier: Nullifier,
+    /// Height of the block that spent the note.
+    pub height: u64,
+}
+
+/// Webhook payload listing the spent notes.
+#[derive(Debug, Serialize)]
+struct SpendEvent<'a> {
+    event: &'static str,
+    alerts: &'a [SpendAlert],
+}
+
+/// Watch the blocks after the snapshot for spends of the notes claimed in `claims_file`.
+///
+/// Scanning starts at `from_height`, or the block after the snapshot height of `config_file`,
+/// and polls the chain tip every `poll_interval`. It returns once a claimed note is spent, after
+/// POSTing the alerts to `webhook_url` if one is set. With `once`, it returns after scanning up to
+/// the current tip, with no alerts if no note was spent.
+///
+/// # Errors
+/// Returns an error if the inputs cannot be read, no claim records its chain nullifier, or the
+/// chain cannot be scanned.
+pub async fn watch_claimed_spends(
+    config_file: PathBuf,
+    claims_file: PathBuf,
+    lightwalletd_url: Option<String>,
+    from_height: Option<u64>,
+    poll_interval: Duration,
+    webhook_url: Option<String>,
+    once: bool,
+) -> ZairResult<Vec<SpendAlert>> {
+    watch_claimed_spends_inner(
+        config_file,
+        claims_file,
+        lightwalletd_url,
+        from_height,
+        poll_interval,
+        webhook_url,
+        once,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Network))
+}
+
+async fn watch_claimed_spends_inner(
+    config_file: PathBuf,
+    claims_file: PathBuf,
+    lightwalletd_url: Option<String>,
+    from_height: Option<u64>,
+    poll_interval: Duration,
+    webhook_url: Option<String>,
+    once: bool,
+) -> eyre::Result<Vec<SpendAlert>> {
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_slice(&stdio::read(&config_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+    let claims: AirdropClaimInputs =
+        serde_json::from_str(&stdio::read_to_string(&claims_file).await?)
+            .context("Failed to parse prepared claims JSON")?;
+    let watched = watched_nullifiers(&claims);
+    if watched.is_empty() {
+        return Err(ZairError::Config(eyre!(
+            "No claim in {} records its chain nullifier; prepare the claims again to watch them",
+            claims_file.display()
+        ))
+        .into());
+    }
+
+    let network = configured_network(&airdrop_config).map_err(ZairError::Config)?;
+    let lightwalletd_url = resolve_lightwalletd_url(network, lightwalletd_url.as_deref());
+    let lightwalletd_url =
+        Uri::from_str(&lightwalletd_url).context("lightwalletd URL is required")?;
+    let lightwalletd = LightWalletd::connect(lightwalletd_url).await?;
+
+    let mut next_height = match from_height {
+        Some(height) => height,
+        None => airdrop_config
+            .snapshot_height
+            .checked_add(1)
+            .context("Snapshot height overflow")?,
+    };
+    info!(
+        notes = watched.len(),
+        from_height = next_height,
+        "Watching for spends of claimed notes"
+    );
+
+    loop {
+        let tip = lightwalletd.latest_height().await?;
+        if tip >= next_height {
+            let mut visitor = BlockNullifiersVisitor::default();
+            lightwalletd
+                .scan_nullifiers(&mut visitor, &(next_height..=tip))
+                .await?;
+            let alerts = spend_alerts(&watched, &visitor.into_blocks());
+            info!(height = tip, "Scanned blocks for spends of claimed notes");
+            next_height = tip.checked_add(1).context("Block height overflow")?;
+
+            if !alerts.is_empty() {
+                for alert in &alerts {
+                    warn!(
+                        pool = %alert.pool,
+                        claim_id = hex::encode(alert.claim_id),
+                        height = alert.height,
+                        "Claimed note was spent"
+                    );
+                }
+                if let Some(url) = &webhook_url {
+                    let event = SpendEvent {
+                        event: SPEND_EVENT,
+                        alerts: &alerts,
+                    };
+                    if let Err(e) = post_event(url, &event).await {
+                        warn!(error = %e, "Failed to send the spend alert webhook");
+                    }
+                }
+                return Ok(alerts);
+            }
+        }
+        if once {
+            return Ok(Vec::new());
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Claim identifiers of the claimed notes by pool and chain nullifier.
+fn watched_nullifiers(claims: &AirdropClaimInputs) -> HashMap<(Pool, Nullifier), [u8; 32]> {
+    let sapling = claims
+        .sapling_claim_input
+        .iter()
+        .map(|claim| (Pool::Sapling, claim.nullifier, claim.claim_id));
+    let orchard = claims
+        .orchard_claim_input
+        .iter()
+        .map(|claim| (Pool::Orchard, claim.nullifier, claim.claim_id));
+    let mut missing = 0_usize;
+    let watched = sapling
+        .chain(orchard)
+        .filter_map(|(pool, nullifier, claim_id)| {
+            if nullifier.is_none() {
+                missing = missing.saturating_add(1);
+            }
+            nullifier.map(|nullifier| ((pool, nullifier), claim_id))
+        })
+        .collect();
+    if missing > 0 {
+        warn!(
+            claims = missing,
+            "Claims without a chain nullifier are not watched; prepare them again to watch them"
+        );
+    }
+    watched
+}
+
+/// Alerts for the watched nullifiers revealed in the scanned blocks.
+fn spend_alerts(
+    watched: &HashMap<(Pool, Nullifier), [u8; 32]>,
+    blocks: &BTreeMap<u64, BlockNullifiers>,
+) -> Vec<SpendAlert> {
+    let mut alerts = Vec::new();
+    for (&height, block) in blocks {
+        let revealed = block
+            .sapling
+            .iter()
+            .map(|nullifier| (Pool::Sapling, *nullifier))
+            .chain(
+                block
+                    .orchard
+                    .iter()
+                    .map(|nullifier| (Pool::Orchard, *nullifier)),
+            );
+        for (pool, nullifier) in revealed {
+            if let Some(claim_id) = watched.get(&(pool, nullifier)) {
+                alerts.push(SpendAlert {
+                    pool,
+                    claim_id: *claim_id,
+                    nullifier,
+                    height,
+                });
+            }
+        }
+    }
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_watched_nullifiers_raise_alerts() {
+        let spent = Nullifier::from(&[1; 32]);
+        let watched = HashMap::from([
+            ((Pool::Sapling, spent), [0xaa; 32]),
+            ((Pool::Orchard, Nullifier::from(&[2; 32])), [0xbb; 32]),
+        ]);
+        let blocks = BTreeMap::from([
+            (
+                101,
+                BlockNullifiers {
+                    sapling: vec![Nullifier::from(&[3; 32])],
+                    // The same bytes in the other pool are a different note.
+                    orchard: vec![spent],
+                },
+            ),
+            (
+                102,
+                BlockNullifiers {
+                    sapling: vec![spent],
+                    orchard: vec![],
+                },
+            ),
+        ]);
+
+        assert_eq!(
+            spend_alerts(&watched, &blocks),
+            vec![SpendAlert {
+                pool: Pool::Sapling,
+                claim_id: [0xaa; 32],
+                nullifier: spent,
+                height: 102,
+            }]
+        );
+    }
+}
@@ -5,11 +5,10 @@ use group::ff::PrimeField as _;
 use orchard::keys::SpendAuthorizingKey;
 use orchard::primitives::redpallas::{SpendAuth, VerificationKey};
 use pasta_curves::pallas;
-use zcash_keys::keys::UnifiedSpendingKey;
-use zcash_protocol::consensus::Network;
-use zip32::AccountId;
+use zair_scan::Network;
 
 use super::super::claim_proofs::{OrchardClaimProofResult, OrchardClaimSecretResult};
+use crate::seed::AccountSecret;
 
 /// Seed-derived Orchard spend-authorizing key.
 pub struct OrchardSpendAuthKey {
@@ -19,14 +18,10 @@ pub struct OrchardSpendAuthKey {
 /// Derive the Orchard spend-authorizing key for an account.
 pub fn derive_spend_auth_key(
     network: Network,
-    seed: &[u8; 64],
+    account_secret: &AccountSecret,
     account_id: u32,
 ) -> eyre::Result<OrchardSpendAuthKey> {
-    let account_id =
-        AccountId::try_from(account_id).map_err(|_| eyre::eyre!("Invalid account-id"))?;
-
-    let usk = UnifiedSpendingKey::from_seed(&network, seed, account_id)
-        .map_err(|e| eyre::eyre!("Failed to derive spending key: {e:?}"))?;
+    let usk = account_secret.spending_key(network, account_id)?;
 
     Ok(OrchardSpendAuthKey {
         key: SpendAuthorizingKey::from(usk.orchard()),
@@ -45,16 +40,26 @@ pub fn sign_claim(
         "Proof/secret mismatch: airdrop nullifier differs"
     );
 
-    let alpha = pallas::Scalar::from_repr(secret.alpha)
+    sign_randomized(&proof.rk, &secret.alpha, key, digest)
+}
+
+/// Sign a digest with the spend-auth key randomized by `alpha` whose public key is `rk`.
+pub fn sign_randomized(
+    rk: &[u8; 32],
+    alpha: &[u8; 32],
+    key: &OrchardSpendAuthKey,
+    digest: &[u8; 32],
+) -> eyre::Result<[u8; 64]> {
+    let alpha = pallas::Scalar::from_repr(*alpha)
         .into_option()
-        .context("Invalid Orchard alpha in secrets file")?;
+        .context("Invalid Orchard alpha")?;
 
     let signing_key = key.key.randomize(&alpha);
     let verifying_key = VerificationKey::<SpendAuth>::from(&signing_key);
     let rk_bytes: [u8; 32] = (&verifying_key).into();
 
     ensure!(
-        rk_bytes == proof.rk,
+        rk_bytes == *rk,
         "Cannot match Orchard proof rk to a seed-derived Orchard spend key"
     );
 
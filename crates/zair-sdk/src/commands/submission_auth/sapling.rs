@@ -2,11 +2,10 @@
 
 use eyre::{ContextCompat as _, ensure};
 use jubjub::Fr;
-use zcash_keys::keys::UnifiedSpendingKey;
-use zcash_protocol::consensus::Network;
-use zip32::AccountId;
+use zair_scan::Network;
 
 use super::super::claim_proofs::{SaplingClaimProofResult, SaplingClaimSecretResult};
+use crate::seed::AccountSecret;
 
 /// Seed-derived Sapling spend-auth keys for both scopes.
 pub struct SaplingSpendAuthKeys {
@@ -17,14 +16,10 @@ pub struct SaplingSpendAuthKeys {
 /// Derive Sapling spend-authorizing keys for external and internal scopes.
 pub fn derive_spend_auth_keys(
     network: Network,
-    seed: &[u8; 64],
+    account_secret: &AccountSecret,
     account_id: u32,
 ) -> eyre::Result<SaplingSpendAuthKeys> {
-    let account_id =
-        AccountId::try_from(account_id).map_err(|_| eyre::eyre!("Invalid account-id"))?;
-
-    let usk = UnifiedSpendingKey::from_seed(&network, seed, account_id)
-        .map_err(|e| eyre::eyre!("Failed to derive spending key: {e:?}"))?;
+    let usk = account_secret.spending_key(network, account_id)?;
 
     let extsk = usk.sapling();
     Ok(SaplingSpendAuthKeys {
@@ -45,23 +40,33 @@ pub fn sign_claim(
         "Proof/secret mismatch: airdrop nullifier differs"
     );
 
-    let alpha = Fr::from_bytes(&secret.alpha)
+    sign_randomized(&proof.rk, &secret.alpha, keys, digest)
+}
+
+/// Sign a digest with the spend-auth key randomized by `alpha` whose public key is `rk`.
+pub fn sign_randomized(
+    rk: &[u8; 32],
+    alpha: &[u8; 32],
+    keys: &SaplingSpendAuthKeys,
+    digest: &[u8; 32],
+) -> eyre::Result<[u8; 64]> {
+    let alpha = Fr::from_bytes(alpha)
         .into_option()
-        .context("Invalid alpha in secrets file")?;
+        .context("Invalid Sapling alpha")?;
 
     let mut matched_signing_key: Option<redjubjub::SigningKey<redjubjub::SpendAuth>> = None;
 
     let external_signing_key = keys.external.randomize(&alpha);
     let external_rk_bytes: [u8; 32] =
         redjubjub::VerificationKey::from(&external_signing_key).into();
-    if external_rk_bytes == proof.rk {
+    if external_rk_bytes == *rk {
         matched_signing_key = Some(external_signing_key);
     }
 
     let internal_signing_key = keys.internal.randomize(&alpha);
     let internal_rk_bytes: [u8; 32] =
         redjubjub::VerificationKey::from(&internal_signing_key).into();
-    if internal_rk_bytes == proof.rk && matched_signing_key.is_none() {
+    if internal_rk_bytes == *rk && matched_signing_key.is_none() {
         matched_signing_key = Some(internal_signing_key);
     }
 
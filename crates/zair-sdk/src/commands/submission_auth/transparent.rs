@@ -0,0 +1,77 @@
+//! Transparent address key derivation/signature helpers.
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use secp256k1::ecdsa::Signature;
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use zair_scan::Network;
+use zcash_keys::encoding::AddressCodec as _;
+use zcash_transparent::address::TransparentAddress;
+use zcash_transparent::keys::NonHardenedChildIndex;
+
+use crate::seed::AccountSecret;
+
+/// Key of one external transparent address of an account.
+pub struct TransparentAddressKey {
+    /// Encoded P2PKH address of the key.
+    pub address: String,
+    /// Compressed public key, which the address hashes.
+    pub public_key: [u8; 33],
+    secret_key: SecretKey,
+}
+
+/// Derive the keys of the first `address_count` external addresses of an account, at the BIP 44
+/// paths `m/44'/<coin_type>'/<account>'/0/<index>`.
+pub fn derive_address_keys(
+    network: Network,
+    account_secret: &AccountSecret,
+    account_id: u32,
+    address_count: u32,
+) -> eyre::Result<Vec<TransparentAddressKey>> {
+    let usk = account_secret.spending_key(network, account_id)?;
+    let secp = Secp256k1::signing_only();
+    (0..address_count)
+        .map(|index| {
+            let child = NonHardenedChildIndex::from_index(index)
+                .with_context(|| format!("Transparent address index {index} is hardened"))?;
+            let secret_key = usk
+                .transparent()
+                .derive_external_secret_key(child)
+                .map_err(|e| eyre::eyre!("Failed to derive transparent key {index}: {e}"))?;
+            let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+            Ok(TransparentAddressKey {
+                address: TransparentAddress::from_pubkey(&public_key).encode(&network),
+                public_key: public_key.serialize(),
+                secret_key,
+            })
+        })
+        .collect()
+}
+
+/// Sign a digest with the key of a transparent address, as a compact ECDSA signature.
+#[must_use]
+pub fn sign(key: &TransparentAddressKey, digest: &[u8; 32]) -> [u8; 64] {
+    Secp256k1::signing_only()
+        .sign_ecdsa(&Message::from_digest(*digest), &key.secret_key)
+        .serialize_compact()
+}
+
+/// Check that `public_key` is the key of the P2PKH `address` and signed `digest`.
+pub fn verify_signature(
+    network: Network,
+    address: &str,
+    public_key: &[u8; 33],
+    signature: &[u8; 64],
+    digest: &[u8; 32],
+) -> eyre::Result<()> {
+    let decoded = TransparentAddress::decode(&network, address)
+        .map_err(|e| eyre::eyre!("Invalid transparent address {address}: {e}"))?;
+    let public_key = PublicKey::from_slice(public_key).context("Invalid secp256k1 public key")?;
+    ensure!(
+        TransparentAddress::from_pubkey(&public_key) == decoded,
+        "Public key does not hash to {address}"
+    );
+    let signature = Signature::from_compact(signature).context("Invalid ECDSA signature")?;
+    Secp256k1::verification_only()
+        .verify_ecdsa(&Message::from_digest(*digest), &signature, &public_key)
+        .context("ECDSA signature does not verify")
+}
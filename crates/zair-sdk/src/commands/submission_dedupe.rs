@@ -0,0 +1,263 @@
+//! Duplicate-claim detection across signed claim submissions.
+//!
+//! An airdrop nullifier can only be claimed once, but nothing stops a claimant from sending the
+//! same claim in several submissions, each with a fresh nonce. The target chain rejects the
+//! repeats, and intake services can flag them earlier by scanning the submissions they received.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use eyre::{Context as _, ensure};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::{info, warn};
+use zair_core::base::{Nullifier, Pool};
+use zair_core::schema::submission::ClaimSubmission;
+
+use crate::build_info::to_artifact_json;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Location of one claim within the scanned submissions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClaimOccurrence {
+    /// Submission file containing the claim.
+    pub file: PathBuf,
+    /// Index of the claim within its pool in that submission.
+    pub index: usize,
+}
+
+/// An airdrop nullifier claimed more than once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateClaim {
+    /// Pool of the claims.
+    pub pool: Pool,
+    /// The repeated airdrop nullifier.
+    pub airdrop_nullifier: Nullifier,
+    /// Every claim of the nullifier, in scan order.
+    pub occurrences: Vec<ClaimOccurrence>,
+}
+
+/// Report written by `verify dedupe`.
+#[derive(Debug, Serialize)]
+pub struct DedupeReport {
+    /// Number of submission files scanned.
+    pub submission_count: usize,
+    /// Number of claims across all submissions.
+    pub claim_count: usize,
+    /// Airdrop nullifiers claimed more than once, Sapling first and sorted by nullifier.
+    pub duplicates: Vec<DuplicateClaim>,
+}
+
+/// Scan a directory of signed submissions for airdrop nullifiers that are claimed more than once.
+///
+/// Every `.json` file in `submissions_dir` must be a claim submission. The report is written to
+/// `report_out` when given, also when duplicates are found.
+///
+/// # Errors
+/// Returns an error if a submission cannot be read or parsed, the report cannot be written, or
+/// any airdrop nullifier is claimed more than once.
+pub async fn dedupe_claim_submissions(
+    submissions_dir: PathBuf,
+    report_out: Option<PathBuf>,
+) -> ZairResult<()> {
+    dedupe_claim_submissions_inner(submissions_dir, report_out)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+async fn dedupe_claim_submissions_inner(
+    submissions_dir: PathBuf,
+    report_out: Option<PathBuf>,
+) -> eyre::Result<()> {
+    info!(dir = ?submissions_dir, "Scanning claim submissions...");
+    let submissions = load_submissions(&submissions_dir).await?;
+    let report = find_duplicate_claims(&submissions);
+
+    for duplicate in &report.duplicates {
+        warn!(
+            pool = %duplicate.pool,
+            airdrop_nullifier = %duplicate.airdrop_nullifier,
+            occurrences = duplicate.occurrences.len(),
+            "Duplicate claim"
+        );
+    }
+
+    if let Some(report_out) = &report_out {
+        let json = to_artifact_json(&report)?;
+        stdio::write(report_out, json)
+            .await
+            .with_context(|| format!("Failed to write {}", report_out.display()))?;
+        info!(file = %report_out.display(), "Saved dedupe report");
+    }
+
+    ensure!(
+        report.duplicates.is_empty(),
+        "{} airdrop nullifier(s) are claimed more than once",
+        report.duplicates.len()
+    );
+    info!(
+        submissions = report.submission_count,
+        claims = report.claim_count,
+        "No duplicate claims"
+    );
+    Ok(())
+}
+
+/// Read every `.json` file of `dir` as a claim submission, ordered by file name.
+pub(super) async fn load_submissions(dir: &Path) -> eyre::Result<Vec<(PathBuf, ClaimSubmission)>> {
+    load_json_files(dir, "submission").await
+}
+
+/// Read every `.json` file of `dir` as a `what`, ordered by file name.
+pub(super) async fn load_json_files<T: DeserializeOwned>(
+    dir: &Path,
+    what: &str,
+) -> eyre::Result<Vec<(PathBuf, T)>> {
+    let mut entries = tokio::fs::read_dir(dir)
+        .await
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?;
+    let mut files = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "json") && entry.file_type().await?.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    let mut values = Vec::with_capacity(files.len());
+    for file in files {
+        let value = serde_json::from_str(&stdio::read_to_string(&file).await?)
+            .with_context(|| format!("Failed to parse {what} {}", file.display()))?;
+        values.push((file, value));
+    }
+    Ok(values)
+}
+
+/// Find the airdrop nullifiers that are claimed more than once across `submissions`.
+///
+/// Repeats within a single submission are reported as well.
+#[must_use]
+pub fn find_duplicate_claims(submissions: &[(PathBuf, ClaimSubmission)]) -> DedupeReport {
+    let mut sapling: BTreeMap<Nullifier, Vec<ClaimOccurrence>> = BTreeMap::new();
+    let mut orchard: BTreeMap<Nullifier, Vec<ClaimOccurrence>> = BTreeMap::new();
+    let mut claim_count = 0_usize;
+
+    for (file, submission) in submissions {
+        for (index, entry) in submission.sapling.iter().enumerate() {
+            sapling
+                .entry(entry.airdrop_nullifier)
+                .or_default()
+                .push(ClaimOccurrence {
+                    file: file.clone(),
+                    index,
+                });
+        }
+        for (index, entry) in submission.orchard.iter().enumerate() {
+            orchard
+                .entry(entry.airdrop_nullifier)
+                .or_default()
+                .push(ClaimOccurrence {
+                    file: file.clone(),
+                    index,
+                });
+        }
+        claim_count = claim_count
+            .saturating_add(submission.sapling.len())
+            .saturating_add(submission.orchard.len());
+    }
+
+    let duplicates = [(Pool::Sapling, sapling), (Pool::Orchard, orchard)]
+        .into_iter()
+        .flat_map(|(pool, claims)| {
+            claims
+                .into_iter()
+                .filter(|(_, occurrences)| occurrences.len() > 1)
+                .map(move |(airdrop_nullifier, occurrences)| DuplicateClaim {
+                    pool,
+                    airdrop_nullifier,
+                    occurrences,
+                })
+        })
+        .collect();
+
+    DedupeReport {
+        submission_count: submissions.len(),
+        claim_count,
+        duplicates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::schema::submission::SaplingSignedClaim;
+
+    use super::*;
+
+    fn submission(nullifiers: &[u8]) -> ClaimSubmission {
+        ClaimSubmission {
+            nonce: [1_u8; 32],
+            expiry_height: 100,
+            sapling: nullifiers
+                .iter()
+                .map(|&v| SaplingSignedClaim {
+                    claim_id: [0_u8; 32],
+                    zkproof: [11_u8; 192],
+                    rk: [22_u8; 32],
+                    cv: Some([33_u8; 32]),
+                    cv_sha256: None,
+                    airdrop_nullifier: Nullifier::from([v; 32]),
+                    proof_hash: [55_u8; 32],
+                    message_hash: [66_u8; 32],
+                    spend_auth_sig: [0_u8; 64],
+                    countersignatures: Vec::new(),
+                })
+                .collect(),
+            orchard: vec![],
+            epoch: None,
+        }
+    }
+
+    #[test]
+    fn repeated_nullifiers_are_reported_with_every_occurrence() {
+        let submissions = vec![
+            (PathBuf::from("a.json"), submission(&[1, 2])),
+            (PathBuf::from("b.json"), submission(&[3, 2, 3])),
+        ];
+
+        let report = find_duplicate_claims(&submissions);
+
+        assert_eq!(report.submission_count, 2);
+        assert_eq!(report.claim_count, 5);
+        let occurrence = |file: &str, index| ClaimOccurrence {
+            file: PathBuf::from(file),
+            index,
+        };
+        assert_eq!(
+            report.duplicates,
+            vec![
+                DuplicateClaim {
+                    pool: Pool::Sapling,
+                    airdrop_nullifier: Nullifier::from([2; 32]),
+                    occurrences: vec![occurrence("a.json", 1), occurrence("b.json", 1)],
+                },
+                DuplicateClaim {
+                    pool: Pool::Sapling,
+                    airdrop_nullifier: Nullifier::from([3; 32]),
+                    occurrences: vec![occurrence("b.json", 0), occurrence("b.json", 2)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn distinct_nullifiers_have_no_duplicates() {
+        let submissions = vec![
+            (PathBuf::from("a.json"), submission(&[1])),
+            (PathBuf::from("b.json"), submission(&[2])),
+        ];
+
+        assert!(find_duplicate_claims(&submissions).duplicates.is_empty());
+    }
+}
@@ -7,6 +7,8 @@ use eyre::{Context as _, ensure};
 use serde::{Deserialize, Serialize};
 use zair_core::base::{Nullifier, hash_message};
 
+use crate::stdio;
+
 /// One per-claim message-file assignment.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaimMessageAssignment {
@@ -36,6 +38,20 @@ pub struct ResolvedMessageHashes {
 }
 
 impl ResolvedMessageHashes {
+    /// Build from already-hashed shared and per-claim messages.
+    #[must_use]
+    pub const fn new(
+        shared: Option<[u8; 32]>,
+        sapling: BTreeMap<Nullifier, [u8; 32]>,
+        orchard: BTreeMap<Nullifier, [u8; 32]>,
+    ) -> Self {
+        Self {
+            shared,
+            sapling,
+            orchard,
+        }
+    }
+
     /// Resolve Sapling message hash for a given nullifier.
     #[must_use]
     pub fn sapling_hash(&self, nullifier: Nullifier) -> Option<[u8; 32]> {
@@ -61,7 +77,7 @@ async fn load_assignment_hashes(
 ) -> eyre::Result<BTreeMap<Nullifier, [u8; 32]>> {
     let mut by_nullifier = BTreeMap::new();
     for assignment in assignments {
-        let message_bytes = tokio::fs::read(&assignment.message_file)
+        let message_bytes = stdio::read(&assignment.message_file)
             .await
             .with_context(|| {
                 format!(
@@ -91,7 +107,7 @@ pub async fn resolve_message_hashes(
     messages_file: Option<&PathBuf>,
 ) -> eyre::Result<ResolvedMessageHashes> {
     let shared = if let Some(path) = shared_message_file {
-        let bytes = tokio::fs::read(path)
+        let bytes = stdio::read(path)
             .await
             .with_context(|| format!("Failed to read shared message file at {}", path.display()))?;
         Some(hash_message(&bytes))
@@ -106,15 +122,13 @@ pub async fn resolve_message_hashes(
         });
     };
 
-    let payload: ClaimMessagesFile = serde_json::from_str(
-        &tokio::fs::read_to_string(messages_file).await?,
-    )
-    .with_context(|| {
-        format!(
-            "Failed to parse claim messages JSON from {}",
-            messages_file.display()
-        )
-    })?;
+    let payload: ClaimMessagesFile =
+        serde_json::from_str(&stdio::read_to_string(messages_file).await?).with_context(|| {
+            format!(
+                "Failed to parse claim messages JSON from {}",
+                messages_file.display()
+            )
+        })?;
 
     let (sapling, orchard) = tokio::try_join!(
         load_assignment_hashes(payload.sapling, "Sapling"),
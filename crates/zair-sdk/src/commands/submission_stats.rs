@@ -0,0 +1,402 @@
+//! Claim statistics across signed claim submissions.
+//!
+//! Organizers summarize the submissions they received: the claims of each pool and how many of
+//! their proofs verify. Every value commitment scheme hides the value of a single claim, so values
+//! are only known for the claims covered by a claimed value proof (`claim total`) that a claimant
+//! published with their submission.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use eyre::{Context as _, ensure};
+use serde::Serialize;
+use tracing::{info, warn};
+use zair_core::base::{Nullifier, Pool};
+use zair_core::schema::submission::ClaimSubmission;
+
+use super::claim_proofs::{
+    ClaimProofsOutput, ProofFilter, ProofVerdict, verify_claim_proof_verdicts,
+};
+use super::claimed_value::{ClaimedValueProof, ensure_claimed_value};
+use super::orchard_params::OrchardParamsMode;
+use super::submission_dedupe::{load_json_files, load_submissions};
+use crate::build_info::to_artifact_json;
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Upper bounds of the value buckets in zatoshis, from 0.001 ZEC to 100 ZEC.
+const VALUE_BUCKET_BOUNDS: [u64; 6] = [
+    100_000,
+    1_000_000,
+    10_000_000,
+    100_000_000,
+    1_000_000_000,
+    10_000_000_000,
+];
+
+/// Claims of one pool across the scanned submissions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PoolStats {
+    /// Pool of the claims.
+    pub pool: Pool,
+    /// Number of claims, repeats included.
+    pub claims: usize,
+    /// Number of distinct airdrop nullifiers.
+    pub unique_claims: usize,
+    /// Number of claims whose proof verified.
+    pub valid_proofs: usize,
+    /// Number of claims whose proof did not verify.
+    pub invalid_proofs: usize,
+}
+
+/// Number of claimed value proofs whose total lies in `[min_value, max_value)` zatoshis.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValueBucket {
+    /// Lowest total in the bucket.
+    pub min_value: u64,
+    /// Exclusive upper bound of the bucket; `None` for the last bucket.
+    pub max_value: Option<u64>,
+    /// Number of proofs in the bucket.
+    pub count: usize,
+}
+
+/// Claimed values, from the claimed value proofs published with the submissions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValueDistribution {
+    /// Number of claimed value proofs that open the commitments of a scanned submission.
+    pub proofs: usize,
+    /// Number of claimed value proofs that do not, or that count claims of another proof.
+    pub rejected_proofs: usize,
+    /// Number of claims covered by the accepted proofs.
+    pub claims: usize,
+    /// Total value of the accepted proofs in zatoshis.
+    pub total_value: u64,
+    /// Accepted proofs by total value, in decades from below 0.001 ZEC to 100 ZEC and above.
+    pub buckets: Vec<ValueBucket>,
+}
+
+/// Report written by `stats`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubmissionStats {
+    /// Number of submission files scanned.
+    pub submission_count: usize,
+    /// Claims per pool, Sapling first.
+    pub pools: Vec<PoolStats>,
+    /// Claimed values, when a directory of claimed value proofs was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub values: Option<ValueDistribution>,
+}
+
+/// Summarize a directory of signed submissions: claim counts and proof verification results per
+/// pool, and the distribution of claimed values proven by the claimed value proofs in
+/// `totals_dir`.
+///
+/// Every `.json` file in `submissions_dir` must be a claim submission, and every `.json` file in
+/// `totals_dir` a claimed value proof. Invalid proofs are counted, not reported as an error. The
+/// report is written to `stats_out` when given.
+///
+/// # Errors
+/// Returns an error if a file cannot be read or parsed, the directory holds no submissions, the
+/// setup artifacts cannot be loaded, or the report cannot be written.
+pub async fn submission_statistics(
+    submissions_dir: PathBuf,
+    totals_dir: Option<PathBuf>,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    airdrop_configuration_file: PathBuf,
+    stats_out: Option<PathBuf>,
+) -> ZairResult<SubmissionStats> {
+    submission_statistics_inner(
+        submissions_dir,
+        totals_dir,
+        verifying_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+        airdrop_configuration_file,
+        stats_out,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+async fn submission_statistics_inner(
+    submissions_dir: PathBuf,
+    totals_dir: Option<PathBuf>,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    airdrop_configuration_file: PathBuf,
+    stats_out: Option<PathBuf>,
+) -> eyre::Result<SubmissionStats> {
+    info!(dir = ?submissions_dir, "Scanning claim submissions...");
+    let submissions = load_submissions(&submissions_dir).await?;
+    ensure!(
+        !submissions.is_empty(),
+        "No submissions found in {}",
+        submissions_dir.display()
+    );
+
+    // Submissions of the same epoch are verified together, so the setup is loaded once per epoch.
+    let mut epochs: BTreeMap<Option<u32>, ClaimProofsOutput> = BTreeMap::new();
+    for (_, submission) in &submissions {
+        let proofs = ClaimProofsOutput::from(submission);
+        let merged = epochs
+            .entry(submission.epoch)
+            .or_insert_with(|| ClaimProofsOutput {
+                sapling_proofs: Vec::new(),
+                orchard_proofs: Vec::new(),
+                failed_claims: Vec::new(),
+                epoch: submission.epoch,
+            });
+        merged.sapling_proofs.extend(proofs.sapling_proofs);
+        merged.orchard_proofs.extend(proofs.orchard_proofs);
+    }
+    let mut verdicts = Vec::new();
+    for (epoch, proofs) in epochs {
+        if proofs.sapling_proofs.is_empty() && proofs.orchard_proofs.is_empty() {
+            continue;
+        }
+        info!(?epoch, "Verifying claim proofs...");
+        verdicts.extend(
+            verify_claim_proof_verdicts(
+                proofs,
+                verifying_key_file.clone(),
+                orchard_params_file.clone(),
+                orchard_params_mode,
+                airdrop_configuration_file.clone(),
+                ProofFilter::default(),
+            )
+            .await?,
+        );
+    }
+
+    let values = match &totals_dir {
+        Some(dir) => Some(value_distribution(dir, &submissions).await?),
+        None => None,
+    };
+    let stats = SubmissionStats {
+        submission_count: submissions.len(),
+        pools: [Pool::Sapling, Pool::Orchard]
+            .into_iter()
+            .map(|pool| pool_stats(pool, &submissions, &verdicts))
+            .collect(),
+        values,
+    };
+
+    if let Some(stats_out) = &stats_out {
+        let json = to_artifact_json(&stats)?;
+        stdio::write(stats_out, json)
+            .await
+            .with_context(|| format!("Failed to write {}", stats_out.display()))?;
+        info!(file = %stats_out.display(), "Saved claim statistics");
+    }
+    Ok(stats)
+}
+
+/// Claim counts and proof verdicts of `pool`.
+fn pool_stats(
+    pool: Pool,
+    submissions: &[(PathBuf, ClaimSubmission)],
+    verdicts: &[ProofVerdict],
+) -> PoolStats {
+    let nullifiers: Vec<Nullifier> = match pool {
+        Pool::Sapling => submissions
+            .iter()
+            .flat_map(|(_, submission)| submission.sapling.iter().map(|c| c.airdrop_nullifier))
+            .collect(),
+        Pool::Orchard => submissions
+            .iter()
+            .flat_map(|(_, submission)| submission.orchard.iter().map(|c| c.airdrop_nullifier))
+            .collect(),
+    };
+    let count = |valid: bool| {
+        verdicts
+            .iter()
+            .filter(|verdict| verdict.pool == pool && verdict.valid == valid)
+            .count()
+    };
+    PoolStats {
+        pool,
+        claims: nullifiers.len(),
+        unique_claims: nullifiers.iter().collect::<BTreeSet<_>>().len(),
+        valid_proofs: count(true),
+        invalid_proofs: count(false),
+    }
+}
+
+/// Check the claimed value proofs of `dir` against `submissions` and bucket their totals.
+async fn value_distribution(
+    dir: &Path,
+    submissions: &[(PathBuf, ClaimSubmission)],
+) -> eyre::Result<ValueDistribution> {
+    info!(dir = ?dir, "Checking claimed value proofs...");
+    let proofs: Vec<(PathBuf, ClaimedValueProof)> =
+        load_json_files(dir, "claimed value proof").await?;
+    let commitments: Vec<ClaimProofsOutput> = submissions
+        .iter()
+        .map(|(_, submission)| ClaimProofsOutput::from(submission))
+        .collect();
+    Ok(distribute_values(&proofs, &commitments))
+}
+
+/// Accept every proof that opens the commitments of a single submission and counts no claim of
+/// an earlier accepted proof, and bucket the accepted totals.
+fn distribute_values(
+    proofs: &[(PathBuf, ClaimedValueProof)],
+    commitments: &[ClaimProofsOutput],
+) -> ValueDistribution {
+    let mut counted: BTreeSet<Nullifier> = BTreeSet::new();
+    let mut totals = Vec::new();
+    let mut rejected_proofs = 0_usize;
+
+    for (file, proof) in proofs {
+        let nullifiers: Vec<Nullifier> = proof
+            .aggregates
+            .iter()
+            .flat_map(|aggregate| aggregate.airdrop_nullifiers.iter().copied())
+            .chain(
+                proof
+                    .openings
+                    .iter()
+                    .map(|opening| opening.airdrop_nullifier),
+            )
+            .collect();
+        let opens = commitments
+            .iter()
+            .any(|submission| ensure_claimed_value(proof, submission).is_ok());
+        let reason = if opens {
+            nullifiers
+                .iter()
+                .any(|nullifier| counted.contains(nullifier))
+                .then_some("counts claims of another proof")
+        } else {
+            Some("does not open the value commitments of any submission")
+        };
+        if let Some(reason) = reason {
+            warn!(file = %file.display(), reason, "Claimed value proof rejected");
+            rejected_proofs = rejected_proofs.saturating_add(1);
+            continue;
+        }
+        counted.extend(nullifiers);
+        totals.push(proof.total_value);
+    }
+
+    ValueDistribution {
+        proofs: totals.len(),
+        rejected_proofs,
+        claims: counted.len(),
+        total_value: totals
+            .iter()
+            .fold(0_u64, |total, value| total.saturating_add(*value)),
+        buckets: value_buckets(&totals),
+    }
+}
+
+/// Count `totals` per decade bucket.
+fn value_buckets(totals: &[u64]) -> Vec<ValueBucket> {
+    let mut min_value = 0_u64;
+    let mut buckets = Vec::with_capacity(VALUE_BUCKET_BOUNDS.len().saturating_add(1));
+    for max_value in VALUE_BUCKET_BOUNDS.into_iter().map(Some).chain([None]) {
+        let count = totals
+            .iter()
+            .filter(|&&total| total >= min_value && max_value.is_none_or(|max| total < max))
+            .count();
+        buckets.push(ValueBucket {
+            min_value,
+            max_value,
+            count,
+        });
+        if let Some(max) = max_value {
+            min_value = max;
+        }
+    }
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::schema::submission::SaplingSignedClaim;
+
+    use super::*;
+
+    fn submission(nullifiers: &[u8]) -> ClaimSubmission {
+        ClaimSubmission {
+            nonce: [1_u8; 32],
+            expiry_height: 100,
+            sapling: nullifiers
+                .iter()
+                .map(|&v| SaplingSignedClaim {
+                    claim_id: [v; 32],
+                    zkproof: [11_u8; 192],
+                    rk: [22_u8; 32],
+                    cv: Some([33_u8; 32]),
+                    cv_sha256: None,
+                    airdrop_nullifier: Nullifier::from([v; 32]),
+                    proof_hash: [55_u8; 32],
+                    message_hash: [66_u8; 32],
+                    spend_auth_sig: [0_u8; 64],
+                    countersignatures: Vec::new(),
+                })
+                .collect(),
+            orchard: vec![],
+            epoch: None,
+        }
+    }
+
+    #[test]
+    fn pool_stats_count_repeats_and_verdicts() {
+        let submissions = vec![
+            (PathBuf::from("a.json"), submission(&[1, 2])),
+            (PathBuf::from("b.json"), submission(&[2, 3])),
+        ];
+        let verdicts: Vec<ProofVerdict> = [(1_u8, true), (2, true), (2, false), (3, true)]
+            .into_iter()
+            .enumerate()
+            .map(|(index, (v, valid))| ProofVerdict {
+                pool: Pool::Sapling,
+                index,
+                claim_id: [v; 32],
+                airdrop_nullifier: Nullifier::from([v; 32]),
+                valid,
+                reason: None,
+            })
+            .collect();
+
+        assert_eq!(
+            pool_stats(Pool::Sapling, &submissions, &verdicts),
+            PoolStats {
+                pool: Pool::Sapling,
+                claims: 4,
+                unique_claims: 3,
+                valid_proofs: 3,
+                invalid_proofs: 1,
+            }
+        );
+        assert_eq!(
+            pool_stats(Pool::Orchard, &submissions, &verdicts),
+            PoolStats {
+                pool: Pool::Orchard,
+                claims: 0,
+                unique_claims: 0,
+                valid_proofs: 0,
+                invalid_proofs: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn totals_fall_into_decade_buckets() {
+        let buckets = value_buckets(&[0, 99_999, 100_000, 250_000_000, 10_000_000_000, u64::MAX]);
+
+        let counts: Vec<usize> = buckets.iter().map(|bucket| bucket.count).collect();
+        assert_eq!(counts, vec![2, 1, 0, 0, 1, 0, 2]);
+        assert_eq!(buckets.first().map(|bucket| bucket.min_value), Some(0));
+        assert_eq!(
+            buckets
+                .last()
+                .map(|bucket| (bucket.min_value, bucket.max_value)),
+            Some((10_000_000_000, None))
+        );
+    }
+}
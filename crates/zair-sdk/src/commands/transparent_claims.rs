@@ -0,0 +1,524 @@
+//! Transparent claims.
+//!
+//! An address of the transparent snapshot claims its balance with an ECDSA signature by its key
+//! in place of a proof. The signature covers [`transparent_signature_digest`] of the UTXO set, the
+//! address, its balance, the claim message and the nonce and expiry height of the claims, so it
+//! cannot be replayed against another snapshot, airdrop or message, or outside its expiry. The
+//! claim reveals the address and its balance.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use rand_core::RngCore as _;
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::{info, warn};
+use zair_core::base::{hash_message, transparent_signature_digest};
+use zair_core::schema::config::{AirdropConfiguration, TransparentSnapshot};
+use zair_core::schema::transparent::TransparentUtxoSet;
+
+use super::claim_submission_sign::resolve_expiry_height;
+use super::claim_submission_verify::ensure_not_expired;
+use super::submission_auth::transparent;
+use crate::build_info::to_artifact_json;
+use crate::common::configured_network;
+use crate::error::{ZairError, ZairResult};
+use crate::seed::{AccountSecret, read_account_secret};
+use crate::stdio;
+
+/// Signed claims of transparent addresses over one message.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransparentClaims {
+    /// Hash of the signed claim message.
+    #[serde_as(as = "Hex")]
+    pub message_hash: [u8; 32],
+    /// Random nonce bound into every signature.
+    #[serde_as(as = "Hex")]
+    pub nonce: [u8; 32],
+    /// Last block height at which the claims may be accepted.
+    pub expiry_height: u64,
+    /// One claim per address.
+    pub claims: Vec<TransparentClaim>,
+    /// Airdrop epoch of the claims; `None` for the base epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u32>,
+}
+
+/// Claim of the snapshot balance of one transparent address.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransparentClaim {
+    /// Encoded P2PKH address.
+    pub address: String,
+    /// Balance of the address in the snapshot, in zatoshis.
+    pub value: u64,
+    /// Compressed secp256k1 public key that the address hashes.
+    #[serde_as(as = "Hex")]
+    pub public_key: [u8; 33],
+    /// Compact ECDSA signature over the transparent claim digest.
+    #[serde_as(as = "Hex")]
+    pub signature: [u8; 64],
+}
+
+/// Sign a claim for every address of the account that holds outputs in the transparent snapshot.
+///
+/// The first `address_count` external addresses of the account are searched. The signatures
+/// expire at `expiry_height`, or at the end of the configured claim window if it is `None`.
+///
+/// # Errors
+/// Returns an error if the inputs cannot be read, the snapshot does not match the configuration,
+/// key derivation fails, or no address of the account is in the snapshot.
+#[allow(clippy::too_many_arguments, reason = "CLI entrypoint parameters")]
+pub async fn sign_transparent_claims(
+    airdrop_configuration_file: PathBuf,
+    transparent_snapshot_file: PathBuf,
+    seed_file: PathBuf,
+    account_id: u32,
+    address_count: u32,
+    message_file: PathBuf,
+    epoch: Option<u32>,
+    expiry_height: Option<u64>,
+    claims_output_file: PathBuf,
+) -> ZairResult<()> {
+    sign_transparent_claims_file(
+        airdrop_configuration_file,
+        transparent_snapshot_file,
+        seed_file,
+        account_id,
+        address_count,
+        message_file,
+        epoch,
+        expiry_height,
+        claims_output_file,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Signing))
+}
+
+#[allow(clippy::too_many_arguments, reason = "CLI entrypoint parameters")]
+async fn sign_transparent_claims_file(
+    airdrop_configuration_file: PathBuf,
+    transparent_snapshot_file: PathBuf,
+    seed_file: PathBuf,
+    account_id: u32,
+    address_count: u32,
+    message_file: PathBuf,
+    epoch: Option<u32>,
+    expiry_height: Option<u64>,
+    claims_output_file: PathBuf,
+) -> eyre::Result<()> {
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+    let set = read_utxo_set(&transparent_snapshot_file).await?;
+
+    info!(file = ?seed_file, "Reading seed from file...");
+    let account_secret = read_account_secret(&seed_file).await?;
+    let message = stdio::read(&message_file)
+        .await
+        .with_context(|| format!("Failed to read message {}", message_file.display()))?;
+
+    let claims = sign_transparent_inner(
+        &airdrop_config,
+        &set,
+        &account_secret,
+        account_id,
+        address_count,
+        &hash_message(&message),
+        epoch,
+        expiry_height,
+    )?;
+    stdio::write(&claims_output_file, to_artifact_json(&claims)?).await?;
+    info!(
+        file = ?claims_output_file,
+        claims = claims.claims.len(),
+        "Transparent claims written"
+    );
+    Ok(())
+}
+
+/// Sign the in-memory transparent snapshot balances of an account.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "Signing inputs are passed explicitly"
+)]
+pub fn sign_transparent_inner(
+    airdrop_config: &AirdropConfiguration,
+    set: &TransparentUtxoSet,
+    account_secret: &AccountSecret,
+    account_id: u32,
+    address_count: u32,
+    message_hash: &[u8; 32],
+    epoch: Option<u32>,
+    expiry_height: Option<u64>,
+) -> eyre::Result<TransparentClaims> {
+    let airdrop_config = &airdrop_config.for_epoch(epoch)?;
+    let snapshot = matching_snapshot(airdrop_config, set)?;
+    let expiry_height = resolve_expiry_height(airdrop_config, expiry_height)?;
+    let mut nonce = [0_u8; 32];
+    rand_core::OsRng.fill_bytes(&mut nonce);
+    let network = configured_network(airdrop_config)?;
+    let balances = set.balances()?;
+
+    let mut claims = Vec::new();
+    for key in transparent::derive_address_keys(network, account_secret, account_id, address_count)?
+    {
+        let Some(value) = balances.get(key.address.as_str()).copied() else {
+            continue;
+        };
+        let digest = claim_digest(
+            airdrop_config,
+            snapshot,
+            &key.address,
+            value,
+            message_hash,
+            &nonce,
+            expiry_height,
+        )?;
+        info!(address = %key.address, value, "Signing transparent claim");
+        claims.push(TransparentClaim {
+            signature: transparent::sign(&key, &digest),
+            address: key.address,
+            value,
+            public_key: key.public_key,
+        });
+    }
+    ensure!(
+        !claims.is_empty(),
+        "None of the first {address_count} transparent addresses of account {account_id} holds \
+         outputs in the snapshot"
+    );
+    airdrop_config.ensure_claim_count(claims.len())?;
+    Ok(TransparentClaims {
+        message_hash: *message_hash,
+        nonce,
+        expiry_height,
+        claims,
+        epoch,
+    })
+}
+
+/// Verify signed transparent claims against the snapshot and claim message.
+///
+/// # Errors
+/// Returns an error if the inputs cannot be read, the snapshot or message does not match, the
+/// claims break the claim policy or expired before `current_height`, an address claims more than
+/// once or another balance than the snapshot's, or a signature is invalid.
+pub async fn verify_transparent_claims(
+    claims_file: PathBuf,
+    transparent_snapshot_file: PathBuf,
+    airdrop_configuration_file: PathBuf,
+    message_file: PathBuf,
+    current_height: Option<u64>,
+) -> ZairResult<()> {
+    verify_transparent_claims_file(
+        claims_file,
+        transparent_snapshot_file,
+        airdrop_configuration_file,
+        message_file,
+        current_height,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+async fn verify_transparent_claims_file(
+    claims_file: PathBuf,
+    transparent_snapshot_file: PathBuf,
+    airdrop_configuration_file: PathBuf,
+    message_file: PathBuf,
+    current_height: Option<u64>,
+) -> eyre::Result<()> {
+    info!(file = ?claims_file, "Loading transparent claims...");
+    let claims: TransparentClaims =
+        serde_json::from_str(&stdio::read_to_string(&claims_file).await?)
+            .context("Failed to parse transparent claims JSON")?;
+    let airdrop_config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+    let set = read_utxo_set(&transparent_snapshot_file).await?;
+    let message = stdio::read(&message_file)
+        .await
+        .with_context(|| format!("Failed to read message {}", message_file.display()))?;
+
+    verify_transparent_inner(
+        &claims,
+        &airdrop_config,
+        &set,
+        &hash_message(&message),
+        current_height,
+    )
+}
+
+/// Verify in-memory transparent claims against a snapshot and message hash.
+pub fn verify_transparent_inner(
+    claims: &TransparentClaims,
+    airdrop_config: &AirdropConfiguration,
+    set: &TransparentUtxoSet,
+    message_hash: &[u8; 32],
+    current_height: Option<u64>,
+) -> eyre::Result<()> {
+    ensure!(
+        !claims.claims.is_empty(),
+        "Transparent claims file contains no claims"
+    );
+    ensure!(
+        claims.message_hash == *message_hash,
+        "Message hash mismatch: the claims were signed over another message"
+    );
+    let airdrop_config = &airdrop_config.for_epoch(claims.epoch)?;
+    ensure_not_expired(
+        "Transparent claims",
+        claims.expiry_height,
+        airdrop_config,
+        current_height,
+    )?;
+    airdrop_config.ensure_claim_count(claims.claims.len())?;
+    if let Some(current_height) = current_height {
+        airdrop_config.ensure_claim_window_open(current_height)?;
+    }
+    let snapshot = matching_snapshot(airdrop_config, set)?;
+    let network = configured_network(airdrop_config)?;
+    let balances = set.balances()?;
+
+    let mut seen = BTreeSet::new();
+    let mut invalid_count = 0_usize;
+    for (idx, claim) in claims.claims.iter().enumerate() {
+        ensure!(
+            seen.insert(claim.address.as_str()),
+            "{} claims more than once",
+            claim.address
+        );
+        let balance = balances
+            .get(claim.address.as_str())
+            .copied()
+            .with_context(|| format!("{} holds no outputs in the snapshot", claim.address))?;
+        ensure!(
+            claim.value == balance,
+            "{} claims {} zatoshis but holds {balance} in the snapshot",
+            claim.address,
+            claim.value
+        );
+        let digest = claim_digest(
+            airdrop_config,
+            snapshot,
+            &claim.address,
+            claim.value,
+            message_hash,
+            &claims.nonce,
+            claims.expiry_height,
+        )?;
+        match transparent::verify_signature(
+            network,
+            &claim.address,
+            &claim.public_key,
+            &claim.signature,
+            &digest,
+        ) {
+            Ok(()) => info!(index = idx, address = %claim.address, "Transparent claim VALID"),
+            Err(e) => {
+                invalid_count = invalid_count.saturating_add(1);
+                warn!(
+                    index = idx,
+                    address = %claim.address,
+                    error = %e,
+                    "Transparent claim INVALID"
+                );
+            }
+        }
+    }
+
+    ensure!(
+        invalid_count == 0,
+        "{invalid_count} transparent claims failed verification"
+    );
+    info!(
+        claims = claims.claims.len(),
+        "All transparent claims are VALID"
+    );
+    Ok(())
+}
+
+async fn read_utxo_set(path: &Path) -> eyre::Result<TransparentUtxoSet> {
+    serde_json::from_str(&stdio::read_to_string(path).await?)
+        .with_context(|| format!("Failed to parse transparent snapshot {}", path.display()))
+}
+
+/// The transparent snapshot of `airdrop_config`, checked to bind `set`.
+fn matching_snapshot<'a>(
+    airdrop_config: &'a AirdropConfiguration,
+    set: &TransparentUtxoSet,
+) -> eyre::Result<&'a TransparentSnapshot> {
+    let snapshot = airdrop_config
+        .transparent
+        .as_ref()
+        .context("Airdrop configuration has no transparent snapshot")?;
+    ensure!(
+        set.snapshot_height == airdrop_config.snapshot_height &&
+            set.hash()? == snapshot.utxo_set_hash,
+        "Transparent snapshot does not match the airdrop configuration"
+    );
+    Ok(snapshot)
+}
+
+fn claim_digest(
+    airdrop_config: &AirdropConfiguration,
+    snapshot: &TransparentSnapshot,
+    address: &str,
+    value: u64,
+    message_hash: &[u8; 32],
+    nonce: &[u8; 32],
+    expiry_height: u64,
+) -> eyre::Result<[u8; 32]> {
+    Ok(transparent_signature_digest(
+        snapshot.target_id.as_bytes(),
+        airdrop_config
+            .chain_id
+            .as_deref()
+            .unwrap_or_default()
+            .as_bytes(),
+        &snapshot.utxo_set_hash,
+        address,
+        value,
+        message_hash,
+        nonce,
+        expiry_height,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::SecretBox;
+    use zair_core::schema::config::AirdropNetwork;
+    use zair_core::schema::transparent::TransparentUtxo;
+
+    use super::*;
+    use crate::common::Network;
+
+    /// An account secret and a snapshot at height 100 of its second and third addresses.
+    fn fixture() -> (AccountSecret, TransparentUtxoSet, AirdropConfiguration) {
+        let secret = AccountSecret::Seed(SecretBox::new(Box::new([7_u8; 64])));
+        let keys = transparent::derive_address_keys(Network::TestNetwork, &secret, 0, 3)
+            .expect("address keys");
+        let utxo = |address: &str, txid, value| TransparentUtxo {
+            address: address.to_owned(),
+            txid: [txid; 32],
+            index: 0,
+            value,
+            height: 90,
+        };
+        let set = TransparentUtxoSet::new(
+            100,
+            keys.iter()
+                .skip(1)
+                .zip(1_u8..)
+                .map(|(key, txid)| utxo(&key.address, txid, u64::from(txid)))
+                .collect(),
+        );
+        let mut config = AirdropConfiguration::new(AirdropNetwork::Testnet, 100, None, None);
+        config.transparent = Some(TransparentSnapshot {
+            utxo_set_hash: set.hash().expect("hash"),
+            total_value: set.total_value().expect("total"),
+            target_id: "ZAIRTEST:T".parse().expect("target ID"),
+        });
+        (secret, set, config)
+    }
+
+    #[test]
+    fn transparent_claims_verify_against_the_snapshot() {
+        let (secret, set, config) = fixture();
+        let message_hash = hash_message(b"namada address");
+        let claims =
+            sign_transparent_inner(&config, &set, &secret, 0, 3, &message_hash, None, Some(200))
+                .expect("sign");
+        assert_eq!(claims.claims.len(), 2);
+        verify_transparent_inner(&claims, &config, &set, &message_hash, None).expect("verify");
+        assert!(
+            verify_transparent_inner(&claims, &config, &set, &hash_message(b"other"), None)
+                .is_err()
+        );
+
+        let mut inflated = claims.clone();
+        if let Some(claim) = inflated.claims.first_mut() {
+            claim.value = claim.value.saturating_add(1);
+        }
+        assert!(verify_transparent_inner(&inflated, &config, &set, &message_hash, None).is_err());
+
+        let mut extended = claims.clone();
+        extended.expiry_height = 201;
+        assert!(verify_transparent_inner(&extended, &config, &set, &message_hash, None).is_err());
+
+        let mut stolen = claims;
+        if let [first, second] = stolen.claims.as_mut_slice() {
+            first.public_key = second.public_key;
+            first.signature = second.signature;
+        }
+        assert!(verify_transparent_inner(&stolen, &config, &set, &message_hash, None).is_err());
+
+        assert!(
+            sign_transparent_inner(&config, &set, &secret, 0, 1, &message_hash, None, Some(200))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn expired_transparent_claims_are_rejected() {
+        let (secret, set, mut config) = fixture();
+        let message_hash = hash_message(b"namada address");
+        let claims =
+            sign_transparent_inner(&config, &set, &secret, 0, 3, &message_hash, None, Some(200))
+                .expect("sign");
+        verify_transparent_inner(&claims, &config, &set, &message_hash, Some(200))
+            .expect("verify at the expiry height");
+
+        let err = verify_transparent_inner(&claims, &config, &set, &message_hash, Some(201))
+            .expect_err("verification must fail after the expiry height");
+        assert!(
+            err.to_string()
+                .contains("Transparent claims expired at height 200, current height is 201"),
+            "{err:?}"
+        );
+
+        config.claim_expiry_height = Some(150);
+        let err = verify_transparent_inner(&claims, &config, &set, &message_hash, None)
+            .expect_err("verification must fail for an expiry past the claim window");
+        assert!(
+            err.to_string()
+                .contains("past the claim window ending at height 150"),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn transparent_claims_outside_the_claim_window_are_rejected() {
+        let (secret, set, mut config) = fixture();
+        let message_hash = hash_message(b"namada address");
+        let claims =
+            sign_transparent_inner(&config, &set, &secret, 0, 3, &message_hash, None, Some(200))
+                .expect("sign");
+
+        config.claim_start_height = Some(120);
+        verify_transparent_inner(&claims, &config, &set, &message_hash, Some(120))
+            .expect("verify once the window opens");
+        let err = verify_transparent_inner(&claims, &config, &set, &message_hash, Some(119))
+            .expect_err("verification must fail before the claim window opens");
+        assert!(
+            err.to_string()
+                .contains("the claim window opens at height 120, current height is 119"),
+            "{err:?}"
+        );
+
+        config.claim_start_height = None;
+        config.max_claims_per_submission = Some(1);
+        let err = verify_transparent_inner(&claims, &config, &set, &message_hash, None)
+            .expect_err("verification must fail above the claim limit");
+        assert!(
+            err.to_string()
+                .contains("2 claims exceed the limit of 1 claims per submission"),
+            "{err:?}"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Transparent snapshots.
+//!
+//! `config add-transparent` lets transparent addresses claim: it collects the outputs of a list of
+//! addresses at the snapshot height with `GetAddressUtxos`, writes them as a
+//! [`TransparentUtxoSet`] and binds the set into the configuration. lightwalletd indexes outputs
+//! by address only, so the organizer supplies the eligible addresses, for example from a block
+//! explorer export.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::str::FromStr as _;
+
+use eyre::{Context as _, ensure};
+use http::Uri;
+use tracing::{info, warn};
+use zair_core::base::TargetId;
+use zair_core::schema::config::{AirdropConfiguration, TransparentSnapshot};
+use zair_core::schema::transparent::TransparentUtxoSet;
+use zair_scan::light_walletd::LightWalletd;
+use zcash_keys::encoding::AddressCodec as _;
+use zcash_transparent::address::TransparentAddress;
+
+use crate::build_info::to_artifact_json;
+use crate::common::{Backend, Network, configured_network, resolve_lightwalletd_url};
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+/// Addresses per `GetAddressUtxos` request.
+const ADDRESS_BATCH: usize = 100;
+
+/// Collect the transparent outputs of the addresses in `addresses_file` at the snapshot height of
+/// `configuration_file`, write them to `snapshot_output_file` and bind them into the
+/// configuration written to `configuration_output_file`.
+///
+/// `addresses_file` lists one P2PKH address per line; empty lines and lines starting with `#` are
+/// skipped. `GetAddressUtxos` serves the current UTXO set, so an output spent between the
+/// snapshot height and the chain tip is left out: collect the set as close to the snapshot
+/// height as possible.
+///
+/// # Errors
+/// Returns an error if the configuration or addresses cannot be read, an address is not a P2PKH
+/// address of the network, or the outputs cannot be fetched or written.
+#[allow(clippy::too_many_arguments, reason = "CLI entrypoint parameters")]
+pub async fn add_transparent_snapshot(
+    configuration_file: PathBuf,
+    addresses_file: PathBuf,
+    lightwalletd_url: Option<String>,
+    backend: Backend,
+    target_id: TargetId,
+    snapshot_output_file: PathBuf,
+    configuration_output_file: PathBuf,
+) -> ZairResult<()> {
+    add_transparent_snapshot_inner(
+        configuration_file,
+        addresses_file,
+        lightwalletd_url,
+        backend,
+        target_id,
+        snapshot_output_file,
+        configuration_output_file,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Snapshot))
+}
+
+#[allow(clippy::too_many_arguments, reason = "CLI entrypoint parameters")]
+async fn add_transparent_snapshot_inner(
+    configuration_file: PathBuf,
+    addresses_file: PathBuf,
+    lightwalletd_url: Option<String>,
+    backend: Backend,
+    target_id: TargetId,
+    snapshot_output_file: PathBuf,
+    configuration_output_file: PathBuf,
+) -> eyre::Result<()> {
+    let mut config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+    ensure!(
+        config.epochs.is_empty(),
+        "{} already lists epochs; add the transparent snapshot to the configuration of each \
+         epoch before `config add-epoch`",
+        configuration_file.display()
+    );
+    let network = configured_network(&config)?;
+    let addresses = parse_addresses(network, &stdio::read_to_string(&addresses_file).await?)?;
+    ensure!(
+        !addresses.is_empty(),
+        "{} lists no addresses",
+        addresses_file.display()
+    );
+
+    let lightwalletd_url = resolve_lightwalletd_url(network, lightwalletd_url.as_deref());
+    let lightwalletd_url = Uri::from_str(&lightwalletd_url).context("Invalid lightwalletd URL")?;
+    let lightwalletd = LightWalletd::connect_backend(lightwalletd_url, backend).await?;
+    let tip = lightwalletd.latest_height().await?;
+    ensure!(
+        tip >= config.snapshot_height,
+        "Chain tip {tip} is below the snapshot height {}",
+        config.snapshot_height
+    );
+    if tip > config.snapshot_height {
+        warn!(
+            tip,
+            snapshot_height = config.snapshot_height,
+            "Outputs spent after the snapshot height are missing from the transparent snapshot"
+        );
+    }
+
+    let mut utxos = Vec::new();
+    for batch in addresses.chunks(ADDRESS_BATCH) {
+        utxos.extend(
+            lightwalletd
+                .address_utxos(batch, config.snapshot_height)
+                .await?,
+        );
+    }
+    let set = TransparentUtxoSet::new(config.snapshot_height, utxos);
+    let snapshot = TransparentSnapshot {
+        utxo_set_hash: set.hash()?,
+        total_value: set.total_value()?,
+        target_id,
+    };
+    info!(
+        addresses = addresses.len(),
+        funded_addresses = set.balances()?.len(),
+        utxos = set.utxos.len(),
+        total_value = snapshot.total_value,
+        "Collected transparent outputs"
+    );
+
+    stdio::write(&snapshot_output_file, to_artifact_json(&set)?).await?;
+    info!(file = ?snapshot_output_file, "Saved transparent snapshot");
+    config.transparent = Some(snapshot);
+    stdio::write(&configuration_output_file, to_artifact_json(&config)?).await?;
+    info!(file = ?configuration_output_file, "Added transparent snapshot to the configuration");
+    Ok(())
+}
+
+/// Distinct P2PKH addresses of `network` listed in `text`, one per line.
+fn parse_addresses(network: Network, text: &str) -> eyre::Result<Vec<String>> {
+    let mut addresses = BTreeSet::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match TransparentAddress::decode(&network, line) {
+            Ok(TransparentAddress::PublicKeyHash(_)) => {
+                addresses.insert(line.to_owned());
+            }
+            Ok(TransparentAddress::ScriptHash(_)) => {
+                eyre::bail!("{line} is a P2SH address; only P2PKH addresses can sign a claim")
+            }
+            Err(e) => eyre::bail!("Invalid transparent address {line}: {e}"),
+        }
+    }
+    Ok(addresses.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_lists_keep_distinct_p2pkh_addresses() {
+        let network = Network::TestNetwork;
+        let p2pkh = TransparentAddress::PublicKeyHash([1; 20]).encode(&network);
+        let other = TransparentAddress::PublicKeyHash([2; 20]).encode(&network);
+        let text = format!("# eligible\n{other}\n\n  {p2pkh}\n{other}\n");
+        let mut expected = vec![p2pkh, other];
+        expected.sort();
+        assert_eq!(
+            parse_addresses(network, &text).expect("addresses"),
+            expected
+        );
+
+        let p2sh = TransparentAddress::ScriptHash([1; 20]).encode(&network);
+        assert!(parse_addresses(network, &p2sh).is_err());
+        let mainnet = TransparentAddress::PublicKeyHash([1; 20]).encode(&Network::MainNetwork);
+        assert!(parse_addresses(network, &mainnet).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Machine-readable evidence of a `verify run`.
+//!
+//! Intake pipelines archive `verification-report.json` next to the submission: the verdict on
+//! every claim proof, the roots and target IDs the proofs were checked against, the fingerprints
+//! of the trusted setup used, and when the run started and finished. The report is written for
+//! rejected submissions too, with the reason.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use eyre::Context as _;
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use tracing::info;
+use zair_core::base::{Pool, TargetId};
+use zair_core::schema::config::AirdropConfiguration;
+
+use super::claim_proofs::ProofVerdict;
+use super::setup_fingerprint::{read_orchard_params_fingerprint, read_sapling_vk_fingerprint};
+use crate::build_info::to_artifact_json;
+use crate::stdio;
+
+/// Default file name of the verification report.
+pub const VERIFICATION_REPORT_FILE: &str = "verification-report.json";
+
+/// What a pool's proofs were checked against.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolEvidence {
+    /// Note commitment tree root of the configuration.
+    #[serde_as(as = "Hex")]
+    pub note_commitment_root: [u8; 32],
+    /// Nullifier gap tree root of the configuration.
+    #[serde_as(as = "Hex")]
+    pub nullifier_gap_root: [u8; 32],
+    /// Target ID of the configuration.
+    pub target_id: TargetId,
+    /// Fingerprint of the Sapling verifying key or Orchard params used, if the pool had proofs.
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup_fingerprint: Option<[u8; 32]>,
+}
+
+/// Evidence of one `verify run`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    /// Verified submission file.
+    pub submission: PathBuf,
+    /// Unix time the verification started, in seconds.
+    pub started_at: u64,
+    /// Unix time the verification finished, in seconds.
+    pub finished_at: u64,
+    /// Chain height the claim window and submission expiry were checked at, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_height: Option<u64>,
+    /// Whether the submission passed every check.
+    pub verified: bool,
+    /// Why the submission was rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether the spend-authorizing signatures verified.
+    pub signatures_valid: bool,
+    /// Sapling roots and setup the proofs were checked against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sapling: Option<PoolEvidence>,
+    /// Orchard roots and setup the proofs were checked against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orchard: Option<PoolEvidence>,
+    /// Verdict on every claim proof.
+    pub proofs: Vec<ProofVerdict>,
+}
+
+/// Files and chain height a `verify run` checked the submission against.
+pub(super) struct VerifiedInputs<'a> {
+    pub submission_file: &'a Path,
+    pub airdrop_configuration_file: &'a Path,
+    pub verifying_key_file: &'a Path,
+    pub orchard_params_file: &'a Path,
+    pub current_height: Option<u64>,
+}
+
+/// Current Unix time in seconds.
+pub(super) fn unix_now() -> eyre::Result<u64> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("System clock is before the Unix epoch")?
+        .as_secs())
+}
+
+/// Write the report of a finished `verify run` to `report_file`.
+pub(super) async fn write_verification_report(
+    report_file: &Path,
+    inputs: &VerifiedInputs<'_>,
+    started_at: u64,
+    signatures: &eyre::Result<()>,
+    proofs: &eyre::Result<Vec<ProofVerdict>>,
+) -> eyre::Result<()> {
+    let airdrop_config: Option<AirdropConfiguration> =
+        stdio::read_to_string(inputs.airdrop_configuration_file)
+            .await
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok());
+    let verdicts = proofs.as_ref().map_or(&[][..], Vec::as_slice);
+    let has_proofs = |pool: Pool| verdicts.iter().any(|verdict| verdict.pool == pool);
+
+    let sapling = match airdrop_config
+        .as_ref()
+        .and_then(|config| config.sapling.as_ref())
+    {
+        Some(sapling) => Some(PoolEvidence {
+            note_commitment_root: sapling.note_commitment_root,
+            nullifier_gap_root: sapling.nullifier_gap_root,
+            target_id: sapling.target_id.clone(),
+            setup_fingerprint: if has_proofs(Pool::Sapling) {
+                Some(read_sapling_vk_fingerprint(inputs.verifying_key_file).await?)
+            } else {
+                None
+            },
+        }),
+        None => None,
+    };
+    let orchard = match airdrop_config
+        .as_ref()
+        .and_then(|config| config.orchard.as_ref())
+    {
+        Some(orchard) => Some(PoolEvidence {
+            note_commitment_root: orchard.note_commitment_root,
+            nullifier_gap_root: orchard.nullifier_gap_root,
+            target_id: orchard.target_id.clone(),
+            setup_fingerprint: if has_proofs(Pool::Orchard) {
+                Some(read_orchard_params_fingerprint(inputs.orchard_params_file).await?)
+            } else {
+                None
+            },
+        }),
+        None => None,
+    };
+
+    let error = match (signatures, proofs) {
+        (Err(error), _) | (Ok(()), Err(error)) => Some(format!("{error:#}")),
+        (Ok(()), Ok(verdicts)) => {
+            let invalid = verdicts.iter().filter(|verdict| !verdict.valid).count();
+            (invalid > 0).then(|| format!("{invalid} proofs failed verification"))
+        }
+    };
+    let report = VerificationReport {
+        submission: inputs.submission_file.to_path_buf(),
+        started_at,
+        finished_at: unix_now()?,
+        current_height: inputs.current_height,
+        verified: error.is_none(),
+        error,
+        signatures_valid: signatures.is_ok(),
+        sapling,
+        orchard,
+        proofs: verdicts.to_vec(),
+    };
+
+    stdio::write(report_file, to_artifact_json(&report)?)
+        .await
+        .with_context(|| format!("Failed to write {}", report_file.display()))?;
+    info!(file = ?report_file, verified = report.verified, "Verification report written");
+    Ok(())
+}
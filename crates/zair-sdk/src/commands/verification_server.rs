@@ -0,0 +1,473 @@
+//! Public HTTP service for submission verification.
+//!
+//! `POST /verify` takes `{"submission": {...}, "message": "<hex>", "current_height": 123}` and
+//! answers with `{"verdict": "accepted"}` or `{"verdict": "rejected", "code": ..., "message":
+//! ...}`. Per-claim messages go in `"messages": {"sapling": {"<nullifier>": "<hex>"}, "orchard":
+//! {...}}`.
+//!
+//! Requests must carry `Authorization: Bearer <key>` with a key from the allowlist, and each key
+//! is limited to a number of requests per minute. Every request, including refused ones, is
+//! written to the audit log. gRPC health checks, which report the service as `zair.verify`, and
+//! reflection need no key.
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use axum::extract::{ConnectInfo, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse as _, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use eyre::{Context as _, ensure};
+use serde::{Deserialize, Serialize};
+use serde_with::hex::Hex;
+use serde_with::serde_as;
+use sha2::{Digest as _, Sha256};
+use tokio::io::AsyncWriteExt as _;
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use zair_core::base::Nullifier;
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::submission::ClaimSubmission;
+use zair_orchard_proofs::ValueCommitmentScheme as OrchardValueCommitmentScheme;
+
+use super::grpc_ops::grpc_ops_router;
+use super::orchard_params::{OrchardParamsMode, load_or_prepare_orchard_params_inner};
+use crate::error::{ZairError, ZairResult};
+use crate::pipeline::{ClaimMessages, VerifyingParams, verify_submission};
+use crate::stdio;
+
+/// Service name reported by the gRPC health service.
+const VERIFY_SERVICE: &str = "zair.verify";
+
+/// Length of a rate-limit window.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Body of a `POST /verify` request.
+#[serde_as]
+#[derive(Deserialize)]
+struct VerifyRequest {
+    submission: ClaimSubmission,
+    #[serde_as(as = "Option<Hex>")]
+    #[serde(default)]
+    message: Option<Vec<u8>>,
+    #[serde(default)]
+    messages: RequestMessages,
+    #[serde(default)]
+    current_height: Option<u64>,
+}
+
+#[serde_as]
+#[derive(Default, Deserialize)]
+struct RequestMessages {
+    #[serde_as(as = "BTreeMap<_, Hex>")]
+    #[serde(default)]
+    sapling: BTreeMap<Nullifier, Vec<u8>>,
+    #[serde_as(as = "BTreeMap<_, Hex>")]
+    #[serde(default)]
+    orchard: BTreeMap<Nullifier, Vec<u8>>,
+}
+
+/// Body of a `POST /verify` response.
+#[derive(Serialize)]
+#[serde(tag = "verdict", rename_all = "snake_case")]
+enum VerifyResponse {
+    Accepted,
+    Rejected { code: &'static str, message: String },
+}
+
+/// Outcome of a request, as written to the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum AuditOutcome {
+    Accepted,
+    Rejected,
+    Unauthorized,
+    RateLimited,
+}
+
+/// One line of the audit log.
+#[derive(Serialize)]
+struct AuditRecord<'a> {
+    timestamp: u64,
+    remote: SocketAddr,
+    client: Option<&'a str>,
+    outcome: AuditOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sapling_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    orchard_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+}
+
+/// Allowlisted API keys, stored as SHA-256 digests mapped to client names.
+#[derive(Debug)]
+struct ApiKeys(HashMap<[u8; 32], String>);
+
+impl ApiKeys {
+    /// Parse `<client name> <key>` lines; blank lines and `#` comments are ignored.
+    fn parse(contents: &str) -> eyre::Result<Self> {
+        let mut keys = HashMap::new();
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, key) = line
+                .split_once(char::is_whitespace)
+                .map(|(name, key)| (name, key.trim()))
+                .filter(|(_, key)| !key.is_empty())
+                .with_context(|| {
+                    format!(
+                        "API keys line {} must be `<client name> <key>`",
+                        line_number.saturating_add(1)
+                    )
+                })?;
+            ensure!(
+                keys.insert(key_digest(key), name.to_owned()).is_none(),
+                "Duplicate API key for client {name}"
+            );
+        }
+        ensure!(!keys.is_empty(), "API keys file contains no keys");
+        Ok(Self(keys))
+    }
+
+    /// Client name of `key`, if it is allowlisted.
+    fn client(&self, key: &str) -> Option<&str> {
+        self.0.get(&key_digest(key)).map(String::as_str)
+    }
+}
+
+fn key_digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+/// Fixed-window request counter per client.
+#[derive(Debug)]
+struct RateLimiter {
+    limit: u32,
+    windows: std::sync::Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    fn new(limit: NonZeroU32) -> Self {
+        Self {
+            limit: limit.get(),
+            windows: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a request by `client` at `now`, or return the time until its window resets if the
+    /// client is over its limit.
+    fn check(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let mut windows = self.windows.lock().unwrap_or_else(PoisonError::into_inner);
+        let (start, count) = windows.entry(client.to_owned()).or_insert((now, 0));
+        let elapsed = now.saturating_duration_since(*start);
+        if elapsed >= RATE_LIMIT_WINDOW {
+            *start = now;
+            *count = 0;
+        } else if *count >= self.limit {
+            return Err(RATE_LIMIT_WINDOW.saturating_sub(elapsed));
+        }
+        *count = count.saturating_add(1);
+        Ok(())
+    }
+}
+
+struct ServerState {
+    config: AirdropConfiguration,
+    params: VerifyingParams,
+    api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
+    audit_log: Option<Mutex<tokio::fs::File>>,
+}
+
+impl ServerState {
+    async fn audit(&self, record: AuditRecord<'_>) {
+        info!(
+            target: "zair::audit",
+            remote = %record.remote,
+            client = record.client,
+            outcome = ?record.outcome,
+            code = record.code,
+            "Verification request"
+        );
+        let Some(audit_log) = &self.audit_log else {
+            return;
+        };
+        let mut line = serde_json::to_vec(&record).expect("Audit records serialize to JSON");
+        line.push(b'\n');
+        if let Err(e) = audit_log.lock().await.write_all(&line).await {
+            warn!("Failed to write audit log: {e}");
+        }
+    }
+}
+
+/// Serve submission verification over HTTP on `addr` until the process is stopped.
+///
+/// The configuration and verifying parameters are loaded once at startup. `api_keys_file` lists
+/// the allowed clients as `<client name> <key>` lines, and each client may send `rate_limit`
+/// requests per minute. With `audit_log`, every request is appended to that file as a JSON line.
+///
+/// # Errors
+/// Returns an error if the configuration, verifying parameters or API keys are invalid, or the
+/// address cannot be bound.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "CLI command entrypoint carries explicit file/path knobs"
+)]
+pub async fn serve_verification(
+    addr: SocketAddr,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    airdrop_configuration_file: PathBuf,
+    api_keys_file: PathBuf,
+    rate_limit: NonZeroU32,
+    audit_log: Option<PathBuf>,
+) -> ZairResult<()> {
+    serve_verification_inner(
+        addr,
+        verifying_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+        airdrop_configuration_file,
+        api_keys_file,
+        rate_limit,
+        audit_log,
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Config))
+}
+
+#[allow(
+    clippy::too_many_arguments,
+    reason = "CLI command entrypoint carries explicit file/path knobs"
+)]
+async fn serve_verification_inner(
+    addr: SocketAddr,
+    verifying_key_file: PathBuf,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+    airdrop_configuration_file: PathBuf,
+    api_keys_file: PathBuf,
+    rate_limit: NonZeroU32,
+    audit_log: Option<PathBuf>,
+) -> eyre::Result<()> {
+    let config: AirdropConfiguration =
+        serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+            .context("Failed to parse airdrop configuration JSON")?;
+    config.circuit.ensure_supported()?;
+    let params = load_verifying_params(
+        &config,
+        &verifying_key_file,
+        orchard_params_file,
+        orchard_params_mode,
+    )
+    .await?;
+    let api_keys = ApiKeys::parse(
+        &stdio::read_to_string(&api_keys_file)
+            .await
+            .with_context(|| format!("Failed to read API keys {}", api_keys_file.display()))?,
+    )?;
+    let audit_log = match audit_log {
+        Some(path) => Some(Mutex::new(
+            tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await
+                .with_context(|| format!("Failed to open audit log {}", path.display()))?,
+        )),
+        None => None,
+    };
+    info!(
+        clients = api_keys.0.len(),
+        rate_limit = rate_limit.get(),
+        "Loaded API key allowlist"
+    );
+
+    let state = Arc::new(ServerState {
+        config,
+        params,
+        api_keys,
+        rate_limiter: RateLimiter::new(rate_limit),
+        audit_log,
+    });
+    let router = Router::new()
+        .route("/verify", post(verify))
+        .with_state(state)
+        .merge(grpc_ops_router(VERIFY_SERVICE).await?);
+
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {addr}"))?;
+    info!(%addr, "Serving submission verification");
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .context("Verification service failed")
+}
+
+/// Load the verifying parameters of every pool in the configuration.
+async fn load_verifying_params(
+    config: &AirdropConfiguration,
+    verifying_key_file: &Path,
+    orchard_params_file: PathBuf,
+    orchard_params_mode: OrchardParamsMode,
+) -> eyre::Result<VerifyingParams> {
+    let sapling_verifying_key = if config.sapling.is_some() {
+        let bytes = tokio::fs::read(verifying_key_file).await.with_context(|| {
+            format!(
+                "Failed to read Sapling verifying key {}",
+                verifying_key_file.display()
+            )
+        })?;
+        Some(
+            bellman::groth16::VerifyingKey::read(&bytes[..])
+                .context("Failed to read verifying key")?,
+        )
+    } else {
+        None
+    };
+    let orchard_params = match &config.orchard {
+        Some(orchard) => Some(
+            load_or_prepare_orchard_params_inner(
+                orchard_params_file,
+                OrchardValueCommitmentScheme::from(orchard.value_commitment_scheme),
+                orchard_params_mode,
+            )
+            .await?,
+        ),
+        None => None,
+    };
+    Ok(VerifyingParams {
+        sapling_verifying_key,
+        orchard_params,
+    })
+}
+
+async fn verify(
+    State(state): State<Arc<ServerState>>,
+    ConnectInfo(remote): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(request): Json<VerifyRequest>,
+) -> Response {
+    let mut record = AuditRecord {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs()),
+        remote,
+        client: None,
+        outcome: AuditOutcome::Unauthorized,
+        sapling_count: None,
+        orchard_count: None,
+        code: None,
+    };
+
+    let client = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|key| state.api_keys.client(key.trim()));
+    let Some(client) = client else {
+        state.audit(record).await;
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    record.client = Some(client);
+
+    if let Err(retry_after) = state.rate_limiter.check(client, Instant::now()) {
+        record.outcome = AuditOutcome::RateLimited;
+        state.audit(record).await;
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(
+                header::RETRY_AFTER,
+                retry_after.as_secs().max(1).to_string(),
+            )],
+        )
+            .into_response();
+    }
+
+    let VerifyRequest {
+        submission,
+        message,
+        messages,
+        current_height,
+    } = request;
+    record.sapling_count = Some(submission.sapling.len());
+    record.orchard_count = Some(submission.orchard.len());
+    let messages = ClaimMessages {
+        shared: message,
+        sapling: messages.sapling,
+        orchard: messages.orchard,
+    };
+
+    let response = match verify_submission(
+        &submission,
+        &state.config,
+        &messages,
+        &state.params,
+        current_height,
+    )
+    .await
+    {
+        Ok(()) => {
+            record.outcome = AuditOutcome::Accepted;
+            VerifyResponse::Accepted
+        }
+        Err(error) => {
+            record.outcome = AuditOutcome::Rejected;
+            record.code = Some(error.code());
+            VerifyResponse::Rejected {
+                code: error.code(),
+                message: format!("{:#}", error.report()),
+            }
+        }
+    };
+    state.audit(record).await;
+    Json(response).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_keys_map_to_client_names() {
+        let keys = ApiKeys::parse("# intake services\nnamada-intake s3cr3t\n\nexplorer  k3y \n")
+            .expect("keys parse");
+        assert_eq!(keys.client("s3cr3t"), Some("namada-intake"));
+        assert_eq!(keys.client("k3y"), Some("explorer"));
+        assert_eq!(keys.client("namada-intake"), None);
+
+        assert!(ApiKeys::parse("a key\nb key\n").is_err(), "duplicate key");
+        assert!(ApiKeys::parse("lonely\n").is_err(), "missing key");
+        assert!(ApiKeys::parse("# nothing\n").is_err(), "empty allowlist");
+    }
+
+    #[test]
+    fn rate_limiter_resets_each_window() {
+        let limiter = RateLimiter::new(NonZeroU32::new(2).expect("non-zero"));
+        let start = Instant::now();
+        let later = |secs| {
+            start
+                .checked_add(Duration::from_secs(secs))
+                .expect("instant")
+        };
+
+        assert_eq!(limiter.check("a", start), Ok(()));
+        assert_eq!(limiter.check("a", start), Ok(()));
+        assert_eq!(limiter.check("a", later(20)), Err(Duration::from_secs(40)));
+        assert_eq!(limiter.check("b", start), Ok(()), "limits are per client");
+        assert_eq!(limiter.check("a", later(60)), Ok(()));
+    }
+}
@@ -0,0 +1,112 @@
+//! JSON webhook notifications.
+
+use std::future::Future;
+
+use eyre::Context as _;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::verification_report::unix_now;
+use crate::error::ZairResult;
+
+/// How a command finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandOutcome {
+    /// The command succeeded.
+    Completed,
+    /// The command failed.
+    Failed,
+}
+
+/// Event POSTed to a notification URL when a long-running command finishes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandEvent {
+    /// How the command finished.
+    pub event: CommandOutcome,
+    /// The command, such as `config build`.
+    pub command: String,
+    /// Unix time the command started, in seconds.
+    pub started_at: u64,
+    /// Unix time the command finished, in seconds.
+    pub finished_at: u64,
+    /// Error code of a failed command, as in [`ZairError::code`](crate::error::ZairError::code).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// Error message of a failed command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Run `command` and POST a [`CommandEvent`] with its outcome to `notify_url`, if set.
+///
+/// A notification that cannot be sent is logged and does not change the result of the command.
+///
+/// # Errors
+/// Returns the error of `command`.
+pub async fn run_with_notification(
+    notify_url: Option<String>,
+    name: &str,
+    command: impl Future<Output = ZairResult<()>>,
+) -> ZairResult<()> {
+    let Some(url) = notify_url else {
+        return command.await;
+    };
+    let started_at = unix_now().unwrap_or_default();
+    let result = command.await;
+    let event = CommandEvent {
+        event: if result.is_ok() {
+            CommandOutcome::Completed
+        } else {
+            CommandOutcome::Failed
+        },
+        command: name.to_owned(),
+        started_at,
+        finished_at: unix_now().unwrap_or_default(),
+        error_code: result.as_ref().err().map(|e| e.code().to_owned()),
+        error: result.as_ref().err().map(|e| format!("{:#}", e.report())),
+    };
+    if let Err(e) = post_event(&url, &event).await {
+        warn!(error = %e, "Failed to send the completion notification");
+    }
+    result
+}
+
+/// POST `event` as JSON to `url`.
+///
+/// # Errors
+/// Returns an error if the event cannot be serialized or the request fails.
+pub(crate) async fn post_event(url: &str, event: &impl Serialize) -> eyre::Result<()> {
+    let body = serde_json::to_vec(event)?;
+    let url = url.to_owned();
+    tokio::task::spawn_blocking(move || {
+        ureq::post(&url)
+            .header("Content-Type", "application/json")
+            .send(body.as_slice())
+            .with_context(|| format!("Failed to notify {url}"))?;
+        info!(url, "Webhook notified");
+        Ok(())
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completed_events_omit_the_error() {
+        let event = CommandEvent {
+            event: CommandOutcome::Completed,
+            command: "config build".to_owned(),
+            started_at: 1,
+            finished_at: 2,
+            error_code: None,
+            error: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).expect("serialize"),
+            r#"{"event":"completed","command":"config build","started_at":1,"finished_at":2}"#
+        );
+    }
+}
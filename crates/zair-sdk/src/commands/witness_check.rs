@@ -0,0 +1,197 @@
+//! Self-check of claim witnesses before proving.
+//!
+//! A witness that does not match the airdrop configuration only fails inside the prover, as an
+//! unsatisfied constraint that names no cause. Recomputing the tree roots and nullifiers natively
+//! first tells the claimant whether the snapshot is stale, the gap does not hold the note, or the
+//! claims were derived for another configuration.
+
+use eyre::{Context as _, ContextCompat as _, ensure};
+use zair_core::base::{Nullifier, Pool};
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::proof_inputs::{ClaimInput, OrchardPrivateInputs, SaplingPrivateInputs};
+use zair_nonmembership::{MerklePathError, orchard_gap_root, verify_sapling_non_membership};
+use zair_prepare::{sapling_note, verify_sapling_note_root};
+use zair_scan::SaplingViewingKeys;
+use zair_scan::user_nullifiers::{NoteNullifier as _, SaplingHidingFactor, SaplingNote};
+
+/// Check a Sapling claim witness against the airdrop configuration.
+///
+/// # Errors
+/// Returns an error naming the mismatch if the claim was prepared for another configuration, the
+/// note or gap does not hash to the configured roots, or the note nullifier is outside the gap.
+pub(super) fn check_sapling_witness(
+    claim: &ClaimInput<SaplingPrivateInputs>,
+    airdrop_config: &AirdropConfiguration,
+    keys: &SaplingViewingKeys,
+) -> eyre::Result<()> {
+    let sapling = airdrop_config
+        .sapling
+        .as_ref()
+        .context("Sapling claims present but airdrop configuration has no sapling pool")?;
+    let inputs = &claim.private_inputs;
+
+    verify_sapling_note_root(inputs, sapling.note_commitment_root).map_err(|e| {
+        eyre::eyre!(
+            "Stale snapshot: {e}; the claims were prepared against another note commitment tree \
+             than the airdrop configuration"
+        )
+    })?;
+    let note = SaplingNote {
+        note: sapling_note(inputs)?,
+        position: inputs.note_commitment_position,
+        scope: inputs.scope.into(),
+    };
+    let nullifier = note.nullifier(keys);
+    verify_sapling_non_membership(
+        sapling.nullifier_gap_root,
+        &nullifier,
+        &inputs.nullifier_gap_left_bound,
+        &inputs.nullifier_gap_right_bound,
+        inputs.nullifier_gap_position,
+        &inputs.nullifier_gap_merkle_path.concat(),
+    )
+    .map_err(|e| {
+        non_membership_error(
+            e,
+            Pool::Sapling,
+            &nullifier,
+            &inputs.nullifier_gap_left_bound,
+            &inputs.nullifier_gap_right_bound,
+            sapling.nullifier_gap_root,
+        )
+    })?;
+
+    let airdrop_nullifier = note
+        .hiding_nullifier(
+            keys,
+            &SaplingHidingFactor {
+                personalization: sapling.target_id.as_bytes(),
+            },
+        )
+        .context("Invalid Sapling target ID")?;
+    ensure!(
+        airdrop_nullifier == claim.public_inputs.airdrop_nullifier,
+        "Wrong scheme: the airdrop nullifier was not derived with the Sapling target ID {:?} of the \
+         airdrop configuration",
+        sapling.target_id
+    );
+    check_claim_id(Pool::Sapling, claim, airdrop_config)
+}
+
+/// Check an Orchard claim witness against the airdrop configuration.
+///
+/// The Orchard note commitment and nullifier need the note's diversifier, which the claim inputs
+/// do not carry, so only the claim binding and the gap are checked here.
+///
+/// # Errors
+/// Returns an error naming the mismatch if the claim was prepared for another configuration or
+/// the gap does not hash to the configured root.
+pub(super) fn check_orchard_witness(
+    claim: &ClaimInput<OrchardPrivateInputs>,
+    airdrop_config: &AirdropConfiguration,
+) -> eyre::Result<()> {
+    let orchard = airdrop_config
+        .orchard
+        .as_ref()
+        .context("Orchard claims present but airdrop configuration has no orchard pool")?;
+    let inputs = &claim.private_inputs;
+
+    check_gap_root(
+        Pool::Orchard,
+        orchard_gap_root(
+            &inputs.nullifier_gap_left_bound,
+            &inputs.nullifier_gap_right_bound,
+            inputs.nullifier_gap_position,
+            &inputs.nullifier_gap_merkle_path,
+        )?,
+        orchard.nullifier_gap_root,
+    )?;
+    check_claim_id(Pool::Orchard, claim, airdrop_config)
+}
+
+fn check_claim_id<P>(
+    pool: Pool,
+    claim: &ClaimInput<P>,
+    airdrop_config: &AirdropConfiguration,
+) -> eyre::Result<()> {
+    let expected = airdrop_config.claim_id(pool, &claim.public_inputs.airdrop_nullifier)?;
+    ensure!(
+        claim.claim_id == expected,
+        "Wrong configuration: the claim ID does not match the {pool:?} target ID and gap root of \
+         the airdrop configuration; the claims were prepared for another airdrop"
+    );
+    Ok(())
+}
+
+fn check_gap_root(pool: Pool, computed: [u8; 32], configured: [u8; 32]) -> eyre::Result<()> {
+    ensure!(
+        computed == configured,
+        "Stale snapshot: the claimed {pool:?} gap hashes to nullifier gap root {}, but the airdrop \
+         configuration has {}; prepare the claims again from the published snapshot",
+        hex::encode(computed),
+        hex::encode(configured)
+    );
+    Ok(())
+}
+
+/// Name the cause of a failed non-membership check for the claimant.
+fn non_membership_error(
+    err: MerklePathError,
+    pool: Pool,
+    nullifier: &Nullifier,
+    left: &Nullifier,
+    right: &Nullifier,
+    configured: [u8; 32],
+) -> eyre::Report {
+    if err == MerklePathError::NullifierNotInGap {
+        eyre::eyre!(
+            "Wrong gap: the note nullifier {nullifier} is not inside the claimed gap ({left}, \
+             {right}); the note was spent before the snapshot or the gap belongs to another note"
+        )
+    } else if err == MerklePathError::RootMismatch {
+        eyre::eyre!(
+            "Stale snapshot: the claimed {pool:?} gap does not hash to nullifier gap root {} of \
+             the airdrop configuration; prepare the claims again from the published snapshot",
+            hex::encode(configured)
+        )
+    } else {
+        eyre::Report::new(err).wrap_err(format!("Invalid {pool:?} non-membership witness"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nullifier_outside_the_gap_names_the_wrong_gap() {
+        let left = Nullifier::from([10_u8; 32]);
+        let right = Nullifier::from([20_u8; 32]);
+        let err = non_membership_error(
+            MerklePathError::NullifierNotInGap,
+            Pool::Sapling,
+            &right,
+            &left,
+            &right,
+            [0_u8; 32],
+        );
+        assert!(err.to_string().starts_with("Wrong gap"), "{err:?}");
+
+        let err = non_membership_error(
+            MerklePathError::RootMismatch,
+            Pool::Sapling,
+            &Nullifier::from([15_u8; 32]),
+            &left,
+            &right,
+            [0_u8; 32],
+        );
+        assert!(err.to_string().starts_with("Stale snapshot"), "{err:?}");
+    }
+
+    #[test]
+    fn gap_root_mismatch_names_a_stale_snapshot() {
+        check_gap_root(Pool::Orchard, [1_u8; 32], [1_u8; 32]).expect("roots match");
+        let err = check_gap_root(Pool::Orchard, [1_u8; 32], [2_u8; 32]).expect_err("roots differ");
+        assert!(err.to_string().starts_with("Stale snapshot"), "{err:?}");
+    }
+}
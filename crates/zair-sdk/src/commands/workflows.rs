@@ -3,38 +3,48 @@
 #[cfg(feature = "prove")]
 mod prove {
     use std::path::{Path, PathBuf};
+    use std::sync::Arc;
 
     use eyre::Context as _;
-    use secrecy::ExposeSecret;
-    use zair_core::schema::config::AirdropConfiguration;
-    use zcash_keys::keys::UnifiedSpendingKey;
-    use zip32::AccountId;
+    use tracing::debug;
+    use zair_core::schema::config::{AirdropConfiguration, ValueCommitmentScheme};
 
-    use super::super::{GapTreeMode, airdrop_claim, generate_claim_proofs, sign_claim_submission};
-    use crate::common::to_zcash_network;
-    use crate::seed::read_seed_file;
+    use super::super::{
+        GapTreeMode, ProofRngSource, airdrop_claim, generate_claim_proofs, sign_claim_submission,
+    };
+    use crate::common::{PoolSelection, configured_network};
+    use crate::error::{ZairError, ZairResult};
+    use crate::paths::{default_setup_input_path, params_dir};
+    use crate::seed::read_account_secret;
+    use crate::stdio;
+
+    // Setup files and messages looked up when `claim run` is not given them.
+    const DEFAULT_SAPLING_PK_FILE: &str = "setup-sapling-pk.params";
+    const DEFAULT_ORCHARD_PARAMS_FILE: &str = "setup-orchard-params.bin";
+    const DEFAULT_MESSAGES_FILE: &str = "claim-messages.json";
 
     async fn derive_ufvk_from_seed(
         seed_file: &Path,
         account_id: u32,
-        airdrop_configuration_file: &Path,
+        airdrop_config: &AirdropConfiguration,
     ) -> eyre::Result<String> {
-        let airdrop_config: AirdropConfiguration =
-            serde_json::from_str(&tokio::fs::read_to_string(airdrop_configuration_file).await?)
-                .context("Failed to parse airdrop configuration JSON")?;
-        let network = to_zcash_network(airdrop_config.network);
-
-        let seed = read_seed_file(seed_file).await?;
+        let network = configured_network(airdrop_config)?;
 
-        let account_id =
-            AccountId::try_from(account_id).map_err(|_| eyre::eyre!("Invalid account"))?;
-        let usk = UnifiedSpendingKey::from_seed(&network, seed.expose_secret(), account_id)
-            .map_err(|e| eyre::eyre!("Failed to derive spending key: {e:?}"))?;
-        let ufvk = usk.to_unified_full_viewing_key();
+        let account_secret = read_account_secret(seed_file).await?;
+        let ufvk = account_secret
+            .spending_key(network, account_id)?
+            .to_unified_full_viewing_key();
         Ok(ufvk.encode(&network))
     }
 
-    /// Run the full claim pipeline: `claim prepare -> claim prove -> claim sign`.
+    /// Run the full claim pipeline: `claim prepare -> claim prove -> claim sign`, for the pools in
+    /// `pool`.
+    ///
+    /// Without `proving_key_file` or `orchard_params_file`, the setup file for the pool's value
+    /// commitment scheme in the configuration is used, as found by
+    /// [`default_setup_input_path`]. Without `message_file` and `messages_file`, the per-claim
+    /// messages are read from `claim-messages.json` in the working directory if it exists. All
+    /// proof randomness is drawn from `rng_source`.
     ///
     /// # Errors
     /// Returns an error if any pipeline step fails.
@@ -57,15 +67,109 @@ mod prove {
         claim_submission_output_file: PathBuf,
         seed_file: PathBuf,
         account_id: u32,
-        proving_key_file: PathBuf,
-        orchard_params_file: PathBuf,
+        proving_key_file: Option<PathBuf>,
+        orchard_params_file: Option<PathBuf>,
+        orchard_params_mode: super::super::OrchardParamsMode,
+        message_file: Option<PathBuf>,
+        messages_file: Option<PathBuf>,
+        expiry_height: Option<u64>,
+        airdrop_configuration_file: PathBuf,
+        rng_source: Arc<dyn ProofRngSource>,
+        pool: PoolSelection,
+    ) -> ZairResult<()> {
+        claim_run_inner(
+            lightwalletd_url,
+            sapling_snapshot_nullifiers,
+            orchard_snapshot_nullifiers,
+            sapling_gap_tree_file,
+            orchard_gap_tree_file,
+            gap_tree_mode,
+            birthday_height,
+            airdrop_claims_output_file,
+            claim_proofs_output_file,
+            claim_secrets_output_file,
+            claim_submission_output_file,
+            seed_file,
+            account_id,
+            proving_key_file,
+            orchard_params_file,
+            orchard_params_mode,
+            message_file,
+            messages_file,
+            expiry_height,
+            airdrop_configuration_file,
+            rng_source,
+            pool,
+        )
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Config))
+    }
+
+    #[allow(
+        clippy::too_many_arguments,
+        clippy::similar_names,
+        reason = "CLI entrypoint parameters"
+    )]
+    async fn claim_run_inner(
+        lightwalletd_url: Option<String>,
+        sapling_snapshot_nullifiers: Option<PathBuf>,
+        orchard_snapshot_nullifiers: Option<PathBuf>,
+        sapling_gap_tree_file: Option<PathBuf>,
+        orchard_gap_tree_file: Option<PathBuf>,
+        gap_tree_mode: GapTreeMode,
+        birthday_height: u64,
+        airdrop_claims_output_file: PathBuf,
+        claim_proofs_output_file: PathBuf,
+        claim_secrets_output_file: PathBuf,
+        claim_submission_output_file: PathBuf,
+        seed_file: PathBuf,
+        account_id: u32,
+        proving_key_file: Option<PathBuf>,
+        orchard_params_file: Option<PathBuf>,
         orchard_params_mode: super::super::OrchardParamsMode,
         message_file: Option<PathBuf>,
         messages_file: Option<PathBuf>,
+        expiry_height: Option<u64>,
         airdrop_configuration_file: PathBuf,
+        rng_source: Arc<dyn ProofRngSource>,
+        pool: PoolSelection,
     ) -> eyre::Result<()> {
+        let airdrop_config: AirdropConfiguration =
+            serde_json::from_str(&stdio::read_to_string(&airdrop_configuration_file).await?)
+                .context("Failed to parse airdrop configuration JSON")?;
+        let proving_key_file = proving_key_file.unwrap_or_else(|| {
+            let scheme = airdrop_config
+                .sapling
+                .as_ref()
+                .map_or_else(ValueCommitmentScheme::default, |sapling| {
+                    sapling.value_commitment_scheme
+                });
+            default_setup_input_path(params_dir, DEFAULT_SAPLING_PK_FILE, scheme.as_str())
+        });
+        let orchard_params_file = orchard_params_file.unwrap_or_else(|| {
+            let scheme = airdrop_config
+                .orchard
+                .as_ref()
+                .map_or_else(ValueCommitmentScheme::default, |orchard| {
+                    orchard.value_commitment_scheme
+                });
+            default_setup_input_path(params_dir, DEFAULT_ORCHARD_PARAMS_FILE, scheme.as_str())
+        });
+        let messages_file = if message_file.is_none() && messages_file.is_none() {
+            let default = PathBuf::from(DEFAULT_MESSAGES_FILE);
+            default.exists().then_some(default)
+        } else {
+            messages_file
+        };
+        debug!(
+            sapling_pk = ?proving_key_file,
+            orchard_params = ?orchard_params_file,
+            messages = ?messages_file,
+            "Resolved claim run inputs"
+        );
+
         let unified_full_viewing_key =
-            derive_ufvk_from_seed(&seed_file, account_id, &airdrop_configuration_file).await?;
+            derive_ufvk_from_seed(&seed_file, account_id, &airdrop_config).await?;
 
         airdrop_claim(
             lightwalletd_url,
@@ -78,6 +182,9 @@ mod prove {
             birthday_height,
             airdrop_claims_output_file.clone(),
             airdrop_configuration_file.clone(),
+            pool,
+            None,
+            false,
         )
         .await?;
 
@@ -91,6 +198,11 @@ mod prove {
             orchard_params_mode,
             claim_secrets_output_file.clone(),
             airdrop_configuration_file.clone(),
+            rng_source,
+            false,
+            None,
+            None,
+            pool,
         )
         .await?;
 
@@ -102,9 +214,13 @@ mod prove {
             airdrop_configuration_file,
             message_file,
             messages_file,
+            None,
+            expiry_height,
             claim_submission_output_file,
+            pool,
         )
-        .await
+        .await?;
+        Ok(())
     }
 }
 
@@ -115,18 +231,26 @@ mod verify {
     use zair_core::schema::submission::ClaimSubmission;
 
     use super::super::claim_proofs::{
-        ClaimProofsOutput, OrchardClaimProofResult, SaplingClaimProofResult,
-        verify_claim_proofs_inner,
+        ClaimProofsOutput, ProofFilter, ensure_all_valid, verify_claim_proof_verdicts,
     };
+    use super::super::verification_report::{VerifiedInputs, unix_now, write_verification_report};
     use super::super::verify_claim_submission_signature;
+    use crate::common::PoolSelection;
+    use crate::error::{ZairError, ZairResult};
+    use crate::stdio;
 
     /// Run full verification: `verify proof -> verify signature`.
     ///
+    /// With `current_height`, a submission that expired before it is rejected. With
+    /// `report_file`, the verdicts and the roots and setup they were checked against are written
+    /// there, also when the submission is rejected.
+    ///
     /// # Errors
-    /// Returns an error if either verification step fails.
+    /// Returns an error if either verification step fails or the report cannot be written.
     #[allow(
+        clippy::too_many_arguments,
         clippy::similar_names,
-        reason = "message_file vs messages_file are distinct CLI args"
+        reason = "CLI entrypoint parameters"
     )]
     pub async fn verify_run(
         verifying_key_file: PathBuf,
@@ -136,52 +260,87 @@ mod verify {
         message_file: Option<PathBuf>,
         messages_file: Option<PathBuf>,
         airdrop_configuration_file: PathBuf,
+        current_height: Option<u64>,
+        report_file: Option<PathBuf>,
+    ) -> ZairResult<()> {
+        verify_run_inner(
+            verifying_key_file,
+            orchard_params_file,
+            orchard_params_mode,
+            submission_file,
+            message_file,
+            messages_file,
+            airdrop_configuration_file,
+            current_height,
+            report_file,
+        )
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Verification))
+    }
+
+    #[allow(
+        clippy::too_many_arguments,
+        clippy::similar_names,
+        reason = "CLI entrypoint parameters"
+    )]
+    async fn verify_run_inner(
+        verifying_key_file: PathBuf,
+        orchard_params_file: PathBuf,
+        orchard_params_mode: super::super::OrchardParamsMode,
+        submission_file: PathBuf,
+        message_file: Option<PathBuf>,
+        messages_file: Option<PathBuf>,
+        airdrop_configuration_file: PathBuf,
+        current_height: Option<u64>,
+        report_file: Option<PathBuf>,
     ) -> eyre::Result<()> {
-        verify_claim_submission_signature(
+        let started_at = unix_now()?;
+        let signatures = verify_claim_submission_signature(
             submission_file.clone(),
             message_file,
             messages_file,
             airdrop_configuration_file.clone(),
+            current_height,
+            PoolSelection::Both,
         )
-        .await?;
+        .await
+        .map_err(eyre::Report::from);
 
-        let submission: ClaimSubmission =
-            serde_json::from_str(&tokio::fs::read_to_string(&submission_file).await?)
-                .context("Failed to parse submission JSON")?;
+        let proofs = async {
+            let submission: ClaimSubmission =
+                serde_json::from_str(&stdio::read_to_string(&submission_file).await?)
+                    .context("Failed to parse submission JSON")?;
+            verify_claim_proof_verdicts(
+                ClaimProofsOutput::from(&submission),
+                verifying_key_file.clone(),
+                orchard_params_file.clone(),
+                orchard_params_mode,
+                airdrop_configuration_file.clone(),
+                ProofFilter::default(),
+            )
+            .await
+        }
+        .await;
 
-        let proofs = ClaimProofsOutput {
-            sapling_proofs: submission
-                .sapling
-                .iter()
-                .map(|entry| SaplingClaimProofResult {
-                    zkproof: entry.zkproof,
-                    rk: entry.rk,
-                    cv: entry.cv,
-                    cv_sha256: entry.cv_sha256,
-                    airdrop_nullifier: entry.airdrop_nullifier,
-                })
-                .collect(),
-            orchard_proofs: submission
-                .orchard
-                .iter()
-                .map(|entry| OrchardClaimProofResult {
-                    zkproof: entry.zkproof.clone(),
-                    rk: entry.rk,
-                    cv: entry.cv,
-                    cv_sha256: entry.cv_sha256,
-                    airdrop_nullifier: entry.airdrop_nullifier,
-                })
-                .collect(),
-        };
+        if let Some(report_file) = report_file {
+            write_verification_report(
+                &report_file,
+                &VerifiedInputs {
+                    submission_file: &submission_file,
+                    airdrop_configuration_file: &airdrop_configuration_file,
+                    verifying_key_file: &verifying_key_file,
+                    orchard_params_file: &orchard_params_file,
+                    current_height,
+                },
+                started_at,
+                &signatures,
+                &proofs,
+            )
+            .await?;
+        }
 
-        verify_claim_proofs_inner(
-            proofs,
-            verifying_key_file,
-            orchard_params_file,
-            orchard_params_mode,
-            airdrop_configuration_file,
-        )
-        .await
+        signatures?;
+        ensure_all_valid(&proofs?)
     }
 }
 
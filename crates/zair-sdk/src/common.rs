@@ -1,16 +1,23 @@
 //! CLI-independent configuration types.
-use zair_core::schema::config::AirdropNetwork;
-use zcash_protocol::consensus::Network;
+use std::collections::BTreeMap;
+
+use eyre::Context as _;
+use zair_core::schema::config::{AirdropConfiguration, AirdropNetwork};
+pub use zair_scan::Network;
+pub use zair_scan::light_walletd::Backend;
+pub use zair_scan::network::{CustomNetwork, parse_branch_id};
 
 /// Common configuration for chain access and snapshot selection.
 #[derive(Debug, Clone)]
 pub struct CommonConfig {
-    /// Network to use (mainnet or testnet).
+    /// Network to use (mainnet, testnet or regtest).
     pub network: Network,
     /// Snapshot height (inclusive).
     pub snapshot_height: u64,
     /// Optional lightwalletd gRPC endpoint URL override.
     pub lightwalletd_url: Option<String>,
+    /// Indexer serving the lightwalletd endpoint.
+    pub backend: Backend,
 }
 
 /// Pool selector used by commands that can operate on one or both pools.
@@ -38,28 +45,54 @@ impl PoolSelection {
     }
 }
 
-/// Convert `zcash_protocol` network to config network.
+/// Convert network to config network.
 #[must_use]
 pub const fn to_airdrop_network(network: Network) -> AirdropNetwork {
     match network {
         Network::MainNetwork => AirdropNetwork::Mainnet,
         Network::TestNetwork => AirdropNetwork::Testnet,
+        Network::RegtestNetwork | Network::Custom(_) => AirdropNetwork::Regtest,
     }
 }
 
-/// Convert config network to `zcash_protocol` network.
+/// Custom activation heights of `network`, in the shape stored in the airdrop configuration.
+#[must_use]
+pub fn to_network_upgrades(network: Network) -> Option<BTreeMap<String, u32>> {
+    let Network::Custom(custom) = network else {
+        return None;
+    };
+    Some(
+        custom
+            .activations()
+            .map(|(branch_id, height)| (format!("{branch_id:08x}"), height))
+            .collect(),
+    )
+}
+
+/// Convert config network to network.
 #[must_use]
 pub const fn to_zcash_network(network: AirdropNetwork) -> Network {
     match network {
         AirdropNetwork::Mainnet => Network::MainNetwork,
         AirdropNetwork::Testnet => Network::TestNetwork,
+        AirdropNetwork::Regtest => Network::RegtestNetwork,
     }
 }
 
+/// Network of an airdrop configuration, including the activation heights of a custom devnet.
+///
+/// # Errors
+/// Returns an error if custom activation heights are set on a standard network or are invalid.
+pub fn configured_network(config: &AirdropConfiguration) -> eyre::Result<Network> {
+    Network::from_configuration(config).context("Invalid custom network upgrades in configuration")
+}
+
 /// Default lightwalletd endpoint for mainnet.
 pub const MAINNET_LIGHTWALLETD_URL: &str = "https://zec.rocks:443";
 /// Default lightwalletd endpoint for testnet.
 pub const TESTNET_LIGHTWALLETD_URL: &str = "https://testnet.zec.rocks:443";
+/// Default lightwalletd endpoint for regtest, a local lightwalletd on its default port.
+pub const REGTEST_LIGHTWALLETD_URL: &str = "http://127.0.0.1:9067";
 
 /// Resolve lightwalletd URL from optional CLI override + network defaults.
 #[must_use]
@@ -74,5 +107,6 @@ pub fn resolve_lightwalletd_url(
     match network {
         Network::MainNetwork => MAINNET_LIGHTWALLETD_URL.to_string(),
         Network::TestNetwork => TESTNET_LIGHTWALLETD_URL.to_string(),
+        Network::RegtestNetwork | Network::Custom(_) => REGTEST_LIGHTWALLETD_URL.to_string(),
     }
 }
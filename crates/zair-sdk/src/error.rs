@@ -0,0 +1,184 @@
+//! Error taxonomy for the SDK command entrypoints.
+//!
+//! Commands use `eyre` internally for context-rich messages. At the public boundary every failure
+//! is classified into a [`ZairError`] variant with a stable [`code`](ZairError::code), so
+//! embedding applications can react to a failure category without parsing messages.
+
+use zair_scan::light_walletd::LightWalletdError;
+
+/// Result type returned by the SDK command entrypoints.
+pub type ZairResult<T> = Result<T, ZairError>;
+
+/// Classified failure of an SDK command.
+///
+/// Each variant carries the full `eyre` report, including the context chain.
+#[derive(Debug, thiserror::Error)]
+pub enum ZairError {
+    /// Invalid or unreadable configuration, key material, or input files.
+    #[error("{0:#}")]
+    Config(eyre::Report),
+    /// Lightwalletd or HTTP communication failed.
+    #[error("{0:#}")]
+    Network(eyre::Report),
+    /// Snapshot collection, reconciliation, or non-membership tree construction failed.
+    #[error("{0:#}")]
+    Snapshot(eyre::Report),
+    /// Trusted setup or claim proof generation failed.
+    #[error("{0:#}")]
+    Proving(eyre::Report),
+    /// Claim submission signing failed.
+    #[error("{0:#}")]
+    Signing(eyre::Report),
+    /// Proof or signature verification failed.
+    #[error("{0:#}")]
+    Verification(eyre::Report),
+}
+
+impl ZairError {
+    /// Stable machine-readable code of the failure category.
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::Config(_) => "config",
+            Self::Network(_) => "network",
+            Self::Snapshot(_) => "snapshot",
+            Self::Proving(_) => "proving",
+            Self::Signing(_) => "signing",
+            Self::Verification(_) => "verification",
+        }
+    }
+
+    /// Process exit code of the failure category, stable across releases.
+    ///
+    /// `1` is left for failures outside the SDK, such as a CLI that fails to start.
+    #[must_use]
+    pub const fn exit_code(&self) -> i32 {
+        match self {
+            Self::Config(_) => 2,
+            Self::Network(_) => 3,
+            Self::Proving(_) => 4,
+            Self::Verification(_) => 5,
+            Self::Snapshot(_) => 6,
+            Self::Signing(_) => 7,
+        }
+    }
+
+    /// Underlying error report.
+    #[must_use]
+    pub const fn report(&self) -> &eyre::Report {
+        match self {
+            Self::Config(report) |
+            Self::Network(report) |
+            Self::Snapshot(report) |
+            Self::Proving(report) |
+            Self::Signing(report) |
+            Self::Verification(report) => report,
+        }
+    }
+
+    /// Consume the error and return the underlying report.
+    #[must_use]
+    pub fn into_report(self) -> eyre::Report {
+        match self {
+            Self::Config(report) |
+            Self::Network(report) |
+            Self::Snapshot(report) |
+            Self::Proving(report) |
+            Self::Signing(report) |
+            Self::Verification(report) => report,
+        }
+    }
+
+    const fn variant(&self) -> fn(eyre::Report) -> Self {
+        match self {
+            Self::Config(_) => Self::Config,
+            Self::Network(_) => Self::Network,
+            Self::Snapshot(_) => Self::Snapshot,
+            Self::Proving(_) => Self::Proving,
+            Self::Signing(_) => Self::Signing,
+            Self::Verification(_) => Self::Verification,
+        }
+    }
+
+    /// Classify an internal report raised while running a command of the `stage` category.
+    ///
+    /// A classified error or a lightwalletd/HTTP failure anywhere in the chain takes precedence,
+    /// and malformed JSON inputs are reported as configuration errors.
+    pub(crate) fn classify(report: eyre::Report, stage: fn(eyre::Report) -> Self) -> Self {
+        let mut variant = stage;
+        for cause in report.chain() {
+            if let Some(classified) = cause.downcast_ref::<Self>() {
+                variant = classified.variant();
+                break;
+            }
+            if cause.is::<LightWalletdError>() || cause.is::<ureq::Error>() {
+                variant = Self::Network;
+                break;
+            }
+            if cause.is::<serde_json::Error>() {
+                variant = Self::Config;
+                break;
+            }
+        }
+        variant(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::{Context as _, eyre};
+
+    use super::*;
+
+    #[test]
+    fn stage_is_used_for_unclassified_errors() {
+        let err = ZairError::classify(eyre!("proof failed"), ZairError::Proving);
+        assert_eq!(err.code(), "proving");
+        assert_eq!(err.exit_code(), 4);
+        assert_eq!(err.to_string(), "proof failed");
+    }
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let errors = [
+            ZairError::Config(eyre!("")),
+            ZairError::Network(eyre!("")),
+            ZairError::Snapshot(eyre!("")),
+            ZairError::Proving(eyre!("")),
+            ZairError::Signing(eyre!("")),
+            ZairError::Verification(eyre!("")),
+        ];
+        let codes: std::collections::HashSet<i32> =
+            errors.iter().map(ZairError::exit_code).collect();
+        assert_eq!(codes.len(), errors.len());
+        // 0 is success and 1 is left for failures outside the SDK.
+        assert!(codes.iter().all(|&code| code > 1));
+    }
+
+    #[test]
+    fn chain_causes_override_stage() {
+        let json_err = serde_json::from_str::<u32>("nope").expect_err("invalid json");
+        let report = Err::<(), _>(json_err)
+            .context("Failed to parse claims JSON")
+            .expect_err("context error");
+        assert_eq!(
+            ZairError::classify(report, ZairError::Proving).code(),
+            "config"
+        );
+
+        let report = eyre::Report::new(LightWalletdError::ChannelClosed).wrap_err("scan failed");
+        assert_eq!(
+            ZairError::classify(report, ZairError::Snapshot).code(),
+            "network"
+        );
+    }
+
+    #[test]
+    fn nested_classification_is_preserved() {
+        let inner = ZairError::Signing(eyre!("bad key"));
+        let report = eyre::Report::new(inner).wrap_err("claim run failed");
+        let err = ZairError::classify(report, ZairError::Proving);
+        assert_eq!(err.code(), "signing");
+        assert!(err.to_string().contains("claim run failed"));
+    }
+}
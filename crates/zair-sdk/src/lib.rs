@@ -1,7 +1,16 @@
 //! ZAIR SDK/workflow library.
 
+pub mod audit;
+pub mod build_info;
 pub mod commands;
 pub mod common;
+pub mod error;
 pub mod network_params;
+pub mod paths;
+pub mod pipeline;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod stdio;
+pub mod telemetry;
 
 mod seed;
@@ -1,6 +1,8 @@
 //! Network/pool activation parameters shared across workflows.
 
-use zcash_protocol::consensus::Network;
+use zair_scan::Network;
+use zair_scan::network::CustomNetwork;
+use zcash_protocol::consensus::NetworkUpgrade;
 
 use crate::common::PoolSelection;
 
@@ -12,31 +14,46 @@ pub const SAPLING_TESTNET_START: u64 = 280_000;
 pub const ORCHARD_MAINNET_START: u64 = 1_687_104;
 /// Orchard activation height on testnet, see [ZIP 252](https://zips.z.cash/zip-0252).
 pub const ORCHARD_TESTNET_START: u64 = 1_842_420;
+/// Sapling and Orchard activation height on regtest, see
+/// [`REGTEST_ACTIVATION_HEIGHT`](zair_scan::network::REGTEST_ACTIVATION_HEIGHT).
+pub const REGTEST_START: u64 = 1;
 
 /// Sapling activation height for the given network.
 #[must_use]
-pub const fn sapling_activation_height(network: Network) -> u64 {
+pub fn sapling_activation_height(network: Network) -> u64 {
     match network {
         Network::MainNetwork => SAPLING_MAINNET_START,
         Network::TestNetwork => SAPLING_TESTNET_START,
+        Network::RegtestNetwork => REGTEST_START,
+        Network::Custom(custom) => custom_start(custom, NetworkUpgrade::Sapling),
     }
 }
 
 /// Orchard activation height for the given network.
 #[must_use]
-pub const fn orchard_activation_height(network: Network) -> u64 {
+pub fn orchard_activation_height(network: Network) -> u64 {
     match network {
         Network::MainNetwork => ORCHARD_MAINNET_START,
         Network::TestNetwork => ORCHARD_TESTNET_START,
+        Network::RegtestNetwork => REGTEST_START,
+        Network::Custom(custom) => custom_start(custom, NetworkUpgrade::Nu5),
     }
 }
 
 /// Activation-aware scan start height for a selected pool.
 #[must_use]
-pub const fn scan_start_height(network: Network, pool: PoolSelection) -> u64 {
+pub fn scan_start_height(network: Network, pool: PoolSelection) -> u64 {
     match pool {
         // Sapling always activates before Orchard on all networks.
         PoolSelection::Sapling | PoolSelection::Both => sapling_activation_height(network),
         PoolSelection::Orchard => orchard_activation_height(network),
     }
 }
+
+/// Activation height of `nu` on a custom network. Custom networks always activate NU5 and the
+/// upgrades before it.
+fn custom_start(custom: CustomNetwork, nu: NetworkUpgrade) -> u64 {
+    custom
+        .activation_height(nu)
+        .map_or(REGTEST_START, |height| u64::from(u32::from(height)))
+}
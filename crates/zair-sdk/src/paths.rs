@@ -0,0 +1,355 @@
+//! Standard on-disk locations for ZAIR artifacts.
+//!
+//! Large artifacts (setup params, snapshots, gap trees) default to the platform data directory
+//! (e.g. `~/.local/share/zair` on Linux, honouring `XDG_DATA_HOME`) instead of the current
+//! working directory. Files already present in the working directory keep taking precedence when
+//! read, so existing setups continue to work unchanged.
+//!
+//! Outputs are written to a `.partial` file next to their final path, flushed to disk and renamed
+//! into place once complete, so neither an interrupted command nor a crash leaves a truncated file
+//! under the final name for a later stage to consume.
+
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use eyre::{Context as _, ContextCompat as _};
+
+use crate::audit;
+
+/// Application directory name under the platform data and cache directories.
+const APP_DIR: &str = "zair";
+
+/// Extension appended to an output while it is being written.
+pub const PARTIAL_EXTENSION: &str = "partial";
+
+/// `.partial` outputs of this process that are not complete yet.
+static PARTIAL_OUTPUTS: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+/// Platform data directory for ZAIR (e.g. `~/.local/share/zair` on Linux).
+///
+/// # Errors
+/// Returns an error if the platform has no data directory (e.g. `HOME` is unset).
+pub fn data_dir() -> eyre::Result<PathBuf> {
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_DIR))
+        .context("Could not determine the platform data directory; pass an explicit path")
+}
+
+/// Platform cache directory for ZAIR (e.g. `~/.cache/zair` on Linux).
+///
+/// # Errors
+/// Returns an error if the platform has no cache directory.
+pub fn cache_dir() -> eyre::Result<PathBuf> {
+    dirs::cache_dir()
+        .map(|dir| dir.join(APP_DIR))
+        .context("Could not determine the platform cache directory; pass an explicit path")
+}
+
+/// Directory for proving/verifying parameters.
+///
+/// # Errors
+/// Returns an error if the platform has no data directory.
+pub fn params_dir() -> eyre::Result<PathBuf> {
+    Ok(data_dir()?.join("params"))
+}
+
+/// Directory for snapshot nullifier files.
+///
+/// # Errors
+/// Returns an error if the platform has no data directory.
+pub fn snapshots_dir() -> eyre::Result<PathBuf> {
+    Ok(data_dir()?.join("snapshots"))
+}
+
+/// Directory for gap-tree files.
+///
+/// # Errors
+/// Returns an error if the platform has no data directory.
+pub fn gap_trees_dir() -> eyre::Result<PathBuf> {
+    Ok(data_dir()?.join("gaptrees"))
+}
+
+/// Default path for a file that is written: `file_name` inside `dir`.
+///
+/// Falls back to `file_name` in the working directory if `dir` cannot be resolved.
+#[must_use]
+pub fn default_output_path(dir: fn() -> eyre::Result<PathBuf>, file_name: &str) -> PathBuf {
+    dir().map_or_else(|_| PathBuf::from(file_name), |dir| dir.join(file_name))
+}
+
+/// Default path for a file that is read: `file_name` in the working directory if it exists there,
+/// otherwise [`default_output_path`].
+#[must_use]
+pub fn default_input_path(dir: fn() -> eyre::Result<PathBuf>, file_name: &str) -> PathBuf {
+    let local = PathBuf::from(file_name);
+    if local.exists() {
+        local
+    } else {
+        default_output_path(dir, file_name)
+    }
+}
+
+/// Name of a setup file generated for one value commitment scheme: `file_name` with `-<scheme>`
+/// before its extension, such as `setup-sapling-pk-sha256.params`.
+#[must_use]
+pub fn scheme_file_name(file_name: &str, scheme: &str) -> String {
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{stem}-{scheme}.{extension}"),
+        None => format!("{file_name}-{scheme}"),
+    }
+}
+
+/// Default path for a setup file that is read for the value commitment `scheme`: the
+/// [`scheme_file_name`] if that file exists in the working directory or `dir`, otherwise
+/// [`default_input_path`] of `file_name`.
+///
+/// Organizers with several schemes keep one file per scheme; with a single scheme, the plain
+/// name works as before.
+#[must_use]
+pub fn default_setup_input_path(
+    dir: fn() -> eyre::Result<PathBuf>,
+    file_name: &str,
+    scheme: &str,
+) -> PathBuf {
+    let scheme_path = default_input_path(dir, &scheme_file_name(file_name, scheme));
+    if scheme_path.exists() {
+        scheme_path
+    } else {
+        default_input_path(dir, file_name)
+    }
+}
+
+/// Height-indexed sidecar of the snapshot file `snapshot`: the same path with an `idx` extension.
+#[must_use]
+pub fn indexed_snapshot_path(snapshot: &Path) -> PathBuf {
+    snapshot.with_extension("idx")
+}
+
+/// Nullifier filter sidecar of the snapshot file `snapshot`: the same path with a `bloom`
+/// extension.
+#[must_use]
+pub fn nullifier_filter_path(snapshot: &Path) -> PathBuf {
+    snapshot.with_extension("bloom")
+}
+
+/// Start writing the output `path`: the `.partial` path to write it to instead.
+///
+/// The partial file is tracked until [`finish_partial`] moves it into place, so an interrupted
+/// command can remove it with [`remove_partial_outputs`].
+#[must_use]
+pub fn begin_partial(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(PARTIAL_EXTENSION);
+    let partial = PathBuf::from(name);
+    if let Ok(mut outputs) = PARTIAL_OUTPUTS.lock() {
+        outputs.insert(partial.clone());
+    }
+    partial
+}
+
+/// Move the complete output `partial`, from [`begin_partial`], to `path`.
+///
+/// The contents are flushed to disk before the rename, so after a crash `path` holds either the
+/// previous file or the complete new one.
+///
+/// # Errors
+/// Returns an error if the file cannot be flushed or renamed.
+pub fn finish_partial(partial: &Path, path: &Path) -> io::Result<()> {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(partial)?
+        .sync_all()?;
+    std::fs::rename(partial, path)?;
+    sync_parent_dir(path);
+    audit::record_output(path);
+    if let Ok(mut outputs) = PARTIAL_OUTPUTS.lock() {
+        outputs.remove(partial);
+    }
+    Ok(())
+}
+
+/// Flush the directory entry of a renamed file. Best effort: not every file system can sync a
+/// directory.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        Some(_) | None => Path::new("."),
+    };
+    let _ = std::fs::File::open(parent).and_then(|dir| dir.sync_all());
+}
+
+/// Directories cannot be opened for syncing off Unix; the rename is left to the file system.
+#[cfg(not(unix))]
+const fn sync_parent_dir(_path: &Path) {}
+
+/// Write `contents` to `path` through a `.partial` file, so `path` only ever holds complete
+/// contents. Special files such as `/dev/stdout` or a named pipe are written directly.
+///
+/// # Errors
+/// Returns an error if the file cannot be written or renamed.
+pub async fn write_complete(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if tokio::fs::metadata(path)
+        .await
+        .is_ok_and(|meta| !meta.is_file())
+    {
+        return tokio::fs::write(path, contents).await;
+    }
+    let partial = begin_partial(path);
+    tokio::fs::write(&partial, contents).await?;
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || finish_partial(&partial, &path)).await?
+}
+
+/// Blocking [`write_complete`].
+///
+/// # Errors
+/// Returns an error if the file cannot be written or renamed.
+pub fn write_complete_blocking(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if std::fs::metadata(path).is_ok_and(|meta| !meta.is_file()) {
+        return std::fs::write(path, contents);
+    }
+    let partial = begin_partial(path);
+    std::fs::write(&partial, contents)?;
+    finish_partial(&partial, path)
+}
+
+/// Remove the `.partial` outputs that were not finished, after a command is interrupted or
+/// fails. Returns the removed files.
+pub fn remove_partial_outputs() -> Vec<PathBuf> {
+    let outputs = PARTIAL_OUTPUTS
+        .lock()
+        .map(|mut outputs| std::mem::take(&mut *outputs))
+        .unwrap_or_default();
+    outputs
+        .into_iter()
+        .filter(|partial| std::fs::remove_file(partial).is_ok())
+        .collect()
+}
+
+/// Create the parent directory of `path` if it does not exist yet.
+///
+/// # Errors
+/// Returns an error if the directory cannot be created.
+pub async fn ensure_parent_dir(path: &Path) -> eyre::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => tokio::fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create directory {}", parent.display())),
+        Some(_) | None => Ok(()),
+    }
+}
+
+/// All standard directories, as printed by `zair paths`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StandardPaths {
+    /// Root data directory.
+    pub data: PathBuf,
+    /// Root cache directory.
+    pub cache: PathBuf,
+    /// Setup parameters.
+    pub params: PathBuf,
+    /// Snapshot nullifier files.
+    pub snapshots: PathBuf,
+    /// Gap-tree files.
+    pub gap_trees: PathBuf,
+}
+
+impl StandardPaths {
+    /// Resolve the standard directories for the current platform and user.
+    ///
+    /// # Errors
+    /// Returns an error if the platform has no data or cache directory.
+    pub fn resolve() -> eyre::Result<Self> {
+        Ok(Self {
+            data: data_dir()?,
+            cache: cache_dir()?,
+            params: params_dir()?,
+            snapshots: snapshots_dir()?,
+            gap_trees: gap_trees_dir()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn outputs_appear_only_when_complete() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let output = dir.path().join("gaptree-sapling.bin");
+
+        let partial = begin_partial(&output);
+        assert_eq!(partial, dir.path().join("gaptree-sapling.bin.partial"));
+        std::fs::write(&partial, [2_u8; 16]).expect("write partial");
+        assert!(!output.exists());
+        finish_partial(&partial, &output).expect("finish");
+        assert!(!partial.exists());
+
+        write_complete(&output, [1_u8; 32]).await.expect("replace");
+        assert_eq!(std::fs::read(&output).expect("read"), [1_u8; 32]);
+        write_complete_blocking(&output, [3_u8; 8]).expect("replace again");
+        assert_eq!(std::fs::read(&output).expect("read"), [3_u8; 8]);
+        assert!(!partial.exists());
+    }
+
+    fn fixed_dir() -> eyre::Result<PathBuf> {
+        Ok(PathBuf::from("/zair-data/params"))
+    }
+
+    fn missing_dir() -> eyre::Result<PathBuf> {
+        Err(eyre::eyre!("no data dir"))
+    }
+
+    #[test]
+    fn output_path_uses_standard_dir() {
+        assert_eq!(
+            default_output_path(fixed_dir, "setup-sapling-pk.params"),
+            PathBuf::from("/zair-data/params/setup-sapling-pk.params")
+        );
+        assert_eq!(
+            default_output_path(missing_dir, "setup-sapling-pk.params"),
+            PathBuf::from("setup-sapling-pk.params")
+        );
+    }
+
+    #[test]
+    fn input_path_prefers_existing_local_file() {
+        // `Cargo.toml` exists in the crate directory tests run from.
+        assert_eq!(
+            default_input_path(fixed_dir, "Cargo.toml"),
+            PathBuf::from("Cargo.toml")
+        );
+        assert_eq!(
+            default_input_path(fixed_dir, "zair-missing-file.bin"),
+            PathBuf::from("/zair-data/params/zair-missing-file.bin")
+        );
+    }
+
+    #[test]
+    fn setup_path_falls_back_to_the_plain_name() {
+        assert_eq!(
+            scheme_file_name("setup-sapling-pk.params", "sha256"),
+            "setup-sapling-pk-sha256.params"
+        );
+        assert_eq!(scheme_file_name("params", "native"), "params-native");
+        assert_eq!(
+            default_setup_input_path(fixed_dir, "setup-sapling-pk.params", "sha256"),
+            PathBuf::from("/zair-data/params/setup-sapling-pk.params")
+        );
+    }
+
+    #[test]
+    fn standard_dirs_share_data_root() {
+        let Ok(paths) = StandardPaths::resolve() else {
+            return;
+        };
+        for dir in [&paths.params, &paths.snapshots, &paths.gap_trees] {
+            assert!(dir.starts_with(&paths.data));
+        }
+    }
+}
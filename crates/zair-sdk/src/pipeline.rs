@@ -0,0 +1,310 @@
+//! In-memory claim pipeline.
+//!
+//! Each stage (prepare, prove, sign, verify) takes and returns plain structs and never touches the
+//! filesystem, so wallets can embed the pipeline directly. The file-based
+//! [`commands`](crate::commands) used by the CLI are thin wrappers that load their inputs, call
+//! these functions, and write the results.
+//!
+//! Only `prepare_claims` performs I/O: it scans the chain through lightwalletd.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use bellman::groth16::VerifyingKey;
+use bls12_381::Bls12;
+use halo2_proofs::poly::commitment::Params;
+use pasta_curves::vesta;
+use secrecy::SecretBox;
+pub use zair_core::base::Nullifier;
+use zair_core::base::{SanitiseNullifiers, hash_message};
+pub use zair_core::schema::config::AirdropConfiguration;
+pub use zair_core::schema::proof_inputs::AirdropClaimInputs;
+pub use zair_core::schema::submission::ClaimSubmission;
+#[cfg(feature = "prove")]
+use zair_sapling_proofs::prover::ClaimParameters;
+
+#[cfg(feature = "deterministic-rng")]
+pub use crate::commands::DeterministicProofRng;
+pub use crate::commands::{
+    ClaimProofsOutput, ClaimSecretsOutput, FailedClaimProof, OrchardClaimProofResult,
+    OrchardClaimSecretResult, SaplingClaimProofResult, SaplingClaimSecretResult, ValueBlinding,
+};
+#[cfg(feature = "prove")]
+pub use crate::commands::{ClaimRng, OsProofRng, ProofRngSource};
+use crate::commands::{
+    PoolTreeSource, ResolvedMessageHashes, prepare_claims_inner, sign_submission_inner,
+    verify_proofs_inner, verify_submission_signatures_inner,
+};
+#[cfg(feature = "prove")]
+use crate::commands::{ProvingKeys, prove_claims_inner};
+#[cfg(feature = "prove")]
+use crate::common::configured_network;
+use crate::error::{ZairError, ZairResult};
+use crate::seed::AccountSecret;
+#[cfg(feature = "prove")]
+use crate::seed::spending_key_from_seed;
+
+/// Trusted-setup artifacts needed to generate claim proofs.
+///
+/// Only the pools that have claims need their artifact.
+#[cfg(feature = "prove")]
+#[derive(Clone, Default)]
+pub struct ProvingParams {
+    /// Sapling Groth16 proving parameters.
+    pub sapling: Option<Arc<ClaimParameters>>,
+    /// Orchard Halo2 params.
+    pub orchard: Option<Arc<Params<vesta::Affine>>>,
+}
+
+/// Trusted-setup artifacts needed to verify claim proofs.
+///
+/// Only the pools that have proofs need their artifact.
+#[derive(Clone, Default)]
+pub struct VerifyingParams {
+    /// Sapling Groth16 verifying key.
+    pub sapling_verifying_key: Option<VerifyingKey<Bls12>>,
+    /// Orchard Halo2 params.
+    pub orchard_params: Option<Arc<Params<vesta::Affine>>>,
+}
+
+/// Messages bound into the claim signatures.
+///
+/// Per-claim messages override the shared message for matching airdrop nullifiers.
+#[derive(Debug, Clone, Default)]
+pub struct ClaimMessages {
+    /// Message signed by claims without a per-claim entry.
+    pub shared: Option<Vec<u8>>,
+    /// Per-claim Sapling messages by airdrop nullifier.
+    pub sapling: BTreeMap<Nullifier, Vec<u8>>,
+    /// Per-claim Orchard messages by airdrop nullifier.
+    pub orchard: BTreeMap<Nullifier, Vec<u8>>,
+}
+
+impl ClaimMessages {
+    /// Sign the same message for every claim.
+    #[must_use]
+    pub fn shared(message: impl Into<Vec<u8>>) -> Self {
+        Self {
+            shared: Some(message.into()),
+            ..Self::default()
+        }
+    }
+
+    fn hashes(&self) -> ResolvedMessageHashes {
+        let hash_all = |messages: &BTreeMap<Nullifier, Vec<u8>>| {
+            messages
+                .iter()
+                .map(|(nullifier, message)| (*nullifier, hash_message(message)))
+                .collect()
+        };
+        ResolvedMessageHashes::new(
+            self.shared.as_deref().map(hash_message),
+            hash_all(&self.sapling),
+            hash_all(&self.orchard),
+        )
+    }
+}
+
+/// Scan the chain for the notes of `unified_full_viewing_key` and build the claim inputs.
+///
+/// The snapshot nullifiers of every pool enabled in `config` are required; the non-membership
+/// trees are built in memory from them. `lightwalletd_url` defaults to the network's public
+/// endpoint.
+///
+/// # Errors
+/// Returns an error if the key is invalid, scanning fails, a snapshot is missing, or a tree root
+/// does not match the configuration.
+pub async fn prepare_claims(
+    config: &AirdropConfiguration,
+    unified_full_viewing_key: &str,
+    birthday_height: u64,
+    lightwalletd_url: Option<String>,
+    sapling_snapshot: Option<Vec<Nullifier>>,
+    orchard_snapshot: Option<Vec<Nullifier>>,
+) -> ZairResult<AirdropClaimInputs> {
+    let source = |nullifiers: Vec<Nullifier>| {
+        PoolTreeSource::Nullifiers(SanitiseNullifiers::new(nullifiers))
+    };
+    prepare_claims_inner(
+        lightwalletd_url,
+        config,
+        unified_full_viewing_key,
+        birthday_height,
+        sapling_snapshot.map(source),
+        orchard_snapshot.map(source),
+        false,
+        Arc::new(|_| {}),
+    )
+    .await
+    .map_err(|e| ZairError::classify(e, ZairError::Snapshot))
+}
+
+/// Generate claim proofs and the local-only secrets needed to sign them.
+///
+/// All claim randomness is drawn from `rng_source`: [`OsProofRng`] unless the caller has a
+/// hardware RNG to plug in.
+///
+/// A claim that fails to prove is listed in the `failed_claims` of the output, and the other
+/// claims are still proven.
+///
+/// # Errors
+/// Returns an error if the seed does not match the claims, a setup artifact is missing, or every
+/// claim fails to prove.
+#[cfg(feature = "prove")]
+pub async fn prove_claims(
+    claims: AirdropClaimInputs,
+    config: &AirdropConfiguration,
+    seed: &[u8; 64],
+    account_id: u32,
+    params: &ProvingParams,
+    rng_source: Arc<dyn ProofRngSource>,
+) -> ZairResult<(ClaimProofsOutput, ClaimSecretsOutput)> {
+    let classify = |e| ZairError::classify(e, ZairError::Proving);
+    let network = configured_network(config).map_err(ZairError::Config)?;
+    let usk = spending_key_from_seed(network, seed, account_id).map_err(classify)?;
+    let keys = ProvingKeys::from_spending_key(&usk);
+    let (proofs, secrets) = prove_claims_inner(claims, config, &keys, params, &rng_source)
+        .await
+        .map_err(classify)?;
+    proofs.ensure_any_proven().map_err(classify)?;
+    Ok((proofs, secrets))
+}
+
+/// Sign claim proofs into a submission package that expires at `expiry_height`, or at the end of
+/// the configured claim window if `None`.
+///
+/// # Errors
+/// Returns an error if proofs and secrets do not match, a message or expiry height is missing, or
+/// signing fails.
+pub fn sign_submission(
+    proofs: &ClaimProofsOutput,
+    secrets: ClaimSecretsOutput,
+    config: &AirdropConfiguration,
+    seed: &[u8; 64],
+    account_id: u32,
+    messages: &ClaimMessages,
+    expiry_height: Option<u64>,
+) -> ZairResult<ClaimSubmission> {
+    sign_submission_inner(
+        proofs,
+        secrets,
+        config,
+        &AccountSecret::Seed(SecretBox::new(Box::new(*seed))),
+        account_id,
+        &messages.hashes(),
+        expiry_height,
+    )
+    .map_err(|e| ZairError::classify(e, ZairError::Signing))
+}
+
+/// Verify claim proofs against the configuration and trusted setup.
+///
+/// # Errors
+/// Returns an error if a setup artifact is missing or does not match the configuration, or any
+/// proof is invalid.
+pub async fn verify_proofs(
+    proofs: ClaimProofsOutput,
+    config: &AirdropConfiguration,
+    params: &VerifyingParams,
+) -> ZairResult<()> {
+    verify_proofs_inner(proofs, config, params)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+/// Verify the spend-auth signatures and message bindings of a submission.
+///
+/// With `current_height`, a submission whose expiry height is below it is rejected.
+///
+/// # Errors
+/// Returns an error if the submission has expired, a proof or message hash does not match, or any
+/// signature is invalid.
+pub fn verify_submission_signatures(
+    submission: &ClaimSubmission,
+    config: &AirdropConfiguration,
+    messages: &ClaimMessages,
+    current_height: Option<u64>,
+) -> ZairResult<()> {
+    verify_submission_signatures_inner(submission, config, &messages.hashes(), current_height)
+        .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+/// Verify a submission end to end: signatures first, then the carried proofs.
+///
+/// # Errors
+/// Returns an error if either verification step fails.
+pub async fn verify_submission(
+    submission: &ClaimSubmission,
+    config: &AirdropConfiguration,
+    messages: &ClaimMessages,
+    params: &VerifyingParams,
+    current_height: Option<u64>,
+) -> ZairResult<()> {
+    verify_submission_signatures(submission, config, messages, current_height)?;
+    verify_proofs(ClaimProofsOutput::from(submission), config, params).await
+}
+
+#[cfg(test)]
+mod tests {
+    use zair_core::base::{Pool, TargetId};
+    use zair_core::schema::config::{AirdropNetwork, SaplingSnapshot, ValueCommitmentScheme};
+
+    use super::*;
+
+    fn sapling_config() -> AirdropConfiguration {
+        AirdropConfiguration::new(
+            AirdropNetwork::Testnet,
+            1,
+            Some(SaplingSnapshot {
+                note_commitment_root: [0_u8; 32],
+                nullifier_gap_root: [0_u8; 32],
+                target_id: TargetId::new(Pool::Sapling, "ZAIRTEST").expect("valid target ID"),
+                value_commitment_scheme: ValueCommitmentScheme::Native,
+                verifying_key_fingerprint: None,
+            }),
+            None,
+        )
+    }
+
+    #[test]
+    fn per_claim_messages_override_shared_message() {
+        let overridden = Nullifier::from([1_u8; 32]);
+        let other = Nullifier::from([2_u8; 32]);
+        let mut messages = ClaimMessages::shared(b"shared".to_vec());
+        messages.sapling.insert(overridden, b"own".to_vec());
+
+        let hashes = messages.hashes();
+        assert_eq!(hashes.sapling_hash(overridden), Some(hash_message(b"own")));
+        assert_eq!(hashes.sapling_hash(other), Some(hash_message(b"shared")));
+        assert_eq!(
+            hashes.orchard_hash(overridden),
+            Some(hash_message(b"shared"))
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_proofs_requires_sapling_verifying_key() {
+        let proofs = ClaimProofsOutput {
+            sapling_proofs: vec![SaplingClaimProofResult {
+                claim_id: [0_u8; 32],
+                zkproof: [1_u8; 192],
+                rk: [2_u8; 32],
+                cv: Some([3_u8; 32]),
+                cv_sha256: None,
+                airdrop_nullifier: Nullifier::from([4_u8; 32]),
+            }],
+            orchard_proofs: vec![],
+            failed_claims: Vec::new(),
+            epoch: None,
+        };
+
+        let err = verify_proofs(proofs, &sapling_config(), &VerifyingParams::default())
+            .await
+            .expect_err("verification must fail without a verifying key");
+        assert_eq!(err.code(), "verification");
+        assert!(
+            err.to_string().contains("no Sapling verifying key"),
+            "{err:?}"
+        );
+    }
+}
@@ -0,0 +1,383 @@
+//! SQLite registry of verified claims.
+//!
+//! Verification services record the verdict of every claim they check, keyed by its airdrop
+//! nullifier and value commitment, and query the totals for dashboards and payout reconciliation.
+//! Claims are grouped into epochs by the time they were recorded.
+
+use std::num::NonZeroU64;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use eyre::Context as _;
+use rusqlite::{Connection, OptionalExtension as _, params};
+use serde::Serialize;
+use tracing::info;
+use zair_core::base::{Nullifier, Pool};
+use zair_core::schema::submission::ClaimSubmission;
+
+use crate::error::{ZairError, ZairResult};
+use crate::stdio;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS claims (
+    id INTEGER PRIMARY KEY,
+    pool INTEGER NOT NULL,
+    airdrop_nullifier BLOB NOT NULL,
+    value_commitment BLOB,
+    verdict TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS claims_by_nullifier ON claims (pool, airdrop_nullifier);
+CREATE INDEX IF NOT EXISTS claims_by_time ON claims (recorded_at);
+";
+
+/// Failure of a registry operation.
+#[derive(Debug, thiserror::Error)]
+pub enum RegistryError {
+    /// The database could not be opened, read or written.
+    #[error("claim registry: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    /// A timestamp or count does not fit the database integer type.
+    #[error("claim registry: value out of range")]
+    OutOfRange,
+}
+
+/// Verification outcome of a claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// The claim passed verification.
+    Accepted,
+    /// The claim failed verification.
+    Rejected,
+}
+
+impl Verdict {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+/// A claim verdict to record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryEntry {
+    /// Pool of the claim.
+    pub pool: Pool,
+    /// Airdrop nullifier of the claim.
+    pub airdrop_nullifier: Nullifier,
+    /// Value commitment of the claim in its configured scheme.
+    pub value_commitment: Option<[u8; 32]>,
+    /// Verification outcome.
+    pub verdict: Verdict,
+    /// Unix time of the verdict in seconds.
+    pub recorded_at: u64,
+}
+
+/// Claim counts by verdict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RegistryTotals {
+    /// Number of accepted claims.
+    pub accepted: u64,
+    /// Number of rejected claims.
+    pub rejected: u64,
+}
+
+/// Claim counts of one epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EpochStats {
+    /// Unix time in seconds at which the epoch starts.
+    pub epoch_start: u64,
+    /// Claim counts recorded in the epoch.
+    pub totals: RegistryTotals,
+}
+
+/// SQLite-backed registry of claim verdicts.
+#[derive(Debug)]
+pub struct ClaimRegistry {
+    conn: Connection,
+}
+
+impl ClaimRegistry {
+    /// Open the registry at `path`, creating the database if it does not exist.
+    ///
+    /// # Errors
+    /// Returns an error if the database cannot be opened or initialized.
+    pub fn open(path: &Path) -> Result<Self, RegistryError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Open a registry that lives in memory, for tests and short-lived services.
+    ///
+    /// # Errors
+    /// Returns an error if the database cannot be initialized.
+    pub fn open_in_memory() -> Result<Self, RegistryError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, RegistryError> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Record the verdicts of `entries` in one transaction.
+    ///
+    /// # Errors
+    /// Returns an error if a timestamp is out of range or the database cannot be written.
+    pub fn record(&mut self, entries: &[RegistryEntry]) -> Result<(), RegistryError> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO claims (pool, airdrop_nullifier, value_commitment, verdict, \
+                 recorded_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for entry in entries {
+                let nullifier: &[u8] = entry.airdrop_nullifier.as_ref();
+                insert.execute(params![
+                    entry.pool.as_byte(),
+                    nullifier,
+                    entry.value_commitment.as_ref().map(<[u8; 32]>::as_slice),
+                    entry.verdict.as_str(),
+                    to_sql_int(entry.recorded_at)?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Whether a claim of `airdrop_nullifier` in `pool` has been accepted.
+    ///
+    /// # Errors
+    /// Returns an error if the database cannot be read.
+    pub fn is_accepted(
+        &self,
+        pool: Pool,
+        airdrop_nullifier: &Nullifier,
+    ) -> Result<bool, RegistryError> {
+        let nullifier: &[u8] = airdrop_nullifier.as_ref();
+        let found = self
+            .conn
+            .query_row(
+                "SELECT 1 FROM claims WHERE pool = ?1 AND airdrop_nullifier = ?2 AND verdict = ?3 \
+                 LIMIT 1",
+                params![pool.as_byte(), nullifier, Verdict::Accepted.as_str()],
+                |_| Ok(()),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// Claim counts by verdict over the whole registry.
+    ///
+    /// # Errors
+    /// Returns an error if the database cannot be read.
+    pub fn totals(&self) -> Result<RegistryTotals, RegistryError> {
+        let (accepted, rejected) = self.conn.query_row(
+            "SELECT COALESCE(SUM(verdict = 'accepted'), 0), COALESCE(SUM(verdict = 'rejected'), 0) \
+             FROM claims",
+            [],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+        )?;
+        Ok(RegistryTotals {
+            accepted: from_sql_int(accepted)?,
+            rejected: from_sql_int(rejected)?,
+        })
+    }
+
+    /// Claim counts by verdict per epoch of `epoch_seconds`, oldest first.
+    ///
+    /// Epochs are aligned to the Unix epoch and only epochs with recorded claims are returned.
+    ///
+    /// # Errors
+    /// Returns an error if the epoch length is out of range or the database cannot be read.
+    pub fn epoch_stats(&self, epoch_seconds: NonZeroU64) -> Result<Vec<EpochStats>, RegistryError> {
+        let mut query = self.conn.prepare(
+            "SELECT recorded_at / ?1 AS epoch, SUM(verdict = 'accepted'), \
+             SUM(verdict = 'rejected') FROM claims GROUP BY epoch ORDER BY epoch",
+        )?;
+        let rows = query.query_map([to_sql_int(epoch_seconds.get())?], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut stats = Vec::new();
+        for row in rows {
+            let (epoch, accepted, rejected) = row?;
+            stats.push(EpochStats {
+                epoch_start: from_sql_int(epoch)?
+                    .checked_mul(epoch_seconds.get())
+                    .ok_or(RegistryError::OutOfRange)?,
+                totals: RegistryTotals {
+                    accepted: from_sql_int(accepted)?,
+                    rejected: from_sql_int(rejected)?,
+                },
+            });
+        }
+        Ok(stats)
+    }
+}
+
+fn to_sql_int(value: u64) -> Result<i64, RegistryError> {
+    i64::try_from(value).map_err(|_| RegistryError::OutOfRange)
+}
+
+fn from_sql_int(value: i64) -> Result<u64, RegistryError> {
+    u64::try_from(value).map_err(|_| RegistryError::OutOfRange)
+}
+
+/// Registry entries for every claim of `submission`, all with the same verdict.
+#[must_use]
+pub fn submission_entries(
+    submission: &ClaimSubmission,
+    verdict: Verdict,
+    recorded_at: u64,
+) -> Vec<RegistryEntry> {
+    let sapling = submission.sapling.iter().map(|entry| RegistryEntry {
+        pool: Pool::Sapling,
+        airdrop_nullifier: entry.airdrop_nullifier,
+        value_commitment: entry.cv.or(entry.cv_sha256),
+        verdict,
+        recorded_at,
+    });
+    let orchard = submission.orchard.iter().map(|entry| RegistryEntry {
+        pool: Pool::Orchard,
+        airdrop_nullifier: entry.airdrop_nullifier,
+        value_commitment: entry.cv.or(entry.cv_sha256).or(entry.cv_poseidon),
+        verdict,
+        recorded_at,
+    });
+    sapling.chain(orchard).collect()
+}
+
+/// Record the verdict of every claim in `submission_file` in the registry at `registry_file`.
+///
+/// # Errors
+/// Returns an error if the submission cannot be read or the registry cannot be written.
+pub async fn record_submission_verdict(
+    registry_file: &Path,
+    submission_file: &Path,
+    verdict: Verdict,
+) -> ZairResult<()> {
+    record_submission_verdict_inner(registry_file, submission_file, verdict)
+        .await
+        .map_err(|e| ZairError::classify(e, ZairError::Verification))
+}
+
+async fn record_submission_verdict_inner(
+    registry_file: &Path,
+    submission_file: &Path,
+    verdict: Verdict,
+) -> eyre::Result<()> {
+    let submission: ClaimSubmission =
+        serde_json::from_str(&stdio::read_to_string(submission_file).await?)
+            .context("Failed to parse submission JSON")?;
+    let recorded_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("System clock is before the Unix epoch")?
+        .as_secs();
+
+    let entries = submission_entries(&submission, verdict, recorded_at);
+    let mut registry = ClaimRegistry::open(registry_file)?;
+    registry.record(&entries)?;
+    info!(
+        file = %registry_file.display(),
+        verdict = verdict.as_str(),
+        claims = entries.len(),
+        "Recorded claims in registry"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(nullifier: u8, verdict: Verdict, recorded_at: u64) -> RegistryEntry {
+        RegistryEntry {
+            pool: Pool::Sapling,
+            airdrop_nullifier: Nullifier::from([nullifier; 32]),
+            value_commitment: Some([7_u8; 32]),
+            verdict,
+            recorded_at,
+        }
+    }
+
+    #[test]
+    fn totals_and_epochs_count_recorded_verdicts() {
+        let mut registry = ClaimRegistry::open_in_memory().expect("open registry");
+        assert_eq!(
+            registry.totals().expect("totals"),
+            RegistryTotals::default()
+        );
+
+        registry
+            .record(&[
+                entry(1, Verdict::Accepted, 10),
+                entry(2, Verdict::Rejected, 50),
+                entry(2, Verdict::Accepted, 120),
+            ])
+            .expect("record");
+
+        assert_eq!(
+            registry.totals().expect("totals"),
+            RegistryTotals {
+                accepted: 2,
+                rejected: 1
+            }
+        );
+        let epoch = NonZeroU64::new(100).expect("non-zero");
+        assert_eq!(
+            registry.epoch_stats(epoch).expect("epoch stats"),
+            vec![
+                EpochStats {
+                    epoch_start: 0,
+                    totals: RegistryTotals {
+                        accepted: 1,
+                        rejected: 1
+                    },
+                },
+                EpochStats {
+                    epoch_start: 100,
+                    totals: RegistryTotals {
+                        accepted: 1,
+                        rejected: 0
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn only_accepted_claims_count_as_accepted() {
+        let mut registry = ClaimRegistry::open_in_memory().expect("open registry");
+        registry
+            .record(&[entry(1, Verdict::Rejected, 10)])
+            .expect("record");
+        let nullifier = Nullifier::from([1_u8; 32]);
+        assert!(
+            !registry
+                .is_accepted(Pool::Sapling, &nullifier)
+                .expect("query")
+        );
+
+        registry
+            .record(&[entry(1, Verdict::Accepted, 20)])
+            .expect("record");
+        assert!(
+            registry
+                .is_accepted(Pool::Sapling, &nullifier)
+                .expect("query")
+        );
+        assert!(
+            !registry
+                .is_accepted(Pool::Orchard, &nullifier)
+                .expect("query")
+        );
+    }
+}
@@ -1,9 +1,18 @@
 //! Seed parsing and file utilities.
 
+use std::borrow::Cow;
 use std::path::Path;
 
+use bech32::Bech32m;
+use bech32::primitives::decode::CheckedHrpstring;
 use eyre::{Context as _, ensure};
-use secrecy::SecretBox;
+use secrecy::{ExposeSecret as _, SecretBox};
+use tracing::warn;
+use zair_scan::Network;
+use zcash_keys::keys::{Era, UnifiedSpendingKey};
+use zip32::AccountId;
+
+use crate::stdio;
 
 /// Parse a hex-encoded seed into a 64-byte array.
 pub fn parse_seed_hex(seed_hex: &str) -> eyre::Result<SecretBox<[u8; 64]>> {
@@ -25,8 +34,130 @@ pub fn parse_seed_hex(seed_hex: &str) -> eyre::Result<SecretBox<[u8; 64]>> {
 
 /// Read a seed file containing hex and parse it into a 64-byte seed.
 pub async fn read_seed_file(path: &Path) -> eyre::Result<SecretBox<[u8; 64]>> {
-    let seed_hex = tokio::fs::read_to_string(path)
+    let seed_hex = stdio::read_to_string(path)
         .await
         .with_context(|| format!("Failed to read seed file {}", path.display()))?;
     parse_seed_hex(seed_hex.trim())
 }
+
+/// Secret the spending keys of a claim are derived from.
+pub enum AccountSecret {
+    /// BIP-39 seed of the wallet, which derives every account.
+    Seed(SecretBox<[u8; 64]>),
+    /// Unified spending key of a single account, as exported by a wallet SDK.
+    SpendingKey(Box<UnifiedSpendingKey>),
+}
+
+impl AccountSecret {
+    /// Unified spending key of `account_id`.
+    ///
+    /// A unified spending key is the key of the account it was exported for, so `account_id` only
+    /// applies to a seed.
+    ///
+    /// # Errors
+    /// Returns an error if the account index is invalid or key derivation from the seed fails.
+    pub fn spending_key(
+        &self,
+        network: Network,
+        account_id: u32,
+    ) -> eyre::Result<Cow<'_, UnifiedSpendingKey>> {
+        match self {
+            Self::Seed(seed) => {
+                spending_key_from_seed(network, seed.expose_secret(), account_id).map(Cow::Owned)
+            }
+            Self::SpendingKey(usk) => {
+                if account_id != 0 {
+                    warn!(
+                        account_id,
+                        "The unified spending key belongs to one account; --account is ignored"
+                    );
+                }
+                Ok(Cow::Borrowed(usk))
+            }
+        }
+    }
+}
+
+/// Derive the unified spending key of `account_id` from `seed`.
+pub fn spending_key_from_seed(
+    network: Network,
+    seed: &[u8; 64],
+    account_id: u32,
+) -> eyre::Result<UnifiedSpendingKey> {
+    let account_id =
+        AccountId::try_from(account_id).map_err(|_| eyre::eyre!("Invalid account-id"))?;
+    UnifiedSpendingKey::from_seed(&network, seed, account_id)
+        .map_err(|e| eyre::eyre!("Failed to derive spending key: {e:?}"))
+}
+
+/// Parse a hex seed, or a unified spending key in the serialization of the Orchard era, encoded
+/// as hex or bech32m.
+///
+/// ZIP 316 defines no string encoding of unified spending keys, so the human-readable part of a
+/// bech32m key is not checked.
+pub fn parse_account_secret(text: &str) -> eyre::Result<AccountSecret> {
+    let text = text.trim();
+    if text.len() == 128 {
+        return parse_seed_hex(text).map(AccountSecret::Seed);
+    }
+    let bytes = zeroize::Zeroizing::new(if text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        hex::decode(text).context("Invalid hex unified spending key")?
+    } else {
+        CheckedHrpstring::new::<Bech32m>(text)
+            .context("Expected a hex seed, or a hex or bech32m unified spending key")?
+            .byte_iter()
+            .collect()
+    });
+    let usk = UnifiedSpendingKey::from_bytes(Era::Orchard, &bytes)
+        .map_err(|e| eyre::eyre!("Invalid unified spending key: {e}"))?;
+    Ok(AccountSecret::SpendingKey(Box::new(usk)))
+}
+
+/// Read a file holding a hex seed or a unified spending key.
+pub async fn read_account_secret(path: &Path) -> eyre::Result<AccountSecret> {
+    let text = zeroize::Zeroizing::new(
+        stdio::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read seed file {}", path.display()))?,
+    );
+    parse_account_secret(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use bech32::Hrp;
+
+    use super::*;
+
+    const SEED: [u8; 64] = [7_u8; 64];
+
+    fn ufvk(secret: &AccountSecret, account_id: u32) -> String {
+        let network = Network::TestNetwork;
+        secret
+            .spending_key(network, account_id)
+            .expect("spending key")
+            .to_unified_full_viewing_key()
+            .encode(&network)
+    }
+
+    #[test]
+    fn unified_spending_keys_parse_as_hex_and_bech32m() {
+        let seed = parse_account_secret(&hex::encode(SEED)).expect("hex seed");
+        assert!(matches!(seed, AccountSecret::Seed(_)));
+
+        let usk = spending_key_from_seed(Network::TestNetwork, &SEED, 2).expect("spending key");
+        let bytes = usk.to_bytes(Era::Orchard);
+        let hrp = Hrp::parse("secret-unified-spending-key-test").expect("hrp");
+        for text in [
+            hex::encode(&bytes),
+            bech32::encode::<Bech32m>(hrp, &bytes).expect("bech32m"),
+        ] {
+            let secret = parse_account_secret(&text).expect("unified spending key");
+            assert!(matches!(secret, AccountSecret::SpendingKey(_)));
+            // The key carries its own account.
+            assert_eq!(ufvk(&secret, 0), ufvk(&seed, 2));
+        }
+
+        assert!(parse_account_secret("not a key").is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! `-` as a file path for standard input and output.
+//!
+//! Every JSON and message file argument accepts `-`: an input is then read from stdin and an
+//! output written to stdout, so commands can be chained in a pipeline or driven by another
+//! process without temporary files. Logs go to stderr to keep stdout clean.
+//!
+//! Stdin is read once and every input given as `-` gets the same bytes, and every output given as
+//! `-` is written to the same stdout, so a command takes at most one `-` input and one `-` output:
+//! see [`ensure_single_stdio`]. Snapshot, gap-tree and setup files are read in place
+//! (memory-mapped, indexed or cached), so they still need a real path.
+
+use std::io;
+use std::path::Path;
+
+use eyre::eyre;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use tokio::sync::OnceCell;
+
+use crate::error::{ZairError, ZairResult};
+use crate::{audit, paths};
+
+/// Path that stands for stdin when read and stdout when written.
+pub const STDIO_PATH: &str = "-";
+
+/// Stdin, read on first use. Commands may read the same input more than once.
+static STDIN: OnceCell<Vec<u8>> = OnceCell::const_new();
+
+/// Whether `path` stands for stdin or stdout.
+#[must_use]
+pub fn is_stdio(path: &Path) -> bool {
+    path == Path::new(STDIO_PATH)
+}
+
+/// Check that at most one of the named `inputs` and one of the named `outputs` is `-`.
+///
+/// Two `-` inputs would both get the bytes of stdin, and two `-` outputs would be written one
+/// after the other to stdout, mixing for example claim secrets into a proofs pipe.
+///
+/// # Errors
+/// Returns a configuration error naming the arguments that share stdin or stdout.
+pub fn ensure_single_stdio<'a>(
+    inputs: impl IntoIterator<Item = (&'a str, &'a Path)>,
+    outputs: impl IntoIterator<Item = (&'a str, &'a Path)>,
+) -> ZairResult<()> {
+    for (stream, args) in [
+        ("stdin", stdio_names(inputs)),
+        ("stdout", stdio_names(outputs)),
+    ] {
+        if args.len() > 1 {
+            return Err(ZairError::Config(eyre!(
+                "Only one argument can use {stream}, but {} are `-`",
+                args.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Names of the arguments given as `-`.
+fn stdio_names<'a>(args: impl IntoIterator<Item = (&'a str, &'a Path)>) -> Vec<&'a str> {
+    args.into_iter()
+        .filter(|(_, path)| is_stdio(path))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Read the file at `path`, or all of stdin if `path` is `-`.
+///
+/// # Errors
+/// Returns an error if the file or stdin cannot be read.
+pub async fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    if !is_stdio(path) {
+        audit::record_input(path);
+        return tokio::fs::read(path).await;
+    }
+    STDIN
+        .get_or_try_init(|| async {
+            let mut bytes = Vec::new();
+            tokio::io::stdin().read_to_end(&mut bytes).await?;
+            Ok::<_, io::Error>(bytes)
+        })
+        .await
+        .cloned()
+}
+
+/// Read the UTF-8 file at `path`, or all of stdin if `path` is `-`.
+///
+/// # Errors
+/// Returns an error if the input cannot be read or is not UTF-8.
+pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    String::from_utf8(read(path).await?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write `contents` to the file at `path`, or to stdout if `path` is `-`.
+///
+/// A file is written through a `.partial` file and renamed into place, see
+/// [`paths::write_complete`].
+///
+/// # Errors
+/// Returns an error if the file or stdout cannot be written.
+pub async fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref();
+    if !is_stdio(path) {
+        return paths::write_complete(path, contents).await;
+    }
+    let mut stdout = tokio::io::stdout();
+    stdout.write_all(contents.as_ref()).await?;
+    stdout.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn plain_paths_are_files() {
+        let path = std::env::temp_dir().join(format!("zair-stdio-{}", std::process::id()));
+        assert!(!is_stdio(&path));
+        assert!(is_stdio(Path::new("-")));
+        assert!(!is_stdio(Path::new("./-")));
+
+        write(&path, "{}").await.expect("write file");
+        assert_eq!(read_to_string(&path).await.expect("read file"), "{}");
+        tokio::fs::remove_file(path).await.expect("remove file");
+    }
+
+    #[test]
+    fn one_argument_per_stream_can_be_stdio() {
+        let stdio = Path::new(STDIO_PATH);
+        let file = Path::new("claim-proofs.json");
+        ensure_single_stdio(
+            [("claims_in", stdio), ("config", file)],
+            [("proofs_out", file), ("secrets_out", stdio)],
+        )
+        .expect("one - per stream");
+
+        let err = ensure_single_stdio([], [("proofs_out", stdio), ("secrets_out", stdio)])
+            .expect_err("two - outputs");
+        assert!(matches!(err, ZairError::Config(_)));
+        assert!(err.to_string().contains("proofs_out, secrets_out"));
+
+        let err = ensure_single_stdio([("claims_in", stdio), ("config", stdio)], [])
+            .expect_err("two - inputs");
+        assert!(matches!(err, ZairError::Config(_)));
+        assert!(err.to_string().contains("stdin"));
+    }
+}
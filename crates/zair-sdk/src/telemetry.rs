@@ -0,0 +1,69 @@
+//! Pipeline metrics.
+//!
+//! With the `metrics` feature, the pipeline records counters and histograms through the
+//! [`metrics`](https://docs.rs/metrics) facade; installing an exporter (e.g. the CLI Prometheus
+//! endpoint) is left to the embedding application. Without the feature every recorder is a no-op.
+//!
+//! Block scan counters are recorded by `zair-scan` as
+//! [`SCANNED_BLOCKS_METRIC`](zair_scan::light_walletd::SCANNED_BLOCKS_METRIC).
+
+use std::time::Duration;
+
+use zair_core::base::Pool;
+
+/// Counter of snapshot nullifiers collected by `config build`, labelled by `pool`.
+pub const NULLIFIERS_COLLECTED_METRIC: &str = "zair_nullifiers_collected_total";
+/// Histogram of gap-tree build time in seconds, labelled by `pool`.
+pub const GAP_TREE_BUILD_SECONDS_METRIC: &str = "zair_gap_tree_build_seconds";
+/// Histogram of non-membership witness extraction time in seconds, labelled by `pool`.
+pub const WITNESS_SECONDS_METRIC: &str = "zair_witness_seconds";
+/// Counter of generated (and self-verified) claim proofs, labelled by `pool`.
+pub const PROOFS_GENERATED_METRIC: &str = "zair_proofs_generated_total";
+/// Histogram of claim proof generation time in seconds, labelled by `pool`.
+pub const PROOF_SECONDS_METRIC: &str = "zair_proof_seconds";
+
+#[cfg(feature = "metrics")]
+const fn pool_label(pool: Pool) -> &'static str {
+    match pool {
+        Pool::Sapling => "sapling",
+        Pool::Orchard => "orchard",
+    }
+}
+
+/// Record the number of snapshot nullifiers collected for `pool`.
+#[cfg(feature = "metrics")]
+pub(crate) fn record_nullifiers_collected(pool: Pool, count: usize) {
+    metrics::counter!(NULLIFIERS_COLLECTED_METRIC, "pool" => pool_label(pool))
+        .increment(u64::try_from(count).unwrap_or(u64::MAX));
+}
+
+/// Record the time taken to build the gap tree for `pool`.
+#[cfg(feature = "metrics")]
+pub(crate) fn record_gap_tree_build(pool: Pool, elapsed: Duration) {
+    metrics::histogram!(GAP_TREE_BUILD_SECONDS_METRIC, "pool" => pool_label(pool)).record(elapsed);
+}
+
+/// Record the time taken to extract one non-membership witness for `pool`.
+#[cfg(feature = "metrics")]
+pub(crate) fn record_witness(pool: Pool, elapsed: Duration) {
+    metrics::histogram!(WITNESS_SECONDS_METRIC, "pool" => pool_label(pool)).record(elapsed);
+}
+
+/// Record a generated claim proof for `pool` and the time it took.
+#[cfg(all(feature = "metrics", feature = "prove"))]
+pub(crate) fn record_proof(pool: Pool, elapsed: Duration) {
+    metrics::counter!(PROOFS_GENERATED_METRIC, "pool" => pool_label(pool)).increment(1);
+    metrics::histogram!(PROOF_SECONDS_METRIC, "pool" => pool_label(pool)).record(elapsed);
+}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) const fn record_nullifiers_collected(_pool: Pool, _count: usize) {}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) const fn record_gap_tree_build(_pool: Pool, _elapsed: Duration) {}
+
+#[cfg(not(feature = "metrics"))]
+pub(crate) const fn record_witness(_pool: Pool, _elapsed: Duration) {}
+
+#[cfg(all(not(feature = "metrics"), feature = "prove"))]
+pub(crate) const fn record_proof(_pool: Pool, _elapsed: Duration) {}
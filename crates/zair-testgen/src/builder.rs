@@ -0,0 +1,360 @@
+//! Synthetic chain construction.
+
+use eyre::{ContextCompat as _, ensure, eyre};
+use ff::{Field as _, PrimeField as _};
+use incrementalmerkletree::frontier::CommitmentTree;
+use orchard::keys::{FullViewingKey, SpendingKey};
+use orchard::note::{ExtractedNoteCommitment, RandomSeed, Rho};
+use orchard::note_encryption::OrchardDomain;
+use orchard::tree::MerkleHashOrchard;
+use pasta_curves::pallas;
+use rand_core::{RngCore as _, SeedableRng as _};
+use rand_xorshift::XorShiftRng;
+use sapling::note_encryption::{SaplingDomain, sapling_note_encryption};
+use test_lightwalletd::block_hash;
+use zair_core::base::{Nullifier, Pool};
+use zair_core::schema::config::AirdropNetwork;
+use zair_scan::Network;
+use zcash_client_backend::proto::compact_formats::{
+    ChainMetadata, CompactBlock, CompactOrchardAction, CompactSaplingOutput, CompactSaplingSpend,
+    CompactTx,
+};
+use zcash_client_backend::proto::service::TreeState;
+use zcash_keys::keys::UnifiedSpendingKey;
+use zcash_note_encryption::{COMPACT_NOTE_SIZE, Domain as _, NoteEncryption};
+use zcash_primitives::merkle_tree::write_commitment_tree;
+use zip32::{AccountId, Scope};
+
+use crate::{Fixture, FixtureManifest, FixtureSpec, GeneratedNote, NoteSpec};
+
+/// Spending key of the account that receives the dummy outputs of Orchard spends.
+const DUMMY_ORCHARD_SPENDING_KEY: [u8; 32] = [0x5a; 32];
+
+/// Generate the fixture described by `spec` for `seed`.
+///
+/// The output only depends on `spec` and `seed`.
+///
+/// # Errors
+/// Returns an error if the spec is inconsistent or key derivation fails.
+pub fn generate(spec: &FixtureSpec, seed: &[u8; 64]) -> eyre::Result<Fixture> {
+    validate_spec(spec)?;
+
+    let network = match spec.network {
+        AirdropNetwork::Mainnet => Network::MainNetwork,
+        AirdropNetwork::Testnet => Network::TestNetwork,
+        AirdropNetwork::Regtest => Network::RegtestNetwork,
+    };
+    let account = AccountId::try_from(spec.account).map_err(|_| eyre!("Invalid account"))?;
+    let usk = UnifiedSpendingKey::from_seed(&network, seed, account)
+        .map_err(|e| eyre!("Failed to derive spending key: {e:?}"))?;
+    let ufvk = usk.to_unified_full_viewing_key();
+
+    let sapling_dfvk = ufvk.sapling().context("UFVK has no Sapling component")?;
+    let orchard_fvk = ufvk.orchard().context("UFVK has no Orchard component")?;
+    let keys = FixtureKeys {
+        sapling_recipient: sapling_dfvk.default_address().1,
+        sapling_nk: sapling_dfvk.to_nk(Scope::External),
+        orchard_recipient: orchard_fvk.address_at(0_u32, Scope::External),
+        orchard_fvk: orchard_fvk.clone(),
+        dummy_recipient: SpendingKey::from_bytes(DUMMY_ORCHARD_SPENDING_KEY)
+            .into_option()
+            .map(|sk| FullViewingKey::from(&sk).address_at(0_u32, Scope::External))
+            .context("Invalid dummy Orchard spending key")?,
+    };
+
+    let mut builder = ChainBuilder::new(spec.rng_seed, network);
+    let mut notes: Vec<Option<GeneratedNote>> = vec![None; spec.notes.len()];
+    let mut tree_states = vec![builder.tree_state(spec.start_height.saturating_sub(1))?];
+    let mut blocks = Vec::new();
+
+    for height in spec.start_height..=spec.tip_height {
+        let mut vtx = Vec::new();
+
+        for (slot, note) in notes.iter_mut().zip(&spec.notes) {
+            if note.height == height {
+                let (tx, position, nullifier) = builder.receive(&keys, note)?;
+                vtx.push(tx);
+                *slot = Some(GeneratedNote {
+                    pool: note.pool,
+                    value: note.value,
+                    height: note.height,
+                    spent_at: note.spent_at,
+                    position,
+                    nullifier,
+                    claimable: note.height <= spec.snapshot_height &&
+                        note.spent_at
+                            .is_none_or(|spent| spent > spec.snapshot_height),
+                });
+            }
+        }
+        for generated in notes.iter().flatten() {
+            if generated.spent_at == Some(height) {
+                vtx.push(builder.spend(&keys, generated)?);
+            }
+        }
+
+        blocks.push(builder.block(height, vtx)?);
+        tree_states.push(builder.tree_state(height)?);
+    }
+
+    let notes = notes
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .context("Every note height is validated to be inside the generated range")?;
+
+    Ok(Fixture {
+        manifest: FixtureManifest {
+            spec: spec.clone(),
+            ufvk: ufvk.encode(&network),
+            notes,
+        },
+        blocks,
+        tree_states,
+    })
+}
+
+fn validate_spec(spec: &FixtureSpec) -> eyre::Result<()> {
+    ensure!(spec.start_height > 0, "start_height must be positive");
+    ensure!(
+        spec.start_height <= spec.snapshot_height && spec.snapshot_height <= spec.tip_height,
+        "Heights must satisfy start_height <= snapshot_height <= tip_height"
+    );
+    ensure!(
+        u32::try_from(spec.tip_height).is_ok(),
+        "tip_height must fit in a u32 block height"
+    );
+    for (index, note) in spec.notes.iter().enumerate() {
+        ensure!(
+            (spec.start_height..=spec.tip_height).contains(&note.height),
+            "Note {index} height {} is outside the generated range",
+            note.height
+        );
+        if let Some(spent_at) = note.spent_at {
+            ensure!(
+                note.height < spent_at && spent_at <= spec.tip_height,
+                "Note {index} must be spent after its creation and within the generated range"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Keys of the fixture account, plus a throwaway recipient for Orchard spend outputs.
+struct FixtureKeys {
+    sapling_recipient: sapling::PaymentAddress,
+    sapling_nk: sapling::keys::NullifierDerivingKey,
+    orchard_recipient: orchard::Address,
+    orchard_fvk: FullViewingKey,
+    dummy_recipient: orchard::Address,
+}
+
+struct ChainBuilder {
+    rng: XorShiftRng,
+    network: Network,
+    sapling_tree: CommitmentTree<sapling::Node, { sapling::NOTE_COMMITMENT_TREE_DEPTH }>,
+    orchard_tree: CommitmentTree<MerkleHashOrchard, 32>,
+    tx_index: u64,
+}
+
+impl ChainBuilder {
+    fn new(rng_seed: u64, network: Network) -> Self {
+        Self {
+            rng: XorShiftRng::seed_from_u64(rng_seed),
+            network,
+            sapling_tree: CommitmentTree::empty(),
+            orchard_tree: CommitmentTree::empty(),
+            tx_index: 0,
+        }
+    }
+
+    /// A transaction creating `note` for the fixture account.
+    ///
+    /// Returns the transaction, the note commitment position and the note's chain nullifier.
+    fn receive(
+        &mut self,
+        keys: &FixtureKeys,
+        note: &NoteSpec,
+    ) -> eyre::Result<(CompactTx, u64, Nullifier)> {
+        match note.pool {
+            Pool::Sapling => {
+                let (sapling_note, output, position) =
+                    self.sapling_output(keys.sapling_recipient, note.value)?;
+                let tx = CompactTx {
+                    outputs: vec![output],
+                    ..CompactTx::default()
+                };
+                let nullifier = sapling_note.nf(&keys.sapling_nk, position).0;
+                Ok((tx, position, Nullifier::from(nullifier)))
+            }
+            Pool::Orchard => {
+                let rho = self.random_nullifier();
+                let (orchard_note, action, position) =
+                    self.orchard_action(rho, keys.orchard_recipient, note.value)?;
+                let tx = CompactTx {
+                    actions: vec![action],
+                    ..CompactTx::default()
+                };
+                let nullifier = orchard_note.nullifier(&keys.orchard_fvk).to_bytes();
+                Ok((tx, position, Nullifier::from(nullifier)))
+            }
+        }
+    }
+
+    /// A transaction revealing the nullifier of a previously generated note.
+    fn spend(&mut self, keys: &FixtureKeys, note: &GeneratedNote) -> eyre::Result<CompactTx> {
+        Ok(match note.pool {
+            Pool::Sapling => CompactTx {
+                spends: vec![CompactSaplingSpend {
+                    nf: note.nullifier.to_vec(),
+                }],
+                ..CompactTx::default()
+            },
+            Pool::Orchard => {
+                let (_, action, _) =
+                    self.orchard_action(*note.nullifier.as_ref(), keys.dummy_recipient, 0)?;
+                CompactTx {
+                    actions: vec![action],
+                    ..CompactTx::default()
+                }
+            }
+        })
+    }
+
+    /// A uniformly random canonical `pallas::Base` encoding.
+    fn random_nullifier(&mut self) -> [u8; 32] {
+        pallas::Base::random(&mut self.rng).to_repr()
+    }
+
+    fn sapling_output(
+        &mut self,
+        recipient: sapling::PaymentAddress,
+        value: u64,
+    ) -> eyre::Result<(sapling::Note, CompactSaplingOutput, u64)> {
+        let mut rseed = [0_u8; 32];
+        self.rng.fill_bytes(&mut rseed);
+        let note = sapling::Note::from_parts(
+            recipient,
+            sapling::value::NoteValue::from_raw(value),
+            sapling::Rseed::AfterZip212(rseed),
+        );
+        let cmu = note.cmu();
+        let encryptor = sapling_note_encryption(None, note.clone(), [0_u8; 512], &mut self.rng);
+
+        let position = u64::try_from(self.sapling_tree.size())?;
+        self.sapling_tree
+            .append(sapling::Node::from_cmu(&cmu))
+            .map_err(|_| eyre!("Sapling commitment tree is full"))?;
+
+        let output = CompactSaplingOutput {
+            cmu: cmu.to_bytes().to_vec(),
+            ephemeral_key: SaplingDomain::epk_bytes(encryptor.epk()).0.to_vec(),
+            ciphertext: compact_ciphertext(&encryptor.encrypt_note_plaintext())?,
+        };
+        Ok((note, output, position))
+    }
+
+    /// An Orchard action revealing `nullifier` and creating a note for `recipient`.
+    fn orchard_action(
+        &mut self,
+        nullifier: [u8; 32],
+        recipient: orchard::Address,
+        value: u64,
+    ) -> eyre::Result<(orchard::Note, CompactOrchardAction, u64)> {
+        let rho = Rho::from_bytes(&nullifier)
+            .into_option()
+            .context("Orchard nullifier is not a canonical field element")?;
+        let note = loop {
+            let mut rseed = [0_u8; 32];
+            self.rng.fill_bytes(&mut rseed);
+            let note = RandomSeed::from_bytes(rseed, &rho)
+                .into_option()
+                .and_then(|rseed| {
+                    orchard::Note::from_parts(
+                        recipient,
+                        orchard::value::NoteValue::from_raw(value),
+                        rho,
+                        rseed,
+                    )
+                    .into_option()
+                });
+            if let Some(note) = note {
+                break note;
+            }
+        };
+        let cmx = ExtractedNoteCommitment::from(note.commitment());
+        let encryptor = NoteEncryption::<OrchardDomain>::new(None, note, [0_u8; 512]);
+
+        let position = u64::try_from(self.orchard_tree.size())?;
+        self.orchard_tree
+            .append(MerkleHashOrchard::from_cmx(&cmx))
+            .map_err(|_| eyre!("Orchard commitment tree is full"))?;
+
+        let action = CompactOrchardAction {
+            nullifier: nullifier.to_vec(),
+            cmx: cmx.to_bytes().to_vec(),
+            ephemeral_key: OrchardDomain::epk_bytes(encryptor.epk()).0.to_vec(),
+            ciphertext: compact_ciphertext(&encryptor.encrypt_note_plaintext())?,
+        };
+        Ok((note, action, position))
+    }
+
+    fn block(&mut self, height: u64, mut vtx: Vec<CompactTx>) -> eyre::Result<CompactBlock> {
+        for tx in &mut vtx {
+            tx.index = self.tx_index;
+            tx.hash = tx_hash(self.tx_index).to_vec();
+            self.tx_index = self.tx_index.saturating_add(1);
+        }
+        Ok(CompactBlock {
+            proto_version: 1,
+            height,
+            hash: block_hash(height).to_vec(),
+            prev_hash: block_hash(height.saturating_sub(1)).to_vec(),
+            vtx,
+            chain_metadata: Some(ChainMetadata {
+                sapling_commitment_tree_size: u32::try_from(self.sapling_tree.size())?,
+                orchard_commitment_tree_size: u32::try_from(self.orchard_tree.size())?,
+            }),
+            ..CompactBlock::default()
+        })
+    }
+
+    /// Tree state after all blocks appended so far, reported at `height`.
+    fn tree_state(&self, height: u64) -> eyre::Result<TreeState> {
+        let mut sapling_tree = Vec::new();
+        write_commitment_tree(&self.sapling_tree, &mut sapling_tree)?;
+        let mut orchard_tree = Vec::new();
+        write_commitment_tree(&self.orchard_tree, &mut orchard_tree)?;
+
+        let mut hash = block_hash(height);
+        hash.reverse();
+        Ok(TreeState {
+            network: match self.network {
+                Network::MainNetwork => "main",
+                Network::TestNetwork => "test",
+                Network::RegtestNetwork | Network::Custom(_) => "regtest",
+            }
+            .to_owned(),
+            height,
+            hash: hex::encode(hash),
+            time: 0,
+            sapling_tree: hex::encode(sapling_tree),
+            orchard_tree: hex::encode(orchard_tree),
+        })
+    }
+}
+
+/// Deterministic synthetic transaction id, distinct from any block hash.
+fn tx_hash(index: u64) -> [u8; 32] {
+    let mut hash = [0xff_u8; 32];
+    for (dst, src) in hash.iter_mut().zip(index.to_le_bytes()) {
+        *dst = src;
+    }
+    hash
+}
+
+fn compact_ciphertext(ciphertext: &[u8]) -> eyre::Result<Vec<u8>> {
+    ciphertext
+        .get(..COMPACT_NOTE_SIZE)
+        .map(<[u8]>::to_vec)
+        .context("Note ciphertext shorter than the compact size")
+}
@@ -0,0 +1,219 @@
+//! Deterministic synthetic chain fixtures for ZAIR.
+//!
+//! Generates a contiguous run of compact blocks containing Sapling and Orchard notes for a seed,
+//! together with the matching tree states and snapshot nullifier files. A fixture is fully
+//! determined by the seed and its [`FixtureSpec`], so circuit and pipeline changes can be
+//! validated against known answers, offline, via [`test_lightwalletd`].
+
+mod builder;
+
+use std::fs;
+use std::path::Path;
+
+pub use builder::generate;
+use eyre::{Context as _, eyre};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use test_lightwalletd::MockChain;
+use zair_core::base::{Nullifier, Pool, SanitiseNullifiers};
+use zair_core::schema::config::AirdropNetwork;
+use zcash_client_backend::proto::compact_formats::CompactBlock;
+use zcash_client_backend::proto::service::TreeState;
+
+/// Fixture manifest file name.
+pub const MANIFEST_FILE: &str = "fixture.json";
+/// Length-delimited compact blocks file name.
+pub const BLOCKS_FILE: &str = "blocks.bin";
+/// Length-delimited tree states file name.
+pub const TREE_STATES_FILE: &str = "tree-states.bin";
+/// Sapling snapshot nullifiers file name (same format as `config build`).
+pub const SNAPSHOT_SAPLING_FILE: &str = "snapshot-sapling.bin";
+/// Orchard snapshot nullifiers file name (same format as `config build`).
+pub const SNAPSHOT_ORCHARD_FILE: &str = "snapshot-orchard.bin";
+
+/// Description of the chain to generate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureSpec {
+    /// Network the seed keys are derived for.
+    pub network: AirdropNetwork,
+    /// ZIP-32 account index of the seed keys.
+    #[serde(default)]
+    pub account: u32,
+    /// First generated block. Trees are empty just below this height.
+    pub start_height: u64,
+    /// Snapshot height (inclusive) used for the snapshot files and claimability.
+    pub snapshot_height: u64,
+    /// Last generated block.
+    pub tip_height: u64,
+    /// Seed for all note and encryption randomness.
+    #[serde(default)]
+    pub rng_seed: u64,
+    /// Notes received by the seed's default addresses.
+    pub notes: Vec<NoteSpec>,
+}
+
+/// A note received by the fixture seed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteSpec {
+    /// Pool the note is created in.
+    pub pool: Pool,
+    /// Note value in zatoshis.
+    pub value: u64,
+    /// Height of the block that creates the note.
+    pub height: u64,
+    /// Height of the block that spends the note, if any.
+    #[serde(default)]
+    pub spent_at: Option<u64>,
+}
+
+/// A note as generated on the synthetic chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratedNote {
+    /// Pool the note was created in.
+    pub pool: Pool,
+    /// Note value in zatoshis.
+    pub value: u64,
+    /// Height of the block that creates the note.
+    pub height: u64,
+    /// Height of the block that spends the note, if any.
+    pub spent_at: Option<u64>,
+    /// Position of the note commitment in the pool's commitment tree.
+    pub position: u64,
+    /// Standard (chain) nullifier of the note.
+    pub nullifier: Nullifier,
+    /// Whether the note exists and is unspent at the snapshot height.
+    pub claimable: bool,
+}
+
+/// Known-answer data stored next to the generated chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureManifest {
+    /// Spec the fixture was generated from.
+    pub spec: FixtureSpec,
+    /// Encoded UFVK of the fixture account.
+    pub ufvk: String,
+    /// Generated notes, in spec order.
+    pub notes: Vec<GeneratedNote>,
+}
+
+/// A generated fixture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fixture {
+    /// Known-answer data.
+    pub manifest: FixtureManifest,
+    /// Contiguous compact blocks from `start_height` to `tip_height`.
+    pub blocks: Vec<CompactBlock>,
+    /// Tree states just below `start_height` and after every block.
+    pub tree_states: Vec<TreeState>,
+}
+
+impl Fixture {
+    /// Chain nullifiers of `pool` revealed up to the snapshot height.
+    ///
+    /// # Errors
+    /// Returns an error if a block contains a malformed nullifier.
+    pub fn snapshot_nullifiers(&self, pool: Pool) -> eyre::Result<SanitiseNullifiers> {
+        let snapshot_height = self.manifest.spec.snapshot_height;
+        let mut nullifiers = Vec::new();
+        for block in self.blocks.iter().filter(|b| b.height <= snapshot_height) {
+            for tx in &block.vtx {
+                match pool {
+                    Pool::Sapling => {
+                        for spend in &tx.spends {
+                            nullifiers.push(Nullifier::try_from(spend.nf.as_slice())?);
+                        }
+                    }
+                    Pool::Orchard => {
+                        for action in &tx.actions {
+                            nullifiers.push(Nullifier::try_from(action.nullifier.as_slice())?);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(SanitiseNullifiers::new(nullifiers))
+    }
+
+    /// In-memory lightwalletd chain serving this fixture.
+    #[must_use]
+    pub fn mock_chain(&self) -> MockChain {
+        let mut chain = MockChain::new();
+        for block in &self.blocks {
+            chain.push_block(block.clone());
+        }
+        for tree_state in &self.tree_states {
+            chain.push_tree_state(tree_state.clone());
+        }
+        chain
+    }
+
+    /// Write the fixture and its snapshot files into `dir`.
+    ///
+    /// # Errors
+    /// Returns an error if the directory or any file cannot be written.
+    pub fn write_to_dir(&self, dir: &Path) -> eyre::Result<()> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(&self.manifest)?,
+        )?;
+        write_messages(&dir.join(BLOCKS_FILE), &self.blocks)?;
+        write_messages(&dir.join(TREE_STATES_FILE), &self.tree_states)?;
+        for (pool, file) in [
+            (Pool::Sapling, SNAPSHOT_SAPLING_FILE),
+            (Pool::Orchard, SNAPSHOT_ORCHARD_FILE),
+        ] {
+            let nullifiers = self.snapshot_nullifiers(pool)?;
+            fs::write(dir.join(file), bytemuck::cast_slice::<_, u8>(&nullifiers))?;
+        }
+        Ok(())
+    }
+
+    /// Read a fixture previously written with [`Fixture::write_to_dir`].
+    ///
+    /// # Errors
+    /// Returns an error if a file is missing or malformed.
+    pub fn read_from_dir(dir: &Path) -> eyre::Result<Self> {
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let manifest = serde_json::from_str(
+            &fs::read_to_string(&manifest_path)
+                .with_context(|| format!("Failed to read {}", manifest_path.display()))?,
+        )?;
+        Ok(Self {
+            manifest,
+            blocks: read_messages(&dir.join(BLOCKS_FILE))?,
+            tree_states: read_messages(&dir.join(TREE_STATES_FILE))?,
+        })
+    }
+}
+
+/// Parse a hex-encoded 64-byte seed, as written by `zair key derive-seed`.
+///
+/// # Errors
+/// Returns an error if the input is not hex or not 64 bytes long.
+pub fn parse_seed_hex(hex_seed: &str) -> eyre::Result<[u8; 64]> {
+    let bytes = hex::decode(hex_seed.trim()).context("Seed must be hex encoded")?;
+    <[u8; 64]>::try_from(bytes.as_slice())
+        .map_err(|_| eyre!("Seed must be 64 bytes, got {}", bytes.len()))
+}
+
+fn write_messages<M: Message>(path: &Path, messages: &[M]) -> eyre::Result<()> {
+    let mut buf = Vec::new();
+    for message in messages {
+        message.encode_length_delimited(&mut buf)?;
+    }
+    fs::write(path, buf).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn read_messages<M: Message + Default>(path: &Path) -> eyre::Result<Vec<M>> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut buf = bytes.as_slice();
+    let mut messages = Vec::new();
+    while !buf.is_empty() {
+        messages.push(
+            M::decode_length_delimited(&mut buf)
+                .with_context(|| format!("Malformed message in {}", path.display()))?,
+        );
+    }
+    Ok(messages)
+}
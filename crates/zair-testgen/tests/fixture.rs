@@ -0,0 +1,141 @@
+//! Known-answer checks for generated fixtures.
+
+use std::str::FromStr as _;
+
+use test_lightwalletd::TestLightwalletd;
+use tonic::transport::Uri;
+use zair_core::base::Pool;
+use zair_core::schema::config::AirdropNetwork;
+use zair_scan::Network;
+use zair_scan::light_walletd::LightWalletd;
+use zair_scan::scanner::{AccountNotesVisitor, BlockScanner};
+use zair_testgen::{Fixture, FixtureSpec, NoteSpec, generate};
+use zcash_keys::keys::UnifiedFullViewingKey;
+
+const SEED: [u8; 64] = [7_u8; 64];
+
+fn spec() -> FixtureSpec {
+    FixtureSpec {
+        network: AirdropNetwork::Testnet,
+        account: 0,
+        start_height: 1_900_001,
+        snapshot_height: 1_900_010,
+        tip_height: 1_900_012,
+        rng_seed: 42,
+        notes: vec![
+            NoteSpec {
+                pool: Pool::Sapling,
+                value: 100_000,
+                height: 1_900_002,
+                spent_at: None,
+            },
+            NoteSpec {
+                pool: Pool::Sapling,
+                value: 25_000,
+                height: 1_900_003,
+                spent_at: Some(1_900_005),
+            },
+            NoteSpec {
+                pool: Pool::Orchard,
+                value: 70_000,
+                height: 1_900_003,
+                spent_at: Some(1_900_011),
+            },
+            NoteSpec {
+                pool: Pool::Orchard,
+                value: 5_000,
+                height: 1_900_011,
+                spent_at: None,
+            },
+        ],
+    }
+}
+
+#[test]
+fn generation_is_deterministic() {
+    let first = generate(&spec(), &SEED).expect("fixture should generate");
+    let second = generate(&spec(), &SEED).expect("fixture should generate");
+
+    assert_eq!(first, second);
+    assert_eq!(first.blocks.len(), 12);
+    assert_eq!(first.tree_states.len(), 13);
+}
+
+#[test]
+fn claimability_follows_snapshot_height() {
+    let fixture = generate(&spec(), &SEED).expect("fixture should generate");
+    let claimable: Vec<bool> = fixture.manifest.notes.iter().map(|n| n.claimable).collect();
+
+    // Spent before the snapshot, spent after the snapshot, created after the snapshot.
+    assert_eq!(claimable, vec![true, false, true, false]);
+
+    let sapling = fixture
+        .snapshot_nullifiers(Pool::Sapling)
+        .expect("snapshot nullifiers");
+    let spent_before = &fixture.manifest.notes[1];
+    assert!(sapling.contains(&spent_before.nullifier));
+    let orchard = fixture
+        .snapshot_nullifiers(Pool::Orchard)
+        .expect("snapshot nullifiers");
+    let spent_after = &fixture.manifest.notes[2];
+    assert!(!orchard.contains(&spent_after.nullifier));
+}
+
+#[test]
+fn fixture_roundtrips_through_directory() {
+    let fixture = generate(&spec(), &SEED).expect("fixture should generate");
+    let dir = tempfile::tempdir().expect("temp dir");
+
+    fixture
+        .write_to_dir(dir.path())
+        .expect("fixture should be written");
+    let read = Fixture::read_from_dir(dir.path()).expect("fixture should be read");
+
+    assert_eq!(fixture, read);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scanner_finds_generated_notes() {
+    let fixture = generate(&spec(), &SEED).expect("fixture should generate");
+    let server = TestLightwalletd::spawn(fixture.mock_chain())
+        .await
+        .expect("server should start");
+    let lightwalletd = LightWalletd::connect(Uri::from_str(&server.url()).expect("valid URL"))
+        .await
+        .expect("should connect");
+
+    let start = fixture.manifest.spec.start_height;
+    let snapshot = fixture.manifest.spec.snapshot_height;
+    let tree_state = lightwalletd
+        .get_tree_state(start.saturating_sub(1))
+        .await
+        .expect("tree state");
+    let visitor = AccountNotesVisitor::from_tree_state(&tree_state).expect("visitor");
+    let metadata = BlockScanner::parse_tree_state(&tree_state).expect("metadata");
+    let ufvk = UnifiedFullViewingKey::decode(&Network::TestNetwork, &fixture.manifest.ufvk)
+        .expect("valid UFVK");
+
+    let (visitor, _) = lightwalletd
+        .scan_blocks_spawned(
+            ufvk,
+            Network::TestNetwork,
+            visitor,
+            &(start..=snapshot),
+            Some(metadata),
+        )
+        .await
+        .expect("scan should succeed");
+
+    let sapling_values: Vec<u64> = visitor
+        .sapling_notes()
+        .iter()
+        .map(|found| found.note.value())
+        .collect();
+    let orchard_values: Vec<u64> = visitor
+        .orchard_notes()
+        .iter()
+        .map(|found| found.note.value().inner())
+        .collect();
+    assert_eq!(sapling_values, vec![100_000, 25_000]);
+    assert_eq!(orchard_values, vec![70_000]);
+}
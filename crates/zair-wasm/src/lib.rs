@@ -0,0 +1,273 @@
+//! WASM bindings for browser-side claim preparation.
+//!
+//! Web wallets cannot open gRPC connections to lightwalletd, so the caller fetches the tree state
+//! and the compact blocks (for example through gRPC-web) and feeds them to a [`ClaimPreparer`]. It
+//! scans the blocks for the viewing key's notes, maps their nullifiers into the snapshot gap trees
+//! and returns the same `claim-prepared.json` document as `zair claim prepare`, without the key or
+//! notes ever leaving the browser.
+//!
+//! ```js
+//! const preparer = new ClaimPreparer(configJson, ufvk, birthdayHeight, treeStateBytes);
+//! for await (const block of compactBlocks(preparer.nextHeight, preparer.snapshotHeight)) {
+//!   preparer.scanBlock(block);
+//! }
+//! const claimsJson = preparer.finish(saplingSnapshot, orchardSnapshot);
+//! ```
+
+use eyre::{Context as _, ensure};
+use prost::Message as _;
+use wasm_bindgen::prelude::*;
+use zair_core::base::{Nullifier, Pool, SanitiseNullifiers, key_fingerprint};
+use zair_core::schema::config::AirdropConfiguration;
+use zair_core::schema::proof_inputs::{AirdropClaimInputs, KeyFingerprints};
+use zair_prepare::{OrchardPool, SaplingPool, prepare_pool_claims};
+use zair_scan::scanner::{AccountNotesVisitor, BlockScanner};
+use zair_scan::{Network, ViewingKeys};
+use zcash_client_backend::data_api::BlockMetadata;
+use zcash_client_backend::proto::compact_formats::CompactBlock;
+use zcash_client_backend::proto::service::TreeState;
+use zcash_keys::keys::UnifiedFullViewingKey;
+
+/// Incremental claim preparation over a caller-supplied compact block stream.
+///
+/// Blocks must be passed in order, from the birthday height up to and including the snapshot
+/// height of the airdrop configuration.
+#[wasm_bindgen]
+pub struct ClaimPreparer {
+    config: AirdropConfiguration,
+    viewing_keys: ViewingKeys,
+    key_fingerprints: KeyFingerprints,
+    scanner: BlockScanner,
+    visitor: AccountNotesVisitor,
+    metadata: BlockMetadata,
+    next_height: u32,
+    snapshot_height: u32,
+}
+
+#[wasm_bindgen]
+impl ClaimPreparer {
+    /// Start a preparation for `ufvk` from `birthday_height`.
+    ///
+    /// `config_json` is the airdrop configuration and `tree_state` the protobuf-encoded
+    /// lightwalletd `TreeState` at `birthday_height - 1`.
+    ///
+    /// # Errors
+    /// Returns an error if an input cannot be decoded or does not match the configuration.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        config_json: &str,
+        ufvk: &str,
+        birthday_height: u32,
+        tree_state: &[u8],
+    ) -> Result<Self, JsError> {
+        Self::try_new(config_json, ufvk, birthday_height, tree_state).map_err(to_js_error)
+    }
+
+    /// Height of the next block to pass to [`Self::scan_block`].
+    #[wasm_bindgen(getter, js_name = nextHeight)]
+    #[allow(
+        clippy::missing_const_for_fn,
+        reason = "wasm-bindgen exports are plain functions"
+    )]
+    pub fn next_height(&self) -> u32 {
+        self.next_height
+    }
+
+    /// Last block height to scan.
+    #[wasm_bindgen(getter, js_name = snapshotHeight)]
+    #[allow(
+        clippy::missing_const_for_fn,
+        reason = "wasm-bindgen exports are plain functions"
+    )]
+    pub fn snapshot_height(&self) -> u32 {
+        self.snapshot_height
+    }
+
+    /// Scan one protobuf-encoded `CompactBlock`.
+    ///
+    /// # Errors
+    /// Returns an error if the block cannot be decoded, is out of order, or does not chain onto
+    /// the previous block.
+    #[wasm_bindgen(js_name = scanBlock)]
+    pub fn scan_block(&mut self, compact_block: &[u8]) -> Result<(), JsError> {
+        self.try_scan_block(compact_block).map_err(to_js_error)
+    }
+
+    /// Build the claim inputs once every block up to the snapshot height was scanned.
+    ///
+    /// The snapshots are the raw `snapshot-*.bin` contents (concatenated 32-byte nullifiers) and
+    /// are required for every pool enabled in the configuration. Returns the claim inputs JSON.
+    /// It contains spending secrets of the notes and must not leave the wallet.
+    ///
+    /// # Errors
+    /// Returns an error if the scan is incomplete, a snapshot is missing or malformed, or a
+    /// snapshot root does not match the configuration.
+    #[allow(
+        clippy::needless_pass_by_value,
+        reason = "wasm-bindgen passes JS byte arrays by value"
+    )]
+    pub fn finish(
+        self,
+        sapling_snapshot: Option<Vec<u8>>,
+        orchard_snapshot: Option<Vec<u8>>,
+    ) -> Result<String, JsError> {
+        self.try_finish(sapling_snapshot.as_deref(), orchard_snapshot.as_deref())
+            .map_err(to_js_error)
+    }
+}
+
+impl ClaimPreparer {
+    fn try_new(
+        config_json: &str,
+        ufvk: &str,
+        birthday_height: u32,
+        tree_state: &[u8],
+    ) -> eyre::Result<Self> {
+        let config: AirdropConfiguration =
+            serde_json::from_str(config_json).context("Invalid airdrop configuration JSON")?;
+        ensure!(
+            config.sapling.is_some() || config.orchard.is_some(),
+            "Airdrop configuration must enable at least one pool (sapling/orchard)"
+        );
+        let snapshot_height = u32::try_from(config.snapshot_height)
+            .context("Snapshot height does not fit a block height")?;
+        ensure!(
+            birthday_height <= snapshot_height,
+            "Birthday height cannot be past snapshot height"
+        );
+
+        let network = Network::from_configuration(&config)
+            .context("Invalid custom network upgrades in configuration")?;
+        let ufvk = UnifiedFullViewingKey::decode(&network, ufvk)
+            .map_err(|e| eyre::eyre!("Failed to decode Unified Full Viewing Key: {e:?}"))?;
+
+        let salt = config.key_fingerprint_salt();
+        let key_fingerprints = KeyFingerprints {
+            sapling: ufvk
+                .sapling()
+                .map(|dfvk| key_fingerprint(&salt, Pool::Sapling, &dfvk.to_bytes())),
+            orchard: ufvk
+                .orchard()
+                .map(|fvk| key_fingerprint(&salt, Pool::Orchard, &fvk.to_bytes())),
+        };
+
+        let tree_state = TreeState::decode(tree_state).context("Invalid tree state")?;
+        ensure!(
+            tree_state.height == u64::from(birthday_height.saturating_sub(1)),
+            "Tree state is at height {} but must be at birthday height - 1",
+            tree_state.height
+        );
+        let visitor = AccountNotesVisitor::from_tree_state(&tree_state)?;
+        let metadata = BlockScanner::parse_tree_state(&tree_state)?;
+
+        Ok(Self {
+            config,
+            viewing_keys: ViewingKeys::new(&ufvk),
+            key_fingerprints,
+            scanner: BlockScanner::from_ufvk(ufvk, network),
+            visitor,
+            metadata,
+            next_height: birthday_height,
+            snapshot_height,
+        })
+    }
+
+    fn try_scan_block(&mut self, compact_block: &[u8]) -> eyre::Result<()> {
+        let block = CompactBlock::decode(compact_block).context("Invalid compact block")?;
+        ensure!(
+            block.height == u64::from(self.next_height),
+            "Expected block {} but got block {}",
+            self.next_height,
+            block.height
+        );
+        ensure!(
+            self.next_height <= self.snapshot_height,
+            "Block {} is past the snapshot height {}",
+            block.height,
+            self.snapshot_height
+        );
+
+        self.metadata = self
+            .scanner
+            .scan_block(block, &mut self.visitor, Some(&self.metadata))?;
+        self.next_height = self.next_height.saturating_add(1);
+        Ok(())
+    }
+
+    fn try_finish(
+        self,
+        sapling_snapshot: Option<&[u8]>,
+        orchard_snapshot: Option<&[u8]>,
+    ) -> eyre::Result<String> {
+        ensure!(
+            self.next_height > self.snapshot_height,
+            "Scan is incomplete: next block is {} but the snapshot height is {}",
+            self.next_height,
+            self.snapshot_height
+        );
+
+        let sapling_snapshot = sapling_snapshot
+            .map(|bytes| parse_snapshot(bytes, "Sapling"))
+            .transpose()?;
+        let orchard_snapshot = orchard_snapshot
+            .map(|bytes| parse_snapshot(bytes, "Orchard"))
+            .transpose()?;
+
+        let sapling = prepare_pool_claims::<SaplingPool>(
+            &self.visitor,
+            &self.viewing_keys,
+            &self.config,
+            sapling_snapshot.as_ref(),
+            |_, _| {},
+        )?;
+        let orchard = prepare_pool_claims::<OrchardPool>(
+            &self.visitor,
+            &self.viewing_keys,
+            &self.config,
+            orchard_snapshot.as_ref(),
+            |_, _| {},
+        )?;
+
+        let claims = AirdropClaimInputs {
+            sapling_claim_input: sapling.claims,
+            orchard_claim_input: orchard.claims,
+            excluded_notes: sapling
+                .excluded
+                .into_iter()
+                .chain(orchard.excluded)
+                .collect(),
+            epoch: None,
+            key_fingerprints: Some(self.key_fingerprints),
+        };
+        Ok(serde_json::to_string_pretty(&claims)?)
+    }
+}
+
+/// Parse raw snapshot bytes (concatenated 32-byte nullifiers).
+fn parse_snapshot(bytes: &[u8], pool: &str) -> eyre::Result<SanitiseNullifiers> {
+    let nullifiers: &[Nullifier] = bytemuck::try_cast_slice(bytes).map_err(|_| {
+        eyre::eyre!(
+            "{pool} snapshot has {} bytes which is not a multiple of the nullifier size",
+            bytes.len()
+        )
+    })?;
+    Ok(SanitiseNullifiers::new(nullifiers.to_vec()))
+}
+
+fn to_js_error(error: eyre::Report) -> JsError {
+    JsError::new(&format!("{error:#}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_bytes_must_be_whole_nullifiers() {
+        let snapshot = parse_snapshot(&[7_u8; 64], "Sapling").expect("two nullifiers");
+        assert_eq!(snapshot.len(), 2);
+
+        let err = parse_snapshot(&[7_u8; 33], "Sapling").expect_err("truncated snapshot");
+        assert!(err.to_string().contains("not a multiple"), "{err:?}");
+    }
+}